@@ -15,6 +15,11 @@ pub enum Error {
     UnsupportedFeature(String),
     DivisionByZero,
     Overflow,
+    TransactionAborted(String),
+    ResourcesExceeded(String),
+    Cancelled,
+    Timeout(String),
+    Durability(String),
     Internal(String),
 }
 
@@ -66,6 +71,22 @@ impl Error {
         Error::UnsupportedFeature(msg.into())
     }
 
+    pub fn transaction_aborted(table: impl Into<String>) -> Self {
+        Error::TransactionAborted(table.into())
+    }
+
+    pub fn resources_exceeded(msg: impl Into<String>) -> Self {
+        Error::ResourcesExceeded(msg.into())
+    }
+
+    pub fn timeout(msg: impl Into<String>) -> Self {
+        Error::Timeout(msg.into())
+    }
+
+    pub fn durability(msg: impl Into<String>) -> Self {
+        Error::Durability(msg.into())
+    }
+
     pub fn internal(msg: impl Into<String>) -> Self {
         Error::Internal(msg.into())
     }
@@ -87,6 +108,19 @@ impl fmt::Display for Error {
             Error::UnsupportedFeature(msg) => write!(f, "Unsupported feature: {}", msg),
             Error::DivisionByZero => write!(f, "Division by zero"),
             Error::Overflow => write!(f, "Numeric overflow"),
+            Error::TransactionAborted(table) => write!(
+                f,
+                "Transaction aborted due to concurrent update against table {}",
+                table
+            ),
+            Error::ResourcesExceeded(msg) => {
+                write!(f, "Resources exceeded during query execution: {}", msg)
+            }
+            Error::Cancelled => write!(f, "Query was cancelled"),
+            Error::Timeout(msg) => write!(f, "Query timed out: {}", msg),
+            Error::Durability(msg) => {
+                write!(f, "Statement committed but was not made durable: {}", msg)
+            }
             Error::Internal(msg) => write!(f, "Internal error: {}", msg),
         }
     }
@@ -86,17 +86,24 @@ impl IntoIterator for Row {
 pub struct QueryResult {
     pub schema: Vec<ColumnInfo>,
     pub rows: Vec<Row>,
+    /// Whether the rows were served from the query result cache.
+    pub cache_hit: bool,
 }
 
 impl QueryResult {
     pub fn new(schema: Vec<ColumnInfo>, rows: Vec<Row>) -> Self {
-        Self { schema, rows }
+        Self {
+            schema,
+            rows,
+            cache_hit: false,
+        }
     }
 
     pub fn from_values(schema: Vec<ColumnInfo>, rows: Vec<Vec<Value>>) -> Self {
         Self {
             schema,
             rows: rows.into_iter().map(Row::new).collect(),
+            cache_hit: false,
         }
     }
 
@@ -108,6 +115,7 @@ impl QueryResult {
         Self {
             schema,
             rows: Vec::new(),
+            cache_hit: false,
         }
     }
 
@@ -172,7 +180,8 @@ impl QueryResult {
             "schema": { "fields": schema_fields },
             "rows": rows,
             "totalRows": self.rows.len().to_string(),
-            "jobComplete": true
+            "jobComplete": true,
+            "cacheHit": self.cache_hit
         })
     }
 }
@@ -1,19 +1,26 @@
-use std::num::NonZeroUsize;
+use std::path::Path;
 use std::sync::{Arc, RwLock};
 
 use lazy_static::lazy_static;
-use lru::LruCache;
 use regex::Regex;
-use yachtsql_common::error::Result;
-use yachtsql_optimizer::OptimizedLogicalPlan;
+use yachtsql_common::error::{Error, Result};
+use yachtsql_common::result::QueryResult;
+use yachtsql_common::types::Value;
+use yachtsql_ir::LogicalPlan;
+use yachtsql_optimizer::{OptimizedLogicalPlan, Optimizer, OptimizerRule};
+use yachtsql_parser::NormalizedQuery;
 use yachtsql_storage::Table;
 
-use crate::concurrent_catalog::ConcurrentCatalog;
+use crate::cancel::{Interrupt, QueryHandle};
+use crate::concurrent_catalog::{CatalogImage, ConcurrentCatalog};
 use crate::concurrent_session::ConcurrentSession;
 use crate::executor::concurrent::ConcurrentPlanExecutor;
+use crate::persistence::{DurableStore, PersistenceOptions};
 use crate::plan::PhysicalPlan;
-
-const PLAN_CACHE_SIZE: usize = 10000;
+use crate::plan_cache::{CatalogChanges, DependencyRecorder, PlanCache, bind_literals};
+use crate::prepared::PreparedStatement;
+use crate::result_cache::{ResultCache, is_cacheable_query};
+use crate::stream::QueryStream;
 
 fn preprocess_range_types(sql: &str) -> String {
     lazy_static! {
@@ -23,20 +30,18 @@ fn preprocess_range_types(sql: &str) -> String {
     RANGE_TYPE_RE.replace_all(sql, "RANGE_$1").to_string()
 }
 
-fn default_plan_cache() -> LruCache<String, OptimizedLogicalPlan> {
-    LruCache::new(NonZeroUsize::new(PLAN_CACHE_SIZE).unwrap())
-}
-
 fn is_cacheable_plan(plan: &OptimizedLogicalPlan) -> bool {
     matches!(
         plan,
         OptimizedLogicalPlan::TableScan { .. }
+            | OptimizedLogicalPlan::TimeTravelScan { .. }
             | OptimizedLogicalPlan::Sample { .. }
             | OptimizedLogicalPlan::Filter { .. }
             | OptimizedLogicalPlan::Project { .. }
             | OptimizedLogicalPlan::NestedLoopJoin { .. }
             | OptimizedLogicalPlan::CrossJoin { .. }
             | OptimizedLogicalPlan::HashJoin { .. }
+            | OptimizedLogicalPlan::MergeJoin { .. }
             | OptimizedLogicalPlan::HashAggregate { .. }
             | OptimizedLogicalPlan::Sort { .. }
             | OptimizedLogicalPlan::Limit { .. }
@@ -54,94 +59,406 @@ fn is_cacheable_plan(plan: &OptimizedLogicalPlan) -> bool {
     )
 }
 
-fn invalidates_cache(plan: &OptimizedLogicalPlan) -> bool {
-    matches!(
-        plan,
-        OptimizedLogicalPlan::CreateTable { .. }
-            | OptimizedLogicalPlan::DropTable { .. }
-            | OptimizedLogicalPlan::AlterTable { .. }
-            | OptimizedLogicalPlan::Truncate { .. }
-            | OptimizedLogicalPlan::CreateView { .. }
-            | OptimizedLogicalPlan::DropView { .. }
-            | OptimizedLogicalPlan::CreateSchema { .. }
-            | OptimizedLogicalPlan::DropSchema { .. }
-            | OptimizedLogicalPlan::UndropSchema { .. }
-            | OptimizedLogicalPlan::AlterSchema { .. }
-            | OptimizedLogicalPlan::CreateFunction { .. }
-            | OptimizedLogicalPlan::DropFunction { .. }
-            | OptimizedLogicalPlan::CreateProcedure { .. }
-            | OptimizedLogicalPlan::DropProcedure { .. }
-            | OptimizedLogicalPlan::CreateSnapshot { .. }
-            | OptimizedLogicalPlan::DropSnapshot { .. }
-    )
+/// Cache key for `sql` run in a session whose name resolution scope is
+/// `scope`, so sessions only share plans and results when their unqualified
+/// names resolve to the same tables and functions.
+fn plan_key(scope: &str, sql: &str) -> String {
+    if scope.is_empty() {
+        sql.to_string()
+    } else {
+        format!("{}\u{0}{}", scope, sql)
+    }
 }
 
 pub struct AsyncQueryExecutor {
     catalog: Arc<ConcurrentCatalog>,
     session: Arc<ConcurrentSession>,
-    plan_cache: Arc<RwLock<LruCache<String, OptimizedLogicalPlan>>>,
+    plan_cache: Arc<PlanCache>,
+    result_cache: Arc<ResultCache>,
+    optimizer: Arc<RwLock<Optimizer>>,
+    store: Option<Arc<DurableStore>>,
 }
 
 impl AsyncQueryExecutor {
     pub fn new() -> Self {
-        Self {
-            catalog: Arc::new(ConcurrentCatalog::new()),
-            session: Arc::new(ConcurrentSession::new()),
-            plan_cache: Arc::new(RwLock::new(default_plan_cache())),
-        }
+        Self::from_catalog_and_session(ConcurrentCatalog::new(), ConcurrentSession::new())
+    }
+
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::open_with_options(path, PersistenceOptions::default())
+    }
+
+    pub fn open_with_options(path: impl AsRef<Path>, options: PersistenceOptions) -> Result<Self> {
+        let (store, catalog) = DurableStore::open(path.as_ref(), options)?;
+        let mut executor = Self::from_catalog_and_session(catalog, ConcurrentSession::new());
+        executor.store = Some(Arc::new(store));
+        Ok(executor)
     }
 
     pub fn from_catalog_and_session(
         catalog: ConcurrentCatalog,
         session: ConcurrentSession,
     ) -> Self {
+        let session = Arc::new(session);
         Self {
-            catalog: Arc::new(catalog),
-            session: Arc::new(session),
-            plan_cache: Arc::new(RwLock::new(default_plan_cache())),
+            catalog: Arc::new(catalog.bind_session(Arc::clone(&session))),
+            session,
+            plan_cache: Arc::new(PlanCache::new()),
+            result_cache: Arc::new(ResultCache::new()),
+            optimizer: Arc::new(RwLock::new(Optimizer::new())),
+            store: None,
+        }
+    }
+
+    pub fn from_image(image: &CatalogImage) -> Self {
+        Self::from_catalog_and_session(
+            ConcurrentCatalog::from_image(image),
+            ConcurrentSession::new(),
+        )
+    }
+
+    pub fn new_session(&self) -> Self {
+        let session = Arc::new(ConcurrentSession::new());
+        Self {
+            catalog: Arc::new(self.catalog.with_session(Arc::clone(&session))),
+            session,
+            plan_cache: Arc::clone(&self.plan_cache),
+            result_cache: Arc::clone(&self.result_cache),
+            optimizer: Arc::clone(&self.optimizer),
+            store: self.store.clone(),
+        }
+    }
+
+    pub fn is_durable(&self) -> bool {
+        self.store.is_some()
+    }
+
+    pub fn data_dir(&self) -> Option<&Path> {
+        self.store.as_deref().map(DurableStore::dir)
+    }
+
+    pub fn checkpoint(&self) -> Result<()> {
+        match &self.store {
+            Some(store) => store.checkpoint(&self.catalog),
+            None => Ok(()),
         }
     }
 
     pub async fn execute_sql(&self, sql: &str) -> Result<Table> {
-        let sql = preprocess_range_types(sql);
-        let cached = {
-            let mut cache = self.plan_cache.write().unwrap();
-            cache.get(&sql).cloned()
-        };
+        let (table, _) = self
+            .execute_sql_with_handle(sql, &QueryHandle::new())
+            .await?;
+        Ok(table)
+    }
 
-        let physical = match cached {
-            Some(plan) => plan,
-            None => {
-                let logical = yachtsql_parser::parse_and_plan(&sql, self)?;
-                let physical = yachtsql_optimizer::optimize(&logical)?;
+    /// Runs a statement and reports whether its result came from the query
+    /// result cache.
+    pub async fn query(&self, sql: &str) -> Result<QueryResult> {
+        let (table, cache_hit) = self
+            .execute_sql_with_handle(sql, &QueryHandle::new())
+            .await?;
+        let mut result = table.to_query_result()?;
+        result.cache_hit = cache_hit;
+        Ok(result)
+    }
 
-                if is_cacheable_plan(&physical) {
-                    let mut cache = self.plan_cache.write().unwrap();
-                    cache.put(sql.clone(), physical.clone());
-                }
+    /// Returns a handle that cancels the statement along with the future
+    /// that runs it. The handle can be sent to another thread or task.
+    pub fn execute_cancellable<'a>(
+        &'a self,
+        sql: &'a str,
+    ) -> (QueryHandle, impl Future<Output = Result<Table>> + 'a) {
+        let handle = QueryHandle::new();
+        let query_handle = handle.clone();
+        (handle, async move {
+            let (table, _) = self.execute_sql_with_handle(sql, &query_handle).await?;
+            Ok(table)
+        })
+    }
 
-                physical
+    async fn execute_sql_with_handle(
+        &self,
+        sql: &str,
+        handle: &QueryHandle,
+    ) -> Result<(Table, bool)> {
+        let physical = self.plan_sql(sql)?;
+        self.execute_optimized(sql, &physical, &[], handle).await
+    }
+
+    /// Runs a query as a stream of result batches. The filters, projections
+    /// and limits at the top of the plan run as batches are pulled; the
+    /// operators below them, and statements other than queries, run to
+    /// completion first.
+    pub async fn query_stream(&self, sql: &str) -> Result<QueryStream> {
+        self.query_stream_with_handle(sql, &QueryHandle::new())
+            .await
+    }
+
+    /// Returns a handle that cancels the stream, both while the query runs
+    /// and while its batches are pulled, along with the future that opens it.
+    pub fn query_stream_cancellable<'a>(
+        &'a self,
+        sql: &'a str,
+    ) -> (QueryHandle, impl Future<Output = Result<QueryStream>> + 'a) {
+        let handle = QueryHandle::new();
+        let query_handle = handle.clone();
+        (handle, async move {
+            self.query_stream_with_handle(sql, &query_handle).await
+        })
+    }
+
+    async fn query_stream_with_handle(
+        &self,
+        sql: &str,
+        handle: &QueryHandle,
+    ) -> Result<QueryStream> {
+        let physical = self.plan_sql(sql)?;
+        if !is_cacheable_plan(&physical) {
+            let (table, _) = self.execute_optimized(sql, &physical, &[], handle).await?;
+            return Ok(QueryStream::from_table(table));
+        }
+
+        let (source, stages) = QueryStream::split_pipeline(self.executor_plan(&physical));
+        if let PhysicalPlan::TableScan {
+            table_name,
+            schema,
+            projection,
+            ..
+        } = &source
+        {
+            let executor = self.plan_executor(&source, &[], handle)?;
+            let table = executor.scan_table(table_name)?;
+            return Ok(QueryStream::scan(
+                table,
+                schema,
+                projection.as_deref(),
+                stages,
+                &executor,
+            ));
+        }
+        self.run_plan(&source, &[], handle, |executor, table| {
+            QueryStream::new(table, stages, executor)
+        })
+        .await
+    }
+
+    fn plan_sql(&self, sql: &str) -> Result<OptimizedLogicalPlan> {
+        let sql = preprocess_range_types(sql);
+        let scope = self.catalog.resolution_scope();
+        if let Some(plan) = self.plan_cache.get(&plan_key(&scope, &sql)) {
+            return Ok(plan);
+        }
+        if let Some(query) = yachtsql_parser::normalize_query(&sql) {
+            let template_key = plan_key(&scope, &query.key);
+            let plan = self
+                .plan_cache
+                .get(&template_key)
+                .and_then(|mut plan| bind_literals(&mut plan, &query.literals).then_some(plan))
+                .or_else(|| self.plan_normalized(&scope, query));
+            if let Some(plan) = plan {
+                self.plan_cache
+                    .put_like(plan_key(&scope, &sql), &template_key, plan.clone());
+                return Ok(plan);
             }
-        };
+        }
 
-        let mut executor_plan = PhysicalPlan::from_physical(&physical);
-        executor_plan.populate_row_counts(&self.catalog);
-        let accesses = executor_plan.extract_table_accesses();
+        let recorder = DependencyRecorder::new(self);
+        let logical = yachtsql_parser::parse_and_plan(&sql, &recorder)?;
+        let physical = self.optimize(&logical)?;
+        if is_cacheable_plan(&physical) {
+            self.plan_cache.put(
+                plan_key(&scope, &sql),
+                physical.clone(),
+                recorder.into_dependencies(),
+            );
+        }
+        Ok(physical)
+    }
 
-        let mut tables = self.catalog.acquire_table_locks(&accesses)?;
-        tables.set_catalog(Arc::clone(&self.catalog));
+    /// Plans a query with its literals hoisted out and caches the result
+    /// under its normalized text. Returns `None` when the query has to be
+    /// planned as written instead.
+    fn plan_normalized(&self, scope: &str, query: NormalizedQuery) -> Option<OptimizedLogicalPlan> {
+        let recorder = DependencyRecorder::new(self);
+        let logical = query.plan(&recorder).ok()?;
+        let template = self.optimize(&logical).ok()?;
+        let mut plan = template.clone();
+        if !bind_literals(&mut plan, &query.literals) {
+            return None;
+        }
+        if is_cacheable_plan(&template) {
+            self.plan_cache.put(
+                plan_key(scope, &query.key),
+                template,
+                recorder.into_dependencies(),
+            );
+        }
+        Some(plan)
+    }
+
+    /// Adds a rewrite rule run after the built-in ones on every statement
+    /// planned by this executor and the sessions sharing its plan cache.
+    pub fn register_optimizer_rule(&self, rule: Arc<dyn OptimizerRule>) {
+        self.optimizer.write().unwrap().add_rule(rule);
+        self.plan_cache.clear();
+        self.result_cache.clear();
+    }
 
-        let executor = ConcurrentPlanExecutor::new(&self.catalog, &self.session, tables);
-        let result = executor.execute_plan(&executor_plan).await?;
+    pub(crate) fn optimize(&self, logical: &LogicalPlan) -> Result<OptimizedLogicalPlan> {
+        self.optimizer.read().unwrap().optimize(logical)
+    }
 
-        executor.tables.commit_writes();
+    pub fn prepare(&self, sql: &str) -> Result<PreparedStatement> {
+        PreparedStatement::new(self.clone(), preprocess_range_types(sql))
+    }
 
-        if invalidates_cache(&physical) {
-            let mut cache = self.plan_cache.write().unwrap();
-            cache.clear();
+    /// Executes a planned statement, returning its result and whether it
+    /// was served from the query result cache.
+    pub(crate) async fn execute_optimized(
+        &self,
+        sql: &str,
+        physical: &OptimizedLogicalPlan,
+        bindings: &[(String, Value)],
+        handle: &QueryHandle,
+    ) -> Result<(Table, bool)> {
+        let cache_key = (self.use_query_cache()
+            && !self.catalog.in_transaction()
+            && is_cacheable_query(physical))
+        .then(|| {
+            let scope = self.catalog.resolution_scope();
+            plan_key(
+                &scope,
+                &ResultCache::key(sql, &self.cache_key_values(bindings)),
+            )
+        });
+        if let Some(key) = &cache_key
+            && let Some(table) = self.result_cache.get(key, &self.catalog)
+        {
+            return Ok((table, true));
         }
 
-        Ok(result)
+        let executor_plan = self.executor_plan(physical);
+        let tables: Vec<String> = executor_plan
+            .extract_table_accesses()
+            .accesses
+            .into_keys()
+            .collect();
+        let versions = cache_key
+            .as_ref()
+            .and_then(|_| self.catalog.table_versions(&tables));
+        let result = self
+            .run_plan(&executor_plan, bindings, handle, |_, table| table)
+            .await;
+
+        let changes = self.catalog_changes(physical);
+        self.plan_cache.invalidate(&changes);
+        if changes.redefines_views_or_functions() {
+            self.result_cache.clear();
+        }
+        let result = result?;
+        if let (Some(key), Some(versions)) = (cache_key, versions) {
+            self.result_cache.put(key, tables, versions, result.clone());
+        }
+
+        Ok((result, false))
+    }
+
+    /// The catalog objects `plan` changes, walking into the bodies of the
+    /// procedures it calls. Runs after the statement, so even a script that
+    /// failed part way evicts the plans of what it changed before failing.
+    fn catalog_changes(&self, plan: &OptimizedLogicalPlan) -> CatalogChanges {
+        CatalogChanges::of(plan, &|name| match self.catalog.get_procedure(name) {
+            Some(procedure) => procedure
+                .body
+                .iter()
+                .map(|statement| self.optimize(statement))
+                .collect::<Result<Vec<_>>>()
+                .ok(),
+            None => Some(Vec::new()),
+        })
+    }
+
+    /// Whether query results are served from and stored in the result
+    /// cache, set per session with `SET @@use_query_cache = false`.
+    fn use_query_cache(&self) -> bool {
+        self.session
+            .get_system_variable("@@use_query_cache")
+            .or_else(|| self.session.get_variable("USE_QUERY_CACHE"))
+            .and_then(|val| val.as_bool())
+            .unwrap_or(true)
+    }
+
+    /// Parameter bindings followed by the session's variables and settings,
+    /// any of which a cached result may depend on.
+    fn cache_key_values(&self, bindings: &[(String, Value)]) -> Vec<(String, Value)> {
+        let mut session_values: Vec<(String, Value)> = self
+            .session
+            .variables()
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .chain(
+                self.session
+                    .system_variables()
+                    .iter()
+                    .map(|(name, value)| (name.clone(), value.clone())),
+            )
+            .collect();
+        session_values.sort_by(|a, b| a.0.cmp(&b.0));
+        bindings.iter().cloned().chain(session_values).collect()
+    }
+
+    fn executor_plan(&self, physical: &OptimizedLogicalPlan) -> PhysicalPlan {
+        let reordered = yachtsql_optimizer::has_inner_joins(physical)
+            .then(|| yachtsql_optimizer::reorder_joins(physical.clone(), &*self.catalog));
+        let mut executor_plan = PhysicalPlan::from_physical(reordered.as_ref().unwrap_or(physical));
+        executor_plan.populate_row_counts(&self.catalog);
+        executor_plan
+    }
+
+    /// Executes `executor_plan` and passes its result to `finish` along with
+    /// the executor that produced it. The session's query timeout starts
+    /// when execution does.
+    async fn run_plan<T>(
+        &self,
+        executor_plan: &PhysicalPlan,
+        bindings: &[(String, Value)],
+        handle: &QueryHandle,
+        finish: impl FnOnce(&ConcurrentPlanExecutor, Table) -> T,
+    ) -> Result<T> {
+        let mut executor = self.plan_executor(executor_plan, bindings, handle)?;
+        let result = executor.execute_plan(executor_plan).await;
+        if result.is_ok() {
+            executor.tables.commit_writes()?;
+        }
+        let flushed = match &self.store {
+            Some(store) => store.flush(&self.catalog),
+            None => Ok(()),
+        };
+        let table = result?;
+        flushed.map_err(|e| Error::durability(e.to_string()))?;
+        Ok(finish(&executor, table))
+    }
+
+    /// An executor holding the tables `executor_plan` accesses, with the
+    /// session's query timeout starting now.
+    fn plan_executor(
+        &self,
+        executor_plan: &PhysicalPlan,
+        bindings: &[(String, Value)],
+        handle: &QueryHandle,
+    ) -> Result<ConcurrentPlanExecutor<'_>> {
+        let accesses = executor_plan.extract_table_accesses();
+        let mut tables = self.catalog.acquire_table_locks(&accesses)?;
+        tables.set_catalog(Arc::clone(&self.catalog));
+
+        let mut executor = ConcurrentPlanExecutor::new(&self.catalog, &self.session, tables);
+        executor.interrupt = Interrupt::new(handle.clone(), executor.query_timeout());
+        executor
+            .variables
+            .write()
+            .unwrap()
+            .extend(bindings.iter().cloned());
+        Ok(executor)
     }
 
     pub async fn execute_batch(&self, queries: Vec<String>) -> Vec<Result<Table>> {
@@ -167,6 +484,9 @@ impl Clone for AsyncQueryExecutor {
             catalog: Arc::clone(&self.catalog),
             session: Arc::clone(&self.session),
             plan_cache: Arc::clone(&self.plan_cache),
+            result_cache: Arc::clone(&self.result_cache),
+            optimizer: Arc::clone(&self.optimizer),
+            store: self.store.clone(),
         }
     }
 }
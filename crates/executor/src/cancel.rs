@@ -0,0 +1,70 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use yachtsql_common::error::{Error, Result};
+
+/// Rows an operator loop processes between two interruption checks.
+const ROWS_PER_CHECK: usize = 1024;
+
+/// Cancels a running query from another thread or task. Clones share the
+/// same query.
+#[derive(Debug, Clone, Default)]
+pub struct QueryHandle {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl QueryHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Asks the query to stop. It fails with `Error::Cancelled` at the next
+    /// point where it checks for interruption.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// The cancellation and timeout state of one query, checked cooperatively
+/// between operators, in operator loops and on every script iteration.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Interrupt {
+    handle: QueryHandle,
+    deadline: Option<(Instant, Duration)>,
+}
+
+impl Interrupt {
+    pub fn new(handle: QueryHandle, timeout: Option<Duration>) -> Self {
+        Self {
+            handle,
+            deadline: timeout.map(|timeout| (Instant::now() + timeout, timeout)),
+        }
+    }
+
+    pub fn check(&self) -> Result<()> {
+        if self.handle.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+        match self.deadline {
+            Some((deadline, timeout)) if Instant::now() >= deadline => Err(Error::timeout(
+                format!("exceeded the limit of {} ms", timeout.as_millis()),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks on every `ROWS_PER_CHECK`-th row of an operator loop, where
+    /// `row` counts the rows processed so far.
+    pub fn check_row(&self, row: usize) -> Result<()> {
+        if row.is_multiple_of(ROWS_PER_CHECK) {
+            self.check()
+        } else {
+            Ok(())
+        }
+    }
+}
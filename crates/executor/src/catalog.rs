@@ -3,7 +3,7 @@ use std::collections::{HashMap, HashSet};
 use serde::{Deserialize, Serialize};
 use yachtsql_common::error::{Error, Result};
 use yachtsql_common::types::DataType;
-use yachtsql_ir::{Expr, FunctionArg, FunctionBody, LogicalPlan, ProcedureArg};
+use yachtsql_ir::{Expr, FunctionArg, FunctionBody, IndexKind, LogicalPlan, ProcedureArg};
 use yachtsql_parser::CatalogProvider;
 use yachtsql_storage::{Schema, Table};
 
@@ -15,6 +15,8 @@ pub struct UserFunction {
     pub body: FunctionBody,
     pub is_temporary: bool,
     pub is_aggregate: bool,
+    #[serde(default)]
+    pub options: Vec<(String, String)>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,6 +30,18 @@ pub struct UserProcedure {
 pub struct ViewDef {
     pub query: String,
     pub column_aliases: Vec<String>,
+    #[serde(default)]
+    pub materialized: bool,
+}
+
+/// A search or vector index. Indexes are catalog metadata only; scans never
+/// use them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableIndex {
+    pub name: String,
+    pub kind: IndexKind,
+    pub columns: Vec<String>,
+    pub options: Vec<(String, String)>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -255,8 +269,16 @@ impl Catalog {
         &self.search_path
     }
 
-    fn resolve_table_name(&self, name: &str) -> String {
+    fn table_key(name: &str) -> String {
         let key = name.to_uppercase();
+        match key.strip_prefix("_SESSION.") {
+            Some(rest) => rest.to_string(),
+            None => key,
+        }
+    }
+
+    fn resolve_table_name(&self, name: &str) -> String {
+        let key = Self::table_key(name);
         if key.contains('.') || self.tables.contains_key(&key) {
             return key;
         }
@@ -270,7 +292,7 @@ impl Catalog {
     }
 
     pub fn create_table(&mut self, name: &str, schema: Schema) -> Result<()> {
-        let key = name.to_uppercase();
+        let key = Self::table_key(name);
         let parts: Vec<&str> = key.split('.').collect();
         if parts.len() == 2 {
             let schema_name = parts[0];
@@ -292,7 +314,7 @@ impl Catalog {
     }
 
     pub fn set_table_defaults(&mut self, name: &str, defaults: Vec<ColumnDefault>) {
-        let key = name.to_uppercase();
+        let key = Self::table_key(name);
         self.table_defaults.insert(key, defaults);
     }
 
@@ -312,7 +334,7 @@ impl Catalog {
     }
 
     pub fn insert_table(&mut self, name: &str, table: Table) -> Result<()> {
-        let key = name.to_uppercase();
+        let key = Self::table_key(name);
         let parts: Vec<&str> = key.split('.').collect();
         if parts.len() == 2 {
             let schema_name = parts[0];
@@ -386,7 +408,7 @@ impl Catalog {
     }
 
     pub fn create_or_replace_table(&mut self, name: &str, table: Table) {
-        let key = name.to_uppercase();
+        let key = Self::table_key(name);
         self.tables.insert(key, table);
     }
 
@@ -473,6 +495,7 @@ impl Catalog {
         column_aliases: Vec<String>,
         or_replace: bool,
         if_not_exists: bool,
+        materialized: bool,
     ) -> Result<()> {
         let key = name.to_uppercase();
         if self.views.contains_key(&key) {
@@ -491,6 +514,7 @@ impl Catalog {
             ViewDef {
                 query,
                 column_aliases,
+                materialized,
             },
         );
         Ok(())
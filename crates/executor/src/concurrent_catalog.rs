@@ -1,27 +1,67 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 
+use chrono::{DateTime, Duration, Utc};
 use dashmap::DashMap;
 use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
 use yachtsql_common::error::{Error, Result};
-use yachtsql_ir::Expr;
-use yachtsql_storage::{Schema, Table};
-
-use crate::catalog::{ColumnDefault, SchemaMetadata, UserFunction, UserProcedure, ViewDef};
+use yachtsql_common::types::{DataType, Value};
+use yachtsql_ir::{ConstraintType, Expr, IndexKind, TableConstraint};
+use yachtsql_storage::{ColumnStatistics, Field, Schema, Segment, Table, TableStatistics};
+
+use crate::catalog::{
+    ColumnDefault, SchemaMetadata, TableIndex, UserFunction, UserProcedure, ViewDef,
+};
+use crate::concurrent_session::{ConcurrentSession, SESSION_SCHEMA, SESSION_SCHEMA_PREFIX};
+use crate::information_schema;
+use crate::persistence::{CatalogObject, CatalogRecord};
 use crate::plan::{AccessType, PhysicalPlan, TableAccessSet};
+use crate::plan_cache::object_key;
 
 pub type TableHandle = Arc<RwLock<Table>>;
 
-#[derive(Debug)]
+/// A source of the commit timestamps recorded for time travel.
+pub type HistoryClock = Arc<dyn Fn() -> DateTime<Utc> + Send + Sync>;
+
+const DEFAULT_TIME_TRAVEL_HOURS: i64 = 168;
+/// Versions of one table kept for time travel, however recent.
+const MAX_HISTORY_VERSIONS: usize = 1000;
+/// Bytes of one table's history. Segments shared between versions count
+/// once, for the oldest version holding them.
+const MAX_HISTORY_BYTES: usize = 256 * 1024 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct CatalogImage {
+    records: Vec<CatalogRecord>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableSnapshotInfo {
+    pub table_name: String,
+    pub base_table_name: String,
+    pub snapshot_time: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DroppedSchemaData {
     pub metadata: SchemaMetadata,
     pub tables: Vec<(String, Table)>,
     pub table_defaults: Vec<(String, Vec<ColumnDefault>)>,
+    pub table_constraints: Vec<(String, Vec<TableConstraint>)>,
+    pub table_options: Vec<(String, Vec<(String, String)>)>,
+    #[serde(default)]
+    pub table_indexes: Vec<(String, Vec<TableIndex>)>,
 }
 
 pub struct TableLockSet {
     read_tables: Mutex<HashMap<String, Table>>,
     write_tables: Mutex<HashMap<String, Table>>,
+    /// The version each write table was copied at, checked again when the
+    /// writes are applied.
+    write_versions: Mutex<HashMap<String, u64>>,
+    dirty_tables: Mutex<HashSet<String>>,
     catalog: Option<Arc<ConcurrentCatalog>>,
 }
 
@@ -33,6 +73,8 @@ impl TableLockSet {
         Self {
             read_tables: Mutex::new(HashMap::new()),
             write_tables: Mutex::new(HashMap::new()),
+            write_versions: Mutex::new(HashMap::new()),
+            dirty_tables: Mutex::new(HashSet::new()),
             catalog: None,
         }
     }
@@ -41,6 +83,8 @@ impl TableLockSet {
         Self {
             read_tables: Mutex::new(HashMap::new()),
             write_tables: Mutex::new(HashMap::new()),
+            write_versions: Mutex::new(HashMap::new()),
+            dirty_tables: Mutex::new(HashSet::new()),
             catalog: Some(catalog),
         }
     }
@@ -57,43 +101,79 @@ impl TableLockSet {
         self.write_tables.lock().unwrap().insert(name, table);
     }
 
-    pub fn get_table(&self, name: &str) -> Option<Table> {
+    fn add_versioned_write_table(&self, name: String, table: Table, version: u64) {
+        self.write_versions
+            .lock()
+            .unwrap()
+            .insert(name.clone(), version);
+        self.add_write_table(name, table);
+    }
+
+    fn lookup_keys(&self, name: &str) -> [String; 2] {
         let upper = name.to_uppercase();
-        if let Some(table) = self.write_tables.lock().unwrap().get(&upper) {
-            return Some(table.clone());
-        }
-        if let Some(table) = self.read_tables.lock().unwrap().get(&upper) {
-            return Some(table.clone());
+        let resolved = match &self.catalog {
+            Some(catalog) => catalog.resolve_table_name(name),
+            None => upper.clone(),
+        };
+        [upper, resolved]
+    }
+
+    pub fn get_table(&self, name: &str) -> Option<Table> {
+        for key in self.lookup_keys(name) {
+            if let Some(table) = self.write_tables.lock().unwrap().get(&key) {
+                return Some(table.clone());
+            }
+            if let Some(table) = self.read_tables.lock().unwrap().get(&key) {
+                return Some(table.clone());
+            }
         }
         None
     }
 
     #[allow(clippy::mut_from_ref)]
     pub fn get_table_mut(&self, name: &str) -> Option<&mut Table> {
-        let upper = name.to_uppercase();
         let mut write_tables = self.write_tables.lock().unwrap();
-        if write_tables.contains_key(&upper) {
-            return Some(unsafe { &mut *(write_tables.get_mut(&upper).unwrap() as *mut Table) });
+        for key in self.lookup_keys(name) {
+            if write_tables.contains_key(&key) {
+                self.dirty_tables.lock().unwrap().insert(key.clone());
+                return Some(unsafe { &mut *(write_tables.get_mut(&key).unwrap() as *mut Table) });
+            }
         }
         None
     }
 
     pub fn update_table(&self, name: &str, table: Table) {
-        let upper = name.to_uppercase();
-        self.write_tables.lock().unwrap().insert(upper, table);
+        let mut write_tables = self.write_tables.lock().unwrap();
+        let [upper, resolved] = self.lookup_keys(name);
+        let key = if !write_tables.contains_key(&upper) && write_tables.contains_key(&resolved) {
+            resolved
+        } else {
+            upper
+        };
+        self.dirty_tables.lock().unwrap().insert(key.clone());
+        write_tables.insert(key, table);
     }
 
-    pub fn snapshot_write_locked_tables(&self) -> HashMap<String, Table> {
-        self.write_tables.lock().unwrap().clone()
+    pub fn commit_writes(&self) -> Result<()> {
+        let Some(ref catalog) = self.catalog else {
+            return Ok(());
+        };
+        let dirty: Vec<String> = self.dirty_tables.lock().unwrap().drain().collect();
+        let write_tables = self.write_tables.lock().unwrap();
+        let write_versions = self.write_versions.lock().unwrap();
+        let writes = dirty
+            .into_iter()
+            .filter_map(|name| {
+                let table = write_tables.get(&name)?.clone();
+                let version = write_versions.get(&name).copied();
+                Some((name, table, version))
+            })
+            .collect();
+        catalog.apply_writes(writes)
     }
 
-    pub fn commit_writes(&self) {
-        if let Some(ref catalog) = self.catalog {
-            let write_tables = self.write_tables.lock().unwrap();
-            for (name, table) in write_tables.iter() {
-                catalog.update_table(name, table.clone());
-            }
-        }
+    pub(crate) fn discard_writes(&self) -> Vec<String> {
+        self.dirty_tables.lock().unwrap().drain().collect()
     }
 }
 
@@ -103,15 +183,58 @@ impl Default for TableLockSet {
     }
 }
 
+/// A committed version of a table. Tables share their segments and column
+/// buffers with their clones until written, so a version only holds its own
+/// copy of the columns of the segments changed after it. `bytes` counts the
+/// segments not shared with the version before it, and `segment_sizes`
+/// caches the size of each of the table's segments.
 #[derive(Debug, Clone)]
-pub struct TransactionSnapshot {
-    pub tables: HashMap<String, Table>,
+struct TableVersion {
+    version: u64,
+    committed_at: DateTime<Utc>,
+    bytes: usize,
+    segment_sizes: Vec<usize>,
+    table: Table,
+}
+
+/// Private state of an open multi-statement transaction: the last version
+/// committed before `BEGIN`, the tables read so far as of that version, plus
+/// the writes staged until `COMMIT`.
+#[derive(Debug, Default)]
+struct Transaction {
+    snapshot: u64,
+    versions: HashMap<String, u64>,
+    tables: HashMap<String, Table>,
+    writes: HashMap<String, Table>,
+}
+
+impl Transaction {
+    fn table(&self, key: &str) -> Option<&Table> {
+        self.writes.get(key).or_else(|| self.tables.get(key))
+    }
+
+    fn forget(&mut self, key: &str) {
+        self.versions.remove(key);
+        self.tables.remove(key);
+        self.writes.remove(key);
+    }
+}
+
+struct HistoryClockSource(HistoryClock);
+
+impl std::fmt::Debug for HistoryClockSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("HistoryClock")
+    }
 }
 
 #[derive(Debug)]
-pub struct ConcurrentCatalog {
+struct CatalogState {
     tables: DashMap<String, TableHandle>,
     table_defaults: DashMap<String, Vec<ColumnDefault>>,
+    table_constraints: DashMap<String, Vec<TableConstraint>>,
+    table_options: DashMap<String, Vec<(String, String)>>,
+    table_indexes: DashMap<String, Vec<TableIndex>>,
     functions: DashMap<String, UserFunction>,
     procedures: DashMap<String, UserProcedure>,
     procedure_bodies: DashMap<String, Vec<PhysicalPlan>>,
@@ -120,133 +243,899 @@ pub struct ConcurrentCatalog {
     schema_metadata: DashMap<String, SchemaMetadata>,
     search_path: RwLock<Vec<String>>,
     dropped_schemas: DashMap<String, DroppedSchemaData>,
-    transaction_snapshot: RwLock<Option<TransactionSnapshot>>,
+    table_versions: DashMap<String, u64>,
+    table_history: DashMap<String, VecDeque<TableVersion>>,
+    /// The version each table's history starts at, which stays known after
+    /// that version is pruned.
+    history_origins: DashMap<String, u64>,
+    snapshots: DashMap<String, TableSnapshotInfo>,
+    table_statistics: DashMap<String, TableStatistics>,
+    last_version: AtomicU64,
+    history_clock: RwLock<Option<HistoryClockSource>>,
+    /// Per view or function name, the generation of its latest definition.
+    /// Views and functions are expanded when a statement is planned, so a
+    /// plan kept across statements compares these to notice redefinitions.
+    definition_generations: DashMap<String, u64>,
+    last_definition_generation: AtomicU64,
+    commit_lock: Mutex<()>,
+    track_changes: AtomicBool,
+    changed_objects: Mutex<HashSet<CatalogObject>>,
+}
+
+/// A session's view of the datasets owned by an engine. Views created with
+/// [`ConcurrentCatalog::with_session`] share all tables, routines and schemas,
+/// but resolve temp tables and the current schema through their own session.
+#[derive(Debug)]
+pub struct ConcurrentCatalog {
+    state: Arc<CatalogState>,
+    session: Option<Arc<ConcurrentSession>>,
+    transaction: RwLock<Option<Transaction>>,
+    /// Per table, the first and last version of the latest run of versions
+    /// committed through this view with no other view committing between.
+    own_versions: DashMap<String, (u64, u64)>,
 }
 
 impl ConcurrentCatalog {
     pub fn new() -> Self {
         Self {
-            tables: DashMap::new(),
-            table_defaults: DashMap::new(),
-            functions: DashMap::new(),
-            procedures: DashMap::new(),
-            procedure_bodies: DashMap::new(),
-            views: DashMap::new(),
-            schemas: DashMap::new(),
-            schema_metadata: DashMap::new(),
-            search_path: RwLock::new(Vec::new()),
-            dropped_schemas: DashMap::new(),
-            transaction_snapshot: RwLock::new(None),
+            state: Arc::new(CatalogState {
+                tables: DashMap::new(),
+                table_defaults: DashMap::new(),
+                table_constraints: DashMap::new(),
+                table_options: DashMap::new(),
+                table_indexes: DashMap::new(),
+                functions: DashMap::new(),
+                procedures: DashMap::new(),
+                procedure_bodies: DashMap::new(),
+                views: DashMap::new(),
+                schemas: DashMap::new(),
+                schema_metadata: DashMap::new(),
+                search_path: RwLock::new(Vec::new()),
+                dropped_schemas: DashMap::new(),
+                table_versions: DashMap::new(),
+                table_history: DashMap::new(),
+                history_origins: DashMap::new(),
+                snapshots: DashMap::new(),
+                table_statistics: DashMap::new(),
+                last_version: AtomicU64::new(0),
+                history_clock: RwLock::new(None),
+                definition_generations: DashMap::new(),
+                last_definition_generation: AtomicU64::new(0),
+                commit_lock: Mutex::new(()),
+                track_changes: AtomicBool::new(false),
+                changed_objects: Mutex::new(HashSet::new()),
+            }),
+            session: None,
+            transaction: RwLock::new(None),
+            own_versions: DashMap::new(),
         }
     }
 
-    pub fn begin_transaction(&self) {
-        let mut tables_snapshot = HashMap::new();
-        for entry in self.tables.iter() {
-            if let Some(table) = entry.value().try_read() {
-                tables_snapshot.insert(entry.key().clone(), table.clone());
+    pub fn with_session(&self, session: Arc<ConcurrentSession>) -> Self {
+        Self {
+            state: Arc::clone(&self.state),
+            session: Some(session),
+            transaction: RwLock::new(None),
+            own_versions: DashMap::new(),
+        }
+    }
+
+    pub(crate) fn bind_session(mut self, session: Arc<ConcurrentSession>) -> Self {
+        self.session = Some(session);
+        self
+    }
+
+    pub fn from_image(image: &CatalogImage) -> Self {
+        let catalog = Self::new();
+        for record in &image.records {
+            catalog.apply_record(record.clone());
+        }
+        catalog
+    }
+
+    pub fn snapshot(&self) -> CatalogImage {
+        let records = self
+            .all_objects()
+            .iter()
+            .map(|object| self.record_for(object))
+            .collect();
+        CatalogImage { records }
+    }
+
+    pub(crate) fn enable_change_tracking(&self) {
+        self.state.track_changes.store(true, Ordering::Release);
+    }
+
+    fn record_change(&self, object: CatalogObject) {
+        self.record_object_change(object, true);
+    }
+
+    /// Records a change to a table's defaults, constraints, options or
+    /// indexes. Its rows and columns are unchanged, so no version is added
+    /// to its history.
+    fn record_metadata_change(&self, key: String) {
+        self.record_object_change(CatalogObject::Table(key), false);
+    }
+
+    fn record_object_change(&self, object: CatalogObject, history: bool) {
+        if let CatalogObject::Table(key) = &object {
+            self.bump_table_version(key);
+            if history || !self.state.tables.contains_key(key) {
+                self.record_table_history(key);
+            }
+            if !self.state.tables.contains_key(key) {
+                self.state.snapshots.remove(key);
+                self.state.table_statistics.remove(key);
+            }
+            if let Some(transaction) = self.transaction.write().as_mut() {
+                transaction.forget(key);
+            }
+            if is_session_table(key) {
+                return;
             }
         }
-        *self.transaction_snapshot.write() = Some(TransactionSnapshot {
-            tables: tables_snapshot,
-        });
+        if self.state.track_changes.load(Ordering::Acquire) {
+            self.state.changed_objects.lock().unwrap().insert(object);
+        }
     }
 
-    pub fn begin_transaction_with_tables(&self, table_names: &[String]) {
-        let mut tables_snapshot = HashMap::new();
-        for name in table_names {
-            let key = name.to_uppercase();
-            if let Some(handle) = self.tables.get(&key)
-                && let Some(table) = handle.try_read()
-            {
-                tables_snapshot.insert(key, table.clone());
+    pub(crate) fn mark_table_changed(&self, name: &str) {
+        let key = self.resolve_table_name(name);
+        self.record_change(CatalogObject::Table(key));
+    }
+
+    pub(crate) fn take_changes(&self) -> Vec<CatalogObject> {
+        self.state.changed_objects.lock().unwrap().drain().collect()
+    }
+
+    pub(crate) fn all_objects(&self) -> Vec<CatalogObject> {
+        let mut tables: HashSet<String> =
+            self.state.tables.iter().map(|r| r.key().clone()).collect();
+        tables.extend(self.state.table_defaults.iter().map(|r| r.key().clone()));
+        tables.extend(self.state.table_constraints.iter().map(|r| r.key().clone()));
+        tables.extend(self.state.table_options.iter().map(|r| r.key().clone()));
+        tables.extend(self.state.table_indexes.iter().map(|r| r.key().clone()));
+        tables.retain(|key| !is_session_table(key));
+        let mut schemas: HashSet<String> =
+            self.state.schemas.iter().map(|r| r.key().clone()).collect();
+        schemas.extend(self.state.dropped_schemas.iter().map(|r| r.key().clone()));
+
+        let mut objects: Vec<CatalogObject> = Vec::new();
+        objects.extend(schemas.into_iter().map(CatalogObject::Schema));
+        objects.extend(tables.into_iter().map(CatalogObject::Table));
+        objects.extend(
+            self.state
+                .functions
+                .iter()
+                .filter(|r| !is_session_table(r.key()))
+                .map(|r| CatalogObject::Function(r.key().clone())),
+        );
+        objects.extend(
+            self.state
+                .procedures
+                .iter()
+                .map(|r| CatalogObject::Procedure(r.key().clone())),
+        );
+        objects.extend(
+            self.state
+                .views
+                .iter()
+                .map(|r| CatalogObject::View(r.key().clone())),
+        );
+        objects.push(CatalogObject::SearchPath);
+        objects
+    }
+
+    pub(crate) fn record_for(&self, object: &CatalogObject) -> CatalogRecord {
+        match object {
+            CatalogObject::Table(key) => CatalogRecord::Table {
+                name: key.clone(),
+                table: self
+                    .state
+                    .tables
+                    .get(key)
+                    .map(|handle| handle.read().clone()),
+                defaults: self.state.table_defaults.get(key).map(|r| r.clone()),
+                constraints: self.state.table_constraints.get(key).map(|r| r.clone()),
+                options: self.state.table_options.get(key).map(|r| r.clone()),
+                indexes: self.state.table_indexes.get(key).map(|r| r.clone()),
+                snapshot: self.state.snapshots.get(key).map(|r| r.clone()),
+            },
+            CatalogObject::Function(key) => CatalogRecord::Function {
+                name: key.clone(),
+                function: self.state.functions.get(key).map(|r| r.clone()),
+            },
+            CatalogObject::Procedure(key) => CatalogRecord::Procedure {
+                name: key.clone(),
+                procedure: self.state.procedures.get(key).map(|r| r.clone()),
+                body: self.state.procedure_bodies.get(key).map(|r| r.clone()),
+            },
+            CatalogObject::View(key) => CatalogRecord::View {
+                name: key.clone(),
+                view: self.state.views.get(key).map(|r| r.clone()),
+            },
+            CatalogObject::Schema(key) => CatalogRecord::Schema {
+                name: key.clone(),
+                metadata: self.state.schemas.contains_key(key).then(|| {
+                    self.state
+                        .schema_metadata
+                        .get(key)
+                        .map(|r| r.clone())
+                        .unwrap_or_default()
+                }),
+                dropped: self.state.dropped_schemas.get(key).map(|r| r.clone()),
+            },
+            CatalogObject::SearchPath => CatalogRecord::SearchPath(self.get_search_path()),
+        }
+    }
+
+    pub(crate) fn apply_record(&self, record: CatalogRecord) {
+        match record {
+            CatalogRecord::Table {
+                name,
+                table,
+                defaults,
+                constraints,
+                options,
+                indexes,
+                snapshot,
+            } => {
+                match snapshot {
+                    Some(snapshot) => {
+                        self.state.snapshots.insert(name.clone(), snapshot);
+                    }
+                    None => {
+                        self.state.snapshots.remove(&name);
+                    }
+                }
+                match table {
+                    Some(table) => {
+                        self.state
+                            .tables
+                            .insert(name.clone(), Arc::new(RwLock::new(table)));
+                    }
+                    None => {
+                        self.state.tables.remove(&name);
+                    }
+                }
+                self.record_table_history(&name);
+                match defaults {
+                    Some(defaults) => {
+                        self.state.table_defaults.insert(name.clone(), defaults);
+                    }
+                    None => {
+                        self.state.table_defaults.remove(&name);
+                    }
+                }
+                match constraints {
+                    Some(constraints) => {
+                        self.state
+                            .table_constraints
+                            .insert(name.clone(), constraints);
+                    }
+                    None => {
+                        self.state.table_constraints.remove(&name);
+                    }
+                }
+                match options {
+                    Some(options) => {
+                        self.state.table_options.insert(name.clone(), options);
+                    }
+                    None => {
+                        self.state.table_options.remove(&name);
+                    }
+                }
+                match indexes {
+                    Some(indexes) => {
+                        self.state.table_indexes.insert(name, indexes);
+                    }
+                    None => {
+                        self.state.table_indexes.remove(&name);
+                    }
+                }
+            }
+            CatalogRecord::Function { name, function } => match function {
+                Some(function) => {
+                    self.bump_definition_generation(&name);
+                    self.state.functions.insert(name, function);
+                }
+                None => {
+                    self.bump_definition_generation(&name);
+                    self.state.functions.remove(&name);
+                }
+            },
+            CatalogRecord::Procedure {
+                name,
+                procedure,
+                body,
+            } => {
+                match procedure {
+                    Some(procedure) => {
+                        self.state.procedures.insert(name.clone(), procedure);
+                    }
+                    None => {
+                        self.state.procedures.remove(&name);
+                    }
+                }
+                match body {
+                    Some(body) => {
+                        self.state.procedure_bodies.insert(name, body);
+                    }
+                    None => {
+                        self.state.procedure_bodies.remove(&name);
+                    }
+                }
+            }
+            CatalogRecord::View { name, view } => match view {
+                Some(view) => {
+                    self.bump_definition_generation(&name);
+                    self.state.views.insert(name, view);
+                }
+                None => {
+                    self.bump_definition_generation(&name);
+                    self.state.views.remove(&name);
+                }
+            },
+            CatalogRecord::Schema {
+                name,
+                metadata,
+                dropped,
+            } => {
+                match metadata {
+                    Some(metadata) => {
+                        self.state.schemas.insert(name.clone(), ());
+                        self.state.schema_metadata.insert(name.clone(), metadata);
+                    }
+                    None => {
+                        self.state.schemas.remove(&name);
+                        self.state.schema_metadata.remove(&name);
+                    }
+                }
+                match dropped {
+                    Some(dropped) => {
+                        self.state.dropped_schemas.insert(name, dropped);
+                    }
+                    None => {
+                        self.state.dropped_schemas.remove(&name);
+                    }
+                }
+            }
+            CatalogRecord::SearchPath(path) => {
+                *self.state.search_path.write() = path;
             }
+            CatalogRecord::TableDelta { .. } => {
+                unreachable!("table deltas are rebuilt into full records before replay")
+            }
+        }
+    }
+
+    /// Puts back changes taken by [`Self::take_changes`] that could not be
+    /// made durable, so they are logged by the next flush.
+    pub(crate) fn restore_changes(&self, changes: Vec<CatalogObject>) {
+        self.state.changed_objects.lock().unwrap().extend(changes);
+    }
+
+    /// The resolved name and current version of each table, or `None` when
+    /// one of them is not a stored table.
+    pub(crate) fn table_versions(&self, names: &[String]) -> Option<Vec<(String, u64)>> {
+        names
+            .iter()
+            .map(|name| {
+                let key = self.resolve_table_name(name);
+                self.state
+                    .tables
+                    .contains_key(&key)
+                    .then(|| (key.clone(), self.table_version(&key)))
+            })
+            .collect()
+    }
+
+    fn table_version(&self, key: &str) -> u64 {
+        self.state.table_versions.get(key).map(|v| *v).unwrap_or(0)
+    }
+
+    fn bump_table_version(&self, key: &str) {
+        let version = self.state.last_version.fetch_add(1, Ordering::AcqRel) + 1;
+        let previous = self
+            .state
+            .table_versions
+            .insert(key.to_string(), version)
+            .unwrap_or(0);
+        let mut run = self.own_versions.entry(key.to_string()).or_insert((0, 0));
+        *run = match *run {
+            (first, last) if last == previous && last != 0 => (first, version),
+            _ => (previous, version),
+        };
+    }
+
+    /// Whether the table is still at `version`, or has only been changed
+    /// since by this view, e.g. by DDL run earlier in the same statement.
+    fn unchanged_since(&self, key: &str, version: u64) -> bool {
+        let current = self.table_version(key);
+        current == version
+            || self
+                .own_versions
+                .get(key)
+                .is_some_and(|run| run.0 <= version && run.1 == current)
+    }
+
+    /// Adds the table as it is now to its time travel history, then drops
+    /// the versions that are out of the time travel window or beyond the
+    /// per-table caps. Session temp tables keep no history.
+    fn record_table_history(&self, key: &str) {
+        let handle = match self.state.tables.get(key).map(|r| r.clone()) {
+            Some(handle) if !is_session_table(key) => handle,
+            _ => {
+                self.state.table_history.remove(key);
+                self.state.history_origins.remove(key);
+                return;
+            }
+        };
+        let table = handle.read().clone();
+        let now = self.history_now();
+        let cutoff = now - self.time_travel_window(key);
+        let version = self.table_version(key);
+        self.state
+            .history_origins
+            .entry(key.to_string())
+            .or_insert(version);
+        let mut history = self.state.table_history.entry(key.to_string()).or_default();
+        let known: HashMap<*const Segment, usize> = history
+            .back()
+            .map(|previous| {
+                previous
+                    .table
+                    .segments()
+                    .iter()
+                    .zip(&previous.segment_sizes)
+                    .map(|(segment, size)| (Arc::as_ptr(segment), *size))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let mut bytes = 0;
+        let segment_sizes = table
+            .segments()
+            .iter()
+            .map(|segment| match known.get(&Arc::as_ptr(segment)) {
+                Some(size) => *size,
+                None => {
+                    let size = segment.memory_size();
+                    bytes += size;
+                    size
+                }
+            })
+            .collect();
+        history.push_back(TableVersion {
+            version,
+            committed_at: now,
+            bytes,
+            segment_sizes,
+            table,
+        });
+        let mut bytes: usize = history.iter().map(|v| v.bytes).sum();
+        while history.len() > 1
+            && (history[1].committed_at <= cutoff
+                || history.len() > MAX_HISTORY_VERSIONS
+                || bytes > MAX_HISTORY_BYTES)
+        {
+            let Some(oldest) = history.pop_front() else {
+                break;
+            };
+            bytes -= oldest.bytes;
+            if let Some(front) = history.front_mut() {
+                let full: usize = front.segment_sizes.iter().sum();
+                bytes += full - front.bytes;
+                front.bytes = full;
+            }
+        }
+    }
+
+    fn time_travel_window(&self, key: &str) -> Duration {
+        let hours = key
+            .rsplit_once('.')
+            .and_then(|(schema, _)| self.state.schema_metadata.get(schema))
+            .and_then(|metadata| {
+                metadata
+                    .options
+                    .iter()
+                    .find(|(k, _)| k.eq_ignore_ascii_case("max_time_travel_hours"))
+                    .and_then(|(_, v)| v.trim_matches('\'').parse::<i64>().ok())
+            })
+            .unwrap_or(DEFAULT_TIME_TRAVEL_HOURS);
+        Duration::hours(hours)
+    }
+
+    /// Stamps table versions with `clock` instead of the system clock, for
+    /// tests that travel between commits without waiting for time to pass.
+    pub fn set_history_clock(&self, clock: HistoryClock) {
+        *self.state.history_clock.write() = Some(HistoryClockSource(clock));
+    }
+
+    /// The time as the table history sees it.
+    pub(crate) fn history_now(&self) -> DateTime<Utc> {
+        match &*self.state.history_clock.read() {
+            Some(HistoryClockSource(clock)) => clock(),
+            None => Utc::now(),
+        }
+    }
+
+    pub fn table_as_of(&self, name: &str, timestamp: DateTime<Utc>) -> Result<Table> {
+        let key = self.resolve_table_name(name);
+        let history = self
+            .state
+            .table_history
+            .get(&key)
+            .ok_or_else(|| Error::TableNotFound(name.to_string()))?;
+        let oldest = history
+            .front()
+            .ok_or_else(|| Error::TableNotFound(name.to_string()))?;
+        let readable_from = oldest
+            .committed_at
+            .max(self.history_now() - self.time_travel_window(&key));
+        if timestamp < readable_from {
+            return Err(Error::invalid_query(format!(
+                "Invalid snapshot time {} for table {}. Cannot read before {}",
+                timestamp, name, readable_from
+            )));
+        }
+        let version = history
+            .iter()
+            .rev()
+            .find(|v| v.committed_at <= timestamp)
+            .unwrap_or(oldest);
+        Ok(version.table.clone())
+    }
+
+    fn is_visible(&self, key: &str) -> bool {
+        if !is_session_table(key) {
+            return true;
+        }
+        match &self.session {
+            Some(session) => key.starts_with(&format!("{}.", session.temp_schema())),
+            None => false,
         }
-        *self.transaction_snapshot.write() = Some(TransactionSnapshot {
-            tables: tables_snapshot,
+    }
+
+    pub fn in_transaction(&self) -> bool {
+        self.transaction.read().is_some()
+    }
+
+    pub fn begin_transaction(&self) -> Result<()> {
+        let mut guard = self.transaction.write();
+        if guard.is_some() {
+            return Err(Error::invalid_query(
+                "Nested transactions are not supported",
+            ));
+        }
+        let _commit = self.state.commit_lock.lock().unwrap();
+        *guard = Some(Transaction {
+            snapshot: self.state.last_version.load(Ordering::Acquire),
+            ..Transaction::default()
         });
+        Ok(())
     }
 
-    pub fn snapshot_table(&self, name: &str, table_data: Table) {
-        let mut snapshot_guard = self.transaction_snapshot.write();
-        if let Some(ref mut snapshot) = *snapshot_guard {
-            snapshot.tables.insert(name.to_uppercase(), table_data);
+    /// The table as the transaction sees it. A table is pinned on first
+    /// access, at the newest version committed before `BEGIN`, so later reads
+    /// and the conflict check at `COMMIT` use that version. Temp tables are
+    /// private to the session and pinned as they are now, as are tables
+    /// created after `BEGIN`. If the version from before `BEGIN` has been
+    /// pruned from the history the transaction is aborted, as it can no
+    /// longer read a consistent snapshot. Returns `None` only for tables
+    /// that do not exist.
+    fn transaction_table(&self, transaction: &mut Transaction, key: &str) -> Result<Option<Table>> {
+        if let Some(table) = transaction.table(key) {
+            return Ok(Some(table.clone()));
+        }
+        let _commit = self.state.commit_lock.lock().unwrap();
+        let Some(handle) = self.state.tables.get(key).map(|r| r.clone()) else {
+            return Ok(None);
+        };
+        let current = self.table_version(key);
+        let (version, table) = if current > transaction.snapshot && !is_session_table(key) {
+            let pinned = self.state.table_history.get(key).and_then(|history| {
+                history
+                    .iter()
+                    .rev()
+                    .find(|v| v.version <= transaction.snapshot)
+                    .map(|v| (v.version, v.table.clone()))
+            });
+            let created_after_begin = self
+                .state
+                .history_origins
+                .get(key)
+                .is_none_or(|origin| *origin > transaction.snapshot);
+            match pinned {
+                Some(pinned) => pinned,
+                None if created_after_begin => (current, handle.read().clone()),
+                None => return Err(Error::transaction_aborted(key)),
+            }
         } else {
-            let mut tables = HashMap::new();
-            tables.insert(name.to_uppercase(), table_data);
-            *snapshot_guard = Some(TransactionSnapshot { tables });
+            (current, handle.read().clone())
+        };
+        transaction.versions.insert(key.to_string(), version);
+        transaction.tables.insert(key.to_string(), table.clone());
+        Ok(Some(table))
+    }
+
+    pub fn commit(&self) -> Result<()> {
+        let Some(transaction) = self.transaction.write().take() else {
+            return Ok(());
+        };
+        let _commit = self.state.commit_lock.lock().unwrap();
+        for key in transaction.writes.keys() {
+            if transaction.versions.get(key) != Some(&self.table_version(key)) {
+                return Err(Error::transaction_aborted(key.clone()));
+            }
         }
+        for (key, table) in transaction.writes {
+            let Some(handle) = self.state.tables.get(&key).map(|r| r.clone()) else {
+                continue;
+            };
+            *handle.write() = table;
+            self.record_change(CatalogObject::Table(key));
+        }
+        Ok(())
     }
 
-    pub fn commit(&self) {
-        *self.transaction_snapshot.write() = None;
+    pub fn rollback(&self) -> Vec<String> {
+        match self.transaction.write().take() {
+            Some(transaction) => transaction.writes.into_keys().collect(),
+            None => Vec::new(),
+        }
     }
 
-    pub fn rollback(&self) {
-        let snapshot = self.transaction_snapshot.write().take();
-        if let Some(snapshot) = snapshot {
-            for (name, table_data) in snapshot.tables {
-                if let Some(handle) = self.tables.get(&name)
-                    && let Some(mut table) = handle.try_write()
-                {
-                    *table = table_data;
+    /// Applies the tables written by a statement. Outside a transaction each
+    /// table must still be at the version it was copied at, else another
+    /// session committed to it in the meantime and the statement is aborted
+    /// rather than overwriting that change.
+    pub(crate) fn apply_writes(&self, writes: Vec<(String, Table, Option<u64>)>) -> Result<()> {
+        if writes.is_empty() {
+            return Ok(());
+        }
+        if let Some(transaction) = self.transaction.write().as_mut() {
+            for (name, table, _) in writes {
+                let key = self.resolve_table_name(&name);
+                if self.state.tables.contains_key(&key) {
+                    transaction.writes.insert(key, table);
                 }
             }
+            return Ok(());
+        }
+        let _commit = self.state.commit_lock.lock().unwrap();
+        for (name, _, version) in &writes {
+            let key = self.resolve_table_name(name);
+            if let Some(version) = version
+                && self.state.tables.contains_key(&key)
+                && !self.unchanged_since(&key, *version)
+            {
+                return Err(Error::transaction_aborted(key));
+            }
+        }
+        for (name, table, _) in writes {
+            self.update_table(&name, table);
         }
+        Ok(())
+    }
+
+    pub fn read_table(&self, name: &str) -> Option<Table> {
+        self.try_read_table(name).ok().flatten()
+    }
+
+    /// Like [`Self::read_table`], but fails if an open transaction can no
+    /// longer read the table as of its snapshot.
+    pub(crate) fn try_read_table(&self, name: &str) -> Result<Option<Table>> {
+        let key = self.resolve_table_name(name);
+        if let Some(transaction) = self.transaction.write().as_mut()
+            && let Some(table) = self.transaction_table(transaction, &key)?
+        {
+            return Ok(Some(table));
+        }
+        let Some(handle) = self.state.tables.get(&key).map(|r| r.clone()) else {
+            return Ok(self.information_schema_table(name));
+        };
+        let table = handle.read().clone();
+        Ok(Some(table))
+    }
+
+    fn information_schema_table(&self, name: &str) -> Option<Table> {
+        let upper = name.to_uppercase();
+        let parts: Vec<&str> = upper.split('.').collect();
+        let position = parts
+            .iter()
+            .position(|part| *part == "INFORMATION_SCHEMA")?;
+        let dataset = match position {
+            0 => None,
+            _ if parts[position - 1].starts_with("REGION-") => None,
+            _ => Some(parts[position - 1]),
+        };
+        match parts.get(position + 1..)? {
+            [view] => information_schema::view(self, dataset, view),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn schema_names(&self) -> Vec<String> {
+        self.state.schemas.iter().map(|r| r.key().clone()).collect()
     }
 
-    pub fn take_transaction_snapshot(&self) -> Option<TransactionSnapshot> {
-        self.transaction_snapshot.write().take()
+    pub(crate) fn schema_options(&self, name: &str) -> HashMap<String, String> {
+        self.state
+            .schema_metadata
+            .get(&name.to_uppercase())
+            .map(|m| m.options.clone())
+            .unwrap_or_default()
+    }
+
+    /// Every persistent table with its catalog key, leaving out session temp tables.
+    pub(crate) fn table_handles(&self) -> Vec<(String, TableHandle)> {
+        self.state
+            .tables
+            .iter()
+            .filter(|r| !is_session_table(r.key()))
+            .map(|r| (r.key().clone(), r.value().clone()))
+            .collect()
+    }
+
+    pub(crate) fn views(&self) -> Vec<(String, ViewDef)> {
+        self.state
+            .views
+            .iter()
+            .map(|r| (r.key().clone(), r.value().clone()))
+            .collect()
     }
 
-    fn resolve_table_name(&self, name: &str) -> String {
+    pub(crate) fn procedures(&self) -> Vec<UserProcedure> {
+        self.state
+            .procedures
+            .iter()
+            .map(|r| r.value().clone())
+            .collect()
+    }
+
+    pub(crate) fn last_modified(&self, name: &str) -> Option<DateTime<Utc>> {
+        let key = self.resolve_table_name(name);
+        self.state
+            .table_history
+            .get(&key)
+            .and_then(|history| history.back().map(|version| version.committed_at))
+    }
+
+    /// The size of the table's segments as recorded when its latest version
+    /// was committed. Session temp tables keep no history and have none.
+    pub(crate) fn stored_bytes(&self, name: &str) -> Option<usize> {
+        let key = self.resolve_table_name(name);
+        self.state.table_history.get(&key).and_then(|history| {
+            history
+                .back()
+                .map(|version| version.segment_sizes.iter().sum())
+        })
+    }
+
+    fn table_key(&self, name: &str) -> String {
         let key = name.to_uppercase();
-        if key.contains('.') || self.tables.contains_key(&key) {
+        match (&self.session, key.strip_prefix(SESSION_SCHEMA)) {
+            (Some(session), Some(rest)) if rest.starts_with('.') => {
+                format!("{}{}", session.temp_schema(), rest)
+            }
+            _ => key,
+        }
+    }
+
+    pub(crate) fn is_temp_table(&self, name: &str) -> bool {
+        is_session_table(&self.resolve_table_name(name))
+    }
+
+    pub(crate) fn resolve_table_name(&self, name: &str) -> String {
+        let key = self.table_key(name);
+        if key.contains('.') {
+            return key;
+        }
+        if let Some(session) = &self.session {
+            let temp_key = format!("{}.{}", session.temp_schema(), key);
+            if self.state.tables.contains_key(&temp_key) {
+                return temp_key;
+            }
+        }
+        if self.state.tables.contains_key(&key) {
             return key;
         }
-        let search_path = self.search_path.read();
+        let search_path = match self.session.as_ref().and_then(|s| s.current_schema()) {
+            Some(schema) => vec![schema.to_uppercase()],
+            None => self.state.search_path.read().clone(),
+        };
         for schema in search_path.iter() {
             let qualified = format!("{}.{}", schema, key);
-            if self.tables.contains_key(&qualified) {
+            if self.state.tables.contains_key(&qualified) {
                 return qualified;
             }
         }
         key
     }
 
+    /// The session state that name resolution depends on besides the shared
+    /// catalog: the current schema and, while the session has any, its temp
+    /// tables and functions. Empty for sessions that resolve names like the
+    /// engine does.
+    pub(crate) fn resolution_scope(&self) -> String {
+        let Some(session) = &self.session else {
+            return String::new();
+        };
+        let mut scope = session
+            .current_schema()
+            .map(|schema| schema.to_uppercase())
+            .unwrap_or_default();
+        let prefix = format!("{}.", session.temp_schema());
+        if self
+            .state
+            .tables
+            .iter()
+            .any(|r| r.key().starts_with(&prefix))
+            || self
+                .state
+                .functions
+                .iter()
+                .any(|r| r.key().starts_with(&prefix))
+        {
+            scope.push('\u{0}');
+            scope.push_str(&prefix);
+        }
+        scope
+    }
+
     pub fn get_table_handle(&self, name: &str) -> Option<TableHandle> {
         let key = self.resolve_table_name(name);
-        self.tables.get(&key).map(|r| r.clone())
+        self.state.tables.get(&key).map(|r| r.clone())
     }
 
     pub fn acquire_table_locks(&self, accesses: &TableAccessSet) -> Result<TableLockSet> {
         let locks = TableLockSet::new();
+        let mut transaction = self.transaction.write();
 
         for (table_name, access_type) in &accesses.accesses {
             let resolved = self.resolve_table_name(table_name);
-            let handle_opt = self.tables.get(&resolved);
+            if *access_type != AccessType::Read {
+                self.check_writable(table_name)?;
+            }
+            let pinned = match transaction.as_mut() {
+                Some(transaction) => self.transaction_table(transaction, &resolved)?,
+                None => None,
+            };
+            if let Some(table) = pinned {
+                match access_type {
+                    AccessType::Read => locks.add_read_table(resolved, table),
+                    AccessType::Write | AccessType::WriteOptional => {
+                        locks.add_write_table(resolved, table)
+                    }
+                }
+                continue;
+            }
+            let handle_opt = self.state.tables.get(&resolved);
 
             match access_type {
                 AccessType::WriteOptional => {
-                    if let Some(handle) = handle_opt {
-                        let handle = handle.clone();
-                        let table = handle.write().clone();
-                        locks.add_write_table(resolved, table);
+                    if let Some(handle) = handle_opt.map(|r| r.clone()) {
+                        let (table, version) = self.versioned_copy(&handle, &resolved);
+                        locks.add_versioned_write_table(resolved, table, version);
                     }
                 }
                 AccessType::Read => {
-                    let handle = handle_opt
-                        .ok_or_else(|| Error::TableNotFound(table_name.clone()))?
-                        .clone();
-                    let table = handle.read().clone();
+                    let Some(handle) = handle_opt else {
+                        let table = self
+                            .information_schema_table(table_name)
+                            .ok_or_else(|| Error::TableNotFound(table_name.clone()))?;
+                        locks.add_read_table(resolved, table);
+                        continue;
+                    };
+                    let table = handle.clone().read().clone();
                     locks.add_read_table(resolved, table);
                 }
                 AccessType::Write => {
                     let handle = handle_opt
                         .ok_or_else(|| Error::TableNotFound(table_name.clone()))?
                         .clone();
-                    let table = handle.write().clone();
-                    locks.add_write_table(resolved, table);
+                    let (table, version) = self.versioned_copy(&handle, &resolved);
+                    locks.add_versioned_write_table(resolved, table, version);
                 }
             }
         }
@@ -254,9 +1143,17 @@ impl ConcurrentCatalog {
         Ok(locks)
     }
 
+    /// Copies a table along with the version it is at, under the commit lock
+    /// so that no write is applied between the two.
+    fn versioned_copy(&self, handle: &TableHandle, key: &str) -> (Table, u64) {
+        let _commit = self.state.commit_lock.lock().unwrap();
+        let table = handle.read().clone();
+        (table, self.table_version(key))
+    }
+
     pub fn create_schema(&self, name: &str, if_not_exists: bool) -> Result<()> {
         let key = name.to_uppercase();
-        if self.schemas.contains_key(&key) {
+        if self.state.schemas.contains_key(&key) {
             if if_not_exists {
                 return Ok(());
             }
@@ -265,9 +1162,12 @@ impl ConcurrentCatalog {
                 name
             )));
         }
-        self.dropped_schemas.remove(&key);
-        self.schemas.insert(key.clone(), ());
-        self.schema_metadata.insert(key, SchemaMetadata::default());
+        self.state.dropped_schemas.remove(&key);
+        self.state.schemas.insert(key.clone(), ());
+        self.state
+            .schema_metadata
+            .insert(key.clone(), SchemaMetadata::default());
+        self.record_change(CatalogObject::Schema(key));
         Ok(())
     }
 
@@ -278,7 +1178,7 @@ impl ConcurrentCatalog {
         options: HashMap<String, String>,
     ) -> Result<()> {
         let key = name.to_uppercase();
-        if self.schemas.contains_key(&key) {
+        if self.state.schemas.contains_key(&key) {
             if if_not_exists {
                 return Ok(());
             }
@@ -287,15 +1187,18 @@ impl ConcurrentCatalog {
                 name
             )));
         }
-        self.dropped_schemas.remove(&key);
-        self.schemas.insert(key.clone(), ());
-        self.schema_metadata.insert(key, SchemaMetadata { options });
+        self.state.dropped_schemas.remove(&key);
+        self.state.schemas.insert(key.clone(), ());
+        self.state
+            .schema_metadata
+            .insert(key.clone(), SchemaMetadata { options });
+        self.record_change(CatalogObject::Schema(key));
         Ok(())
     }
 
     pub fn drop_schema(&self, name: &str, if_exists: bool, cascade: bool) -> Result<()> {
         let key = name.to_uppercase();
-        if !self.schemas.contains_key(&key) {
+        if !self.state.schemas.contains_key(&key) {
             if if_exists {
                 return Ok(());
             }
@@ -304,6 +1207,7 @@ impl ConcurrentCatalog {
 
         let prefix = format!("{}.", key);
         let tables_in_schema: Vec<String> = self
+            .state
             .tables
             .iter()
             .filter(|r| r.key().starts_with(&prefix))
@@ -319,41 +1223,61 @@ impl ConcurrentCatalog {
 
         let mut dropped_tables = Vec::new();
         let mut dropped_defaults = Vec::new();
+        let mut dropped_constraints = Vec::new();
+        let mut dropped_options = Vec::new();
+        let mut dropped_indexes = Vec::new();
         for table_key in tables_in_schema {
-            if let Some((_, handle)) = self.tables.remove(&table_key) {
+            if let Some((_, handle)) = self.state.tables.remove(&table_key) {
                 let table = handle.read().clone();
                 dropped_tables.push((table_key.clone(), table));
             }
-            if let Some((_, defaults)) = self.table_defaults.remove(&table_key) {
-                dropped_defaults.push((table_key, defaults));
+            if let Some((_, defaults)) = self.state.table_defaults.remove(&table_key) {
+                dropped_defaults.push((table_key.clone(), defaults));
+            }
+            if let Some((_, constraints)) = self.state.table_constraints.remove(&table_key) {
+                dropped_constraints.push((table_key.clone(), constraints));
             }
+            if let Some((_, options)) = self.state.table_options.remove(&table_key) {
+                dropped_options.push((table_key.clone(), options));
+            }
+            if let Some((_, indexes)) = self.state.table_indexes.remove(&table_key) {
+                dropped_indexes.push((table_key.clone(), indexes));
+            }
+            self.record_change(CatalogObject::Table(table_key));
         }
 
-        self.schemas.remove(&key);
+        self.state.schemas.remove(&key);
         let metadata = self
+            .state
             .schema_metadata
             .remove(&key)
             .map(|(_, m)| m)
             .unwrap_or_default();
 
-        self.dropped_schemas.insert(
-            key,
+        self.state.dropped_schemas.insert(
+            key.clone(),
             DroppedSchemaData {
                 metadata,
                 tables: dropped_tables,
                 table_defaults: dropped_defaults,
+                table_constraints: dropped_constraints,
+                table_options: dropped_options,
+                table_indexes: dropped_indexes,
             },
         );
+        self.record_change(CatalogObject::Schema(key));
         Ok(())
     }
 
     pub fn is_schema_dropped(&self, name: &str) -> bool {
-        self.dropped_schemas.contains_key(&name.to_uppercase())
+        self.state
+            .dropped_schemas
+            .contains_key(&name.to_uppercase())
     }
 
     pub fn undrop_schema(&self, name: &str, if_not_exists: bool) -> Result<()> {
         let key = name.to_uppercase();
-        if self.schemas.contains_key(&key) {
+        if self.state.schemas.contains_key(&key) {
             if if_not_exists {
                 return Ok(());
             }
@@ -362,17 +1286,40 @@ impl ConcurrentCatalog {
                 name
             )));
         }
-        let dropped = self.dropped_schemas.remove(&key);
+        let dropped = self.state.dropped_schemas.remove(&key);
         match dropped {
             Some((_, dropped_data)) => {
-                self.schemas.insert(key.clone(), ());
-                self.schema_metadata.insert(key, dropped_data.metadata);
+                self.state.schemas.insert(key.clone(), ());
+                self.state
+                    .schema_metadata
+                    .insert(key.clone(), dropped_data.metadata);
                 for (table_key, table) in dropped_data.tables {
-                    self.tables.insert(table_key, Arc::new(RwLock::new(table)));
+                    self.state
+                        .tables
+                        .insert(table_key.clone(), Arc::new(RwLock::new(table)));
+                    self.record_change(CatalogObject::Table(table_key));
                 }
                 for (table_key, defaults) in dropped_data.table_defaults {
-                    self.table_defaults.insert(table_key, defaults);
+                    self.state
+                        .table_defaults
+                        .insert(table_key.clone(), defaults);
+                    self.record_metadata_change(table_key);
+                }
+                for (table_key, constraints) in dropped_data.table_constraints {
+                    self.state
+                        .table_constraints
+                        .insert(table_key.clone(), constraints);
+                    self.record_metadata_change(table_key);
                 }
+                for (table_key, options) in dropped_data.table_options {
+                    self.state.table_options.insert(table_key.clone(), options);
+                    self.record_metadata_change(table_key);
+                }
+                for (table_key, indexes) in dropped_data.table_indexes {
+                    self.state.table_indexes.insert(table_key.clone(), indexes);
+                    self.record_metadata_change(table_key);
+                }
+                self.record_change(CatalogObject::Schema(key));
                 Ok(())
             }
             None => {
@@ -388,58 +1335,65 @@ impl ConcurrentCatalog {
     }
 
     pub fn schema_exists(&self, name: &str) -> bool {
-        self.schemas.contains_key(&name.to_uppercase())
+        self.state.schemas.contains_key(&name.to_uppercase())
     }
 
     pub fn alter_schema_options(&self, name: &str, options: HashMap<String, String>) -> Result<()> {
         let key = name.to_uppercase();
-        if !self.schemas.contains_key(&key) {
+        if !self.state.schemas.contains_key(&key) {
             return Err(Error::invalid_query(format!("Schema not found: {}", name)));
         }
-        if let Some(mut metadata) = self.schema_metadata.get_mut(&key) {
+        if let Some(mut metadata) = self.state.schema_metadata.get_mut(&key) {
             for (k, v) in options {
                 metadata.options.insert(k, v);
             }
         }
+        self.record_change(CatalogObject::Schema(key));
         Ok(())
     }
 
     pub fn get_schema_default_collation(&self, name: &str) -> Option<String> {
         let key = name.to_uppercase();
-        self.schema_metadata
+        self.state
+            .schema_metadata
             .get(&key)
             .and_then(|metadata| metadata.options.get("default_collate").cloned())
     }
 
     pub fn set_search_path(&self, schemas: Vec<String>) {
-        *self.search_path.write() = schemas.into_iter().map(|s| s.to_uppercase()).collect();
+        *self.state.search_path.write() = schemas.into_iter().map(|s| s.to_uppercase()).collect();
+        self.record_change(CatalogObject::SearchPath);
     }
 
     pub fn get_search_path(&self) -> Vec<String> {
-        self.search_path.read().clone()
+        self.state.search_path.read().clone()
     }
 
     pub fn create_table(&self, name: &str, schema: Schema) -> Result<()> {
-        let key = name.to_uppercase();
-        if self.tables.contains_key(&key) {
+        let key = self.table_key(name);
+        if self.state.tables.contains_key(&key) {
             return Err(Error::invalid_query(format!(
                 "Table already exists: {}",
                 name
             )));
         }
         let table = Table::new(schema);
-        self.tables.insert(key, Arc::new(RwLock::new(table)));
+        self.state
+            .tables
+            .insert(key.clone(), Arc::new(RwLock::new(table)));
+        self.record_change(CatalogObject::Table(key));
         Ok(())
     }
 
     pub fn set_table_defaults(&self, name: &str, defaults: Vec<ColumnDefault>) {
-        let key = name.to_uppercase();
-        self.table_defaults.insert(key, defaults);
+        let key = self.table_key(name);
+        self.state.table_defaults.insert(key.clone(), defaults);
+        self.record_metadata_change(key);
     }
 
     pub fn get_table_defaults(&self, name: &str) -> Option<Vec<ColumnDefault>> {
         let key = self.resolve_table_name(name);
-        self.table_defaults.get(&key).map(|r| r.clone())
+        self.state.table_defaults.get(&key).map(|r| r.clone())
     }
 
     pub fn get_column_default(&self, table_name: &str, column_name: &str) -> Option<Expr> {
@@ -452,101 +1406,446 @@ impl ConcurrentCatalog {
         None
     }
 
+    /// Replaces the constraints declared on a table. PRIMARY KEY, FOREIGN KEY,
+    /// UNIQUE and CHECK constraints are only enforced when the session sets
+    /// `@@yachtsql.enforce_constraints`.
+    pub fn set_table_constraints(&self, name: &str, constraints: Vec<TableConstraint>) {
+        let key = self.table_key(name);
+        if constraints.is_empty() {
+            if self.state.table_constraints.remove(&key).is_none() {
+                return;
+            }
+        } else {
+            self.state
+                .table_constraints
+                .insert(key.clone(), constraints);
+        }
+        self.record_metadata_change(key);
+    }
+
+    pub fn get_table_constraints(&self, name: &str) -> Vec<TableConstraint> {
+        let key = self.resolve_table_name(name);
+        self.state
+            .table_constraints
+            .get(&key)
+            .map(|r| r.clone())
+            .unwrap_or_default()
+    }
+
+    /// The FOREIGN KEY constraints that reference `name`, each with the key
+    /// of the table declaring it. Other sessions' temporary tables are
+    /// skipped.
+    pub fn referencing_constraints(&self, name: &str) -> Vec<(String, TableConstraint)> {
+        let key = self.resolve_table_name(name);
+        let own_prefix = self
+            .session
+            .as_ref()
+            .map(|session| format!("{}.", session.temp_schema()));
+        let mut referencing = Vec::new();
+        for entry in self.state.table_constraints.iter() {
+            if is_session_table(entry.key())
+                && !own_prefix
+                    .as_ref()
+                    .is_some_and(|prefix| entry.key().starts_with(prefix.as_str()))
+            {
+                continue;
+            }
+            for constraint in entry.value() {
+                if let ConstraintType::ForeignKey {
+                    references_table, ..
+                } = &constraint.constraint_type
+                    && self.resolve_table_name(references_table) == key
+                {
+                    referencing.push((entry.key().clone(), constraint.clone()));
+                }
+            }
+        }
+        referencing
+    }
+
+    pub fn add_table_constraint(&self, name: &str, constraint: TableConstraint) -> Result<()> {
+        let mut constraints = self.get_table_constraints(name);
+        if let Some(constraint_name) = &constraint.name
+            && constraints.iter().any(|c| {
+                c.name
+                    .as_ref()
+                    .is_some_and(|n| n.eq_ignore_ascii_case(constraint_name))
+            })
+        {
+            return Err(Error::invalid_query(format!(
+                "Constraint {} already exists on table {}",
+                constraint_name, name
+            )));
+        }
+        if matches!(
+            constraint.constraint_type,
+            ConstraintType::PrimaryKey { .. }
+        ) && constraints
+            .iter()
+            .any(|c| matches!(c.constraint_type, ConstraintType::PrimaryKey { .. }))
+        {
+            return Err(Error::invalid_query(format!(
+                "Table {} already has a primary key",
+                name
+            )));
+        }
+        constraints.push(constraint);
+        self.set_table_constraints(&self.resolve_table_name(name), constraints);
+        Ok(())
+    }
+
+    pub fn drop_table_constraint(&self, name: &str, constraint_name: &str) -> Result<()> {
+        let mut constraints = self.get_table_constraints(name);
+        let before = constraints.len();
+        constraints.retain(|c| {
+            !c.name
+                .as_ref()
+                .is_some_and(|n| n.eq_ignore_ascii_case(constraint_name))
+        });
+        if constraints.len() == before {
+            return Err(Error::invalid_query(format!(
+                "Constraint {} not found on table {}",
+                constraint_name, name
+            )));
+        }
+        self.set_table_constraints(&self.resolve_table_name(name), constraints);
+        Ok(())
+    }
+
+    pub fn drop_primary_key(&self, name: &str) -> Result<()> {
+        let mut constraints = self.get_table_constraints(name);
+        let before = constraints.len();
+        constraints.retain(|c| !matches!(c.constraint_type, ConstraintType::PrimaryKey { .. }));
+        if constraints.len() == before {
+            return Err(Error::invalid_query(format!(
+                "Table {} does not have a primary key",
+                name
+            )));
+        }
+        self.set_table_constraints(&self.resolve_table_name(name), constraints);
+        Ok(())
+    }
+
+    /// Merges `OPTIONS` set on a table. Values are kept as SQL literals, and
+    /// setting an option to NULL removes it.
+    pub fn set_table_options(&self, name: &str, options: &[(String, String)]) {
+        if options.is_empty() {
+            return;
+        }
+        let key = self.resolve_table_name(name);
+        let mut current = self.get_table_options(&key);
+        for (option, value) in options {
+            current.retain(|(existing, _)| !existing.eq_ignore_ascii_case(option));
+            if !value.eq_ignore_ascii_case("NULL") {
+                current.push((option.clone(), value.clone()));
+            }
+        }
+        if current.is_empty() {
+            self.state.table_options.remove(&key);
+        } else {
+            self.state.table_options.insert(key.clone(), current);
+        }
+        self.record_metadata_change(key);
+    }
+
+    pub fn get_table_options(&self, name: &str) -> Vec<(String, String)> {
+        let key = self.resolve_table_name(name);
+        self.state
+            .table_options
+            .get(&key)
+            .map(|r| r.clone())
+            .unwrap_or_default()
+    }
+
+    /// Adds a search or vector index to a table. Index names are unique per
+    /// table and kind.
+    pub fn create_index(
+        &self,
+        table_name: &str,
+        index: TableIndex,
+        or_replace: bool,
+        if_not_exists: bool,
+    ) -> Result<()> {
+        let key = self.resolve_table_name(table_name);
+        if !self.state.tables.contains_key(&key) {
+            return Err(Error::TableNotFound(table_name.to_string()));
+        }
+        let mut indexes = self.get_table_indexes(&key);
+        if let Some(pos) = indexes
+            .iter()
+            .position(|i| i.kind == index.kind && i.name.eq_ignore_ascii_case(&index.name))
+        {
+            if if_not_exists {
+                return Ok(());
+            }
+            if !or_replace {
+                return Err(Error::invalid_query(format!(
+                    "Index already exists: {}",
+                    index.name
+                )));
+            }
+            indexes.remove(pos);
+        }
+        indexes.push(index);
+        self.state.table_indexes.insert(key.clone(), indexes);
+        self.record_metadata_change(key);
+        Ok(())
+    }
+
+    pub fn drop_index(
+        &self,
+        table_name: &str,
+        name: &str,
+        kind: IndexKind,
+        if_exists: bool,
+    ) -> Result<()> {
+        let key = self.resolve_table_name(table_name);
+        let mut indexes = self.get_table_indexes(&key);
+        let before = indexes.len();
+        indexes.retain(|i| !(i.kind == kind && i.name.eq_ignore_ascii_case(name)));
+        if indexes.len() == before {
+            if if_exists {
+                return Ok(());
+            }
+            return Err(Error::invalid_query(format!("Index not found: {}", name)));
+        }
+        if indexes.is_empty() {
+            self.state.table_indexes.remove(&key);
+        } else {
+            self.state.table_indexes.insert(key.clone(), indexes);
+        }
+        self.record_metadata_change(key);
+        Ok(())
+    }
+
+    pub fn get_table_indexes(&self, name: &str) -> Vec<TableIndex> {
+        let key = self.resolve_table_name(name);
+        self.state
+            .table_indexes
+            .get(&key)
+            .map(|r| r.clone())
+            .unwrap_or_default()
+    }
+
     pub fn insert_table(&self, name: &str, table: Table) -> Result<()> {
-        let key = name.to_uppercase();
-        if self.tables.contains_key(&key) {
+        let key = self.table_key(name);
+        if self.state.tables.contains_key(&key) {
             return Err(Error::invalid_query(format!(
                 "Table already exists: {}",
                 name
             )));
         }
-        self.tables.insert(key, Arc::new(RwLock::new(table)));
+        self.state
+            .tables
+            .insert(key.clone(), Arc::new(RwLock::new(table)));
+        self.record_change(CatalogObject::Table(key));
         Ok(())
     }
 
     pub fn drop_table(&self, name: &str) -> Result<()> {
         let key = self.resolve_table_name(name);
-        if self.tables.remove(&key).is_none() {
+        if self.state.tables.remove(&key).is_none() {
             return Err(Error::TableNotFound(name.to_string()));
         }
+        self.state.table_constraints.remove(&key);
+        self.state.table_options.remove(&key);
+        self.state.table_indexes.remove(&key);
+        self.record_change(CatalogObject::Table(key));
         Ok(())
     }
 
     pub fn table_exists(&self, name: &str) -> bool {
         let key = self.resolve_table_name(name);
-        self.tables.contains_key(&key)
+        self.state.tables.contains_key(&key)
     }
 
     pub fn rename_table(&self, old_name: &str, new_name: &str) -> Result<()> {
         let old_key = self.resolve_table_name(old_name);
-        let new_key = new_name.to_uppercase();
+        let new_key = match old_key.split_once('.') {
+            Some((schema, _)) if is_session_table(&old_key) && !new_name.contains('.') => {
+                format!("{}.{}", schema, new_name.to_uppercase())
+            }
+            _ => self.table_key(new_name),
+        };
 
-        if !self.tables.contains_key(&old_key) {
+        if !self.state.tables.contains_key(&old_key) {
             return Err(Error::TableNotFound(old_name.to_string()));
         }
-        if self.tables.contains_key(&new_key) {
+        if self.state.tables.contains_key(&new_key) {
             return Err(Error::invalid_query(format!(
                 "Table already exists: {}",
                 new_name
             )));
         }
 
-        if let Some((_, handle)) = self.tables.remove(&old_key) {
-            self.tables.insert(new_key, handle);
+        if let Some((_, handle)) = self.state.tables.remove(&old_key) {
+            self.state.tables.insert(new_key.clone(), handle);
+        }
+        if let Some((_, mut info)) = self.state.snapshots.remove(&old_key) {
+            info.table_name = new_name.to_string();
+            self.state.snapshots.insert(new_key.clone(), info);
+        }
+        if let Some((_, stats)) = self.state.table_statistics.remove(&old_key) {
+            self.state.table_statistics.insert(new_key.clone(), stats);
         }
+        if let Some((_, constraints)) = self.state.table_constraints.remove(&old_key) {
+            self.state
+                .table_constraints
+                .insert(new_key.clone(), constraints);
+        }
+        if let Some((_, options)) = self.state.table_options.remove(&old_key) {
+            self.state.table_options.insert(new_key.clone(), options);
+        }
+        if let Some((_, indexes)) = self.state.table_indexes.remove(&old_key) {
+            self.state.table_indexes.insert(new_key.clone(), indexes);
+        }
+        self.record_change(CatalogObject::Table(old_key));
+        self.record_change(CatalogObject::Table(new_key));
         Ok(())
     }
 
     pub fn replace_table(&self, name: &str, table: Table) -> Result<()> {
-        let key = name.to_uppercase();
-        if !self.tables.contains_key(&key) {
+        let key = self.resolve_table_name(name);
+        if !self.state.tables.contains_key(&key) {
             return Err(Error::TableNotFound(name.to_string()));
         }
-        self.tables.insert(key, Arc::new(RwLock::new(table)));
+        self.state.snapshots.remove(&key);
+        self.state.table_statistics.remove(&key);
+        self.state
+            .tables
+            .insert(key.clone(), Arc::new(RwLock::new(table)));
+        self.record_change(CatalogObject::Table(key));
         Ok(())
     }
 
     pub fn create_or_replace_table(&self, name: &str, table: Table) {
-        let key = name.to_uppercase();
-        self.tables.insert(key, Arc::new(RwLock::new(table)));
+        let key = self.table_key(name);
+        self.state.snapshots.remove(&key);
+        self.state.table_statistics.remove(&key);
+        self.state.table_options.remove(&key);
+        self.state.table_indexes.remove(&key);
+        self.state
+            .tables
+            .insert(key.clone(), Arc::new(RwLock::new(table)));
+        self.record_change(CatalogObject::Table(key));
+    }
+
+    pub fn create_snapshot(&self, name: &str, table: Table, info: TableSnapshotInfo) -> Result<()> {
+        let key = self.table_key(name);
+        if self.state.tables.contains_key(&key) {
+            return Err(Error::invalid_query(format!(
+                "Table already exists: {}",
+                name
+            )));
+        }
+        self.state.snapshots.insert(key.clone(), info);
+        self.state
+            .tables
+            .insert(key.clone(), Arc::new(RwLock::new(table)));
+        self.record_change(CatalogObject::Table(key));
+        Ok(())
+    }
+
+    pub fn is_snapshot(&self, name: &str) -> bool {
+        let key = self.resolve_table_name(name);
+        self.state.snapshots.contains_key(&key)
+    }
+
+    pub fn check_writable(&self, name: &str) -> Result<()> {
+        if self.is_snapshot(name) {
+            return Err(Error::invalid_query(format!(
+                "Table {} is a table snapshot. Table snapshots are read-only and cannot be modified",
+                name
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn set_table_statistics(&self, name: &str, stats: TableStatistics) {
+        let key = self.resolve_table_name(name);
+        self.state.table_statistics.insert(key, stats);
+    }
+
+    pub fn table_statistics(&self, name: &str) -> Option<TableStatistics> {
+        let key = self.resolve_table_name(name);
+        self.state.table_statistics.get(&key).map(|r| r.clone())
+    }
+
+    pub fn table_snapshots(&self) -> Vec<TableSnapshotInfo> {
+        self.state
+            .snapshots
+            .iter()
+            .filter(|entry| self.is_visible(entry.key()))
+            .map(|entry| entry.value().clone())
+            .collect()
     }
 
     pub fn update_table(&self, name: &str, table: Table) {
-        let key = name.to_uppercase();
-        if let Some(handle) = self.tables.get(&key) {
+        let key = self.resolve_table_name(name);
+        if let Some(handle) = self.state.tables.get(&key).map(|r| r.clone()) {
             *handle.write() = table;
+            self.record_change(CatalogObject::Table(key));
         }
     }
 
     pub fn create_function(&self, func: UserFunction, or_replace: bool) -> Result<()> {
-        let key = func.name.to_uppercase();
-        if self.functions.contains_key(&key) && !or_replace {
+        let key = match &self.session {
+            Some(session) if func.is_temporary => {
+                format!("{}.{}", session.temp_schema(), func.name.to_uppercase())
+            }
+            _ => func.name.to_uppercase(),
+        };
+        if self.state.functions.contains_key(&key) && !or_replace {
             return Err(Error::invalid_query(format!(
                 "Function already exists: {}",
                 func.name
             )));
         }
-        self.functions.insert(key, func);
+        self.state.functions.insert(key.clone(), func);
+        self.bump_definition_generation(&key);
+        if !is_session_table(&key) {
+            self.record_change(CatalogObject::Function(key));
+        }
         Ok(())
     }
 
     pub fn drop_function(&self, name: &str) -> Result<()> {
-        let key = name.to_uppercase();
-        if self.functions.remove(&key).is_none() {
+        let key = self.function_key(name);
+        if self.state.functions.remove(&key).is_none() {
             return Err(Error::invalid_query(format!(
                 "Function not found: {}",
                 name
             )));
         }
+        self.bump_definition_generation(&key);
+        if !is_session_table(&key) {
+            self.record_change(CatalogObject::Function(key));
+        }
         Ok(())
     }
 
     pub fn get_function(&self, name: &str) -> Option<UserFunction> {
-        self.functions.get(&name.to_uppercase()).map(|r| r.clone())
+        self.state
+            .functions
+            .get(&self.function_key(name))
+            .map(|r| r.clone())
     }
 
     pub fn function_exists(&self, name: &str) -> bool {
-        self.functions.contains_key(&name.to_uppercase())
+        self.state.functions.contains_key(&self.function_key(name))
+    }
+
+    /// Resolves a function name to its key, preferring the session's temp
+    /// functions over the shared ones like temp tables do.
+    fn function_key(&self, name: &str) -> String {
+        let key = name.to_uppercase();
+        if let Some(session) = &self.session {
+            let temp_key = format!("{}.{}", session.temp_schema(), key);
+            if self.state.functions.contains_key(&temp_key) {
+                return temp_key;
+            }
+        }
+        key
     }
 
     pub fn create_procedure(
@@ -556,7 +1855,7 @@ impl ConcurrentCatalog {
         if_not_exists: bool,
     ) -> Result<()> {
         let key = proc.name.to_uppercase();
-        if self.procedures.contains_key(&key) {
+        if self.state.procedures.contains_key(&key) {
             if if_not_exists {
                 return Ok(());
             }
@@ -567,45 +1866,70 @@ impl ConcurrentCatalog {
                 )));
             }
         }
-        self.procedures.insert(key, proc);
+        self.state.procedures.insert(key.clone(), proc);
+        self.record_change(CatalogObject::Procedure(key));
         Ok(())
     }
 
     pub fn drop_procedure(&self, name: &str) -> Result<()> {
         let key = name.to_uppercase();
-        if self.procedures.remove(&key).is_none() {
+        if self.state.procedures.remove(&key).is_none() {
             return Err(Error::invalid_query(format!(
                 "Procedure not found: {}",
                 name
             )));
         }
+        self.state.procedure_bodies.remove(&key);
+        self.record_change(CatalogObject::Procedure(key));
         Ok(())
     }
 
     pub fn get_procedure(&self, name: &str) -> Option<UserProcedure> {
-        self.procedures.get(&name.to_uppercase()).map(|r| r.clone())
+        self.state
+            .procedures
+            .get(&name.to_uppercase())
+            .map(|r| r.clone())
     }
 
     pub fn procedure_exists(&self, name: &str) -> bool {
-        self.procedures.contains_key(&name.to_uppercase())
+        self.state.procedures.contains_key(&name.to_uppercase())
     }
 
     pub fn set_procedure_body(&self, name: &str, body: Vec<PhysicalPlan>) {
         let key = name.to_uppercase();
-        self.procedure_bodies.insert(key, body);
+        self.state.procedure_bodies.insert(key.clone(), body);
+        self.record_change(CatalogObject::Procedure(key));
     }
 
     pub fn get_procedure_body(&self, name: &str) -> Option<Vec<PhysicalPlan>> {
-        self.procedure_bodies
+        self.state
+            .procedure_bodies
             .get(&name.to_uppercase())
             .map(|r| r.clone())
     }
 
+    /// The shared functions and this session's temp functions, keyed on
+    /// their names. Temp functions shadow shared ones of the same name.
     pub fn get_functions(&self) -> HashMap<String, UserFunction> {
-        self.functions
+        let prefix = self
+            .session
+            .as_ref()
+            .map(|session| format!("{}.", session.temp_schema()));
+        let mut functions: HashMap<String, UserFunction> = self
+            .state
+            .functions
             .iter()
+            .filter(|r| !is_session_table(r.key()))
             .map(|r| (r.key().clone(), r.value().clone()))
-            .collect()
+            .collect();
+        if let Some(prefix) = prefix {
+            for r in self.state.functions.iter() {
+                if let Some(name) = r.key().strip_prefix(&prefix) {
+                    functions.insert(name.to_string(), r.value().clone());
+                }
+            }
+        }
+        functions
     }
 
     pub fn create_view(
@@ -615,9 +1939,10 @@ impl ConcurrentCatalog {
         column_aliases: Vec<String>,
         or_replace: bool,
         if_not_exists: bool,
+        materialized: bool,
     ) -> Result<()> {
         let key = name.to_uppercase();
-        if self.views.contains_key(&key) {
+        if self.state.views.contains_key(&key) {
             if if_not_exists {
                 return Ok(());
             }
@@ -628,34 +1953,69 @@ impl ConcurrentCatalog {
                 )));
             }
         }
-        self.views.insert(
-            key,
+        self.state.views.insert(
+            key.clone(),
             ViewDef {
                 query,
                 column_aliases,
+                materialized,
             },
         );
+        self.bump_definition_generation(&key);
+        self.record_change(CatalogObject::View(key));
         Ok(())
     }
 
     pub fn drop_view(&self, name: &str, if_exists: bool) -> Result<()> {
         let key = name.to_uppercase();
-        if self.views.remove(&key).is_none() && !if_exists {
+        if self.state.views.remove(&key).is_none() && !if_exists {
             return Err(Error::invalid_query(format!("View not found: {}", name)));
         }
+        self.bump_definition_generation(&key);
+        self.record_change(CatalogObject::View(key));
         Ok(())
     }
 
+    /// Records that the view or function `key` was defined, replaced or
+    /// dropped.
+    fn bump_definition_generation(&self, key: &str) {
+        let generation = self
+            .state
+            .last_definition_generation
+            .fetch_add(1, Ordering::SeqCst)
+            + 1;
+        self.state
+            .definition_generations
+            .insert(object_key(key), generation);
+    }
+
+    /// The generation of the latest definition of any view or function
+    /// named `name`, or 0 if none was ever defined. It changes whenever one
+    /// is created, replaced or dropped.
+    pub(crate) fn definition_generation(&self, name: &str) -> u64 {
+        self.state
+            .definition_generations
+            .get(&object_key(name))
+            .map_or(0, |r| *r)
+    }
+
     pub fn get_view(&self, name: &str) -> Option<ViewDef> {
-        self.views.get(&name.to_uppercase()).map(|r| r.clone())
+        self.state
+            .views
+            .get(&name.to_uppercase())
+            .map(|r| r.clone())
     }
 
     pub fn view_exists(&self, name: &str) -> bool {
-        self.views.contains_key(&name.to_uppercase())
+        self.state.views.contains_key(&name.to_uppercase())
     }
 
     pub fn get_table_schema(&self, name: &str) -> Option<Schema> {
-        let handle = self.get_table_handle(name)?;
+        let Some(handle) = self.get_table_handle(name) else {
+            return self
+                .information_schema_table(name)
+                .map(|table| table.schema().clone());
+        };
         let table = handle.read();
         Some(table.schema().clone())
     }
@@ -667,6 +2027,34 @@ impl Default for ConcurrentCatalog {
     }
 }
 
+impl Drop for ConcurrentCatalog {
+    fn drop(&mut self) {
+        if let Some(session) = &self.session {
+            let prefix = format!("{}.", session.temp_schema());
+            self.state.tables.retain(|key, _| !key.starts_with(&prefix));
+            self.state
+                .table_defaults
+                .retain(|key, _| !key.starts_with(&prefix));
+            self.state
+                .table_constraints
+                .retain(|key, _| !key.starts_with(&prefix));
+            self.state
+                .table_options
+                .retain(|key, _| !key.starts_with(&prefix));
+            self.state
+                .table_indexes
+                .retain(|key, _| !key.starts_with(&prefix));
+            self.state
+                .functions
+                .retain(|key, _| !key.starts_with(&prefix));
+        }
+    }
+}
+
+fn is_session_table(key: &str) -> bool {
+    key.starts_with(SESSION_SCHEMA_PREFIX)
+}
+
 impl yachtsql_parser::CatalogProvider for ConcurrentCatalog {
     fn get_table_schema(&self, name: &str) -> Option<Schema> {
         self.get_table_schema(name)
@@ -691,3 +2079,17 @@ impl yachtsql_parser::CatalogProvider for ConcurrentCatalog {
             })
     }
 }
+
+impl yachtsql_optimizer::StatisticsProvider for ConcurrentCatalog {
+    fn row_count(&self, table_name: &str) -> Option<u64> {
+        let handle = self.get_table_handle(table_name)?;
+        let row_count = handle.read().row_count();
+        Some(row_count as u64)
+    }
+
+    fn column_statistics(&self, table_name: &str, column_name: &str) -> Option<ColumnStatistics> {
+        let key = self.resolve_table_name(table_name);
+        let stats = self.state.table_statistics.get(&key)?;
+        stats.column(column_name).cloned()
+    }
+}
@@ -1,11 +1,18 @@
 use std::collections::HashMap;
 use std::sync::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 use dashmap::DashMap;
 use yachtsql_common::types::Value;
 
+pub(crate) const SESSION_SCHEMA: &str = "_SESSION";
+pub(crate) const SESSION_SCHEMA_PREFIX: &str = "_SESSION_";
+
+static NEXT_SESSION_ID: AtomicU64 = AtomicU64::new(1);
+
 #[derive(Debug)]
 pub struct ConcurrentSession {
+    id: u64,
     variables: DashMap<String, Value>,
     system_variables: RwLock<HashMap<String, Value>>,
     current_schema: RwLock<Option<String>>,
@@ -17,12 +24,21 @@ impl ConcurrentSession {
         system_variables.insert("@@time_zone".to_string(), Value::String("UTC".to_string()));
 
         Self {
+            id: NEXT_SESSION_ID.fetch_add(1, Ordering::Relaxed),
             variables: DashMap::new(),
             system_variables: RwLock::new(system_variables),
             current_schema: RwLock::new(None),
         }
     }
 
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub(crate) fn temp_schema(&self) -> String {
+        format!("{}{}", SESSION_SCHEMA_PREFIX, self.id)
+    }
+
     pub fn get_variable(&self, name: &str) -> Option<Value> {
         self.variables.get(&name.to_uppercase()).map(|v| v.clone())
     }
@@ -854,6 +854,92 @@ impl Accumulator {
         Ok(())
     }
 
+    /// Whether partial states of `expr` built over separate inputs can be
+    /// combined with [`Accumulator::merge`].
+    pub(crate) fn supports_merge(expr: &Expr) -> bool {
+        get_agg_func(expr).is_some()
+            && !is_distinct_aggregate(expr)
+            && matches!(
+                Accumulator::from_expr(expr),
+                Accumulator::Count(_)
+                    | Accumulator::CountIf(_)
+                    | Accumulator::Sum(_)
+                    | Accumulator::Avg { .. }
+                    | Accumulator::Min(_)
+                    | Accumulator::Max(_)
+                    | Accumulator::First(_)
+                    | Accumulator::Last(_)
+                    | Accumulator::BitAnd(_)
+                    | Accumulator::BitOr(_)
+                    | Accumulator::BitXor(_)
+                    | Accumulator::LogicalAnd(_)
+                    | Accumulator::LogicalOr(_)
+                    | Accumulator::Variance { .. }
+                    | Accumulator::SumIf(_)
+                    | Accumulator::AvgIf { .. }
+                    | Accumulator::MinIf(_)
+                    | Accumulator::MaxIf(_)
+            )
+    }
+
+    /// Folds in the state of `other`, which accumulated rows that come after
+    /// the rows of `self`.
+    pub(crate) fn merge(&mut self, other: Accumulator) {
+        fn combine<T>(a: &mut Option<T>, b: Option<T>, f: impl FnOnce(T, T) -> T) {
+            *a = match (a.take(), b) {
+                (Some(a), Some(b)) => Some(f(a, b)),
+                (a, b) => a.or(b),
+            };
+        }
+
+        match (self, other) {
+            (Accumulator::Count(a), Accumulator::Count(b))
+            | (Accumulator::CountIf(a), Accumulator::CountIf(b)) => *a += b,
+            (Accumulator::Sum(a), Accumulator::Sum(b))
+            | (Accumulator::SumIf(a), Accumulator::SumIf(b)) => combine(a, b, |a, b| a + b),
+            (Accumulator::Avg { sum, count }, Accumulator::Avg { sum: s, count: c })
+            | (Accumulator::AvgIf { sum, count }, Accumulator::AvgIf { sum: s, count: c }) => {
+                *sum += s;
+                *count += c;
+            }
+            (Accumulator::Min(a), Accumulator::Min(b))
+            | (Accumulator::MinIf(a), Accumulator::MinIf(b)) => combine(a, b, std::cmp::min),
+            (Accumulator::Max(a), Accumulator::Max(b))
+            | (Accumulator::MaxIf(a), Accumulator::MaxIf(b)) => combine(a, b, std::cmp::max),
+            (Accumulator::First(a), Accumulator::First(b)) => combine(a, b, |a, _| a),
+            (Accumulator::Last(a), Accumulator::Last(b)) => combine(a, b, |_, b| b),
+            (Accumulator::BitAnd(a), Accumulator::BitAnd(b)) => combine(a, b, |a, b| a & b),
+            (Accumulator::BitOr(a), Accumulator::BitOr(b)) => combine(a, b, |a, b| a | b),
+            (Accumulator::BitXor(a), Accumulator::BitXor(b)) => combine(a, b, |a, b| a ^ b),
+            (Accumulator::LogicalAnd(a), Accumulator::LogicalAnd(b)) => {
+                combine(a, b, |a, b| a && b)
+            }
+            (Accumulator::LogicalOr(a), Accumulator::LogicalOr(b)) => combine(a, b, |a, b| a || b),
+            (
+                Accumulator::Variance {
+                    count, mean, m2, ..
+                },
+                Accumulator::Variance {
+                    count: n_b,
+                    mean: mean_b,
+                    m2: m2_b,
+                    ..
+                },
+            ) => {
+                if n_b == 0 {
+                    return;
+                }
+                let n_a = *count as f64;
+                let total = n_a + n_b as f64;
+                let delta = mean_b - *mean;
+                *mean += delta * n_b as f64 / total;
+                *m2 += m2_b + delta * delta * n_a * n_b as f64 / total;
+                *count += n_b;
+            }
+            _ => {}
+        }
+    }
+
     pub(crate) fn finalize(&self) -> Value {
         match self {
             Accumulator::Count(n) => Value::Int64(*n),
@@ -1,15 +1,20 @@
 mod accumulator;
 
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::collections::hash_map::Entry;
 
 use accumulator::Accumulator;
 use ordered_float::OrderedFloat;
-use yachtsql_common::error::Result;
+use yachtsql_common::error::{Error, Result};
 use yachtsql_common::types::Value;
 use yachtsql_ir::{AggregateFunction, Expr, PlanSchema};
-use yachtsql_storage::Table;
+use yachtsql_storage::{Column, Record, Table};
 
+use super::memory::{MemoryBudget, MemoryTracker, SpillFile, partition_of, row_size};
+use super::morsel::{row_ranges, run_morsels};
 use super::{PlanExecutor, plan_schema_to_schema};
+use crate::cancel::Interrupt;
 use crate::ir_evaluator::IrEvaluator;
 use crate::plan::PhysicalPlan;
 
@@ -21,6 +26,9 @@ pub fn compute_aggregate(
     grouping_sets: Option<&Vec<Vec<usize>>>,
     variables: &HashMap<String, Value>,
     user_function_defs: &HashMap<String, crate::ir_evaluator::UserFunctionDef>,
+    threads: usize,
+    budget: &MemoryBudget,
+    interrupt: &Interrupt,
 ) -> Result<Table> {
     if can_use_columnar_aggregate(aggregates, group_by, grouping_sets) {
         return execute_columnar_aggregate(input_table, aggregates, schema);
@@ -34,50 +42,49 @@ pub fn compute_aggregate(
     let result_schema = plan_schema_to_schema(schema);
     let mut result = Table::empty(result_schema);
 
+    let threads = if aggregates.iter().all(Accumulator::supports_merge) {
+        threads
+    } else {
+        1
+    };
+
     if group_by.is_empty() {
-        let mut accumulators: Vec<Accumulator> =
-            aggregates.iter().map(Accumulator::from_expr).collect();
-
-        for record in input_table.rows()? {
-            for (acc, agg_expr) in accumulators.iter_mut().zip(aggregates.iter()) {
-                if matches!(
-                    acc,
-                    Accumulator::SumIf(_)
-                        | Accumulator::AvgIf { .. }
-                        | Accumulator::MinIf(_)
-                        | Accumulator::MaxIf(_)
-                ) {
-                    let (value, condition) =
-                        extract_conditional_agg_args(&evaluator, agg_expr, &record)?;
-                    acc.accumulate_conditional(&value, condition)?;
-                } else if matches!(acc, Accumulator::ArrayAgg { .. }) {
-                    let arg_val = extract_agg_arg(&evaluator, agg_expr, &record)?;
-                    let sort_keys = extract_order_by_keys(&evaluator, agg_expr, &record)?;
-                    acc.accumulate_array_agg(&arg_val, sort_keys)?;
-                } else if matches!(acc, Accumulator::Covariance { .. }) {
-                    let (x, y) = extract_bivariate_args(&evaluator, agg_expr, &record)?;
-                    acc.accumulate_bivariate(&x, &y)?;
-                } else if matches!(acc, Accumulator::ApproxTopSum { .. }) {
-                    let (value, weight) = extract_bivariate_args(&evaluator, agg_expr, &record)?;
-                    acc.accumulate_approx_top_sum(&value, &weight)?;
-                } else {
-                    let arg_val = extract_agg_arg(&evaluator, agg_expr, &record)?;
-                    acc.accumulate(&arg_val)?;
-                }
+        let memory = budget.tracker("Aggregate");
+        let rows = input_table.rows()?;
+        memory.add_rows(&rows)?;
+        let ranges = row_ranges(rows.len(), threads);
+        let partials = run_morsels(threads, ranges.len(), interrupt, |i| {
+            let mut accumulators: Vec<Accumulator> =
+                aggregates.iter().map(Accumulator::from_expr).collect();
+            for (n, record) in rows[ranges[i].clone()].iter().enumerate() {
+                interrupt.check_row(n)?;
+                accumulate_record(&mut accumulators, aggregates, &evaluator, record)?;
+            }
+            Ok(accumulators)
+        })?;
+
+        let mut partials = partials.into_iter();
+        let mut accumulators = partials.next().unwrap_or_default();
+        for partial in partials {
+            for (acc, other) in accumulators.iter_mut().zip(partial) {
+                acc.merge(other);
             }
         }
 
         let row: Vec<Value> = accumulators.iter().map(|a| a.finalize()).collect();
         result.push_row(row)?;
     } else if let Some(sets) = grouping_sets {
+        let memory = budget.tracker("HashAggregate");
         let rows = input_table.rows()?;
+        memory.add_rows(&rows)?;
 
         for grouping_set in sets {
             let active_indices: Vec<usize> = grouping_set.clone();
             let mut group_map: HashMap<String, (Vec<Value>, Vec<Accumulator>, Vec<usize>)> =
                 HashMap::new();
 
-            for record in &rows {
+            for (n, record) in rows.iter().enumerate() {
+                interrupt.check_row(n)?;
                 let mut group_key_values = Vec::new();
                 for (i, group_expr) in group_by.iter().enumerate() {
                     if active_indices.contains(&i) {
@@ -152,64 +159,167 @@ pub fn compute_aggregate(
             }
         }
     } else {
-        let mut groups: HashMap<Vec<String>, Vec<Accumulator>> = HashMap::new();
-        let mut group_keys: HashMap<Vec<String>, Vec<Value>> = HashMap::new();
-
-        for record in input_table.rows()? {
-            let group_key_values: Vec<Value> = group_by
-                .iter()
-                .map(|e| evaluator.evaluate(e, &record))
-                .collect::<Result<_>>()?;
-            let group_key_strings: Vec<String> = group_key_values
-                .iter()
-                .map(|v| format!("{:?}", v))
-                .collect();
-
-            let accumulators = groups
-                .entry(group_key_strings.clone())
-                .or_insert_with(|| aggregates.iter().map(Accumulator::from_expr).collect());
-            group_keys
-                .entry(group_key_strings.clone())
-                .or_insert(group_key_values);
-
-            for (acc, agg_expr) in accumulators.iter_mut().zip(aggregates.iter()) {
-                if matches!(
-                    acc,
-                    Accumulator::SumIf(_)
-                        | Accumulator::AvgIf { .. }
-                        | Accumulator::MinIf(_)
-                        | Accumulator::MaxIf(_)
-                ) {
-                    let (value, condition) =
-                        extract_conditional_agg_args(&evaluator, agg_expr, &record)?;
-                    acc.accumulate_conditional(&value, condition)?;
-                } else if matches!(acc, Accumulator::ArrayAgg { .. }) {
-                    let arg_val = extract_agg_arg(&evaluator, agg_expr, &record)?;
-                    let sort_keys = extract_order_by_keys(&evaluator, agg_expr, &record)?;
-                    acc.accumulate_array_agg(&arg_val, sort_keys)?;
-                } else if matches!(acc, Accumulator::Covariance { .. }) {
-                    let (x, y) = extract_bivariate_args(&evaluator, agg_expr, &record)?;
-                    acc.accumulate_bivariate(&x, &y)?;
-                } else if matches!(acc, Accumulator::ApproxTopSum { .. }) {
-                    let (value, weight) = extract_bivariate_args(&evaluator, agg_expr, &record)?;
-                    acc.accumulate_approx_top_sum(&value, &weight)?;
-                } else {
-                    let arg_val = extract_agg_arg(&evaluator, agg_expr, &record)?;
-                    acc.accumulate(&arg_val)?;
+        let memory = budget.tracker("HashAggregate");
+        let rows = input_table.rows()?;
+        let ranges = row_ranges(rows.len(), threads);
+        let partials = memory.add_rows(&rows).and_then(|()| {
+            run_morsels(threads, ranges.len(), interrupt, |i| {
+                aggregate_groups(
+                    &rows[ranges[i].clone()],
+                    group_by,
+                    aggregates,
+                    &evaluator,
+                    &memory,
+                    interrupt,
+                )
+            })
+        });
+        let partials = match (partials, budget.spill_dir()) {
+            (Err(Error::ResourcesExceeded(_)), Some(dir)) => {
+                drop((rows, memory));
+                let entry_size = input_table.schema().field_count() * std::mem::size_of::<Value>()
+                    + group_by.len() * std::mem::size_of::<Value>()
+                    + aggregates.len() * std::mem::size_of::<Accumulator>();
+                let partitions = budget.spill_partitions(
+                    input_table.memory_size() + input_table.row_count() * entry_size,
+                );
+                let mut files = (0..partitions)
+                    .map(|_| SpillFile::create(dir))
+                    .collect::<Result<Vec<_>>>()?;
+                for n in 0..input_table.row_count() {
+                    interrupt.check_row(n)?;
+                    let record = input_table.get_row(n)?;
+                    let key = group_key(group_by, &evaluator, &record)?.1;
+                    files[partition_of(&key, partitions)].push(record.values())?;
+                }
+                for file in &mut files {
+                    let memory = budget.tracker("HashAggregate");
+                    let records = file
+                        .read()?
+                        .map(|values| values.map(Record::from_values))
+                        .collect::<Result<Vec<_>>>()?;
+                    memory.add_rows(&records)?;
+                    let groups = aggregate_groups(
+                        &records, group_by, aggregates, &evaluator, &memory, interrupt,
+                    )?;
+                    push_groups(&mut result, groups)?;
                 }
+                return Ok(result);
             }
-        }
+            (partials, _) => partials?,
+        };
 
-        for (key_strings, accumulators) in groups {
-            let mut row = group_keys.get(&key_strings).unwrap().clone();
-            row.extend(accumulators.iter().map(|a| a.finalize()));
-            result.push_row(row)?;
+        let mut partials = partials.into_iter();
+        let mut groups = partials.next().unwrap_or_default();
+        for partial in partials {
+            for (key, (values, accumulators)) in partial {
+                match groups.get_mut(&key) {
+                    Some((_, merged)) => {
+                        for (acc, other) in merged.iter_mut().zip(accumulators) {
+                            acc.merge(other);
+                        }
+                    }
+                    None => {
+                        groups.insert(key, (values, accumulators));
+                    }
+                }
+            }
         }
+        push_groups(&mut result, groups)?;
     }
 
     Ok(result)
 }
 
+/// Group key values and accumulators, keyed by the rendered group key.
+type GroupStates = HashMap<Vec<String>, (Vec<Value>, Vec<Accumulator>)>;
+
+fn aggregate_groups(
+    rows: &[Record],
+    group_by: &[Expr],
+    aggregates: &[Expr],
+    evaluator: &IrEvaluator,
+    memory: &MemoryTracker,
+    interrupt: &Interrupt,
+) -> Result<GroupStates> {
+    let mut groups = GroupStates::new();
+    for (n, record) in rows.iter().enumerate() {
+        interrupt.check_row(n)?;
+        let (group_key_values, group_key_strings) = group_key(group_by, evaluator, record)?;
+        let (_, accumulators) = match groups.entry(group_key_strings) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                memory.add(
+                    row_size(&group_key_values)
+                        + aggregates.len() * std::mem::size_of::<Accumulator>(),
+                )?;
+                entry.insert((
+                    group_key_values,
+                    aggregates.iter().map(Accumulator::from_expr).collect(),
+                ))
+            }
+        };
+        accumulate_record(accumulators, aggregates, evaluator, record)?;
+    }
+    Ok(groups)
+}
+
+fn group_key(
+    group_by: &[Expr],
+    evaluator: &IrEvaluator,
+    record: &Record,
+) -> Result<(Vec<Value>, Vec<String>)> {
+    let values: Vec<Value> = group_by
+        .iter()
+        .map(|e| evaluator.evaluate(e, record))
+        .collect::<Result<_>>()?;
+    let strings = values.iter().map(|v| format!("{:?}", v)).collect();
+    Ok((values, strings))
+}
+
+fn push_groups(result: &mut Table, groups: GroupStates) -> Result<()> {
+    for (group_key, accumulators) in groups.into_values() {
+        let mut row = group_key;
+        row.extend(accumulators.iter().map(|a| a.finalize()));
+        result.push_row(row)?;
+    }
+    Ok(())
+}
+
+fn accumulate_record(
+    accumulators: &mut [Accumulator],
+    aggregates: &[Expr],
+    evaluator: &IrEvaluator,
+    record: &Record,
+) -> Result<()> {
+    for (acc, agg_expr) in accumulators.iter_mut().zip(aggregates.iter()) {
+        if matches!(
+            acc,
+            Accumulator::SumIf(_)
+                | Accumulator::AvgIf { .. }
+                | Accumulator::MinIf(_)
+                | Accumulator::MaxIf(_)
+        ) {
+            let (value, condition) = extract_conditional_agg_args(evaluator, agg_expr, record)?;
+            acc.accumulate_conditional(&value, condition)?;
+        } else if matches!(acc, Accumulator::ArrayAgg { .. }) {
+            let arg_val = extract_agg_arg(evaluator, agg_expr, record)?;
+            let sort_keys = extract_order_by_keys(evaluator, agg_expr, record)?;
+            acc.accumulate_array_agg(&arg_val, sort_keys)?;
+        } else if matches!(acc, Accumulator::Covariance { .. }) {
+            let (x, y) = extract_bivariate_args(evaluator, agg_expr, record)?;
+            acc.accumulate_bivariate(&x, &y)?;
+        } else if matches!(acc, Accumulator::ApproxTopSum { .. }) {
+            let (value, weight) = extract_bivariate_args(evaluator, agg_expr, record)?;
+            acc.accumulate_approx_top_sum(&value, &weight)?;
+        } else {
+            let arg_val = extract_agg_arg(evaluator, agg_expr, record)?;
+            acc.accumulate(&arg_val)?;
+        }
+    }
+    Ok(())
+}
+
 #[allow(clippy::wildcard_enum_match_arm)]
 fn get_simple_column_index(expr: &Expr) -> Option<usize> {
     match expr {
@@ -221,6 +331,15 @@ fn get_simple_column_index(expr: &Expr) -> Option<usize> {
 }
 
 #[allow(clippy::wildcard_enum_match_arm)]
+/// Sum of the non-null values across column parts, `None` when every value
+/// is null.
+fn sum_parts(parts: &[Cow<'_, Column>]) -> Option<f64> {
+    parts
+        .iter()
+        .filter_map(|part| part.sum())
+        .reduce(|a, b| a + b)
+}
+
 fn can_use_columnar_aggregate(
     aggregates: &[Expr],
     group_by: &[Expr],
@@ -295,37 +414,44 @@ fn execute_columnar_aggregate(
                     Value::Int64(input_table.row_count() as i64)
                 } else {
                     let col_idx = get_simple_column_index(&args[0]).unwrap();
-                    let column = input_table.column(col_idx).unwrap();
-                    Value::Int64(column.count_valid() as i64)
+                    let parts = input_table.column_parts(col_idx).unwrap();
+                    Value::Int64(parts.iter().map(|p| p.count_valid()).sum::<usize>() as i64)
                 }
             }
             AggregateFunction::Sum => {
                 let col_idx = get_simple_column_index(&args[0]).unwrap();
-                let column = input_table.column(col_idx).unwrap();
-                column.sum().map(Value::float64).unwrap_or(Value::Null)
+                let parts = input_table.column_parts(col_idx).unwrap();
+                sum_parts(&parts).map(Value::float64).unwrap_or(Value::Null)
             }
             AggregateFunction::Avg => {
                 let col_idx = get_simple_column_index(&args[0]).unwrap();
-                let column = input_table.column(col_idx).unwrap();
-                let count = column.count_valid();
+                let parts = input_table.column_parts(col_idx).unwrap();
+                let count: usize = parts.iter().map(|p| p.count_valid()).sum();
                 if count == 0 {
                     Value::Null
                 } else {
-                    column
-                        .sum()
+                    sum_parts(&parts)
                         .map(|s| Value::float64(s / count as f64))
                         .unwrap_or(Value::Null)
                 }
             }
             AggregateFunction::Min => {
                 let col_idx = get_simple_column_index(&args[0]).unwrap();
-                let column = input_table.column(col_idx).unwrap();
-                column.min().unwrap_or(Value::Null)
+                let parts = input_table.column_parts(col_idx).unwrap();
+                parts
+                    .iter()
+                    .filter_map(|p| p.min())
+                    .min()
+                    .unwrap_or(Value::Null)
             }
             AggregateFunction::Max => {
                 let col_idx = get_simple_column_index(&args[0]).unwrap();
-                let column = input_table.column(col_idx).unwrap();
-                column.max().unwrap_or(Value::Null)
+                let parts = input_table.column_parts(col_idx).unwrap();
+                parts
+                    .iter()
+                    .filter_map(|p| p.max())
+                    .max()
+                    .unwrap_or(Value::Null)
             }
             _ => unreachable!(),
         };
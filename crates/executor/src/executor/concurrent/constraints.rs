@@ -0,0 +1,394 @@
+use std::collections::HashSet;
+
+use yachtsql_common::error::{Error, Result};
+use yachtsql_common::types::Value;
+use yachtsql_ir::{ConstraintType, Expr, TableConstraint};
+use yachtsql_storage::Table;
+
+use super::ConcurrentPlanExecutor;
+use crate::ir_evaluator::IrEvaluator;
+
+impl ConcurrentPlanExecutor<'_> {
+    /// Strict mode, set per session with `SET @@yachtsql.enforce_constraints = TRUE`.
+    /// Otherwise constraints are informational only, as in BigQuery.
+    pub(crate) fn constraints_enforced(&self) -> bool {
+        self.session_setting("@@yachtsql.enforce_constraints")
+            .as_ref()
+            .and_then(Value::as_bool)
+            .unwrap_or(false)
+    }
+
+    /// The table `table_name` as a statement writing to it finds it, for
+    /// `enforce_constraints` to compare its writes with, or `None` when
+    /// constraints are not enforced.
+    pub(crate) fn constraint_baseline(&self, table_name: &str) -> Option<Table> {
+        if !self.constraints_enforced() {
+            return None;
+        }
+        self.statement_table(table_name).ok()
+    }
+
+    /// Validates the current statement's writes to `table_name`, given the
+    /// table as the statement found it in `before`. Rows in segments the
+    /// statement replaced are checked against the constraints declared on
+    /// the table, and keys it removed against the FOREIGN KEY constraints
+    /// referencing it; rows in segments it left shared were already valid.
+    /// The statement's writes are discarded on error, so a violating INSERT,
+    /// UPDATE, DELETE, MERGE or LOAD has no effect.
+    pub(crate) fn enforce_constraints(
+        &self,
+        table_name: &str,
+        before: Option<&Table>,
+    ) -> Result<()> {
+        if !self.constraints_enforced() {
+            return Ok(());
+        }
+        let constraints = self.catalog.get_table_constraints(table_name);
+        let referencing = self.catalog.referencing_constraints(table_name);
+        if constraints.is_empty() && referencing.is_empty() {
+            return Ok(());
+        }
+        let table = self
+            .tables
+            .get_table(table_name)
+            .ok_or_else(|| Error::TableNotFound(table_name.to_string()))?;
+        let written = match before {
+            Some(before) => table.unshared_with(before),
+            None => table.clone(),
+        };
+        let kept = table.unshared_with(&written);
+        self.check_written_rows(table_name, &written, &kept, &constraints)?;
+        if let Some(before) = before {
+            let removed = before.unshared_with(&table);
+            self.check_removed_keys(table_name, &removed, &written, &kept, &referencing)?;
+        }
+        Ok(())
+    }
+
+    /// Validates every row of `table` against `constraints`.
+    pub(crate) fn check_constraints(
+        &self,
+        table_name: &str,
+        table: &Table,
+        constraints: &[TableConstraint],
+    ) -> Result<()> {
+        let kept = Table::empty(table.schema().clone());
+        self.check_written_rows(table_name, table, &kept, constraints)
+    }
+
+    /// Validates the rows in `written` against `constraints`, with the rows
+    /// in `kept` making up the rest of the table.
+    fn check_written_rows(
+        &self,
+        table_name: &str,
+        written: &Table,
+        kept: &Table,
+        constraints: &[TableConstraint],
+    ) -> Result<()> {
+        if written.row_count() == 0 {
+            return Ok(());
+        }
+        for constraint in constraints {
+            match &constraint.constraint_type {
+                ConstraintType::PrimaryKey { columns } => {
+                    check_unique(table_name, written, kept, constraint, columns, true)?
+                }
+                ConstraintType::Unique { columns } => {
+                    check_unique(table_name, written, kept, constraint, columns, false)?
+                }
+                ConstraintType::Check { expr } => {
+                    self.check_expr(table_name, written, constraint, expr)?
+                }
+                ConstraintType::ForeignKey {
+                    columns,
+                    references_table,
+                    references_columns,
+                } => self.check_foreign_key(
+                    table_name,
+                    written,
+                    constraint,
+                    columns,
+                    references_table,
+                    references_columns,
+                )?,
+            }
+        }
+        Ok(())
+    }
+
+    fn check_expr(
+        &self,
+        table_name: &str,
+        table: &Table,
+        constraint: &TableConstraint,
+        expr: &Expr,
+    ) -> Result<()> {
+        let udf = self.get_user_functions();
+        let evaluator = IrEvaluator::new(table.schema()).with_user_functions(&udf);
+        for record in table.rows()? {
+            if evaluator.evaluate(expr, &record)? == Value::Bool(false) {
+                return Err(Error::invalid_query(format!(
+                    "{} violated by row ({})",
+                    describe(table_name, constraint),
+                    format_values(record.values())
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn check_foreign_key(
+        &self,
+        table_name: &str,
+        table: &Table,
+        constraint: &TableConstraint,
+        columns: &[String],
+        references_table: &str,
+        references_columns: &[String],
+    ) -> Result<()> {
+        let references_columns = self.referenced_columns(
+            table_name,
+            constraint,
+            columns,
+            references_table,
+            references_columns,
+        )?;
+        let keys: Vec<Vec<Value>> = key_rows(table, columns)?
+            .into_iter()
+            .filter(|key| !key.iter().any(Value::is_null))
+            .collect();
+        let mut missing: HashSet<&Vec<Value>> = keys.iter().collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        let referenced = self.statement_table(references_table)?;
+        for key in key_rows(&referenced, &references_columns)? {
+            missing.remove(&key);
+            if missing.is_empty() {
+                return Ok(());
+            }
+        }
+        let key = keys
+            .iter()
+            .find(|key| missing.contains(key))
+            .expect("a missing key is one of the keys");
+        Err(Error::invalid_query(format!(
+            "{} violated: key ({}) not found in {}({})",
+            describe(table_name, constraint),
+            format_values(key),
+            references_table,
+            references_columns.join(", ")
+        )))
+    }
+
+    /// Checks that no table still references a key `table_name` lost: a key
+    /// of the `removed` rows found in neither the `written` nor the `kept`
+    /// rows.
+    fn check_removed_keys(
+        &self,
+        table_name: &str,
+        removed: &Table,
+        written: &Table,
+        kept: &Table,
+        referencing: &[(String, TableConstraint)],
+    ) -> Result<()> {
+        if removed.row_count() == 0 {
+            return Ok(());
+        }
+        for (child_name, constraint) in referencing {
+            let ConstraintType::ForeignKey {
+                columns,
+                references_table,
+                references_columns,
+            } = &constraint.constraint_type
+            else {
+                continue;
+            };
+            let references_columns = self.referenced_columns(
+                child_name,
+                constraint,
+                columns,
+                references_table,
+                references_columns,
+            )?;
+            let mut lost: HashSet<Vec<Value>> = key_rows(removed, &references_columns)?
+                .into_iter()
+                .filter(|key| !key.iter().any(Value::is_null))
+                .collect();
+            for key in key_rows(written, &references_columns)? {
+                lost.remove(&key);
+            }
+            if lost.is_empty() {
+                continue;
+            }
+            for key in key_rows(kept, &references_columns)? {
+                lost.remove(&key);
+            }
+            if lost.is_empty() {
+                continue;
+            }
+            let child = self.statement_table(child_name)?;
+            if let Some(key) = key_rows(&child, columns)?
+                .into_iter()
+                .find(|key| lost.contains(key))
+            {
+                return Err(Error::invalid_query(format!(
+                    "{} violated: key ({}) removed from {} is still referenced",
+                    describe(child_name, constraint),
+                    format_values(&key),
+                    table_name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// The columns a FOREIGN KEY constraint on `table_name` references,
+    /// defaulting to the primary key of the referenced table.
+    fn referenced_columns(
+        &self,
+        table_name: &str,
+        constraint: &TableConstraint,
+        columns: &[String],
+        references_table: &str,
+        references_columns: &[String],
+    ) -> Result<Vec<String>> {
+        let references_columns = if references_columns.is_empty() {
+            self.catalog
+                .get_table_constraints(references_table)
+                .into_iter()
+                .find_map(|c| match c.constraint_type {
+                    ConstraintType::PrimaryKey { columns } => Some(columns),
+                    _ => None,
+                })
+                .ok_or_else(|| {
+                    Error::invalid_query(format!(
+                        "{} references {}, which has no primary key",
+                        describe(table_name, constraint),
+                        references_table
+                    ))
+                })?
+        } else {
+            references_columns.to_vec()
+        };
+        if references_columns.len() != columns.len() {
+            return Err(Error::invalid_query(format!(
+                "{} has {} columns but references {} columns",
+                describe(table_name, constraint),
+                columns.len(),
+                references_columns.len()
+            )));
+        }
+        Ok(references_columns)
+    }
+
+    /// The table `name` as the current statement sees it, including its own
+    /// writes.
+    fn statement_table(&self, name: &str) -> Result<Table> {
+        match self.tables.get_table(name) {
+            Some(table) => Ok(table),
+            None => self
+                .catalog
+                .try_read_table(name)?
+                .ok_or_else(|| Error::TableNotFound(name.to_string())),
+        }
+    }
+}
+
+/// Checks the keys of the `written` rows are unique among themselves and
+/// among the `kept` rows of the table.
+fn check_unique(
+    table_name: &str,
+    written: &Table,
+    kept: &Table,
+    constraint: &TableConstraint,
+    columns: &[String],
+    primary: bool,
+) -> Result<()> {
+    let duplicate = |key: &[Value]| {
+        Error::invalid_query(format!(
+            "{} violated: duplicate key ({})",
+            describe(table_name, constraint),
+            format_values(key)
+        ))
+    };
+    let mut seen: HashSet<Vec<Value>> = HashSet::new();
+    for key in key_rows(written, columns)? {
+        if let Some(pos) = key.iter().position(Value::is_null) {
+            if primary {
+                return Err(Error::invalid_query(format!(
+                    "{} violated: column {} cannot be NULL",
+                    describe(table_name, constraint),
+                    columns[pos]
+                )));
+            }
+            continue;
+        }
+        if seen.contains(&key) {
+            return Err(duplicate(&key));
+        }
+        seen.insert(key);
+    }
+    if seen.is_empty() {
+        return Ok(());
+    }
+    for key in key_rows(kept, columns)? {
+        if seen.contains(&key) {
+            return Err(duplicate(&key));
+        }
+    }
+    Ok(())
+}
+
+/// The values of `columns` in each live row of `table`, read from the key
+/// columns alone.
+fn key_rows(table: &Table, columns: &[String]) -> Result<Vec<Vec<Value>>> {
+    let parts = columns
+        .iter()
+        .map(|col| {
+            table
+                .schema()
+                .fields()
+                .iter()
+                .position(|f| f.name.eq_ignore_ascii_case(col))
+                .and_then(|idx| table.column_parts(idx))
+                .ok_or_else(|| Error::ColumnNotFound(col.clone()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let mut keys = Vec::with_capacity(table.row_count());
+    let Some(first) = parts.first() else {
+        return Ok(keys);
+    };
+    for (segment, part) in first.iter().enumerate() {
+        for row in 0..part.len() {
+            keys.push(
+                parts
+                    .iter()
+                    .map(|column| column[segment].get_value(row))
+                    .collect(),
+            );
+        }
+    }
+    Ok(keys)
+}
+
+fn describe(table_name: &str, constraint: &TableConstraint) -> String {
+    let (kind, target) = match &constraint.constraint_type {
+        ConstraintType::PrimaryKey { columns } => ("PRIMARY KEY", columns.join(", ")),
+        ConstraintType::Unique { columns } => ("UNIQUE", columns.join(", ")),
+        ConstraintType::ForeignKey { columns, .. } => ("FOREIGN KEY", columns.join(", ")),
+        ConstraintType::Check { expr } => ("CHECK", expr.to_string()),
+    };
+    match &constraint.name {
+        Some(name) => format!("{} constraint {} on {}", kind, name, table_name),
+        None => format!("{} ({}) constraint on {}", kind, target, table_name),
+    }
+}
+
+fn format_values(values: &[Value]) -> String {
+    values
+        .iter()
+        .map(Value::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
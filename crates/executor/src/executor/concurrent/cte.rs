@@ -94,6 +94,7 @@ impl ConcurrentPlanExecutor<'_> {
 
         while !working_set.is_empty() && iteration < MAX_RECURSION_DEPTH {
             iteration += 1;
+            self.interrupt.check()?;
 
             self.cte_results
                 .write()
@@ -2,11 +2,15 @@ use std::collections::HashMap;
 
 use yachtsql_common::error::{Error, Result};
 use yachtsql_common::types::DataType;
-use yachtsql_ir::{AlterTableOp, ColumnDef, FunctionArg, FunctionBody, ProcedureArg};
-use yachtsql_storage::{Field, FieldMode, Record, Schema, Table};
+use yachtsql_ir::{
+    AlterTableOp, ColumnDef, Expr, FunctionArg, FunctionBody, IndexKind, ProcedureArg,
+    TableConstraint,
+};
+use yachtsql_storage::{Field, FieldMode, Record, Schema, Table, TableStatistics};
 
 use super::ConcurrentPlanExecutor;
-use crate::catalog::{ColumnDefault, UserFunction, UserProcedure};
+use crate::catalog::{ColumnDefault, TableIndex, UserFunction, UserProcedure};
+use crate::concurrent_catalog::TableSnapshotInfo;
 use crate::ir_evaluator::IrEvaluator;
 use crate::plan::PhysicalPlan;
 
@@ -20,10 +24,22 @@ impl ConcurrentPlanExecutor<'_> {
         Ok(Table::empty(Schema::new()))
     }
 
+    pub(crate) fn execute_analyze(&self, table_name: &str) -> Result<Table> {
+        let table = self
+            .tables
+            .get_table(table_name)
+            .ok_or_else(|| Error::TableNotFound(table_name.to_string()))?;
+        self.catalog
+            .set_table_statistics(table_name, TableStatistics::collect(&table));
+        Ok(Table::empty(Schema::new()))
+    }
+
     pub(crate) async fn execute_create_table(
         &self,
         table_name: &str,
         columns: &[ColumnDef],
+        constraints: &[TableConstraint],
+        options: &[(String, String)],
         if_not_exists: bool,
         or_replace: bool,
         query: Option<&PhysicalPlan>,
@@ -58,6 +74,9 @@ impl ConcurrentPlanExecutor<'_> {
             } else {
                 self.catalog.insert_table(table_name, result)?;
             }
+            self.catalog
+                .set_table_constraints(table_name, constraints.to_vec());
+            self.catalog.set_table_options(table_name, options);
             return Ok(Table::empty(schema));
         }
 
@@ -103,6 +122,9 @@ impl ConcurrentPlanExecutor<'_> {
         if !defaults.is_empty() {
             self.catalog.set_table_defaults(table_name, defaults);
         }
+        self.catalog
+            .set_table_constraints(table_name, constraints.to_vec());
+        self.catalog.set_table_options(table_name, options);
 
         Ok(Table::empty(Schema::new()))
     }
@@ -265,10 +287,26 @@ impl ConcurrentPlanExecutor<'_> {
                     }
                 }
             }
-            AlterTableOp::SetOptions { options: _ } => {}
-            AlterTableOp::AddConstraint { constraint: _ } => {}
-            AlterTableOp::DropConstraint { name: _ } => {}
-            AlterTableOp::DropPrimaryKey => {}
+            AlterTableOp::SetOptions { options } => {
+                self.catalog.set_table_options(table_name, options);
+            }
+            AlterTableOp::AddConstraint { constraint } => {
+                if self.constraints_enforced() {
+                    let table = self
+                        .tables
+                        .get_table(table_name)
+                        .ok_or_else(|| Error::TableNotFound(table_name.to_string()))?;
+                    self.check_constraints(table_name, &table, std::slice::from_ref(constraint))?;
+                }
+                self.catalog
+                    .add_table_constraint(table_name, constraint.clone())?;
+            }
+            AlterTableOp::DropConstraint { name } => {
+                self.catalog.drop_table_constraint(table_name, name)?;
+            }
+            AlterTableOp::DropPrimaryKey => {
+                self.catalog.drop_primary_key(table_name)?;
+            }
         }
         Ok(Table::empty(Schema::new()))
     }
@@ -280,6 +318,7 @@ impl ConcurrentPlanExecutor<'_> {
         column_aliases: &[String],
         or_replace: bool,
         if_not_exists: bool,
+        materialized: bool,
     ) -> Result<Table> {
         self.catalog.create_view(
             name,
@@ -287,6 +326,7 @@ impl ConcurrentPlanExecutor<'_> {
             column_aliases.to_vec(),
             or_replace,
             if_not_exists,
+            materialized,
         )?;
         Ok(Table::empty(Schema::new()))
     }
@@ -301,11 +341,14 @@ impl ConcurrentPlanExecutor<'_> {
         name: &str,
         if_not_exists: bool,
         or_replace: bool,
+        options: &[(String, String)],
     ) -> Result<Table> {
         if or_replace && self.catalog.schema_exists(name) {
             self.catalog.drop_schema(name, true, true)?;
         }
-        self.catalog.create_schema(name, if_not_exists)?;
+        let options: HashMap<String, String> = options.iter().cloned().collect();
+        self.catalog
+            .create_schema_with_options(name, if_not_exists, options)?;
         Ok(Table::empty(Schema::new()))
     }
 
@@ -329,6 +372,17 @@ impl ConcurrentPlanExecutor<'_> {
         name: &str,
         options: &[(String, String)],
     ) -> Result<Table> {
+        for (key, value) in options {
+            if key.eq_ignore_ascii_case("max_time_travel_hours") {
+                let hours = value.trim_matches('\'').parse::<i64>().ok();
+                if !hours.is_some_and(|h| (48..=168).contains(&h) && h % 24 == 0) {
+                    return Err(Error::invalid_query(format!(
+                        "Invalid value for max_time_travel_hours: {}. Allowed values are multiples of 24 between 48 and 168",
+                        value
+                    )));
+                }
+            }
+        }
         let opts: HashMap<String, String> = options.iter().cloned().collect();
         self.catalog.alter_schema_options(name, opts)?;
         Ok(Table::empty(Schema::new()))
@@ -344,6 +398,7 @@ impl ConcurrentPlanExecutor<'_> {
         if_not_exists: bool,
         is_temp: bool,
         is_aggregate: bool,
+        options: &[(String, String)],
     ) -> Result<Table> {
         if self.catalog.function_exists(name) && !or_replace {
             if if_not_exists {
@@ -362,6 +417,7 @@ impl ConcurrentPlanExecutor<'_> {
             body: body.clone(),
             is_temporary: is_temp,
             is_aggregate,
+            options: options.to_vec(),
         };
         self.catalog.create_function(func, or_replace)?;
         self.refresh_user_functions();
@@ -382,6 +438,62 @@ impl ConcurrentPlanExecutor<'_> {
         Ok(Table::empty(Schema::new()))
     }
 
+    pub(crate) fn execute_create_index(
+        &self,
+        name: &str,
+        table_name: &str,
+        kind: IndexKind,
+        columns: &[String],
+        options: &[(String, String)],
+        or_replace: bool,
+        if_not_exists: bool,
+    ) -> Result<Table> {
+        let handle = self
+            .catalog
+            .get_table_handle(table_name)
+            .ok_or_else(|| Error::TableNotFound(table_name.to_string()))?;
+        let schema = handle.read().schema().clone();
+        let mut resolved = Vec::with_capacity(columns.len());
+        for column in columns {
+            if kind == IndexKind::Search && column == "ALL COLUMNS" {
+                resolved.extend(
+                    schema
+                        .fields()
+                        .iter()
+                        .filter(|f| matches!(f.data_type, DataType::String))
+                        .map(|f| f.name.clone()),
+                );
+                continue;
+            }
+            let field = schema
+                .fields()
+                .iter()
+                .find(|f| f.name.eq_ignore_ascii_case(column))
+                .ok_or_else(|| Error::ColumnNotFound(column.clone()))?;
+            resolved.push(field.name.clone());
+        }
+        let index = TableIndex {
+            name: name.to_string(),
+            kind,
+            columns: resolved,
+            options: options.to_vec(),
+        };
+        self.catalog
+            .create_index(table_name, index, or_replace, if_not_exists)?;
+        Ok(Table::empty(Schema::new()))
+    }
+
+    pub(crate) fn execute_drop_index(
+        &self,
+        name: &str,
+        table_name: &str,
+        kind: IndexKind,
+        if_exists: bool,
+    ) -> Result<Table> {
+        self.catalog.drop_index(table_name, name, kind, if_exists)?;
+        Ok(Table::empty(Schema::new()))
+    }
+
     pub(crate) fn execute_create_procedure(
         &self,
         name: &str,
@@ -422,6 +534,7 @@ impl ConcurrentPlanExecutor<'_> {
         snapshot_name: &str,
         source_name: &str,
         if_not_exists: bool,
+        as_of: Option<&Expr>,
     ) -> Result<Table> {
         if self.catalog.table_exists(snapshot_name) {
             if if_not_exists {
@@ -433,13 +546,29 @@ impl ConcurrentPlanExecutor<'_> {
             )));
         }
 
-        let source = self
-            .tables
-            .get_table(source_name)
-            .ok_or_else(|| Error::TableNotFound(source_name.to_string()))?
-            .clone();
+        let (source, snapshot_time) = match as_of {
+            Some(expr) => {
+                let timestamp = self.evaluate_snapshot_time(expr)?;
+                (self.catalog.table_as_of(source_name, timestamp)?, timestamp)
+            }
+            None => {
+                let source = self
+                    .tables
+                    .get_table(source_name)
+                    .ok_or_else(|| Error::TableNotFound(source_name.to_string()))?;
+                (source, self.catalog.history_now())
+            }
+        };
 
-        self.catalog.insert_table(snapshot_name, source)?;
+        self.catalog.create_snapshot(
+            snapshot_name,
+            source,
+            TableSnapshotInfo {
+                table_name: snapshot_name.to_string(),
+                base_table_name: source_name.to_string(),
+                snapshot_time,
+            },
+        )?;
         Ok(Table::empty(Schema::new()))
     }
 
@@ -454,6 +583,12 @@ impl ConcurrentPlanExecutor<'_> {
             }
             return Err(Error::TableNotFound(snapshot_name.to_string()));
         }
+        if !self.catalog.is_snapshot(snapshot_name) {
+            return Err(Error::invalid_query(format!(
+                "Table {} is not a table snapshot",
+                snapshot_name
+            )));
+        }
         self.catalog.drop_table(snapshot_name)?;
         Ok(Table::empty(Schema::new()))
     }
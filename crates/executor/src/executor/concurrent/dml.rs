@@ -6,7 +6,7 @@ use yachtsql_ir::{Assignment, Expr, MergeClause};
 use yachtsql_storage::{Record, Schema, Table};
 
 use super::{ConcurrentPlanExecutor, coerce_value};
-use crate::ir_evaluator::IrEvaluator;
+use crate::ir_evaluator::{BatchEvaluator, IrEvaluator};
 use crate::plan::PhysicalPlan;
 
 impl ConcurrentPlanExecutor<'_> {
@@ -236,7 +236,7 @@ impl ConcurrentPlanExecutor<'_> {
             .iter()
             .any(|a| Self::expr_contains_subquery(&a.value));
 
-        let mut new_table = Table::empty(base_schema.clone());
+        let mut updates: Vec<(usize, Vec<Value>)> = Vec::new();
 
         match from {
             Some(from_plan) => {
@@ -340,17 +340,11 @@ impl ConcurrentPlanExecutor<'_> {
                     }
                 }
 
-                for (idx, target_row) in target_rows.iter().enumerate() {
-                    if let Some(updated_row) = updated_rows.get(&idx) {
-                        new_table.push_row(updated_row.clone())?;
-                    } else {
-                        new_table.push_row(target_row.clone())?;
-                    }
-                }
+                updates.extend(updated_rows);
             }
             None => {
                 if filter_has_subquery || assignments_have_subquery {
-                    for record in table.rows()? {
+                    for (row_idx, record) in table.rows()?.into_iter().enumerate() {
                         let matches = if let Some(f) = filter {
                             self.eval_expr_with_subqueries(f, &target_schema, &record)
                                 .await?
@@ -390,9 +384,7 @@ impl ConcurrentPlanExecutor<'_> {
                                     }
                                 }
                             }
-                            new_table.push_row(new_row)?;
-                        } else {
-                            new_table.push_row(record.values().to_vec())?;
+                            updates.push((row_idx, new_row));
                         }
                     }
                 } else {
@@ -402,40 +394,28 @@ impl ConcurrentPlanExecutor<'_> {
                         .with_variables(&vars)
                         .with_user_functions(&udf);
 
-                    for record in table.rows()? {
-                        let matches = filter
-                            .map(|f| evaluator.evaluate(f, &record))
-                            .transpose()?
-                            .map(|v| v.as_bool().unwrap_or(false))
-                            .unwrap_or(true);
-
-                        if matches {
-                            let mut new_row = record.values().to_vec();
-                            for assignment in assignments {
-                                let (base_col, field_path) =
-                                    Self::parse_assignment_column(&assignment.column);
-                                if let Some(idx) = target_schema.field_index(&base_col) {
-                                    let val = match &assignment.value {
-                                        Expr::Default => {
-                                            default_values[idx].clone().unwrap_or(Value::Null)
-                                        }
-                                        _ => evaluator.evaluate(&assignment.value, &record)?,
-                                    };
-                                    if field_path.is_empty() {
-                                        new_row[idx] = val;
-                                    } else {
-                                        new_row[idx] = Self::set_nested_field(
-                                            &new_row[idx],
-                                            &field_path,
-                                            val,
-                                        )?;
+                    let matched = self.matching_rows(&evaluator, &table, filter)?;
+                    for (row_idx, record) in matched {
+                        let mut new_row = record.values().to_vec();
+                        for assignment in assignments {
+                            let (base_col, field_path) =
+                                Self::parse_assignment_column(&assignment.column);
+                            if let Some(idx) = target_schema.field_index(&base_col) {
+                                let val = match &assignment.value {
+                                    Expr::Default => {
+                                        default_values[idx].clone().unwrap_or(Value::Null)
                                     }
+                                    _ => evaluator.evaluate(&assignment.value, &record)?,
+                                };
+                                if field_path.is_empty() {
+                                    new_row[idx] = val;
+                                } else {
+                                    new_row[idx] =
+                                        Self::set_nested_field(&new_row[idx], &field_path, val)?;
                                 }
                             }
-                            new_table.push_row(new_row)?;
-                        } else {
-                            new_table.push_row(record.values().to_vec())?;
                         }
+                        updates.push((row_idx, new_row));
                     }
                 }
             }
@@ -445,11 +425,40 @@ impl ConcurrentPlanExecutor<'_> {
             .tables
             .get_table_mut(table_name)
             .ok_or_else(|| Error::TableNotFound(table_name.to_string()))?;
-        *target = new_table;
+        target.update_rows(updates)?;
 
         Ok(Table::empty(Schema::new()))
     }
 
+    /// Rows of `table` matching `filter`, paired with their index. The
+    /// filter is evaluated column-at-a-time where possible, so only matching
+    /// rows are materialized.
+    fn matching_rows(
+        &self,
+        evaluator: &IrEvaluator,
+        table: &Table,
+        filter: Option<&Expr>,
+    ) -> Result<Vec<(usize, Record)>> {
+        let Some(filter) = filter else {
+            return Ok(table.rows()?.into_iter().enumerate().collect());
+        };
+        if let Some(selection) = BatchEvaluator::new(evaluator, table).select(filter) {
+            let records = table.gather_rows(&selection)?.rows()?;
+            return Ok(selection.into_iter().zip(records).collect());
+        }
+        let mut matched = Vec::new();
+        for (row_idx, record) in table.rows()?.into_iter().enumerate() {
+            if evaluator
+                .evaluate(filter, &record)?
+                .as_bool()
+                .unwrap_or(false)
+            {
+                matched.push((row_idx, record));
+            }
+        }
+        Ok(matched)
+    }
+
     fn parse_assignment_column(column: &str) -> (String, Vec<String>) {
         let parts: Vec<&str> = column.split('.').collect();
         if parts.len() > 1 {
@@ -517,10 +526,10 @@ impl ConcurrentPlanExecutor<'_> {
             .as_ref()
             .is_some_and(|f| Self::expr_contains_subquery(f));
 
-        let mut new_table = Table::empty(base_schema.clone());
+        let mut deleted = Vec::new();
 
         if has_subquery {
-            for record in table.rows()? {
+            for (row_idx, record) in table.rows()?.into_iter().enumerate() {
                 let matches = match filter {
                     Some(f) => self
                         .eval_expr_with_subqueries(f, &schema, &record)
@@ -530,8 +539,8 @@ impl ConcurrentPlanExecutor<'_> {
                     None => true,
                 };
 
-                if !matches {
-                    new_table.push_row(record.values().to_vec())?;
+                if matches {
+                    deleted.push(row_idx);
                 }
             }
         } else {
@@ -543,24 +552,28 @@ impl ConcurrentPlanExecutor<'_> {
                 .with_system_variables(&sys_vars)
                 .with_user_functions(&udf);
 
-            for record in table.rows()? {
-                let matches = filter
-                    .map(|f| evaluator.evaluate(f, &record))
-                    .transpose()?
-                    .map(|v| v.as_bool().unwrap_or(false))
-                    .unwrap_or(true);
-
-                if !matches {
-                    new_table.push_row(record.values().to_vec())?;
-                }
-            }
+            deleted = match filter {
+                None => (0..table.row_count()).collect(),
+                Some(f) => match BatchEvaluator::new(&evaluator, &table).select(f) {
+                    Some(selection) => selection,
+                    None => {
+                        let mut selection = Vec::new();
+                        for (row_idx, record) in table.rows()?.into_iter().enumerate() {
+                            if evaluator.evaluate(f, &record)?.as_bool().unwrap_or(false) {
+                                selection.push(row_idx);
+                            }
+                        }
+                        selection
+                    }
+                },
+            };
         }
 
         let target = self
             .tables
             .get_table_mut(table_name)
             .ok_or_else(|| Error::TableNotFound(table_name.to_string()))?;
-        *target = new_table;
+        target.delete_rows(&deleted);
 
         Ok(Table::empty(Schema::new()))
     }
@@ -906,14 +919,8 @@ impl ConcurrentPlanExecutor<'_> {
             .get_table_mut(target_table)
             .ok_or_else(|| Error::TableNotFound(target_table.to_string()))?;
 
-        for (idx, new_values) in &updates {
-            target.update_row(*idx, new_values.clone())?;
-        }
-
-        deletes.sort();
-        for idx in deletes.into_iter().rev() {
-            target.remove_row(idx);
-        }
+        target.update_rows(updates)?;
+        target.delete_rows(&deletes);
 
         for insert_row in inserts {
             target.push_row(insert_row)?;
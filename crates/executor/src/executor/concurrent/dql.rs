@@ -1,5 +1,8 @@
-use std::collections::HashSet;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet};
+use std::path::Path;
 
+use chrono::{DateTime, Utc};
 use rand::Rng;
 use rand::seq::SliceRandom;
 use yachtsql_common::error::{Error, Result};
@@ -9,8 +12,11 @@ use yachtsql_optimizer::SampleType;
 use yachtsql_storage::{Field, FieldMode, Record, Schema, Table};
 
 use super::{ConcurrentPlanExecutor, compare_values_for_sort};
+use crate::cancel::Interrupt;
+use crate::executor::memory::{MemoryBudget, SpillFile};
+use crate::executor::morsel::{MORSEL_ROWS, row_ranges, run_morsels};
 use crate::executor::plan_schema_to_schema;
-use crate::ir_evaluator::IrEvaluator;
+use crate::ir_evaluator::{BatchEvaluator, IrEvaluator};
 use crate::plan::PhysicalPlan;
 
 impl ConcurrentPlanExecutor<'_> {
@@ -18,66 +24,108 @@ impl ConcurrentPlanExecutor<'_> {
         &self,
         table_name: &str,
         planned_schema: &PlanSchema,
+        projection: Option<&[usize]>,
     ) -> Result<Table> {
-        if let Some(cte_table) = self.cte_results.read().unwrap().get(table_name) {
-            return Ok(self.apply_planned_schema(cte_table, planned_schema));
-        }
-        let table_name_upper = table_name.to_uppercase();
-        if let Some(cte_table) = self.cte_results.read().unwrap().get(&table_name_upper) {
-            return Ok(self.apply_planned_schema(cte_table, planned_schema));
-        }
-        let table_name_lower = table_name.to_lowercase();
-        if let Some(cte_table) = self.cte_results.read().unwrap().get(&table_name_lower) {
-            return Ok(self.apply_planned_schema(cte_table, planned_schema));
+        let table = self.scan_table(table_name)?;
+        Ok(self.apply_planned_schema(&table, planned_schema, projection))
+    }
+
+    /// The table a scan of `table_name` reads, before the planned schema is
+    /// applied: a CTE result, the statement's copy, or the catalog's table.
+    pub(crate) fn scan_table(&self, table_name: &str) -> Result<Table> {
+        {
+            let cte_results = self.cte_results.read().unwrap();
+            let cte_table = cte_results
+                .get(table_name)
+                .or_else(|| cte_results.get(&table_name.to_uppercase()))
+                .or_else(|| cte_results.get(&table_name.to_lowercase()));
+            if let Some(cte_table) = cte_table {
+                return Ok(cte_table.clone());
+            }
         }
 
         if let Some(table) = self.tables.get_table(table_name) {
-            return Ok(self.apply_planned_schema(&table, planned_schema));
+            return Ok(table);
         }
 
-        if let Some(handle) = self.catalog.get_table_handle(table_name) {
-            let guard = handle.read();
-            return Ok(self.apply_planned_schema(&guard, planned_schema));
+        if let Some(table) = self.catalog.try_read_table(table_name)? {
+            return Ok(table);
         }
 
         Err(Error::TableNotFound(table_name.to_string()))
     }
 
-    pub(crate) fn apply_planned_schema(
+    pub(crate) fn execute_time_travel_scan(
         &self,
-        source_table: &Table,
+        table_name: &str,
         planned_schema: &PlanSchema,
-    ) -> Table {
-        if planned_schema.fields.is_empty() {
-            return source_table.clone();
+        as_of: &Expr,
+    ) -> Result<Table> {
+        let timestamp = self.evaluate_snapshot_time(as_of)?;
+        let table = self.catalog.table_as_of(table_name, timestamp)?;
+        let historical = table.schema().fields();
+        let mut column_indices = Vec::with_capacity(planned_schema.fields.len());
+        for field in &planned_schema.fields {
+            let idx = historical
+                .iter()
+                .position(|f| f.name.eq_ignore_ascii_case(&field.name));
+            if let Some(idx) = idx
+                && historical[idx].data_type != field.data_type
+            {
+                return Err(Error::invalid_query(format!(
+                    "Column {} of table {} was {} at {}, not {}",
+                    field.name, table_name, historical[idx].data_type, timestamp, field.data_type
+                )));
+            }
+            column_indices.push(idx);
+        }
+        if column_indices.iter().all(Option::is_some) {
+            return Ok(self.apply_planned_schema(&table, planned_schema, None));
         }
 
-        let mut new_schema = Schema::new();
-        let mut column_indices = Vec::new();
-        for plan_field in &planned_schema.fields {
-            let mode = if plan_field.nullable {
-                FieldMode::Nullable
-            } else {
-                FieldMode::Required
-            };
-            let mut field = Field::new(&plan_field.name, plan_field.data_type.clone(), mode);
-            if let Some(ref table) = plan_field.table {
-                field = field.with_source_table(table.clone());
-            }
-            let source_field_idx = source_table
-                .schema()
-                .fields()
+        // Columns added since `timestamp` read as NULL.
+        let mut result = Table::empty(plan_schema_to_schema(planned_schema));
+        for record in table.rows()? {
+            let values = column_indices
                 .iter()
-                .position(|f| f.name.eq_ignore_ascii_case(&plan_field.name));
-            if let Some(idx) = source_field_idx {
-                if let Some(ref collation) = source_table.schema().fields()[idx].collation {
-                    field.collation = Some(collation.clone());
-                }
-                column_indices.push(idx);
+                .map(|idx| idx.map_or(Value::Null, |idx| record.values()[idx].clone()))
+                .collect();
+            result.push_row(values)?;
+        }
+        Ok(result)
+    }
+
+    pub(crate) fn evaluate_snapshot_time(&self, as_of: &Expr) -> Result<DateTime<Utc>> {
+        let vars = self.get_variables();
+        let sys_vars = self.get_system_variables();
+        let udf = self.get_user_functions();
+        let empty_schema = Schema::new();
+        let evaluator = IrEvaluator::new(&empty_schema)
+            .with_variables(&vars)
+            .with_system_variables(&sys_vars)
+            .with_user_functions(&udf);
+        match evaluator.evaluate(as_of, &Record::new())? {
+            Value::Timestamp(ts) => Ok(ts),
+            Value::DateTime(dt) => Ok(dt.and_utc()),
+            other => Err(Error::invalid_query(format!(
+                "FOR SYSTEM_TIME AS OF expects a TIMESTAMP, got {}",
+                other.data_type()
+            ))),
+        }
+    }
+
+    pub(crate) fn apply_planned_schema(
+        &self,
+        source_table: &Table,
+        planned_schema: &PlanSchema,
+        projection: Option<&[usize]>,
+    ) -> Table {
+        match planned_layout(source_table.schema(), planned_schema, projection) {
+            Some((schema, column_indices)) => {
+                source_table.with_reordered_schema(schema, &column_indices)
             }
-            new_schema.add_field(field);
+            None => source_table.clone(),
         }
-        source_table.with_reordered_schema(new_schema, &column_indices)
     }
 
     pub(crate) async fn execute_filter(
@@ -108,12 +156,20 @@ impl ConcurrentPlanExecutor<'_> {
                 .with_system_variables(&sys_vars)
                 .with_user_functions(&udf);
 
-            for record in input_table.rows()? {
-                let val = evaluator.evaluate(predicate, &record)?;
-                if val.as_bool().unwrap_or(false) {
-                    result.push_row(record.values().to_vec())?;
+            // Large inputs run morsel by morsel, in parallel when enabled,
+            // and check for interruption between morsels.
+            if input_table.row_count() > MORSEL_ROWS {
+                let parts = input_table.split_segments();
+                let threads = self.parallel_threads();
+                let filtered = run_morsels(threads, parts.len(), &self.interrupt, |i| {
+                    filter_table(&evaluator, &parts[i], predicate)
+                })?;
+                for part in &filtered {
+                    result.append(part);
                 }
+                return Ok(result);
             }
+            return filter_table(&evaluator, &input_table, predicate);
         }
 
         Ok(result)
@@ -130,7 +186,7 @@ impl ConcurrentPlanExecutor<'_> {
         let result_schema = plan_schema_to_schema(schema);
         let has_subqueries = expressions.iter().any(Self::expr_contains_subquery);
 
-        let mut result = Table::empty(result_schema);
+        let mut result = Table::empty(result_schema.clone());
 
         if has_subqueries {
             for record in input_table.rows()? {
@@ -152,14 +208,18 @@ impl ConcurrentPlanExecutor<'_> {
                 .with_system_variables(&sys_vars)
                 .with_user_functions(&udf);
 
-            for record in input_table.rows()? {
-                let mut new_row = Vec::with_capacity(expressions.len());
-                for expr in expressions {
-                    let val = evaluator.evaluate(expr, &record)?;
-                    new_row.push(val);
+            if input_table.row_count() > MORSEL_ROWS {
+                let parts = input_table.split_segments();
+                let threads = self.parallel_threads();
+                let projected = run_morsels(threads, parts.len(), &self.interrupt, |i| {
+                    project_table(&evaluator, &parts[i], expressions, &result_schema)
+                })?;
+                for part in &projected {
+                    result.append(part);
                 }
-                result.push_row(new_row)?;
+                return Ok(result);
             }
+            return project_table(&evaluator, &input_table, expressions, &result_schema);
         }
 
         Ok(result)
@@ -214,54 +274,51 @@ impl ConcurrentPlanExecutor<'_> {
             .with_system_variables(&sys_vars)
             .with_user_functions(&udf);
 
-        let mut rows: Vec<Record> = input_table.rows()?;
-
-        rows.sort_by(|a, b| {
-            for sort_expr in sort_exprs {
-                let val_a = evaluator
-                    .evaluate(&sort_expr.expr, a)
-                    .unwrap_or(Value::Null);
-                let val_b = evaluator
-                    .evaluate(&sort_expr.expr, b)
-                    .unwrap_or(Value::Null);
-
-                let ordering = compare_values_for_sort(&val_a, &val_b);
-                let ordering = if !sort_expr.asc {
-                    ordering.reverse()
-                } else {
-                    ordering
-                };
-
-                match (val_a.is_null(), val_b.is_null()) {
-                    (true, true) => {}
-                    (true, false) => {
-                        return if sort_expr.nulls_first {
-                            std::cmp::Ordering::Less
-                        } else {
-                            std::cmp::Ordering::Greater
-                        };
-                    }
-                    (false, true) => {
-                        return if sort_expr.nulls_first {
-                            std::cmp::Ordering::Greater
-                        } else {
-                            std::cmp::Ordering::Less
-                        };
-                    }
-                    (false, false) => {}
-                }
-
-                if ordering != std::cmp::Ordering::Equal {
-                    return ordering;
+        let budget = self.memory_budget();
+        let memory = budget.tracker("Sort");
+        let interrupt = &self.interrupt;
+        let rows: Vec<Record> = input_table.rows()?;
+        let threads = self.parallel_threads();
+        let ranges = row_ranges(rows.len(), threads);
+        let runs = memory.add_rows(&rows).and_then(|()| {
+            run_morsels(threads, ranges.len(), interrupt, |i| {
+                let mut run = Vec::with_capacity(ranges[i].len());
+                for idx in ranges[i].clone() {
+                    interrupt.check_row(idx)?;
+                    let keys = sort_keys(&evaluator, sort_exprs, &rows[idx]);
+                    memory.add_row(&keys)?;
+                    run.push((keys, idx));
                 }
-            }
-            std::cmp::Ordering::Equal
+                run.sort_by(|a, b| compare_sort_keys(sort_exprs, &a.0, &b.0));
+                Ok(run)
+            })
         });
+        let runs = match (runs, budget.spill_dir()) {
+            (Err(Error::ResourcesExceeded(_)), Some(dir)) => {
+                drop((rows, memory));
+                return spill_sort(
+                    &evaluator,
+                    sort_exprs,
+                    &input_table,
+                    schema.clone(),
+                    dir,
+                    &budget,
+                    interrupt,
+                );
+            }
+            (runs, _) => runs?,
+        };
 
         let mut result = Table::empty(schema);
-        for record in rows {
-            result.push_row(record.values().to_vec())?;
-        }
+        let runs = runs
+            .into_iter()
+            .map(|run| run.into_iter().map(Ok))
+            .collect();
+        merge_sorted_runs(sort_exprs, runs, interrupt, |idx| {
+            let values = rows[idx].values().to_vec();
+            memory.add_row(&values)?;
+            result.push_row(values)
+        })?;
 
         Ok(result)
     }
@@ -317,7 +374,8 @@ impl ConcurrentPlanExecutor<'_> {
         let mut result = Table::empty(schema);
         let mut seen: HashSet<Vec<Value>> = HashSet::new();
 
-        for record in input_table.rows()? {
+        for (n, record) in input_table.rows()?.into_iter().enumerate() {
+            self.interrupt.check_row(n)?;
             let values = record.values().to_vec();
             if seen.insert(values.clone()) {
                 result.push_row(values)?;
@@ -346,6 +404,9 @@ impl ConcurrentPlanExecutor<'_> {
             grouping_sets,
             &vars,
             &udf,
+            self.parallel_threads(),
+            &self.memory_budget(),
+            &self.interrupt,
         )
     }
 
@@ -358,7 +419,14 @@ impl ConcurrentPlanExecutor<'_> {
         let input_table = self.execute_plan(input).await?;
         let vars = self.get_variables();
         let udf = self.get_user_functions();
-        crate::executor::compute_window(&input_table, window_exprs, schema, &vars, &udf)
+        crate::executor::compute_window(
+            &input_table,
+            window_exprs,
+            schema,
+            &vars,
+            &udf,
+            &self.interrupt,
+        )
     }
 
     pub(crate) async fn execute_values(
@@ -390,3 +458,279 @@ impl ConcurrentPlanExecutor<'_> {
         Ok(result)
     }
 }
+
+pub(crate) fn filter_table(
+    evaluator: &IrEvaluator,
+    table: &Table,
+    predicate: &Expr,
+) -> Result<Table> {
+    if let Some(filtered) = BatchEvaluator::new(evaluator, table).filter(predicate) {
+        return Ok(filtered);
+    }
+
+    let mut result = Table::empty(table.schema().clone());
+    for record in table.rows()? {
+        let val = evaluator.evaluate(predicate, &record)?;
+        if val.as_bool().unwrap_or(false) {
+            result.push_row(record.values().to_vec())?;
+        }
+    }
+    Ok(result)
+}
+
+pub(crate) fn project_table(
+    evaluator: &IrEvaluator,
+    table: &Table,
+    expressions: &[Expr],
+    schema: &Schema,
+) -> Result<Table> {
+    if let Some(projected) = BatchEvaluator::new(evaluator, table).project(expressions, schema) {
+        return Ok(projected);
+    }
+
+    let mut result = Table::empty(schema.clone());
+    for record in table.rows()? {
+        let mut new_row = Vec::with_capacity(expressions.len());
+        for expr in expressions {
+            let val = evaluator.evaluate(expr, &record)?;
+            new_row.push(val);
+        }
+        result.push_row(new_row)?;
+    }
+    Ok(result)
+}
+
+fn sort_keys(evaluator: &IrEvaluator, sort_exprs: &[SortExpr], record: &Record) -> Vec<Value> {
+    sort_exprs
+        .iter()
+        .map(|sort_expr| {
+            evaluator
+                .evaluate(&sort_expr.expr, record)
+                .unwrap_or(Value::Null)
+        })
+        .collect()
+}
+
+fn compare_sort_keys(sort_exprs: &[SortExpr], a: &[Value], b: &[Value]) -> Ordering {
+    for ((sort_expr, val_a), val_b) in sort_exprs.iter().zip(a).zip(b) {
+        let ordering = compare_values_for_sort(val_a, val_b);
+        let ordering = if !sort_expr.asc {
+            ordering.reverse()
+        } else {
+            ordering
+        };
+
+        match (val_a.is_null(), val_b.is_null()) {
+            (true, true) => {}
+            (true, false) => {
+                return if sort_expr.nulls_first {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                };
+            }
+            (false, true) => {
+                return if sort_expr.nulls_first {
+                    Ordering::Greater
+                } else {
+                    Ordering::Less
+                };
+            }
+            (false, false) => {}
+        }
+
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+/// External merge sort: reads the input row by row into runs of about half
+/// the free budget, writes each run sorted to a spill file with its keys,
+/// then merges the files. Runs are contiguous ranges of the input,
+/// so ties keep their input order as in the in-memory sort.
+fn spill_sort(
+    evaluator: &IrEvaluator,
+    sort_exprs: &[SortExpr],
+    input: &Table,
+    schema: Schema,
+    dir: &Path,
+    budget: &MemoryBudget,
+    interrupt: &Interrupt,
+) -> Result<Table> {
+    let row_values = input.schema().field_count() + sort_exprs.len();
+    let bytes = input.memory_size() + input.row_count() * row_values * std::mem::size_of::<Value>();
+    let run_bytes = bytes.div_ceil(budget.spill_partitions(bytes));
+    let mut files = Vec::new();
+    let mut idx = 0;
+    while idx < input.row_count() {
+        let memory = budget.tracker("Sort");
+        let mut run: Vec<(Vec<Value>, Record)> = Vec::new();
+        while idx < input.row_count() && memory.charged() < run_bytes {
+            interrupt.check_row(idx)?;
+            let record = input.get_row(idx)?;
+            let keys = sort_keys(evaluator, sort_exprs, &record);
+            memory.add_row(&keys)?;
+            memory.add_row(record.values())?;
+            run.push((keys, record));
+            idx += 1;
+        }
+        run.sort_by(|a, b| compare_sort_keys(sort_exprs, &a.0, &b.0));
+        let mut file = SpillFile::create(dir)?;
+        for (mut keys, record) in run {
+            keys.extend_from_slice(record.values());
+            file.push(&keys)?;
+        }
+        files.push(file);
+    }
+
+    let runs = files
+        .iter_mut()
+        .map(|file| {
+            Ok(file.read()?.map(|entry| {
+                entry.map(|mut keys| {
+                    let values = keys.split_off(sort_exprs.len());
+                    (keys, values)
+                })
+            }))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let memory = budget.tracker("Sort");
+    let mut result = Table::empty(schema);
+    merge_sorted_runs(sort_exprs, runs, interrupt, |values| {
+        memory.add_row(&values)?;
+        result.push_row(values)
+    })?;
+    Ok(result)
+}
+
+/// K-way merge of sorted runs of `(keys, item)` pairs, passing the items to
+/// `emit` in order. Ties keep the order of the runs, so the merge is as
+/// stable as the run sorts.
+fn merge_sorted_runs<T, I>(
+    sort_exprs: &[SortExpr],
+    mut runs: Vec<I>,
+    interrupt: &Interrupt,
+    mut emit: impl FnMut(T) -> Result<()>,
+) -> Result<()>
+where
+    I: Iterator<Item = Result<(Vec<Value>, T)>>,
+{
+    let mut emitted = 0;
+    let mut emit = |item| {
+        interrupt.check_row(emitted)?;
+        emitted += 1;
+        emit(item)
+    };
+    if runs.len() == 1 {
+        for entry in runs.pop().unwrap() {
+            emit(entry?.1)?;
+        }
+        return Ok(());
+    }
+
+    let mut heap = BinaryHeap::new();
+    for (run, entries) in runs.iter_mut().enumerate() {
+        if let Some(entry) = entries.next() {
+            let (keys, item) = entry?;
+            heap.push(MergeHead {
+                keys,
+                run,
+                item,
+                sort_exprs,
+            });
+        }
+    }
+    while let Some(head) = heap.pop() {
+        emit(head.item)?;
+        if let Some(entry) = runs[head.run].next() {
+            let (keys, item) = entry?;
+            heap.push(MergeHead {
+                keys,
+                run: head.run,
+                item,
+                sort_exprs,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// The next row of one sorted run. Ordered so that `BinaryHeap`, a max-heap,
+/// pops the smallest keys first.
+struct MergeHead<'a, T> {
+    keys: Vec<Value>,
+    run: usize,
+    item: T,
+    sort_exprs: &'a [SortExpr],
+}
+
+impl<T> Ord for MergeHead<'_, T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare_sort_keys(self.sort_exprs, &other.keys, &self.keys)
+            .then_with(|| other.run.cmp(&self.run))
+    }
+}
+
+impl<T> PartialOrd for MergeHead<'_, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> PartialEq for MergeHead<'_, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for MergeHead<'_, T> {}
+
+/// The schema a scan with `planned_schema` and `projection` produces from a
+/// table with `source`'s schema, along with the source column behind each of
+/// its fields, or `None` when the scan returns the table as is.
+pub(crate) fn planned_layout(
+    source: &Schema,
+    planned_schema: &PlanSchema,
+    projection: Option<&[usize]>,
+) -> Option<(Schema, Vec<usize>)> {
+    if planned_schema.fields.is_empty() && projection.is_none() {
+        return None;
+    }
+
+    let source_fields = source.fields();
+    let mut new_schema = Schema::new();
+    let mut column_indices = Vec::new();
+    for (position, plan_field) in planned_schema.fields.iter().enumerate() {
+        let mode = if plan_field.nullable {
+            FieldMode::Nullable
+        } else {
+            FieldMode::Required
+        };
+        let mut field = Field::new(&plan_field.name, plan_field.data_type.clone(), mode);
+        if let Some(ref table) = plan_field.table {
+            field = field.with_source_table(table.clone());
+        }
+        let source_field_idx = projection
+            .and_then(|projection| projection.get(position).copied())
+            .filter(|&idx| {
+                source_fields
+                    .get(idx)
+                    .is_some_and(|f| f.name.eq_ignore_ascii_case(&plan_field.name))
+            })
+            .or_else(|| {
+                source_fields
+                    .iter()
+                    .position(|f| f.name.eq_ignore_ascii_case(&plan_field.name))
+            });
+        if let Some(idx) = source_field_idx {
+            if let Some(ref collation) = source_fields[idx].collation {
+                field.collation = Some(collation.clone());
+            }
+            column_indices.push(idx);
+        }
+        new_schema.add_field(field);
+    }
+    Some((new_schema, column_indices))
+}
@@ -1,11 +1,13 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 
 use yachtsql_common::error::Result;
 use yachtsql_common::types::Value;
-use yachtsql_ir::{Expr, JoinType, PlanSchema};
+use yachtsql_ir::{BinaryOp, Expr, JoinType, PlanSchema};
 use yachtsql_storage::{Record, Schema, Table};
 
 use super::{ConcurrentPlanExecutor, plan_schema_to_schema};
+use crate::executor::join_algorithms::{JoinEvaluators, concat_schemas, hash_join, merge_join};
+use crate::executor::memory::MemoryBudget;
 use crate::ir_evaluator::IrEvaluator;
 use crate::plan::PhysicalPlan;
 
@@ -19,22 +21,7 @@ impl ConcurrentPlanExecutor<'_> {
         schema: &PlanSchema,
         parallel: bool,
     ) -> Result<Table> {
-        let use_parallel = parallel && self.is_parallel_execution_enabled();
-
-        let (left_table, right_table) = if use_parallel {
-            let rt = tokio::runtime::Handle::current();
-            let (l, r) = std::thread::scope(|s| {
-                let left_handle = s.spawn(|| rt.block_on(self.execute_plan(left)));
-                let right_handle = s.spawn(|| rt.block_on(self.execute_plan(right)));
-                (left_handle.join().unwrap(), right_handle.join().unwrap())
-            });
-            (l?, r?)
-        } else {
-            (
-                self.execute_plan(left).await?,
-                self.execute_plan(right).await?,
-            )
-        };
+        let (left_table, right_table) = self.execute_join_inputs(left, right, parallel).await?;
         let left_schema = left_table.schema().clone();
         let right_schema = right_table.schema().clone();
         let result_schema = plan_schema_to_schema(schema);
@@ -55,15 +42,24 @@ impl ConcurrentPlanExecutor<'_> {
             .with_system_variables(&sys_vars)
             .with_user_functions(&udf);
 
+        let budget = self.memory_budget();
+        let memory = budget.tracker(if *join_type == JoinType::Cross {
+            "CrossJoin"
+        } else {
+            "NestedLoopJoin"
+        });
         let mut result = Table::empty(result_schema.clone());
         let left_rows = left_table.rows()?;
+        memory.add_rows(&left_rows)?;
         let right_rows = right_table.rows()?;
+        memory.add_rows(&right_rows)?;
         let left_width = left_schema.field_count();
         let right_width = right_schema.field_count();
 
         match join_type {
             JoinType::Inner => {
                 for left_record in &left_rows {
+                    self.interrupt.check()?;
                     for right_record in &right_rows {
                         let mut combined = left_record.values().to_vec();
                         combined.extend(right_record.values().to_vec());
@@ -76,6 +72,7 @@ impl ConcurrentPlanExecutor<'_> {
                             .unwrap_or(true);
 
                         if matches {
+                            memory.add_row(&combined)?;
                             result.push_row(combined)?;
                         }
                     }
@@ -83,6 +80,7 @@ impl ConcurrentPlanExecutor<'_> {
             }
             JoinType::Left => {
                 for left_record in &left_rows {
+                    self.interrupt.check()?;
                     let mut found_match = false;
                     for right_record in &right_rows {
                         let mut combined = left_record.values().to_vec();
@@ -97,18 +95,21 @@ impl ConcurrentPlanExecutor<'_> {
 
                         if matches {
                             found_match = true;
+                            memory.add_row(&combined)?;
                             result.push_row(combined)?;
                         }
                     }
                     if !found_match {
                         let mut combined = left_record.values().to_vec();
                         combined.extend(vec![Value::Null; right_width]);
+                        memory.add_row(&combined)?;
                         result.push_row(combined)?;
                     }
                 }
             }
             JoinType::Right => {
                 for right_record in &right_rows {
+                    self.interrupt.check()?;
                     let mut found_match = false;
                     for left_record in &left_rows {
                         let mut combined = left_record.values().to_vec();
@@ -123,12 +124,14 @@ impl ConcurrentPlanExecutor<'_> {
 
                         if matches {
                             found_match = true;
+                            memory.add_row(&combined)?;
                             result.push_row(combined)?;
                         }
                     }
                     if !found_match {
                         let mut combined = vec![Value::Null; left_width];
                         combined.extend(right_record.values().to_vec());
+                        memory.add_row(&combined)?;
                         result.push_row(combined)?;
                     }
                 }
@@ -136,6 +139,7 @@ impl ConcurrentPlanExecutor<'_> {
             JoinType::Full => {
                 let mut matched_right: HashSet<usize> = HashSet::new();
                 for left_record in &left_rows {
+                    self.interrupt.check()?;
                     let mut found_match = false;
                     for (ri, right_record) in right_rows.iter().enumerate() {
                         let mut combined = left_record.values().to_vec();
@@ -151,12 +155,14 @@ impl ConcurrentPlanExecutor<'_> {
                         if matches {
                             found_match = true;
                             matched_right.insert(ri);
+                            memory.add_row(&combined)?;
                             result.push_row(combined)?;
                         }
                     }
                     if !found_match {
                         let mut combined = left_record.values().to_vec();
                         combined.extend(vec![Value::Null; right_width]);
+                        memory.add_row(&combined)?;
                         result.push_row(combined)?;
                     }
                 }
@@ -164,15 +170,18 @@ impl ConcurrentPlanExecutor<'_> {
                     if !matched_right.contains(&ri) {
                         let mut combined = vec![Value::Null; left_width];
                         combined.extend(right_record.values().to_vec());
+                        memory.add_row(&combined)?;
                         result.push_row(combined)?;
                     }
                 }
             }
             JoinType::Cross => {
                 for left_record in &left_rows {
+                    self.interrupt.check()?;
                     for right_record in &right_rows {
                         let mut combined = left_record.values().to_vec();
                         combined.extend(right_record.values().to_vec());
+                        memory.add_row(&combined)?;
                         result.push_row(combined)?;
                     }
                 }
@@ -193,6 +202,7 @@ impl ConcurrentPlanExecutor<'_> {
             .await
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub(crate) async fn execute_hash_join(
         &self,
         left: &PhysicalPlan,
@@ -200,86 +210,104 @@ impl ConcurrentPlanExecutor<'_> {
         join_type: &JoinType,
         left_keys: &[Expr],
         right_keys: &[Expr],
+        residual: Option<&Expr>,
         schema: &PlanSchema,
         parallel: bool,
     ) -> Result<Table> {
-        let use_parallel = parallel && self.is_parallel_execution_enabled();
+        let (left_table, right_table) = self.execute_join_inputs(left, right, parallel).await?;
+        let combined_schema = concat_schemas(left_table.schema(), right_table.schema());
+        let vars = self.get_variables();
+        let sys_vars = self.get_system_variables();
+        let udf = self.get_user_functions();
+        let evaluator = |schema| {
+            IrEvaluator::new(schema)
+                .with_variables(&vars)
+                .with_system_variables(&sys_vars)
+                .with_user_functions(&udf)
+        };
+        let evaluators = JoinEvaluators {
+            left: evaluator(left_table.schema()),
+            right: evaluator(right_table.schema()),
+            combined: evaluator(&combined_schema),
+        };
+        hash_join(
+            &left_table,
+            &right_table,
+            *join_type,
+            left_keys,
+            right_keys,
+            residual,
+            &evaluators,
+            plan_schema_to_schema(schema),
+            self.parallel_threads(),
+            &self.memory_budget(),
+            &self.interrupt,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn execute_merge_join(
+        &self,
+        left: &PhysicalPlan,
+        right: &PhysicalPlan,
+        join_type: &JoinType,
+        left_keys: &[Expr],
+        right_keys: &[Expr],
+        comparison: BinaryOp,
+        residual: Option<&Expr>,
+        schema: &PlanSchema,
+        parallel: bool,
+    ) -> Result<Table> {
+        let (left_table, right_table) = self.execute_join_inputs(left, right, parallel).await?;
+        let combined_schema = concat_schemas(left_table.schema(), right_table.schema());
+        let vars = self.get_variables();
+        let sys_vars = self.get_system_variables();
+        let udf = self.get_user_functions();
+        let evaluator = |schema| {
+            IrEvaluator::new(schema)
+                .with_variables(&vars)
+                .with_system_variables(&sys_vars)
+                .with_user_functions(&udf)
+        };
+        let evaluators = JoinEvaluators {
+            left: evaluator(left_table.schema()),
+            right: evaluator(right_table.schema()),
+            combined: evaluator(&combined_schema),
+        };
+        merge_join(
+            &left_table,
+            &right_table,
+            *join_type,
+            left_keys,
+            right_keys,
+            comparison,
+            residual,
+            &evaluators,
+            plan_schema_to_schema(schema),
+            &self.memory_budget(),
+            &self.interrupt,
+        )
+    }
 
-        let (left_table, right_table) = if use_parallel {
+    async fn execute_join_inputs(
+        &self,
+        left: &PhysicalPlan,
+        right: &PhysicalPlan,
+        parallel: bool,
+    ) -> Result<(Table, Table)> {
+        if parallel && self.is_parallel_execution_enabled() {
             let rt = tokio::runtime::Handle::current();
             let (l, r) = std::thread::scope(|s| {
                 let left_handle = s.spawn(|| rt.block_on(self.execute_plan(left)));
                 let right_handle = s.spawn(|| rt.block_on(self.execute_plan(right)));
                 (left_handle.join().unwrap(), right_handle.join().unwrap())
             });
-            (l?, r?)
+            Ok((l?, r?))
         } else {
-            (
+            Ok((
                 self.execute_plan(left).await?,
                 self.execute_plan(right).await?,
-            )
-        };
-        let left_schema = left_table.schema().clone();
-        let right_schema = right_table.schema().clone();
-        let result_schema = plan_schema_to_schema(schema);
-
-        match join_type {
-            JoinType::Inner => {
-                let vars = self.get_variables();
-                let sys_vars = self.get_system_variables();
-                let udf = self.get_user_functions();
-                let left_evaluator = IrEvaluator::new(&left_schema)
-                    .with_variables(&vars)
-                    .with_system_variables(&sys_vars)
-                    .with_user_functions(&udf);
-                let right_evaluator = IrEvaluator::new(&right_schema)
-                    .with_variables(&vars)
-                    .with_system_variables(&sys_vars)
-                    .with_user_functions(&udf);
-
-                let right_rows = right_table.rows()?;
-                let mut hash_table: HashMap<Vec<Value>, Vec<Record>> = HashMap::new();
-
-                for right_record in right_rows {
-                    let key_values: Vec<Value> = right_keys
-                        .iter()
-                        .map(|expr| right_evaluator.evaluate(expr, &right_record))
-                        .collect::<Result<Vec<_>>>()?;
-
-                    let has_null = key_values.iter().any(|v| matches!(v, Value::Null));
-                    if has_null {
-                        continue;
-                    }
-
-                    hash_table.entry(key_values).or_default().push(right_record);
-                }
-
-                let mut result = Table::empty(result_schema);
-                for left_record in left_table.rows()? {
-                    let key_values: Vec<Value> = left_keys
-                        .iter()
-                        .map(|expr| left_evaluator.evaluate(expr, &left_record))
-                        .collect::<Result<Vec<_>>>()?;
-
-                    let has_null = key_values.iter().any(|v| matches!(v, Value::Null));
-                    if has_null {
-                        continue;
-                    }
-
-                    if let Some(matching_rows) = hash_table.get(&key_values) {
-                        for right_record in matching_rows {
-                            let mut combined = left_record.values().to_vec();
-                            combined.extend(right_record.values().to_vec());
-                            result.push_row(combined)?;
-                        }
-                    }
-                }
-
-                Ok(result)
-            }
-            _ => {
-                panic!("HashJoin only supports Inner join type currently");
-            }
+            ))
         }
     }
 }
@@ -1,3 +1,4 @@
+mod constraints;
 mod cte;
 mod ddl;
 mod dml;
@@ -11,24 +12,33 @@ mod unnest;
 mod utils;
 
 use std::collections::{HashMap, HashSet};
-use std::sync::RwLock;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicUsize;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 
 use arrow::array::Array;
 use async_recursion::async_recursion;
 use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, Timelike, Utc};
+pub(crate) use dql::{filter_table, planned_layout, project_table};
 use futures::future::{join, join_all};
 use yachtsql_common::error::{Error, Result};
 use yachtsql_common::types::{DataType, Value};
 use yachtsql_ir::{
-    AlterTableOp, Assignment, BinaryOp, ColumnDef, CteDefinition, ExportFormat, ExportOptions,
-    Expr, FunctionArg, FunctionBody, GapFillColumn, GapFillStrategy, JoinType, LoadFormat,
-    LoadOptions, LogicalPlan, MergeClause, PlanSchema, ProcedureArg, RaiseLevel, SetOperationType,
-    SortExpr, UnnestColumn, WindowFrame,
+    AlterTableOp, Assignment, BinaryOp, ColumnDef, CteDefinition, ExplainFormat, ExportFormat,
+    ExportOptions, Expr, FunctionArg, FunctionBody, GapFillColumn, GapFillStrategy, JoinType,
+    LoadFormat, LoadOptions, LogicalPlan, MergeClause, PlanSchema, ProcedureArg, RaiseLevel,
+    SetOperationType, SortExpr, UnnestColumn, WindowFrame,
 };
 use yachtsql_optimizer::{OptimizedLogicalPlan, SampleType, optimize};
 use yachtsql_storage::{Field, FieldMode, Record, Schema, Table};
 
-use super::window::{WindowFuncType, compute_window_function, partition_rows, sort_partition};
+use super::explain::{PlanProfile, explain_table};
+use super::memory::MemoryBudget;
+use super::window_utils::{
+    WindowFuncType, compute_window_function, partition_rows, sort_partition,
+};
+use crate::cancel::Interrupt;
 use crate::catalog::{ColumnDefault, UserFunction, UserProcedure};
 use crate::concurrent_catalog::{ConcurrentCatalog, TableLockSet};
 use crate::concurrent_session::ConcurrentSession;
@@ -138,6 +148,9 @@ pub struct ConcurrentPlanExecutor<'a> {
     pub(crate) system_variables: RwLock<HashMap<String, Value>>,
     pub(crate) cte_results: RwLock<HashMap<String, Table>>,
     pub(crate) user_function_defs: RwLock<HashMap<String, UserFunctionDef>>,
+    pub(crate) profile: RwLock<Option<Arc<PlanProfile>>>,
+    pub(crate) interrupt: Interrupt,
+    pub(crate) memory_used: Arc<AtomicUsize>,
 }
 
 impl<'a> ConcurrentPlanExecutor<'a> {
@@ -176,6 +189,9 @@ impl<'a> ConcurrentPlanExecutor<'a> {
             system_variables: RwLock::new(system_variables),
             cte_results: RwLock::new(HashMap::new()),
             user_function_defs: RwLock::new(user_function_defs),
+            profile: RwLock::new(None),
+            interrupt: Interrupt::default(),
+            memory_used: Arc::new(AtomicUsize::new(0)),
         }
     }
 
@@ -233,6 +249,100 @@ impl<'a> ConcurrentPlanExecutor<'a> {
         }
     }
 
+    /// Worker threads available to one operator; 1 when parallel execution
+    /// is disabled. Set per session with `SET PARALLEL_THREADS = n`.
+    pub(crate) fn parallel_threads(&self) -> usize {
+        if !self.is_parallel_execution_enabled() {
+            return 1;
+        }
+
+        let configured = self
+            .session_setting("PARALLEL_THREADS")
+            .as_ref()
+            .and_then(Value::as_i64);
+        if let Some(threads) = configured {
+            return threads.max(1) as usize;
+        }
+
+        std::env::var("YACHTSQL_PARALLEL_THREADS")
+            .ok()
+            .and_then(|val| val.parse::<usize>().ok())
+            .unwrap_or_else(|| {
+                std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get)
+            })
+            .max(1)
+    }
+
+    /// Memory the whole query may use, set per session with
+    /// `SET MEMORY_LIMIT_BYTES = n`. Every budget handed out by this executor
+    /// charges the same counter. Sorts, hash aggregates and hash joins spill
+    /// to `SET SPILL_DIRECTORY = '...'` instead of failing when set.
+    pub(crate) fn memory_budget(&self) -> MemoryBudget {
+        let limit = match self.session_setting("MEMORY_LIMIT_BYTES") {
+            Some(val) => val.as_i64().map(|bytes| bytes.max(0) as usize),
+            None => std::env::var("YACHTSQL_MEMORY_LIMIT_BYTES")
+                .ok()
+                .and_then(|val| val.parse().ok()),
+        };
+        let spill_dir = match self.session_setting("SPILL_DIRECTORY") {
+            Some(val) => val.as_str().map(PathBuf::from),
+            None => std::env::var_os("YACHTSQL_SPILL_DIRECTORY").map(PathBuf::from),
+        };
+        MemoryBudget::new(limit, spill_dir, Arc::clone(&self.memory_used))
+    }
+
+    /// How long a query may run, set per session with
+    /// `SET @@query_timeout_ms = n`; unset or 0 means no limit.
+    pub(crate) fn query_timeout(&self) -> Option<Duration> {
+        self.session_setting("@@query_timeout_ms")
+            .or_else(|| self.session_setting("QUERY_TIMEOUT_MS"))
+            .as_ref()
+            .and_then(Value::as_i64)
+            .filter(|ms| *ms > 0)
+            .map(|ms| Duration::from_millis(ms as u64))
+    }
+
+    fn session_setting(&self, name: &str) -> Option<Value> {
+        if let Some(val) = self.variables.read().unwrap().get(name) {
+            return Some(val.clone());
+        }
+        self.system_variables.read().unwrap().get(name).cloned()
+    }
+
+    /// DDL changes the shared catalog as soon as it runs, outside the
+    /// snapshot and the conflict check, so inside a transaction it may only
+    /// touch the session's temp tables and functions.
+    fn check_transactional(&self, plan: &PhysicalPlan) -> Result<()> {
+        let session_only = match plan {
+            PhysicalPlan::CreateTable { table_name, .. }
+            | PhysicalPlan::AlterTable { table_name, .. } => self.catalog.is_temp_table(table_name),
+            PhysicalPlan::DropTable { table_names, .. } => table_names
+                .iter()
+                .all(|name| self.catalog.is_temp_table(name)),
+            PhysicalPlan::CreateFunction { is_temp, .. } => *is_temp,
+            PhysicalPlan::CreateView { .. }
+            | PhysicalPlan::DropView { .. }
+            | PhysicalPlan::CreateSchema { .. }
+            | PhysicalPlan::DropSchema { .. }
+            | PhysicalPlan::UndropSchema { .. }
+            | PhysicalPlan::AlterSchema { .. }
+            | PhysicalPlan::DropFunction { .. }
+            | PhysicalPlan::CreateProcedure { .. }
+            | PhysicalPlan::DropProcedure { .. }
+            | PhysicalPlan::CreateIndex { .. }
+            | PhysicalPlan::DropIndex { .. }
+            | PhysicalPlan::CreateSnapshot { .. }
+            | PhysicalPlan::DropSnapshot { .. } => false,
+            _ => return Ok(()),
+        };
+        if session_only || !self.catalog.in_transaction() {
+            return Ok(());
+        }
+        Err(Error::invalid_query(
+            "DDL statements are not supported inside a transaction, except on temporary tables and functions",
+        ))
+    }
+
     pub async fn execute(&self, plan: &OptimizedLogicalPlan) -> Result<Table> {
         let executor_plan = PhysicalPlan::from_physical(plan);
         self.execute_plan(&executor_plan).await
@@ -240,10 +350,50 @@ impl<'a> ConcurrentPlanExecutor<'a> {
 
     #[async_recursion(?Send)]
     pub async fn execute_plan(&self, plan: &PhysicalPlan) -> Result<Table> {
+        self.interrupt.check()?;
+        self.check_transactional(plan)?;
+        let profile = self
+            .profile
+            .read()
+            .unwrap()
+            .clone()
+            .filter(|profile| profile.contains(plan));
+        let Some(profile) = profile else {
+            return self.execute_operator(plan).await;
+        };
+        let start = Instant::now();
+        let result = self.execute_operator(plan).await?;
+        let parallel = match plan {
+            PhysicalPlan::NestedLoopJoin { parallel, .. }
+            | PhysicalPlan::CrossJoin { parallel, .. }
+            | PhysicalPlan::HashJoin { parallel, .. }
+            | PhysicalPlan::MergeJoin { parallel, .. }
+            | PhysicalPlan::Union { parallel, .. }
+            | PhysicalPlan::Intersect { parallel, .. }
+            | PhysicalPlan::Except { parallel, .. } => *parallel,
+            PhysicalPlan::WithCte { parallel_ctes, .. } => !parallel_ctes.is_empty(),
+            _ => false,
+        } && self.is_parallel_execution_enabled();
+        profile.record(plan, &result, start.elapsed(), parallel);
+        Ok(result)
+    }
+
+    async fn execute_operator(&self, plan: &PhysicalPlan) -> Result<Table> {
         match plan {
             PhysicalPlan::TableScan {
-                table_name, schema, ..
-            } => self.execute_scan(table_name, schema).await,
+                table_name,
+                schema,
+                projection,
+                ..
+            } => {
+                self.execute_scan(table_name, schema, projection.as_deref())
+                    .await
+            }
+            PhysicalPlan::TimeTravelScan {
+                table_name,
+                schema,
+                as_of,
+            } => self.execute_time_travel_scan(table_name, schema, as_of),
             PhysicalPlan::Sample {
                 input,
                 sample_type,
@@ -290,11 +440,43 @@ impl<'a> ConcurrentPlanExecutor<'a> {
                 join_type,
                 left_keys,
                 right_keys,
+                residual,
                 schema,
                 parallel,
             } => {
                 self.execute_hash_join(
-                    left, right, join_type, left_keys, right_keys, schema, *parallel,
+                    left,
+                    right,
+                    join_type,
+                    left_keys,
+                    right_keys,
+                    residual.as_ref(),
+                    schema,
+                    *parallel,
+                )
+                .await
+            }
+            PhysicalPlan::MergeJoin {
+                left,
+                right,
+                join_type,
+                left_keys,
+                right_keys,
+                comparison,
+                residual,
+                schema,
+                parallel,
+            } => {
+                self.execute_merge_join(
+                    left,
+                    right,
+                    join_type,
+                    left_keys,
+                    right_keys,
+                    *comparison,
+                    residual.as_ref(),
+                    schema,
+                    *parallel,
                 )
                 .await
             }
@@ -377,7 +559,12 @@ impl<'a> ConcurrentPlanExecutor<'a> {
                 table_name,
                 columns,
                 source,
-            } => self.execute_insert(table_name, columns, source).await,
+            } => {
+                let before = self.constraint_baseline(table_name);
+                let result = self.execute_insert(table_name, columns, source).await?;
+                self.enforce_constraints(table_name, before.as_ref())?;
+                Ok(result)
+            }
             PhysicalPlan::Update {
                 table_name,
                 alias,
@@ -385,32 +572,49 @@ impl<'a> ConcurrentPlanExecutor<'a> {
                 from,
                 filter,
             } => {
-                self.execute_update(
-                    table_name,
-                    alias.as_deref(),
-                    assignments,
-                    from.as_deref(),
-                    filter.as_ref(),
-                )
-                .await
+                let before = self.constraint_baseline(table_name);
+                let result = self
+                    .execute_update(
+                        table_name,
+                        alias.as_deref(),
+                        assignments,
+                        from.as_deref(),
+                        filter.as_ref(),
+                    )
+                    .await?;
+                self.enforce_constraints(table_name, before.as_ref())?;
+                Ok(result)
             }
             PhysicalPlan::Delete {
                 table_name,
                 alias,
                 filter,
             } => {
-                self.execute_delete(table_name, alias.as_deref(), filter.as_ref())
-                    .await
+                let before = self.constraint_baseline(table_name);
+                let result = self
+                    .execute_delete(table_name, alias.as_deref(), filter.as_ref())
+                    .await?;
+                self.enforce_constraints(table_name, before.as_ref())?;
+                Ok(result)
             }
             PhysicalPlan::Merge {
                 target_table,
                 source,
                 on,
                 clauses,
-            } => self.execute_merge(target_table, source, on, clauses).await,
+            } => {
+                let before = self.constraint_baseline(target_table);
+                let result = self
+                    .execute_merge(target_table, source, on, clauses)
+                    .await?;
+                self.enforce_constraints(target_table, before.as_ref())?;
+                Ok(result)
+            }
             PhysicalPlan::CreateTable {
                 table_name,
                 columns,
+                constraints,
+                options,
                 if_not_exists,
                 or_replace,
                 query,
@@ -418,6 +622,8 @@ impl<'a> ConcurrentPlanExecutor<'a> {
                 self.execute_create_table(
                     table_name,
                     columns,
+                    constraints,
+                    options,
                     *if_not_exists,
                     *or_replace,
                     query.as_deref(),
@@ -434,6 +640,13 @@ impl<'a> ConcurrentPlanExecutor<'a> {
                 if_exists,
             } => self.execute_alter_table(table_name, operation, *if_exists),
             PhysicalPlan::Truncate { table_name } => self.execute_truncate(table_name),
+            PhysicalPlan::Analyze { table_name } => self.execute_analyze(table_name),
+            PhysicalPlan::Explain {
+                input,
+                analyze,
+                format,
+                schema,
+            } => self.execute_explain(input, *analyze, *format, schema).await,
             PhysicalPlan::CreateView {
                 name,
                 query: _,
@@ -441,19 +654,22 @@ impl<'a> ConcurrentPlanExecutor<'a> {
                 column_aliases,
                 or_replace,
                 if_not_exists,
+                materialized,
             } => self.execute_create_view(
                 name,
                 query_sql,
                 column_aliases,
                 *or_replace,
                 *if_not_exists,
+                *materialized,
             ),
             PhysicalPlan::DropView { name, if_exists } => self.execute_drop_view(name, *if_exists),
             PhysicalPlan::CreateSchema {
                 name,
                 if_not_exists,
                 or_replace,
-            } => self.execute_create_schema(name, *if_not_exists, *or_replace),
+                options,
+            } => self.execute_create_schema(name, *if_not_exists, *or_replace, options),
             PhysicalPlan::DropSchema {
                 name,
                 if_exists,
@@ -473,6 +689,7 @@ impl<'a> ConcurrentPlanExecutor<'a> {
                 if_not_exists,
                 is_temp,
                 is_aggregate,
+                options,
             } => self.execute_create_function(
                 name,
                 args,
@@ -482,6 +699,7 @@ impl<'a> ConcurrentPlanExecutor<'a> {
                 *if_not_exists,
                 *is_temp,
                 *is_aggregate,
+                options,
             ),
             PhysicalPlan::DropFunction { name, if_exists } => {
                 self.execute_drop_function(name, *if_exists)
@@ -496,6 +714,29 @@ impl<'a> ConcurrentPlanExecutor<'a> {
             PhysicalPlan::DropProcedure { name, if_exists } => {
                 self.execute_drop_procedure(name, *if_exists)
             }
+            PhysicalPlan::CreateIndex {
+                name,
+                table_name,
+                kind,
+                columns,
+                options,
+                or_replace,
+                if_not_exists,
+            } => self.execute_create_index(
+                name,
+                table_name,
+                *kind,
+                columns,
+                options,
+                *or_replace,
+                *if_not_exists,
+            ),
+            PhysicalPlan::DropIndex {
+                name,
+                table_name,
+                kind,
+                if_exists,
+            } => self.execute_drop_index(name, table_name, *kind, *if_exists),
             PhysicalPlan::Call {
                 procedure_name,
                 args,
@@ -508,7 +749,13 @@ impl<'a> ConcurrentPlanExecutor<'a> {
                 options,
                 temp_table,
                 temp_schema,
-            } => self.execute_load(table_name, options, *temp_table, temp_schema.as_ref()),
+            } => {
+                let before = self.constraint_baseline(table_name);
+                let result =
+                    self.execute_load(table_name, options, *temp_table, temp_schema.as_ref())?;
+                self.enforce_constraints(table_name, before.as_ref())?;
+                Ok(result)
+            }
             PhysicalPlan::Declare {
                 name,
                 data_type,
@@ -566,7 +813,13 @@ impl<'a> ConcurrentPlanExecutor<'a> {
                 snapshot_name,
                 source_name,
                 if_not_exists,
-            } => self.execute_create_snapshot(snapshot_name, source_name, *if_not_exists),
+                as_of,
+            } => self.execute_create_snapshot(
+                snapshot_name,
+                source_name,
+                *if_not_exists,
+                as_of.as_ref(),
+            ),
             PhysicalPlan::DropSnapshot {
                 snapshot_name,
                 if_exists,
@@ -585,15 +838,13 @@ impl<'a> ConcurrentPlanExecutor<'a> {
             PhysicalPlan::Grant { .. } => Ok(Table::empty(Schema::new())),
             PhysicalPlan::Revoke { .. } => Ok(Table::empty(Schema::new())),
             PhysicalPlan::BeginTransaction => {
-                self.catalog.begin_transaction();
-                let locked_snapshots = self.tables.snapshot_write_locked_tables();
-                for (name, table_data) in locked_snapshots {
-                    self.catalog.snapshot_table(&name, table_data);
-                }
+                self.tables.commit_writes()?;
+                self.catalog.begin_transaction()?;
                 Ok(Table::empty(Schema::new()))
             }
             PhysicalPlan::Commit => {
-                self.catalog.commit();
+                self.tables.commit_writes()?;
+                self.catalog.commit()?;
                 Ok(Table::empty(Schema::new()))
             }
             PhysicalPlan::Rollback => {
@@ -629,6 +880,27 @@ impl<'a> ConcurrentPlanExecutor<'a> {
         }
     }
 
+    async fn execute_explain(
+        &self,
+        input: &PhysicalPlan,
+        analyze: bool,
+        format: ExplainFormat,
+        schema: &PlanSchema,
+    ) -> Result<Table> {
+        if !analyze {
+            return explain_table(input, None, format, schema);
+        }
+        let profile = Arc::new(PlanProfile::new(input));
+        let previous = self.profile.write().unwrap().replace(profile.clone());
+        let result = self.execute_plan(input).await;
+        *self.profile.write().unwrap() = previous;
+        result?;
+        let stats = Arc::try_unwrap(profile)
+            .map(PlanProfile::into_stats)
+            .map_err(|_| Error::internal("EXPLAIN ANALYZE profile is still in use"))?;
+        explain_table(input, Some(&stats), format, schema)
+    }
+
     async fn execute_assert(&self, condition: &Expr, message: Option<&Expr>) -> Result<Table> {
         let empty_schema = Schema::new();
         let empty_record = Record::new();
@@ -1613,7 +1885,7 @@ impl<'a> ConcurrentPlanExecutor<'a> {
         }
     }
 
-    fn expr_contains_subquery(expr: &Expr) -> bool {
+    pub(crate) fn expr_contains_subquery(expr: &Expr) -> bool {
         match expr {
             Expr::Exists { .. }
             | Expr::InSubquery { .. }
@@ -92,19 +92,14 @@ impl ConcurrentPlanExecutor<'_> {
             for (table_name, access_type) in accesses.accesses.iter() {
                 let upper_name = table_name.to_uppercase();
                 let already_locked = self.tables.get_table(&upper_name).is_some();
-                if !already_locked && let Some(handle) = self.catalog.get_table_handle(table_name) {
+                if !already_locked && let Some(table) = self.catalog.try_read_table(table_name)? {
                     match access_type {
                         crate::plan::AccessType::Read => {
-                            if let Some(guard) = handle.try_read() {
-                                self.tables
-                                    .add_read_table(upper_name.clone(), guard.clone());
-                            }
+                            self.tables.add_read_table(upper_name.clone(), table);
                         }
                         crate::plan::AccessType::Write | crate::plan::AccessType::WriteOptional => {
-                            if let Some(guard) = handle.try_write() {
-                                self.tables
-                                    .add_write_table(upper_name.clone(), guard.clone());
-                            }
+                            self.catalog.check_writable(table_name)?;
+                            self.tables.add_write_table(upper_name.clone(), table);
                         }
                     }
                 }
@@ -131,25 +126,16 @@ impl ConcurrentPlanExecutor<'_> {
     }
 
     pub(crate) fn rollback_transaction(&self) {
-        if let Some(mut snapshot) = self.catalog.take_transaction_snapshot() {
-            let mut restored_tables = Vec::new();
-            for (name, table_data) in &snapshot.tables {
-                if let Some(table) = self.tables.get_table_mut(name) {
-                    *table = table_data.clone();
-                    restored_tables.push(name.clone());
-                }
-            }
-            for name in restored_tables {
-                snapshot.tables.remove(&name);
-            }
-            for (name, table_data) in snapshot.tables {
-                if let Some(handle) = self.catalog.get_table_handle(&name)
-                    && let Some(mut table) = handle.try_write()
-                {
-                    *table = table_data;
-                }
+        let mut discarded = self.tables.discard_writes();
+        discarded.extend(self.catalog.rollback());
+        for name in discarded {
+            if self.tables.get_table(&name).is_some()
+                && let Some(table) = self.catalog.read_table(&name)
+            {
+                self.tables.update_table(&name, table);
             }
         }
+        self.tables.discard_writes();
     }
 
     pub(crate) fn execute_declare(
@@ -216,7 +202,8 @@ impl ConcurrentPlanExecutor<'_> {
         if upper_name == "SEARCH_PATH"
             && let Some(schema_name) = val.as_str()
         {
-            self.catalog.set_search_path(vec![schema_name.to_string()]);
+            self.session
+                .set_current_schema(Some(schema_name.to_uppercase()));
         }
 
         if name.starts_with("@@") {
@@ -343,6 +330,7 @@ impl ConcurrentPlanExecutor<'_> {
         const MAX_ITERATIONS: usize = 10000;
 
         'outer: loop {
+            self.interrupt.check()?;
             let cond = {
                 let vars = self.get_variables();
                 let sys_vars = self.get_system_variables();
@@ -412,6 +400,7 @@ impl ConcurrentPlanExecutor<'_> {
         const MAX_ITERATIONS: usize = 10000;
 
         'outer: loop {
+            self.interrupt.check()?;
             for stmt in body {
                 match self.execute_plan(stmt).await {
                     Ok(r) => result = r,
@@ -480,6 +469,7 @@ impl ConcurrentPlanExecutor<'_> {
         const MAX_ITERATIONS: usize = 10000;
 
         'outer: loop {
+            self.interrupt.check()?;
             for stmt in body {
                 match self.execute_plan(stmt).await {
                     Ok(r) => result = r,
@@ -531,6 +521,7 @@ impl ConcurrentPlanExecutor<'_> {
         let mut result = Table::empty(Schema::new());
 
         'outer: for record in query_result.rows()? {
+            self.interrupt.check()?;
             let values = record.values();
             let struct_fields: Vec<(String, Value)> = schema_fields
                 .iter()
@@ -605,6 +596,7 @@ impl ConcurrentPlanExecutor<'_> {
                 Err(Error::InvalidQuery(msg)) if msg == "RETURN outside of function" => {
                     return Ok(last_result);
                 }
+                Err(e @ (Error::Cancelled | Error::Timeout(_))) => return Err(e),
                 Err(e) => {
                     let error_message = e.to_string();
                     let stmt_text = source_sql.clone().unwrap_or_else(|| format!("{:?}", plan));
@@ -776,14 +768,13 @@ impl ConcurrentPlanExecutor<'_> {
         for (table_name, access_type) in accesses.accesses.iter() {
             let upper_name = table_name.to_uppercase();
             let already_locked = self.tables.get_table(&upper_name).is_some();
-            if !already_locked && let Some(handle) = self.catalog.get_table_handle(table_name) {
+            if !already_locked && let Some(table) = self.catalog.try_read_table(table_name)? {
                 match access_type {
                     crate::plan::AccessType::Read => {
-                        let table = handle.read().clone();
                         self.tables.add_read_table(upper_name.clone(), table);
                     }
                     crate::plan::AccessType::Write | crate::plan::AccessType::WriteOptional => {
-                        let table = handle.write().clone();
+                        self.catalog.check_writable(table_name)?;
                         self.tables.add_write_table(upper_name.clone(), table);
                     }
                 }
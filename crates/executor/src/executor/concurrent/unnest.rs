@@ -7,7 +7,7 @@ use yachtsql_storage::{Record, Schema, Table};
 
 use super::ConcurrentPlanExecutor;
 use crate::executor::plan_schema_to_schema;
-use crate::executor::window::{
+use crate::executor::window_utils::{
     WindowFuncType, compute_window_function, partition_rows, sort_partition,
 };
 use crate::ir_evaluator::IrEvaluator;
@@ -161,6 +161,7 @@ impl ConcurrentPlanExecutor<'_> {
                     &order_by,
                     &frame,
                     &evaluator,
+                    &self.interrupt,
                 )?;
 
                 for (local_idx, row_idx) in indices.iter().enumerate() {
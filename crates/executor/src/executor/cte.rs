@@ -158,6 +158,8 @@ fn collect_union_terms(
         | LogicalPlan::DropTable { .. }
         | LogicalPlan::AlterTable { .. }
         | LogicalPlan::Truncate { .. }
+        | LogicalPlan::Analyze { .. }
+        | LogicalPlan::Explain { .. }
         | LogicalPlan::CreateView { .. }
         | LogicalPlan::DropView { .. }
         | LogicalPlan::CreateSchema { .. }
@@ -183,10 +185,13 @@ fn collect_union_terms(
         | LogicalPlan::AlterSchema { .. }
         | LogicalPlan::CreateProcedure { .. }
         | LogicalPlan::DropProcedure { .. }
+        | LogicalPlan::CreateIndex { .. }
+        | LogicalPlan::DropIndex { .. }
         | LogicalPlan::LoadData { .. }
         | LogicalPlan::Repeat { .. }
         | LogicalPlan::CreateSnapshot { .. }
         | LogicalPlan::DropSnapshot { .. }
+        | LogicalPlan::TimeTravelScan { .. }
         | LogicalPlan::Sample { .. }
         | LogicalPlan::Assert { .. }
         | LogicalPlan::Grant { .. }
@@ -210,6 +215,7 @@ fn references_table(plan: &LogicalPlan, table_name: &str) -> bool {
         LogicalPlan::Scan {
             table_name: name, ..
         } => name.eq_ignore_ascii_case(table_name),
+        LogicalPlan::TimeTravelScan { .. } => false,
         LogicalPlan::Filter { input, .. } => references_table(input, table_name),
         LogicalPlan::Project { input, .. } => references_table(input, table_name),
         LogicalPlan::Aggregate { input, .. } => references_table(input, table_name),
@@ -242,6 +248,8 @@ fn references_table(plan: &LogicalPlan, table_name: &str) -> bool {
         LogicalPlan::DropTable { .. } => false,
         LogicalPlan::AlterTable { .. } => false,
         LogicalPlan::Truncate { .. } => false,
+        LogicalPlan::Analyze { .. } => false,
+        LogicalPlan::Explain { input, .. } => references_table(input, table_name),
         LogicalPlan::CreateView { .. } => false,
         LogicalPlan::DropView { .. } => false,
         LogicalPlan::CreateSchema { .. } => false,
@@ -279,6 +287,8 @@ fn references_table(plan: &LogicalPlan, table_name: &str) -> bool {
         LogicalPlan::AlterSchema { .. } => false,
         LogicalPlan::CreateProcedure { .. } => false,
         LogicalPlan::DropProcedure { .. } => false,
+        LogicalPlan::CreateIndex { .. } => false,
+        LogicalPlan::DropIndex { .. } => false,
         LogicalPlan::LoadData { .. } => false,
         LogicalPlan::Repeat { body, .. } => body.iter().any(|p| references_table(p, table_name)),
         LogicalPlan::CreateSnapshot { .. } => false,
@@ -1,5 +1,7 @@
 mod io;
 
+use std::collections::HashMap;
+
 use yachtsql_common::error::{Error, Result};
 use yachtsql_common::types::{DataType, Value};
 use yachtsql_ir::{
@@ -267,6 +269,13 @@ impl<'a> PlanExecutor<'a> {
         Ok(Table::empty(Schema::new()))
     }
 
+    pub fn execute_analyze(&mut self, table_name: &str) -> Result<Table> {
+        if !self.catalog.table_exists(table_name) {
+            return Err(Error::TableNotFound(table_name.to_string()));
+        }
+        Ok(Table::empty(Schema::new()))
+    }
+
     pub fn execute_create_view(
         &mut self,
         name: &str,
@@ -274,6 +283,7 @@ impl<'a> PlanExecutor<'a> {
         column_aliases: &[String],
         or_replace: bool,
         if_not_exists: bool,
+        materialized: bool,
     ) -> Result<Table> {
         self.catalog.create_view(
             name,
@@ -281,6 +291,7 @@ impl<'a> PlanExecutor<'a> {
             column_aliases.to_vec(),
             or_replace,
             if_not_exists,
+            materialized,
         )?;
         Ok(Table::empty(Schema::new()))
     }
@@ -295,11 +306,14 @@ impl<'a> PlanExecutor<'a> {
         name: &str,
         if_not_exists: bool,
         or_replace: bool,
+        options: &[(String, String)],
     ) -> Result<Table> {
         if or_replace && self.catalog.schema_exists(name) {
             self.catalog.drop_schema(name, true, true)?;
         }
-        self.catalog.create_schema(name, if_not_exists)?;
+        let options: HashMap<String, String> = options.iter().cloned().collect();
+        self.catalog
+            .create_schema_with_options(name, if_not_exists, options)?;
         Ok(Table::empty(Schema::new()))
     }
 
@@ -339,6 +353,7 @@ impl<'a> PlanExecutor<'a> {
         if_not_exists: bool,
         is_temp: bool,
         is_aggregate: bool,
+        options: &[(String, String)],
     ) -> Result<Table> {
         if if_not_exists && self.catalog.function_exists(name) {
             return Ok(Table::empty(Schema::new()));
@@ -350,6 +365,7 @@ impl<'a> PlanExecutor<'a> {
             body: body.clone(),
             is_temporary: is_temp,
             is_aggregate,
+            options: options.to_vec(),
         };
         self.catalog.create_function(func, or_replace)?;
         self.refresh_user_functions();
@@ -448,6 +464,15 @@ fn executor_plan_to_logical_plan(plan: &PhysicalPlan) -> yachtsql_ir::LogicalPla
             schema: schema.clone(),
             projection: projection.clone(),
         },
+        PhysicalPlan::TimeTravelScan {
+            table_name,
+            schema,
+            as_of,
+        } => LogicalPlan::TimeTravelScan {
+            table_name: table_name.clone(),
+            schema: schema.clone(),
+            as_of: as_of.clone(),
+        },
         PhysicalPlan::Filter { input, predicate } => LogicalPlan::Filter {
             input: Box::new(executor_plan_to_logical_plan(input)),
             predicate: predicate.clone(),
@@ -491,43 +516,22 @@ fn executor_plan_to_logical_plan(plan: &PhysicalPlan) -> yachtsql_ir::LogicalPla
             left,
             right,
             join_type,
-            left_keys,
-            right_keys,
             schema,
             ..
-        } => {
-            let condition = if left_keys.len() == 1 {
-                Some(yachtsql_ir::Expr::BinaryOp {
-                    left: Box::new(left_keys[0].clone()),
-                    op: yachtsql_ir::BinaryOp::Eq,
-                    right: Box::new(right_keys[0].clone()),
-                })
-            } else {
-                let equalities: Vec<yachtsql_ir::Expr> = left_keys
-                    .iter()
-                    .zip(right_keys.iter())
-                    .map(|(l, r)| yachtsql_ir::Expr::BinaryOp {
-                        left: Box::new(l.clone()),
-                        op: yachtsql_ir::BinaryOp::Eq,
-                        right: Box::new(r.clone()),
-                    })
-                    .collect();
-                equalities
-                    .into_iter()
-                    .reduce(|acc, e| yachtsql_ir::Expr::BinaryOp {
-                        left: Box::new(acc),
-                        op: yachtsql_ir::BinaryOp::And,
-                        right: Box::new(e),
-                    })
-            };
-            LogicalPlan::Join {
-                left: Box::new(executor_plan_to_logical_plan(left)),
-                right: Box::new(executor_plan_to_logical_plan(right)),
-                join_type: *join_type,
-                condition,
-                schema: schema.clone(),
-            }
         }
+        | PhysicalPlan::MergeJoin {
+            left,
+            right,
+            join_type,
+            schema,
+            ..
+        } => LogicalPlan::Join {
+            left: Box::new(executor_plan_to_logical_plan(left)),
+            right: Box::new(executor_plan_to_logical_plan(right)),
+            join_type: *join_type,
+            condition: plan.join_condition(),
+            schema: schema.clone(),
+        },
         PhysicalPlan::HashAggregate {
             input,
             group_by,
@@ -696,12 +700,16 @@ fn executor_plan_to_logical_plan(plan: &PhysicalPlan) -> yachtsql_ir::LogicalPla
         PhysicalPlan::CreateTable {
             table_name,
             columns,
+            constraints,
+            options,
             if_not_exists,
             or_replace,
             query,
         } => LogicalPlan::CreateTable {
             table_name: table_name.clone(),
             columns: columns.clone(),
+            constraints: constraints.clone(),
+            options: options.clone(),
             if_not_exists: *if_not_exists,
             or_replace: *or_replace,
             query: query
@@ -727,6 +735,20 @@ fn executor_plan_to_logical_plan(plan: &PhysicalPlan) -> yachtsql_ir::LogicalPla
         PhysicalPlan::Truncate { table_name } => LogicalPlan::Truncate {
             table_name: table_name.clone(),
         },
+        PhysicalPlan::Analyze { table_name } => LogicalPlan::Analyze {
+            table_name: table_name.clone(),
+        },
+        PhysicalPlan::Explain {
+            input,
+            analyze,
+            format,
+            schema,
+        } => LogicalPlan::Explain {
+            input: Box::new(executor_plan_to_logical_plan(input)),
+            analyze: *analyze,
+            format: *format,
+            schema: schema.clone(),
+        },
         PhysicalPlan::CreateView {
             name,
             query,
@@ -734,6 +756,7 @@ fn executor_plan_to_logical_plan(plan: &PhysicalPlan) -> yachtsql_ir::LogicalPla
             column_aliases,
             or_replace,
             if_not_exists,
+            materialized,
         } => LogicalPlan::CreateView {
             name: name.clone(),
             query: Box::new(executor_plan_to_logical_plan(query)),
@@ -741,6 +764,7 @@ fn executor_plan_to_logical_plan(plan: &PhysicalPlan) -> yachtsql_ir::LogicalPla
             column_aliases: column_aliases.clone(),
             or_replace: *or_replace,
             if_not_exists: *if_not_exists,
+            materialized: *materialized,
         },
         PhysicalPlan::DropView { name, if_exists } => LogicalPlan::DropView {
             name: name.clone(),
@@ -750,10 +774,12 @@ fn executor_plan_to_logical_plan(plan: &PhysicalPlan) -> yachtsql_ir::LogicalPla
             name,
             if_not_exists,
             or_replace,
+            options,
         } => LogicalPlan::CreateSchema {
             name: name.clone(),
             if_not_exists: *if_not_exists,
             or_replace: *or_replace,
+            options: options.clone(),
         },
         PhysicalPlan::DropSchema {
             name,
@@ -784,6 +810,7 @@ fn executor_plan_to_logical_plan(plan: &PhysicalPlan) -> yachtsql_ir::LogicalPla
             if_not_exists,
             is_temp,
             is_aggregate,
+            options,
         } => LogicalPlan::CreateFunction {
             name: name.clone(),
             args: args.clone(),
@@ -793,6 +820,7 @@ fn executor_plan_to_logical_plan(plan: &PhysicalPlan) -> yachtsql_ir::LogicalPla
             if_not_exists: *if_not_exists,
             is_temp: *is_temp,
             is_aggregate: *is_aggregate,
+            options: options.clone(),
         },
         PhysicalPlan::DropFunction { name, if_exists } => LogicalPlan::DropFunction {
             name: name.clone(),
@@ -811,6 +839,34 @@ fn executor_plan_to_logical_plan(plan: &PhysicalPlan) -> yachtsql_ir::LogicalPla
             or_replace: *or_replace,
             if_not_exists: *if_not_exists,
         },
+        PhysicalPlan::CreateIndex {
+            name,
+            table_name,
+            kind,
+            columns,
+            options,
+            or_replace,
+            if_not_exists,
+        } => LogicalPlan::CreateIndex {
+            name: name.clone(),
+            table_name: table_name.clone(),
+            kind: *kind,
+            columns: columns.clone(),
+            options: options.clone(),
+            or_replace: *or_replace,
+            if_not_exists: *if_not_exists,
+        },
+        PhysicalPlan::DropIndex {
+            name,
+            table_name,
+            kind,
+            if_exists,
+        } => LogicalPlan::DropIndex {
+            name: name.clone(),
+            table_name: table_name.clone(),
+            kind: *kind,
+            if_exists: *if_exists,
+        },
         PhysicalPlan::DropProcedure { name, if_exists } => LogicalPlan::DropProcedure {
             name: name.clone(),
             if_exists: *if_exists,
@@ -909,10 +965,12 @@ fn executor_plan_to_logical_plan(plan: &PhysicalPlan) -> yachtsql_ir::LogicalPla
             snapshot_name,
             source_name,
             if_not_exists,
+            as_of,
         } => LogicalPlan::CreateSnapshot {
             snapshot_name: snapshot_name.clone(),
             source_name: source_name.clone(),
             if_not_exists: *if_not_exists,
+            as_of: as_of.clone(),
         },
         PhysicalPlan::DropSnapshot {
             snapshot_name,
@@ -0,0 +1,398 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde_json::{Map, Value as JsonValue, json};
+use yachtsql_common::error::Result;
+use yachtsql_common::types::Value;
+use yachtsql_ir::{BinaryOp, ExplainFormat, Expr, JoinType, PlanSchema, SortExpr};
+use yachtsql_storage::Table;
+
+use super::plan_schema_to_schema;
+use crate::plan::PhysicalPlan;
+
+/// Runtime counters of one operator, accumulated over every execution of it.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct OperatorStats {
+    pub loops: u64,
+    pub rows_out: u64,
+    pub elapsed: Duration,
+    pub peak_memory: usize,
+    pub parallel: bool,
+}
+
+/// Per-operator statistics for one EXPLAIN ANALYZE run. Operators are
+/// identified by their address in the plan tree and numbered in preorder.
+pub(crate) struct PlanProfile {
+    ids: HashMap<usize, usize>,
+    stats: Mutex<Vec<OperatorStats>>,
+}
+
+impl PlanProfile {
+    pub fn new(root: &PhysicalPlan) -> Self {
+        let mut ids = HashMap::new();
+        let mut stack = vec![root];
+        while let Some(plan) = stack.pop() {
+            ids.insert(node_key(plan), ids.len());
+            stack.extend(plan.children().into_iter().rev());
+        }
+        let stats = Mutex::new(vec![OperatorStats::default(); ids.len()]);
+        Self { ids, stats }
+    }
+
+    pub fn contains(&self, plan: &PhysicalPlan) -> bool {
+        self.ids.contains_key(&node_key(plan))
+    }
+
+    pub fn record(&self, plan: &PhysicalPlan, result: &Table, elapsed: Duration, parallel: bool) {
+        let Some(&id) = self.ids.get(&node_key(plan)) else {
+            return;
+        };
+        let mut stats = self.stats.lock().unwrap();
+        let entry = &mut stats[id];
+        entry.loops += 1;
+        entry.rows_out += result.row_count() as u64;
+        entry.elapsed += elapsed;
+        entry.peak_memory = entry.peak_memory.max(result.memory_size());
+        entry.parallel |= parallel;
+    }
+
+    pub fn into_stats(self) -> Vec<OperatorStats> {
+        self.stats.into_inner().unwrap()
+    }
+}
+
+fn node_key(plan: &PhysicalPlan) -> usize {
+    plan as *const PhysicalPlan as usize
+}
+
+/// Renders `plan` as the result of an EXPLAIN statement. `stats` holds the
+/// preorder-numbered counters of an EXPLAIN ANALYZE run.
+pub(crate) fn explain_table(
+    plan: &PhysicalPlan,
+    stats: Option<&[OperatorStats]>,
+    format: ExplainFormat,
+    schema: &PlanSchema,
+) -> Result<Table> {
+    let mut next_id = 0;
+    let rows = match format {
+        ExplainFormat::Text => {
+            let mut lines = Vec::new();
+            text_lines(plan, stats, 0, &mut next_id, &mut lines);
+            lines
+        }
+        ExplainFormat::Json => vec![json_node(plan, stats, &mut next_id).to_string()],
+    };
+    Table::from_values(
+        plan_schema_to_schema(schema),
+        rows.into_iter()
+            .map(|row| vec![Value::String(row)])
+            .collect(),
+    )
+}
+
+fn text_lines(
+    plan: &PhysicalPlan,
+    stats: Option<&[OperatorStats]>,
+    depth: usize,
+    next_id: &mut usize,
+    lines: &mut Vec<String>,
+) {
+    let id = *next_id;
+    *next_id += 1;
+    let (name, details) = describe(plan);
+    let mut line = format!("{}{}", "  ".repeat(depth), name);
+    if !details.is_empty() {
+        let _ = write!(line, ": {}", details);
+    }
+    let _ = write!(line, " (est_rows={})", plan.estimate_rows());
+
+    let children = plan.children();
+    if let Some(stats) = stats {
+        let own = &stats[id];
+        if own.loops == 0 {
+            line.push_str(" (never executed)");
+        } else {
+            let _ = write!(
+                line,
+                " (actual rows_in={} rows_out={} loops={} time={:.3}ms peak_memory={} parallel={})",
+                rows_in(&children, stats, *next_id),
+                own.rows_out,
+                own.loops,
+                own.elapsed.as_secs_f64() * 1000.0,
+                format_bytes(own.peak_memory),
+                own.parallel
+            );
+        }
+    }
+    lines.push(line);
+
+    for child in children {
+        text_lines(child, stats, depth + 1, next_id, lines);
+    }
+}
+
+fn json_node(
+    plan: &PhysicalPlan,
+    stats: Option<&[OperatorStats]>,
+    next_id: &mut usize,
+) -> JsonValue {
+    let id = *next_id;
+    *next_id += 1;
+    let (name, details) = describe(plan);
+    let mut node = Map::new();
+    node.insert("operator".into(), json!(name));
+    if !details.is_empty() {
+        node.insert("details".into(), json!(details));
+    }
+    node.insert("estimated_rows".into(), json!(plan.estimate_rows()));
+
+    let children = plan.children();
+    if let Some(stats) = stats {
+        let own = &stats[id];
+        let actual = if own.loops == 0 {
+            JsonValue::Null
+        } else {
+            json!({
+                "rows_in": rows_in(&children, stats, *next_id),
+                "rows_out": own.rows_out,
+                "loops": own.loops,
+                "time_ms": own.elapsed.as_secs_f64() * 1000.0,
+                "peak_memory_bytes": own.peak_memory,
+                "parallel": own.parallel,
+            })
+        };
+        node.insert("actual".into(), actual);
+    }
+    let children: Vec<JsonValue> = children
+        .into_iter()
+        .map(|child| json_node(child, stats, next_id))
+        .collect();
+    if !children.is_empty() {
+        node.insert("children".into(), JsonValue::Array(children));
+    }
+    JsonValue::Object(node)
+}
+
+/// Rows produced by the children of a node, whose first child has preorder
+/// number `first_child`.
+fn rows_in(children: &[&PhysicalPlan], stats: &[OperatorStats], first_child: usize) -> u64 {
+    let mut id = first_child;
+    let mut rows = 0;
+    for child in children {
+        rows += stats[id].rows_out;
+        id += subtree_size(child);
+    }
+    rows
+}
+
+fn subtree_size(plan: &PhysicalPlan) -> usize {
+    1 + plan.children().into_iter().map(subtree_size).sum::<usize>()
+}
+
+pub(crate) fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{}B", bytes)
+    } else {
+        format!("{:.1}{}", value, UNITS[unit])
+    }
+}
+
+fn exprs(exprs: &[Expr]) -> String {
+    exprs
+        .iter()
+        .map(|e| e.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn sort_keys(sort_exprs: &[SortExpr]) -> String {
+    sort_exprs
+        .iter()
+        .map(|e| e.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Operator name and a one-line summary of its arguments.
+fn describe(plan: &PhysicalPlan) -> (&'static str, String) {
+    match plan {
+        PhysicalPlan::TableScan {
+            table_name,
+            schema,
+            projection,
+            ..
+        } => {
+            let details = match projection {
+                Some(_) => format!(
+                    "{} [{}]",
+                    table_name,
+                    schema
+                        .fields
+                        .iter()
+                        .map(|f| f.name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                None => table_name.clone(),
+            };
+            ("TableScan", details)
+        }
+        PhysicalPlan::TimeTravelScan {
+            table_name, as_of, ..
+        } => ("TimeTravelScan", format!("{} AS OF {}", table_name, as_of)),
+        PhysicalPlan::Sample {
+            sample_type,
+            sample_value,
+            ..
+        } => ("Sample", format!("{:?} {}", sample_type, sample_value)),
+        PhysicalPlan::Filter { predicate, .. } => ("Filter", predicate.to_string()),
+        PhysicalPlan::Project { expressions, .. } => ("Project", exprs(expressions)),
+        PhysicalPlan::NestedLoopJoin {
+            join_type,
+            condition,
+            ..
+        } => (
+            "NestedLoopJoin",
+            match condition {
+                Some(condition) => format!("{:?} ON {}", join_type, condition),
+                None => format!("{:?}", join_type),
+            },
+        ),
+        PhysicalPlan::CrossJoin { .. } => ("CrossJoin", String::new()),
+        PhysicalPlan::HashJoin {
+            join_type,
+            left_keys,
+            right_keys,
+            residual,
+            ..
+        } => (
+            "HashJoin",
+            join_details(
+                *join_type,
+                left_keys,
+                BinaryOp::Eq,
+                right_keys,
+                residual.as_ref(),
+            ),
+        ),
+        PhysicalPlan::MergeJoin {
+            join_type,
+            left_keys,
+            right_keys,
+            comparison,
+            residual,
+            ..
+        } => (
+            "MergeJoin",
+            join_details(
+                *join_type,
+                left_keys,
+                *comparison,
+                right_keys,
+                residual.as_ref(),
+            ),
+        ),
+        PhysicalPlan::HashAggregate {
+            group_by,
+            aggregates,
+            ..
+        } => (
+            "HashAggregate",
+            format!(
+                "group_by=[{}] aggregates=[{}]",
+                exprs(group_by),
+                exprs(aggregates)
+            ),
+        ),
+        PhysicalPlan::Sort { sort_exprs, .. } => ("Sort", sort_keys(sort_exprs)),
+        PhysicalPlan::Limit { limit, offset, .. } => {
+            let mut details = match limit {
+                Some(limit) => format!("limit={}", limit),
+                None => String::new(),
+            };
+            if let Some(offset) = offset {
+                if !details.is_empty() {
+                    details.push(' ');
+                }
+                let _ = write!(details, "offset={}", offset);
+            }
+            ("Limit", details)
+        }
+        PhysicalPlan::TopN {
+            sort_exprs, limit, ..
+        } => ("TopN", format!("{} limit={}", sort_keys(sort_exprs), limit)),
+        PhysicalPlan::Distinct { .. } => ("Distinct", String::new()),
+        PhysicalPlan::Union { all, .. } => ("Union", set_quantifier(*all)),
+        PhysicalPlan::Intersect { all, .. } => ("Intersect", set_quantifier(*all)),
+        PhysicalPlan::Except { all, .. } => ("Except", set_quantifier(*all)),
+        PhysicalPlan::Window { window_exprs, .. } => ("Window", exprs(window_exprs)),
+        PhysicalPlan::Unnest { columns, .. } => (
+            "Unnest",
+            columns
+                .iter()
+                .map(|c| c.expr.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+        PhysicalPlan::Qualify { predicate, .. } => ("Qualify", predicate.to_string()),
+        PhysicalPlan::WithCte { ctes, .. } => (
+            "WithCte",
+            ctes.iter()
+                .map(|c| c.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+        PhysicalPlan::Values { values, .. } => ("Values", format!("{} rows", values.len())),
+        PhysicalPlan::Empty { .. } => ("Empty", String::new()),
+        PhysicalPlan::GapFill { ts_column, .. } => ("GapFill", ts_column.clone()),
+        PhysicalPlan::Insert { table_name, .. } => ("Insert", table_name.clone()),
+        PhysicalPlan::Update { table_name, .. } => ("Update", table_name.clone()),
+        PhysicalPlan::Delete {
+            table_name, filter, ..
+        } => (
+            "Delete",
+            match filter {
+                Some(filter) => format!("{} WHERE {}", table_name, filter),
+                None => table_name.clone(),
+            },
+        ),
+        PhysicalPlan::Merge { target_table, .. } => ("Merge", target_table.clone()),
+        PhysicalPlan::CreateTable { table_name, .. } => ("CreateTable", table_name.clone()),
+        PhysicalPlan::ExportData { .. } => ("ExportData", String::new()),
+        PhysicalPlan::Explain { .. } => ("Explain", String::new()),
+        _ => ("Statement", String::new()),
+    }
+}
+
+fn join_details(
+    join_type: JoinType,
+    left_keys: &[Expr],
+    comparison: BinaryOp,
+    right_keys: &[Expr],
+    residual: Option<&Expr>,
+) -> String {
+    let mut details = format!(
+        "{:?} ON [{}] {} [{}]",
+        join_type,
+        exprs(left_keys),
+        comparison,
+        exprs(right_keys)
+    );
+    if let Some(residual) = residual {
+        let _ = write!(details, " FILTER {}", residual);
+    }
+    details
+}
+
+fn set_quantifier(all: bool) -> String {
+    if all { "ALL" } else { "DISTINCT" }.to_string()
+}
@@ -1,101 +1,15 @@
-use std::collections::HashMap;
-use std::hash::{Hash, Hasher};
-
 use yachtsql_common::error::Result;
 use yachtsql_common::types::Value;
-use yachtsql_ir::{Expr, JoinType, PlanSchema};
+use yachtsql_ir::{BinaryOp, Expr, JoinType, PlanSchema};
 use yachtsql_storage::{Record, Schema, Table};
 
+use super::join_algorithms::{JoinEvaluators, concat_schemas, hash_join, merge_join};
+use super::memory::MemoryBudget;
 use super::{PlanExecutor, plan_schema_to_schema};
+use crate::cancel::Interrupt;
 use crate::ir_evaluator::IrEvaluator;
 use crate::plan::PhysicalPlan;
 
-#[derive(Clone, PartialEq)]
-struct HashKey(Vec<Value>);
-
-impl Eq for HashKey {}
-
-impl Hash for HashKey {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        for val in &self.0 {
-            match val {
-                Value::Null => 0u8.hash(state),
-                Value::Bool(b) => {
-                    1u8.hash(state);
-                    b.hash(state);
-                }
-                Value::Int64(i) => {
-                    2u8.hash(state);
-                    i.hash(state);
-                }
-                Value::Float64(f) => {
-                    3u8.hash(state);
-                    f.to_bits().hash(state);
-                }
-                Value::String(s) => {
-                    4u8.hash(state);
-                    s.hash(state);
-                }
-                Value::Bytes(b) => {
-                    5u8.hash(state);
-                    b.hash(state);
-                }
-                Value::Date(d) => {
-                    6u8.hash(state);
-                    d.hash(state);
-                }
-                Value::DateTime(dt) => {
-                    7u8.hash(state);
-                    dt.hash(state);
-                }
-                Value::Time(t) => {
-                    8u8.hash(state);
-                    t.hash(state);
-                }
-                Value::Timestamp(ts) => {
-                    9u8.hash(state);
-                    ts.hash(state);
-                }
-                Value::Interval(interval) => {
-                    10u8.hash(state);
-                    interval.months.hash(state);
-                    interval.days.hash(state);
-                    interval.nanos.hash(state);
-                }
-                Value::Numeric(n) => {
-                    11u8.hash(state);
-                    n.to_string().hash(state);
-                }
-                Value::Array(arr) => {
-                    12u8.hash(state);
-                    arr.len().hash(state);
-                }
-                Value::Struct(fields) => {
-                    13u8.hash(state);
-                    fields.len().hash(state);
-                }
-                Value::Json(j) => {
-                    14u8.hash(state);
-                    j.to_string().hash(state);
-                }
-                Value::Geography(_) => {
-                    15u8.hash(state);
-                }
-                Value::Range { .. } => {
-                    16u8.hash(state);
-                }
-                Value::BigNumeric(n) => {
-                    17u8.hash(state);
-                    n.to_string().hash(state);
-                }
-                Value::Default => {
-                    18u8.hash(state);
-                }
-            }
-        }
-    }
-}
-
 impl<'a> PlanExecutor<'a> {
     pub fn execute_nested_loop_join(
         &mut self,
@@ -111,7 +25,7 @@ impl<'a> PlanExecutor<'a> {
         let result_schema = plan_schema_to_schema(schema);
         let mut result = Table::empty(result_schema.clone());
 
-        let combined_schema = combine_schemas(left_table.schema(), right_table.schema());
+        let combined_schema = concat_schemas(left_table.schema(), right_table.schema());
 
         match join_type {
             JoinType::Inner => {
@@ -175,6 +89,7 @@ impl<'a> PlanExecutor<'a> {
         Ok(result)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn execute_hash_join(
         &mut self,
         left: &PhysicalPlan,
@@ -182,86 +97,65 @@ impl<'a> PlanExecutor<'a> {
         join_type: &JoinType,
         left_keys: &[Expr],
         right_keys: &[Expr],
+        residual: Option<&Expr>,
         schema: &PlanSchema,
     ) -> Result<Table> {
         let left_table = self.execute_plan(left)?;
         let right_table = self.execute_plan(right)?;
-
-        let result_schema = plan_schema_to_schema(schema);
-        let mut result = Table::empty(result_schema);
-
-        match join_type {
-            JoinType::Inner => {
-                self.hash_inner_join(
-                    &left_table,
-                    &right_table,
-                    left_keys,
-                    right_keys,
-                    &mut result,
-                )?;
-            }
-            _ => {
-                panic!("HashJoin only supports Inner join type currently");
-            }
-        }
-
-        Ok(result)
+        let combined_schema = concat_schemas(left_table.schema(), right_table.schema());
+        let evaluators = JoinEvaluators {
+            left: IrEvaluator::new(left_table.schema()),
+            right: IrEvaluator::new(right_table.schema()),
+            combined: IrEvaluator::new(&combined_schema),
+        };
+        hash_join(
+            &left_table,
+            &right_table,
+            *join_type,
+            left_keys,
+            right_keys,
+            residual,
+            &evaluators,
+            plan_schema_to_schema(schema),
+            1,
+            &MemoryBudget::unlimited(),
+            &Interrupt::default(),
+        )
     }
 
-    fn hash_inner_join(
-        &self,
-        left: &Table,
-        right: &Table,
+    #[allow(clippy::too_many_arguments)]
+    pub fn execute_merge_join(
+        &mut self,
+        left: &PhysicalPlan,
+        right: &PhysicalPlan,
+        join_type: &JoinType,
         left_keys: &[Expr],
         right_keys: &[Expr],
-        result: &mut Table,
-    ) -> Result<()> {
-        let left_schema = left.schema();
-        let right_schema = right.schema();
-
-        let left_evaluator = IrEvaluator::new(left_schema);
-        let right_evaluator = IrEvaluator::new(right_schema);
-
-        let right_rows = right.rows()?;
-        let mut hash_table: HashMap<HashKey, Vec<&Record>> = HashMap::new();
-
-        for right_record in &right_rows {
-            let key_values: Vec<Value> = right_keys
-                .iter()
-                .map(|expr| right_evaluator.evaluate(expr, right_record))
-                .collect::<Result<Vec<_>>>()?;
-
-            let has_null = key_values.iter().any(|v| matches!(v, Value::Null));
-            if has_null {
-                continue;
-            }
-
-            let key = HashKey(key_values);
-            hash_table.entry(key).or_default().push(right_record);
-        }
-
-        for left_record in left.rows()? {
-            let key_values: Vec<Value> = left_keys
-                .iter()
-                .map(|expr| left_evaluator.evaluate(expr, &left_record))
-                .collect::<Result<Vec<_>>>()?;
-
-            let has_null = key_values.iter().any(|v| matches!(v, Value::Null));
-            if has_null {
-                continue;
-            }
-
-            let key = HashKey(key_values);
-
-            if let Some(matching_rows) = hash_table.get(&key) {
-                for right_record in matching_rows {
-                    let combined_values = combine_records(&left_record, right_record);
-                    result.push_row(combined_values)?;
-                }
-            }
-        }
-
-        Ok(())
+        comparison: BinaryOp,
+        residual: Option<&Expr>,
+        schema: &PlanSchema,
+    ) -> Result<Table> {
+        let left_table = self.execute_plan(left)?;
+        let right_table = self.execute_plan(right)?;
+        let combined_schema = concat_schemas(left_table.schema(), right_table.schema());
+        let evaluators = JoinEvaluators {
+            left: IrEvaluator::new(left_table.schema()),
+            right: IrEvaluator::new(right_table.schema()),
+            combined: IrEvaluator::new(&combined_schema),
+        };
+        merge_join(
+            &left_table,
+            &right_table,
+            *join_type,
+            left_keys,
+            right_keys,
+            comparison,
+            residual,
+            &evaluators,
+            plan_schema_to_schema(schema),
+            &MemoryBudget::unlimited(),
+            &Interrupt::default(),
+        )
     }
 
     fn inner_join(
@@ -455,17 +349,6 @@ impl<'a> PlanExecutor<'a> {
     }
 }
 
-fn combine_schemas(left: &Schema, right: &Schema) -> Schema {
-    let mut schema = Schema::new();
-    for field in left.fields() {
-        schema.add_field(field.clone());
-    }
-    for field in right.fields() {
-        schema.add_field(field.clone());
-    }
-    schema
-}
-
 fn combine_records(left: &Record, right: &Record) -> Vec<Value> {
     let mut values = left.values().to_vec();
     values.extend(right.values().to_vec());
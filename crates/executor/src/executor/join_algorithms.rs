@@ -0,0 +1,530 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use rust_decimal::prelude::ToPrimitive;
+use yachtsql_common::error::{Error, Result};
+use yachtsql_common::types::Value;
+use yachtsql_ir::{BinaryOp, Expr, JoinType};
+use yachtsql_storage::{Record, Schema, Table};
+
+use super::memory::{MemoryBudget, MemoryTracker, SpillFile, partition_of, row_size};
+use super::morsel::{row_ranges, run_morsels};
+use crate::cancel::Interrupt;
+use crate::ir_evaluator::IrEvaluator;
+
+/// Evaluators over the left input, the right input and their concatenation.
+pub(crate) struct JoinEvaluators<'a> {
+    pub left: IrEvaluator<'a>,
+    pub right: IrEvaluator<'a>,
+    pub combined: IrEvaluator<'a>,
+}
+
+/// Schema of a row of `left` followed by a row of `right`.
+pub(crate) fn concat_schemas(left: &Schema, right: &Schema) -> Schema {
+    let mut schema = Schema::new();
+    for field in left.fields().iter().chain(right.fields()) {
+        schema.add_field(field.clone());
+    }
+    schema
+}
+
+/// Joins on `left_keys = right_keys` by hashing the right input. Rows with a
+/// NULL key never match but are still kept by outer joins. Key evaluation
+/// for the build and the whole probe run on up to `threads` workers. The
+/// copies of both inputs, the hash table, the matching pairs and the output
+/// are charged to the budget. When they outgrow it, the copies are dropped,
+/// both inputs are partitioned by key into spill files row by row and
+/// joined one partition at a time.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn hash_join(
+    left: &Table,
+    right: &Table,
+    join_type: JoinType,
+    left_keys: &[Expr],
+    right_keys: &[Expr],
+    residual: Option<&Expr>,
+    evaluators: &JoinEvaluators,
+    schema: Schema,
+    threads: usize,
+    budget: &MemoryBudget,
+    interrupt: &Interrupt,
+) -> Result<Table> {
+    let keys = (left_keys, right_keys);
+    let memory = budget.tracker("HashJoin");
+    let pairs = match (
+        in_memory_hash_pairs(
+            (left, right),
+            keys,
+            residual,
+            evaluators,
+            threads,
+            &memory,
+            interrupt,
+        ),
+        budget.spill_dir(),
+    ) {
+        (Err(Error::ResourcesExceeded(_)), Some(dir)) => {
+            drop(memory);
+            return spilled_hash_join(
+                (left, right),
+                join_type,
+                keys,
+                residual,
+                evaluators,
+                schema,
+                dir,
+                budget,
+                interrupt,
+            );
+        }
+        (pairs, _) => pairs?,
+    };
+
+    let (left_rows, right_rows, pairs) = pairs;
+    let widths = (left.schema().field_count(), right.schema().field_count());
+    emit_join(
+        &left_rows,
+        &right_rows,
+        widths,
+        join_type,
+        pairs,
+        schema,
+        &memory,
+        interrupt,
+    )
+}
+
+/// Copies both inputs and finds their matching pairs, charging the copies,
+/// the hash table and the pairs to `memory`.
+fn in_memory_hash_pairs(
+    (left, right): (&Table, &Table),
+    keys: (&[Expr], &[Expr]),
+    residual: Option<&Expr>,
+    evaluators: &JoinEvaluators,
+    threads: usize,
+    memory: &MemoryTracker,
+    interrupt: &Interrupt,
+) -> Result<(Vec<Record>, Vec<Record>, Vec<(usize, usize)>)> {
+    let right_rows = right.rows()?;
+    memory.add_rows(&right_rows)?;
+    let left_rows = left.rows()?;
+    memory.add_rows(&left_rows)?;
+    let pairs = hash_pairs(
+        (&left_rows, &right_rows),
+        keys,
+        residual,
+        evaluators,
+        threads,
+        memory,
+        interrupt,
+    )?;
+    Ok((left_rows, right_rows, pairs))
+}
+
+fn hash_pairs(
+    (left_rows, right_rows): (&[Record], &[Record]),
+    (left_keys, right_keys): (&[Expr], &[Expr]),
+    residual: Option<&Expr>,
+    evaluators: &JoinEvaluators,
+    threads: usize,
+    memory: &MemoryTracker,
+    interrupt: &Interrupt,
+) -> Result<Vec<(usize, usize)>> {
+    let build = row_ranges(right_rows.len(), threads);
+    let build_keys = run_morsels(threads, build.len(), interrupt, |i| {
+        let mut keys = Vec::with_capacity(build[i].len());
+        for ri in build[i].clone() {
+            interrupt.check_row(ri)?;
+            let key = join_key(&evaluators.right, right_keys, &right_rows[ri])?;
+            if let Some(key) = &key {
+                memory.add(row_size(key) + std::mem::size_of::<usize>())?;
+            }
+            keys.push(key);
+        }
+        Ok(keys)
+    })?;
+    let mut buckets: HashMap<Vec<Value>, Vec<usize>> = HashMap::new();
+    for (ri, key) in build_keys.into_iter().flatten().enumerate() {
+        if let Some(key) = key {
+            buckets.entry(key).or_default().push(ri);
+        }
+    }
+
+    let probe = row_ranges(left_rows.len(), threads);
+    let pairs = run_morsels(threads, probe.len(), interrupt, |i| {
+        let mut pairs = Vec::new();
+        for li in probe[i].clone() {
+            interrupt.check_row(li)?;
+            let record = &left_rows[li];
+            let Some(key) = join_key(&evaluators.left, left_keys, record)? else {
+                continue;
+            };
+            for &ri in buckets.get(&key).into_iter().flatten() {
+                if residual_matches(evaluators, residual, record, &right_rows[ri])? {
+                    memory.add(std::mem::size_of::<(usize, usize)>())?;
+                    pairs.push((li, ri));
+                }
+            }
+        }
+        Ok(pairs)
+    })?;
+    Ok(pairs.concat())
+}
+
+/// Grace hash join: writes whole rows of both inputs to per-partition spill
+/// files, then builds and probes one partition at a time. Only the right
+/// rows of one partition and the output are held in memory; the output is
+/// put back into the order `emit_join` produces at the end.
+#[allow(clippy::too_many_arguments)]
+fn spilled_hash_join(
+    (left, right): (&Table, &Table),
+    join_type: JoinType,
+    (left_keys, right_keys): (&[Expr], &[Expr]),
+    residual: Option<&Expr>,
+    evaluators: &JoinEvaluators,
+    schema: Schema,
+    dir: &Path,
+    budget: &MemoryBudget,
+    interrupt: &Interrupt,
+) -> Result<Table> {
+    let entry_size = (right.schema().field_count() + right_keys.len())
+        * std::mem::size_of::<Value>()
+        + std::mem::size_of::<usize>();
+    let partitions = budget.spill_partitions(right.memory_size() + right.row_count() * entry_size);
+    let right_files = partition_rows(
+        &evaluators.right,
+        right_keys,
+        right,
+        partitions,
+        dir,
+        interrupt,
+    )?;
+    let left_files = partition_rows(
+        &evaluators.left,
+        left_keys,
+        left,
+        partitions,
+        dir,
+        interrupt,
+    )?;
+
+    let right_major = join_type == JoinType::Right;
+    let keep_left = matches!(join_type, JoinType::Left | JoinType::Full);
+    let keep_right = matches!(join_type, JoinType::Right | JoinType::Full);
+    let left_nulls = vec![Value::Null; left.schema().field_count()];
+    let right_nulls = vec![Value::Null; right.schema().field_count()];
+    let output_memory = budget.tracker("HashJoin");
+    let mut output: Vec<((usize, usize), Vec<Value>)> = Vec::new();
+    let mut push = |li: usize, ri: usize, row: Vec<Value>| {
+        interrupt.check_row(output.len())?;
+        output_memory.add_row(&row)?;
+        let tag = if right_major { (ri, li) } else { (li, ri) };
+        output.push((tag, row));
+        Ok::<_, Error>(())
+    };
+
+    for (mut right_file, mut left_file) in right_files.into_iter().zip(left_files) {
+        let memory = budget.tracker("HashJoin");
+        let mut right_rows = Vec::new();
+        let mut buckets: HashMap<Vec<Value>, Vec<usize>> = HashMap::new();
+        for (n, entry) in right_file.read()?.enumerate() {
+            interrupt.check_row(n)?;
+            let (ri, record) = spilled_row(entry?);
+            memory.add_row(record.values())?;
+            if let Some(key) = join_key(&evaluators.right, right_keys, &record)? {
+                memory.add(row_size(&key) + std::mem::size_of::<usize>())?;
+                buckets.entry(key).or_default().push(right_rows.len());
+            }
+            right_rows.push((ri, record, false));
+        }
+        for (n, entry) in left_file.read()?.enumerate() {
+            interrupt.check_row(n)?;
+            let (li, record) = spilled_row(entry?);
+            let mut matched = false;
+            if let Some(key) = join_key(&evaluators.left, left_keys, &record)? {
+                for &pos in buckets.get(&key).into_iter().flatten() {
+                    let (ri, right_record, right_matched) = &mut right_rows[pos];
+                    if residual_matches(evaluators, residual, &record, right_record)? {
+                        push(li, *ri, combine(record.values(), right_record.values()))?;
+                        *right_matched = true;
+                        matched = true;
+                    }
+                }
+            }
+            if !matched && keep_left {
+                push(li, usize::MAX, combine(record.values(), &right_nulls))?;
+            }
+        }
+        if keep_right {
+            for (ri, record, _) in right_rows.iter().filter(|(_, _, matched)| !matched) {
+                push(usize::MAX, *ri, combine(&left_nulls, record.values()))?;
+            }
+        }
+    }
+
+    output.sort_unstable_by_key(|(tag, _)| *tag);
+    let mut result = Table::empty(schema);
+    for (_, row) in output {
+        result.push_row(row)?;
+    }
+    Ok(result)
+}
+
+/// Writes `[row index, values...]` for every row of `table` to the spill
+/// file of its key's partition. Rows with a NULL key go to the first
+/// partition so that outer joins still see them.
+fn partition_rows(
+    evaluator: &IrEvaluator,
+    keys: &[Expr],
+    table: &Table,
+    partitions: usize,
+    dir: &Path,
+    interrupt: &Interrupt,
+) -> Result<Vec<SpillFile>> {
+    let mut files = (0..partitions)
+        .map(|_| SpillFile::create(dir))
+        .collect::<Result<Vec<_>>>()?;
+    for idx in 0..table.row_count() {
+        interrupt.check_row(idx)?;
+        let record = table.get_row(idx)?;
+        let partition = match join_key(evaluator, keys, &record)? {
+            Some(key) => partition_of(&key, partitions),
+            None => 0,
+        };
+        let mut entry = Vec::with_capacity(record.values().len() + 1);
+        entry.push(Value::Int64(idx as i64));
+        entry.extend_from_slice(record.values());
+        files[partition].push(&entry)?;
+    }
+    Ok(files)
+}
+
+fn spilled_row(mut entry: Vec<Value>) -> (usize, Record) {
+    let values = entry.split_off(1);
+    let idx = entry[0].as_i64().unwrap_or_default() as usize;
+    (idx, Record::from_values(values))
+}
+
+/// Joins on `left_keys <comparison> right_keys` by sorting both inputs on
+/// their keys and merging them. Range comparisons take a single key pair,
+/// which the planner brings to one type.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn merge_join(
+    left: &Table,
+    right: &Table,
+    join_type: JoinType,
+    left_keys: &[Expr],
+    right_keys: &[Expr],
+    comparison: BinaryOp,
+    residual: Option<&Expr>,
+    evaluators: &JoinEvaluators,
+    schema: Schema,
+    budget: &MemoryBudget,
+    interrupt: &Interrupt,
+) -> Result<Table> {
+    let memory = budget.tracker("MergeJoin");
+    let left_rows = left.rows()?;
+    memory.add_rows(&left_rows)?;
+    let right_rows = right.rows()?;
+    memory.add_rows(&right_rows)?;
+    let keys_memory = budget.tracker("MergeJoin");
+    let left_sorted = sorted_keys(
+        &evaluators.left,
+        left_keys,
+        &left_rows,
+        &keys_memory,
+        interrupt,
+    )?;
+    let right_sorted = sorted_keys(
+        &evaluators.right,
+        right_keys,
+        &right_rows,
+        &keys_memory,
+        interrupt,
+    )?;
+
+    let mut pairs = Vec::new();
+    let mut lower = 0;
+    let mut upper = 0;
+    for (n, (key, li)) in left_sorted.iter().enumerate() {
+        interrupt.check_row(n)?;
+        while lower < right_sorted.len() && right_sorted[lower].0 < *key {
+            lower += 1;
+        }
+        upper = upper.max(lower);
+        while upper < right_sorted.len() && right_sorted[upper].0 <= *key {
+            upper += 1;
+        }
+        let matches = match comparison {
+            BinaryOp::Eq => &right_sorted[lower..upper],
+            BinaryOp::Lt => &right_sorted[upper..],
+            BinaryOp::LtEq => &right_sorted[lower..],
+            BinaryOp::Gt => &right_sorted[..lower],
+            BinaryOp::GtEq => &right_sorted[..upper],
+            _ => &right_sorted[..0],
+        };
+        for (_, ri) in matches {
+            if residual_matches(evaluators, residual, &left_rows[*li], &right_rows[*ri])? {
+                memory.add(std::mem::size_of::<(usize, usize)>())?;
+                pairs.push((*li, *ri));
+            }
+        }
+    }
+
+    drop((left_sorted, right_sorted, keys_memory));
+    let widths = (left.schema().field_count(), right.schema().field_count());
+    emit_join(
+        &left_rows,
+        &right_rows,
+        widths,
+        join_type,
+        pairs,
+        schema,
+        &memory,
+        interrupt,
+    )
+}
+
+fn join_key(evaluator: &IrEvaluator, keys: &[Expr], record: &Record) -> Result<Option<Vec<Value>>> {
+    Ok(key_values(evaluator, keys, record)?
+        .map(|values| values.into_iter().map(normalize_key).collect()))
+}
+
+/// The values of `keys` for `record`, or `None` if any is NULL.
+fn key_values(
+    evaluator: &IrEvaluator,
+    keys: &[Expr],
+    record: &Record,
+) -> Result<Option<Vec<Value>>> {
+    let mut values = Vec::with_capacity(keys.len());
+    for expr in keys {
+        let value = evaluator.evaluate(expr, record)?;
+        if value.is_null() {
+            return Ok(None);
+        }
+        values.push(value);
+    }
+    Ok(Some(values))
+}
+
+/// Integral FLOAT64 and NUMERIC keys become INT64 so that keys of different
+/// numeric types hash alike. Sorted keys are left as they are, as a NUMERIC
+/// key turned INT64 no longer orders against the other NUMERIC keys.
+fn normalize_key(value: Value) -> Value {
+    match &value {
+        Value::Float64(f) if f.fract() == 0.0 && f.abs() < i64::MAX as f64 => {
+            Value::Int64(f.0 as i64)
+        }
+        Value::Numeric(d) | Value::BigNumeric(d) if d.fract().is_zero() => {
+            d.to_i64().map_or(value, Value::Int64)
+        }
+        _ => value,
+    }
+}
+
+fn sorted_keys(
+    evaluator: &IrEvaluator,
+    keys: &[Expr],
+    rows: &[Record],
+    memory: &MemoryTracker,
+    interrupt: &Interrupt,
+) -> Result<Vec<(Vec<Value>, usize)>> {
+    let mut sorted = Vec::with_capacity(rows.len());
+    for (idx, record) in rows.iter().enumerate() {
+        interrupt.check_row(idx)?;
+        if let Some(key) = key_values(evaluator, keys, record)? {
+            memory.add(row_size(&key) + std::mem::size_of::<usize>())?;
+            sorted.push((key, idx));
+        }
+    }
+    sorted.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(sorted)
+}
+
+fn residual_matches(
+    evaluators: &JoinEvaluators,
+    residual: Option<&Expr>,
+    left: &Record,
+    right: &Record,
+) -> Result<bool> {
+    let Some(residual) = residual else {
+        return Ok(true);
+    };
+    let record = Record::from_values(combine(left.values(), right.values()));
+    Ok(evaluators
+        .combined
+        .evaluate(residual, &record)?
+        .as_bool()
+        .unwrap_or(false))
+}
+
+/// Builds the join output from the matching `(left, right)` row pairs, in the
+/// order the nested loop join produces: left-major, except for right joins.
+fn emit_join(
+    left_rows: &[Record],
+    right_rows: &[Record],
+    (left_width, right_width): (usize, usize),
+    join_type: JoinType,
+    mut pairs: Vec<(usize, usize)>,
+    schema: Schema,
+    memory: &MemoryTracker,
+    interrupt: &Interrupt,
+) -> Result<Table> {
+    let push = |result: &mut Table, row: Vec<Value>| {
+        interrupt.check_row(result.row_count())?;
+        memory.add_row(&row)?;
+        result.push_row(row)
+    };
+    let left_nulls = vec![Value::Null; left_width];
+    let right_nulls = vec![Value::Null; right_width];
+    let mut result = Table::empty(schema);
+
+    if join_type == JoinType::Right {
+        pairs.sort_unstable_by_key(|&(li, ri)| (ri, li));
+        let mut next = pairs.iter().peekable();
+        for (ri, right) in right_rows.iter().enumerate() {
+            let mut matched = false;
+            while let Some(&(li, _)) = next.next_if(|(_, r)| *r == ri) {
+                push(&mut result, combine(left_rows[li].values(), right.values()))?;
+                matched = true;
+            }
+            if !matched {
+                push(&mut result, combine(&left_nulls, right.values()))?;
+            }
+        }
+        return Ok(result);
+    }
+
+    pairs.sort_unstable();
+    let keep_left = matches!(join_type, JoinType::Left | JoinType::Full);
+    let mut right_matched = vec![false; right_rows.len()];
+    let mut next = pairs.iter().peekable();
+    for (li, left) in left_rows.iter().enumerate() {
+        let mut matched = false;
+        while let Some(&(_, ri)) = next.next_if(|(l, _)| *l == li) {
+            push(&mut result, combine(left.values(), right_rows[ri].values()))?;
+            right_matched[ri] = true;
+            matched = true;
+        }
+        if !matched && keep_left {
+            push(&mut result, combine(left.values(), &right_nulls))?;
+        }
+    }
+    if join_type == JoinType::Full {
+        for (right, _) in right_rows
+            .iter()
+            .zip(&right_matched)
+            .filter(|(_, matched)| !**matched)
+        {
+            push(&mut result, combine(&left_nulls, right.values()))?;
+        }
+    }
+    Ok(result)
+}
+
+fn combine(left: &[Value], right: &[Value]) -> Vec<Value> {
+    let mut values = Vec::with_capacity(left.len() + right.len());
+    values.extend_from_slice(left);
+    values.extend_from_slice(right);
+    values
+}
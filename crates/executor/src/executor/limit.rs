@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::cmp::Ordering;
 
 use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
@@ -93,16 +94,19 @@ struct ColumnarSortKeys<'a> {
     nulls_first: Vec<bool>,
 }
 
+fn sort_key_columns<'a>(table: &'a Table, sort_exprs: &[SortExpr]) -> Option<Vec<Cow<'a, Column>>> {
+    sort_exprs
+        .iter()
+        .map(|sort_expr| table.column(try_resolve_column_index(&sort_expr.expr)?))
+        .collect()
+}
+
 impl<'a> ColumnarSortKeys<'a> {
-    fn new(table: &'a Table, sort_exprs: &[SortExpr]) -> Option<Self> {
-        let mut columns = Vec::with_capacity(sort_exprs.len());
-
-        for sort_expr in sort_exprs {
-            let col_idx = try_resolve_column_index(&sort_expr.expr)?;
-            let column = table.column(col_idx)?;
-            let sort_col_ref = SortColumnRef::from_column(column)?;
-            columns.push(sort_col_ref);
-        }
+    fn new(key_columns: &'a [Cow<'a, Column>], sort_exprs: &[SortExpr]) -> Option<Self> {
+        let columns = key_columns
+            .iter()
+            .map(|column| SortColumnRef::from_column(column))
+            .collect::<Option<Vec<_>>>()?;
 
         Some(ColumnarSortKeys {
             columns,
@@ -195,10 +199,14 @@ impl<'a> PlanExecutor<'a> {
             return Ok(Table::empty(schema));
         }
 
-        let sort_keys = match ColumnarSortKeys::new(&input_table, sort_exprs) {
+        let key_columns = sort_key_columns(&input_table, sort_exprs);
+        let sort_keys = match key_columns
+            .as_deref()
+            .and_then(|columns| ColumnarSortKeys::new(columns, sort_exprs))
+        {
             Some(keys) => keys,
             None => {
-                return self.execute_topn_fallback(input_table, sort_exprs, limit);
+                return self.execute_topn_fallback(input_table.clone(), sort_exprs, limit);
             }
         };
 
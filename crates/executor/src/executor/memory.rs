@@ -0,0 +1,229 @@
+use std::fs::{self, File};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use yachtsql_common::error::{Error, Result};
+use yachtsql_common::types::Value;
+use yachtsql_storage::Record;
+
+use super::explain::format_bytes;
+
+const MAX_SPILL_PARTITIONS: usize = 256;
+
+static NEXT_SPILL_FILE: AtomicUsize = AtomicUsize::new(0);
+
+/// Memory a whole query may use for the rows its operators hold: copies of
+/// their inputs, hash tables, sort keys, group states and output buffers.
+/// Budgets built for the same query share `used`, so the limit covers every
+/// operator at once. Operators that can spill fall back to partitioning
+/// their rows through files under `spill_dir` when they run out of budget.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MemoryBudget {
+    limit: Option<usize>,
+    spill_dir: Option<PathBuf>,
+    used: Arc<AtomicUsize>,
+}
+
+impl MemoryBudget {
+    pub fn new(limit: Option<usize>, spill_dir: Option<PathBuf>, used: Arc<AtomicUsize>) -> Self {
+        Self {
+            limit,
+            spill_dir,
+            used,
+        }
+    }
+
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    pub fn spill_dir(&self) -> Option<&Path> {
+        self.spill_dir.as_deref()
+    }
+
+    pub fn check(&self, operator: &str, bytes: usize) -> Result<()> {
+        match self.limit {
+            Some(limit) if bytes > limit => Err(Error::resources_exceeded(format!(
+                "{} brought the query to {} of memory, exceeding the session limit of {}",
+                operator,
+                format_bytes(bytes),
+                format_bytes(limit)
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn tracker<'a>(&'a self, operator: &'a str) -> MemoryTracker<'a> {
+        MemoryTracker {
+            budget: self,
+            operator,
+            charged: AtomicUsize::new(0),
+        }
+    }
+
+    /// Partitions to spill `bytes` of rows into so that each partition
+    /// takes about half of what is left of the budget.
+    pub fn spill_partitions(&self, bytes: usize) -> usize {
+        let free = self
+            .limit
+            .unwrap_or(usize::MAX)
+            .saturating_sub(self.used.load(Ordering::Relaxed));
+        bytes
+            .div_ceil((free / 2).max(1))
+            .clamp(2, MAX_SPILL_PARTITIONS)
+    }
+}
+
+/// Bytes held by one operator, charged to the query's budget as they grow
+/// and given back when the tracker is dropped. The workers of a parallel
+/// operator share one tracker.
+pub(crate) struct MemoryTracker<'a> {
+    budget: &'a MemoryBudget,
+    operator: &'a str,
+    charged: AtomicUsize,
+}
+
+impl MemoryTracker<'_> {
+    pub fn add(&self, bytes: usize) -> Result<()> {
+        if self.budget.limit.is_none() {
+            return Ok(());
+        }
+        self.charged.fetch_add(bytes, Ordering::Relaxed);
+        let used = self.budget.used.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        self.budget.check(self.operator, used)
+    }
+
+    pub fn add_row(&self, values: &[Value]) -> Result<()> {
+        if self.budget.limit.is_none() {
+            return Ok(());
+        }
+        self.add(row_size(values))
+    }
+
+    /// Charges a materialized copy of an input's rows.
+    pub fn add_rows(&self, rows: &[Record]) -> Result<()> {
+        if self.budget.limit.is_none() {
+            return Ok(());
+        }
+        self.add(rows.iter().map(|row| row_size(row.values())).sum())
+    }
+
+    /// Gives back bytes whose rows were dropped or written to disk.
+    pub fn release(&self, bytes: usize) {
+        if self.budget.limit.is_none() {
+            return;
+        }
+        let bytes = bytes.min(self.charged.load(Ordering::Relaxed));
+        self.charged.fetch_sub(bytes, Ordering::Relaxed);
+        self.budget.used.fetch_sub(bytes, Ordering::Relaxed);
+    }
+
+    /// Bytes currently charged by this tracker.
+    pub fn charged(&self) -> usize {
+        self.charged.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for MemoryTracker<'_> {
+    fn drop(&mut self) {
+        let charged = *self.charged.get_mut();
+        self.budget.used.fetch_sub(charged, Ordering::Relaxed);
+    }
+}
+
+/// Approximate bytes held by a row of values.
+pub(crate) fn row_size(values: &[Value]) -> usize {
+    values.iter().map(value_size).sum()
+}
+
+fn value_size(value: &Value) -> usize {
+    let heap = match value {
+        Value::String(s) | Value::Geography(s) => s.len(),
+        Value::Bytes(b) => b.len(),
+        Value::Array(values) => row_size(values),
+        Value::Struct(fields) => fields
+            .iter()
+            .map(|(name, value)| name.len() + value_size(value))
+            .sum(),
+        Value::Json(json) => json.to_string().len(),
+        _ => 0,
+    };
+    std::mem::size_of::<Value>() + heap
+}
+
+/// The spill partition of `key` among `partitions`.
+pub(crate) fn partition_of<K: Hash + ?Sized>(key: &K, partitions: usize) -> usize {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    (hasher.finish() % partitions as u64) as usize
+}
+
+/// Rows written to a temporary file, which is removed when dropped.
+pub(crate) struct SpillFile {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    rows: usize,
+}
+
+impl SpillFile {
+    pub fn create(dir: &Path) -> Result<Self> {
+        fs::create_dir_all(dir).map_err(spill_error)?;
+        let path = dir.join(format!(
+            "yachtsql-spill-{}-{}",
+            std::process::id(),
+            NEXT_SPILL_FILE.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = File::create(&path).map_err(spill_error)?;
+        Ok(Self {
+            path,
+            writer: BufWriter::new(file),
+            rows: 0,
+        })
+    }
+
+    pub fn push(&mut self, values: &[Value]) -> Result<()> {
+        ciborium::into_writer(values, &mut self.writer).map_err(spill_error)?;
+        self.rows += 1;
+        Ok(())
+    }
+
+    /// Reads the rows back in the order they were written.
+    pub fn read(&mut self) -> Result<SpillReader> {
+        self.writer.flush().map_err(spill_error)?;
+        let file = File::open(&self.path).map_err(spill_error)?;
+        Ok(SpillReader {
+            reader: BufReader::new(file),
+            remaining: self.rows,
+        })
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+pub(crate) struct SpillReader {
+    reader: BufReader<File>,
+    remaining: usize,
+}
+
+impl Iterator for SpillReader {
+    type Item = Result<Vec<Value>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        Some(ciborium::from_reader(&mut self.reader).map_err(spill_error))
+    }
+}
+
+fn spill_error(err: impl std::fmt::Display) -> Error {
+    Error::internal(format!("Spill file error: {}", err))
+}
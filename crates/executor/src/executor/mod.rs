@@ -4,9 +4,13 @@ mod cte;
 mod ddl;
 mod distinct;
 mod dml;
+mod explain;
 mod filter;
 mod join;
+pub(crate) mod join_algorithms;
 mod limit;
+pub(crate) mod memory;
+pub(crate) mod morsel;
 mod project;
 mod qualify;
 mod sample;
@@ -17,6 +21,7 @@ mod sort;
 mod unnest;
 mod values;
 mod window;
+mod window_utils;
 
 use std::collections::HashMap;
 
@@ -107,8 +112,14 @@ impl<'a> PlanExecutor<'a> {
     pub fn execute_plan(&mut self, plan: &PhysicalPlan) -> Result<Table> {
         match plan {
             PhysicalPlan::TableScan {
-                table_name, schema, ..
-            } => self.execute_scan(table_name, schema),
+                table_name,
+                schema,
+                projection,
+                ..
+            } => self.execute_scan(table_name, schema, projection.as_deref()),
+            PhysicalPlan::TimeTravelScan { .. } => Err(Error::unsupported(
+                "FOR SYSTEM_TIME AS OF requires the concurrent executor",
+            )),
             PhysicalPlan::Sample {
                 input,
                 sample_type,
@@ -140,9 +151,38 @@ impl<'a> PlanExecutor<'a> {
                 join_type,
                 left_keys,
                 right_keys,
+                residual,
+                schema,
+                ..
+            } => self.execute_hash_join(
+                left,
+                right,
+                join_type,
+                left_keys,
+                right_keys,
+                residual.as_ref(),
+                schema,
+            ),
+            PhysicalPlan::MergeJoin {
+                left,
+                right,
+                join_type,
+                left_keys,
+                right_keys,
+                comparison,
+                residual,
                 schema,
                 ..
-            } => self.execute_hash_join(left, right, join_type, left_keys, right_keys, schema),
+            } => self.execute_merge_join(
+                left,
+                right,
+                join_type,
+                left_keys,
+                right_keys,
+                *comparison,
+                residual.as_ref(),
+                schema,
+            ),
             PhysicalPlan::HashAggregate {
                 input,
                 group_by,
@@ -234,6 +274,7 @@ impl<'a> PlanExecutor<'a> {
                 if_not_exists,
                 or_replace,
                 query,
+                ..
             } => self.execute_create_table(
                 table_name,
                 columns,
@@ -251,6 +292,16 @@ impl<'a> PlanExecutor<'a> {
                 if_exists,
             } => self.execute_alter_table(table_name, operation, *if_exists),
             PhysicalPlan::Truncate { table_name } => self.execute_truncate(table_name),
+            PhysicalPlan::Analyze { table_name } => self.execute_analyze(table_name),
+            PhysicalPlan::Explain {
+                input,
+                analyze: false,
+                format,
+                schema,
+            } => explain::explain_table(input, None, *format, schema),
+            PhysicalPlan::Explain { analyze: true, .. } => Err(Error::unsupported(
+                "EXPLAIN ANALYZE requires the concurrent executor",
+            )),
             PhysicalPlan::CreateView {
                 name,
                 query: _,
@@ -258,19 +309,22 @@ impl<'a> PlanExecutor<'a> {
                 column_aliases,
                 or_replace,
                 if_not_exists,
+                materialized,
             } => self.execute_create_view(
                 name,
                 query_sql,
                 column_aliases,
                 *or_replace,
                 *if_not_exists,
+                *materialized,
             ),
             PhysicalPlan::DropView { name, if_exists } => self.execute_drop_view(name, *if_exists),
             PhysicalPlan::CreateSchema {
                 name,
                 if_not_exists,
                 or_replace,
-            } => self.execute_create_schema(name, *if_not_exists, *or_replace),
+                options,
+            } => self.execute_create_schema(name, *if_not_exists, *or_replace, options),
             PhysicalPlan::DropSchema {
                 name,
                 if_exists,
@@ -290,6 +344,7 @@ impl<'a> PlanExecutor<'a> {
                 if_not_exists,
                 is_temp,
                 is_aggregate,
+                options,
             } => self.execute_create_function(
                 name,
                 args,
@@ -299,6 +354,7 @@ impl<'a> PlanExecutor<'a> {
                 *if_not_exists,
                 *is_temp,
                 *is_aggregate,
+                options,
             ),
             PhysicalPlan::DropFunction { name, if_exists } => {
                 self.execute_drop_function(name, *if_exists)
@@ -313,6 +369,9 @@ impl<'a> PlanExecutor<'a> {
             PhysicalPlan::DropProcedure { name, if_exists } => {
                 self.execute_drop_procedure(name, *if_exists)
             }
+            PhysicalPlan::CreateIndex { .. } | PhysicalPlan::DropIndex { .. } => {
+                Ok(Table::empty(Schema::new()))
+            }
             PhysicalPlan::Call {
                 procedure_name,
                 args,
@@ -377,10 +436,14 @@ impl<'a> PlanExecutor<'a> {
                 };
                 Err(Error::InvalidQuery(msg))
             }
+            PhysicalPlan::CreateSnapshot { as_of: Some(_), .. } => Err(Error::unsupported(
+                "FOR SYSTEM_TIME AS OF requires the concurrent executor",
+            )),
             PhysicalPlan::CreateSnapshot {
                 snapshot_name,
                 source_name,
                 if_not_exists,
+                as_of: None,
             } => self.execute_create_snapshot(snapshot_name, source_name, *if_not_exists),
             PhysicalPlan::DropSnapshot {
                 snapshot_name,
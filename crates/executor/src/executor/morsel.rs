@@ -0,0 +1,89 @@
+use std::ops::Range;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use yachtsql_common::error::Result;
+use yachtsql_storage::SEGMENT_ROWS;
+
+use crate::cancel::Interrupt;
+
+/// Rows in one unit of parallel work, matching a table segment so that a
+/// table's morsels are its segments.
+pub(crate) const MORSEL_ROWS: usize = SEGMENT_ROWS;
+
+/// Splits `rows` rows into consecutive morsel-sized ranges.
+pub(crate) fn morsel_ranges(rows: usize) -> Vec<Range<usize>> {
+    (0..rows)
+        .step_by(MORSEL_ROWS)
+        .map(|start| start..(start + MORSEL_ROWS).min(rows))
+        .collect()
+}
+
+/// Whether an input of `rows` rows is worth splitting across `threads`.
+pub(crate) fn should_split(rows: usize, threads: usize) -> bool {
+    threads > 1 && rows > MORSEL_ROWS
+}
+
+/// The morsels of `rows` rows when they are worth splitting across
+/// `threads`, otherwise a single range covering all of them.
+pub(crate) fn row_ranges(rows: usize, threads: usize) -> Vec<Range<usize>> {
+    if should_split(rows, threads) {
+        morsel_ranges(rows)
+    } else {
+        std::iter::once(0..rows).collect()
+    }
+}
+
+/// Runs `work` on morsels `0..count` with up to `threads` scoped workers,
+/// each claiming the next unprocessed morsel until none are left. Results
+/// come back in morsel order; the first error, including cancellation or
+/// timeout of the query, stops the remaining work.
+pub(crate) fn run_morsels<T, F>(
+    threads: usize,
+    count: usize,
+    interrupt: &Interrupt,
+    work: F,
+) -> Result<Vec<T>>
+where
+    T: Send,
+    F: Fn(usize) -> Result<T> + Sync,
+{
+    let work = |morsel| {
+        interrupt.check()?;
+        work(morsel)
+    };
+    let workers = threads.min(count);
+    if workers <= 1 {
+        return (0..count).map(work).collect();
+    }
+
+    let next = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    let results: Mutex<Vec<Option<Result<T>>>> = Mutex::new((0..count).map(|_| None).collect());
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                while !failed.load(Ordering::Relaxed) {
+                    let morsel = next.fetch_add(1, Ordering::Relaxed);
+                    if morsel >= count {
+                        break;
+                    }
+                    let result = work(morsel);
+                    if result.is_err() {
+                        failed.store(true, Ordering::Relaxed);
+                    }
+                    results.lock().unwrap()[morsel] = Some(result);
+                }
+            });
+        }
+    });
+
+    let mut output = Vec::with_capacity(count);
+    for result in results.into_inner().unwrap() {
+        match result {
+            Some(result) => output.push(result?),
+            None => break,
+        }
+    }
+    Ok(output)
+}
@@ -6,7 +6,10 @@ use yachtsql_ir::{Expr, SortExpr, WindowFrame};
 use yachtsql_storage::{Record, Schema, Table};
 
 use super::PlanExecutor;
-use super::window::{WindowFuncType, compute_window_function, partition_rows, sort_partition};
+use super::window_utils::{
+    WindowFuncType, compute_window_function, partition_rows, sort_partition,
+};
+use crate::cancel::Interrupt;
 use crate::ir_evaluator::IrEvaluator;
 use crate::plan::PhysicalPlan;
 
@@ -63,6 +66,7 @@ impl<'a> PlanExecutor<'a> {
                     &order_by,
                     &frame,
                     &evaluator,
+                    &Interrupt::default(),
                 )?;
 
                 for (local_idx, row_idx) in indices.iter().enumerate() {
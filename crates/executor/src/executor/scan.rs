@@ -5,9 +5,14 @@ use yachtsql_storage::{Field, FieldMode, Schema, Table};
 use super::PlanExecutor;
 
 impl<'a> PlanExecutor<'a> {
-    pub fn execute_scan(&self, table_name: &str, planned_schema: &PlanSchema) -> Result<Table> {
+    pub fn execute_scan(
+        &self,
+        table_name: &str,
+        planned_schema: &PlanSchema,
+        projection: Option<&[usize]>,
+    ) -> Result<Table> {
         if let Some(cte_table) = self.cte_results.get(table_name) {
-            return Ok(self.apply_planned_schema(cte_table, planned_schema));
+            return Ok(self.apply_planned_schema(cte_table, planned_schema, projection));
         }
 
         let table = self
@@ -15,11 +20,16 @@ impl<'a> PlanExecutor<'a> {
             .get_table(table_name)
             .ok_or_else(|| Error::TableNotFound(table_name.to_string()))?;
 
-        Ok(self.apply_planned_schema(table, planned_schema))
+        Ok(self.apply_planned_schema(table, planned_schema, projection))
     }
 
-    fn apply_planned_schema(&self, source_table: &Table, planned_schema: &PlanSchema) -> Table {
-        if planned_schema.fields.is_empty() {
+    fn apply_planned_schema(
+        &self,
+        source_table: &Table,
+        planned_schema: &PlanSchema,
+        projection: Option<&[usize]>,
+    ) -> Table {
+        if planned_schema.fields.is_empty() && projection.is_none() {
             return source_table.clone();
         }
 
@@ -47,6 +57,9 @@ impl<'a> PlanExecutor<'a> {
             new_schema.add_field(field);
         }
 
-        source_table.with_schema(new_schema)
+        match projection {
+            Some(projection) => source_table.with_reordered_schema(new_schema, projection),
+            None => source_table.with_schema(new_schema),
+        }
     }
 }
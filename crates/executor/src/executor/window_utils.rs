@@ -1,20 +1,20 @@
 use std::collections::HashMap;
+use std::ops::Range;
 
+use ordered_float::OrderedFloat;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
 use yachtsql_common::error::{Error, Result};
 use yachtsql_common::types::Value;
 use yachtsql_ir::{
-    AggregateFunction, Expr, SortExpr, WindowFrame, WindowFrameBound, WindowFunction,
+    AggregateFunction, BinaryOp, Expr, SortExpr, WindowFrame, WindowFrameBound,
+    WindowFrameExclusion, WindowFrameUnit, WindowFunction,
 };
 use yachtsql_storage::Record;
 
+use crate::cancel::Interrupt;
 use crate::ir_evaluator::IrEvaluator;
 
-#[derive(Clone)]
-pub enum WindowFuncType {
-    Window(WindowFunction),
-    Aggregate(AggregateFunction),
-}
-
 pub fn extract_window_spec(
     expr: &Expr,
 ) -> Result<(
@@ -173,7 +173,9 @@ pub fn compute_window_function(
     order_by: &[SortExpr],
     frame: &Option<WindowFrame>,
     evaluator: &IrEvaluator,
+    interrupt: &Interrupt,
 ) -> Result<Vec<Value>> {
+    interrupt.check()?;
     let partition_size = sorted_indices.len();
     let mut results = Vec::with_capacity(partition_size);
 
@@ -294,19 +296,35 @@ pub fn compute_window_function(
                 }
             }
             WindowFunction::FirstValue => {
-                let first_idx = sorted_indices[0];
-                let first_val = extract_window_arg(expr, 0, evaluator, &rows[first_idx])?;
-                results = vec![first_val; partition_size];
+                if let Some(frame) = frame {
+                    results = compute_frame_navigation(
+                        rows,
+                        sorted_indices,
+                        expr,
+                        order_by,
+                        frame,
+                        evaluator,
+                        interrupt,
+                        |frame_rows| frame_rows.first().copied(),
+                    )?;
+                } else {
+                    let first_idx = sorted_indices[0];
+                    let first_val = extract_window_arg(expr, 0, evaluator, &rows[first_idx])?;
+                    results = vec![first_val; partition_size];
+                }
             }
             WindowFunction::LastValue => {
                 if let Some(frame) = frame {
-                    for curr_pos in 0..partition_size {
-                        let end_bound = frame.end.as_ref().unwrap_or(&WindowFrameBound::CurrentRow);
-                        let end_idx = compute_frame_end(end_bound, curr_pos, partition_size);
-                        let actual_idx = sorted_indices[end_idx.min(partition_size - 1)];
-                        let val = extract_window_arg(expr, 0, evaluator, &rows[actual_idx])?;
-                        results.push(val);
-                    }
+                    results = compute_frame_navigation(
+                        rows,
+                        sorted_indices,
+                        expr,
+                        order_by,
+                        frame,
+                        evaluator,
+                        interrupt,
+                        |frame_rows| frame_rows.last().copied(),
+                    )?;
                 } else {
                     let last_idx = sorted_indices[partition_size - 1];
                     let last_val = extract_window_arg(expr, 0, evaluator, &rows[last_idx])?;
@@ -317,13 +335,26 @@ pub fn compute_window_function(
                 let n = extract_window_arg(expr, 1, evaluator, &rows[sorted_indices[0]])?
                     .as_i64()
                     .unwrap_or(1) as usize;
-                let nth_val = if n > 0 && n <= partition_size {
-                    let nth_idx = sorted_indices[n - 1];
-                    extract_window_arg(expr, 0, evaluator, &rows[nth_idx])?
+                if let Some(frame) = frame {
+                    results = compute_frame_navigation(
+                        rows,
+                        sorted_indices,
+                        expr,
+                        order_by,
+                        frame,
+                        evaluator,
+                        interrupt,
+                        |frame_rows| n.checked_sub(1).and_then(|i| frame_rows.get(i).copied()),
+                    )?;
                 } else {
-                    Value::Null
-                };
-                results = vec![nth_val; partition_size];
+                    let nth_val = if n > 0 && n <= partition_size {
+                        let nth_idx = sorted_indices[n - 1];
+                        extract_window_arg(expr, 0, evaluator, &rows[nth_idx])?
+                    } else {
+                        Value::Null
+                    };
+                    results = vec![nth_val; partition_size];
+                }
             }
             WindowFunction::PercentRank => {
                 if partition_size <= 1 {
@@ -355,6 +386,7 @@ pub fn compute_window_function(
                 let mut prev_values: Option<Vec<Value>> = None;
                 let mut count_less_or_equal = 0usize;
                 for (i, &idx) in sorted_indices.iter().enumerate() {
+                    interrupt.check_row(i)?;
                     let curr_values: Vec<Value> = order_by
                         .iter()
                         .map(|ob| {
@@ -391,15 +423,11 @@ pub fn compute_window_function(
             let has_order_by = !order_by.is_empty();
 
             if let Some(frame) = frame {
+                let frames =
+                    PartitionFrames::new(rows, sorted_indices, order_by, frame, evaluator)?;
                 for curr_pos in 0..partition_size {
-                    let start_idx = compute_frame_start(&frame.start, curr_pos, partition_size);
-                    let end_bound = frame.end.as_ref().unwrap_or(&WindowFrameBound::CurrentRow);
-                    let end_idx = compute_frame_end(end_bound, curr_pos, partition_size);
-                    let frame_indices: Vec<usize> = if start_idx <= end_idx {
-                        sorted_indices[start_idx..=end_idx].to_vec()
-                    } else {
-                        vec![]
-                    };
+                    interrupt.check_row(curr_pos)?;
+                    let frame_indices = frames.frame_rows(curr_pos);
                     let agg_result =
                         compute_aggregate(func, expr, rows, &frame_indices, evaluator)?;
                     results.push(agg_result);
@@ -429,7 +457,8 @@ pub fn compute_window_function(
                 }
                 peer_groups.push((group_start, partition_size - 1));
 
-                for (group_start, group_end) in &peer_groups {
+                for (n, (group_start, group_end)) in peer_groups.iter().enumerate() {
+                    interrupt.check_row(n)?;
                     let running_indices: Vec<usize> = sorted_indices[..=*group_end].to_vec();
                     let agg_result =
                         compute_aggregate(func, expr, rows, &running_indices, evaluator)?;
@@ -447,7 +476,7 @@ pub fn compute_window_function(
     Ok(results)
 }
 
-pub fn extract_window_arg(
+fn extract_window_arg(
     expr: &Expr,
     n: usize,
     evaluator: &IrEvaluator,
@@ -465,31 +494,345 @@ pub fn extract_window_arg(
     }
 }
 
-pub fn compute_frame_start(
+fn compute_frame_navigation(
+    rows: &[Record],
+    sorted_indices: &[usize],
+    expr: &Expr,
+    order_by: &[SortExpr],
+    frame: &WindowFrame,
+    evaluator: &IrEvaluator,
+    interrupt: &Interrupt,
+    pick: impl Fn(&[usize]) -> Option<usize>,
+) -> Result<Vec<Value>> {
+    let frames = PartitionFrames::new(rows, sorted_indices, order_by, frame, evaluator)?;
+    (0..sorted_indices.len())
+        .map(|curr_pos| {
+            interrupt.check_row(curr_pos)?;
+            match pick(&frames.frame_rows(curr_pos)) {
+                Some(idx) => extract_window_arg(expr, 0, evaluator, &rows[idx]),
+                None => Ok(Value::Null),
+            }
+        })
+        .collect()
+}
+
+enum ResolvedBound {
+    UnboundedPreceding,
+    Preceding(Value),
+    CurrentRow,
+    Following(Value),
+    UnboundedFollowing,
+}
+
+/// The frame of every row of a sorted partition, as inclusive position spans
+/// into the partition plus the peer groups needed to apply `EXCLUDE`.
+pub struct PartitionFrames<'a> {
+    sorted_indices: &'a [usize],
+    spans: Vec<Option<(usize, usize)>>,
+    peer_groups: Vec<(usize, usize)>,
+    group_of: Vec<usize>,
+    exclusion: Option<WindowFrameExclusion>,
+}
+
+impl<'a> PartitionFrames<'a> {
+    pub fn new(
+        rows: &[Record],
+        sorted_indices: &'a [usize],
+        order_by: &[SortExpr],
+        frame: &WindowFrame,
+        evaluator: &IrEvaluator,
+    ) -> Result<Self> {
+        let partition_size = sorted_indices.len();
+        let keys: Vec<Vec<Value>> = sorted_indices
+            .iter()
+            .map(|&idx| {
+                order_by
+                    .iter()
+                    .map(|ob| {
+                        evaluator
+                            .evaluate(&ob.expr, &rows[idx])
+                            .unwrap_or(Value::Null)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut peer_groups: Vec<(usize, usize)> = Vec::new();
+        let mut group_of = Vec::with_capacity(partition_size);
+        for pos in 0..partition_size {
+            if pos == 0 || keys[pos] != keys[pos - 1] {
+                peer_groups.push((pos, pos));
+            }
+            let last = peer_groups.len() - 1;
+            peer_groups[last].1 = pos;
+            group_of.push(last);
+        }
+
+        let mut frames = Self {
+            sorted_indices,
+            spans: Vec::with_capacity(partition_size),
+            peer_groups,
+            group_of,
+            exclusion: frame.exclusion,
+        };
+        if partition_size == 0 {
+            return Ok(frames);
+        }
+
+        let record = &rows[sorted_indices[0]];
+        let start = resolve_bound(&frame.start, frame.unit, record, evaluator)?;
+        let end = match &frame.end {
+            Some(bound) => resolve_bound(bound, frame.unit, record, evaluator)?,
+            None => ResolvedBound::CurrentRow,
+        };
+
+        let has_offset = |bound: &ResolvedBound| {
+            matches!(
+                bound,
+                ResolvedBound::Preceding(_) | ResolvedBound::Following(_)
+            )
+        };
+        if frame.unit == WindowFrameUnit::Range
+            && (has_offset(&start) || has_offset(&end))
+            && order_by.len() != 1
+        {
+            return Err(Error::invalid_query(
+                "RANGE frame with an offset requires exactly one ORDER BY expression",
+            ));
+        }
+
+        let range_keys = (order_by.len() == 1).then(|| {
+            let keys: Vec<Value> = keys.iter().map(|k| k[0].clone()).collect();
+            let non_null_start = keys.iter().position(|k| !k.is_null()).unwrap_or(0);
+            let non_null_end = keys.iter().rposition(|k| !k.is_null()).map_or(0, |p| p + 1);
+            RangeKeys {
+                keys,
+                non_null: non_null_start..non_null_end,
+                asc: order_by[0].asc,
+            }
+        });
+
+        for pos in 0..partition_size {
+            let (first, last) = match frame.unit {
+                WindowFrameUnit::Rows => (
+                    frames.rows_edge(&start, pos, true),
+                    frames.rows_edge(&end, pos, false),
+                ),
+                WindowFrameUnit::Groups => (
+                    frames.groups_edge(&start, pos, true),
+                    frames.groups_edge(&end, pos, false),
+                ),
+                WindowFrameUnit::Range => (
+                    frames.range_edge(&start, pos, true, range_keys.as_ref(), evaluator)?,
+                    frames.range_edge(&end, pos, false, range_keys.as_ref(), evaluator)?,
+                ),
+            };
+            let first = first.max(0);
+            let last = last.min(partition_size as i64 - 1);
+            frames.spans.push(if first <= last {
+                Some((first as usize, last as usize))
+            } else {
+                None
+            });
+        }
+
+        Ok(frames)
+    }
+
+    /// Row indices in the frame of the row at `curr_pos`, in partition order.
+    pub fn frame_rows(&self, curr_pos: usize) -> Vec<usize> {
+        let Some((first, last)) = self.spans[curr_pos] else {
+            return Vec::new();
+        };
+        let (group_start, group_end) = self.peer_groups[self.group_of[curr_pos]];
+        let is_peer = |pos: usize| pos >= group_start && pos <= group_end;
+        (first..=last)
+            .filter(|&pos| match self.exclusion {
+                Some(WindowFrameExclusion::CurrentRow) => pos != curr_pos,
+                Some(WindowFrameExclusion::Group) => !is_peer(pos),
+                Some(WindowFrameExclusion::Ties) => pos == curr_pos || !is_peer(pos),
+                Some(WindowFrameExclusion::NoOthers) | None => true,
+            })
+            .map(|pos| self.sorted_indices[pos])
+            .collect()
+    }
+
+    fn unbounded_edge(&self, bound: &ResolvedBound, is_start: bool) -> Option<i64> {
+        let partition_size = self.sorted_indices.len() as i64;
+        match bound {
+            ResolvedBound::UnboundedPreceding if is_start => Some(0),
+            ResolvedBound::UnboundedPreceding => Some(-1),
+            ResolvedBound::UnboundedFollowing if is_start => Some(partition_size),
+            ResolvedBound::UnboundedFollowing => Some(partition_size - 1),
+            _ => None,
+        }
+    }
+
+    fn rows_edge(&self, bound: &ResolvedBound, pos: usize, is_start: bool) -> i64 {
+        if let Some(edge) = self.unbounded_edge(bound, is_start) {
+            return edge;
+        }
+        let pos = pos as i64;
+        match bound {
+            ResolvedBound::Preceding(offset) => pos.saturating_sub(offset_rows(offset)),
+            ResolvedBound::Following(offset) => pos.saturating_add(offset_rows(offset)),
+            _ => pos,
+        }
+    }
+
+    fn groups_edge(&self, bound: &ResolvedBound, pos: usize, is_start: bool) -> i64 {
+        if let Some(edge) = self.unbounded_edge(bound, is_start) {
+            return edge;
+        }
+        let group = self.group_of[pos] as i64;
+        let target = match bound {
+            ResolvedBound::Preceding(offset) => group.saturating_sub(offset_rows(offset)),
+            ResolvedBound::Following(offset) => group.saturating_add(offset_rows(offset)),
+            _ => group,
+        };
+        if target < 0 {
+            return if is_start { 0 } else { -1 };
+        }
+        match self.peer_groups.get(target as usize) {
+            Some(&(group_start, _)) if is_start => group_start as i64,
+            Some(&(_, group_end)) => group_end as i64,
+            None if is_start => self.sorted_indices.len() as i64,
+            None => self.sorted_indices.len() as i64 - 1,
+        }
+    }
+
+    fn range_edge(
+        &self,
+        bound: &ResolvedBound,
+        pos: usize,
+        is_start: bool,
+        range_keys: Option<&RangeKeys>,
+        evaluator: &IrEvaluator,
+    ) -> Result<i64> {
+        if let Some(edge) = self.unbounded_edge(bound, is_start) {
+            return Ok(edge);
+        }
+        let (group_start, group_end) = self.peer_groups[self.group_of[pos]];
+        let peer_edge = if is_start { group_start } else { group_end } as i64;
+        let (offset, preceding) = match bound {
+            ResolvedBound::Preceding(offset) => (offset, true),
+            ResolvedBound::Following(offset) => (offset, false),
+            _ => return Ok(peer_edge),
+        };
+        let Some(RangeKeys {
+            keys,
+            non_null,
+            asc,
+        }) = range_keys
+        else {
+            return Ok(peer_edge);
+        };
+        let key = &keys[pos];
+        if key.is_null() {
+            return Ok(peer_edge);
+        }
+
+        let op = if preceding == *asc {
+            BinaryOp::Sub
+        } else {
+            BinaryOp::Add
+        };
+        let target =
+            evaluator.eval_binary_op_with_values(op, key.clone(), coerce_offset(key, offset))?;
+
+        let candidates = &keys[non_null.clone()];
+        Ok(if is_start {
+            let before = candidates.partition_point(|k| {
+                let ordering = k.cmp(&target);
+                if *asc {
+                    ordering.is_lt()
+                } else {
+                    ordering.is_gt()
+                }
+            });
+            (non_null.start + before) as i64
+        } else {
+            let not_after = candidates.partition_point(|k| {
+                let ordering = k.cmp(&target);
+                if *asc {
+                    ordering.is_le()
+                } else {
+                    ordering.is_ge()
+                }
+            });
+            (non_null.start + not_after) as i64 - 1
+        })
+    }
+}
+
+/// The single `ORDER BY` key of a `RANGE` frame, with the span of non-NULL
+/// keys that offsets are measured against.
+struct RangeKeys {
+    keys: Vec<Value>,
+    non_null: Range<usize>,
+    asc: bool,
+}
+
+fn resolve_bound(
     bound: &WindowFrameBound,
-    curr_pos: usize,
-    _partition_size: usize,
-) -> usize {
-    match bound {
-        WindowFrameBound::Preceding(None) => 0,
-        WindowFrameBound::Preceding(Some(n)) => curr_pos.saturating_sub(*n as usize),
-        WindowFrameBound::CurrentRow => curr_pos,
-        WindowFrameBound::Following(Some(n)) => curr_pos + *n as usize,
-        WindowFrameBound::Following(None) => curr_pos,
+    unit: WindowFrameUnit,
+    record: &Record,
+    evaluator: &IrEvaluator,
+) -> Result<ResolvedBound> {
+    let offset = match bound {
+        WindowFrameBound::CurrentRow => return Ok(ResolvedBound::CurrentRow),
+        WindowFrameBound::Preceding(None) => return Ok(ResolvedBound::UnboundedPreceding),
+        WindowFrameBound::Following(None) => return Ok(ResolvedBound::UnboundedFollowing),
+        WindowFrameBound::Preceding(Some(offset)) | WindowFrameBound::Following(Some(offset)) => {
+            evaluator.evaluate(offset, record)?
+        }
+    };
+    let negative = match (&offset, unit) {
+        (Value::Null, _) => {
+            return Err(Error::invalid_query("Window frame offset cannot be NULL"));
+        }
+        (Value::Int64(n), _) => *n < 0,
+        (_, WindowFrameUnit::Rows | WindowFrameUnit::Groups) => {
+            return Err(Error::invalid_query(format!(
+                "ROWS and GROUPS frame offsets must be INT64, got {:?}",
+                offset
+            )));
+        }
+        (Value::Float64(f), _) => f.0 < 0.0,
+        (Value::Numeric(d), _) => d.is_sign_negative() && !d.is_zero(),
+        (Value::Interval(i), _) => i.months < 0 || i.days < 0 || i.nanos < 0,
+        _ => false,
+    };
+    if negative {
+        return Err(Error::invalid_query(format!(
+            "Window frame offset cannot be negative, got {:?}",
+            offset
+        )));
     }
+    Ok(match bound {
+        WindowFrameBound::Preceding(_) => ResolvedBound::Preceding(offset),
+        _ => ResolvedBound::Following(offset),
+    })
 }
 
-pub fn compute_frame_end(bound: &WindowFrameBound, curr_pos: usize, partition_size: usize) -> usize {
-    match bound {
-        WindowFrameBound::Preceding(None) => 0,
-        WindowFrameBound::Preceding(Some(n)) => curr_pos.saturating_sub(*n as usize),
-        WindowFrameBound::CurrentRow => curr_pos,
-        WindowFrameBound::Following(Some(n)) => (curr_pos + *n as usize).min(partition_size - 1),
-        WindowFrameBound::Following(None) => partition_size - 1,
+fn offset_rows(offset: &Value) -> i64 {
+    offset.as_i64().unwrap_or(0)
+}
+
+fn coerce_offset(key: &Value, offset: &Value) -> Value {
+    match (key, offset) {
+        (Value::Numeric(_), Value::Int64(n)) => Value::Numeric(Decimal::from(*n)),
+        (Value::Numeric(_), Value::Float64(f)) => {
+            Decimal::from_f64(f.0).map_or_else(|| offset.clone(), Value::Numeric)
+        }
+        (Value::Float64(_), Value::Numeric(d)) => d
+            .to_f64()
+            .map_or_else(|| offset.clone(), |f| Value::Float64(OrderedFloat(f))),
+        _ => offset.clone(),
     }
 }
 
-pub fn compute_aggregate(
+fn compute_aggregate(
     func: &AggregateFunction,
     expr: &Expr,
     rows: &[Record],
@@ -566,3 +909,9 @@ pub fn compute_aggregate(
         _ => Ok(Value::Null),
     }
 }
+
+#[derive(Clone)]
+pub enum WindowFuncType {
+    Window(WindowFunction),
+    Aggregate(AggregateFunction),
+}
@@ -0,0 +1,583 @@
+//! `INFORMATION_SCHEMA` views beyond the core metadata views: snapshots,
+//! the PostgreSQL-style constraint views, options, search and vector
+//! indexes, storage, and the empty views over state this engine lacks.
+
+use yachtsql_common::types::{DataType, Value};
+use yachtsql_ir::{ConstraintType, Expr, IndexKind};
+use yachtsql_storage::{Field, Table};
+
+use super::{
+    CATALOG_NAME, InformationSchema, Relation, build, collect_check_columns, option_type,
+    optional_string, routine_prefix, split_routine_name, string_fields,
+};
+use crate::catalog::TableIndex;
+
+pub(super) fn view(views: &InformationSchema<'_>, name: &str) -> Option<Table> {
+    let table = match name {
+        "TABLE_SNAPSHOTS" => views.table_snapshots(),
+        "CHECK_CONSTRAINTS" => views.check_constraints(),
+        "REFERENTIAL_CONSTRAINTS" => views.referential_constraints(),
+        "CONSTRAINT_COLUMN_USAGE" => views.constraint_column_usage(),
+        "SCHEMATA_OPTIONS" => views.schemata_options(),
+        "ROUTINE_OPTIONS" => views.routine_options(),
+        "MATERIALIZED_VIEWS" => views.materialized_views(),
+        "SEARCH_INDEXES" => views.indexes(IndexKind::Search),
+        "SEARCH_INDEX_COLUMNS" => views.index_columns(IndexKind::Search),
+        "SEARCH_INDEX_OPTIONS" => views.index_options(IndexKind::Search),
+        "VECTOR_INDEXES" => views.indexes(IndexKind::Vector),
+        "VECTOR_INDEX_COLUMNS" => views.index_columns(IndexKind::Vector),
+        "VECTOR_INDEX_OPTIONS" => views.index_options(IndexKind::Vector),
+        "TABLE_STORAGE"
+        | "TABLE_STORAGE_BY_PROJECT"
+        | "TABLE_STORAGE_BY_FOLDER"
+        | "TABLE_STORAGE_BY_ORGANIZATION" => views.table_storage(),
+        name => empty_view(name)?,
+    };
+    Some(table)
+}
+
+impl InformationSchema<'_> {
+    fn table_snapshots(&self) -> Table {
+        let mut fields = string_fields(&[
+            "table_catalog",
+            "table_schema",
+            "table_name",
+            "base_table_catalog",
+            "base_table_schema",
+            "base_table_name",
+        ]);
+        fields.push(Field::nullable("snapshot_time", DataType::Timestamp));
+        let split = |name: &str| match name.rsplit_once('.') {
+            Some((schema, table)) => (Value::string(schema), Value::string(table)),
+            None => (Value::null(), Value::string(name)),
+        };
+        let mut snapshots = self.catalog.table_snapshots();
+        snapshots.sort_by(|a, b| a.table_name.cmp(&b.table_name));
+        let rows = snapshots
+            .into_iter()
+            .filter(|info| self.in_dataset(info.table_name.rsplit_once('.').map(|(s, _)| s)))
+            .map(|info| {
+                let (table_schema, table_name) = split(&info.table_name);
+                let (base_schema, base_name) = split(&info.base_table_name);
+                vec![
+                    Value::string(CATALOG_NAME),
+                    table_schema,
+                    table_name,
+                    Value::string(CATALOG_NAME),
+                    base_schema,
+                    base_name,
+                    Value::timestamp(info.snapshot_time),
+                ]
+            })
+            .collect();
+        build(fields, rows)
+    }
+
+    /// Tables created without a dataset report `public` as their constraint
+    /// schema, matching the PostgreSQL-style constraint names.
+    fn check_constraints(&self) -> Table {
+        let fields = string_fields(&[
+            "constraint_catalog",
+            "constraint_schema",
+            "constraint_name",
+            "check_clause",
+        ]);
+        let rows = self
+            .named_constraints()
+            .into_iter()
+            .filter_map(
+                |(relation, name, constraint)| match constraint.constraint_type {
+                    ConstraintType::Check { expr } => Some(vec![
+                        Value::string(CATALOG_NAME),
+                        Value::string(relation.schema_name.as_deref().unwrap_or("public")),
+                        Value::string(name),
+                        Value::string(check_clause(&expr)),
+                    ]),
+                    _ => None,
+                },
+            )
+            .collect();
+        build(fields, rows)
+    }
+
+    /// Foreign keys with the primary key or unique constraint they reference.
+    fn referential_constraints(&self) -> Table {
+        let fields = string_fields(&[
+            "constraint_catalog",
+            "constraint_schema",
+            "constraint_name",
+            "unique_constraint_catalog",
+            "unique_constraint_schema",
+            "unique_constraint_name",
+            "match_option",
+            "update_rule",
+            "delete_rule",
+        ]);
+        let constraints = self.named_constraints();
+        let mut rows = Vec::new();
+        for (relation, name, constraint) in &constraints {
+            let ConstraintType::ForeignKey {
+                references_table,
+                references_columns,
+                ..
+            } = &constraint.constraint_type
+            else {
+                continue;
+            };
+            let referenced = constraints.iter().find(|(target, _, c)| {
+                target.refers_to(references_table)
+                    && match &c.constraint_type {
+                        ConstraintType::PrimaryKey { columns }
+                        | ConstraintType::Unique { columns } => {
+                            same_columns(columns, references_columns)
+                        }
+                        _ => false,
+                    }
+            });
+            rows.push(vec![
+                Value::string(CATALOG_NAME),
+                optional_string(relation.schema_name.as_deref()),
+                Value::string(name),
+                Value::string(CATALOG_NAME),
+                optional_string(
+                    referenced.and_then(|(target, _, _)| target.schema_name.as_deref()),
+                ),
+                optional_string(referenced.map(|(_, name, _)| name.as_str())),
+                Value::string("NONE"),
+                Value::string("NO ACTION"),
+                Value::string("NO ACTION"),
+            ]);
+        }
+        build(fields, rows)
+    }
+
+    /// Columns used by each constraint. Foreign keys list the referenced
+    /// columns, as in the SQL standard.
+    fn constraint_column_usage(&self) -> Table {
+        let fields = string_fields(&[
+            "table_catalog",
+            "table_schema",
+            "table_name",
+            "column_name",
+            "constraint_catalog",
+            "constraint_schema",
+            "constraint_name",
+        ]);
+        let mut rows = Vec::new();
+        for (relation, name, constraint) in self.named_constraints() {
+            let (table, columns) = match &constraint.constraint_type {
+                ConstraintType::PrimaryKey { columns } | ConstraintType::Unique { columns } => {
+                    (relation.prefix(), columns.clone())
+                }
+                ConstraintType::ForeignKey {
+                    references_table,
+                    references_columns,
+                    ..
+                } => {
+                    let target = Relation::new(references_table.to_uppercase());
+                    (target.prefix(), references_columns.clone())
+                }
+                ConstraintType::Check { expr } => {
+                    let mut columns = Vec::new();
+                    if !collect_check_columns(expr, &mut columns) {
+                        continue;
+                    }
+                    (relation.prefix(), columns)
+                }
+            };
+            for column in columns {
+                let mut row = table.clone();
+                row.push(Value::string(column));
+                row.extend([
+                    Value::string(CATALOG_NAME),
+                    optional_string(relation.schema_name.as_deref()),
+                    Value::string(&name),
+                ]);
+                rows.push(row);
+            }
+        }
+        build(fields, rows)
+    }
+
+    fn schemata_options(&self) -> Table {
+        let fields = string_fields(&[
+            "catalog_name",
+            "schema_name",
+            "option_name",
+            "option_type",
+            "option_value",
+        ]);
+        let mut names = self.catalog.schema_names();
+        names.sort();
+        let mut rows = Vec::new();
+        for name in names {
+            if name.starts_with('_') || !self.in_dataset(Some(&name)) {
+                continue;
+            }
+            let mut options: Vec<(String, String)> = self
+                .catalog
+                .schema_options(&name)
+                .into_iter()
+                .filter(|(key, _)| key != "default_collate")
+                .collect();
+            options.sort();
+            for (option, value) in options {
+                rows.push(vec![
+                    Value::string(CATALOG_NAME),
+                    Value::string(name.to_lowercase()),
+                    Value::string(option),
+                    Value::string(option_type(&value)),
+                    Value::string(value),
+                ]);
+            }
+        }
+        build(fields, rows)
+    }
+
+    fn routine_options(&self) -> Table {
+        let fields = string_fields(&[
+            "specific_catalog",
+            "specific_schema",
+            "specific_name",
+            "option_name",
+            "option_type",
+            "option_value",
+        ]);
+        let mut rows = Vec::new();
+        for function in self.functions() {
+            let (schema_name, name) = split_routine_name(&function.name);
+            if !self.in_dataset(schema_name.as_deref()) {
+                continue;
+            }
+            for (option, value) in &function.options {
+                let mut row = routine_prefix(schema_name.as_deref(), &name);
+                row.extend([
+                    Value::string(option),
+                    Value::string(option_type(value)),
+                    Value::string(value),
+                ]);
+                rows.push(row);
+            }
+        }
+        build(fields, rows)
+    }
+
+    fn materialized_views(&self) -> Table {
+        let mut fields = string_fields(&[
+            "table_catalog",
+            "table_schema",
+            "table_name",
+            "view_definition",
+        ]);
+        fields.push(Field::nullable("last_refresh_time", DataType::Timestamp));
+        let rows = self
+            .view_defs()
+            .into_iter()
+            .filter(|(_, view)| view.materialized)
+            .map(|(relation, view)| {
+                let mut row = relation.prefix();
+                row.push(Value::string(view.query));
+                row.push(Value::null());
+                row
+            })
+            .collect();
+        build(fields, rows)
+    }
+
+    fn table_indexes(&self, kind: IndexKind) -> Vec<(Relation, TableIndex)> {
+        let mut indexes = Vec::new();
+        for (relation, _) in self.base_tables() {
+            for index in self.catalog.get_table_indexes(&relation.key) {
+                if index.kind == kind {
+                    indexes.push((Relation::new(relation.key.clone()), index));
+                }
+            }
+        }
+        indexes
+    }
+
+    /// Indexes are kept as metadata only and are never built, so every index
+    /// reports itself as `ACTIVE` with full coverage.
+    fn indexes(&self, kind: IndexKind) -> Table {
+        let mut fields = string_fields(&[
+            "index_catalog",
+            "index_schema",
+            "table_name",
+            "index_name",
+            "index_status",
+            "ddl",
+        ]);
+        fields.push(Field::nullable("coverage_percentage", DataType::Int64));
+        let rows = self
+            .table_indexes(kind)
+            .into_iter()
+            .map(|(relation, index)| {
+                let keyword = match kind {
+                    IndexKind::Search => "SEARCH",
+                    IndexKind::Vector => "VECTOR",
+                };
+                let mut ddl = format!(
+                    "CREATE {} INDEX {} ON {}({})",
+                    keyword,
+                    index.name,
+                    relation.qualified_name(),
+                    index.columns.join(", ")
+                );
+                if !index.options.is_empty() {
+                    let options: Vec<String> = index
+                        .options
+                        .iter()
+                        .map(|(name, value)| format!("{}={}", name, value))
+                        .collect();
+                    ddl.push_str(&format!(" OPTIONS({})", options.join(", ")));
+                }
+                vec![
+                    Value::string(CATALOG_NAME),
+                    optional_string(relation.schema_name.as_deref()),
+                    Value::string(relation.name),
+                    Value::string(index.name),
+                    Value::string("ACTIVE"),
+                    Value::string(ddl),
+                    Value::int64(100),
+                ]
+            })
+            .collect();
+        build(fields, rows)
+    }
+
+    fn index_columns(&self, kind: IndexKind) -> Table {
+        let fields = string_fields(&[
+            "index_catalog",
+            "index_schema",
+            "table_name",
+            "index_name",
+            "column_name",
+            "index_field_path",
+        ]);
+        let mut rows = Vec::new();
+        for (relation, index) in self.table_indexes(kind) {
+            for column in &index.columns {
+                rows.push(vec![
+                    Value::string(CATALOG_NAME),
+                    optional_string(relation.schema_name.as_deref()),
+                    Value::string(&relation.name),
+                    Value::string(&index.name),
+                    Value::string(column),
+                    Value::string(column),
+                ]);
+            }
+        }
+        build(fields, rows)
+    }
+
+    fn index_options(&self, kind: IndexKind) -> Table {
+        let fields = string_fields(&[
+            "index_catalog",
+            "index_schema",
+            "table_name",
+            "index_name",
+            "option_name",
+            "option_type",
+            "option_value",
+        ]);
+        let mut rows = Vec::new();
+        for (relation, index) in self.table_indexes(kind) {
+            for (option, value) in &index.options {
+                rows.push(vec![
+                    Value::string(CATALOG_NAME),
+                    optional_string(relation.schema_name.as_deref()),
+                    Value::string(&relation.name),
+                    Value::string(&index.name),
+                    Value::string(option),
+                    Value::string(option_type(value)),
+                    Value::string(value),
+                ]);
+            }
+        }
+        build(fields, rows)
+    }
+
+    /// Logical bytes are the segment sizes recorded when the table's latest
+    /// version was committed, NULL for session temp tables. Nothing is stored
+    /// compressed or moved to long-term storage, so physical bytes equal
+    /// logical bytes and all of it is active.
+    fn table_storage(&self) -> Table {
+        let mut fields = string_fields(&["table_catalog", "table_schema", "table_name"]);
+        for name in [
+            "total_rows",
+            "total_partitions",
+            "total_logical_bytes",
+            "active_logical_bytes",
+            "long_term_logical_bytes",
+            "total_physical_bytes",
+        ] {
+            fields.push(Field::nullable(name, DataType::Int64));
+        }
+        fields.push(Field::nullable("deleted", DataType::Bool));
+        fields.push(Field::nullable(
+            "storage_last_modified_time",
+            DataType::Timestamp,
+        ));
+        let rows = self
+            .base_tables()
+            .into_iter()
+            .map(|(relation, handle)| {
+                let bytes = || {
+                    self.catalog
+                        .stored_bytes(&relation.key)
+                        .map_or_else(Value::null, |bytes| Value::int64(bytes as i64))
+                };
+                let mut row = relation.prefix();
+                row.extend([
+                    Value::int64(handle.read().row_count() as i64),
+                    Value::int64(1),
+                    bytes(),
+                    bytes(),
+                    Value::int64(0),
+                    bytes(),
+                    Value::bool_val(false),
+                    self.catalog
+                        .last_modified(&relation.key)
+                        .map_or_else(Value::null, Value::timestamp),
+                ]);
+                row
+            })
+            .collect();
+        build(fields, rows)
+    }
+}
+
+/// CHECK clauses are reported parenthesized, as PostgreSQL does. Binary
+/// operators already print their own parentheses.
+fn check_clause(expr: &Expr) -> String {
+    match expr {
+        Expr::BinaryOp { .. } => expr.to_string(),
+        _ => format!("({})", expr),
+    }
+}
+
+fn same_columns(a: &[String], b: &[String]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x.eq_ignore_ascii_case(y))
+}
+
+/// Views over jobs, sessions, reservations, privileges and other state this
+/// engine does not have. They exist so that queries against them plan, and
+/// always return no rows.
+fn empty_view(name: &str) -> Option<Table> {
+    let fields: &[&str] = match name {
+        "DOMAINS" => &[
+            "domain_catalog",
+            "domain_schema",
+            "domain_name",
+            "data_type",
+        ],
+        "CHARACTER_SETS" => &[
+            "character_set_catalog",
+            "character_set_schema",
+            "character_set_name",
+            "default_collate_name",
+        ],
+        "COLLATIONS" => &[
+            "collation_catalog",
+            "collation_schema",
+            "collation_name",
+            "pad_attribute",
+        ],
+        "ENABLED_ROLES" => &["role_name"],
+        "APPLICABLE_ROLES" => &["grantee", "role_name", "is_grantable"],
+        "TABLE_PRIVILEGES" => &[
+            "grantor",
+            "grantee",
+            "table_catalog",
+            "table_schema",
+            "table_name",
+            "privilege_type",
+        ],
+        "COLUMN_PRIVILEGES" => &[
+            "grantor",
+            "grantee",
+            "table_catalog",
+            "table_schema",
+            "table_name",
+            "column_name",
+            "privilege_type",
+        ],
+        "USAGE_PRIVILEGES" => &[
+            "grantor",
+            "grantee",
+            "object_catalog",
+            "object_schema",
+            "object_name",
+            "object_type",
+        ],
+        "OBJECT_PRIVILEGES" => &[
+            "object_catalog",
+            "object_schema",
+            "object_name",
+            "object_type",
+            "privilege_type",
+            "grantee",
+        ],
+        "SESSIONS" | "SESSIONS_BY_USER" | "SESSIONS_BY_PROJECT" => {
+            &["session_id", "user_email", "project_id", "is_active"]
+        }
+        "JOBS"
+        | "JOBS_BY_USER"
+        | "JOBS_BY_PROJECT"
+        | "JOBS_BY_FOLDER"
+        | "JOBS_BY_ORGANIZATION"
+        | "JOBS_TIMELINE"
+        | "JOBS_TIMELINE_BY_USER"
+        | "JOBS_TIMELINE_BY_PROJECT"
+        | "JOBS_TIMELINE_BY_FOLDER"
+        | "JOBS_TIMELINE_BY_ORGANIZATION" => &[
+            "job_id",
+            "user_email",
+            "project_id",
+            "job_type",
+            "state",
+            "query",
+        ],
+        "STREAMING_TIMELINE"
+        | "STREAMING_TIMELINE_BY_PROJECT"
+        | "STREAMING_TIMELINE_BY_FOLDER"
+        | "STREAMING_TIMELINE_BY_ORGANIZATION" => {
+            &["project_id", "dataset_id", "table_id", "error_code"]
+        }
+        "WRITE_API_TIMELINE" | "WRITE_API_TIMELINE_BY_PROJECT" => &[
+            "project_id",
+            "dataset_id",
+            "table_id",
+            "stream_type",
+            "error_code",
+        ],
+        "RESERVATIONS" | "RESERVATIONS_BY_PROJECT" => {
+            &["project_id", "reservation_name", "edition"]
+        }
+        "CAPACITY_COMMITMENTS" | "CAPACITY_COMMITMENTS_BY_PROJECT" => &[
+            "project_id",
+            "capacity_commitment_id",
+            "commitment_plan",
+            "state",
+        ],
+        "ASSIGNMENTS" | "ASSIGNMENTS_BY_PROJECT" => &[
+            "project_id",
+            "assignment_id",
+            "reservation_name",
+            "job_type",
+        ],
+        "BI_CAPACITIES" => &["project_id", "bi_capacity_name"],
+        "SCHEMATA_LINKS" => &["catalog_name", "schema_name", "linked_schema_name"],
+        "SCHEMATA_REPLICAS" => &["catalog_name", "schema_name", "replica_name", "location"],
+        "INSIGHTS" | "RECOMMENDATIONS" => &["recommendation_id", "recommender", "subtype"],
+        "PROJECT_OPTIONS" | "EFFECTIVE_PROJECT_OPTIONS" => &[
+            "option_name",
+            "option_description",
+            "option_type",
+            "option_value",
+        ],
+        "SHARED_DATASET_USAGE" => &["project_id", "dataset_id", "table_id", "job_id"],
+        _ => return None,
+    };
+    Some(build(string_fields(fields), Vec::new()))
+}
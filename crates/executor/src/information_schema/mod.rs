@@ -0,0 +1,870 @@
+//! `INFORMATION_SCHEMA` views. Each one is built on demand from the catalog
+//! whenever a query reads it, optionally restricted to a single dataset.
+
+mod extended;
+
+use yachtsql_common::types::{DataType, Value};
+use yachtsql_ir::{ConstraintType, Expr, FunctionBody, ProcedureArgMode, TableConstraint};
+use yachtsql_parser::{CatalogProvider, FunctionDefinition, ViewDefinition};
+use yachtsql_storage::{Field, FieldMode, Schema, Table};
+
+use crate::catalog::ViewDef;
+use crate::concurrent_catalog::{ConcurrentCatalog, TableHandle};
+
+const CATALOG_NAME: &str = "default";
+
+pub(crate) fn view(
+    catalog: &ConcurrentCatalog,
+    dataset: Option<&str>,
+    name: &str,
+) -> Option<Table> {
+    let views = InformationSchema { catalog, dataset };
+    let table = match name {
+        "SCHEMATA" => views.schemata(),
+        "TABLES" => views.tables(),
+        "COLUMNS" => views.columns(),
+        "COLUMN_FIELD_PATHS" => views.column_field_paths(),
+        "VIEWS" => views.views(),
+        "ROUTINES" => views.routines(),
+        "PARAMETERS" => views.parameters(),
+        "TABLE_OPTIONS" => views.table_options(),
+        "TABLE_CONSTRAINTS" => views.table_constraints(),
+        "KEY_COLUMN_USAGE" => views.key_column_usage(),
+        "PARTITIONS" => views.partitions(),
+        name => return extended::view(&views, name),
+    };
+    Some(table)
+}
+
+struct InformationSchema<'a> {
+    catalog: &'a ConcurrentCatalog,
+    dataset: Option<&'a str>,
+}
+
+/// A table or view as the views report it: lowercase dataset and name, the
+/// dataset being NULL for objects created without one.
+struct Relation {
+    key: String,
+    schema_name: Option<String>,
+    name: String,
+}
+
+impl Relation {
+    fn new(key: String) -> Self {
+        let lower = key.to_lowercase();
+        let (schema_name, name) = match lower.rsplit_once('.') {
+            Some((schema, name)) => (Some(schema.to_string()), name.to_string()),
+            None => (None, lower),
+        };
+        Self {
+            key,
+            schema_name,
+            name,
+        }
+    }
+
+    fn qualified_name(&self) -> String {
+        match &self.schema_name {
+            Some(schema) => format!("{}.{}", schema, self.name),
+            None => self.name.clone(),
+        }
+    }
+
+    /// Whether a table name as written in SQL, such as a foreign key's
+    /// referenced table, names this relation.
+    fn refers_to(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        name == self.qualified_name() || (!name.contains('.') && name == self.name)
+    }
+
+    fn prefix(&self) -> Vec<Value> {
+        vec![
+            Value::string(CATALOG_NAME),
+            optional_string(self.schema_name.as_deref()),
+            Value::string(&self.name),
+        ]
+    }
+}
+
+impl InformationSchema<'_> {
+    fn in_dataset(&self, schema_name: Option<&str>) -> bool {
+        match self.dataset {
+            Some(dataset) => schema_name.is_some_and(|s| s.eq_ignore_ascii_case(dataset)),
+            None => true,
+        }
+    }
+
+    fn base_tables(&self) -> Vec<(Relation, TableHandle)> {
+        let mut tables: Vec<(Relation, TableHandle)> = self
+            .catalog
+            .table_handles()
+            .into_iter()
+            .map(|(key, handle)| (Relation::new(key), handle))
+            .filter(|(relation, _)| self.in_dataset(relation.schema_name.as_deref()))
+            .collect();
+        tables.sort_by(|a, b| a.0.key.cmp(&b.0.key));
+        tables
+    }
+
+    fn view_defs(&self) -> Vec<(Relation, ViewDef)> {
+        let mut views: Vec<(Relation, ViewDef)> = self
+            .catalog
+            .views()
+            .into_iter()
+            .map(|(key, view)| (Relation::new(key), view))
+            .filter(|(relation, _)| self.in_dataset(relation.schema_name.as_deref()))
+            .collect();
+        views.sort_by(|a, b| a.0.key.cmp(&b.0.key));
+        views
+    }
+
+    /// Tables and views with their columns, for `COLUMN_FIELD_PATHS`. View columns come from planning
+    /// the view query; views that no longer plan are reported without columns.
+    fn relation_columns(&self) -> Vec<(Relation, Vec<Field>)> {
+        let mut relations: Vec<(Relation, Vec<Field>)> = self
+            .base_tables()
+            .into_iter()
+            .map(|(relation, handle)| (relation, handle.read().schema().fields().to_vec()))
+            .collect();
+        for (relation, view) in self.view_defs() {
+            let fields = view_fields(self.catalog, &view).unwrap_or_default();
+            relations.push((relation, fields));
+        }
+        relations.sort_by(|a, b| a.0.key.cmp(&b.0.key));
+        relations
+    }
+
+    fn schemata(&self) -> Table {
+        let fields = string_fields(&[
+            "catalog_name",
+            "schema_name",
+            "schema_owner",
+            "location",
+            "default_collation_name",
+        ]);
+        let mut names = self.catalog.schema_names();
+        names.sort();
+        let rows = names
+            .into_iter()
+            .filter(|name| !name.starts_with('_') && self.in_dataset(Some(name)))
+            .map(|name| {
+                let options = self.catalog.schema_options(&name);
+                let option = |key: &str| {
+                    options
+                        .iter()
+                        .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                        .map(|(_, v)| v.as_str())
+                };
+                vec![
+                    Value::string(CATALOG_NAME),
+                    Value::string(name.to_lowercase()),
+                    Value::null(),
+                    optional_string(option("location")),
+                    optional_string(self.catalog.get_schema_default_collation(&name).as_deref()),
+                ]
+            })
+            .collect();
+        build(fields, rows)
+    }
+
+    fn tables(&self) -> Table {
+        let fields = string_fields(&[
+            "table_catalog",
+            "table_schema",
+            "table_name",
+            "table_type",
+            "is_insertable_into",
+            "is_typed",
+            "ddl",
+        ]);
+        let mut rows = Vec::new();
+        for (relation, handle) in self.base_tables() {
+            let snapshot = self.catalog.is_snapshot(&relation.key);
+            let mut row = relation.prefix();
+            row.push(Value::string(if snapshot {
+                "SNAPSHOT"
+            } else {
+                "BASE TABLE"
+            }));
+            row.push(Value::string(if snapshot { "NO" } else { "YES" }));
+            row.push(Value::string("NO"));
+            let ddl = self.table_ddl(&relation, handle.read().schema());
+            row.push(Value::string(ddl));
+            rows.push(row);
+        }
+        for (relation, view) in self.view_defs() {
+            let table_type = if view.materialized {
+                "MATERIALIZED VIEW"
+            } else {
+                "VIEW"
+            };
+            let mut row = relation.prefix();
+            row.push(Value::string(table_type));
+            row.push(Value::string("NO"));
+            row.push(Value::string("NO"));
+            row.push(Value::string(format!(
+                "CREATE {} {}\nAS {};",
+                table_type,
+                relation.qualified_name(),
+                view.query
+            )));
+            rows.push(row);
+        }
+        build(fields, rows)
+    }
+
+    fn table_ddl(&self, relation: &Relation, schema: &Schema) -> String {
+        let defaults = self
+            .catalog
+            .get_table_defaults(&relation.key)
+            .unwrap_or_default();
+        let mut elements: Vec<String> = schema
+            .fields()
+            .iter()
+            .map(|field| {
+                let mut column = format!("{} {}", field.name, field.data_type);
+                if field.mode == FieldMode::Required {
+                    column.push_str(" NOT NULL");
+                }
+                if let Some(default) = defaults
+                    .iter()
+                    .find(|d| d.column_name.eq_ignore_ascii_case(&field.name))
+                {
+                    column.push_str(&format!(" DEFAULT {}", default.default_expr));
+                }
+                column
+            })
+            .collect();
+        for constraint in self.catalog.get_table_constraints(&relation.key) {
+            elements.push(constraint_ddl(&constraint));
+        }
+        let mut ddl = format!(
+            "CREATE TABLE {}\n(\n  {}\n)",
+            relation.qualified_name(),
+            elements.join(",\n  ")
+        );
+        let options = self.catalog.get_table_options(&relation.key);
+        if !options.is_empty() {
+            let options: Vec<String> = options
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect();
+            ddl.push_str(&format!("\nOPTIONS(\n  {}\n)", options.join(",\n  ")));
+        }
+        ddl.push(';');
+        ddl
+    }
+
+    /// Columns of base tables only; view columns are listed in
+    /// `COLUMN_FIELD_PATHS`.
+    fn columns(&self) -> Table {
+        let mut fields =
+            string_fields(&["table_catalog", "table_schema", "table_name", "column_name"]);
+        fields.push(Field::nullable("ordinal_position", DataType::Int64));
+        fields.extend(string_fields(&[
+            "is_nullable",
+            "data_type",
+            "is_hidden",
+            "is_system_defined",
+            "is_partitioning_column",
+        ]));
+        fields.push(Field::nullable(
+            "clustering_ordinal_position",
+            DataType::Int64,
+        ));
+        fields.extend(string_fields(&["collation_name", "column_default"]));
+
+        let mut rows = Vec::new();
+        for (relation, handle) in self.base_tables() {
+            let columns = handle.read().schema().fields().to_vec();
+            let defaults = self
+                .catalog
+                .get_table_defaults(&relation.key)
+                .unwrap_or_default();
+            for (position, field) in columns.iter().enumerate() {
+                let default = defaults
+                    .iter()
+                    .find(|d| d.column_name.eq_ignore_ascii_case(&field.name))
+                    .map(|d| d.default_expr.to_string());
+                let mut row = relation.prefix();
+                row.push(Value::string(&field.name));
+                row.push(Value::int64(position as i64 + 1));
+                row.push(Value::string(if field.mode == FieldMode::Required {
+                    "NO"
+                } else {
+                    "YES"
+                }));
+                row.push(Value::string(field.data_type.to_string()));
+                row.push(Value::string("NO"));
+                row.push(Value::string("NO"));
+                row.push(Value::string("NO"));
+                row.push(Value::null());
+                row.push(optional_string(field.collation.as_deref()));
+                row.push(optional_string(default.as_deref()));
+                rows.push(row);
+            }
+        }
+        build(fields, rows)
+    }
+
+    fn column_field_paths(&self) -> Table {
+        let fields = string_fields(&[
+            "table_catalog",
+            "table_schema",
+            "table_name",
+            "column_name",
+            "field_path",
+            "data_type",
+            "description",
+            "collation_name",
+        ]);
+        let mut rows = Vec::new();
+        for (relation, columns) in self.relation_columns() {
+            for field in &columns {
+                let mut paths = Vec::new();
+                collect_field_paths(&field.name, &field.data_type, &mut paths);
+                if let Some((_, data_type)) = paths.first_mut() {
+                    *data_type = field.data_type.clone();
+                }
+                for (path, data_type) in paths {
+                    let is_column = path == field.name;
+                    let mut row = relation.prefix();
+                    row.push(Value::string(&field.name));
+                    row.push(Value::string(path));
+                    row.push(Value::string(data_type.to_string()));
+                    row.push(optional_string(if is_column {
+                        field.description.as_deref()
+                    } else {
+                        None
+                    }));
+                    row.push(optional_string(if is_column {
+                        field.collation.as_deref()
+                    } else {
+                        None
+                    }));
+                    rows.push(row);
+                }
+            }
+        }
+        build(fields, rows)
+    }
+
+    fn views(&self) -> Table {
+        let fields = string_fields(&[
+            "table_catalog",
+            "table_schema",
+            "table_name",
+            "view_definition",
+            "check_option",
+            "use_standard_sql",
+        ]);
+        let rows = self
+            .view_defs()
+            .into_iter()
+            .map(|(relation, view)| {
+                let mut row = relation.prefix();
+                row.push(Value::string(view.query));
+                row.push(Value::null());
+                row.push(Value::string("YES"));
+                row
+            })
+            .collect();
+        build(fields, rows)
+    }
+
+    fn routines(&self) -> Table {
+        let fields = string_fields(&[
+            "specific_catalog",
+            "specific_schema",
+            "specific_name",
+            "routine_catalog",
+            "routine_schema",
+            "routine_name",
+            "routine_type",
+            "data_type",
+            "routine_body",
+            "routine_definition",
+            "external_language",
+        ]);
+        let mut rows = Vec::new();
+        for function in self.functions() {
+            let (schema_name, name) = split_routine_name(&function.name);
+            let (schema_name, name) = (schema_name.as_deref(), name.as_str());
+            if !self.in_dataset(schema_name) {
+                continue;
+            }
+            let (routine_type, data_type) = match &function.body {
+                FunctionBody::SqlQuery(_) => ("TABLE FUNCTION", None),
+                _ if function.is_aggregate => ("AGGREGATE FUNCTION", Some(&function.return_type)),
+                _ => ("FUNCTION", Some(&function.return_type)),
+            };
+            let (routine_body, definition, language) = match &function.body {
+                FunctionBody::Sql(expr) => ("SQL", expr.to_string(), None),
+                FunctionBody::SqlQuery(query) => ("SQL", query.clone(), None),
+                FunctionBody::JavaScript(code) => ("EXTERNAL", code.clone(), Some("js")),
+                FunctionBody::Language { name, code } => {
+                    ("EXTERNAL", code.clone(), Some(name.as_str()))
+                }
+            };
+            let mut row = routine_prefix(schema_name, name);
+            row.extend([
+                Value::string(CATALOG_NAME),
+                optional_string(schema_name),
+                Value::string(name),
+                Value::string(routine_type),
+                data_type
+                    .filter(|dt| **dt != DataType::Unknown)
+                    .map_or_else(Value::null, |dt| Value::string(dt.to_string())),
+                Value::string(routine_body),
+                Value::string(definition),
+                optional_string(language),
+            ]);
+            rows.push(row);
+        }
+        for procedure in self.procedures() {
+            let (schema_name, name) = split_routine_name(&procedure.name);
+            let (schema_name, name) = (schema_name.as_deref(), name.as_str());
+            if !self.in_dataset(schema_name) {
+                continue;
+            }
+            let mut row = routine_prefix(schema_name, name);
+            row.extend([
+                Value::string(CATALOG_NAME),
+                optional_string(schema_name),
+                Value::string(name),
+                Value::string("PROCEDURE"),
+                Value::null(),
+                Value::string("SQL"),
+                Value::null(),
+                Value::null(),
+            ]);
+            rows.push(row);
+        }
+        build(fields, rows)
+    }
+
+    fn parameters(&self) -> Table {
+        let mut fields = string_fields(&["specific_catalog", "specific_schema", "specific_name"]);
+        fields.push(Field::nullable("ordinal_position", DataType::Int64));
+        fields.extend(string_fields(&[
+            "parameter_mode",
+            "is_result",
+            "parameter_name",
+            "data_type",
+            "parameter_default",
+            "is_aggregate",
+        ]));
+        let mut rows = Vec::new();
+        for function in self.functions() {
+            let (schema_name, name) = split_routine_name(&function.name);
+            let (schema_name, name) = (schema_name.as_deref(), name.as_str());
+            if !self.in_dataset(schema_name) {
+                continue;
+            }
+            for (position, arg) in function.parameters.iter().enumerate() {
+                let mut row = routine_prefix(schema_name, name);
+                row.extend([
+                    Value::int64(position as i64 + 1),
+                    Value::string("IN"),
+                    Value::string("NO"),
+                    Value::string(&arg.name),
+                    Value::string(arg.data_type.to_string()),
+                    optional_string(arg.default.as_ref().map(|d| d.to_string()).as_deref()),
+                    Value::string(if function.is_aggregate { "YES" } else { "NO" }),
+                ]);
+                rows.push(row);
+            }
+        }
+        for procedure in self.procedures() {
+            let (schema_name, name) = split_routine_name(&procedure.name);
+            let (schema_name, name) = (schema_name.as_deref(), name.as_str());
+            if !self.in_dataset(schema_name) {
+                continue;
+            }
+            for (position, arg) in procedure.parameters.iter().enumerate() {
+                let mode = match arg.mode {
+                    ProcedureArgMode::In => "IN",
+                    ProcedureArgMode::Out => "OUT",
+                    ProcedureArgMode::InOut => "INOUT",
+                };
+                let mut row = routine_prefix(schema_name, name);
+                row.extend([
+                    Value::int64(position as i64 + 1),
+                    Value::string(mode),
+                    Value::string("NO"),
+                    Value::string(&arg.name),
+                    Value::string(arg.data_type.to_string()),
+                    Value::null(),
+                    Value::string("NO"),
+                ]);
+                rows.push(row);
+            }
+        }
+        build(fields, rows)
+    }
+
+    fn functions(&self) -> Vec<crate::catalog::UserFunction> {
+        let mut functions: Vec<_> = self
+            .catalog
+            .get_functions()
+            .into_values()
+            .filter(|f| !f.is_temporary)
+            .collect();
+        functions.sort_by_key(|f| f.name.to_uppercase());
+        functions
+    }
+
+    fn procedures(&self) -> Vec<crate::catalog::UserProcedure> {
+        let mut procedures = self.catalog.procedures();
+        procedures.sort_by_key(|p| p.name.to_uppercase());
+        procedures
+    }
+
+    fn table_options(&self) -> Table {
+        let fields = string_fields(&[
+            "table_catalog",
+            "table_schema",
+            "table_name",
+            "option_name",
+            "option_type",
+            "option_value",
+        ]);
+        let mut rows = Vec::new();
+        for (relation, _) in self.base_tables() {
+            for (name, value) in self.catalog.get_table_options(&relation.key) {
+                let mut row = relation.prefix();
+                row.push(Value::string(name));
+                row.push(Value::string(option_type(&value)));
+                row.push(Value::string(value));
+                rows.push(row);
+            }
+        }
+        build(fields, rows)
+    }
+
+    /// Constraints of every table in declaration order. Unnamed constraints get
+    /// PostgreSQL-style names such as `orders_pkey` or `users_age_check`.
+    fn named_constraints(&self) -> Vec<(Relation, String, TableConstraint)> {
+        let mut result = Vec::new();
+        for (relation, _) in self.base_tables() {
+            let mut checks = 0;
+            for constraint in self.catalog.get_table_constraints(&relation.key) {
+                let name = match &constraint.name {
+                    Some(name) => name.clone(),
+                    None => match &constraint.constraint_type {
+                        ConstraintType::PrimaryKey { .. } => format!("{}_pkey", relation.name),
+                        ConstraintType::Unique { columns } => {
+                            format!("{}_{}_key", relation.name, columns.join("_"))
+                        }
+                        ConstraintType::ForeignKey { columns, .. } => {
+                            format!("{}_{}_fkey", relation.name, columns.join("_"))
+                        }
+                        ConstraintType::Check { expr } => match check_column(expr) {
+                            Some(column) => format!("{}_{}_check", relation.name, column),
+                            None => {
+                                checks += 1;
+                                match checks {
+                                    1 => format!("{}_check", relation.name),
+                                    n => format!("{}_check{}", relation.name, n - 1),
+                                }
+                            }
+                        },
+                    },
+                };
+                result.push((Relation::new(relation.key.clone()), name, constraint));
+            }
+        }
+        result
+    }
+
+    fn table_constraints(&self) -> Table {
+        let fields = string_fields(&[
+            "constraint_catalog",
+            "constraint_schema",
+            "constraint_name",
+            "table_catalog",
+            "table_schema",
+            "table_name",
+            "constraint_type",
+            "is_deferrable",
+            "initially_deferred",
+            "enforced",
+        ]);
+        let rows = self
+            .named_constraints()
+            .into_iter()
+            .map(|(relation, name, constraint)| {
+                let constraint_type = match constraint.constraint_type {
+                    ConstraintType::PrimaryKey { .. } => "PRIMARY KEY",
+                    ConstraintType::ForeignKey { .. } => "FOREIGN KEY",
+                    ConstraintType::Unique { .. } => "UNIQUE",
+                    ConstraintType::Check { .. } => "CHECK",
+                };
+                let mut row = vec![
+                    Value::string(CATALOG_NAME),
+                    optional_string(relation.schema_name.as_deref()),
+                    Value::string(name),
+                ];
+                row.extend(relation.prefix());
+                row.extend([
+                    Value::string(constraint_type),
+                    Value::string("NO"),
+                    Value::string("NO"),
+                    Value::string("NO"),
+                ]);
+                row
+            })
+            .collect();
+        build(fields, rows)
+    }
+
+    fn key_column_usage(&self) -> Table {
+        let mut fields = string_fields(&[
+            "constraint_catalog",
+            "constraint_schema",
+            "constraint_name",
+            "table_catalog",
+            "table_schema",
+            "table_name",
+            "column_name",
+        ]);
+        fields.push(Field::nullable("ordinal_position", DataType::Int64));
+        fields.push(Field::nullable(
+            "position_in_unique_constraint",
+            DataType::Int64,
+        ));
+        let mut rows = Vec::new();
+        for (relation, name, constraint) in self.named_constraints() {
+            let (columns, foreign) = match &constraint.constraint_type {
+                ConstraintType::PrimaryKey { columns } | ConstraintType::Unique { columns } => {
+                    (columns, false)
+                }
+                ConstraintType::ForeignKey { columns, .. } => (columns, true),
+                ConstraintType::Check { .. } => continue,
+            };
+            for (position, column) in columns.iter().enumerate() {
+                let mut row = vec![
+                    Value::string(CATALOG_NAME),
+                    optional_string(relation.schema_name.as_deref()),
+                    Value::string(&name),
+                ];
+                row.extend(relation.prefix());
+                row.push(Value::string(column));
+                row.push(Value::int64(position as i64 + 1));
+                row.push(if foreign {
+                    Value::int64(position as i64 + 1)
+                } else {
+                    Value::null()
+                });
+                rows.push(row);
+            }
+        }
+        build(fields, rows)
+    }
+
+    /// Tables are stored unpartitioned, so each one reports a single partition
+    /// with a NULL `partition_id`, as BigQuery does for unpartitioned tables.
+    fn partitions(&self) -> Table {
+        let mut fields = string_fields(&[
+            "table_catalog",
+            "table_schema",
+            "table_name",
+            "partition_id",
+        ]);
+        fields.push(Field::nullable("total_rows", DataType::Int64));
+        fields.push(Field::nullable("last_modified_time", DataType::Timestamp));
+        fields.push(Field::nullable("storage_tier", DataType::String));
+        let rows = self
+            .base_tables()
+            .into_iter()
+            .map(|(relation, handle)| {
+                let mut row = relation.prefix();
+                row.push(Value::null());
+                row.push(Value::int64(handle.read().row_count() as i64));
+                row.push(
+                    self.catalog
+                        .last_modified(&relation.key)
+                        .map_or_else(Value::null, Value::timestamp),
+                );
+                row.push(Value::string("ACTIVE"));
+                row
+            })
+            .collect();
+        build(fields, rows)
+    }
+}
+
+/// Resolves only real tables, views and functions, so that planning a view
+/// defined over `INFORMATION_SCHEMA` cannot recurse back into these views.
+struct StoredObjects<'a>(&'a ConcurrentCatalog);
+
+impl CatalogProvider for StoredObjects<'_> {
+    fn get_table_schema(&self, name: &str) -> Option<Schema> {
+        let handle = self.0.get_table_handle(name)?;
+        let schema = handle.read().schema().clone();
+        Some(schema)
+    }
+
+    fn get_view(&self, name: &str) -> Option<ViewDefinition> {
+        CatalogProvider::get_view(self.0, name)
+    }
+
+    fn get_function(&self, name: &str) -> Option<FunctionDefinition> {
+        CatalogProvider::get_function(self.0, name)
+    }
+}
+
+fn view_fields(catalog: &ConcurrentCatalog, view: &ViewDef) -> Option<Vec<Field>> {
+    let plan = yachtsql_parser::parse_and_plan(&view.query, &StoredObjects(catalog)).ok()?;
+    let fields = plan
+        .schema()
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let name = view.column_aliases.get(i).unwrap_or(&field.name);
+            let mode = if field.nullable {
+                FieldMode::Nullable
+            } else {
+                FieldMode::Required
+            };
+            Field::new(name, field.data_type.clone(), mode)
+        })
+        .collect();
+    Some(fields)
+}
+
+fn collect_field_paths(path: &str, data_type: &DataType, paths: &mut Vec<(String, DataType)>) {
+    paths.push((path.to_string(), data_type.clone()));
+    let nested = match data_type {
+        DataType::Array(inner) => inner.as_ref(),
+        other => other,
+    };
+    if let DataType::Struct(fields) = nested {
+        for field in fields {
+            collect_field_paths(&format!("{}.{}", path, field.name), &field.data_type, paths);
+        }
+    }
+}
+
+fn constraint_ddl(constraint: &TableConstraint) -> String {
+    let body = match &constraint.constraint_type {
+        ConstraintType::PrimaryKey { columns } => {
+            format!("PRIMARY KEY ({}) NOT ENFORCED", columns.join(", "))
+        }
+        ConstraintType::Unique { columns } => format!("UNIQUE ({})", columns.join(", ")),
+        ConstraintType::ForeignKey {
+            columns,
+            references_table,
+            references_columns,
+        } => format!(
+            "FOREIGN KEY ({}) REFERENCES {}({}) NOT ENFORCED",
+            columns.join(", "),
+            references_table,
+            references_columns.join(", ")
+        ),
+        ConstraintType::Check { expr } => format!("CHECK ({})", expr),
+    };
+    match &constraint.name {
+        Some(name) => format!("CONSTRAINT {} {}", name, body),
+        None => body,
+    }
+}
+
+/// The single column a CHECK constraint reads, if it reads exactly one.
+fn check_column(expr: &Expr) -> Option<String> {
+    let mut columns = Vec::new();
+    if !collect_check_columns(expr, &mut columns) {
+        return None;
+    }
+    match columns.as_slice() {
+        [column] => Some(column.clone()),
+        _ => None,
+    }
+}
+
+/// Collects the distinct columns a CHECK expression reads. Returns false for
+/// expressions it cannot see through.
+fn collect_check_columns(expr: &Expr, columns: &mut Vec<String>) -> bool {
+    match expr {
+        Expr::Column { name, .. } => {
+            if !columns.iter().any(|c| c.eq_ignore_ascii_case(name)) {
+                columns.push(name.clone());
+            }
+            true
+        }
+        Expr::Literal(_) => true,
+        Expr::BinaryOp { left, right, .. } => {
+            collect_check_columns(left, columns) && collect_check_columns(right, columns)
+        }
+        Expr::UnaryOp { expr, .. } | Expr::Cast { expr, .. } | Expr::IsNull { expr, .. } => {
+            collect_check_columns(expr, columns)
+        }
+        Expr::ScalarFunction { args, .. } => {
+            args.iter().all(|arg| collect_check_columns(arg, columns))
+        }
+        Expr::InList { expr, list, .. } => {
+            collect_check_columns(expr, columns)
+                && list.iter().all(|item| collect_check_columns(item, columns))
+        }
+        Expr::Between {
+            expr, low, high, ..
+        } => {
+            collect_check_columns(expr, columns)
+                && collect_check_columns(low, columns)
+                && collect_check_columns(high, columns)
+        }
+        Expr::Like { expr, pattern, .. } => {
+            collect_check_columns(expr, columns) && collect_check_columns(pattern, columns)
+        }
+        _ => false,
+    }
+}
+
+fn option_type(value: &str) -> &'static str {
+    if value.starts_with('\'') || value.starts_with('"') {
+        "STRING"
+    } else if value.eq_ignore_ascii_case("TRUE") || value.eq_ignore_ascii_case("FALSE") {
+        "BOOL"
+    } else if value.parse::<i64>().is_ok() {
+        "INT64"
+    } else if value.parse::<f64>().is_ok() {
+        "FLOAT64"
+    } else if value.starts_with('[') {
+        "ARRAY"
+    } else {
+        "STRING"
+    }
+}
+
+fn split_routine_name(name: &str) -> (Option<String>, String) {
+    let name = name.to_lowercase();
+    match name.rsplit_once('.') {
+        Some((schema, routine)) => (Some(schema.to_string()), routine.to_string()),
+        None => (None, name),
+    }
+}
+
+fn routine_prefix(schema_name: Option<&str>, name: &str) -> Vec<Value> {
+    vec![
+        Value::string(CATALOG_NAME),
+        optional_string(schema_name),
+        Value::string(name),
+    ]
+}
+
+fn optional_string(value: Option<&str>) -> Value {
+    value.map_or_else(Value::null, Value::string)
+}
+
+fn string_fields(names: &[&str]) -> Vec<Field> {
+    names
+        .iter()
+        .map(|name| Field::nullable(*name, DataType::String))
+        .collect()
+}
+
+fn build(fields: Vec<Field>, rows: Vec<Vec<Value>>) -> Table {
+    let schema = Schema::from_fields(fields);
+    Table::from_values(schema.clone(), rows).unwrap_or_else(|_| Table::empty(schema))
+}
@@ -0,0 +1,578 @@
+//! Column-at-a-time evaluation for the expressions that dominate filters and
+//! projections. Every kernel mirrors the row-wise semantics in
+//! [`IrEvaluator`]; anything it cannot reproduce exactly (unsupported
+//! expressions, type combinations, or rows that would raise an error) makes
+//! the whole batch return `None`, and the caller re-runs the operator row by
+//! row so results and error messages stay identical.
+
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+use aligned_vec::AVec;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use indexmap::IndexMap;
+use ordered_float::OrderedFloat;
+use yachtsql_common::types::Value;
+use yachtsql_ir::{BinaryOp, Expr, ScalarFunction, UnaryOp};
+use yachtsql_storage::{Column, NullBitmap, Record, Schema, Table};
+
+use super::IrEvaluator;
+
+enum Vector<'t> {
+    Column(Cow<'t, Column>),
+    Scalar(Value),
+}
+
+enum Lane<'v, T> {
+    Values(&'v [T], &'v NullBitmap),
+    Constant(&'v T),
+    Null,
+}
+
+impl<'v, T> Lane<'v, T> {
+    #[inline]
+    fn get(&self, index: usize) -> Option<&'v T> {
+        match self {
+            Lane::Values(data, nulls) => (!nulls.is_null(index)).then(|| &data[index]),
+            Lane::Constant(value) => Some(value),
+            Lane::Null => None,
+        }
+    }
+}
+
+macro_rules! lane_accessor {
+    ($name:ident, $column:ident, $value:ident, $ty:ty) => {
+        fn $name<'v>(vector: &'v Vector<'_>) -> Option<Lane<'v, $ty>> {
+            match vector {
+                Vector::Column(column) => match column.as_ref() {
+                    Column::$column { data, nulls } => Some(Lane::Values(data, nulls)),
+                    _ => None,
+                },
+                Vector::Scalar(Value::$value(value)) => Some(Lane::Constant(value)),
+                Vector::Scalar(Value::Null) => Some(Lane::Null),
+                Vector::Scalar(_) => None,
+            }
+        }
+    };
+}
+
+lane_accessor!(bool_lane, Bool, Bool, bool);
+lane_accessor!(int64_lane, Int64, Int64, i64);
+lane_accessor!(string_lane, String, String, String);
+lane_accessor!(date_lane, Date, Date, NaiveDate);
+lane_accessor!(time_lane, Time, Time, NaiveTime);
+lane_accessor!(datetime_lane, DateTime, DateTime, NaiveDateTime);
+lane_accessor!(timestamp_lane, Timestamp, Timestamp, DateTime<Utc>);
+
+fn float64_lane<'v>(vector: &'v Vector<'_>) -> Option<Lane<'v, f64>> {
+    match vector {
+        Vector::Column(column) => match column.as_ref() {
+            Column::Float64 { data, nulls } => Some(Lane::Values(data, nulls)),
+            _ => None,
+        },
+        Vector::Scalar(Value::Float64(value)) => Some(Lane::Constant(&value.0)),
+        Vector::Scalar(Value::Null) => Some(Lane::Null),
+        Vector::Scalar(_) => None,
+    }
+}
+
+fn build_bool(len: usize, mut f: impl FnMut(usize) -> Option<Option<bool>>) -> Option<Column> {
+    let mut data = Vec::with_capacity(len);
+    let mut nulls = NullBitmap::new_valid(len);
+    for i in 0..len {
+        match f(i)? {
+            Some(value) => data.push(value),
+            None => {
+                data.push(false);
+                nulls.set_null(i);
+            }
+        }
+    }
+    Some(Column::Bool { data, nulls })
+}
+
+fn build_int64(len: usize, mut f: impl FnMut(usize) -> Option<Option<i64>>) -> Option<Column> {
+    let mut data = AVec::with_capacity(64, len);
+    let mut nulls = NullBitmap::new_valid(len);
+    for i in 0..len {
+        match f(i)? {
+            Some(value) => data.push(value),
+            None => {
+                data.push(0);
+                nulls.set_null(i);
+            }
+        }
+    }
+    Some(Column::Int64 { data, nulls })
+}
+
+fn build_float64(len: usize, mut f: impl FnMut(usize) -> Option<Option<f64>>) -> Option<Column> {
+    let mut data = AVec::with_capacity(64, len);
+    let mut nulls = NullBitmap::new_valid(len);
+    for i in 0..len {
+        match f(i)? {
+            Some(value) => data.push(value),
+            None => {
+                data.push(0.0);
+                nulls.set_null(i);
+            }
+        }
+    }
+    Some(Column::Float64 { data, nulls })
+}
+
+fn build_string(len: usize, mut f: impl FnMut(usize) -> Option<Option<String>>) -> Option<Column> {
+    let mut data = Vec::with_capacity(len);
+    let mut nulls = NullBitmap::new_valid(len);
+    for i in 0..len {
+        match f(i)? {
+            Some(value) => data.push(value),
+            None => {
+                data.push(String::new());
+                nulls.set_null(i);
+            }
+        }
+    }
+    Some(Column::String { data, nulls })
+}
+
+fn is_constant(expr: &Expr) -> bool {
+    match expr {
+        Expr::Literal(_) => true,
+        Expr::UnaryOp { expr, .. } | Expr::Cast { expr, .. } => is_constant(expr),
+        Expr::BinaryOp { left, right, .. } => is_constant(left) && is_constant(right),
+        _ => false,
+    }
+}
+
+fn ordering_matches(op: BinaryOp, ordering: Ordering) -> bool {
+    match op {
+        BinaryOp::Eq => ordering.is_eq(),
+        BinaryOp::NotEq => ordering.is_ne(),
+        BinaryOp::Lt => ordering.is_lt(),
+        BinaryOp::LtEq => ordering.is_le(),
+        BinaryOp::Gt => ordering.is_gt(),
+        _ => ordering.is_ge(),
+    }
+}
+
+fn compare_lanes<A, B>(
+    len: usize,
+    left: Lane<'_, A>,
+    right: Lane<'_, B>,
+    matches: impl Fn(&A, &B) -> bool,
+) -> Option<Column> {
+    build_bool(len, |i| match (left.get(i), right.get(i)) {
+        (Some(a), Some(b)) => Some(Some(matches(a, b))),
+        _ => Some(None),
+    })
+}
+
+fn mixed_numeric_matches(op: BinaryOp, a: f64, b: f64) -> bool {
+    match op {
+        BinaryOp::Eq => (a - b).abs() < f64::EPSILON,
+        BinaryOp::NotEq => (a - b).abs() >= f64::EPSILON,
+        _ => ordering_matches(op, a.partial_cmp(&b).unwrap_or(Ordering::Equal)),
+    }
+}
+
+pub struct BatchEvaluator<'a> {
+    evaluator: &'a IrEvaluator<'a>,
+    table: &'a Table,
+    len: usize,
+}
+
+impl<'a> BatchEvaluator<'a> {
+    pub fn new(evaluator: &'a IrEvaluator<'a>, table: &'a Table) -> Self {
+        Self {
+            evaluator,
+            table,
+            len: table.row_count(),
+        }
+    }
+
+    /// The table's segments when it has more than one; each is evaluated on
+    /// its own so no column has to be concatenated first.
+    fn segments(&self) -> Option<Vec<Table>> {
+        let parts = self.table.split_segments();
+        (parts.len() > 1).then_some(parts)
+    }
+
+    /// Returns the indices of the rows for which `predicate` is TRUE.
+    pub fn select(&self, predicate: &Expr) -> Option<Vec<usize>> {
+        if let Some(parts) = self.segments() {
+            let mut selection = Vec::new();
+            let mut offset = 0;
+            for part in &parts {
+                let part_selection = BatchEvaluator::new(self.evaluator, part).select(predicate)?;
+                selection.extend(part_selection.into_iter().map(|i| i + offset));
+                offset += part.row_count();
+            }
+            return Some(selection);
+        }
+        match self.eval(predicate)? {
+            Vector::Scalar(Value::Bool(true)) => Some((0..self.len).collect()),
+            Vector::Scalar(Value::Bool(false) | Value::Null) => Some(Vec::new()),
+            Vector::Scalar(_) => None,
+            Vector::Column(column) => match column.as_ref() {
+                Column::Bool { data, nulls } => Some(
+                    (0..self.len)
+                        .filter(|&i| data[i] && !nulls.is_null(i))
+                        .collect(),
+                ),
+                _ => None,
+            },
+        }
+    }
+
+    /// Keeps the rows for which `predicate` is TRUE. Segments that pass
+    /// entirely are shared with the input instead of copied.
+    pub fn filter(&self, predicate: &Expr) -> Option<Table> {
+        let mut result = Table::empty(self.table.schema().clone());
+        for part in self.table.split_segments() {
+            let selection = BatchEvaluator::new(self.evaluator, &part).select(predicate)?;
+            if selection.len() == part.row_count() {
+                result.append(&part);
+            } else if !selection.is_empty() {
+                result.append(&part.gather_rows(&selection).ok()?);
+            }
+        }
+        Some(result)
+    }
+
+    /// Evaluates every expression into a column of the matching field in
+    /// `schema`.
+    pub fn project(&self, expressions: &[Expr], schema: &Schema) -> Option<Table> {
+        if expressions.is_empty() || schema.field_count() != expressions.len() {
+            return None;
+        }
+        if let Some(parts) = self.segments() {
+            let mut result = Table::empty(schema.clone());
+            for part in &parts {
+                result.append(
+                    &BatchEvaluator::new(self.evaluator, part).project(expressions, schema)?,
+                );
+            }
+            return Some(result);
+        }
+        let mut columns = IndexMap::with_capacity(expressions.len());
+        for (field, expr) in schema.fields().iter().zip(expressions) {
+            let column = self.eval_into(expr, &Column::new(&field.data_type))?;
+            columns.insert(field.name.clone(), column);
+        }
+        if columns.len() != expressions.len() {
+            return None;
+        }
+        Some(Table::from_columns(schema.clone(), columns))
+    }
+
+    fn eval_into(&self, expr: &Expr, target: &Column) -> Option<Column> {
+        let mut result = Column::clone(target);
+        match self.eval(expr)? {
+            Vector::Column(column) if column.same_layout(target) => {
+                return Some(column.into_owned());
+            }
+            Vector::Column(column) => {
+                for i in 0..self.len {
+                    result.push(column.get_value(i)).ok()?;
+                }
+            }
+            Vector::Scalar(value) => {
+                for _ in 0..self.len {
+                    result.push(value.clone()).ok()?;
+                }
+            }
+        }
+        Some(result)
+    }
+
+    fn eval(&self, expr: &Expr) -> Option<Vector<'a>> {
+        if is_constant(expr) {
+            return self
+                .evaluator
+                .evaluate(expr, &Record::new())
+                .ok()
+                .map(Vector::Scalar);
+        }
+        match expr {
+            Expr::Column {
+                index: Some(index), ..
+            } if *index < self.table.num_columns() => self.table.column(*index).map(Vector::Column),
+            Expr::BinaryOp { left, op, right } => {
+                let left_vec = self.eval(left)?;
+                let right_vec = self.eval(right)?;
+                self.eval_binary(left, *op, right, left_vec, right_vec)
+            }
+            Expr::UnaryOp { op, expr } => self.eval_unary(*op, self.eval(expr)?),
+            Expr::IsNull { expr, negated } => Some(match self.eval(expr)? {
+                Vector::Scalar(value) => Vector::Scalar(Value::Bool(value.is_null() != *negated)),
+                Vector::Column(column) => Vector::Column(Cow::Owned(build_bool(self.len, |i| {
+                    Some(Some(column.is_null(i) != *negated))
+                })?)),
+            }),
+            Expr::InList {
+                expr,
+                list,
+                negated,
+            } => self.eval_in_list(expr, list, *negated),
+            Expr::ScalarFunction { name, args } => self.eval_function(name, args),
+            _ => None,
+        }
+    }
+
+    fn eval_binary(
+        &self,
+        left_expr: &Expr,
+        op: BinaryOp,
+        right_expr: &Expr,
+        left: Vector<'a>,
+        right: Vector<'a>,
+    ) -> Option<Vector<'a>> {
+        if let (Vector::Scalar(_), Vector::Scalar(_)) = (&left, &right) {
+            return None;
+        }
+        let column = match op {
+            BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div => {
+                self.arithmetic(op, &left, &right)?
+            }
+            BinaryOp::Eq
+            | BinaryOp::NotEq
+            | BinaryOp::Lt
+            | BinaryOp::LtEq
+            | BinaryOp::Gt
+            | BinaryOp::GtEq => {
+                let collated = matches!(op, BinaryOp::Eq | BinaryOp::NotEq)
+                    && (self.evaluator.get_collation_for_expr(left_expr).is_some()
+                        || self.evaluator.get_collation_for_expr(right_expr).is_some());
+                self.comparison(op, &left, &right, collated)?
+            }
+            BinaryOp::And | BinaryOp::Or => {
+                let (a, b) = (bool_lane(&left)?, bool_lane(&right)?);
+                let is_and = op == BinaryOp::And;
+                build_bool(self.len, |i| {
+                    Some(match (a.get(i).copied(), b.get(i).copied()) {
+                        (Some(x), _) | (_, Some(x)) if x != is_and => Some(x),
+                        (Some(_), Some(_)) => Some(is_and),
+                        _ => None,
+                    })
+                })?
+            }
+            BinaryOp::Concat => {
+                let (a, b) = (string_lane(&left)?, string_lane(&right)?);
+                build_string(self.len, |i| {
+                    Some(match (a.get(i), b.get(i)) {
+                        (Some(x), Some(y)) => Some(format!("{}{}", x, y)),
+                        _ => None,
+                    })
+                })?
+            }
+            _ => return None,
+        };
+        Some(Vector::Column(Cow::Owned(column)))
+    }
+
+    fn arithmetic(&self, op: BinaryOp, left: &Vector<'_>, right: &Vector<'_>) -> Option<Column> {
+        let len = self.len;
+        if let (Some(a), Some(b)) = (int64_lane(left), int64_lane(right)) {
+            if op == BinaryOp::Div {
+                return build_float64(len, |i| match (a.get(i), b.get(i)) {
+                    (Some(_), Some(0)) => None,
+                    (Some(x), Some(y)) => Some(Some(*x as f64 / *y as f64)),
+                    _ => Some(None),
+                });
+            }
+            return build_int64(len, |i| match (a.get(i), b.get(i)) {
+                (Some(x), Some(y)) => match op {
+                    BinaryOp::Add => x.checked_add(*y),
+                    BinaryOp::Sub => x.checked_sub(*y),
+                    _ => x.checked_mul(*y),
+                }
+                .map(Some),
+                _ => Some(None),
+            });
+        }
+        let float_op = |x: f64, y: f64| match op {
+            BinaryOp::Add => Some(x + y),
+            BinaryOp::Sub => Some(x - y),
+            BinaryOp::Mul => Some(x * y),
+            _ if y == 0.0 => None,
+            _ => Some(x / y),
+        };
+        if let (Some(a), Some(b)) = (float64_lane(left), float64_lane(right)) {
+            return build_float64(len, |i| match (a.get(i), b.get(i)) {
+                (Some(x), Some(y)) => float_op(*x, *y).map(Some),
+                _ => Some(None),
+            });
+        }
+        if let (Some(a), Some(b)) = (int64_lane(left), float64_lane(right)) {
+            return build_float64(len, |i| match (a.get(i), b.get(i)) {
+                (Some(x), Some(y)) => float_op(*x as f64, *y).map(Some),
+                _ => Some(None),
+            });
+        }
+        if let (Some(a), Some(b)) = (float64_lane(left), int64_lane(right)) {
+            return build_float64(len, |i| match (a.get(i), b.get(i)) {
+                (Some(x), Some(y)) => float_op(*x, *y as f64).map(Some),
+                _ => Some(None),
+            });
+        }
+        None
+    }
+
+    fn comparison(
+        &self,
+        op: BinaryOp,
+        left: &Vector<'_>,
+        right: &Vector<'_>,
+        collated: bool,
+    ) -> Option<Column> {
+        let len = self.len;
+        if let (Some(a), Some(b)) = (int64_lane(left), int64_lane(right)) {
+            return compare_lanes(len, a, b, |x, y| ordering_matches(op, x.cmp(y)));
+        }
+        if let (Some(a), Some(b)) = (float64_lane(left), float64_lane(right)) {
+            return compare_lanes(len, a, b, |x, y| {
+                ordering_matches(op, OrderedFloat(*x).cmp(&OrderedFloat(*y)))
+            });
+        }
+        if let (Some(a), Some(b)) = (int64_lane(left), float64_lane(right)) {
+            return compare_lanes(len, a, b, |x, y| mixed_numeric_matches(op, *x as f64, *y));
+        }
+        if let (Some(a), Some(b)) = (float64_lane(left), int64_lane(right)) {
+            return compare_lanes(len, a, b, |x, y| mixed_numeric_matches(op, *x, *y as f64));
+        }
+        if let (Some(a), Some(b)) = (string_lane(left), string_lane(right)) {
+            if collated {
+                return None;
+            }
+            return compare_lanes(len, a, b, |x, y| ordering_matches(op, x.cmp(y)));
+        }
+        if let (Some(a), Some(b)) = (bool_lane(left), bool_lane(right)) {
+            return compare_lanes(len, a, b, |x, y| ordering_matches(op, x.cmp(y)));
+        }
+        if let (Some(a), Some(b)) = (date_lane(left), date_lane(right)) {
+            return compare_lanes(len, a, b, |x, y| ordering_matches(op, x.cmp(y)));
+        }
+        if let (Some(a), Some(b)) = (time_lane(left), time_lane(right)) {
+            return compare_lanes(len, a, b, |x, y| ordering_matches(op, x.cmp(y)));
+        }
+        if let (Some(a), Some(b)) = (datetime_lane(left), datetime_lane(right)) {
+            return compare_lanes(len, a, b, |x, y| ordering_matches(op, x.cmp(y)));
+        }
+        if let (Some(a), Some(b)) = (timestamp_lane(left), timestamp_lane(right)) {
+            return compare_lanes(len, a, b, |x, y| ordering_matches(op, x.cmp(y)));
+        }
+        None
+    }
+
+    fn eval_unary(&self, op: UnaryOp, operand: Vector<'a>) -> Option<Vector<'a>> {
+        let column = match (op, &operand) {
+            (_, Vector::Scalar(_)) => return None,
+            (UnaryOp::Not, _) => {
+                let a = bool_lane(&operand)?;
+                build_bool(self.len, |i| Some(a.get(i).map(|x| !x)))?
+            }
+            (UnaryOp::Minus, _) => {
+                if let Some(a) = int64_lane(&operand) {
+                    build_int64(self.len, |i| match a.get(i) {
+                        Some(x) => x.checked_neg().map(Some),
+                        None => Some(None),
+                    })?
+                } else {
+                    let a = float64_lane(&operand)?;
+                    build_float64(self.len, |i| Some(a.get(i).map(|x| -x)))?
+                }
+            }
+            _ => return None,
+        };
+        Some(Vector::Column(Cow::Owned(column)))
+    }
+
+    fn eval_in_list(&self, expr: &Expr, list: &[Expr], negated: bool) -> Option<Vector<'a>> {
+        let items = list
+            .iter()
+            .map(|item| match item {
+                Expr::Literal(_) => self.evaluator.evaluate(item, &Record::new()).ok(),
+                _ => None,
+            })
+            .collect::<Option<Vec<Value>>>()?;
+        let has_null = items.iter().any(Value::is_null);
+        let operand = self.eval(expr)?;
+        if let Vector::Scalar(_) = operand {
+            return None;
+        }
+        let outcome = |found: bool| match (found, has_null) {
+            (true, _) => Some(!negated),
+            (false, true) => None,
+            (false, false) => Some(negated),
+        };
+
+        let column = if let Some(lane) = int64_lane(&operand) {
+            let set = items
+                .iter()
+                .filter(|v| !v.is_null())
+                .map(|v| match v {
+                    Value::Int64(n) => Some(*n),
+                    _ => None,
+                })
+                .collect::<Option<HashSet<i64>>>()?;
+            build_bool(self.len, |i| {
+                Some(lane.get(i).and_then(|x| outcome(set.contains(x))))
+            })?
+        } else if let Some(lane) = string_lane(&operand) {
+            let set = items
+                .iter()
+                .filter(|v| !v.is_null())
+                .map(|v| match v {
+                    Value::String(s) => Some(s.as_str()),
+                    _ => None,
+                })
+                .collect::<Option<HashSet<&str>>>()?;
+            build_bool(self.len, |i| {
+                Some(lane.get(i).and_then(|x| outcome(set.contains(x.as_str()))))
+            })?
+        } else {
+            return None;
+        };
+        Some(Vector::Column(Cow::Owned(column)))
+    }
+
+    fn eval_function(&self, name: &ScalarFunction, args: &[Expr]) -> Option<Vector<'a>> {
+        let args = args
+            .iter()
+            .map(|arg| self.eval(arg))
+            .collect::<Option<Vec<_>>>()?;
+        if args.iter().all(|arg| matches!(arg, Vector::Scalar(_))) {
+            return None;
+        }
+        let len = self.len;
+        let column = match (name, args.as_slice()) {
+            (ScalarFunction::Upper, [arg]) => {
+                let a = string_lane(arg)?;
+                build_string(len, |i| Some(a.get(i).map(|s| s.to_uppercase())))?
+            }
+            (ScalarFunction::Lower, [arg]) => {
+                let a = string_lane(arg)?;
+                build_string(len, |i| Some(a.get(i).map(|s| s.to_lowercase())))?
+            }
+            (ScalarFunction::Length, [arg]) => {
+                let a = string_lane(arg)?;
+                build_int64(len, |i| Some(a.get(i).map(|s| s.chars().count() as i64)))?
+            }
+            (ScalarFunction::StartsWith | ScalarFunction::EndsWith, [arg, affix]) => {
+                let (a, b) = (string_lane(arg)?, string_lane(affix)?);
+                let starts = matches!(name, ScalarFunction::StartsWith);
+                build_bool(len, |i| {
+                    Some(match (a.get(i), b.get(i)) {
+                        (Some(s), Some(p)) if starts => Some(s.starts_with(p.as_str())),
+                        (Some(s), Some(p)) => Some(s.ends_with(p.as_str())),
+                        _ => None,
+                    })
+                })?
+            }
+            _ => return None,
+        };
+        Some(Vector::Column(Cow::Owned(column)))
+    }
+}
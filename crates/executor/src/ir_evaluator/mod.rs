@@ -2,11 +2,13 @@
 #![allow(clippy::only_used_in_recursion)]
 #![allow(clippy::collapsible_if)]
 
+mod batch;
 mod functions;
 mod helpers;
 
 use std::collections::HashMap;
 
+pub use batch::BatchEvaluator;
 use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc};
 use geo::{
     BooleanOps, BoundingRect, Centroid, Contains, ConvexHull, GeodesicArea, GeodesicDistance,
@@ -33,6 +35,7 @@ use yachtsql_ir::{
 };
 use yachtsql_storage::{Record, Schema};
 
+#[derive(Clone)]
 pub struct UserFunctionDef {
     pub parameters: Vec<FunctionArg>,
     pub body: FunctionBody,
@@ -285,6 +288,9 @@ impl<'a> IrEvaluator<'a> {
                 )))
             }
             Expr::Placeholder { id } => {
+                if let Some(val) = self.variables.and_then(|vars| vars.get(id)) {
+                    return Ok(val.clone());
+                }
                 Err(Error::InvalidQuery(format!("Unbound placeholder: {}", id)))
             }
             Expr::Lambda { .. } => Err(Error::InvalidQuery(
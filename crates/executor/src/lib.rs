@@ -18,21 +18,32 @@ mod py_udf;
 mod session;
 
 mod async_executor;
+mod cancel;
 mod concurrent_catalog;
 mod concurrent_session;
+mod information_schema;
+mod persistence;
+mod plan_cache;
+mod prepared;
+mod result_cache;
+mod stream;
 
 use std::num::NonZeroUsize;
 
 pub use async_executor::AsyncQueryExecutor;
-pub use catalog::{Catalog, ColumnDefault, UserFunction, UserProcedure, ViewDef};
-pub use concurrent_catalog::{ConcurrentCatalog, TableLockSet};
+pub use cancel::QueryHandle;
+pub use catalog::{Catalog, ColumnDefault, TableIndex, UserFunction, UserProcedure, ViewDef};
+pub use concurrent_catalog::{CatalogImage, ConcurrentCatalog, HistoryClock, TableLockSet};
 pub use concurrent_session::ConcurrentSession;
 pub use error::{Error, Result};
 pub use executor::{PlanExecutor, plan_schema_to_schema};
 pub use ir_evaluator::{IrEvaluator, UserFunctionDef};
 use lru::LruCache;
+pub use persistence::PersistenceOptions;
 pub use plan::PhysicalPlan;
+pub use prepared::{ParameterInfo, PreparedStatement, QueryParams};
 pub use session::Session;
+pub use stream::QueryStream;
 use yachtsql_optimizer::OptimizedLogicalPlan;
 pub use yachtsql_storage::{Record, Table};
 
@@ -45,12 +56,14 @@ fn default_plan_cache() -> LruCache<String, OptimizedLogicalPlan> {
 fn is_cacheable_plan(plan: &OptimizedLogicalPlan) -> bool {
     match plan {
         OptimizedLogicalPlan::TableScan { .. }
+        | OptimizedLogicalPlan::TimeTravelScan { .. }
         | OptimizedLogicalPlan::Sample { .. }
         | OptimizedLogicalPlan::Filter { .. }
         | OptimizedLogicalPlan::Project { .. }
         | OptimizedLogicalPlan::NestedLoopJoin { .. }
         | OptimizedLogicalPlan::CrossJoin { .. }
         | OptimizedLogicalPlan::HashJoin { .. }
+        | OptimizedLogicalPlan::MergeJoin { .. }
         | OptimizedLogicalPlan::HashAggregate { .. }
         | OptimizedLogicalPlan::Sort { .. }
         | OptimizedLogicalPlan::Limit { .. }
@@ -74,6 +87,8 @@ fn is_cacheable_plan(plan: &OptimizedLogicalPlan) -> bool {
         | OptimizedLogicalPlan::DropTable { .. }
         | OptimizedLogicalPlan::AlterTable { .. }
         | OptimizedLogicalPlan::Truncate { .. }
+        | OptimizedLogicalPlan::Analyze { .. }
+        | OptimizedLogicalPlan::Explain { .. }
         | OptimizedLogicalPlan::CreateView { .. }
         | OptimizedLogicalPlan::DropView { .. }
         | OptimizedLogicalPlan::CreateSchema { .. }
@@ -84,6 +99,8 @@ fn is_cacheable_plan(plan: &OptimizedLogicalPlan) -> bool {
         | OptimizedLogicalPlan::DropFunction { .. }
         | OptimizedLogicalPlan::CreateProcedure { .. }
         | OptimizedLogicalPlan::DropProcedure { .. }
+        | OptimizedLogicalPlan::CreateIndex { .. }
+        | OptimizedLogicalPlan::DropIndex { .. }
         | OptimizedLogicalPlan::Call { .. }
         | OptimizedLogicalPlan::ExportData { .. }
         | OptimizedLogicalPlan::LoadData { .. }
@@ -134,12 +151,14 @@ fn invalidates_cache(plan: &OptimizedLogicalPlan) -> bool {
         | OptimizedLogicalPlan::DropSnapshot { .. } => true,
 
         OptimizedLogicalPlan::TableScan { .. }
+        | OptimizedLogicalPlan::TimeTravelScan { .. }
         | OptimizedLogicalPlan::Sample { .. }
         | OptimizedLogicalPlan::Filter { .. }
         | OptimizedLogicalPlan::Project { .. }
         | OptimizedLogicalPlan::NestedLoopJoin { .. }
         | OptimizedLogicalPlan::CrossJoin { .. }
         | OptimizedLogicalPlan::HashJoin { .. }
+        | OptimizedLogicalPlan::MergeJoin { .. }
         | OptimizedLogicalPlan::HashAggregate { .. }
         | OptimizedLogicalPlan::Sort { .. }
         | OptimizedLogicalPlan::Limit { .. }
@@ -158,6 +177,10 @@ fn invalidates_cache(plan: &OptimizedLogicalPlan) -> bool {
         | OptimizedLogicalPlan::Update { .. }
         | OptimizedLogicalPlan::Delete { .. }
         | OptimizedLogicalPlan::Merge { .. }
+        | OptimizedLogicalPlan::Analyze { .. }
+        | OptimizedLogicalPlan::Explain { .. }
+        | OptimizedLogicalPlan::CreateIndex { .. }
+        | OptimizedLogicalPlan::DropIndex { .. }
         | OptimizedLogicalPlan::Call { .. }
         | OptimizedLogicalPlan::ExportData { .. }
         | OptimizedLogicalPlan::LoadData { .. }
@@ -0,0 +1,511 @@
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use yachtsql_common::error::{Error, Result};
+use yachtsql_ir::TableConstraint;
+use yachtsql_storage::{Table, TableDelta};
+
+use crate::catalog::{
+    ColumnDefault, SchemaMetadata, TableIndex, UserFunction, UserProcedure, ViewDef,
+};
+use crate::concurrent_catalog::{ConcurrentCatalog, DroppedSchemaData, TableSnapshotInfo};
+use crate::plan::PhysicalPlan;
+
+const WAL_FILE: &str = "catalog.wal";
+const CHECKPOINT_FILE: &str = "catalog.checkpoint";
+const CHECKPOINT_TMP_FILE: &str = "catalog.checkpoint.tmp";
+
+const WAL_MAGIC: &[u8; 8] = b"YSQLWAL1";
+const CHECKPOINT_MAGIC: &[u8; 8] = b"YSQLCKP1";
+
+const FRAME_HEADER_LEN: usize = 12;
+
+#[derive(Debug, Clone)]
+pub struct PersistenceOptions {
+    pub checkpoint_wal_bytes: u64,
+    pub sync_writes: bool,
+}
+
+impl Default for PersistenceOptions {
+    fn default() -> Self {
+        Self {
+            checkpoint_wal_bytes: 64 * 1024 * 1024,
+            sync_writes: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum CatalogObject {
+    Table(String),
+    Function(String),
+    Procedure(String),
+    View(String),
+    Schema(String),
+    SearchPath,
+}
+
+/// Full image of one catalog object. `None` means the object no longer exists,
+/// so replaying a record is idempotent regardless of the state it is applied to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) enum CatalogRecord {
+    Table {
+        name: String,
+        table: Option<Table>,
+        defaults: Option<Vec<ColumnDefault>>,
+        constraints: Option<Vec<TableConstraint>>,
+        options: Option<Vec<(String, String)>>,
+        #[serde(default)]
+        indexes: Option<Vec<TableIndex>>,
+        snapshot: Option<TableSnapshotInfo>,
+    },
+    Function {
+        name: String,
+        function: Option<UserFunction>,
+    },
+    Procedure {
+        name: String,
+        procedure: Option<UserProcedure>,
+        body: Option<Vec<PhysicalPlan>>,
+    },
+    View {
+        name: String,
+        view: Option<ViewDef>,
+    },
+    Schema {
+        name: String,
+        metadata: Option<SchemaMetadata>,
+        dropped: Option<DroppedSchemaData>,
+    },
+    SearchPath(Vec<String>),
+    /// A table written to the WAL as its changes since the version of it
+    /// logged before, which replay rebuilds it from. Checkpoints hold full
+    /// `Table` images only.
+    TableDelta {
+        name: String,
+        delta: TableDelta,
+        defaults: Option<Vec<ColumnDefault>>,
+        constraints: Option<Vec<TableConstraint>>,
+        options: Option<Vec<(String, String)>>,
+        indexes: Option<Vec<TableIndex>>,
+        snapshot: Option<TableSnapshotInfo>,
+    },
+}
+
+/// `logged` holds each table as of its last WAL record, sharing storage
+/// with the catalog, so the next record only needs the segments changed
+/// since. It is empty after a checkpoint, whose images replay with a
+/// different segment layout.
+#[derive(Debug)]
+struct WalWriter {
+    file: File,
+    len: u64,
+    logged: HashMap<String, Table>,
+}
+
+#[derive(Debug)]
+pub(crate) struct DurableStore {
+    dir: PathBuf,
+    options: PersistenceOptions,
+    wal: Mutex<WalWriter>,
+}
+
+impl DurableStore {
+    pub(crate) fn open(
+        dir: &Path,
+        options: PersistenceOptions,
+    ) -> Result<(Self, ConcurrentCatalog)> {
+        fs::create_dir_all(dir).map_err(|e| io_error("create database directory", dir, e))?;
+        let catalog = ConcurrentCatalog::new();
+
+        let checkpoint_path = dir.join(CHECKPOINT_FILE);
+        if checkpoint_path.exists() {
+            let file = File::open(&checkpoint_path)
+                .map_err(|e| io_error("open checkpoint", &checkpoint_path, e))?;
+            let (frames, complete) = read_frames(file, CHECKPOINT_MAGIC, &checkpoint_path)?;
+            if !complete {
+                return Err(Error::internal(format!(
+                    "Corrupt checkpoint: {}",
+                    checkpoint_path.display()
+                )));
+            }
+            for records in frames {
+                for record in records {
+                    catalog.apply_record(record);
+                }
+            }
+        }
+
+        let wal_path = dir.join(WAL_FILE);
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&wal_path)
+            .map_err(|e| io_error("open WAL", &wal_path, e))?;
+        let existing_len = file
+            .metadata()
+            .map_err(|e| io_error("stat WAL", &wal_path, e))?
+            .len();
+
+        let len = if existing_len < WAL_MAGIC.len() as u64 {
+            file.set_len(0)
+                .map_err(|e| io_error("truncate WAL", &wal_path, e))?;
+            file.write_all(WAL_MAGIC)
+                .map_err(|e| io_error("write WAL", &wal_path, e))?;
+            WAL_MAGIC.len() as u64
+        } else {
+            let reader = file
+                .try_clone()
+                .map_err(|e| io_error("open WAL", &wal_path, e))?;
+            let mut valid_len = WAL_MAGIC.len() as u64;
+            let (frames, _) = read_frames_with(reader, WAL_MAGIC, &wal_path, |frame_len| {
+                valid_len += frame_len;
+            })?;
+            for records in frames {
+                for record in records {
+                    catalog.apply_record(replayed_record(&catalog, record)?);
+                }
+            }
+            if valid_len < existing_len {
+                file.set_len(valid_len)
+                    .map_err(|e| io_error("truncate WAL", &wal_path, e))?;
+            }
+            valid_len
+        };
+        file.seek(SeekFrom::Start(len))
+            .map_err(|e| io_error("seek WAL", &wal_path, e))?;
+
+        catalog.enable_change_tracking();
+
+        let logged = catalog
+            .all_objects()
+            .iter()
+            .filter_map(|object| match catalog.record_for(object) {
+                CatalogRecord::Table {
+                    name,
+                    table: Some(table),
+                    ..
+                } => Some((name, table)),
+                _ => None,
+            })
+            .collect();
+        let store = Self {
+            dir: dir.to_path_buf(),
+            options,
+            wal: Mutex::new(WalWriter { file, len, logged }),
+        };
+        Ok((store, catalog))
+    }
+
+    pub(crate) fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Logs every object changed since the last flush, whichever session
+    /// changed it. Writes staged by open transactions are not part of the
+    /// shared state, so only committed changes reach the log. If the frame
+    /// cannot be written the WAL is cut back to its last complete frame and
+    /// the changes stay pending for the next flush.
+    pub(crate) fn flush(&self, catalog: &ConcurrentCatalog) -> Result<()> {
+        let mut wal = self.wal.lock().unwrap();
+        let changes = catalog.take_changes();
+        if changes.is_empty() {
+            return Ok(());
+        }
+        let mut tables = Vec::new();
+        let records: Vec<CatalogRecord> = changes
+            .iter()
+            .map(|object| match durable_record(catalog, object) {
+                CatalogRecord::Table {
+                    name,
+                    table,
+                    defaults,
+                    constraints,
+                    options,
+                    indexes,
+                    snapshot,
+                } => match table {
+                    Some(table) => {
+                        let delta = table.delta_from(wal.logged.get(&name));
+                        tables.push((name.clone(), Some(table)));
+                        CatalogRecord::TableDelta {
+                            name,
+                            delta,
+                            defaults,
+                            constraints,
+                            options,
+                            indexes,
+                            snapshot,
+                        }
+                    }
+                    None => {
+                        tables.push((name.clone(), None));
+                        CatalogRecord::Table {
+                            name,
+                            table,
+                            defaults,
+                            constraints,
+                            options,
+                            indexes,
+                            snapshot,
+                        }
+                    }
+                },
+                record => record,
+            })
+            .collect();
+
+        if let Err(err) = encode_frame(&records).and_then(|frame| self.append(&mut wal, &frame)) {
+            catalog.restore_changes(changes);
+            return Err(err);
+        }
+        for (name, table) in tables {
+            match table {
+                Some(table) => wal.logged.insert(name, table),
+                None => wal.logged.remove(&name),
+            };
+        }
+
+        if wal.len >= self.options.checkpoint_wal_bytes {
+            self.write_checkpoint(catalog, &mut wal)?;
+        }
+        Ok(())
+    }
+
+    /// Appends a frame, truncating a partial write so the next frame starts
+    /// where this one did.
+    fn append(&self, wal: &mut WalWriter, frame: &[u8]) -> Result<()> {
+        let wal_path = self.dir.join(WAL_FILE);
+        let written = wal.file.write_all(frame).and_then(|_| {
+            if self.options.sync_writes {
+                wal.file.sync_data()
+            } else {
+                Ok(())
+            }
+        });
+        if let Err(err) = written {
+            let _ = wal.file.set_len(wal.len);
+            let _ = wal.file.seek(SeekFrom::Start(wal.len));
+            return Err(io_error("write WAL", &wal_path, err));
+        }
+        wal.len += frame.len() as u64;
+        Ok(())
+    }
+
+    pub(crate) fn checkpoint(&self, catalog: &ConcurrentCatalog) -> Result<()> {
+        if catalog.in_transaction() {
+            return Err(Error::invalid_query(
+                "Cannot checkpoint while a transaction is active",
+            ));
+        }
+        let mut wal = self.wal.lock().unwrap();
+        self.write_checkpoint(catalog, &mut wal)
+    }
+
+    fn write_checkpoint(&self, catalog: &ConcurrentCatalog, wal: &mut WalWriter) -> Result<()> {
+        let changes = catalog.take_changes();
+        let written = self.install_checkpoint(catalog, wal);
+        if written.is_err() {
+            catalog.restore_changes(changes);
+        }
+        written
+    }
+
+    fn install_checkpoint(&self, catalog: &ConcurrentCatalog, wal: &mut WalWriter) -> Result<()> {
+        let tmp_path = self.dir.join(CHECKPOINT_TMP_FILE);
+        let file =
+            File::create(&tmp_path).map_err(|e| io_error("create checkpoint", &tmp_path, e))?;
+        let mut writer = BufWriter::new(file);
+        writer
+            .write_all(CHECKPOINT_MAGIC)
+            .map_err(|e| io_error("write checkpoint", &tmp_path, e))?;
+        for object in catalog.all_objects() {
+            let frame = encode_frame(&[durable_record(catalog, &object)])?;
+            writer
+                .write_all(&frame)
+                .map_err(|e| io_error("write checkpoint", &tmp_path, e))?;
+        }
+        let file = writer
+            .into_inner()
+            .map_err(|e| io_error("write checkpoint", &tmp_path, e.into_error()))?;
+        file.sync_all()
+            .map_err(|e| io_error("sync checkpoint", &tmp_path, e))?;
+
+        let checkpoint_path = self.dir.join(CHECKPOINT_FILE);
+        fs::rename(&tmp_path, &checkpoint_path)
+            .map_err(|e| io_error("install checkpoint", &checkpoint_path, e))?;
+        if let Ok(dir) = File::open(&self.dir) {
+            let _ = dir.sync_all();
+        }
+
+        let wal_path = self.dir.join(WAL_FILE);
+        let len = WAL_MAGIC.len() as u64;
+        wal.file
+            .set_len(len)
+            .map_err(|e| io_error("truncate WAL", &wal_path, e))?;
+        wal.file
+            .seek(SeekFrom::Start(len))
+            .map_err(|e| io_error("seek WAL", &wal_path, e))?;
+        wal.file
+            .sync_all()
+            .map_err(|e| io_error("sync WAL", &wal_path, e))?;
+        wal.len = len;
+        wal.logged.clear();
+        Ok(())
+    }
+}
+
+/// Turns a replayed `TableDelta` record into the full `Table` record it
+/// stands for, using the table as rebuilt by the records before it.
+fn replayed_record(catalog: &ConcurrentCatalog, record: CatalogRecord) -> Result<CatalogRecord> {
+    let CatalogRecord::TableDelta {
+        name,
+        delta,
+        defaults,
+        constraints,
+        options,
+        indexes,
+        snapshot,
+    } = record
+    else {
+        return Ok(record);
+    };
+    let base = match catalog.record_for(&CatalogObject::Table(name.clone())) {
+        CatalogRecord::Table { table, .. } => table,
+        _ => None,
+    };
+    Ok(CatalogRecord::Table {
+        table: Some(Table::from_delta(base.as_ref(), delta)?),
+        name,
+        defaults,
+        constraints,
+        options,
+        indexes,
+        snapshot,
+    })
+}
+
+fn durable_record(catalog: &ConcurrentCatalog, object: &CatalogObject) -> CatalogRecord {
+    match catalog.record_for(object) {
+        CatalogRecord::Function {
+            name,
+            function: Some(function),
+        } if function.is_temporary => CatalogRecord::Function {
+            name,
+            function: None,
+        },
+        record => record,
+    }
+}
+
+fn io_error(action: &str, path: &Path, err: std::io::Error) -> Error {
+    Error::internal(format!(
+        "Failed to {} '{}': {}",
+        action,
+        path.display(),
+        err
+    ))
+}
+
+fn encode_frame(records: &[CatalogRecord]) -> Result<Vec<u8>> {
+    let mut payload = Vec::new();
+    ciborium::into_writer(records, &mut payload)
+        .map_err(|e| Error::internal(format!("Failed to encode catalog record: {}", e)))?;
+    let mut frame = Vec::with_capacity(FRAME_HEADER_LEN + payload.len());
+    frame.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+    frame.extend_from_slice(&crc32fast::hash(&payload).to_le_bytes());
+    frame.extend_from_slice(&payload);
+    Ok(frame)
+}
+
+fn read_frames(
+    file: File,
+    magic: &[u8; 8],
+    path: &Path,
+) -> Result<(Vec<Vec<CatalogRecord>>, bool)> {
+    read_frames_with(file, magic, path, |_| {})
+}
+
+/// Reads frames until end of file. A short or checksum-failing frame ends the
+/// scan and is reported through the returned flag; that only happens when the
+/// process died halfway through an append.
+fn read_frames_with(
+    file: File,
+    magic: &[u8; 8],
+    path: &Path,
+    mut on_frame: impl FnMut(u64),
+) -> Result<(Vec<Vec<CatalogRecord>>, bool)> {
+    let file_len = file
+        .metadata()
+        .map_err(|e| io_error("stat", path, e))?
+        .len();
+    let mut reader = BufReader::new(file);
+    reader
+        .seek(SeekFrom::Start(0))
+        .map_err(|e| io_error("seek", path, e))?;
+
+    let mut header = [0u8; 8];
+    reader
+        .read_exact(&mut header)
+        .map_err(|e| io_error("read", path, e))?;
+    if &header != magic {
+        return Err(Error::internal(format!(
+            "Unrecognized file format: {}",
+            path.display()
+        )));
+    }
+
+    let mut frames = Vec::new();
+    let mut offset = magic.len() as u64;
+    loop {
+        let mut frame_header = [0u8; FRAME_HEADER_LEN];
+        match read_full(&mut reader, &mut frame_header) {
+            Ok(0) => return Ok((frames, true)),
+            Ok(n) if n < FRAME_HEADER_LEN => return Ok((frames, false)),
+            Ok(_) => {}
+            Err(e) => return Err(io_error("read", path, e)),
+        }
+        let payload_len = u64::from_le_bytes(frame_header[..8].try_into().unwrap()) as usize;
+        let checksum = u32::from_le_bytes(frame_header[8..].try_into().unwrap());
+        offset += FRAME_HEADER_LEN as u64;
+        if offset + payload_len as u64 > file_len {
+            return Ok((frames, false));
+        }
+
+        let mut payload = vec![0u8; payload_len];
+        match read_full(&mut reader, &mut payload) {
+            Ok(n) if n < payload_len => return Ok((frames, false)),
+            Ok(_) => {}
+            Err(e) => return Err(io_error("read", path, e)),
+        }
+        if crc32fast::hash(&payload) != checksum {
+            return Ok((frames, false));
+        }
+        let records: Vec<CatalogRecord> = match ciborium::from_reader(payload.as_slice()) {
+            Ok(records) => records,
+            Err(_) => return Ok((frames, false)),
+        };
+        offset += payload_len as u64;
+        on_frame((FRAME_HEADER_LEN + payload_len) as u64);
+        frames.push(records);
+    }
+}
+
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
@@ -1,10 +1,12 @@
 use std::collections::BTreeMap;
 
+use serde::{Deserialize, Serialize};
 use yachtsql_common::types::DataType;
 use yachtsql_ir::{
-    AlterTableOp, Assignment, ColumnDef, CteDefinition, DclResourceType, ExportOptions, Expr,
-    FunctionArg, FunctionBody, GapFillColumn, JoinType, LoadOptions, MergeClause, PlanSchema,
-    ProcedureArg, RaiseLevel, SortExpr, UnnestColumn,
+    AlterTableOp, Assignment, BinaryOp, ColumnDef, CteDefinition, DclResourceType, ExplainFormat,
+    ExportOptions, Expr, FunctionArg, FunctionBody, GapFillColumn, IndexKind, JoinType,
+    LoadOptions, MergeClause, PlanSchema, ProcedureArg, RaiseLevel, SortExpr, TableConstraint,
+    UnnestColumn,
 };
 use yachtsql_optimizer::{OptimizedLogicalPlan, SampleType};
 
@@ -48,7 +50,7 @@ impl TableAccessSet {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum PhysicalPlan {
     TableScan {
         table_name: String,
@@ -57,6 +59,12 @@ pub enum PhysicalPlan {
         row_count: Option<u64>,
     },
 
+    TimeTravelScan {
+        table_name: String,
+        schema: PlanSchema,
+        as_of: Expr,
+    },
+
     Sample {
         input: Box<PhysicalPlan>,
         sample_type: SampleType,
@@ -96,6 +104,19 @@ pub enum PhysicalPlan {
         join_type: JoinType,
         left_keys: Vec<Expr>,
         right_keys: Vec<Expr>,
+        residual: Option<Expr>,
+        schema: PlanSchema,
+        parallel: bool,
+    },
+
+    MergeJoin {
+        left: Box<PhysicalPlan>,
+        right: Box<PhysicalPlan>,
+        join_type: JoinType,
+        left_keys: Vec<Expr>,
+        right_keys: Vec<Expr>,
+        comparison: BinaryOp,
+        residual: Option<Expr>,
         schema: PlanSchema,
         parallel: bool,
     },
@@ -214,6 +235,8 @@ pub enum PhysicalPlan {
     CreateTable {
         table_name: String,
         columns: Vec<ColumnDef>,
+        constraints: Vec<TableConstraint>,
+        options: Vec<(String, String)>,
         if_not_exists: bool,
         or_replace: bool,
         query: Option<Box<PhysicalPlan>>,
@@ -234,6 +257,17 @@ pub enum PhysicalPlan {
         table_name: String,
     },
 
+    Analyze {
+        table_name: String,
+    },
+
+    Explain {
+        input: Box<PhysicalPlan>,
+        analyze: bool,
+        format: ExplainFormat,
+        schema: PlanSchema,
+    },
+
     CreateView {
         name: String,
         query: Box<PhysicalPlan>,
@@ -241,6 +275,7 @@ pub enum PhysicalPlan {
         column_aliases: Vec<String>,
         or_replace: bool,
         if_not_exists: bool,
+        materialized: bool,
     },
 
     DropView {
@@ -252,6 +287,7 @@ pub enum PhysicalPlan {
         name: String,
         if_not_exists: bool,
         or_replace: bool,
+        options: Vec<(String, String)>,
     },
 
     DropSchema {
@@ -279,6 +315,7 @@ pub enum PhysicalPlan {
         if_not_exists: bool,
         is_temp: bool,
         is_aggregate: bool,
+        options: Vec<(String, String)>,
     },
 
     DropFunction {
@@ -286,6 +323,23 @@ pub enum PhysicalPlan {
         if_exists: bool,
     },
 
+    CreateIndex {
+        name: String,
+        table_name: String,
+        kind: IndexKind,
+        columns: Vec<String>,
+        options: Vec<(String, String)>,
+        or_replace: bool,
+        if_not_exists: bool,
+    },
+
+    DropIndex {
+        name: String,
+        table_name: String,
+        kind: IndexKind,
+        if_exists: bool,
+    },
+
     CreateProcedure {
         name: String,
         args: Vec<ProcedureArg>,
@@ -392,6 +446,7 @@ pub enum PhysicalPlan {
         snapshot_name: String,
         source_name: String,
         if_not_exists: bool,
+        as_of: Option<Expr>,
     },
 
     DropSnapshot {
@@ -455,6 +510,16 @@ impl PhysicalPlan {
                 row_count: None,
             },
 
+            OptimizedLogicalPlan::TimeTravelScan {
+                table_name,
+                schema,
+                as_of,
+            } => PhysicalPlan::TimeTravelScan {
+                table_name: table_name.clone(),
+                schema: schema.clone(),
+                as_of: as_of.clone(),
+            },
+
             OptimizedLogicalPlan::Sample {
                 input,
                 sample_type,
@@ -522,6 +587,7 @@ impl PhysicalPlan {
                 join_type,
                 left_keys,
                 right_keys,
+                residual,
                 schema,
             } => {
                 let left_plan = Box::new(Self::from_physical(left));
@@ -533,6 +599,33 @@ impl PhysicalPlan {
                     join_type: *join_type,
                     left_keys: left_keys.clone(),
                     right_keys: right_keys.clone(),
+                    residual: residual.clone(),
+                    schema: schema.clone(),
+                    parallel,
+                }
+            }
+
+            OptimizedLogicalPlan::MergeJoin {
+                left,
+                right,
+                join_type,
+                left_keys,
+                right_keys,
+                comparison,
+                residual,
+                schema,
+            } => {
+                let left_plan = Box::new(Self::from_physical(left));
+                let right_plan = Box::new(Self::from_physical(right));
+                let parallel = Self::should_parallelize(&left_plan, &right_plan);
+                PhysicalPlan::MergeJoin {
+                    left: left_plan,
+                    right: right_plan,
+                    join_type: *join_type,
+                    left_keys: left_keys.clone(),
+                    right_keys: right_keys.clone(),
+                    comparison: *comparison,
+                    residual: residual.clone(),
                     schema: schema.clone(),
                     parallel,
                 }
@@ -737,12 +830,16 @@ impl PhysicalPlan {
             OptimizedLogicalPlan::CreateTable {
                 table_name,
                 columns,
+                constraints,
+                options,
                 if_not_exists,
                 or_replace,
                 query,
             } => PhysicalPlan::CreateTable {
                 table_name: table_name.clone(),
                 columns: columns.clone(),
+                constraints: constraints.clone(),
+                options: options.clone(),
                 if_not_exists: *if_not_exists,
                 or_replace: *or_replace,
                 query: query.as_ref().map(|q| Box::new(Self::from_physical(q))),
@@ -770,6 +867,22 @@ impl PhysicalPlan {
                 table_name: table_name.clone(),
             },
 
+            OptimizedLogicalPlan::Analyze { table_name } => PhysicalPlan::Analyze {
+                table_name: table_name.clone(),
+            },
+
+            OptimizedLogicalPlan::Explain {
+                input,
+                analyze,
+                format,
+                schema,
+            } => PhysicalPlan::Explain {
+                input: Box::new(Self::from_physical(input)),
+                analyze: *analyze,
+                format: *format,
+                schema: schema.clone(),
+            },
+
             OptimizedLogicalPlan::CreateView {
                 name,
                 query,
@@ -777,6 +890,7 @@ impl PhysicalPlan {
                 column_aliases,
                 or_replace,
                 if_not_exists,
+                materialized,
             } => PhysicalPlan::CreateView {
                 name: name.clone(),
                 query: Box::new(Self::from_physical(query)),
@@ -784,6 +898,7 @@ impl PhysicalPlan {
                 column_aliases: column_aliases.clone(),
                 or_replace: *or_replace,
                 if_not_exists: *if_not_exists,
+                materialized: *materialized,
             },
 
             OptimizedLogicalPlan::DropView { name, if_exists } => PhysicalPlan::DropView {
@@ -795,10 +910,12 @@ impl PhysicalPlan {
                 name,
                 if_not_exists,
                 or_replace,
+                options,
             } => PhysicalPlan::CreateSchema {
                 name: name.clone(),
                 if_not_exists: *if_not_exists,
                 or_replace: *or_replace,
+                options: options.clone(),
             },
 
             OptimizedLogicalPlan::DropSchema {
@@ -833,6 +950,7 @@ impl PhysicalPlan {
                 if_not_exists,
                 is_temp,
                 is_aggregate,
+                options,
             } => PhysicalPlan::CreateFunction {
                 name: name.clone(),
                 args: args.clone(),
@@ -842,6 +960,7 @@ impl PhysicalPlan {
                 if_not_exists: *if_not_exists,
                 is_temp: *is_temp,
                 is_aggregate: *is_aggregate,
+                options: options.clone(),
             },
 
             OptimizedLogicalPlan::DropFunction { name, if_exists } => PhysicalPlan::DropFunction {
@@ -849,6 +968,36 @@ impl PhysicalPlan {
                 if_exists: *if_exists,
             },
 
+            OptimizedLogicalPlan::CreateIndex {
+                name,
+                table_name,
+                kind,
+                columns,
+                options,
+                or_replace,
+                if_not_exists,
+            } => PhysicalPlan::CreateIndex {
+                name: name.clone(),
+                table_name: table_name.clone(),
+                kind: *kind,
+                columns: columns.clone(),
+                options: options.clone(),
+                or_replace: *or_replace,
+                if_not_exists: *if_not_exists,
+            },
+
+            OptimizedLogicalPlan::DropIndex {
+                name,
+                table_name,
+                kind,
+                if_exists,
+            } => PhysicalPlan::DropIndex {
+                name: name.clone(),
+                table_name: table_name.clone(),
+                kind: *kind,
+                if_exists: *if_exists,
+            },
+
             OptimizedLogicalPlan::CreateProcedure {
                 name,
                 args,
@@ -998,10 +1147,12 @@ impl PhysicalPlan {
                 snapshot_name,
                 source_name,
                 if_not_exists,
+                as_of,
             } => PhysicalPlan::CreateSnapshot {
                 snapshot_name: snapshot_name.clone(),
                 source_name: source_name.clone(),
                 if_not_exists: *if_not_exists,
+                as_of: as_of.clone(),
             },
 
             OptimizedLogicalPlan::DropSnapshot {
@@ -1084,12 +1235,14 @@ impl PhysicalPlan {
     pub fn schema(&self) -> Option<&PlanSchema> {
         match self {
             PhysicalPlan::TableScan { schema, .. } => Some(schema),
+            PhysicalPlan::TimeTravelScan { schema, .. } => Some(schema),
             PhysicalPlan::Sample { input, .. } => input.schema(),
             PhysicalPlan::Filter { input, .. } => input.schema(),
             PhysicalPlan::Project { schema, .. } => Some(schema),
             PhysicalPlan::NestedLoopJoin { schema, .. } => Some(schema),
             PhysicalPlan::CrossJoin { schema, .. } => Some(schema),
             PhysicalPlan::HashJoin { schema, .. } => Some(schema),
+            PhysicalPlan::MergeJoin { schema, .. } => Some(schema),
             PhysicalPlan::HashAggregate { schema, .. } => Some(schema),
             PhysicalPlan::Sort { input, .. } => input.schema(),
             PhysicalPlan::Limit { input, .. } => input.schema(),
@@ -1105,10 +1258,89 @@ impl PhysicalPlan {
             PhysicalPlan::Values { schema, .. } => Some(schema),
             PhysicalPlan::Empty { schema } => Some(schema),
             PhysicalPlan::GapFill { schema, .. } => Some(schema),
+            PhysicalPlan::Explain { schema, .. } => Some(schema),
             _ => None,
         }
     }
 
+    /// Operator inputs of this node, in the order they are displayed by
+    /// EXPLAIN. Statement bodies of scripting constructs are not included.
+    pub fn children(&self) -> Vec<&PhysicalPlan> {
+        match self {
+            PhysicalPlan::Sample { input, .. }
+            | PhysicalPlan::Filter { input, .. }
+            | PhysicalPlan::Project { input, .. }
+            | PhysicalPlan::HashAggregate { input, .. }
+            | PhysicalPlan::Sort { input, .. }
+            | PhysicalPlan::Limit { input, .. }
+            | PhysicalPlan::TopN { input, .. }
+            | PhysicalPlan::Distinct { input }
+            | PhysicalPlan::Window { input, .. }
+            | PhysicalPlan::Unnest { input, .. }
+            | PhysicalPlan::Qualify { input, .. }
+            | PhysicalPlan::GapFill { input, .. }
+            | PhysicalPlan::Explain { input, .. } => vec![input.as_ref()],
+            PhysicalPlan::NestedLoopJoin { left, right, .. }
+            | PhysicalPlan::CrossJoin { left, right, .. }
+            | PhysicalPlan::HashJoin { left, right, .. }
+            | PhysicalPlan::MergeJoin { left, right, .. }
+            | PhysicalPlan::Intersect { left, right, .. }
+            | PhysicalPlan::Except { left, right, .. } => vec![left.as_ref(), right.as_ref()],
+            PhysicalPlan::Union { inputs, .. } => inputs.iter().collect(),
+            PhysicalPlan::WithCte { body, .. } => vec![body.as_ref()],
+            PhysicalPlan::Insert { source, .. } | PhysicalPlan::Merge { source, .. } => {
+                vec![source.as_ref()]
+            }
+            PhysicalPlan::Update { from, .. } => from.iter().map(|f| f.as_ref()).collect(),
+            PhysicalPlan::CreateTable { query, .. } => query.iter().map(|q| q.as_ref()).collect(),
+            PhysicalPlan::ExportData { query, .. } => vec![query.as_ref()],
+            _ => Vec::new(),
+        }
+    }
+
+    /// The condition of a join node over the combined schema of its inputs,
+    /// with hash and merge join keys turned back into comparisons.
+    pub fn join_condition(&self) -> Option<Expr> {
+        let (left, left_keys, comparison, right_keys, residual) = match self {
+            PhysicalPlan::NestedLoopJoin { condition, .. } => return condition.clone(),
+            PhysicalPlan::HashJoin {
+                left,
+                left_keys,
+                right_keys,
+                residual,
+                ..
+            } => (left, left_keys, BinaryOp::Eq, right_keys, residual),
+            PhysicalPlan::MergeJoin {
+                left,
+                left_keys,
+                comparison,
+                right_keys,
+                residual,
+                ..
+            } => (left, left_keys, *comparison, right_keys, residual),
+            _ => return None,
+        };
+        let offset = left.schema().map_or(0, |s| s.fields.len());
+        left_keys
+            .iter()
+            .zip(right_keys)
+            .map(|(l, r)| {
+                let mut r = r.clone();
+                shift_columns(&mut r, offset);
+                Expr::BinaryOp {
+                    left: Box::new(l.clone()),
+                    op: comparison,
+                    right: Box::new(r),
+                }
+            })
+            .chain(residual.clone())
+            .reduce(|acc, e| Expr::BinaryOp {
+                left: Box::new(acc),
+                op: BinaryOp::And,
+                right: Box::new(e),
+            })
+    }
+
     pub fn extract_table_accesses(&self) -> TableAccessSet {
         let mut accesses = TableAccessSet::new();
         let mut cte_names = std::collections::HashSet::new();
@@ -1146,6 +1378,7 @@ impl PhysicalPlan {
             PhysicalPlan::NestedLoopJoin { left, right, .. }
             | PhysicalPlan::CrossJoin { left, right, .. }
             | PhysicalPlan::HashJoin { left, right, .. }
+            | PhysicalPlan::MergeJoin { left, right, .. }
             | PhysicalPlan::Intersect { left, right, .. }
             | PhysicalPlan::Except { left, right, .. } => {
                 left.collect_accesses(accesses, cte_names);
@@ -1197,6 +1430,14 @@ impl PhysicalPlan {
                 accesses.add_write(table_name.clone());
             }
 
+            PhysicalPlan::Analyze { table_name } => {
+                accesses.add_read(table_name.clone());
+            }
+
+            PhysicalPlan::Explain { input, .. } => {
+                input.collect_accesses(accesses, cte_names);
+            }
+
             PhysicalPlan::AlterTable {
                 table_name,
                 if_exists,
@@ -1285,6 +1526,8 @@ impl PhysicalPlan {
             | PhysicalPlan::CreateFunction { .. }
             | PhysicalPlan::DropFunction { .. }
             | PhysicalPlan::DropProcedure { .. }
+            | PhysicalPlan::CreateIndex { .. }
+            | PhysicalPlan::DropIndex { .. }
             | PhysicalPlan::Call { .. }
             | PhysicalPlan::Declare { .. }
             | PhysicalPlan::SetVariable { .. }
@@ -1304,6 +1547,7 @@ impl PhysicalPlan {
             | PhysicalPlan::TryCatch { .. }
             | PhysicalPlan::Values { .. }
             | PhysicalPlan::Empty { .. }
+            | PhysicalPlan::TimeTravelScan { .. }
             | PhysicalPlan::GapFill { .. } => {}
         }
     }
@@ -1319,7 +1563,8 @@ impl PhysicalPlan {
             PhysicalPlan::NestedLoopJoin { left, right, .. } => {
                 left.estimate_rows().saturating_mul(right.estimate_rows())
             }
-            PhysicalPlan::HashJoin { left, right, .. } => {
+            PhysicalPlan::HashJoin { left, right, .. }
+            | PhysicalPlan::MergeJoin { left, right, .. } => {
                 std::cmp::max(left.estimate_rows(), right.estimate_rows())
             }
             PhysicalPlan::CrossJoin { left, right, .. } => {
@@ -1409,7 +1654,8 @@ impl PhysicalPlan {
             | PhysicalPlan::Unnest { input, .. }
             | PhysicalPlan::Qualify { input, .. }
             | PhysicalPlan::Sample { input, .. }
-            | PhysicalPlan::GapFill { input, .. } => {
+            | PhysicalPlan::GapFill { input, .. }
+            | PhysicalPlan::Explain { input, .. } => {
                 input.populate_row_counts(catalog);
             }
             PhysicalPlan::HashAggregate { input, .. } => {
@@ -1432,6 +1678,12 @@ impl PhysicalPlan {
                 right,
                 parallel,
                 ..
+            }
+            | PhysicalPlan::MergeJoin {
+                left,
+                right,
+                parallel,
+                ..
             } => {
                 left.populate_row_counts(catalog);
                 right.populate_row_counts(catalog);
@@ -1485,10 +1737,12 @@ impl PhysicalPlan {
             }
             PhysicalPlan::Values { .. }
             | PhysicalPlan::Empty { .. }
+            | PhysicalPlan::TimeTravelScan { .. }
             | PhysicalPlan::CreateTable { .. }
             | PhysicalPlan::DropTable { .. }
             | PhysicalPlan::AlterTable { .. }
             | PhysicalPlan::Truncate { .. }
+            | PhysicalPlan::Analyze { .. }
             | PhysicalPlan::Insert { .. }
             | PhysicalPlan::Update { .. }
             | PhysicalPlan::Delete { .. }
@@ -1503,6 +1757,8 @@ impl PhysicalPlan {
             | PhysicalPlan::DropFunction { .. }
             | PhysicalPlan::CreateProcedure { .. }
             | PhysicalPlan::DropProcedure { .. }
+            | PhysicalPlan::CreateIndex { .. }
+            | PhysicalPlan::DropIndex { .. }
             | PhysicalPlan::Call { .. }
             | PhysicalPlan::SetVariable { .. }
             | PhysicalPlan::SetMultipleVariables { .. }
@@ -1532,3 +1788,16 @@ impl PhysicalPlan {
         }
     }
 }
+
+fn shift_columns(expr: &mut Expr, offset: usize) {
+    if let Expr::Column {
+        index: Some(idx), ..
+    } = expr
+    {
+        *idx += offset;
+        return;
+    }
+    for child in expr.children_mut() {
+        shift_columns(child, offset);
+    }
+}
@@ -0,0 +1,310 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::num::NonZeroUsize;
+use std::sync::RwLock;
+
+use lru::LruCache;
+use yachtsql_ir::{Expr, Literal};
+use yachtsql_optimizer::OptimizedLogicalPlan;
+use yachtsql_parser::{CatalogProvider, FunctionDefinition, ViewDefinition};
+use yachtsql_storage::Schema;
+
+const PLAN_CACHE_SIZE: usize = 10000;
+
+/// Optimized plans keyed on their normalized query text, and on the exact
+/// text of queries already seen so that repeats skip parsing. Each entry
+/// records the tables, views and functions looked up while planning it, and
+/// DDL only evicts the entries that looked up the objects it changes.
+pub(crate) struct PlanCache {
+    entries: RwLock<LruCache<String, CachedPlan>>,
+}
+
+struct CachedPlan {
+    plan: OptimizedLogicalPlan,
+    dependencies: HashSet<String>,
+}
+
+impl PlanCache {
+    pub fn new() -> Self {
+        Self {
+            entries: RwLock::new(LruCache::new(NonZeroUsize::new(PLAN_CACHE_SIZE).unwrap())),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<OptimizedLogicalPlan> {
+        let mut entries = self.entries.write().unwrap();
+        entries.get(key).map(|entry| entry.plan.clone())
+    }
+
+    pub fn put(&self, key: String, plan: OptimizedLogicalPlan, dependencies: HashSet<String>) {
+        self.entries
+            .write()
+            .unwrap()
+            .put(key, CachedPlan { plan, dependencies });
+    }
+
+    /// Caches `plan` under `key` with the dependencies of the entry cached
+    /// under `template`, if there is one.
+    pub fn put_like(&self, key: String, template: &str, plan: OptimizedLogicalPlan) {
+        let mut entries = self.entries.write().unwrap();
+        if let Some(dependencies) = entries.peek(template).map(|e| e.dependencies.clone()) {
+            entries.put(key, CachedPlan { plan, dependencies });
+        }
+    }
+
+    pub fn clear(&self) {
+        self.entries.write().unwrap().clear();
+    }
+
+    /// Evicts the plans depending on the objects in `changes`.
+    pub fn invalidate(&self, changes: &CatalogChanges) {
+        if changes.everything {
+            return self.clear();
+        }
+        if changes.objects.is_empty() {
+            return;
+        }
+
+        let mut entries = self.entries.write().unwrap();
+        let stale: Vec<String> = entries
+            .iter()
+            .filter(|(_, entry)| !entry.dependencies.is_disjoint(&changes.objects))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in stale {
+            entries.pop(&key);
+        }
+    }
+}
+
+/// The catalog objects a statement creates, drops or alters, including
+/// those changed by the statements of its script bodies and of the
+/// procedures it calls.
+#[derive(Debug, Default)]
+pub(crate) struct CatalogChanges {
+    objects: HashSet<String>,
+    /// Schema changes can affect how any name resolves, and the statements
+    /// run by `EXECUTE IMMEDIATE` are not known until they run.
+    everything: bool,
+    views_or_functions: bool,
+    called: HashSet<String>,
+}
+
+impl CatalogChanges {
+    /// Collects the changes made by `plan`. `procedure_body` returns the
+    /// statements of a procedure by name, or `None` when they cannot be
+    /// known, which counts as changing everything. Each procedure is walked
+    /// once.
+    pub fn of(
+        plan: &OptimizedLogicalPlan,
+        procedure_body: &dyn Fn(&str) -> Option<Vec<OptimizedLogicalPlan>>,
+    ) -> Self {
+        let mut changes = Self::default();
+        changes.collect(plan, procedure_body);
+        changes
+    }
+
+    /// Whether a view or SQL function may have been replaced. Cached results
+    /// track the versions of the tables they read, but views and functions
+    /// are expanded at planning time, so this invalidates every result.
+    pub fn redefines_views_or_functions(&self) -> bool {
+        self.everything || self.views_or_functions
+    }
+
+    fn collect(
+        &mut self,
+        plan: &OptimizedLogicalPlan,
+        procedure_body: &dyn Fn(&str) -> Option<Vec<OptimizedLogicalPlan>>,
+    ) {
+        let changed: Vec<&str> = match plan {
+            OptimizedLogicalPlan::Explain {
+                input,
+                analyze: true,
+                ..
+            } => return self.collect(input, procedure_body),
+            OptimizedLogicalPlan::CreateTable { table_name, .. } => vec![table_name],
+            OptimizedLogicalPlan::AlterTable {
+                table_name,
+                operation,
+                ..
+            } => match operation {
+                yachtsql_ir::AlterTableOp::RenameTable { new_name } => {
+                    vec![table_name, new_name]
+                }
+                _ => vec![table_name],
+            },
+            OptimizedLogicalPlan::DropTable { table_names, .. } => {
+                table_names.iter().map(String::as_str).collect()
+            }
+            OptimizedLogicalPlan::CreateView { name, .. }
+            | OptimizedLogicalPlan::DropView { name, .. }
+            | OptimizedLogicalPlan::CreateFunction { name, .. }
+            | OptimizedLogicalPlan::DropFunction { name, .. } => {
+                self.views_or_functions = true;
+                vec![name]
+            }
+            OptimizedLogicalPlan::CreateSnapshot { snapshot_name, .. }
+            | OptimizedLogicalPlan::DropSnapshot { snapshot_name, .. } => vec![snapshot_name],
+            OptimizedLogicalPlan::CreateSchema { .. }
+            | OptimizedLogicalPlan::DropSchema { .. }
+            | OptimizedLogicalPlan::UndropSchema { .. }
+            | OptimizedLogicalPlan::AlterSchema { .. }
+            | OptimizedLogicalPlan::ExecuteImmediate { .. } => {
+                self.everything = true;
+                return;
+            }
+            OptimizedLogicalPlan::Call { procedure_name, .. } => {
+                if !self.called.insert(object_key(procedure_name)) {
+                    return;
+                }
+                match procedure_body(procedure_name) {
+                    Some(body) => {
+                        for statement in &body {
+                            self.collect(statement, procedure_body);
+                        }
+                    }
+                    None => self.everything = true,
+                }
+                return;
+            }
+            _ => {
+                for statement in plan.nested_statements() {
+                    self.collect(statement, procedure_body);
+                }
+                return;
+            }
+        };
+        self.objects.extend(changed.into_iter().map(object_key));
+    }
+}
+
+/// Objects are matched on their unqualified name, so a change to `ds.t`
+/// also evicts plans that read a temp table `t`. This is deliberate: which
+/// table a name resolves to depends on the session that planned it, and
+/// evicting a plan too many only costs a replan, while missing one would
+/// serve a plan built against an old schema.
+pub(crate) fn object_key(name: &str) -> String {
+    let name = name.rsplit('.').next().unwrap_or(name);
+    name.trim_matches('`').to_uppercase()
+}
+
+/// Forwards lookups to the wrapped catalog, remembering every name asked
+/// for, including those that were not found.
+pub(crate) struct DependencyRecorder<'a, C> {
+    catalog: &'a C,
+    names: RefCell<HashSet<String>>,
+}
+
+impl<'a, C: CatalogProvider> DependencyRecorder<'a, C> {
+    pub fn new(catalog: &'a C) -> Self {
+        Self {
+            catalog,
+            names: RefCell::new(HashSet::new()),
+        }
+    }
+
+    pub fn into_dependencies(self) -> HashSet<String> {
+        self.names.into_inner()
+    }
+
+    fn record(&self, name: &str) {
+        self.names.borrow_mut().insert(object_key(name));
+    }
+}
+
+impl<C: CatalogProvider> CatalogProvider for DependencyRecorder<'_, C> {
+    fn get_table_schema(&self, name: &str) -> Option<Schema> {
+        self.record(name);
+        self.catalog.get_table_schema(name)
+    }
+
+    fn get_view(&self, name: &str) -> Option<ViewDefinition> {
+        self.record(name);
+        self.catalog.get_view(name)
+    }
+
+    fn get_function(&self, name: &str) -> Option<FunctionDefinition> {
+        self.record(name);
+        self.catalog.get_function(name)
+    }
+}
+
+/// Replaces the placeholders of a plan built from a normalized query with
+/// the literals hoisted out of its text. Returns false if any of them is not
+/// reachable from the plan's operators, e.g. because planning moved it into
+/// a subquery.
+pub(crate) fn bind_literals(
+    plan: &mut OptimizedLogicalPlan,
+    literals: &[(String, Literal)],
+) -> bool {
+    let mut bound = HashSet::new();
+    bind_plan(plan, literals, &mut bound);
+    bound.len() == literals.len()
+}
+
+fn bind_plan<'a>(
+    plan: &mut OptimizedLogicalPlan,
+    literals: &'a [(String, Literal)],
+    bound: &mut HashSet<&'a str>,
+) {
+    for expr in expressions_mut(plan) {
+        bind_expr(expr, literals, bound);
+    }
+    for child in plan.children_mut() {
+        bind_plan(child, literals, bound);
+    }
+}
+
+fn bind_expr<'a>(expr: &mut Expr, literals: &'a [(String, Literal)], bound: &mut HashSet<&'a str>) {
+    if let Expr::Placeholder { id } = expr
+        && let Some((id, literal)) = literals.iter().find(|(hoisted, _)| hoisted == id)
+    {
+        bound.insert(id);
+        *expr = Expr::Literal(literal.clone());
+        return;
+    }
+    for child in expr.children_mut() {
+        bind_expr(child, literals, bound);
+    }
+}
+
+fn expressions_mut(plan: &mut OptimizedLogicalPlan) -> Vec<&mut Expr> {
+    match plan {
+        OptimizedLogicalPlan::TimeTravelScan { as_of, .. } => vec![as_of],
+        OptimizedLogicalPlan::Filter { predicate, .. }
+        | OptimizedLogicalPlan::Qualify { predicate, .. } => vec![predicate],
+        OptimizedLogicalPlan::Project { expressions, .. } => expressions.iter_mut().collect(),
+        OptimizedLogicalPlan::NestedLoopJoin { condition, .. } => condition.iter_mut().collect(),
+        OptimizedLogicalPlan::HashJoin {
+            left_keys,
+            right_keys,
+            residual,
+            ..
+        }
+        | OptimizedLogicalPlan::MergeJoin {
+            left_keys,
+            right_keys,
+            residual,
+            ..
+        } => left_keys
+            .iter_mut()
+            .chain(right_keys.iter_mut())
+            .chain(residual.iter_mut())
+            .collect(),
+        OptimizedLogicalPlan::HashAggregate {
+            group_by,
+            aggregates,
+            ..
+        } => group_by.iter_mut().chain(aggregates.iter_mut()).collect(),
+        OptimizedLogicalPlan::Sort { sort_exprs, .. }
+        | OptimizedLogicalPlan::TopN { sort_exprs, .. } => {
+            sort_exprs.iter_mut().map(|sort| &mut sort.expr).collect()
+        }
+        OptimizedLogicalPlan::Window { window_exprs, .. } => window_exprs.iter_mut().collect(),
+        OptimizedLogicalPlan::Unnest { columns, .. } => {
+            columns.iter_mut().map(|column| &mut column.expr).collect()
+        }
+        OptimizedLogicalPlan::Values { values, .. } => values.iter_mut().flatten().collect(),
+        _ => Vec::new(),
+    }
+}
@@ -89,6 +89,12 @@ impl<'a> PlanExecutor<'a> {
                 "AlterTable not yet implemented in IR plan executor".into(),
             )),
             LogicalPlan::Truncate { table_name } => self.execute_truncate(table_name),
+            LogicalPlan::Analyze { .. } => Err(Error::UnsupportedFeature(
+                "ANALYZE not yet implemented in IR plan executor".into(),
+            )),
+            LogicalPlan::Explain { .. } => Err(Error::UnsupportedFeature(
+                "EXPLAIN not yet implemented in IR plan executor".into(),
+            )),
             LogicalPlan::Window { .. } => Err(Error::UnsupportedFeature(
                 "Window functions not yet implemented in IR plan executor".into(),
             )),
@@ -0,0 +1,676 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use yachtsql_common::error::{Error, Result};
+use yachtsql_common::result::QueryResult;
+use yachtsql_common::types::{DataType, Value};
+use yachtsql_ir::{BinaryOp, Expr, PlanField, PlanSchema};
+use yachtsql_optimizer::OptimizedLogicalPlan;
+use yachtsql_parser::QueryParameter;
+use yachtsql_storage::{Schema, Table};
+
+use crate::async_executor::AsyncQueryExecutor;
+use crate::cancel::QueryHandle;
+use crate::ir_evaluator::IrEvaluator;
+use crate::plan::PhysicalPlan;
+use crate::plan_cache::DependencyRecorder;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryParams {
+    Named(HashMap<String, Value>),
+    Positional(Vec<Value>),
+}
+
+impl From<Vec<Value>> for QueryParams {
+    fn from(values: Vec<Value>) -> Self {
+        QueryParams::Positional(values)
+    }
+}
+
+impl From<&[Value]> for QueryParams {
+    fn from(values: &[Value]) -> Self {
+        QueryParams::Positional(values.to_vec())
+    }
+}
+
+impl<const N: usize> From<[Value; N]> for QueryParams {
+    fn from(values: [Value; N]) -> Self {
+        QueryParams::Positional(values.to_vec())
+    }
+}
+
+impl From<HashMap<String, Value>> for QueryParams {
+    fn from(values: HashMap<String, Value>) -> Self {
+        QueryParams::Named(values)
+    }
+}
+
+impl<const N: usize> From<[(&str, Value); N]> for QueryParams {
+    fn from(values: [(&str, Value); N]) -> Self {
+        QueryParams::Named(
+            values
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect(),
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParameterInfo {
+    pub name: Option<String>,
+    pub data_type: DataType,
+}
+
+struct PreparedPlan {
+    plan: OptimizedLogicalPlan,
+    parameters: Vec<QueryParameter>,
+    types: Vec<DataType>,
+    dependencies: Vec<(String, Option<Schema>)>,
+    /// The definition generation of each name looked up while planning,
+    /// which changes when a view or function of that name is redefined.
+    definitions: Vec<(String, u64)>,
+}
+
+pub struct PreparedStatement {
+    executor: AsyncQueryExecutor,
+    sql: String,
+    prepared: RwLock<Arc<PreparedPlan>>,
+}
+
+impl PreparedStatement {
+    pub(crate) fn new(executor: AsyncQueryExecutor, sql: String) -> Result<Self> {
+        let prepared = prepare_plan(&executor, &sql)?;
+        Ok(Self {
+            executor,
+            sql,
+            prepared: RwLock::new(Arc::new(prepared)),
+        })
+    }
+
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    pub fn parameters(&self) -> Vec<ParameterInfo> {
+        let prepared = self.prepared.read().unwrap();
+        prepared
+            .parameters
+            .iter()
+            .zip(&prepared.types)
+            .map(|(param, data_type)| ParameterInfo {
+                name: match param {
+                    QueryParameter::Named(name) => Some(name.clone()),
+                    QueryParameter::Positional(_) => None,
+                },
+                data_type: data_type.clone(),
+            })
+            .collect()
+    }
+
+    pub async fn execute(&self, params: impl Into<QueryParams>) -> Result<Table> {
+        let (table, _) = self.run(params.into()).await?;
+        Ok(table)
+    }
+
+    pub async fn query(&self, params: impl Into<QueryParams>) -> Result<QueryResult> {
+        let (table, cache_hit) = self.run(params.into()).await?;
+        let mut result = table.to_query_result()?;
+        result.cache_hit = cache_hit;
+        Ok(result)
+    }
+
+    async fn run(&self, params: QueryParams) -> Result<(Table, bool)> {
+        let prepared = self.current_plan()?;
+        let bindings = self.bind(&prepared, params)?;
+        self.executor
+            .execute_optimized(&self.sql, &prepared.plan, &bindings, &QueryHandle::new())
+            .await
+    }
+
+    fn current_plan(&self) -> Result<Arc<PreparedPlan>> {
+        let prepared = Arc::clone(&self.prepared.read().unwrap());
+        let catalog = self.executor.catalog();
+        let stale = prepared
+            .dependencies
+            .iter()
+            .any(|(name, schema)| catalog.get_table_schema(name) != *schema)
+            || prepared
+                .definitions
+                .iter()
+                .any(|(name, generation)| catalog.definition_generation(name) != *generation);
+        if !stale {
+            return Ok(prepared);
+        }
+        let replanned = Arc::new(prepare_plan(&self.executor, &self.sql)?);
+        *self.prepared.write().unwrap() = Arc::clone(&replanned);
+        Ok(replanned)
+    }
+
+    fn bind(&self, prepared: &PreparedPlan, params: QueryParams) -> Result<Vec<(String, Value)>> {
+        let named = prepared
+            .parameters
+            .iter()
+            .any(|p| matches!(p, QueryParameter::Named(_)));
+        let params = match params {
+            QueryParams::Positional(values) if named && values.is_empty() => {
+                QueryParams::Named(HashMap::new())
+            }
+            params => params,
+        };
+        let mut bindings = Vec::with_capacity(prepared.parameters.len());
+        match params {
+            QueryParams::Positional(values) => {
+                if named {
+                    return Err(Error::invalid_query(
+                        "Query uses named parameters; bind them by name",
+                    ));
+                }
+                if values.len() != prepared.parameters.len() {
+                    return Err(Error::invalid_query(format!(
+                        "Query expects {} positional parameters but {} were provided",
+                        prepared.parameters.len(),
+                        values.len()
+                    )));
+                }
+                for ((param, data_type), value) in
+                    prepared.parameters.iter().zip(&prepared.types).zip(values)
+                {
+                    bindings.push((
+                        param.variable_name(),
+                        coerce_parameter(param, data_type, value)?,
+                    ));
+                }
+            }
+            QueryParams::Named(values) => {
+                if prepared
+                    .parameters
+                    .iter()
+                    .any(|p| matches!(p, QueryParameter::Positional(_)))
+                {
+                    return Err(Error::invalid_query(
+                        "Query uses positional parameters; bind them in order",
+                    ));
+                }
+                let mut values: HashMap<String, Value> = values
+                    .into_iter()
+                    .map(|(name, value)| {
+                        let name = name.strip_prefix('@').unwrap_or(&name).to_uppercase();
+                        (format!("@{}", name), value)
+                    })
+                    .collect();
+                for (param, data_type) in prepared.parameters.iter().zip(&prepared.types) {
+                    let key = param.variable_name();
+                    match values.remove(&key) {
+                        Some(value) => {
+                            let value = coerce_parameter(param, data_type, value)?;
+                            bindings.push((key, value));
+                        }
+                        None if self.executor.session().get_variable(&key).is_some() => {}
+                        None => {
+                            return Err(Error::invalid_query(format!(
+                                "Missing value for query parameter {}",
+                                display_name(param)
+                            )));
+                        }
+                    }
+                }
+                if let Some(extra) = values.keys().next() {
+                    return Err(Error::invalid_query(format!(
+                        "Query has no parameter named {}",
+                        extra
+                    )));
+                }
+            }
+        }
+        Ok(bindings)
+    }
+}
+
+fn prepare_plan(executor: &AsyncQueryExecutor, sql: &str) -> Result<PreparedPlan> {
+    let recorder = DependencyRecorder::new(executor);
+    let (logical, parameters) = yachtsql_parser::parse_and_plan_with_parameters(sql, &recorder)?;
+    let named = parameters
+        .iter()
+        .any(|p| matches!(p, QueryParameter::Named(_)));
+    let positional = parameters
+        .iter()
+        .any(|p| matches!(p, QueryParameter::Positional(_)));
+    if named && positional {
+        return Err(Error::invalid_query(
+            "Query cannot mix named (@name) and positional (?) parameters",
+        ));
+    }
+
+    let plan = executor.optimize(&logical)?;
+
+    let mut inference = TypeInference::new(executor);
+    inference.visit_plan(&plan);
+    let types = parameters
+        .iter()
+        .map(|p| {
+            inference
+                .types
+                .get(&p.variable_name())
+                .cloned()
+                .unwrap_or(DataType::Unknown)
+        })
+        .collect();
+
+    let catalog = executor.catalog();
+    let dependencies = PhysicalPlan::from_physical(&plan)
+        .extract_table_accesses()
+        .accesses
+        .into_keys()
+        .map(|name| {
+            let schema = catalog.get_table_schema(&name);
+            (name, schema)
+        })
+        .collect();
+    let definitions = recorder
+        .into_dependencies()
+        .into_iter()
+        .map(|name| {
+            let generation = catalog.definition_generation(&name);
+            (name, generation)
+        })
+        .collect();
+
+    Ok(PreparedPlan {
+        plan,
+        parameters,
+        types,
+        dependencies,
+        definitions,
+    })
+}
+
+fn display_name(param: &QueryParameter) -> String {
+    match param {
+        QueryParameter::Named(name) => format!("@{}", name),
+        QueryParameter::Positional(position) => format!("at position {}", position),
+    }
+}
+
+fn coerce_parameter(param: &QueryParameter, expected: &DataType, value: Value) -> Result<Value> {
+    let actual = value.data_type();
+    if value.is_null()
+        || *expected == DataType::Unknown
+        || std::mem::discriminant(&actual) == std::mem::discriminant(expected)
+    {
+        return Ok(value);
+    }
+    match (&actual, expected) {
+        (DataType::Int64, DataType::Float64)
+        | (DataType::Int64, DataType::Numeric(_))
+        | (DataType::Int64, DataType::BigNumeric)
+        | (DataType::Numeric(_), DataType::BigNumeric)
+        | (DataType::Date, DataType::DateTime)
+        | (DataType::Date, DataType::Timestamp) => IrEvaluator::cast_value(value, expected, false),
+        _ => Err(Error::invalid_query(format!(
+            "Query parameter {} has type {} but {} was expected",
+            display_name(param),
+            actual.to_bq_type(),
+            expected.to_bq_type()
+        ))),
+    }
+}
+
+fn parameter_key(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Placeholder { id } => Some(id.clone()),
+        Expr::Variable { name } if name.starts_with('@') && !name.starts_with("@@") => {
+            Some(name.to_uppercase())
+        }
+        Expr::Alias { expr, .. } => parameter_key(expr),
+        _ => None,
+    }
+}
+
+fn static_type(expr: &Expr, schema: &PlanSchema) -> Option<DataType> {
+    let data_type = match expr {
+        Expr::Literal(lit) => lit.data_type(),
+        Expr::Column {
+            index: Some(index), ..
+        } => schema.fields.get(*index)?.data_type.clone(),
+        Expr::Column { name, .. } => schema.field_by_name(name)?.1.data_type.clone(),
+        Expr::Cast { data_type, .. } => data_type.clone(),
+        Expr::TypedString { data_type, .. } => data_type.clone(),
+        Expr::Alias { expr, .. } => return static_type(expr, schema),
+        _ => return None,
+    };
+    (data_type != DataType::Unknown).then_some(data_type)
+}
+
+fn table_plan_schema(schema: &Schema) -> PlanSchema {
+    PlanSchema {
+        fields: schema
+            .fields()
+            .iter()
+            .map(|f| PlanField::new(f.name.clone(), f.data_type.clone()))
+            .collect(),
+    }
+}
+
+struct TypeInference<'a> {
+    executor: &'a AsyncQueryExecutor,
+    types: HashMap<String, DataType>,
+}
+
+impl<'a> TypeInference<'a> {
+    fn new(executor: &'a AsyncQueryExecutor) -> Self {
+        Self {
+            executor,
+            types: HashMap::new(),
+        }
+    }
+
+    fn hint(&mut self, expr: &Expr, data_type: Option<DataType>) {
+        if let (Some(key), Some(data_type)) = (parameter_key(expr), data_type) {
+            self.types.entry(key).or_insert(data_type);
+        }
+    }
+
+    fn hint_pair(&mut self, left: &Expr, right: &Expr, schema: &PlanSchema) {
+        self.hint(left, static_type(right, schema));
+        self.hint(right, static_type(left, schema));
+    }
+
+    fn table_schema(&self, table_name: &str) -> Option<PlanSchema> {
+        self.executor
+            .catalog()
+            .get_table_schema(table_name)
+            .map(|schema| table_plan_schema(&schema))
+    }
+
+    fn visit_plan(&mut self, plan: &OptimizedLogicalPlan) {
+        match plan {
+            OptimizedLogicalPlan::Filter { input, predicate }
+            | OptimizedLogicalPlan::Qualify { input, predicate } => {
+                self.visit_expr(predicate, input.schema());
+                self.visit_plan(input);
+            }
+            OptimizedLogicalPlan::Project {
+                input, expressions, ..
+            } => {
+                for expr in expressions {
+                    self.visit_expr(expr, input.schema());
+                }
+                self.visit_plan(input);
+            }
+            OptimizedLogicalPlan::NestedLoopJoin {
+                left,
+                right,
+                condition,
+                schema,
+                ..
+            } => {
+                if let Some(condition) = condition {
+                    self.visit_expr(condition, schema);
+                }
+                self.visit_plan(left);
+                self.visit_plan(right);
+            }
+            OptimizedLogicalPlan::HashJoin {
+                left,
+                right,
+                left_keys,
+                right_keys,
+                residual,
+                schema,
+                ..
+            }
+            | OptimizedLogicalPlan::MergeJoin {
+                left,
+                right,
+                left_keys,
+                right_keys,
+                residual,
+                schema,
+                ..
+            } => {
+                for (l, r) in left_keys.iter().zip(right_keys) {
+                    self.hint(l, static_type(r, right.schema()));
+                    self.hint(r, static_type(l, left.schema()));
+                    self.visit_expr(l, left.schema());
+                    self.visit_expr(r, right.schema());
+                }
+                if let Some(residual) = residual {
+                    self.visit_expr(residual, schema);
+                }
+                self.visit_plan(left);
+                self.visit_plan(right);
+            }
+            OptimizedLogicalPlan::HashAggregate {
+                input,
+                group_by,
+                aggregates,
+                ..
+            } => {
+                for expr in group_by.iter().chain(aggregates) {
+                    self.visit_expr(expr, input.schema());
+                }
+                self.visit_plan(input);
+            }
+            OptimizedLogicalPlan::Sort { input, sort_exprs }
+            | OptimizedLogicalPlan::TopN {
+                input, sort_exprs, ..
+            } => {
+                for sort_expr in sort_exprs {
+                    self.visit_expr(&sort_expr.expr, input.schema());
+                }
+                self.visit_plan(input);
+            }
+            OptimizedLogicalPlan::Window {
+                input,
+                window_exprs,
+                ..
+            } => {
+                for expr in window_exprs {
+                    self.visit_expr(expr, input.schema());
+                }
+                self.visit_plan(input);
+            }
+            OptimizedLogicalPlan::Values { values, schema } => {
+                for row in values {
+                    for (i, expr) in row.iter().enumerate() {
+                        let field_type = schema
+                            .fields
+                            .get(i)
+                            .map(|f| f.data_type.clone())
+                            .filter(|t| *t != DataType::Unknown);
+                        self.hint(expr, field_type);
+                        self.visit_expr(expr, schema);
+                    }
+                }
+            }
+            OptimizedLogicalPlan::Insert {
+                table_name,
+                columns,
+                source,
+            } => {
+                if let (Some(table_schema), OptimizedLogicalPlan::Values { values, .. }) =
+                    (self.table_schema(table_name), source.as_ref())
+                {
+                    let target_types: Vec<Option<DataType>> = if columns.is_empty() {
+                        table_schema
+                            .fields
+                            .iter()
+                            .map(|f| Some(f.data_type.clone()))
+                            .collect()
+                    } else {
+                        columns
+                            .iter()
+                            .map(|c| {
+                                table_schema
+                                    .field_by_name(c)
+                                    .map(|(_, f)| f.data_type.clone())
+                            })
+                            .collect()
+                    };
+                    for row in values {
+                        for (expr, target) in row.iter().zip(&target_types) {
+                            self.hint(expr, target.clone());
+                        }
+                    }
+                }
+                self.visit_plan(source);
+            }
+            OptimizedLogicalPlan::Update {
+                table_name,
+                assignments,
+                from,
+                filter,
+                ..
+            } => {
+                let mut schema = self.table_schema(table_name).unwrap_or_default();
+                for assignment in assignments {
+                    let column_type = schema
+                        .field_by_name(&assignment.column)
+                        .map(|(_, f)| f.data_type.clone());
+                    self.hint(&assignment.value, column_type);
+                }
+                if let Some(from) = from {
+                    schema.fields.extend(from.schema().fields.iter().cloned());
+                    self.visit_plan(from);
+                }
+                for assignment in assignments {
+                    self.visit_expr(&assignment.value, &schema);
+                }
+                if let Some(filter) = filter {
+                    self.visit_expr(filter, &schema);
+                }
+            }
+            OptimizedLogicalPlan::Delete {
+                table_name,
+                filter: Some(filter),
+                ..
+            } => {
+                let schema = self.table_schema(table_name).unwrap_or_default();
+                self.visit_expr(filter, &schema);
+            }
+            OptimizedLogicalPlan::Sample { input, .. }
+            | OptimizedLogicalPlan::Limit { input, .. }
+            | OptimizedLogicalPlan::Distinct { input }
+            | OptimizedLogicalPlan::Unnest { input, .. }
+            | OptimizedLogicalPlan::GapFill { input, .. } => self.visit_plan(input),
+            OptimizedLogicalPlan::CrossJoin { left, right, .. }
+            | OptimizedLogicalPlan::Intersect { left, right, .. }
+            | OptimizedLogicalPlan::Except { left, right, .. } => {
+                self.visit_plan(left);
+                self.visit_plan(right);
+            }
+            OptimizedLogicalPlan::Union { inputs, .. } => {
+                for input in inputs {
+                    self.visit_plan(input);
+                }
+            }
+            OptimizedLogicalPlan::WithCte { body, .. } => self.visit_plan(body),
+            OptimizedLogicalPlan::Merge { source, .. } => self.visit_plan(source),
+            OptimizedLogicalPlan::CreateTable {
+                query: Some(query), ..
+            } => self.visit_plan(query),
+            _ => {}
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &Expr, schema: &PlanSchema) {
+        match expr {
+            Expr::BinaryOp { left, op, right } => {
+                match op {
+                    BinaryOp::And | BinaryOp::Or => {
+                        self.hint(left, Some(DataType::Bool));
+                        self.hint(right, Some(DataType::Bool));
+                    }
+                    BinaryOp::Concat => {
+                        self.hint(left, static_type(right, schema).or(Some(DataType::String)));
+                        self.hint(right, static_type(left, schema).or(Some(DataType::String)));
+                    }
+                    _ => self.hint_pair(left, right, schema),
+                }
+                self.visit_expr(left, schema);
+                self.visit_expr(right, schema);
+            }
+            Expr::IsDistinctFrom { left, right, .. } => {
+                self.hint_pair(left, right, schema);
+                self.visit_expr(left, schema);
+                self.visit_expr(right, schema);
+            }
+            Expr::InList { expr, list, .. } => {
+                let expr_type = static_type(expr, schema);
+                for item in list {
+                    self.hint(item, expr_type.clone());
+                    self.hint(expr, static_type(item, schema));
+                    self.visit_expr(item, schema);
+                }
+                self.visit_expr(expr, schema);
+            }
+            Expr::Between {
+                expr, low, high, ..
+            } => {
+                let expr_type = static_type(expr, schema);
+                self.hint(low, expr_type.clone());
+                self.hint(high, expr_type);
+                self.hint(expr, static_type(low, schema).or(static_type(high, schema)));
+                self.visit_expr(expr, schema);
+                self.visit_expr(low, schema);
+                self.visit_expr(high, schema);
+            }
+            Expr::Like { expr, pattern, .. } => {
+                self.hint(expr, Some(DataType::String));
+                self.hint(pattern, Some(DataType::String));
+                self.visit_expr(expr, schema);
+                self.visit_expr(pattern, schema);
+            }
+            Expr::Case {
+                operand,
+                when_clauses,
+                else_result,
+            } => {
+                if let Some(operand) = operand {
+                    for clause in when_clauses {
+                        self.hint_pair(operand, &clause.condition, schema);
+                    }
+                    self.visit_expr(operand, schema);
+                } else {
+                    for clause in when_clauses {
+                        self.hint(&clause.condition, Some(DataType::Bool));
+                    }
+                }
+                for clause in when_clauses {
+                    self.visit_expr(&clause.condition, schema);
+                    self.visit_expr(&clause.result, schema);
+                }
+                if let Some(else_result) = else_result {
+                    self.visit_expr(else_result, schema);
+                }
+            }
+            Expr::UnaryOp { expr, .. }
+            | Expr::Cast { expr, .. }
+            | Expr::IsNull { expr, .. }
+            | Expr::Alias { expr, .. }
+            | Expr::StructAccess { expr, .. }
+            | Expr::Extract { expr, .. }
+            | Expr::JsonAccess { expr, .. }
+            | Expr::Interval { value: expr, .. } => self.visit_expr(expr, schema),
+            Expr::ScalarFunction { args, .. }
+            | Expr::Aggregate { args, .. }
+            | Expr::UserDefinedAggregate { args, .. } => {
+                for arg in args {
+                    self.visit_expr(arg, schema);
+                }
+            }
+            Expr::Array { elements, .. } => {
+                for element in elements {
+                    self.visit_expr(element, schema);
+                }
+            }
+            Expr::Struct { fields } => {
+                for (_, field) in fields {
+                    self.visit_expr(field, schema);
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use std::num::NonZeroUsize;
+use std::sync::RwLock;
+
+use lru::LruCache;
+use yachtsql_common::types::Value;
+use yachtsql_ir::{Expr, ScalarFunction};
+use yachtsql_optimizer::OptimizedLogicalPlan;
+use yachtsql_storage::Table;
+
+use crate::concurrent_catalog::ConcurrentCatalog;
+
+const RESULT_CACHE_SIZE: usize = 1000;
+
+/// Results of deterministic queries, keyed on their canonical text and the
+/// values of their parameters and session variables. An entry stays valid
+/// while every table the query read is at the version it had when the query
+/// ran.
+pub(crate) struct ResultCache {
+    entries: RwLock<LruCache<String, CachedResult>>,
+}
+
+struct CachedResult {
+    tables: Vec<String>,
+    versions: Vec<(String, u64)>,
+    result: Table,
+}
+
+impl ResultCache {
+    pub fn new() -> Self {
+        Self {
+            entries: RwLock::new(LruCache::new(NonZeroUsize::new(RESULT_CACHE_SIZE).unwrap())),
+        }
+    }
+
+    /// Queries differing only in whitespace or keyword case share a key.
+    pub fn key(sql: &str, values: &[(String, Value)]) -> String {
+        let mut key = yachtsql_parser::canonical_query(sql).unwrap_or_else(|| normalize_sql(sql));
+        for (name, value) in values {
+            key.push_str(&format!("\u{0}{}={:?}", name, value));
+        }
+        key
+    }
+
+    /// The cached result for `key` if none of the tables it read changed
+    /// since. Stale entries are dropped.
+    pub fn get(&self, key: &str, catalog: &ConcurrentCatalog) -> Option<Table> {
+        let mut entries = self.entries.write().unwrap();
+        let entry = entries.get(key)?;
+        if catalog.table_versions(&entry.tables).as_ref() == Some(&entry.versions) {
+            return Some(entry.result.clone());
+        }
+        entries.pop(key);
+        None
+    }
+
+    /// Caches `result` of a query that read `tables` at `versions`, which
+    /// must be taken before the query read them.
+    pub fn put(
+        &self,
+        key: String,
+        tables: Vec<String>,
+        versions: Vec<(String, u64)>,
+        result: Table,
+    ) {
+        self.entries.write().unwrap().put(
+            key,
+            CachedResult {
+                tables,
+                versions,
+                result,
+            },
+        );
+    }
+
+    pub fn clear(&self) {
+        self.entries.write().unwrap().clear();
+    }
+}
+
+/// Collapses whitespace outside of literals and quoted identifiers and drops
+/// a trailing semicolon, for text that does not parse as a single query.
+fn normalize_sql(sql: &str) -> String {
+    let mut normalized = String::with_capacity(sql.len());
+    let mut quote = None;
+    let mut chars = sql.trim().trim_end_matches(';').trim_end().chars();
+    while let Some(c) = chars.next() {
+        match quote {
+            Some(q) => {
+                normalized.push(c);
+                if c == '\\' {
+                    normalized.extend(chars.next());
+                } else if c == q {
+                    quote = None;
+                }
+            }
+            None if c.is_whitespace() => {
+                if !normalized.ends_with(' ') {
+                    normalized.push(' ');
+                }
+            }
+            None => {
+                if matches!(c, '\'' | '"' | '`') {
+                    quote = Some(c);
+                }
+                normalized.push(c);
+            }
+        }
+    }
+    normalized
+}
+
+/// Whether `plan` is a query whose result depends only on its text, the
+/// values of its parameters and variables, and the tables it scans.
+pub(crate) fn is_cacheable_query(plan: &OptimizedLogicalPlan) -> bool {
+    let expressions: Vec<&Expr> = match plan {
+        OptimizedLogicalPlan::TableScan { .. }
+        | OptimizedLogicalPlan::Limit { .. }
+        | OptimizedLogicalPlan::Distinct { .. }
+        | OptimizedLogicalPlan::Union { .. }
+        | OptimizedLogicalPlan::Intersect { .. }
+        | OptimizedLogicalPlan::Except { .. }
+        | OptimizedLogicalPlan::CrossJoin { .. }
+        | OptimizedLogicalPlan::Empty { .. } => Vec::new(),
+        OptimizedLogicalPlan::Filter { predicate, .. }
+        | OptimizedLogicalPlan::Qualify { predicate, .. } => vec![predicate],
+        OptimizedLogicalPlan::Project { expressions, .. } => expressions.iter().collect(),
+        OptimizedLogicalPlan::NestedLoopJoin { condition, .. } => condition.iter().collect(),
+        OptimizedLogicalPlan::HashJoin {
+            left_keys,
+            right_keys,
+            residual,
+            ..
+        }
+        | OptimizedLogicalPlan::MergeJoin {
+            left_keys,
+            right_keys,
+            residual,
+            ..
+        } => left_keys
+            .iter()
+            .chain(right_keys)
+            .chain(residual.as_ref())
+            .collect(),
+        OptimizedLogicalPlan::HashAggregate {
+            group_by,
+            aggregates,
+            ..
+        } => group_by.iter().chain(aggregates).collect(),
+        OptimizedLogicalPlan::Sort { sort_exprs, .. }
+        | OptimizedLogicalPlan::TopN { sort_exprs, .. } => {
+            sort_exprs.iter().map(|sort| &sort.expr).collect()
+        }
+        OptimizedLogicalPlan::Window { window_exprs, .. } => window_exprs.iter().collect(),
+        OptimizedLogicalPlan::Unnest { columns, .. } => {
+            columns.iter().map(|column| &column.expr).collect()
+        }
+        OptimizedLogicalPlan::Values { values, .. } => values.iter().flatten().collect(),
+        OptimizedLogicalPlan::WithCte { ctes, .. } => {
+            let ctes_cacheable = ctes.iter().all(|cte| {
+                yachtsql_optimizer::optimize(&cte.query).is_ok_and(|plan| is_cacheable_query(&plan))
+            });
+            if !ctes_cacheable {
+                return false;
+            }
+            Vec::new()
+        }
+        _ => return false,
+    };
+    expressions.into_iter().all(is_deterministic)
+        && plan.children().into_iter().all(is_cacheable_query)
+}
+
+fn is_deterministic(expr: &Expr) -> bool {
+    match expr {
+        Expr::Subquery(_)
+        | Expr::ScalarSubquery(_)
+        | Expr::ArraySubquery(_)
+        | Expr::Exists { .. }
+        | Expr::InSubquery { .. }
+        | Expr::Parameter { .. }
+        | Expr::UserDefinedAggregate { .. } => false,
+        Expr::ScalarFunction {
+            name:
+                ScalarFunction::Rand
+                | ScalarFunction::RandCanonical
+                | ScalarFunction::GenerateUuid
+                | ScalarFunction::CurrentDate
+                | ScalarFunction::CurrentTimestamp
+                | ScalarFunction::CurrentTime
+                | ScalarFunction::CurrentDatetime
+                | ScalarFunction::SessionUser
+                | ScalarFunction::Custom(_),
+            ..
+        } => false,
+        other => other.children().into_iter().all(is_deterministic),
+    }
+}
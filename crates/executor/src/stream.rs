@@ -0,0 +1,313 @@
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::Stream;
+use yachtsql_common::error::Result;
+use yachtsql_common::types::Value;
+use yachtsql_ir::{Expr, PlanSchema};
+use yachtsql_storage::{Schema, Table};
+
+use crate::cancel::Interrupt;
+use crate::executor::concurrent::{
+    ConcurrentPlanExecutor, filter_table, planned_layout, project_table,
+};
+use crate::executor::plan_schema_to_schema;
+use crate::ir_evaluator::{IrEvaluator, UserFunctionDef};
+use crate::plan::PhysicalPlan;
+
+/// The result of a query as a stream of record batches of at most one
+/// segment each.
+///
+/// The filters, projections and limits at the top of the plan are streamed
+/// one batch per poll on the polling task. When they read a table directly,
+/// the scan is streamed too: each poll takes the next segment of the table
+/// as of when the stream was opened, so a satisfied `LIMIT` or a dropped
+/// stream never touches the rest of it. Any other input, such as a join,
+/// aggregate, sort, view or CTE, runs to completion when the stream is
+/// opened. Each poll checks the query's cancellation handle and timeout.
+pub struct QueryStream {
+    schema: Schema,
+    source: Source,
+    stages: Vec<Stage>,
+    context: StageContext,
+    interrupt: Interrupt,
+    done: bool,
+}
+
+/// Where the batches of a stream come from.
+enum Source {
+    /// The segments of a computed input.
+    Batches(std::vec::IntoIter<Table>),
+    /// The segments of a scanned table, laid out for the scan one at a time.
+    Scan {
+        table: Table,
+        next: usize,
+        layout: Option<(Schema, Vec<usize>)>,
+    },
+}
+
+/// A streamable operator split off the top of a plan.
+pub(crate) enum Stage {
+    Filter(Expr),
+    Project {
+        expressions: Vec<Expr>,
+        schema: Schema,
+    },
+    Limit {
+        skip: usize,
+        remaining: Option<usize>,
+    },
+}
+
+struct StageContext {
+    variables: HashMap<String, Value>,
+    system_variables: HashMap<String, Value>,
+    user_functions: HashMap<String, UserFunctionDef>,
+}
+
+impl QueryStream {
+    /// Streams an already materialized result.
+    pub(crate) fn from_table(table: Table) -> Self {
+        Self {
+            schema: table.schema().clone(),
+            source: Source::Batches(table.split_segments().into_iter()),
+            stages: Vec::new(),
+            context: StageContext {
+                variables: HashMap::new(),
+                system_variables: HashMap::new(),
+                user_functions: HashMap::new(),
+            },
+            interrupt: Interrupt::default(),
+            done: false,
+        }
+    }
+
+    /// Splits the streamable operators off the top of `plan`, returning the
+    /// plan that produces their input.
+    pub(crate) fn split_pipeline(plan: PhysicalPlan) -> (PhysicalPlan, Vec<Stage>) {
+        let mut stages = Vec::new();
+        let mut plan = plan;
+        loop {
+            plan = match plan {
+                PhysicalPlan::Filter { input, predicate }
+                    if !ConcurrentPlanExecutor::expr_contains_subquery(&predicate) =>
+                {
+                    stages.push(Stage::Filter(predicate));
+                    *input
+                }
+                PhysicalPlan::Project {
+                    input,
+                    expressions,
+                    schema,
+                } if !expressions
+                    .iter()
+                    .any(ConcurrentPlanExecutor::expr_contains_subquery) =>
+                {
+                    stages.push(Stage::Project {
+                        expressions,
+                        schema: plan_schema_to_schema(&schema),
+                    });
+                    *input
+                }
+                PhysicalPlan::Limit {
+                    input,
+                    limit,
+                    offset,
+                } => {
+                    stages.push(Stage::Limit {
+                        skip: offset.unwrap_or(0),
+                        remaining: limit,
+                    });
+                    *input
+                }
+                plan => break (plan, stages.into_iter().rev().collect()),
+            }
+        }
+    }
+
+    /// Streams `source` through `stages`, evaluating expressions with the
+    /// variables and functions of `executor`.
+    pub(crate) fn new(
+        source: Table,
+        stages: Vec<Stage>,
+        executor: &ConcurrentPlanExecutor,
+    ) -> Self {
+        let schema = source.schema().clone();
+        let source = Source::Batches(source.split_segments().into_iter());
+        Self::with_source(schema, source, stages, executor)
+    }
+
+    /// Streams a scan of `table` through `stages`, reading one segment of
+    /// `table` per batch.
+    pub(crate) fn scan(
+        table: Table,
+        planned_schema: &PlanSchema,
+        projection: Option<&[usize]>,
+        stages: Vec<Stage>,
+        executor: &ConcurrentPlanExecutor,
+    ) -> Self {
+        let layout = planned_layout(table.schema(), planned_schema, projection);
+        let schema = match &layout {
+            Some((schema, _)) => schema.clone(),
+            None => table.schema().clone(),
+        };
+        let source = Source::Scan {
+            table,
+            next: 0,
+            layout,
+        };
+        Self::with_source(schema, source, stages, executor)
+    }
+
+    fn with_source(
+        source_schema: Schema,
+        source: Source,
+        stages: Vec<Stage>,
+        executor: &ConcurrentPlanExecutor,
+    ) -> Self {
+        let schema = stages
+            .iter()
+            .rev()
+            .find_map(|stage| match stage {
+                Stage::Project { schema, .. } => Some(schema.clone()),
+                Stage::Filter(_) | Stage::Limit { .. } => None,
+            })
+            .unwrap_or(source_schema);
+        Self {
+            schema,
+            source,
+            stages,
+            context: StageContext {
+                variables: executor.get_variables().clone(),
+                system_variables: executor.get_system_variables().clone(),
+                user_functions: executor.get_user_functions().clone(),
+            },
+            interrupt: executor.interrupt.clone(),
+            done: false,
+        }
+    }
+
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    fn next_batch(&mut self) -> Result<Option<Table>> {
+        while !self.done {
+            self.interrupt.check()?;
+            let Some(mut batch) = self.source.next() else {
+                self.done = true;
+                break;
+            };
+            for stage in &mut self.stages {
+                if batch.row_count() == 0 {
+                    break;
+                }
+                batch = stage.apply(batch, &self.context)?;
+                if stage.is_exhausted() {
+                    self.done = true;
+                }
+            }
+            if batch.row_count() > 0 {
+                return Ok(Some(batch));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl Stream for QueryStream {
+    type Item = Result<Table>;
+
+    fn poll_next(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let stream = self.get_mut();
+        let batch = stream.next_batch();
+        if batch.is_err() {
+            stream.done = true;
+        }
+        Poll::Ready(batch.transpose())
+    }
+}
+
+impl Source {
+    fn next(&mut self) -> Option<Table> {
+        match self {
+            Source::Batches(batches) => batches.next(),
+            Source::Scan {
+                table,
+                next,
+                layout,
+            } => {
+                while *next < table.segments().len() {
+                    let batch = table.segment_table(*next);
+                    *next += 1;
+                    if batch.row_count() == 0 {
+                        continue;
+                    }
+                    return Some(match layout {
+                        Some((schema, column_indices)) => {
+                            batch.with_reordered_schema(schema.clone(), column_indices)
+                        }
+                        None => batch,
+                    });
+                }
+                None
+            }
+        }
+    }
+}
+
+impl Stage {
+    fn apply(&mut self, batch: Table, context: &StageContext) -> Result<Table> {
+        match self {
+            Stage::Filter(predicate) => {
+                filter_table(&context.evaluator(batch.schema()), &batch, predicate)
+            }
+            Stage::Project {
+                expressions,
+                schema,
+            } => project_table(
+                &context.evaluator(batch.schema()),
+                &batch,
+                expressions,
+                schema,
+            ),
+            Stage::Limit { skip, remaining } => {
+                let rows = batch.row_count();
+                let start = (*skip).min(rows);
+                *skip -= start;
+                let end = match remaining {
+                    Some(remaining) => {
+                        let end = start + (*remaining).min(rows - start);
+                        *remaining -= end - start;
+                        end
+                    }
+                    None => rows,
+                };
+                if start == 0 && end == rows {
+                    return Ok(batch);
+                }
+                batch.gather_rows(&(start..end).collect::<Vec<_>>())
+            }
+        }
+    }
+
+    fn is_exhausted(&self) -> bool {
+        matches!(
+            self,
+            Stage::Limit {
+                remaining: Some(0),
+                ..
+            }
+        )
+    }
+}
+
+impl StageContext {
+    fn evaluator<'a>(&'a self, schema: &'a Schema) -> IrEvaluator<'a> {
+        IrEvaluator::new(schema)
+            .with_variables(&self.variables)
+            .with_system_variables(&self.system_variables)
+            .with_user_functions(&self.user_functions)
+    }
+}
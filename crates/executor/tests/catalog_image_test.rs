@@ -0,0 +1,193 @@
+use yachtsql_common::types::Value;
+use yachtsql_executor::{AsyncQueryExecutor, ConcurrentCatalog};
+
+async fn column_values(executor: &AsyncQueryExecutor, sql: &str) -> Vec<Vec<Value>> {
+    let table = executor.execute_sql(sql).await.unwrap();
+    table
+        .to_records()
+        .unwrap()
+        .into_iter()
+        .map(|r| r.into_values())
+        .collect()
+}
+
+async fn golden_fixture() -> AsyncQueryExecutor {
+    let executor = AsyncQueryExecutor::new();
+    executor
+        .execute_sql("CREATE TABLE users (id INT64, name STRING DEFAULT 'anon')")
+        .await
+        .unwrap();
+    executor
+        .execute_sql("INSERT INTO users VALUES (1, 'Alice'), (2, 'Bob')")
+        .await
+        .unwrap();
+    executor
+        .execute_sql("CREATE VIEW user_names AS SELECT name FROM users")
+        .await
+        .unwrap();
+    executor
+        .execute_sql("CREATE FUNCTION double_it(x INT64) AS (x * 2)")
+        .await
+        .unwrap();
+    executor
+        .execute_sql(
+            "CREATE PROCEDURE add_user(uid INT64) BEGIN INSERT INTO users (id) VALUES (uid); END",
+        )
+        .await
+        .unwrap();
+    executor.execute_sql("CREATE SCHEMA sales").await.unwrap();
+    executor
+        .execute_sql("CREATE TABLE sales.orders (id INT64)")
+        .await
+        .unwrap();
+    executor
+}
+
+#[tokio::test]
+async fn test_restored_image_has_full_catalog() {
+    let golden = golden_fixture().await;
+    let image = golden.catalog().snapshot();
+
+    let executor = AsyncQueryExecutor::from_image(&image);
+    executor.execute_sql("CALL add_user(3)").await.unwrap();
+    assert_eq!(
+        column_values(&executor, "SELECT id, name FROM users ORDER BY id").await,
+        vec![
+            vec![Value::int64(1), Value::string("Alice")],
+            vec![Value::int64(2), Value::string("Bob")],
+            vec![Value::int64(3), Value::string("anon")],
+        ]
+    );
+    assert_eq!(
+        column_values(&executor, "SELECT COUNT(*) FROM user_names").await,
+        vec![vec![Value::int64(3)]]
+    );
+    assert_eq!(
+        column_values(&executor, "SELECT double_it(21)").await,
+        vec![vec![Value::int64(42)]]
+    );
+    assert_eq!(
+        column_values(&executor, "SELECT COUNT(*) FROM sales.orders").await,
+        vec![vec![Value::int64(0)]]
+    );
+}
+
+#[tokio::test]
+async fn test_restored_tables_share_columns_until_written() {
+    let golden = golden_fixture().await;
+    let image = golden.catalog().snapshot();
+
+    let first = ConcurrentCatalog::from_image(&image);
+    let second = ConcurrentCatalog::from_image(&image);
+    let source = golden.catalog().get_table_handle("users").unwrap();
+    assert!(
+        first
+            .get_table_handle("users")
+            .unwrap()
+            .read()
+            .shares_column_buffers(&source.read())
+    );
+    assert!(
+        second
+            .get_table_handle("users")
+            .unwrap()
+            .read()
+            .shares_column_buffers(&source.read())
+    );
+
+    let forked = AsyncQueryExecutor::from_image(&image);
+    forked
+        .execute_sql("INSERT INTO users VALUES (9, 'Zed')")
+        .await
+        .unwrap();
+    assert!(
+        !forked
+            .catalog()
+            .get_table_handle("users")
+            .unwrap()
+            .read()
+            .shares_column_buffers(&source.read())
+    );
+    assert_eq!(
+        column_values(&golden, "SELECT COUNT(*) FROM users").await,
+        vec![vec![Value::int64(2)]]
+    );
+    assert_eq!(
+        column_values(&forked, "SELECT COUNT(*) FROM users").await,
+        vec![vec![Value::int64(3)]]
+    );
+}
+
+#[tokio::test]
+async fn test_image_is_unaffected_by_later_source_changes() {
+    let golden = golden_fixture().await;
+    let image = golden.catalog().snapshot();
+
+    golden.execute_sql("DROP TABLE users").await.unwrap();
+    golden
+        .execute_sql("DROP SCHEMA sales CASCADE")
+        .await
+        .unwrap();
+
+    let executor = AsyncQueryExecutor::from_image(&image);
+    assert_eq!(
+        column_values(&executor, "SELECT COUNT(*) FROM users").await,
+        vec![vec![Value::int64(2)]]
+    );
+    assert_eq!(
+        column_values(&executor, "SELECT COUNT(*) FROM sales.orders").await,
+        vec![vec![Value::int64(0)]]
+    );
+}
+
+#[tokio::test]
+async fn test_image_preserves_search_path() {
+    let golden = AsyncQueryExecutor::new();
+    golden.execute_sql("CREATE SCHEMA app").await.unwrap();
+    golden
+        .execute_sql("CREATE TABLE app.items (id INT64)")
+        .await
+        .unwrap();
+    golden.catalog().set_search_path(vec!["app".to_string()]);
+
+    let executor = AsyncQueryExecutor::from_image(&golden.catalog().snapshot());
+    assert_eq!(
+        executor.catalog().get_search_path(),
+        vec!["APP".to_string()]
+    );
+    assert_eq!(
+        column_values(&executor, "SELECT COUNT(*) FROM items").await,
+        vec![vec![Value::int64(0)]]
+    );
+}
+
+#[tokio::test]
+async fn test_clone_shares_columns_until_written() {
+    let executor = golden_fixture().await;
+    executor
+        .execute_sql("CREATE TABLE users_clone CLONE users")
+        .await
+        .unwrap();
+    executor
+        .execute_sql("CREATE SNAPSHOT TABLE users_snap CLONE users")
+        .await
+        .unwrap();
+
+    let catalog = executor.catalog();
+    let source = catalog.get_table_handle("users").unwrap();
+    let clone = catalog.get_table_handle("users_clone").unwrap();
+    let snap = catalog.get_table_handle("users_snap").unwrap();
+    assert!(clone.read().shares_column_buffers(&source.read()));
+    assert!(snap.read().shares_column_buffers(&source.read()));
+
+    executor
+        .execute_sql("INSERT INTO users_clone VALUES (9, 'Zed')")
+        .await
+        .unwrap();
+    let clone = catalog.get_table_handle("users_clone").unwrap();
+    assert!(!clone.read().shares_column_buffers(&source.read()));
+    assert_eq!(
+        column_values(&executor, "SELECT COUNT(*) FROM users").await,
+        vec![vec![Value::int64(2)]]
+    );
+}
@@ -0,0 +1,466 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+
+use yachtsql_common::error::Error;
+use yachtsql_common::types::Value;
+use yachtsql_executor::{AsyncQueryExecutor, PersistenceOptions};
+
+async fn column_values(executor: &AsyncQueryExecutor, sql: &str) -> Vec<Vec<Value>> {
+    let table = executor.execute_sql(sql).await.unwrap();
+    table
+        .to_records()
+        .unwrap()
+        .into_iter()
+        .map(|r| r.into_values())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_reopen_recovers_catalog() {
+    let dir = tempfile::tempdir().unwrap();
+    {
+        let executor = AsyncQueryExecutor::open(dir.path()).unwrap();
+        executor
+            .execute_sql("CREATE TABLE users (id INT64, name STRING)")
+            .await
+            .unwrap();
+        executor
+            .execute_sql("INSERT INTO users VALUES (1, 'Alice'), (2, 'Bob')")
+            .await
+            .unwrap();
+        executor
+            .execute_sql("CREATE VIEW user_names AS SELECT name FROM users")
+            .await
+            .unwrap();
+        executor
+            .execute_sql("CREATE FUNCTION add_one(x INT64) AS (x + 1)")
+            .await
+            .unwrap();
+        executor.execute_sql("CREATE SCHEMA sales").await.unwrap();
+        executor
+            .execute_sql("CREATE TABLE sales.orders (id INT64)")
+            .await
+            .unwrap();
+        executor
+            .execute_sql("INSERT INTO sales.orders VALUES (10)")
+            .await
+            .unwrap();
+    }
+
+    let executor = AsyncQueryExecutor::open(dir.path()).unwrap();
+    assert_eq!(
+        column_values(&executor, "SELECT id, name FROM users ORDER BY id").await,
+        vec![
+            vec![Value::int64(1), Value::string("Alice")],
+            vec![Value::int64(2), Value::string("Bob")],
+        ]
+    );
+    assert_eq!(
+        column_values(&executor, "SELECT name FROM user_names ORDER BY name").await,
+        vec![vec![Value::string("Alice")], vec![Value::string("Bob")]]
+    );
+    assert_eq!(
+        column_values(&executor, "SELECT add_one(41)").await,
+        vec![vec![Value::int64(42)]]
+    );
+    assert_eq!(
+        column_values(&executor, "SELECT id FROM sales.orders").await,
+        vec![vec![Value::int64(10)]]
+    );
+}
+
+#[tokio::test]
+async fn test_drop_and_update_are_persisted() {
+    let dir = tempfile::tempdir().unwrap();
+    {
+        let executor = AsyncQueryExecutor::open(dir.path()).unwrap();
+        executor
+            .execute_sql("CREATE TABLE t (id INT64, v STRING)")
+            .await
+            .unwrap();
+        executor
+            .execute_sql("CREATE TABLE gone (id INT64)")
+            .await
+            .unwrap();
+        executor
+            .execute_sql("INSERT INTO t VALUES (1, 'a'), (2, 'b'), (3, 'c')")
+            .await
+            .unwrap();
+        executor
+            .execute_sql("UPDATE t SET v = 'z' WHERE id = 2")
+            .await
+            .unwrap();
+        executor
+            .execute_sql("DELETE FROM t WHERE id = 3")
+            .await
+            .unwrap();
+        executor.execute_sql("DROP TABLE gone").await.unwrap();
+    }
+
+    let executor = AsyncQueryExecutor::open(dir.path()).unwrap();
+    assert_eq!(
+        column_values(&executor, "SELECT id, v FROM t ORDER BY id").await,
+        vec![
+            vec![Value::int64(1), Value::string("a")],
+            vec![Value::int64(2), Value::string("z")],
+        ]
+    );
+    assert!(executor.execute_sql("SELECT * FROM gone").await.is_err());
+}
+
+#[tokio::test]
+async fn test_wal_logs_only_changed_segments() {
+    let dir = tempfile::tempdir().unwrap();
+    let wal_path = dir.path().join("catalog.wal");
+    let wal_len = || std::fs::metadata(&wal_path).unwrap().len();
+    {
+        let executor = AsyncQueryExecutor::open(dir.path()).unwrap();
+        executor
+            .execute_sql("CREATE TABLE big (id INT64, label STRING)")
+            .await
+            .unwrap();
+        let before = wal_len();
+        executor
+            .execute_sql(
+                "INSERT INTO big SELECT n, CONCAT('row-', CAST(n AS STRING)) FROM UNNEST(GENERATE_ARRAY(1, 24576)) AS n",
+            )
+            .await
+            .unwrap();
+        let full = wal_len() - before;
+
+        let before = wal_len();
+        executor
+            .execute_sql("INSERT INTO big VALUES (0, 'extra')")
+            .await
+            .unwrap();
+        assert!((wal_len() - before) * 10 < full);
+
+        executor
+            .execute_sql("UPDATE big SET label = 'changed' WHERE id = 5")
+            .await
+            .unwrap();
+        executor
+            .execute_sql("DELETE FROM big WHERE id = 24576")
+            .await
+            .unwrap();
+    }
+
+    let executor = AsyncQueryExecutor::open(dir.path()).unwrap();
+    assert_eq!(
+        column_values(&executor, "SELECT COUNT(*), SUM(id) FROM big").await,
+        vec![vec![Value::int64(24576), Value::int64(24576 * 24575 / 2)]]
+    );
+    assert_eq!(
+        column_values(
+            &executor,
+            "SELECT label FROM big WHERE id IN (0, 5) ORDER BY id"
+        )
+        .await,
+        vec![vec![Value::string("extra")], vec![Value::string("changed")]]
+    );
+    executor
+        .execute_sql("INSERT INTO big VALUES (-1, 'after reopen')")
+        .await
+        .unwrap();
+    drop(executor);
+
+    let executor = AsyncQueryExecutor::open(dir.path()).unwrap();
+    assert_eq!(
+        column_values(&executor, "SELECT COUNT(*) FROM big").await,
+        vec![vec![Value::int64(24577)]]
+    );
+}
+
+#[tokio::test]
+async fn test_checkpoint_then_reopen() {
+    let dir = tempfile::tempdir().unwrap();
+    {
+        let executor = AsyncQueryExecutor::open(dir.path()).unwrap();
+        executor
+            .execute_sql("CREATE TABLE t (id INT64)")
+            .await
+            .unwrap();
+        executor
+            .execute_sql("INSERT INTO t VALUES (1), (2)")
+            .await
+            .unwrap();
+        executor.checkpoint().unwrap();
+        executor
+            .execute_sql("INSERT INTO t VALUES (3)")
+            .await
+            .unwrap();
+    }
+
+    let wal_len = std::fs::metadata(dir.path().join("catalog.wal"))
+        .unwrap()
+        .len();
+    assert!(wal_len > 8);
+
+    let executor = AsyncQueryExecutor::open(dir.path()).unwrap();
+    assert_eq!(
+        column_values(&executor, "SELECT id FROM t ORDER BY id").await,
+        vec![
+            vec![Value::int64(1)],
+            vec![Value::int64(2)],
+            vec![Value::int64(3)],
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_automatic_checkpoint_truncates_wal() {
+    let dir = tempfile::tempdir().unwrap();
+    let options = PersistenceOptions {
+        checkpoint_wal_bytes: 1,
+        ..PersistenceOptions::default()
+    };
+    {
+        let executor = AsyncQueryExecutor::open_with_options(dir.path(), options.clone()).unwrap();
+        executor
+            .execute_sql("CREATE TABLE t (id INT64)")
+            .await
+            .unwrap();
+        executor
+            .execute_sql("INSERT INTO t VALUES (1)")
+            .await
+            .unwrap();
+    }
+
+    let wal_len = std::fs::metadata(dir.path().join("catalog.wal"))
+        .unwrap()
+        .len();
+    assert_eq!(wal_len, 8);
+    assert!(dir.path().join("catalog.checkpoint").exists());
+
+    let executor = AsyncQueryExecutor::open_with_options(dir.path(), options).unwrap();
+    assert_eq!(
+        column_values(&executor, "SELECT id FROM t").await,
+        vec![vec![Value::int64(1)]]
+    );
+}
+
+#[tokio::test]
+async fn test_failed_log_write_reports_committed_statement() {
+    let dir = tempfile::tempdir().unwrap();
+    {
+        let executor = AsyncQueryExecutor::open(dir.path()).unwrap();
+        executor
+            .execute_sql("CREATE TABLE t (id INT64)")
+            .await
+            .unwrap();
+    }
+    std::fs::create_dir(dir.path().join("catalog.checkpoint.tmp")).unwrap();
+
+    let executor = AsyncQueryExecutor::open_with_options(
+        dir.path(),
+        PersistenceOptions {
+            checkpoint_wal_bytes: 1,
+            ..PersistenceOptions::default()
+        },
+    )
+    .unwrap();
+    let err = executor
+        .execute_sql("INSERT INTO t VALUES (1)")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Durability(_)), "{}", err);
+    assert_eq!(
+        column_values(&executor, "SELECT id FROM t").await,
+        vec![vec![Value::int64(1)]]
+    );
+}
+
+#[tokio::test]
+async fn test_torn_wal_tail_is_discarded() {
+    let dir = tempfile::tempdir().unwrap();
+    {
+        let executor = AsyncQueryExecutor::open(dir.path()).unwrap();
+        executor
+            .execute_sql("CREATE TABLE t (id INT64)")
+            .await
+            .unwrap();
+        executor
+            .execute_sql("INSERT INTO t VALUES (1)")
+            .await
+            .unwrap();
+    }
+
+    let wal_path = dir.path().join("catalog.wal");
+    let intact_len = std::fs::metadata(&wal_path).unwrap().len();
+    {
+        let mut file = OpenOptions::new().append(true).open(&wal_path).unwrap();
+        file.write_all(&[0xFF, 0x01, 0x00, 0x00, 0x00]).unwrap();
+    }
+
+    {
+        let executor = AsyncQueryExecutor::open(dir.path()).unwrap();
+        assert_eq!(std::fs::metadata(&wal_path).unwrap().len(), intact_len);
+        assert_eq!(
+            column_values(&executor, "SELECT id FROM t").await,
+            vec![vec![Value::int64(1)]]
+        );
+        executor
+            .execute_sql("INSERT INTO t VALUES (2)")
+            .await
+            .unwrap();
+    }
+
+    let executor = AsyncQueryExecutor::open(dir.path()).unwrap();
+    assert_eq!(
+        column_values(&executor, "SELECT id FROM t ORDER BY id").await,
+        vec![vec![Value::int64(1)], vec![Value::int64(2)]]
+    );
+}
+
+#[tokio::test]
+async fn test_rolled_back_transaction_is_not_persisted() {
+    let dir = tempfile::tempdir().unwrap();
+    {
+        let executor = AsyncQueryExecutor::open(dir.path()).unwrap();
+        executor
+            .execute_sql("CREATE TABLE t (id INT64)")
+            .await
+            .unwrap();
+        executor
+            .execute_sql("INSERT INTO t VALUES (1)")
+            .await
+            .unwrap();
+        executor.execute_sql("BEGIN TRANSACTION").await.unwrap();
+        executor
+            .execute_sql("INSERT INTO t VALUES (2)")
+            .await
+            .unwrap();
+        executor.execute_sql("ROLLBACK TRANSACTION").await.unwrap();
+        executor.execute_sql("BEGIN TRANSACTION").await.unwrap();
+        executor
+            .execute_sql("INSERT INTO t VALUES (3)")
+            .await
+            .unwrap();
+        executor.execute_sql("COMMIT TRANSACTION").await.unwrap();
+    }
+
+    let executor = AsyncQueryExecutor::open(dir.path()).unwrap();
+    assert_eq!(
+        column_values(&executor, "SELECT id FROM t ORDER BY id").await,
+        vec![vec![Value::int64(1)], vec![Value::int64(3)]]
+    );
+}
+
+#[tokio::test]
+async fn test_commits_are_persisted_while_a_transaction_is_open() {
+    let dir = tempfile::tempdir().unwrap();
+    {
+        let executor = AsyncQueryExecutor::open(dir.path()).unwrap();
+        let other = executor.new_session();
+        executor
+            .execute_sql("CREATE TABLE t (id INT64)")
+            .await
+            .unwrap();
+        executor.execute_sql("BEGIN TRANSACTION").await.unwrap();
+        other.execute_sql("BEGIN TRANSACTION").await.unwrap();
+        other.execute_sql("INSERT INTO t VALUES (1)").await.unwrap();
+        other.execute_sql("COMMIT TRANSACTION").await.unwrap();
+        executor
+            .execute_sql("INSERT INTO t VALUES (2)")
+            .await
+            .unwrap();
+        other
+            .execute_sql(
+                "BEGIN
+                    BEGIN TRANSACTION;
+                    INSERT INTO t VALUES (3);
+                    COMMIT TRANSACTION;
+                    BEGIN TRANSACTION;
+                    INSERT INTO t VALUES (4);
+                END",
+            )
+            .await
+            .unwrap();
+    }
+
+    let executor = AsyncQueryExecutor::open(dir.path()).unwrap();
+    assert_eq!(
+        column_values(&executor, "SELECT id FROM t ORDER BY id").await,
+        vec![vec![Value::int64(1)], vec![Value::int64(3)]]
+    );
+}
+
+#[tokio::test]
+async fn test_float_special_values_roundtrip() {
+    let dir = tempfile::tempdir().unwrap();
+    {
+        let executor = AsyncQueryExecutor::open(dir.path()).unwrap();
+        executor
+            .execute_sql("CREATE TABLE f (x FLOAT64)")
+            .await
+            .unwrap();
+        executor
+            .execute_sql(
+                "INSERT INTO f VALUES (CAST('NaN' AS FLOAT64)), (CAST('inf' AS FLOAT64)), (1.5), (NULL)",
+            )
+            .await
+            .unwrap();
+    }
+
+    let executor = AsyncQueryExecutor::open(dir.path()).unwrap();
+    assert_eq!(
+        column_values(
+            &executor,
+            "SELECT COUNTIF(IS_NAN(x)), COUNTIF(IS_INF(x)), COUNTIF(x = 1.5), COUNTIF(x IS NULL) FROM f",
+        )
+        .await,
+        vec![vec![
+            Value::int64(1),
+            Value::int64(1),
+            Value::int64(1),
+            Value::int64(1)
+        ]]
+    );
+}
+
+#[tokio::test]
+async fn test_sessions_share_durable_catalog() {
+    let dir = tempfile::tempdir().unwrap();
+    let executor = AsyncQueryExecutor::open(dir.path()).unwrap();
+    let other = executor.new_session();
+    executor
+        .execute_sql("CREATE TABLE t (id INT64)")
+        .await
+        .unwrap();
+    other.execute_sql("INSERT INTO t VALUES (7)").await.unwrap();
+    assert_eq!(
+        column_values(&executor, "SELECT id FROM t").await,
+        vec![vec![Value::int64(7)]]
+    );
+}
+
+#[tokio::test]
+async fn test_snapshot_stays_read_only_after_reopen() {
+    let dir = tempfile::tempdir().unwrap();
+    {
+        let executor = AsyncQueryExecutor::open(dir.path()).unwrap();
+        executor
+            .execute_sql("CREATE TABLE t (id INT64)")
+            .await
+            .unwrap();
+        executor
+            .execute_sql("INSERT INTO t VALUES (1)")
+            .await
+            .unwrap();
+        executor
+            .execute_sql("CREATE SNAPSHOT TABLE t_snap CLONE t")
+            .await
+            .unwrap();
+    }
+
+    let executor = AsyncQueryExecutor::open(dir.path()).unwrap();
+    let err = executor
+        .execute_sql("INSERT INTO t_snap VALUES (2)")
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("read-only"));
+    assert_eq!(
+        column_values(&executor, "SELECT id FROM t_snap").await,
+        vec![vec![Value::int64(1)]]
+    );
+}
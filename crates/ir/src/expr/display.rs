@@ -0,0 +1,399 @@
+use std::fmt::{self, Display, Formatter};
+
+use super::{
+    AggregateFunction, BinaryOp, Expr, JsonPathElement, Literal, ScalarFunction, SortExpr, UnaryOp,
+    WindowFunction,
+};
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Literal(literal) => write!(f, "{}", literal),
+            Expr::Column { table, name, .. } => match table {
+                Some(table) => write!(f, "{}.{}", table, name),
+                None => write!(f, "{}", name),
+            },
+            Expr::BinaryOp { left, op, right } => write!(f, "({} {} {})", left, op, right),
+            Expr::UnaryOp { op, expr } => match op {
+                UnaryOp::Not => write!(f, "NOT {}", expr),
+                UnaryOp::Minus => write!(f, "-{}", expr),
+                UnaryOp::Plus => write!(f, "+{}", expr),
+                UnaryOp::BitwiseNot => write!(f, "~{}", expr),
+            },
+            Expr::ScalarFunction { name, args } => {
+                write!(f, "{}({})", name, List(args))
+            }
+            Expr::Aggregate {
+                func,
+                args,
+                distinct,
+                ..
+            } => write!(
+                f,
+                "{}({}{})",
+                func,
+                if *distinct { "DISTINCT " } else { "" },
+                List(args)
+            ),
+            Expr::UserDefinedAggregate {
+                name,
+                args,
+                distinct,
+                ..
+            } => write!(
+                f,
+                "{}({}{})",
+                name,
+                if *distinct { "DISTINCT " } else { "" },
+                List(args)
+            ),
+            Expr::Window {
+                func,
+                args,
+                partition_by,
+                order_by,
+                ..
+            } => {
+                write!(f, "{}({}) ", func, List(args))?;
+                write_over(f, partition_by, order_by)
+            }
+            Expr::AggregateWindow {
+                func,
+                args,
+                distinct,
+                partition_by,
+                order_by,
+                ..
+            } => {
+                write!(
+                    f,
+                    "{}({}{}) ",
+                    func,
+                    if *distinct { "DISTINCT " } else { "" },
+                    List(args)
+                )?;
+                write_over(f, partition_by, order_by)
+            }
+            Expr::Case {
+                operand,
+                when_clauses,
+                else_result,
+            } => {
+                write!(f, "CASE")?;
+                if let Some(operand) = operand {
+                    write!(f, " {}", operand)?;
+                }
+                for clause in when_clauses {
+                    write!(f, " WHEN {} THEN {}", clause.condition, clause.result)?;
+                }
+                if let Some(else_result) = else_result {
+                    write!(f, " ELSE {}", else_result)?;
+                }
+                write!(f, " END")
+            }
+            Expr::Cast {
+                expr,
+                data_type,
+                safe,
+            } => write!(
+                f,
+                "{}({} AS {})",
+                if *safe { "SAFE_CAST" } else { "CAST" },
+                expr,
+                data_type
+            ),
+            Expr::IsNull { expr, negated } => {
+                write!(f, "{} IS {}NULL", expr, not(*negated))
+            }
+            Expr::IsDistinctFrom {
+                left,
+                right,
+                negated,
+            } => write!(f, "{} IS {}DISTINCT FROM {}", left, not(*negated), right),
+            Expr::InList {
+                expr,
+                list,
+                negated,
+            } => write!(f, "{} {}IN ({})", expr, not(*negated), List(list)),
+            Expr::InSubquery { expr, negated, .. } => {
+                write!(f, "{} {}IN (<subquery>)", expr, not(*negated))
+            }
+            Expr::InUnnest {
+                expr,
+                array_expr,
+                negated,
+            } => write!(f, "{} {}IN UNNEST({})", expr, not(*negated), array_expr),
+            Expr::Exists { negated, .. } => write!(f, "{}EXISTS (<subquery>)", not(*negated)),
+            Expr::Between {
+                expr,
+                low,
+                high,
+                negated,
+            } => write!(f, "{} {}BETWEEN {} AND {}", expr, not(*negated), low, high),
+            Expr::Like {
+                expr,
+                pattern,
+                negated,
+                case_insensitive,
+            } => write!(
+                f,
+                "{} {}{} {}",
+                expr,
+                not(*negated),
+                if *case_insensitive { "ILIKE" } else { "LIKE" },
+                pattern
+            ),
+            Expr::Extract { field, expr } => {
+                write!(f, "EXTRACT({} FROM {})", sql_name(field), expr)
+            }
+            Expr::Substring {
+                expr,
+                start,
+                length,
+            } => {
+                write!(f, "SUBSTR({}", expr)?;
+                if let Some(start) = start {
+                    write!(f, ", {}", start)?;
+                }
+                if let Some(length) = length {
+                    write!(f, ", {}", length)?;
+                }
+                write!(f, ")")
+            }
+            Expr::Trim {
+                expr, trim_what, ..
+            } => match trim_what {
+                Some(what) => write!(f, "TRIM({}, {})", expr, what),
+                None => write!(f, "TRIM({})", expr),
+            },
+            Expr::Position { substr, string } => {
+                write!(f, "STRPOS({}, {})", string, substr)
+            }
+            Expr::Overlay {
+                expr,
+                overlay_what,
+                overlay_from,
+                overlay_for,
+            } => {
+                write!(
+                    f,
+                    "OVERLAY({} PLACING {} FROM {}",
+                    expr, overlay_what, overlay_from
+                )?;
+                if let Some(overlay_for) = overlay_for {
+                    write!(f, " FOR {}", overlay_for)?;
+                }
+                write!(f, ")")
+            }
+            Expr::Array { elements, .. } => write!(f, "[{}]", List(elements)),
+            Expr::ArrayAccess { array, index } => write!(f, "{}[{}]", array, index),
+            Expr::Struct { fields } => {
+                write!(f, "STRUCT(")?;
+                for (i, (name, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", value)?;
+                    if let Some(name) = name {
+                        write!(f, " AS {}", name)?;
+                    }
+                }
+                write!(f, ")")
+            }
+            Expr::StructAccess { expr, field } => write!(f, "{}.{}", expr, field),
+            Expr::TypedString { data_type, value } => write!(f, "{} '{}'", data_type, value),
+            Expr::Interval {
+                value,
+                leading_field,
+            } => {
+                write!(f, "INTERVAL {}", value)?;
+                if let Some(field) = leading_field {
+                    write!(f, " {}", sql_name(field))?;
+                }
+                Ok(())
+            }
+            Expr::Alias { expr, name } => write!(f, "{} AS {}", expr, name),
+            Expr::Wildcard { table } => match table {
+                Some(table) => write!(f, "{}.*", table),
+                None => write!(f, "*"),
+            },
+            Expr::Subquery(_) | Expr::ScalarSubquery(_) => write!(f, "(<subquery>)"),
+            Expr::ArraySubquery(_) => write!(f, "ARRAY(<subquery>)"),
+            Expr::Parameter { name } => write!(f, "@{}", name),
+            Expr::Variable { name } => write!(f, "{}", name),
+            Expr::Placeholder { id } => write!(f, "{}", id),
+            Expr::Lambda { params, body } => write!(f, "({}) -> {}", params.join(", "), body),
+            Expr::AtTimeZone {
+                timestamp,
+                time_zone,
+            } => write!(f, "{} AT TIME ZONE {}", timestamp, time_zone),
+            Expr::JsonAccess { expr, path } => {
+                write!(f, "{}", expr)?;
+                for element in path {
+                    match element {
+                        JsonPathElement::Key(key) => write!(f, ".{}", key)?,
+                        JsonPathElement::Index(index) => write!(f, "[{}]", index)?,
+                    }
+                }
+                Ok(())
+            }
+            Expr::Default => write!(f, "DEFAULT"),
+        }
+    }
+}
+
+impl Display for Literal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Literal::Null => write!(f, "NULL"),
+            Literal::Bool(b) => write!(f, "{}", if *b { "TRUE" } else { "FALSE" }),
+            Literal::Int64(v) => write!(f, "{}", v),
+            Literal::Float64(v) => write!(f, "{}", v.0),
+            Literal::Numeric(v) => write!(f, "NUMERIC '{}'", v),
+            Literal::BigNumeric(v) => write!(f, "BIGNUMERIC '{}'", v),
+            Literal::String(s) => write!(f, "'{}'", s.replace('\'', "\\'")),
+            Literal::Bytes(bytes) => write!(f, "b'<{} bytes>'", bytes.len()),
+            Literal::Date(days) => write!(f, "DATE({})", days),
+            Literal::Time(nanos) => write!(f, "TIME({})", nanos),
+            Literal::Timestamp(micros) => write!(f, "TIMESTAMP_MICROS({})", micros),
+            Literal::Datetime(micros) => write!(f, "DATETIME({})", micros),
+            Literal::Interval {
+                months,
+                days,
+                nanos,
+            } => write!(f, "INTERVAL '{}-{} {}ns'", months, days, nanos),
+            Literal::Array(elements) => {
+                write!(f, "[")?;
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", element)?;
+                }
+                write!(f, "]")
+            }
+            Literal::Struct(fields) => {
+                write!(f, "STRUCT(")?;
+                for (i, (name, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{} AS {}", value, name)?;
+                }
+                write!(f, ")")
+            }
+            Literal::Json(value) => write!(f, "JSON '{}'", value),
+        }
+    }
+}
+
+impl Display for SortExpr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.expr)?;
+        if !self.asc {
+            write!(f, " DESC")?;
+        }
+        if self.nulls_first == self.asc {
+            write!(
+                f,
+                " NULLS {}",
+                if self.nulls_first { "FIRST" } else { "LAST" }
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for BinaryOp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Mod => "%",
+            BinaryOp::Eq => "=",
+            BinaryOp::NotEq => "!=",
+            BinaryOp::Lt => "<",
+            BinaryOp::LtEq => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::GtEq => ">=",
+            BinaryOp::And => "AND",
+            BinaryOp::Or => "OR",
+            BinaryOp::Concat => "||",
+            BinaryOp::BitwiseAnd => "&",
+            BinaryOp::BitwiseOr => "|",
+            BinaryOp::BitwiseXor => "^",
+            BinaryOp::ShiftLeft => "<<",
+            BinaryOp::ShiftRight => ">>",
+        })
+    }
+}
+
+impl Display for ScalarFunction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ScalarFunction::Custom(name) => f.write_str(name),
+            other => f.write_str(&sql_name(other)),
+        }
+    }
+}
+
+impl Display for AggregateFunction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&sql_name(self))
+    }
+}
+
+impl Display for WindowFunction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&sql_name(self))
+    }
+}
+
+/// Upper snake case of a unit variant's name, e.g. `DateTrunc` -> `DATE_TRUNC`.
+fn sql_name(value: &impl fmt::Debug) -> String {
+    let name = format!("{:?}", value);
+    let mut out = String::with_capacity(name.len() + 4);
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if c.is_ascii_uppercase() && prev_lower {
+            out.push('_');
+        }
+        prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        out.push(c.to_ascii_uppercase());
+    }
+    out
+}
+
+fn not(negated: bool) -> &'static str {
+    if negated { "NOT " } else { "" }
+}
+
+fn write_over(f: &mut Formatter<'_>, partition_by: &[Expr], order_by: &[SortExpr]) -> fmt::Result {
+    write!(f, "OVER (")?;
+    if !partition_by.is_empty() {
+        write!(f, "PARTITION BY {}", List(partition_by))?;
+    }
+    if !order_by.is_empty() {
+        if !partition_by.is_empty() {
+            write!(f, " ")?;
+        }
+        write!(f, "ORDER BY {}", List(order_by))?;
+    }
+    write!(f, ")")
+}
+
+struct List<'a, T>(&'a [T]);
+
+impl<T: Display> Display for List<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (i, item) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", item)?;
+        }
+        Ok(())
+    }
+}
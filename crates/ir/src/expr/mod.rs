@@ -1,8 +1,10 @@
 mod datetime;
+mod display;
 mod functions;
 mod literal;
 mod operators;
 mod types;
+mod visit;
 mod window;
 
 pub use datetime::*;
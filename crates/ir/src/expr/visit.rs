@@ -0,0 +1,253 @@
+use super::Expr;
+
+impl Expr {
+    /// Direct child expressions. Plans nested inside subquery expressions are
+    /// not expressions and are therefore not returned.
+    pub fn children(&self) -> Vec<&Expr> {
+        match self {
+            Expr::Literal(_)
+            | Expr::Column { .. }
+            | Expr::TypedString { .. }
+            | Expr::Wildcard { .. }
+            | Expr::Subquery(_)
+            | Expr::ScalarSubquery(_)
+            | Expr::ArraySubquery(_)
+            | Expr::Exists { .. }
+            | Expr::Parameter { .. }
+            | Expr::Variable { .. }
+            | Expr::Placeholder { .. }
+            | Expr::Default => Vec::new(),
+            Expr::BinaryOp { left, right, .. } | Expr::IsDistinctFrom { left, right, .. } => {
+                vec![left.as_ref(), right.as_ref()]
+            }
+            Expr::UnaryOp { expr, .. }
+            | Expr::Cast { expr, .. }
+            | Expr::IsNull { expr, .. }
+            | Expr::InSubquery { expr, .. }
+            | Expr::Extract { expr, .. }
+            | Expr::StructAccess { expr, .. }
+            | Expr::Interval { value: expr, .. }
+            | Expr::Alias { expr, .. }
+            | Expr::Lambda { body: expr, .. }
+            | Expr::JsonAccess { expr, .. } => vec![expr.as_ref()],
+            Expr::ScalarFunction { args, .. } => args.iter().collect(),
+            Expr::Aggregate {
+                args,
+                filter,
+                order_by,
+                ..
+            } => args
+                .iter()
+                .chain(filter.as_deref())
+                .chain(order_by.iter().map(|s| &s.expr))
+                .collect(),
+            Expr::UserDefinedAggregate { args, filter, .. } => {
+                args.iter().chain(filter.as_deref()).collect()
+            }
+            Expr::Window {
+                args,
+                partition_by,
+                order_by,
+                frame,
+                ..
+            }
+            | Expr::AggregateWindow {
+                args,
+                partition_by,
+                order_by,
+                frame,
+                ..
+            } => args
+                .iter()
+                .chain(partition_by.iter())
+                .chain(order_by.iter().map(|s| &s.expr))
+                .chain(frame.iter().flat_map(|f| f.offsets()))
+                .collect(),
+            Expr::Case {
+                operand,
+                when_clauses,
+                else_result,
+            } => operand
+                .as_deref()
+                .into_iter()
+                .chain(when_clauses.iter().flat_map(|w| [&w.condition, &w.result]))
+                .chain(else_result.as_deref())
+                .collect(),
+            Expr::InList { expr, list, .. } => {
+                std::iter::once(expr.as_ref()).chain(list.iter()).collect()
+            }
+            Expr::InUnnest {
+                expr, array_expr, ..
+            } => vec![expr.as_ref(), array_expr.as_ref()],
+            Expr::Between {
+                expr, low, high, ..
+            } => vec![expr.as_ref(), low.as_ref(), high.as_ref()],
+            Expr::Like { expr, pattern, .. } => vec![expr.as_ref(), pattern.as_ref()],
+            Expr::Substring {
+                expr,
+                start,
+                length,
+            } => std::iter::once(expr.as_ref())
+                .chain(start.as_deref())
+                .chain(length.as_deref())
+                .collect(),
+            Expr::Trim {
+                expr, trim_what, ..
+            } => std::iter::once(expr.as_ref())
+                .chain(trim_what.as_deref())
+                .collect(),
+            Expr::Position { substr, string } => vec![substr.as_ref(), string.as_ref()],
+            Expr::Overlay {
+                expr,
+                overlay_what,
+                overlay_from,
+                overlay_for,
+            } => [expr.as_ref(), overlay_what.as_ref(), overlay_from.as_ref()]
+                .into_iter()
+                .chain(overlay_for.as_deref())
+                .collect(),
+            Expr::Array { elements, .. } => elements.iter().collect(),
+            Expr::ArrayAccess { array, index } => vec![array.as_ref(), index.as_ref()],
+            Expr::Struct { fields } => fields.iter().map(|(_, e)| e).collect(),
+            Expr::AtTimeZone {
+                timestamp,
+                time_zone,
+            } => vec![timestamp.as_ref(), time_zone.as_ref()],
+        }
+    }
+
+    /// Mutable counterpart of [`Expr::children`], in the same order.
+    pub fn children_mut(&mut self) -> Vec<&mut Expr> {
+        match self {
+            Expr::Literal(_)
+            | Expr::Column { .. }
+            | Expr::TypedString { .. }
+            | Expr::Wildcard { .. }
+            | Expr::Subquery(_)
+            | Expr::ScalarSubquery(_)
+            | Expr::ArraySubquery(_)
+            | Expr::Exists { .. }
+            | Expr::Parameter { .. }
+            | Expr::Variable { .. }
+            | Expr::Placeholder { .. }
+            | Expr::Default => Vec::new(),
+            Expr::BinaryOp { left, right, .. } | Expr::IsDistinctFrom { left, right, .. } => {
+                vec![left.as_mut(), right.as_mut()]
+            }
+            Expr::UnaryOp { expr, .. }
+            | Expr::Cast { expr, .. }
+            | Expr::IsNull { expr, .. }
+            | Expr::InSubquery { expr, .. }
+            | Expr::Extract { expr, .. }
+            | Expr::StructAccess { expr, .. }
+            | Expr::Interval { value: expr, .. }
+            | Expr::Alias { expr, .. }
+            | Expr::Lambda { body: expr, .. }
+            | Expr::JsonAccess { expr, .. } => vec![expr.as_mut()],
+            Expr::ScalarFunction { args, .. } => args.iter_mut().collect(),
+            Expr::Aggregate {
+                args,
+                filter,
+                order_by,
+                ..
+            } => args
+                .iter_mut()
+                .chain(filter.as_deref_mut())
+                .chain(order_by.iter_mut().map(|s| &mut s.expr))
+                .collect(),
+            Expr::UserDefinedAggregate { args, filter, .. } => {
+                args.iter_mut().chain(filter.as_deref_mut()).collect()
+            }
+            Expr::Window {
+                args,
+                partition_by,
+                order_by,
+                frame,
+                ..
+            }
+            | Expr::AggregateWindow {
+                args,
+                partition_by,
+                order_by,
+                frame,
+                ..
+            } => args
+                .iter_mut()
+                .chain(partition_by.iter_mut())
+                .chain(order_by.iter_mut().map(|s| &mut s.expr))
+                .chain(frame.iter_mut().flat_map(|f| f.offsets_mut()))
+                .collect(),
+            Expr::Case {
+                operand,
+                when_clauses,
+                else_result,
+            } => operand
+                .as_deref_mut()
+                .into_iter()
+                .chain(
+                    when_clauses
+                        .iter_mut()
+                        .flat_map(|w| [&mut w.condition, &mut w.result]),
+                )
+                .chain(else_result.as_deref_mut())
+                .collect(),
+            Expr::InList { expr, list, .. } => std::iter::once(expr.as_mut())
+                .chain(list.iter_mut())
+                .collect(),
+            Expr::InUnnest {
+                expr, array_expr, ..
+            } => vec![expr.as_mut(), array_expr.as_mut()],
+            Expr::Between {
+                expr, low, high, ..
+            } => vec![expr.as_mut(), low.as_mut(), high.as_mut()],
+            Expr::Like { expr, pattern, .. } => vec![expr.as_mut(), pattern.as_mut()],
+            Expr::Substring {
+                expr,
+                start,
+                length,
+            } => std::iter::once(expr.as_mut())
+                .chain(start.as_deref_mut())
+                .chain(length.as_deref_mut())
+                .collect(),
+            Expr::Trim {
+                expr, trim_what, ..
+            } => std::iter::once(expr.as_mut())
+                .chain(trim_what.as_deref_mut())
+                .collect(),
+            Expr::Position { substr, string } => vec![substr.as_mut(), string.as_mut()],
+            Expr::Overlay {
+                expr,
+                overlay_what,
+                overlay_from,
+                overlay_for,
+            } => [expr.as_mut(), overlay_what.as_mut(), overlay_from.as_mut()]
+                .into_iter()
+                .chain(overlay_for.as_deref_mut())
+                .collect(),
+            Expr::Array { elements, .. } => elements.iter_mut().collect(),
+            Expr::ArrayAccess { array, index } => vec![array.as_mut(), index.as_mut()],
+            Expr::Struct { fields } => fields.iter_mut().map(|(_, e)| e).collect(),
+            Expr::AtTimeZone {
+                timestamp,
+                time_zone,
+            } => vec![timestamp.as_mut(), time_zone.as_mut()],
+        }
+    }
+
+    pub fn contains_subquery(&self) -> bool {
+        match self {
+            Expr::InSubquery { .. }
+            | Expr::Exists { .. }
+            | Expr::Subquery(_)
+            | Expr::ScalarSubquery(_)
+            | Expr::ArraySubquery(_) => true,
+            other => other.children().into_iter().any(Expr::contains_subquery),
+        }
+    }
+    pub fn contains_column_ref(&self) -> bool {
+        match self {
+            Expr::Column { index, .. } => index.is_some(),
+            other => other.children().into_iter().any(Expr::contains_column_ref),
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use super::Expr;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum WindowFunction {
     RowNumber,
@@ -20,6 +22,7 @@ pub struct WindowFrame {
     pub unit: WindowFrameUnit,
     pub start: WindowFrameBound,
     pub end: Option<WindowFrameBound>,
+    pub exclusion: Option<WindowFrameExclusion>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -29,11 +32,23 @@ pub enum WindowFrameUnit {
     Groups,
 }
 
+/// A frame bound; `None` offsets are `UNBOUNDED`. Offsets are constant
+/// expressions evaluated once per partition: a row count for `ROWS`, a
+/// peer group count for `GROUPS`, and a distance from the ordering key for
+/// `RANGE`, such as `INTERVAL 7 DAY` over a `DATE` key.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum WindowFrameBound {
     CurrentRow,
-    Preceding(Option<u64>),
-    Following(Option<u64>),
+    Preceding(Option<Box<Expr>>),
+    Following(Option<Box<Expr>>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WindowFrameExclusion {
+    CurrentRow,
+    Group,
+    Ties,
+    NoOthers,
 }
 
 impl Default for WindowFrame {
@@ -42,6 +57,35 @@ impl Default for WindowFrame {
             unit: WindowFrameUnit::Range,
             start: WindowFrameBound::Preceding(None),
             end: Some(WindowFrameBound::CurrentRow),
+            exclusion: None,
+        }
+    }
+}
+
+impl WindowFrame {
+    pub fn offsets(&self) -> impl Iterator<Item = &Expr> {
+        std::iter::once(&self.start)
+            .chain(self.end.as_ref())
+            .filter_map(WindowFrameBound::offset)
+    }
+
+    pub fn offsets_mut(&mut self) -> impl Iterator<Item = &mut Expr> {
+        std::iter::once(&mut self.start)
+            .chain(self.end.as_mut())
+            .filter_map(|bound| match bound {
+                WindowFrameBound::Preceding(Some(offset))
+                | WindowFrameBound::Following(Some(offset)) => Some(offset.as_mut()),
+                _ => None,
+            })
+    }
+}
+
+impl WindowFrameBound {
+    pub fn offset(&self) -> Option<&Expr> {
+        match self {
+            WindowFrameBound::Preceding(Some(offset))
+            | WindowFrameBound::Following(Some(offset)) => Some(offset),
+            _ => None,
         }
     }
 }
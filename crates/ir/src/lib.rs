@@ -5,12 +5,13 @@ pub mod schema;
 pub use expr::{
     AggregateFunction, BinaryOp, DateTimeField, Expr, JsonPathElement, Literal, ScalarFunction,
     SortExpr, TrimWhere, UnaryOp, WeekStartDay, WhenClause, WindowFrame, WindowFrameBound,
-    WindowFrameUnit, WindowFunction,
+    WindowFrameExclusion, WindowFrameUnit, WindowFunction,
 };
 pub use plan::{
-    AlterColumnAction, AlterTableOp, ConstraintType, CteDefinition, DclResourceType, ExportFormat,
-    ExportOptions, FunctionArg, FunctionBody, GapFillColumn, GapFillStrategy, JoinType, LoadFormat,
-    LoadOptions, LogicalPlan, MergeClause, NamedWindowDefinition, ProcedureArg, ProcedureArgMode,
-    RaiseLevel, SampleType, SetOperationType, TableConstraint, UnnestColumn, WindowSpec,
+    AlterColumnAction, AlterTableOp, ConstraintType, CteDefinition, DclResourceType, ExplainFormat,
+    ExportFormat, ExportOptions, FunctionArg, FunctionBody, GapFillColumn, GapFillStrategy,
+    IndexKind, JoinType, LoadFormat, LoadOptions, LogicalPlan, MergeClause, NamedWindowDefinition,
+    ProcedureArg, ProcedureArgMode, RaiseLevel, SampleType, SetOperationType, TableConstraint,
+    UnnestColumn, WindowSpec,
 };
 pub use schema::{Assignment, ColumnDef, EMPTY_SCHEMA, PlanField, PlanSchema};
@@ -114,6 +114,12 @@ pub enum LoadFormat {
     Avro,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum IndexKind {
+    Search,
+    Vector,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TableConstraint {
     pub name: Option<String>,
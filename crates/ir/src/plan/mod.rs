@@ -35,6 +35,12 @@ pub enum LogicalPlan {
         projection: Option<Vec<usize>>,
     },
 
+    TimeTravelScan {
+        table_name: String,
+        schema: PlanSchema,
+        as_of: Expr,
+    },
+
     Sample {
         input: Box<LogicalPlan>,
         sample_type: SampleType,
@@ -152,6 +158,8 @@ pub enum LogicalPlan {
     CreateTable {
         table_name: String,
         columns: Vec<ColumnDef>,
+        constraints: Vec<TableConstraint>,
+        options: Vec<(String, String)>,
         if_not_exists: bool,
         or_replace: bool,
         query: Option<Box<LogicalPlan>>,
@@ -172,6 +180,17 @@ pub enum LogicalPlan {
         table_name: String,
     },
 
+    Analyze {
+        table_name: String,
+    },
+
+    Explain {
+        input: Box<LogicalPlan>,
+        analyze: bool,
+        format: ExplainFormat,
+        schema: PlanSchema,
+    },
+
     CreateView {
         name: String,
         query: Box<LogicalPlan>,
@@ -179,6 +198,7 @@ pub enum LogicalPlan {
         column_aliases: Vec<String>,
         or_replace: bool,
         if_not_exists: bool,
+        materialized: bool,
     },
 
     DropView {
@@ -190,6 +210,7 @@ pub enum LogicalPlan {
         name: String,
         if_not_exists: bool,
         or_replace: bool,
+        options: Vec<(String, String)>,
     },
 
     DropSchema {
@@ -217,6 +238,7 @@ pub enum LogicalPlan {
         if_not_exists: bool,
         is_temp: bool,
         is_aggregate: bool,
+        options: Vec<(String, String)>,
     },
 
     DropFunction {
@@ -224,6 +246,23 @@ pub enum LogicalPlan {
         if_exists: bool,
     },
 
+    CreateIndex {
+        name: String,
+        table_name: String,
+        kind: IndexKind,
+        columns: Vec<String>,
+        options: Vec<(String, String)>,
+        or_replace: bool,
+        if_not_exists: bool,
+    },
+
+    DropIndex {
+        name: String,
+        table_name: String,
+        kind: IndexKind,
+        if_exists: bool,
+    },
+
     CreateProcedure {
         name: String,
         args: Vec<ProcedureArg>,
@@ -330,6 +369,7 @@ pub enum LogicalPlan {
         snapshot_name: String,
         source_name: String,
         if_not_exists: bool,
+        as_of: Option<Expr>,
     },
 
     DropSnapshot {
@@ -383,6 +423,7 @@ impl LogicalPlan {
     pub fn schema(&self) -> &PlanSchema {
         match self {
             LogicalPlan::Scan { schema, .. } => schema,
+            LogicalPlan::TimeTravelScan { schema, .. } => schema,
             LogicalPlan::Sample { input, .. } => input.schema(),
             LogicalPlan::Filter { input, .. } => input.schema(),
             LogicalPlan::Project { schema, .. } => schema,
@@ -406,6 +447,8 @@ impl LogicalPlan {
             LogicalPlan::DropTable { .. } => &EMPTY_SCHEMA,
             LogicalPlan::AlterTable { .. } => &EMPTY_SCHEMA,
             LogicalPlan::Truncate { .. } => &EMPTY_SCHEMA,
+            LogicalPlan::Analyze { .. } => &EMPTY_SCHEMA,
+            LogicalPlan::Explain { schema, .. } => schema,
             LogicalPlan::CreateView { .. } => &EMPTY_SCHEMA,
             LogicalPlan::DropView { .. } => &EMPTY_SCHEMA,
             LogicalPlan::CreateSchema { .. } => &EMPTY_SCHEMA,
@@ -414,6 +457,8 @@ impl LogicalPlan {
             LogicalPlan::AlterSchema { .. } => &EMPTY_SCHEMA,
             LogicalPlan::CreateFunction { .. } => &EMPTY_SCHEMA,
             LogicalPlan::DropFunction { .. } => &EMPTY_SCHEMA,
+            LogicalPlan::CreateIndex { .. } => &EMPTY_SCHEMA,
+            LogicalPlan::DropIndex { .. } => &EMPTY_SCHEMA,
             LogicalPlan::CreateProcedure { .. } => &EMPTY_SCHEMA,
             LogicalPlan::DropProcedure { .. } => &EMPTY_SCHEMA,
             LogicalPlan::Call { .. } => &EMPTY_SCHEMA,
@@ -446,6 +491,66 @@ impl LogicalPlan {
         }
     }
 
+    /// Query inputs of this node, including the queries feeding DML and
+    /// `CREATE TABLE ... AS`. CTE definitions, script bodies and subqueries
+    /// inside expressions are not included.
+    pub fn children(&self) -> Vec<&LogicalPlan> {
+        match self {
+            LogicalPlan::Sample { input, .. }
+            | LogicalPlan::Filter { input, .. }
+            | LogicalPlan::Project { input, .. }
+            | LogicalPlan::Aggregate { input, .. }
+            | LogicalPlan::Sort { input, .. }
+            | LogicalPlan::Limit { input, .. }
+            | LogicalPlan::Distinct { input }
+            | LogicalPlan::Window { input, .. }
+            | LogicalPlan::Unnest { input, .. }
+            | LogicalPlan::Qualify { input, .. }
+            | LogicalPlan::GapFill { input, .. }
+            | LogicalPlan::Explain { input, .. } => vec![input.as_ref()],
+            LogicalPlan::Join { left, right, .. }
+            | LogicalPlan::SetOperation { left, right, .. } => vec![left.as_ref(), right.as_ref()],
+            LogicalPlan::WithCte { body, .. } => vec![body.as_ref()],
+            LogicalPlan::Insert { source, .. } | LogicalPlan::Merge { source, .. } => {
+                vec![source.as_ref()]
+            }
+            LogicalPlan::Update { from, .. } => from.iter().map(|f| f.as_ref()).collect(),
+            LogicalPlan::CreateTable { query, .. } => query.iter().map(|q| q.as_ref()).collect(),
+            LogicalPlan::ExportData { query, .. } => vec![query.as_ref()],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Mutable counterpart of [`Self::children`].
+    pub fn children_mut(&mut self) -> Vec<&mut LogicalPlan> {
+        match self {
+            LogicalPlan::Sample { input, .. }
+            | LogicalPlan::Filter { input, .. }
+            | LogicalPlan::Project { input, .. }
+            | LogicalPlan::Aggregate { input, .. }
+            | LogicalPlan::Sort { input, .. }
+            | LogicalPlan::Limit { input, .. }
+            | LogicalPlan::Distinct { input }
+            | LogicalPlan::Window { input, .. }
+            | LogicalPlan::Unnest { input, .. }
+            | LogicalPlan::Qualify { input, .. }
+            | LogicalPlan::GapFill { input, .. }
+            | LogicalPlan::Explain { input, .. } => vec![input.as_mut()],
+            LogicalPlan::Join { left, right, .. }
+            | LogicalPlan::SetOperation { left, right, .. } => vec![left.as_mut(), right.as_mut()],
+            LogicalPlan::WithCte { body, .. } => vec![body.as_mut()],
+            LogicalPlan::Insert { source, .. } | LogicalPlan::Merge { source, .. } => {
+                vec![source.as_mut()]
+            }
+            LogicalPlan::Update { from, .. } => from.iter_mut().map(|f| f.as_mut()).collect(),
+            LogicalPlan::CreateTable { query, .. } => {
+                query.iter_mut().map(|q| q.as_mut()).collect()
+            }
+            LogicalPlan::ExportData { query, .. } => vec![query.as_mut()],
+            _ => Vec::new(),
+        }
+    }
+
     pub fn scan(table_name: impl Into<String>, schema: PlanSchema) -> Self {
         LogicalPlan::Scan {
             table_name: table_name.into(),
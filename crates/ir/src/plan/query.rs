@@ -8,3 +8,9 @@ pub enum JoinType {
     Full,
     Cross,
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ExplainFormat {
+    Text,
+    Json,
+}
@@ -0,0 +1,413 @@
+use yachtsql_common::types::Value;
+use yachtsql_ir::{BinaryOp, Expr, JoinType, Literal, UnaryOp};
+use yachtsql_storage::ColumnStatistics;
+
+use crate::optimized_logical_plan::{OptimizedLogicalPlan, SampleType};
+
+/// Rows assumed for a table the statistics provider does not know.
+const DEFAULT_TABLE_ROWS: f64 = 1000.0;
+pub(crate) const DEFAULT_SELECTIVITY: f64 = 0.5;
+const RANGE_SELECTIVITY: f64 = 1.0 / 3.0;
+const EQUALITY_SELECTIVITY: f64 = 0.1;
+const NULL_SELECTIVITY: f64 = 0.1;
+
+/// Source of the table and column statistics the cost model works from.
+pub trait StatisticsProvider {
+    fn row_count(&self, table_name: &str) -> Option<u64>;
+
+    fn column_statistics(&self, table_name: &str, column_name: &str) -> Option<ColumnStatistics>;
+}
+
+/// Provider for planning without statistics; every estimate falls back to
+/// the model's defaults.
+pub struct NoStatistics;
+
+impl StatisticsProvider for NoStatistics {
+    fn row_count(&self, _table_name: &str) -> Option<u64> {
+        None
+    }
+
+    fn column_statistics(&self, _table_name: &str, _column_name: &str) -> Option<ColumnStatistics> {
+        None
+    }
+}
+
+pub struct CostModel<'a> {
+    stats: &'a dyn StatisticsProvider,
+}
+
+impl<'a> CostModel<'a> {
+    pub fn new(stats: &'a dyn StatisticsProvider) -> Self {
+        Self { stats }
+    }
+
+    pub fn estimate_rows(&self, plan: &OptimizedLogicalPlan) -> f64 {
+        match plan {
+            OptimizedLogicalPlan::TableScan { table_name, .. }
+            | OptimizedLogicalPlan::TimeTravelScan { table_name, .. } => self
+                .stats
+                .row_count(table_name)
+                .map_or(DEFAULT_TABLE_ROWS, |rows| rows as f64),
+            OptimizedLogicalPlan::Values { values, .. } => values.len() as f64,
+            OptimizedLogicalPlan::Empty { .. } => 0.0,
+            OptimizedLogicalPlan::Filter { input, predicate }
+            | OptimizedLogicalPlan::Qualify { input, predicate } => {
+                self.estimate_rows(input) * self.selectivity(input, predicate)
+            }
+            OptimizedLogicalPlan::Project { input, .. }
+            | OptimizedLogicalPlan::Sort { input, .. }
+            | OptimizedLogicalPlan::Window { input, .. } => self.estimate_rows(input),
+            OptimizedLogicalPlan::Sample {
+                input,
+                sample_type,
+                sample_value,
+            } => {
+                let rows = self.estimate_rows(input);
+                match sample_type {
+                    SampleType::Rows => rows.min(*sample_value as f64),
+                    SampleType::Percent => rows * (*sample_value as f64 / 100.0),
+                }
+            }
+            OptimizedLogicalPlan::HashAggregate {
+                input, group_by, ..
+            } => {
+                if group_by.is_empty() {
+                    return 1.0;
+                }
+                let rows = self.estimate_rows(input);
+                let groups: Option<f64> = group_by
+                    .iter()
+                    .map(|expr| self.expr_distinct_count(input, expr))
+                    .product();
+                groups.unwrap_or(rows / 10.0).min(rows).max(1.0)
+            }
+            OptimizedLogicalPlan::Limit { input, limit, .. } => {
+                let rows = self.estimate_rows(input);
+                limit.map_or(rows, |limit| rows.min(limit as f64))
+            }
+            OptimizedLogicalPlan::TopN { input, limit, .. } => {
+                self.estimate_rows(input).min(*limit as f64)
+            }
+            OptimizedLogicalPlan::Distinct { input } => (self.estimate_rows(input) / 2.0).max(1.0),
+            OptimizedLogicalPlan::HashJoin {
+                left,
+                right,
+                join_type,
+                left_keys,
+                right_keys,
+                residual,
+                ..
+            }
+            | OptimizedLogicalPlan::MergeJoin {
+                left,
+                right,
+                join_type,
+                left_keys,
+                right_keys,
+                residual,
+                comparison: BinaryOp::Eq,
+                ..
+            } => {
+                let selectivity: f64 = left_keys
+                    .iter()
+                    .zip(right_keys)
+                    .map(|(l, r)| self.equi_join_selectivity(left, l, right, r))
+                    .product();
+                let residual = residual.as_ref().map_or(1.0, |_| DEFAULT_SELECTIVITY);
+                self.join_rows(left, right, *join_type, selectivity * residual)
+            }
+            OptimizedLogicalPlan::MergeJoin {
+                left,
+                right,
+                join_type,
+                residual,
+                ..
+            } => {
+                let residual = residual.as_ref().map_or(1.0, |_| DEFAULT_SELECTIVITY);
+                self.join_rows(left, right, *join_type, RANGE_SELECTIVITY * residual)
+            }
+            OptimizedLogicalPlan::NestedLoopJoin {
+                left,
+                right,
+                join_type,
+                condition,
+                ..
+            } => {
+                let selectivity = condition.as_ref().map_or(1.0, |_| DEFAULT_SELECTIVITY);
+                self.join_rows(left, right, *join_type, selectivity)
+            }
+            OptimizedLogicalPlan::CrossJoin { left, right, .. } => {
+                self.estimate_rows(left) * self.estimate_rows(right)
+            }
+            OptimizedLogicalPlan::Union { inputs, .. } => {
+                inputs.iter().map(|input| self.estimate_rows(input)).sum()
+            }
+            OptimizedLogicalPlan::Intersect { left, right, .. } => {
+                self.estimate_rows(left).min(self.estimate_rows(right))
+            }
+            OptimizedLogicalPlan::Except { left, .. } => self.estimate_rows(left),
+            OptimizedLogicalPlan::Unnest { input, .. } => self.estimate_rows(input) * 10.0,
+            OptimizedLogicalPlan::WithCte { body, .. } => self.estimate_rows(body),
+            OptimizedLogicalPlan::GapFill { input, .. } => self.estimate_rows(input) * 2.0,
+            _ => 1.0,
+        }
+    }
+
+    fn join_rows(
+        &self,
+        left: &OptimizedLogicalPlan,
+        right: &OptimizedLogicalPlan,
+        join_type: JoinType,
+        selectivity: f64,
+    ) -> f64 {
+        let left_rows = self.estimate_rows(left);
+        let right_rows = self.estimate_rows(right);
+        let matched = left_rows * right_rows * selectivity;
+        match join_type {
+            JoinType::Inner | JoinType::Cross => matched,
+            JoinType::Left => matched.max(left_rows),
+            JoinType::Right => matched.max(right_rows),
+            JoinType::Full => matched.max(left_rows + right_rows),
+        }
+    }
+
+    /// Fraction of rows in a join of `left` and `right` for which
+    /// `left_key = right_key` holds, assuming the smaller key domain is
+    /// contained in the larger one.
+    pub fn equi_join_selectivity(
+        &self,
+        left: &OptimizedLogicalPlan,
+        left_key: &Expr,
+        right: &OptimizedLogicalPlan,
+        right_key: &Expr,
+    ) -> f64 {
+        let left_distinct = self
+            .expr_distinct_count(left, left_key)
+            .unwrap_or_else(|| self.estimate_rows(left));
+        let right_distinct = self
+            .expr_distinct_count(right, right_key)
+            .unwrap_or_else(|| self.estimate_rows(right));
+        1.0 / left_distinct.max(right_distinct).max(1.0)
+    }
+
+    /// Statistics of the base-table column that output column `index` of
+    /// `plan` passes through unchanged, if any.
+    pub fn column_statistics(
+        &self,
+        plan: &OptimizedLogicalPlan,
+        index: usize,
+    ) -> Option<ColumnStatistics> {
+        match plan {
+            OptimizedLogicalPlan::TableScan {
+                table_name, schema, ..
+            } => {
+                let field = schema.fields.get(index)?;
+                self.stats.column_statistics(table_name, &field.name)
+            }
+            OptimizedLogicalPlan::Filter { input, .. }
+            | OptimizedLogicalPlan::Qualify { input, .. }
+            | OptimizedLogicalPlan::Sort { input, .. }
+            | OptimizedLogicalPlan::Limit { input, .. }
+            | OptimizedLogicalPlan::TopN { input, .. }
+            | OptimizedLogicalPlan::Distinct { input }
+            | OptimizedLogicalPlan::Sample { input, .. }
+            | OptimizedLogicalPlan::Window { input, .. } => self.column_statistics(input, index),
+            OptimizedLogicalPlan::Project {
+                input, expressions, ..
+            } => match expressions.get(index)? {
+                Expr::Column {
+                    index: Some(source),
+                    ..
+                } => self.column_statistics(input, *source),
+                _ => None,
+            },
+            OptimizedLogicalPlan::HashAggregate {
+                input, group_by, ..
+            } => match group_by.get(index)? {
+                Expr::Column {
+                    index: Some(source),
+                    ..
+                } => self.column_statistics(input, *source),
+                _ => None,
+            },
+            OptimizedLogicalPlan::HashJoin { left, right, .. }
+            | OptimizedLogicalPlan::MergeJoin { left, right, .. }
+            | OptimizedLogicalPlan::NestedLoopJoin { left, right, .. }
+            | OptimizedLogicalPlan::CrossJoin { left, right, .. } => {
+                let left_width = left.schema().fields.len();
+                if index < left_width {
+                    self.column_statistics(left, index)
+                } else {
+                    self.column_statistics(right, index - left_width)
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Estimated distinct values of `expr` over the rows of `plan`, when
+    /// `expr` is a column with known statistics.
+    pub fn expr_distinct_count(&self, plan: &OptimizedLogicalPlan, expr: &Expr) -> Option<f64> {
+        let Expr::Column {
+            index: Some(index), ..
+        } = expr
+        else {
+            return None;
+        };
+        let stats = self.column_statistics(plan, *index)?;
+        let rows = self.estimate_rows(plan);
+        Some((stats.distinct_count as f64).min(rows).max(1.0))
+    }
+
+    /// Fraction of the rows of `input` for which `predicate` holds.
+    pub fn selectivity(&self, input: &OptimizedLogicalPlan, predicate: &Expr) -> f64 {
+        let selectivity = match predicate {
+            Expr::Literal(Literal::Bool(true)) => 1.0,
+            Expr::Literal(Literal::Bool(false) | Literal::Null) => 0.0,
+            Expr::BinaryOp {
+                left,
+                op: BinaryOp::And,
+                right,
+            } => self.selectivity(input, left) * self.selectivity(input, right),
+            Expr::BinaryOp {
+                left,
+                op: BinaryOp::Or,
+                right,
+            } => {
+                let l = self.selectivity(input, left);
+                let r = self.selectivity(input, right);
+                l + r - l * r
+            }
+            Expr::UnaryOp {
+                op: UnaryOp::Not,
+                expr,
+            } => 1.0 - self.selectivity(input, expr),
+            Expr::BinaryOp {
+                left,
+                op: BinaryOp::Eq,
+                right,
+            } => self.equality_selectivity(input, left, right),
+            Expr::BinaryOp {
+                left,
+                op: BinaryOp::NotEq,
+                right,
+            } => 1.0 - self.equality_selectivity(input, left, right),
+            Expr::BinaryOp {
+                left,
+                op: op @ (BinaryOp::Lt | BinaryOp::LtEq | BinaryOp::Gt | BinaryOp::GtEq),
+                right,
+            } => self
+                .range_selectivity(input, left, *op, right)
+                .unwrap_or(RANGE_SELECTIVITY),
+            Expr::Between {
+                expr,
+                low,
+                high,
+                negated,
+            } => {
+                let above = self.range_selectivity(input, expr, BinaryOp::GtEq, low);
+                let below = self.range_selectivity(input, expr, BinaryOp::LtEq, high);
+                let within = match (above, below) {
+                    (Some(above), Some(below)) => (above + below - 1.0).max(0.0),
+                    _ => RANGE_SELECTIVITY,
+                };
+                if *negated { 1.0 - within } else { within }
+            }
+            Expr::IsNull { expr, negated } => {
+                let fraction = match expr.as_ref() {
+                    Expr::Column {
+                        index: Some(index), ..
+                    } => self
+                        .column_statistics(input, *index)
+                        .map_or(NULL_SELECTIVITY, |stats| stats.null_fraction),
+                    _ => NULL_SELECTIVITY,
+                };
+                if *negated { 1.0 - fraction } else { fraction }
+            }
+            Expr::InList {
+                expr,
+                list,
+                negated,
+            } => {
+                let per_value = self
+                    .expr_distinct_count(input, expr)
+                    .map_or(EQUALITY_SELECTIVITY, |distinct| 1.0 / distinct);
+                let matched = (per_value * list.len() as f64).min(1.0);
+                if *negated { 1.0 - matched } else { matched }
+            }
+            _ => DEFAULT_SELECTIVITY,
+        };
+        selectivity.clamp(0.0, 1.0)
+    }
+
+    fn equality_selectivity(&self, input: &OptimizedLogicalPlan, left: &Expr, right: &Expr) -> f64 {
+        let left_distinct = self.expr_distinct_count(input, left);
+        let right_distinct = self.expr_distinct_count(input, right);
+        match (left_distinct, right_distinct) {
+            (Some(l), Some(r)) => 1.0 / l.max(r),
+            (Some(distinct), None) | (None, Some(distinct)) => 1.0 / distinct,
+            (None, None) => EQUALITY_SELECTIVITY,
+        }
+    }
+
+    /// Interpolates `column op literal` between the column's minimum and
+    /// maximum, for numeric columns with statistics.
+    fn range_selectivity(
+        &self,
+        input: &OptimizedLogicalPlan,
+        left: &Expr,
+        op: BinaryOp,
+        right: &Expr,
+    ) -> Option<f64> {
+        let (column, op, literal) = match (left, right) {
+            (Expr::Column { .. }, Expr::Literal(literal)) => (left, op, literal),
+            (Expr::Literal(literal), Expr::Column { .. }) => (right, flip(op), literal),
+            _ => return None,
+        };
+        let Expr::Column {
+            index: Some(index), ..
+        } = column
+        else {
+            return None;
+        };
+        let value = literal_number(literal)?;
+        let stats = self.column_statistics(input, *index)?;
+        let min = value_number(stats.min.as_ref()?)?;
+        let max = value_number(stats.max.as_ref()?)?;
+        if max <= min {
+            return None;
+        }
+        let below = ((value - min) / (max - min)).clamp(0.0, 1.0);
+        let fraction = match op {
+            BinaryOp::Lt | BinaryOp::LtEq => below,
+            _ => 1.0 - below,
+        };
+        Some(fraction * (1.0 - stats.null_fraction))
+    }
+}
+
+fn flip(op: BinaryOp) -> BinaryOp {
+    match op {
+        BinaryOp::Lt => BinaryOp::Gt,
+        BinaryOp::LtEq => BinaryOp::GtEq,
+        BinaryOp::Gt => BinaryOp::Lt,
+        BinaryOp::GtEq => BinaryOp::LtEq,
+        other => other,
+    }
+}
+
+fn literal_number(literal: &Literal) -> Option<f64> {
+    match literal {
+        Literal::Int64(v) => Some(*v as f64),
+        Literal::Float64(v) => Some(v.0),
+        _ => None,
+    }
+}
+
+fn value_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Int64(v) => Some(*v as f64),
+        Value::Float64(v) => Some(v.0),
+        _ => None,
+    }
+}
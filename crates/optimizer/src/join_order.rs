@@ -0,0 +1,647 @@
+//! Cost-based ordering of inner-join chains. A chain of inner, cross and
+//! hash joins (plus a filter sitting directly on top of it) is flattened
+//! into its base relations and join predicates, re-planned as a left-deep
+//! tree in the cheapest order found, and projected back to the original
+//! column order.
+
+use yachtsql_ir::{BinaryOp, Expr, JoinType, PlanSchema};
+
+use crate::cost::{CostModel, DEFAULT_SELECTIVITY, StatisticsProvider};
+use crate::optimized_logical_plan::OptimizedLogicalPlan;
+use crate::planner::{combine_predicates, split_and_predicates};
+
+/// Chains with more relations are ordered greedily rather than exhaustively.
+const MAX_EXHAUSTIVE_RELATIONS: usize = 10;
+/// A plan only departs from the written join order or build side when the
+/// estimate improves by at least this factor; below it the written plan
+/// (and the row order it produces) is kept.
+const MIN_IMPROVEMENT: f64 = 2.0;
+/// Build inputs smaller than this are cheap enough to leave where written.
+const MIN_BUILD_SWAP_ROWS: f64 = 1000.0;
+
+/// Reorders every inner-join chain in `plan` and picks the smaller input of
+/// each hash join as its build side, using `stats` for estimates.
+pub fn reorder_joins(
+    plan: OptimizedLogicalPlan,
+    stats: &dyn StatisticsProvider,
+) -> OptimizedLogicalPlan {
+    reorder(&CostModel::new(stats), plan)
+}
+
+/// Whether `plan` contains a join chain that [`reorder_joins`] would
+/// consider.
+pub fn has_inner_joins(plan: &OptimizedLogicalPlan) -> bool {
+    is_inner_join(plan) || plan.children().into_iter().any(has_inner_joins)
+}
+
+fn reorder(model: &CostModel<'_>, mut plan: OptimizedLogicalPlan) -> OptimizedLogicalPlan {
+    let is_chain_root = match &plan {
+        OptimizedLogicalPlan::Filter { input, .. } => is_inner_join(input),
+        other => is_inner_join(other),
+    };
+    if is_chain_root && let Some(graph) = JoinGraph::build(model, &plan) {
+        return graph.into_plan(model);
+    }
+    for child in plan.children_mut() {
+        let taken = std::mem::replace(
+            child,
+            OptimizedLogicalPlan::Empty {
+                schema: PlanSchema::new(),
+            },
+        );
+        *child = reorder(model, taken);
+    }
+    plan
+}
+
+fn is_inner_join(plan: &OptimizedLogicalPlan) -> bool {
+    matches!(
+        plan,
+        OptimizedLogicalPlan::HashJoin {
+            join_type: JoinType::Inner,
+            ..
+        } | OptimizedLogicalPlan::NestedLoopJoin {
+            join_type: JoinType::Inner,
+            ..
+        } | OptimizedLogicalPlan::CrossJoin { .. }
+    )
+}
+
+struct Relation {
+    plan: OptimizedLogicalPlan,
+    offset: usize,
+    width: usize,
+    rows: f64,
+}
+
+struct JoinPredicate {
+    expr: Expr,
+    relations: u64,
+    selectivity: f64,
+}
+
+struct JoinGraph {
+    relations: Vec<Relation>,
+    predicates: Vec<JoinPredicate>,
+    constant_predicates: Vec<Expr>,
+    schema: PlanSchema,
+}
+
+impl JoinGraph {
+    fn build(model: &CostModel<'_>, root: &OptimizedLogicalPlan) -> Option<Self> {
+        let (chain, mut conjuncts) = match root {
+            OptimizedLogicalPlan::Filter { input, predicate } => {
+                (input.as_ref(), split_and_predicates(predicate))
+            }
+            other => (other, Vec::new()),
+        };
+        let schema = chain.schema().clone();
+
+        let mut leaves = Vec::new();
+        flatten(chain, 0, &mut leaves, &mut conjuncts)?;
+        if leaves.len() < 2 || leaves.len() > u64::BITS as usize {
+            return None;
+        }
+        let width: usize = leaves.iter().map(|(_, _, w)| w).sum();
+        if width != schema.fields.len() {
+            return None;
+        }
+
+        let mut owner = Vec::with_capacity(width);
+        for (idx, (_, _, leaf_width)) in leaves.iter().enumerate() {
+            owner.extend(std::iter::repeat_n(idx, *leaf_width));
+        }
+
+        let mut local_filters: Vec<Vec<Expr>> = vec![Vec::new(); leaves.len()];
+        let mut multi_relation = Vec::new();
+        let mut constant_predicates = Vec::new();
+        for conjunct in conjuncts {
+            if conjunct.contains_subquery() {
+                return None;
+            }
+            let mut columns = Vec::new();
+            if !column_indices(&conjunct, &mut columns) || columns.iter().any(|&c| c >= width) {
+                return None;
+            }
+            let relations = columns.iter().fold(0u64, |mask, &c| mask | (1 << owner[c]));
+            match relations.count_ones() {
+                0 => constant_predicates.push(conjunct),
+                1 => {
+                    let leaf = relations.trailing_zeros() as usize;
+                    let mut local = conjunct;
+                    shift_columns(&mut local, -(leaves[leaf].1 as isize));
+                    local_filters[leaf].push(local);
+                }
+                _ => multi_relation.push((conjunct, relations)),
+            }
+        }
+
+        let relations: Vec<Relation> = leaves
+            .into_iter()
+            .zip(local_filters)
+            .map(|((plan, offset, width), filters)| {
+                let plan = reorder(model, plan.clone());
+                let plan = match combine_predicates(filters) {
+                    Some(predicate) => OptimizedLogicalPlan::Filter {
+                        input: Box::new(plan),
+                        predicate,
+                    },
+                    None => plan,
+                };
+                Relation {
+                    rows: model.estimate_rows(&plan),
+                    plan,
+                    offset,
+                    width,
+                }
+            })
+            .collect();
+
+        let predicates = multi_relation
+            .into_iter()
+            .map(|(expr, mask)| JoinPredicate {
+                selectivity: predicate_selectivity(model, &relations, &owner, &expr),
+                expr,
+                relations: mask,
+            })
+            .collect();
+
+        Some(Self {
+            relations,
+            predicates,
+            constant_predicates,
+            schema,
+        })
+    }
+
+    fn cardinality(&self, set: u64) -> f64 {
+        let rows: f64 = bits(set).map(|r| self.relations[r].rows).product();
+        let selectivity: f64 = self
+            .predicates
+            .iter()
+            .filter(|p| p.relations & !set == 0)
+            .map(|p| p.selectivity)
+            .product();
+        rows * selectivity
+    }
+
+    fn order_cost(&self, order: &[usize]) -> f64 {
+        let mut set = 0u64;
+        let mut cost = 0.0;
+        for (i, &r) in order.iter().enumerate() {
+            set |= 1 << r;
+            if i > 0 {
+                cost += self.cardinality(set);
+            }
+        }
+        cost
+    }
+
+    fn connects(&self, set: u64, relation: usize) -> bool {
+        let with = set | (1 << relation);
+        self.predicates.iter().any(|p| {
+            p.relations & (1 << relation) != 0 && p.relations & set != 0 && p.relations & !with == 0
+        })
+    }
+
+    /// Left-deep order minimizing the summed size of intermediate results.
+    /// Cross products are only considered where no predicate connects.
+    fn best_order(&self) -> Vec<usize> {
+        let n = self.relations.len();
+        if n > MAX_EXHAUSTIVE_RELATIONS {
+            return self.greedy_order();
+        }
+
+        let full = (1u64 << n) - 1;
+        let mut best: Vec<Option<(f64, usize)>> = vec![None; 1 << n];
+        for r in 0..n {
+            best[1 << r] = Some((0.0, r));
+        }
+        for set in 1..=full {
+            if set.count_ones() < 2 {
+                continue;
+            }
+            let cardinality = self.cardinality(set);
+            for connected_only in [true, false] {
+                for r in bits(set) {
+                    let rest = set & !(1 << r);
+                    let Some((rest_cost, _)) = best[rest as usize] else {
+                        continue;
+                    };
+                    if connected_only && !self.connects(rest, r) {
+                        continue;
+                    }
+                    let cost = rest_cost + cardinality;
+                    if best[set as usize].is_none_or(|(c, _)| cost < c) {
+                        best[set as usize] = Some((cost, r));
+                    }
+                }
+                if best[set as usize].is_some() {
+                    break;
+                }
+            }
+        }
+
+        let mut order = Vec::with_capacity(n);
+        let mut set = full;
+        while set != 0 {
+            let (_, last) = best[set as usize].expect("every subset has a plan");
+            order.push(last);
+            set &= !(1 << last);
+        }
+        order.reverse();
+        order
+    }
+
+    fn greedy_order(&self) -> Vec<usize> {
+        let n = self.relations.len();
+        let first = (0..n)
+            .min_by(|&a, &b| self.relations[a].rows.total_cmp(&self.relations[b].rows))
+            .unwrap_or(0);
+        let mut order = vec![first];
+        let mut set = 1u64 << first;
+        while order.len() < n {
+            let remaining: Vec<usize> = (0..n).filter(|r| set & (1 << r) == 0).collect();
+            let connected: Vec<usize> = remaining
+                .iter()
+                .copied()
+                .filter(|&r| self.connects(set, r))
+                .collect();
+            let candidates = if connected.is_empty() {
+                remaining
+            } else {
+                connected
+            };
+            let next = candidates
+                .into_iter()
+                .min_by(|&a, &b| {
+                    self.cardinality(set | (1 << a))
+                        .total_cmp(&self.cardinality(set | (1 << b)))
+                })
+                .expect("a relation remains");
+            order.push(next);
+            set |= 1 << next;
+        }
+        order
+    }
+
+    fn into_plan(mut self, model: &CostModel<'_>) -> OptimizedLogicalPlan {
+        let written: Vec<usize> = (0..self.relations.len()).collect();
+        let best = self.best_order();
+        let order = if self.order_cost(&best) * MIN_IMPROVEMENT < self.order_cost(&written) {
+            best
+        } else {
+            written
+        };
+
+        let total = self.schema.fields.len();
+        let mut owner = vec![0; total];
+        for (idx, relation) in self.relations.iter().enumerate() {
+            owner[relation.offset..relation.offset + relation.width].fill(idx);
+        }
+
+        let placeholder = || OptimizedLogicalPlan::Empty {
+            schema: PlanSchema::new(),
+        };
+        let first = &mut self.relations[order[0]];
+        let mut plan = std::mem::replace(&mut first.plan, placeholder());
+        let mut layout: Vec<usize> = (first.offset..first.offset + first.width).collect();
+        let mut set = 1u64 << order[0];
+        let mut used = vec![false; self.predicates.len()];
+
+        for &r in &order[1..] {
+            let joined = set | (1 << r);
+            let mut equi = Vec::new();
+            let mut residual = Vec::new();
+            for (idx, predicate) in self.predicates.iter().enumerate() {
+                if used[idx]
+                    || predicate.relations & (1 << r) == 0
+                    || predicate.relations & !joined != 0
+                {
+                    continue;
+                }
+                used[idx] = true;
+                match equi_sides(&predicate.expr, &owner, set, r) {
+                    Some(sides) => equi.push(sides),
+                    None => residual.push(predicate.expr.clone()),
+                }
+            }
+
+            let relation = &mut self.relations[r];
+            let relation_layout: Vec<usize> =
+                (relation.offset..relation.offset + relation.width).collect();
+            let relation_plan = std::mem::replace(&mut relation.plan, placeholder());
+            let build_is_larger = relation.rows >= MIN_BUILD_SWAP_ROWS
+                && relation.rows > model.estimate_rows(&plan) * MIN_IMPROVEMENT;
+            let swap = !equi.is_empty() && build_is_larger;
+
+            let (left, left_layout, right, right_layout) = if swap {
+                (relation_plan, relation_layout, plan, layout)
+            } else {
+                (plan, layout, relation_plan, relation_layout)
+            };
+            let (left_keys, right_keys): (Vec<Expr>, Vec<Expr>) = equi
+                .into_iter()
+                .map(|(joined_key, relation_key)| {
+                    let (left_key, right_key) = if swap {
+                        (relation_key, joined_key)
+                    } else {
+                        (joined_key, relation_key)
+                    };
+                    (
+                        remapped(left_key, &left_layout, total),
+                        remapped(right_key, &right_layout, total),
+                    )
+                })
+                .unzip();
+
+            let mut join_layout = left_layout;
+            join_layout.extend(right_layout);
+            let schema = PlanSchema {
+                fields: left
+                    .schema()
+                    .fields
+                    .iter()
+                    .chain(&right.schema().fields)
+                    .cloned()
+                    .collect(),
+            };
+            let residual = combine_predicates(
+                residual
+                    .into_iter()
+                    .map(|expr| remapped(expr, &join_layout, total))
+                    .collect(),
+            );
+
+            plan = if left_keys.is_empty() {
+                match residual {
+                    Some(condition) => OptimizedLogicalPlan::NestedLoopJoin {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                        join_type: JoinType::Inner,
+                        condition: Some(condition),
+                        schema,
+                    },
+                    None => OptimizedLogicalPlan::CrossJoin {
+                        left: Box::new(left),
+                        right: Box::new(right),
+                        schema,
+                    },
+                }
+            } else {
+                let join = OptimizedLogicalPlan::HashJoin {
+                    left: Box::new(left),
+                    right: Box::new(right),
+                    join_type: JoinType::Inner,
+                    left_keys,
+                    right_keys,
+                    residual: None,
+                    schema,
+                };
+                match residual {
+                    Some(predicate) => OptimizedLogicalPlan::Filter {
+                        input: Box::new(join),
+                        predicate,
+                    },
+                    None => join,
+                }
+            };
+            layout = join_layout;
+            set = joined;
+        }
+
+        if let Some(predicate) = combine_predicates(std::mem::take(&mut self.constant_predicates)) {
+            plan = OptimizedLogicalPlan::Filter {
+                input: Box::new(plan),
+                predicate,
+            };
+        }
+
+        if layout.iter().copied().eq(0..total) {
+            restore_schema(&mut plan, self.schema);
+            return plan;
+        }
+        let mut position = vec![0; total];
+        for (pos, &column) in layout.iter().enumerate() {
+            position[column] = pos;
+        }
+        let expressions = self
+            .schema
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(idx, field)| Expr::Column {
+                table: field.table.clone(),
+                name: field.name.clone(),
+                index: Some(position[idx]),
+            })
+            .collect();
+        OptimizedLogicalPlan::Project {
+            input: Box::new(plan),
+            expressions,
+            schema: self.schema,
+        }
+    }
+}
+
+/// Collects the base relations of an inner-join chain as `(plan, offset,
+/// width)` and adds its join conditions to `predicates`, with column indices
+/// relative to the whole chain.
+fn flatten<'p>(
+    plan: &'p OptimizedLogicalPlan,
+    offset: usize,
+    leaves: &mut Vec<(&'p OptimizedLogicalPlan, usize, usize)>,
+    predicates: &mut Vec<Expr>,
+) -> Option<()> {
+    match plan {
+        OptimizedLogicalPlan::HashJoin {
+            left,
+            right,
+            join_type: JoinType::Inner,
+            left_keys,
+            right_keys,
+            residual,
+            ..
+        } => {
+            let left_width = left.schema().fields.len();
+            flatten(left, offset, leaves, predicates)?;
+            flatten(right, offset + left_width, leaves, predicates)?;
+            if let Some(residual) = residual {
+                let mut residual = residual.clone();
+                shift_columns(&mut residual, offset as isize);
+                predicates.extend(split_and_predicates(&residual));
+            }
+            for (left_key, right_key) in left_keys.iter().zip(right_keys) {
+                let mut left_key = left_key.clone();
+                let mut right_key = right_key.clone();
+                shift_columns(&mut left_key, offset as isize);
+                shift_columns(&mut right_key, (offset + left_width) as isize);
+                predicates.push(Expr::BinaryOp {
+                    left: Box::new(left_key),
+                    op: BinaryOp::Eq,
+                    right: Box::new(right_key),
+                });
+            }
+        }
+        OptimizedLogicalPlan::NestedLoopJoin {
+            left,
+            right,
+            join_type: JoinType::Inner,
+            condition,
+            ..
+        } => {
+            let left_width = left.schema().fields.len();
+            flatten(left, offset, leaves, predicates)?;
+            flatten(right, offset + left_width, leaves, predicates)?;
+            if let Some(condition) = condition {
+                let mut condition = condition.clone();
+                shift_columns(&mut condition, offset as isize);
+                predicates.extend(split_and_predicates(&condition));
+            }
+        }
+        OptimizedLogicalPlan::CrossJoin { left, right, .. } => {
+            let left_width = left.schema().fields.len();
+            flatten(left, offset, leaves, predicates)?;
+            flatten(right, offset + left_width, leaves, predicates)?;
+        }
+        leaf => leaves.push((leaf, offset, leaf.schema().fields.len())),
+    }
+    Some(())
+}
+
+fn predicate_selectivity(
+    model: &CostModel<'_>,
+    relations: &[Relation],
+    owner: &[usize],
+    expr: &Expr,
+) -> f64 {
+    if let Expr::BinaryOp {
+        left,
+        op: BinaryOp::Eq,
+        right,
+    } = expr
+        && let (Some(l), Some(r)) = (column_owner(left, owner), column_owner(right, owner))
+        && l != r
+    {
+        let local = |e: &Expr, relation: &Relation| {
+            let mut e = e.clone();
+            shift_columns(&mut e, -(relation.offset as isize));
+            e
+        };
+        let (left_rel, right_rel) = (&relations[l], &relations[r]);
+        return model.equi_join_selectivity(
+            &left_rel.plan,
+            &local(left, left_rel),
+            &right_rel.plan,
+            &local(right, right_rel),
+        );
+    }
+    DEFAULT_SELECTIVITY
+}
+
+fn column_owner(expr: &Expr, owner: &[usize]) -> Option<usize> {
+    match expr {
+        Expr::Column {
+            index: Some(idx), ..
+        } => owner.get(*idx).copied(),
+        _ => None,
+    }
+}
+
+/// Splits `a = b` into the side over the already joined relations `set`
+/// and the side over `relation`, when it is an equi-join between them.
+fn equi_sides(expr: &Expr, owner: &[usize], set: u64, relation: usize) -> Option<(Expr, Expr)> {
+    let Expr::BinaryOp {
+        left,
+        op: BinaryOp::Eq,
+        right,
+    } = expr
+    else {
+        return None;
+    };
+    let mask = |e: &Expr| {
+        let mut columns = Vec::new();
+        column_indices(e, &mut columns);
+        columns.iter().fold(0u64, |m, &c| m | (1 << owner[c]))
+    };
+    let (left_mask, right_mask) = (mask(left), mask(right));
+    let within_set = |m: u64| m != 0 && m & !set == 0;
+    let is_relation = |m: u64| m == 1 << relation;
+    if within_set(left_mask) && is_relation(right_mask) {
+        Some(((**left).clone(), (**right).clone()))
+    } else if within_set(right_mask) && is_relation(left_mask) {
+        Some(((**right).clone(), (**left).clone()))
+    } else {
+        None
+    }
+}
+
+/// Gives the root join of the rebuilt chain the schema of the chain it
+/// replaces, so qualifiers and nullability above it are unchanged.
+fn restore_schema(plan: &mut OptimizedLogicalPlan, original: PlanSchema) {
+    match plan {
+        OptimizedLogicalPlan::Filter { input, .. } => restore_schema(input, original),
+        OptimizedLogicalPlan::HashJoin { schema, .. }
+        | OptimizedLogicalPlan::NestedLoopJoin { schema, .. }
+        | OptimizedLogicalPlan::CrossJoin { schema, .. } => *schema = original,
+        _ => {}
+    }
+}
+
+fn bits(set: u64) -> impl Iterator<Item = usize> {
+    (0..u64::BITS as usize).filter(move |&r| set & (1 << r) != 0)
+}
+
+fn column_indices(expr: &Expr, columns: &mut Vec<usize>) -> bool {
+    match expr {
+        Expr::Column {
+            index: Some(idx), ..
+        } => {
+            columns.push(*idx);
+            true
+        }
+        Expr::Column { index: None, .. } => false,
+        _ => expr
+            .children()
+            .into_iter()
+            .all(|child| column_indices(child, columns)),
+    }
+}
+
+fn shift_columns(expr: &mut Expr, delta: isize) {
+    if let Expr::Column {
+        index: Some(idx), ..
+    } = expr
+    {
+        *idx = idx.wrapping_add_signed(delta);
+        return;
+    }
+    for child in expr.children_mut() {
+        shift_columns(child, delta);
+    }
+}
+
+/// Rewrites chain-wide column indices into positions within `layout`.
+fn remapped(mut expr: Expr, layout: &[usize], total: usize) -> Expr {
+    let mut position = vec![None; total];
+    for (pos, &column) in layout.iter().enumerate() {
+        position[column] = Some(pos);
+    }
+    remap(&mut expr, &position);
+    expr
+}
+
+fn remap(expr: &mut Expr, position: &[Option<usize>]) {
+    if let Expr::Column {
+        index: Some(idx), ..
+    } = expr
+    {
+        if let Some(Some(pos)) = position.get(*idx) {
+            *idx = *pos;
+        }
+        return;
+    }
+    for child in expr.children_mut() {
+        remap(child, position);
+    }
+}
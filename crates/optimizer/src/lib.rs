@@ -1,13 +1,22 @@
+mod cost;
+mod join_order;
 mod optimized_logical_plan;
 mod planner;
+mod rules;
 #[cfg(test)]
 mod tests;
 
+pub use cost::{CostModel, NoStatistics, StatisticsProvider};
+pub use join_order::{has_inner_joins, reorder_joins};
 pub use optimized_logical_plan::{OptimizedLogicalPlan, SampleType};
 pub use planner::PhysicalPlanner;
+pub use rules::{
+    ConstantFolding, LimitPushdown, Optimizer, OptimizerRule, PredicatePushdown, ProjectionPruning,
+    RemoveRedundantOperators, default_rules,
+};
 use yachtsql_common::error::Result;
 use yachtsql_ir::LogicalPlan;
 
 pub fn optimize(logical: &LogicalPlan) -> Result<OptimizedLogicalPlan> {
-    PhysicalPlanner::new().plan(logical)
+    Optimizer::new().optimize(logical)
 }
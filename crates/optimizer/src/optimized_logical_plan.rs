@@ -1,9 +1,10 @@
 use serde::{Deserialize, Serialize};
 use yachtsql_common::types::DataType;
 use yachtsql_ir::{
-    AlterTableOp, Assignment, ColumnDef, CteDefinition, DclResourceType, ExportOptions, Expr,
-    FunctionArg, FunctionBody, GapFillColumn, JoinType, LoadOptions, MergeClause, PlanSchema,
-    ProcedureArg, RaiseLevel, SortExpr, UnnestColumn,
+    AlterTableOp, Assignment, BinaryOp, ColumnDef, CteDefinition, DclResourceType, ExplainFormat,
+    ExportOptions, Expr, FunctionArg, FunctionBody, GapFillColumn, IndexKind, JoinType,
+    LoadOptions, MergeClause, PlanSchema, ProcedureArg, RaiseLevel, SortExpr, TableConstraint,
+    UnnestColumn,
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -20,6 +21,12 @@ pub enum OptimizedLogicalPlan {
         projection: Option<Vec<usize>>,
     },
 
+    TimeTravelScan {
+        table_name: String,
+        schema: PlanSchema,
+        as_of: Expr,
+    },
+
     Sample {
         input: Box<OptimizedLogicalPlan>,
         sample_type: SampleType,
@@ -57,6 +64,21 @@ pub enum OptimizedLogicalPlan {
         join_type: JoinType,
         left_keys: Vec<Expr>,
         right_keys: Vec<Expr>,
+        residual: Option<Expr>,
+        schema: PlanSchema,
+    },
+
+    /// Rows match when `left_keys comparison right_keys` holds and the
+    /// residual is true. `Eq` compares every key pair; the range comparisons
+    /// (`Lt`, `LtEq`, `Gt`, `GtEq`) take a single key on each side.
+    MergeJoin {
+        left: Box<OptimizedLogicalPlan>,
+        right: Box<OptimizedLogicalPlan>,
+        join_type: JoinType,
+        left_keys: Vec<Expr>,
+        right_keys: Vec<Expr>,
+        comparison: BinaryOp,
+        residual: Option<Expr>,
         schema: PlanSchema,
     },
 
@@ -170,6 +192,8 @@ pub enum OptimizedLogicalPlan {
     CreateTable {
         table_name: String,
         columns: Vec<ColumnDef>,
+        constraints: Vec<TableConstraint>,
+        options: Vec<(String, String)>,
         if_not_exists: bool,
         or_replace: bool,
         query: Option<Box<OptimizedLogicalPlan>>,
@@ -190,6 +214,17 @@ pub enum OptimizedLogicalPlan {
         table_name: String,
     },
 
+    Analyze {
+        table_name: String,
+    },
+
+    Explain {
+        input: Box<OptimizedLogicalPlan>,
+        analyze: bool,
+        format: ExplainFormat,
+        schema: PlanSchema,
+    },
+
     CreateView {
         name: String,
         query: Box<OptimizedLogicalPlan>,
@@ -197,6 +232,7 @@ pub enum OptimizedLogicalPlan {
         column_aliases: Vec<String>,
         or_replace: bool,
         if_not_exists: bool,
+        materialized: bool,
     },
 
     DropView {
@@ -208,6 +244,7 @@ pub enum OptimizedLogicalPlan {
         name: String,
         if_not_exists: bool,
         or_replace: bool,
+        options: Vec<(String, String)>,
     },
 
     DropSchema {
@@ -235,6 +272,7 @@ pub enum OptimizedLogicalPlan {
         if_not_exists: bool,
         is_temp: bool,
         is_aggregate: bool,
+        options: Vec<(String, String)>,
     },
 
     DropFunction {
@@ -242,6 +280,23 @@ pub enum OptimizedLogicalPlan {
         if_exists: bool,
     },
 
+    CreateIndex {
+        name: String,
+        table_name: String,
+        kind: IndexKind,
+        columns: Vec<String>,
+        options: Vec<(String, String)>,
+        or_replace: bool,
+        if_not_exists: bool,
+    },
+
+    DropIndex {
+        name: String,
+        table_name: String,
+        kind: IndexKind,
+        if_exists: bool,
+    },
+
     CreateProcedure {
         name: String,
         args: Vec<ProcedureArg>,
@@ -348,6 +403,7 @@ pub enum OptimizedLogicalPlan {
         snapshot_name: String,
         source_name: String,
         if_not_exists: bool,
+        as_of: Option<Expr>,
     },
 
     DropSnapshot {
@@ -402,12 +458,14 @@ impl OptimizedLogicalPlan {
         use yachtsql_ir::EMPTY_SCHEMA;
         match self {
             OptimizedLogicalPlan::TableScan { schema, .. } => schema,
+            OptimizedLogicalPlan::TimeTravelScan { schema, .. } => schema,
             OptimizedLogicalPlan::Sample { input, .. } => input.schema(),
             OptimizedLogicalPlan::Filter { input, .. } => input.schema(),
             OptimizedLogicalPlan::Project { schema, .. } => schema,
             OptimizedLogicalPlan::NestedLoopJoin { schema, .. } => schema,
             OptimizedLogicalPlan::CrossJoin { schema, .. } => schema,
             OptimizedLogicalPlan::HashJoin { schema, .. } => schema,
+            OptimizedLogicalPlan::MergeJoin { schema, .. } => schema,
             OptimizedLogicalPlan::HashAggregate { schema, .. } => schema,
             OptimizedLogicalPlan::Sort { input, .. } => input.schema(),
             OptimizedLogicalPlan::Limit { input, .. } => input.schema(),
@@ -430,6 +488,8 @@ impl OptimizedLogicalPlan {
             OptimizedLogicalPlan::DropTable { .. } => &EMPTY_SCHEMA,
             OptimizedLogicalPlan::AlterTable { .. } => &EMPTY_SCHEMA,
             OptimizedLogicalPlan::Truncate { .. } => &EMPTY_SCHEMA,
+            OptimizedLogicalPlan::Analyze { .. } => &EMPTY_SCHEMA,
+            OptimizedLogicalPlan::Explain { schema, .. } => schema,
             OptimizedLogicalPlan::CreateView { .. } => &EMPTY_SCHEMA,
             OptimizedLogicalPlan::DropView { .. } => &EMPTY_SCHEMA,
             OptimizedLogicalPlan::CreateSchema { .. } => &EMPTY_SCHEMA,
@@ -438,6 +498,8 @@ impl OptimizedLogicalPlan {
             OptimizedLogicalPlan::AlterSchema { .. } => &EMPTY_SCHEMA,
             OptimizedLogicalPlan::CreateFunction { .. } => &EMPTY_SCHEMA,
             OptimizedLogicalPlan::DropFunction { .. } => &EMPTY_SCHEMA,
+            OptimizedLogicalPlan::CreateIndex { .. } => &EMPTY_SCHEMA,
+            OptimizedLogicalPlan::DropIndex { .. } => &EMPTY_SCHEMA,
             OptimizedLogicalPlan::CreateProcedure { .. } => &EMPTY_SCHEMA,
             OptimizedLogicalPlan::DropProcedure { .. } => &EMPTY_SCHEMA,
             OptimizedLogicalPlan::Call { .. } => &EMPTY_SCHEMA,
@@ -469,4 +531,112 @@ impl OptimizedLogicalPlan {
             OptimizedLogicalPlan::GapFill { schema, .. } => schema,
         }
     }
+
+    /// Query inputs of this node, including the queries feeding DML and
+    /// `CREATE TABLE ... AS`. Script bodies and view definitions are not
+    /// included.
+    pub fn children(&self) -> Vec<&OptimizedLogicalPlan> {
+        match self {
+            OptimizedLogicalPlan::Sample { input, .. }
+            | OptimizedLogicalPlan::Filter { input, .. }
+            | OptimizedLogicalPlan::Project { input, .. }
+            | OptimizedLogicalPlan::HashAggregate { input, .. }
+            | OptimizedLogicalPlan::Sort { input, .. }
+            | OptimizedLogicalPlan::Limit { input, .. }
+            | OptimizedLogicalPlan::TopN { input, .. }
+            | OptimizedLogicalPlan::Distinct { input }
+            | OptimizedLogicalPlan::Window { input, .. }
+            | OptimizedLogicalPlan::Unnest { input, .. }
+            | OptimizedLogicalPlan::Qualify { input, .. }
+            | OptimizedLogicalPlan::GapFill { input, .. }
+            | OptimizedLogicalPlan::Explain { input, .. } => vec![input.as_ref()],
+            OptimizedLogicalPlan::NestedLoopJoin { left, right, .. }
+            | OptimizedLogicalPlan::CrossJoin { left, right, .. }
+            | OptimizedLogicalPlan::HashJoin { left, right, .. }
+            | OptimizedLogicalPlan::MergeJoin { left, right, .. }
+            | OptimizedLogicalPlan::Intersect { left, right, .. }
+            | OptimizedLogicalPlan::Except { left, right, .. } => {
+                vec![left.as_ref(), right.as_ref()]
+            }
+            OptimizedLogicalPlan::Union { inputs, .. } => inputs.iter().collect(),
+            OptimizedLogicalPlan::WithCte { body, .. } => vec![body.as_ref()],
+            OptimizedLogicalPlan::Insert { source, .. }
+            | OptimizedLogicalPlan::Merge { source, .. } => vec![source.as_ref()],
+            OptimizedLogicalPlan::Update { from, .. } => from.iter().map(|f| f.as_ref()).collect(),
+            OptimizedLogicalPlan::CreateTable { query, .. } => {
+                query.iter().map(|q| q.as_ref()).collect()
+            }
+            OptimizedLogicalPlan::ExportData { query, .. } => vec![query.as_ref()],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Mutable counterpart of [`Self::children`].
+    pub fn children_mut(&mut self) -> Vec<&mut OptimizedLogicalPlan> {
+        match self {
+            OptimizedLogicalPlan::Sample { input, .. }
+            | OptimizedLogicalPlan::Filter { input, .. }
+            | OptimizedLogicalPlan::Project { input, .. }
+            | OptimizedLogicalPlan::HashAggregate { input, .. }
+            | OptimizedLogicalPlan::Sort { input, .. }
+            | OptimizedLogicalPlan::Limit { input, .. }
+            | OptimizedLogicalPlan::TopN { input, .. }
+            | OptimizedLogicalPlan::Distinct { input }
+            | OptimizedLogicalPlan::Window { input, .. }
+            | OptimizedLogicalPlan::Unnest { input, .. }
+            | OptimizedLogicalPlan::Qualify { input, .. }
+            | OptimizedLogicalPlan::GapFill { input, .. }
+            | OptimizedLogicalPlan::Explain { input, .. } => vec![input.as_mut()],
+            OptimizedLogicalPlan::NestedLoopJoin { left, right, .. }
+            | OptimizedLogicalPlan::CrossJoin { left, right, .. }
+            | OptimizedLogicalPlan::HashJoin { left, right, .. }
+            | OptimizedLogicalPlan::MergeJoin { left, right, .. }
+            | OptimizedLogicalPlan::Intersect { left, right, .. }
+            | OptimizedLogicalPlan::Except { left, right, .. } => {
+                vec![left.as_mut(), right.as_mut()]
+            }
+            OptimizedLogicalPlan::Union { inputs, .. } => inputs.iter_mut().collect(),
+            OptimizedLogicalPlan::WithCte { body, .. } => vec![body.as_mut()],
+            OptimizedLogicalPlan::Insert { source, .. }
+            | OptimizedLogicalPlan::Merge { source, .. } => vec![source.as_mut()],
+            OptimizedLogicalPlan::Update { from, .. } => {
+                from.iter_mut().map(|f| f.as_mut()).collect()
+            }
+            OptimizedLogicalPlan::CreateTable { query, .. } => {
+                query.iter_mut().map(|q| q.as_mut()).collect()
+            }
+            OptimizedLogicalPlan::ExportData { query, .. } => vec![query.as_mut()],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Statements in the script bodies of this node: IF branches, loop,
+    /// block and FOR bodies, and TRY/CATCH blocks. Procedure bodies only run
+    /// on CALL and are not included.
+    pub fn nested_statements(&self) -> Vec<&OptimizedLogicalPlan> {
+        match self {
+            OptimizedLogicalPlan::If {
+                then_branch,
+                else_branch,
+                ..
+            } => then_branch
+                .iter()
+                .chain(else_branch.iter().flatten())
+                .collect(),
+            OptimizedLogicalPlan::While { body, .. }
+            | OptimizedLogicalPlan::Loop { body, .. }
+            | OptimizedLogicalPlan::Block { body, .. }
+            | OptimizedLogicalPlan::Repeat { body, .. }
+            | OptimizedLogicalPlan::For { body, .. } => body.iter().collect(),
+            OptimizedLogicalPlan::TryCatch {
+                try_block,
+                catch_block,
+            } => try_block
+                .iter()
+                .map(|(statement, _)| statement)
+                .chain(catch_block)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
 }
@@ -1,61 +1,146 @@
 use yachtsql_common::error::Result;
-use yachtsql_ir::{BinaryOp, Expr, JoinType, LogicalPlan, SetOperationType};
+use yachtsql_common::types::DataType;
+use yachtsql_ir::{BinaryOp, Expr, JoinType, LogicalPlan, PlanSchema, SetOperationType};
 
 use crate::optimized_logical_plan::{OptimizedLogicalPlan, SampleType};
+use crate::rules::{is_volatile, map_columns, referenced_columns};
 
 pub struct PhysicalPlanner;
 
-fn extract_equi_join_keys(
+/// A join condition split into the conjuncts a hash or merge join can match
+/// on and the residual checked on each matching row pair. Right-side key
+/// expressions index the right input.
+struct JoinCondition {
+    left_keys: Vec<Expr>,
+    right_keys: Vec<Expr>,
+    ranges: Vec<(Expr, BinaryOp, Expr, Expr)>,
+    residual: Vec<Expr>,
+}
+
+fn split_join_condition(
     condition: &Expr,
     left_schema_len: usize,
-) -> Option<(Vec<Expr>, Vec<Expr>)> {
-    let mut left_keys = Vec::new();
-    let mut right_keys = Vec::new();
+    schema: &PlanSchema,
+) -> JoinCondition {
+    let mut split = JoinCondition {
+        left_keys: Vec::new(),
+        right_keys: Vec::new(),
+        ranges: Vec::new(),
+        residual: Vec::new(),
+    };
+    for conjunct in split_and_predicates(condition) {
+        match join_key_pair(&conjunct, left_schema_len, schema) {
+            Some((left, BinaryOp::Eq, right)) => {
+                split.left_keys.push(left);
+                split.right_keys.push(right);
+            }
+            Some((left, op, right)) => split.ranges.push((left, op, right, conjunct)),
+            None => split.residual.push(conjunct),
+        }
+    }
+    split
+}
 
-    if !collect_equi_keys(condition, left_schema_len, &mut left_keys, &mut right_keys) {
+/// `(left, op, right)` for a comparison between an expression over only the
+/// left input and one over only the right input, flipping the operator when
+/// the sides are written the other way round. Keys of different numeric
+/// types are cast to the wider one, since join operators compare key values
+/// of the same type only; keys of other differing types are not extracted.
+fn join_key_pair(
+    conjunct: &Expr,
+    left_schema_len: usize,
+    schema: &PlanSchema,
+) -> Option<(Expr, BinaryOp, Expr)> {
+    let Expr::BinaryOp { left, op, right } = conjunct else {
         return None;
-    }
+    };
+    let flipped = match op {
+        BinaryOp::Eq => BinaryOp::Eq,
+        BinaryOp::Lt => BinaryOp::Gt,
+        BinaryOp::LtEq => BinaryOp::GtEq,
+        BinaryOp::Gt => BinaryOp::Lt,
+        BinaryOp::GtEq => BinaryOp::LtEq,
+        _ => return None,
+    };
+    let (left_key, op, right_key) = match (
+        input_side(left, left_schema_len)?,
+        input_side(right, left_schema_len)?,
+    ) {
+        (ExprSide::Left, ExprSide::Right) => ((**left).clone(), *op, (**right).clone()),
+        (ExprSide::Right, ExprSide::Left) => ((**right).clone(), flipped, (**left).clone()),
+        _ => return None,
+    };
+    let (left_key, mut right_key) = coerce_keys(left_key, right_key, op, schema)?;
+    map_columns(&mut right_key, &|idx| idx - left_schema_len);
+    Some((left_key, op, right_key))
+}
 
-    if left_keys.is_empty() {
+/// Brings a pair of join keys to one type. Keys whose types cannot be read
+/// off the plan are left as they are for equality, which hashes numeric keys
+/// alike, but not for range comparisons, which need an ordered type.
+fn coerce_keys(left: Expr, right: Expr, op: BinaryOp, schema: &PlanSchema) -> Option<(Expr, Expr)> {
+    let (Some(left_type), Some(right_type)) = (key_type(&left, schema), key_type(&right, schema))
+    else {
+        return (op == BinaryOp::Eq).then_some((left, right));
+    };
+    if op != BinaryOp::Eq && !is_ordered(&left_type) {
         return None;
     }
+    if std::mem::discriminant(&left_type) == std::mem::discriminant(&right_type) {
+        return Some((left, right));
+    }
+    let cast = |expr: Expr, data_type: &DataType| Expr::Cast {
+        expr: Box::new(expr),
+        data_type: data_type.clone(),
+        safe: false,
+    };
+    match (numeric_rank(&left_type)?, numeric_rank(&right_type)?) {
+        (l, r) if l < r => Some((cast(left, &right_type), right)),
+        _ => Some((left, cast(right, &left_type))),
+    }
+}
 
-    Some((left_keys, right_keys))
+/// The type of a join key that can be read off the plan: a column of the
+/// joined inputs, a literal or a cast.
+fn key_type(expr: &Expr, schema: &PlanSchema) -> Option<DataType> {
+    let data_type = match expr {
+        Expr::Column {
+            index: Some(idx), ..
+        } => schema.fields.get(*idx)?.data_type.clone(),
+        Expr::Literal(literal) => literal.data_type(),
+        Expr::Cast { data_type, .. } => data_type.clone(),
+        Expr::Alias { expr, .. } => return key_type(expr, schema),
+        _ => return None,
+    };
+    (data_type != DataType::Unknown).then_some(data_type)
 }
 
-fn collect_equi_keys(
-    expr: &Expr,
-    left_schema_len: usize,
-    left_keys: &mut Vec<Expr>,
-    right_keys: &mut Vec<Expr>,
-) -> bool {
-    match expr {
-        Expr::BinaryOp { left, op, right } => match op {
-            BinaryOp::And => {
-                collect_equi_keys(left, left_schema_len, left_keys, right_keys)
-                    && collect_equi_keys(right, left_schema_len, left_keys, right_keys)
-            }
-            BinaryOp::Eq => {
-                let left_side = classify_expr_side(left, left_schema_len);
-                let right_side = classify_expr_side(right, left_schema_len);
-
-                match (left_side, right_side) {
-                    (Some(ExprSide::Left), Some(ExprSide::Right)) => {
-                        left_keys.push((**left).clone());
-                        right_keys.push(adjust_right_expr(right, left_schema_len));
-                        true
-                    }
-                    (Some(ExprSide::Right), Some(ExprSide::Left)) => {
-                        left_keys.push((**right).clone());
-                        right_keys.push(adjust_right_expr(left, left_schema_len));
-                        true
-                    }
-                    _ => false,
-                }
-            }
-            _ => false,
-        },
-        _ => false,
+fn is_ordered(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Bool
+            | DataType::Int64
+            | DataType::Float64
+            | DataType::Numeric(_)
+            | DataType::BigNumeric
+            | DataType::String
+            | DataType::Bytes
+            | DataType::Date
+            | DataType::DateTime
+            | DataType::Time
+            | DataType::Timestamp
+    )
+}
+
+/// Orders the numeric types by the values they hold, as BigQuery coerces
+/// them.
+fn numeric_rank(data_type: &DataType) -> Option<u8> {
+    match data_type {
+        DataType::Int64 => Some(0),
+        DataType::Numeric(_) => Some(1),
+        DataType::BigNumeric => Some(2),
+        DataType::Float64 => Some(3),
+        _ => None,
     }
 }
 
@@ -65,22 +150,101 @@ enum ExprSide {
     Right,
 }
 
-fn classify_expr_side(expr: &Expr, left_schema_len: usize) -> Option<ExprSide> {
-    match expr {
-        Expr::Column {
-            index: Some(idx), ..
-        } => {
-            if *idx < left_schema_len {
-                Some(ExprSide::Left)
-            } else {
-                Some(ExprSide::Right)
-            }
-        }
-        Expr::Column { index: None, .. } => None,
-        _ => None,
+fn input_side(expr: &Expr, left_schema_len: usize) -> Option<ExprSide> {
+    if is_volatile(expr) {
+        return None;
+    }
+    let columns = referenced_columns(expr)?;
+    let (first, last) = (*columns.first()?, *columns.last()?);
+    if last < left_schema_len {
+        Some(ExprSide::Left)
+    } else if first >= left_schema_len {
+        Some(ExprSide::Right)
+    } else {
+        None
+    }
+}
+
+/// Chooses the join operator for `condition`: a hash join on its equality
+/// conjuncts; a merge join on a range comparison when there is no equality;
+/// a nested loop join otherwise.
+fn plan_join(
+    left: OptimizedLogicalPlan,
+    right: OptimizedLogicalPlan,
+    join_type: JoinType,
+    condition: Option<&Expr>,
+    left_schema_len: usize,
+    schema: &PlanSchema,
+) -> OptimizedLogicalPlan {
+    let nested_loop = |left, right| OptimizedLogicalPlan::NestedLoopJoin {
+        left: Box::new(left),
+        right: Box::new(right),
+        join_type,
+        condition: condition.cloned(),
+        schema: schema.clone(),
+    };
+    let Some(condition) = condition else {
+        return nested_loop(left, right);
+    };
+    let JoinCondition {
+        left_keys,
+        right_keys,
+        mut ranges,
+        mut residual,
+    } = split_join_condition(condition, left_schema_len, schema);
+
+    if !left_keys.is_empty() {
+        residual.extend(ranges.into_iter().map(|(_, _, _, conjunct)| conjunct));
+        let residual = combine_predicates(residual);
+        return OptimizedLogicalPlan::HashJoin {
+            left: Box::new(left),
+            right: Box::new(right),
+            join_type,
+            left_keys,
+            right_keys,
+            residual,
+            schema: schema.clone(),
+        };
+    }
+
+    if ranges.is_empty() {
+        return nested_loop(left, right);
+    }
+    let (left_key, comparison, right_key, _) = ranges.remove(0);
+    residual.extend(ranges.into_iter().map(|(_, _, _, conjunct)| conjunct));
+    OptimizedLogicalPlan::MergeJoin {
+        left: Box::new(left),
+        right: Box::new(right),
+        join_type,
+        left_keys: vec![left_key],
+        right_keys: vec![right_key],
+        comparison,
+        residual: combine_predicates(residual),
+        schema: schema.clone(),
     }
 }
 
+/// The join condition a hash or merge join evaluates, over the combined
+/// schema of its inputs.
+fn key_condition(
+    left: &OptimizedLogicalPlan,
+    left_keys: Vec<Expr>,
+    comparison: BinaryOp,
+    right_keys: Vec<Expr>,
+    residual: Option<Expr>,
+) -> Option<Expr> {
+    let left_schema_len = left.schema().fields.len();
+    let comparisons = left_keys.into_iter().zip(right_keys).map(|(l, mut r)| {
+        map_columns(&mut r, &|idx| idx + left_schema_len);
+        Expr::BinaryOp {
+            left: Box::new(l),
+            op: comparison,
+            right: Box::new(r),
+        }
+    });
+    combine_predicates(comparisons.chain(residual).collect())
+}
+
 #[derive(PartialEq, Clone, Copy)]
 enum PredicateSide {
     Left,
@@ -129,7 +293,10 @@ fn classify_predicate_side(expr: &Expr, left_schema_len: usize) -> Option<Predic
             }
             result.or(Some(PredicateSide::Both))
         }
-        Expr::Literal(_) => None,
+        Expr::Literal(_)
+        | Expr::Parameter { .. }
+        | Expr::Placeholder { .. }
+        | Expr::Variable { .. } => None,
         Expr::Cast { expr, .. } => classify_predicate_side(expr, left_schema_len),
         Expr::Like { expr, pattern, .. } => {
             let expr_side = classify_predicate_side(expr, left_schema_len);
@@ -179,7 +346,7 @@ fn classify_predicate_side(expr: &Expr, left_schema_len: usize) -> Option<Predic
     }
 }
 
-fn split_and_predicates(expr: &Expr) -> Vec<Expr> {
+pub(crate) fn split_and_predicates(expr: &Expr) -> Vec<Expr> {
     match expr {
         Expr::BinaryOp {
             left,
@@ -194,7 +361,7 @@ fn split_and_predicates(expr: &Expr) -> Vec<Expr> {
     }
 }
 
-fn combine_predicates(predicates: Vec<Expr>) -> Option<Expr> {
+pub(crate) fn combine_predicates(predicates: Vec<Expr>) -> Option<Expr> {
     predicates.into_iter().reduce(|acc, pred| Expr::BinaryOp {
         left: Box::new(acc),
         op: BinaryOp::And,
@@ -276,17 +443,6 @@ fn adjust_predicate_indices(expr: &Expr, offset: usize) -> Expr {
     }
 }
 
-fn adjust_right_expr(expr: &Expr, left_schema_len: usize) -> Expr {
-    match expr {
-        Expr::Column { table, name, index } => Expr::Column {
-            table: table.clone(),
-            name: name.clone(),
-            index: index.map(|i| i.saturating_sub(left_schema_len)),
-        },
-        other => other.clone(),
-    }
-}
-
 impl PhysicalPlanner {
     pub fn new() -> Self {
         Self
@@ -305,6 +461,16 @@ impl PhysicalPlanner {
                 projection: projection.clone(),
             }),
 
+            LogicalPlan::TimeTravelScan {
+                table_name,
+                schema,
+                as_of,
+            } => Ok(OptimizedLogicalPlan::TimeTravelScan {
+                table_name: table_name.clone(),
+                schema: schema.clone(),
+                as_of: as_of.clone(),
+            }),
+
             LogicalPlan::Sample {
                 input,
                 sample_type,
@@ -368,27 +534,21 @@ impl PhysicalPlanner {
                             self.plan(right)?
                         };
 
-                    let join_plan = if let Some(cond) = condition
-                        && let Some((left_keys, right_keys)) =
-                            extract_equi_join_keys(cond, left_schema_len)
-                    {
-                        OptimizedLogicalPlan::HashJoin {
-                            left: Box::new(optimized_left),
-                            right: Box::new(optimized_right),
-                            join_type: *join_type,
-                            left_keys,
-                            right_keys,
-                            schema: schema.clone(),
-                        }
-                    } else {
-                        OptimizedLogicalPlan::NestedLoopJoin {
-                            left: Box::new(optimized_left),
-                            right: Box::new(optimized_right),
-                            join_type: *join_type,
-                            condition: condition.clone(),
-                            schema: schema.clone(),
-                        }
-                    };
+                    // Comparisons between the two inputs can be matched by
+                    // the join itself instead of filtering its output.
+                    let (join_preds, post_join_preds): (Vec<Expr>, Vec<Expr>) = post_join_preds
+                        .into_iter()
+                        .partition(|pred| join_key_pair(pred, left_schema_len).is_some());
+                    let condition =
+                        combine_predicates(condition.iter().cloned().chain(join_preds).collect());
+                    let join_plan = plan_join(
+                        optimized_left,
+                        optimized_right,
+                        *join_type,
+                        condition.as_ref(),
+                        left_schema_len,
+                        schema,
+                    );
 
                     if let Some(post_filter) = combine_predicates(post_join_preds) {
                         Ok(OptimizedLogicalPlan::Filter {
@@ -454,36 +614,15 @@ impl PhysicalPlanner {
                         right: Box::new(optimized_right),
                         schema: schema.clone(),
                     }),
-                    JoinType::Inner => {
-                        if let Some(cond) = condition
-                            && let Some((left_keys, right_keys)) =
-                                extract_equi_join_keys(cond, left_schema_len)
-                        {
-                            return Ok(OptimizedLogicalPlan::HashJoin {
-                                left: Box::new(optimized_left),
-                                right: Box::new(optimized_right),
-                                join_type: *join_type,
-                                left_keys,
-                                right_keys,
-                                schema: schema.clone(),
-                            });
-                        }
-                        Ok(OptimizedLogicalPlan::NestedLoopJoin {
-                            left: Box::new(optimized_left),
-                            right: Box::new(optimized_right),
-                            join_type: *join_type,
-                            condition: condition.clone(),
-                            schema: schema.clone(),
-                        })
-                    }
-                    JoinType::Left | JoinType::Right | JoinType::Full => {
-                        Ok(OptimizedLogicalPlan::NestedLoopJoin {
-                            left: Box::new(optimized_left),
-                            right: Box::new(optimized_right),
-                            join_type: *join_type,
-                            condition: condition.clone(),
-                            schema: schema.clone(),
-                        })
+                    JoinType::Inner | JoinType::Left | JoinType::Right | JoinType::Full => {
+                        Ok(plan_join(
+                            optimized_left,
+                            optimized_right,
+                            *join_type,
+                            condition.as_ref(),
+                            left_schema_len,
+                            schema,
+                        ))
                     }
                 }
             }
@@ -664,6 +803,8 @@ impl PhysicalPlanner {
             LogicalPlan::CreateTable {
                 table_name,
                 columns,
+                constraints,
+                options,
                 if_not_exists,
                 or_replace,
                 query,
@@ -676,6 +817,8 @@ impl PhysicalPlanner {
                 Ok(OptimizedLogicalPlan::CreateTable {
                     table_name: table_name.clone(),
                     columns: columns.clone(),
+                    constraints: constraints.clone(),
+                    options: options.clone(),
                     if_not_exists: *if_not_exists,
                     or_replace: *or_replace,
                     query: optimized_query,
@@ -704,6 +847,22 @@ impl PhysicalPlanner {
                 table_name: table_name.clone(),
             }),
 
+            LogicalPlan::Analyze { table_name } => Ok(OptimizedLogicalPlan::Analyze {
+                table_name: table_name.clone(),
+            }),
+
+            LogicalPlan::Explain {
+                input,
+                analyze,
+                format,
+                schema,
+            } => Ok(OptimizedLogicalPlan::Explain {
+                input: Box::new(self.plan(input)?),
+                analyze: *analyze,
+                format: *format,
+                schema: schema.clone(),
+            }),
+
             LogicalPlan::CreateView {
                 name,
                 query,
@@ -711,6 +870,7 @@ impl PhysicalPlanner {
                 column_aliases,
                 or_replace,
                 if_not_exists,
+                materialized,
             } => {
                 let query = self.plan(query)?;
                 Ok(OptimizedLogicalPlan::CreateView {
@@ -720,6 +880,7 @@ impl PhysicalPlanner {
                     column_aliases: column_aliases.clone(),
                     or_replace: *or_replace,
                     if_not_exists: *if_not_exists,
+                    materialized: *materialized,
                 })
             }
 
@@ -732,10 +893,12 @@ impl PhysicalPlanner {
                 name,
                 if_not_exists,
                 or_replace,
+                options,
             } => Ok(OptimizedLogicalPlan::CreateSchema {
                 name: name.clone(),
                 if_not_exists: *if_not_exists,
                 or_replace: *or_replace,
+                options: options.clone(),
             }),
 
             LogicalPlan::DropSchema {
@@ -770,6 +933,7 @@ impl PhysicalPlanner {
                 if_not_exists,
                 is_temp,
                 is_aggregate,
+                options,
             } => Ok(OptimizedLogicalPlan::CreateFunction {
                 name: name.clone(),
                 args: args.clone(),
@@ -779,6 +943,7 @@ impl PhysicalPlanner {
                 if_not_exists: *if_not_exists,
                 is_temp: *is_temp,
                 is_aggregate: *is_aggregate,
+                options: options.clone(),
             }),
 
             LogicalPlan::DropFunction { name, if_exists } => {
@@ -788,6 +953,36 @@ impl PhysicalPlanner {
                 })
             }
 
+            LogicalPlan::CreateIndex {
+                name,
+                table_name,
+                kind,
+                columns,
+                options,
+                or_replace,
+                if_not_exists,
+            } => Ok(OptimizedLogicalPlan::CreateIndex {
+                name: name.clone(),
+                table_name: table_name.clone(),
+                kind: *kind,
+                columns: columns.clone(),
+                options: options.clone(),
+                or_replace: *or_replace,
+                if_not_exists: *if_not_exists,
+            }),
+
+            LogicalPlan::DropIndex {
+                name,
+                table_name,
+                kind,
+                if_exists,
+            } => Ok(OptimizedLogicalPlan::DropIndex {
+                name: name.clone(),
+                table_name: table_name.clone(),
+                kind: *kind,
+                if_exists: *if_exists,
+            }),
+
             LogicalPlan::CreateProcedure {
                 name,
                 args,
@@ -990,10 +1185,12 @@ impl PhysicalPlanner {
                 snapshot_name,
                 source_name,
                 if_not_exists,
+                as_of,
             } => Ok(OptimizedLogicalPlan::CreateSnapshot {
                 snapshot_name: snapshot_name.clone(),
                 source_name: source_name.clone(),
                 if_not_exists: *if_not_exists,
+                as_of: as_of.clone(),
             }),
 
             LogicalPlan::DropSnapshot {
@@ -1129,6 +1326,15 @@ impl OptimizedLogicalPlan {
                 schema,
                 projection,
             },
+            OptimizedLogicalPlan::TimeTravelScan {
+                table_name,
+                schema,
+                as_of,
+            } => LogicalPlan::TimeTravelScan {
+                table_name,
+                schema,
+                as_of,
+            },
             OptimizedLogicalPlan::Sample {
                 input,
                 sample_type,
@@ -1187,43 +1393,29 @@ impl OptimizedLogicalPlan {
                 join_type,
                 left_keys,
                 right_keys,
+                residual,
                 schema,
             } => {
-                let left_schema_len = left.schema().fields.len();
-                let restore_right_index = |expr: Expr| -> Expr {
-                    match expr {
-                        Expr::Column { table, name, index } => Expr::Column {
-                            table,
-                            name,
-                            index: index.map(|i| i + left_schema_len),
-                        },
-                        other => other,
-                    }
-                };
-                let condition = if left_keys.len() == 1 {
-                    Some(Expr::BinaryOp {
-                        left: Box::new(left_keys.into_iter().next().unwrap()),
-                        op: BinaryOp::Eq,
-                        right: Box::new(restore_right_index(
-                            right_keys.into_iter().next().unwrap(),
-                        )),
-                    })
-                } else {
-                    let equalities: Vec<Expr> = left_keys
-                        .into_iter()
-                        .zip(right_keys)
-                        .map(|(l, r)| Expr::BinaryOp {
-                            left: Box::new(l),
-                            op: BinaryOp::Eq,
-                            right: Box::new(restore_right_index(r)),
-                        })
-                        .collect();
-                    equalities.into_iter().reduce(|acc, e| Expr::BinaryOp {
-                        left: Box::new(acc),
-                        op: BinaryOp::And,
-                        right: Box::new(e),
-                    })
-                };
+                let condition = key_condition(&left, left_keys, BinaryOp::Eq, right_keys, residual);
+                LogicalPlan::Join {
+                    left: Box::new(left.into_logical()),
+                    right: Box::new(right.into_logical()),
+                    join_type,
+                    condition,
+                    schema,
+                }
+            }
+            OptimizedLogicalPlan::MergeJoin {
+                left,
+                right,
+                join_type,
+                left_keys,
+                right_keys,
+                comparison,
+                residual,
+                schema,
+            } => {
+                let condition = key_condition(&left, left_keys, comparison, right_keys, residual);
                 LogicalPlan::Join {
                     left: Box::new(left.into_logical()),
                     right: Box::new(right.into_logical()),
@@ -1387,12 +1579,16 @@ impl OptimizedLogicalPlan {
             OptimizedLogicalPlan::CreateTable {
                 table_name,
                 columns,
+                constraints,
+                options,
                 if_not_exists,
                 or_replace,
                 query,
             } => LogicalPlan::CreateTable {
                 table_name,
                 columns,
+                constraints,
+                options,
                 if_not_exists,
                 or_replace,
                 query: query.map(|q| Box::new(q.into_logical())),
@@ -1414,6 +1610,18 @@ impl OptimizedLogicalPlan {
                 if_exists,
             },
             OptimizedLogicalPlan::Truncate { table_name } => LogicalPlan::Truncate { table_name },
+            OptimizedLogicalPlan::Analyze { table_name } => LogicalPlan::Analyze { table_name },
+            OptimizedLogicalPlan::Explain {
+                input,
+                analyze,
+                format,
+                schema,
+            } => LogicalPlan::Explain {
+                input: Box::new(input.into_logical()),
+                analyze,
+                format,
+                schema,
+            },
             OptimizedLogicalPlan::CreateView {
                 name,
                 query,
@@ -1421,6 +1629,7 @@ impl OptimizedLogicalPlan {
                 column_aliases,
                 or_replace,
                 if_not_exists,
+                materialized,
             } => LogicalPlan::CreateView {
                 name,
                 query: Box::new(query.into_logical()),
@@ -1428,6 +1637,7 @@ impl OptimizedLogicalPlan {
                 column_aliases,
                 or_replace,
                 if_not_exists,
+                materialized,
             },
             OptimizedLogicalPlan::DropView { name, if_exists } => {
                 LogicalPlan::DropView { name, if_exists }
@@ -1436,10 +1646,12 @@ impl OptimizedLogicalPlan {
                 name,
                 if_not_exists,
                 or_replace,
+                options,
             } => LogicalPlan::CreateSchema {
                 name,
                 if_not_exists,
                 or_replace,
+                options,
             },
             OptimizedLogicalPlan::DropSchema {
                 name,
@@ -1469,6 +1681,7 @@ impl OptimizedLogicalPlan {
                 if_not_exists,
                 is_temp,
                 is_aggregate,
+                options,
             } => LogicalPlan::CreateFunction {
                 name,
                 args,
@@ -1478,10 +1691,39 @@ impl OptimizedLogicalPlan {
                 if_not_exists,
                 is_temp,
                 is_aggregate,
+                options,
             },
             OptimizedLogicalPlan::DropFunction { name, if_exists } => {
                 LogicalPlan::DropFunction { name, if_exists }
             }
+            OptimizedLogicalPlan::CreateIndex {
+                name,
+                table_name,
+                kind,
+                columns,
+                options,
+                or_replace,
+                if_not_exists,
+            } => LogicalPlan::CreateIndex {
+                name,
+                table_name,
+                kind,
+                columns,
+                options,
+                or_replace,
+                if_not_exists,
+            },
+            OptimizedLogicalPlan::DropIndex {
+                name,
+                table_name,
+                kind,
+                if_exists,
+            } => LogicalPlan::DropIndex {
+                name,
+                table_name,
+                kind,
+                if_exists,
+            },
             OptimizedLogicalPlan::CreateProcedure {
                 name,
                 args,
@@ -1594,10 +1836,12 @@ impl OptimizedLogicalPlan {
                 snapshot_name,
                 source_name,
                 if_not_exists,
+                as_of,
             } => LogicalPlan::CreateSnapshot {
                 snapshot_name,
                 source_name,
                 if_not_exists,
+                as_of,
             },
             OptimizedLogicalPlan::DropSnapshot {
                 snapshot_name,
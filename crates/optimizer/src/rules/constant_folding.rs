@@ -0,0 +1,214 @@
+use std::cmp::Ordering;
+
+use yachtsql_common::error::Result;
+use yachtsql_ir::{BinaryOp, Expr, Literal, LogicalPlan, UnaryOp};
+
+use super::{OptimizerRule, empty_like};
+
+/// Evaluates operators over literals and simplifies boolean connectives with
+/// a constant side. Filters left with a constant predicate are removed or
+/// replaced by an empty input.
+pub struct ConstantFolding;
+
+impl OptimizerRule for ConstantFolding {
+    fn name(&self) -> &str {
+        "constant_folding"
+    }
+
+    fn rewrite(&self, plan: &LogicalPlan) -> Result<Option<LogicalPlan>> {
+        match plan {
+            LogicalPlan::Filter { input, predicate } => {
+                let folded = fold(predicate);
+                match &folded {
+                    Expr::Literal(Literal::Bool(true)) => Ok(Some(input.as_ref().clone())),
+                    Expr::Literal(Literal::Bool(false) | Literal::Null)
+                        if !input.schema().fields.is_empty() =>
+                    {
+                        Ok(Some(empty_like(input.schema())))
+                    }
+                    _ if folded != *predicate => Ok(Some(LogicalPlan::Filter {
+                        input: input.clone(),
+                        predicate: folded,
+                    })),
+                    _ => Ok(None),
+                }
+            }
+            LogicalPlan::Project {
+                input,
+                expressions,
+                schema,
+            } => {
+                let folded: Vec<Expr> = expressions.iter().map(fold).collect();
+                if folded == *expressions {
+                    return Ok(None);
+                }
+                Ok(Some(LogicalPlan::Project {
+                    input: input.clone(),
+                    expressions: folded,
+                    schema: schema.clone(),
+                }))
+            }
+            LogicalPlan::Join {
+                left,
+                right,
+                join_type,
+                condition: Some(condition),
+                schema,
+            } => {
+                let folded = fold(condition);
+                if folded == *condition {
+                    return Ok(None);
+                }
+                let condition = match folded {
+                    Expr::Literal(Literal::Bool(true)) => None,
+                    other => Some(other),
+                };
+                Ok(Some(LogicalPlan::Join {
+                    left: left.clone(),
+                    right: right.clone(),
+                    join_type: *join_type,
+                    condition,
+                    schema: schema.clone(),
+                }))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+pub(crate) fn fold(expr: &Expr) -> Expr {
+    let mut expr = expr.clone();
+    for child in expr.children_mut() {
+        *child = fold(child);
+    }
+    match &expr {
+        Expr::BinaryOp { left, op, right } => fold_binary(left, *op, right).unwrap_or(expr),
+        Expr::UnaryOp { op, expr: operand } => match (op, operand.as_ref()) {
+            (UnaryOp::Not, Expr::Literal(Literal::Bool(b))) => Expr::literal_bool(!b),
+            (UnaryOp::Not, Expr::Literal(Literal::Null)) => Expr::literal_null(),
+            (UnaryOp::Minus, Expr::Literal(Literal::Int64(v))) => match v.checked_neg() {
+                Some(v) => Expr::literal_i64(v),
+                None => expr,
+            },
+            (UnaryOp::Minus, Expr::Literal(Literal::Float64(v))) => Expr::literal_f64(-v.0),
+            _ => expr,
+        },
+        Expr::IsNull {
+            expr: operand,
+            negated,
+        } => match operand.as_ref() {
+            Expr::Literal(literal) => {
+                Expr::literal_bool(matches!(literal, Literal::Null) != *negated)
+            }
+            _ => expr,
+        },
+        _ => expr,
+    }
+}
+
+fn fold_binary(left: &Expr, op: BinaryOp, right: &Expr) -> Option<Expr> {
+    match op {
+        BinaryOp::And => match (bool_literal(left), bool_literal(right)) {
+            (Some(Some(false)), _) | (_, Some(Some(false))) => Some(Expr::literal_bool(false)),
+            (Some(Some(true)), _) => Some(right.clone()),
+            (_, Some(Some(true))) => Some(left.clone()),
+            (Some(None), Some(None)) => Some(Expr::literal_null()),
+            _ => None,
+        },
+        BinaryOp::Or => match (bool_literal(left), bool_literal(right)) {
+            (Some(Some(true)), _) | (_, Some(Some(true))) => Some(Expr::literal_bool(true)),
+            (Some(Some(false)), _) => Some(right.clone()),
+            (_, Some(Some(false))) => Some(left.clone()),
+            (Some(None), Some(None)) => Some(Expr::literal_null()),
+            _ => None,
+        },
+        _ => {
+            let (Expr::Literal(l), Expr::Literal(r)) = (left, right) else {
+                return None;
+            };
+            fold_literals(l, op, r)
+        }
+    }
+}
+
+/// `Some(Some(b))` for a boolean literal, `Some(None)` for NULL.
+fn bool_literal(expr: &Expr) -> Option<Option<bool>> {
+    match expr {
+        Expr::Literal(Literal::Bool(b)) => Some(Some(*b)),
+        Expr::Literal(Literal::Null) => Some(None),
+        _ => None,
+    }
+}
+
+fn fold_literals(left: &Literal, op: BinaryOp, right: &Literal) -> Option<Expr> {
+    if is_comparison(op) {
+        if matches!(left, Literal::Null) || matches!(right, Literal::Null) {
+            return Some(Expr::literal_null());
+        }
+        let ordering = compare_literals(left, right)?;
+        let result = match op {
+            BinaryOp::Eq => ordering == Ordering::Equal,
+            BinaryOp::NotEq => ordering != Ordering::Equal,
+            BinaryOp::Lt => ordering == Ordering::Less,
+            BinaryOp::LtEq => ordering != Ordering::Greater,
+            BinaryOp::Gt => ordering == Ordering::Greater,
+            BinaryOp::GtEq => ordering != Ordering::Less,
+            _ => unreachable!(),
+        };
+        return Some(Expr::literal_bool(result));
+    }
+
+    match (left, right) {
+        (Literal::Int64(l), Literal::Int64(r)) => {
+            let value = match op {
+                BinaryOp::Add => l.checked_add(*r),
+                BinaryOp::Sub => l.checked_sub(*r),
+                BinaryOp::Mul => l.checked_mul(*r),
+                _ => None,
+            }?;
+            Some(Expr::literal_i64(value))
+        }
+        _ => {
+            let (l, r) = (float_value(left)?, float_value(right)?);
+            let value = match op {
+                BinaryOp::Add => l + r,
+                BinaryOp::Sub => l - r,
+                BinaryOp::Mul => l * r,
+                _ => return None,
+            };
+            Some(Expr::literal_f64(value))
+        }
+    }
+}
+
+fn is_comparison(op: BinaryOp) -> bool {
+    matches!(
+        op,
+        BinaryOp::Eq
+            | BinaryOp::NotEq
+            | BinaryOp::Lt
+            | BinaryOp::LtEq
+            | BinaryOp::Gt
+            | BinaryOp::GtEq
+    )
+}
+
+fn compare_literals(left: &Literal, right: &Literal) -> Option<Ordering> {
+    match (left, right) {
+        (Literal::Int64(l), Literal::Int64(r)) => Some(l.cmp(r)),
+        (Literal::Bool(l), Literal::Bool(r)) => Some(l.cmp(r)),
+        (Literal::String(l), Literal::String(r)) => Some(l.cmp(r)),
+        _ => {
+            let (l, r) = (float_value(left)?, float_value(right)?);
+            l.partial_cmp(&r)
+        }
+    }
+}
+
+fn float_value(literal: &Literal) -> Option<f64> {
+    match literal {
+        Literal::Int64(v) => Some(*v as f64),
+        Literal::Float64(v) => Some(v.0),
+        _ => None,
+    }
+}
@@ -0,0 +1,112 @@
+use yachtsql_common::error::Result;
+use yachtsql_ir::{LogicalPlan, SetOperationType};
+
+use super::OptimizerRule;
+
+/// Merges stacked limits, moves limits below projections (so that a
+/// following sort can be planned as a top-N) and bounds each input of a
+/// `UNION ALL` by the rows the outer limit can still return.
+pub struct LimitPushdown;
+
+impl OptimizerRule for LimitPushdown {
+    fn name(&self) -> &str {
+        "limit_pushdown"
+    }
+
+    fn rewrite(&self, plan: &LogicalPlan) -> Result<Option<LogicalPlan>> {
+        let LogicalPlan::Limit {
+            input,
+            limit,
+            offset,
+        } = plan
+        else {
+            return Ok(None);
+        };
+
+        Ok(match input.as_ref() {
+            LogicalPlan::Limit {
+                input: inner,
+                limit: inner_limit,
+                offset: inner_offset,
+            } => {
+                let skipped = offset.unwrap_or(0);
+                let limit = match (limit, inner_limit) {
+                    (Some(outer), Some(inner)) => Some((*outer).min(inner.saturating_sub(skipped))),
+                    (Some(outer), None) => Some(*outer),
+                    (None, Some(inner)) => Some(inner.saturating_sub(skipped)),
+                    (None, None) => None,
+                };
+                let offset = skipped + inner_offset.unwrap_or(0);
+                Some(LogicalPlan::Limit {
+                    input: inner.clone(),
+                    limit,
+                    offset: (offset > 0).then_some(offset),
+                })
+            }
+
+            LogicalPlan::Project {
+                input: inner,
+                expressions,
+                schema,
+            } if !expressions.iter().any(|e| e.contains_subquery()) => Some(LogicalPlan::Project {
+                input: Box::new(LogicalPlan::Limit {
+                    input: inner.clone(),
+                    limit: *limit,
+                    offset: *offset,
+                }),
+                expressions: expressions.clone(),
+                schema: schema.clone(),
+            }),
+
+            LogicalPlan::SetOperation {
+                left,
+                right,
+                op: SetOperationType::Union,
+                all: true,
+                schema,
+            } => {
+                let Some(limit) = limit else {
+                    return Ok(None);
+                };
+                let bound = limit + offset.unwrap_or(0);
+                if is_bounded(left, bound) && is_bounded(right, bound) {
+                    return Ok(None);
+                }
+                Some(LogicalPlan::Limit {
+                    input: Box::new(LogicalPlan::SetOperation {
+                        left: Box::new(bounded(left, bound)),
+                        right: Box::new(bounded(right, bound)),
+                        op: SetOperationType::Union,
+                        all: true,
+                        schema: schema.clone(),
+                    }),
+                    limit: Some(*limit),
+                    offset: *offset,
+                })
+            }
+
+            _ => None,
+        })
+    }
+}
+
+fn is_bounded(plan: &LogicalPlan, bound: usize) -> bool {
+    matches!(
+        plan,
+        LogicalPlan::Limit {
+            limit: Some(limit),
+            ..
+        } if *limit <= bound
+    )
+}
+
+fn bounded(plan: &LogicalPlan, bound: usize) -> LogicalPlan {
+    if is_bounded(plan, bound) {
+        return plan.clone();
+    }
+    LogicalPlan::Limit {
+        input: Box::new(plan.clone()),
+        limit: Some(bound),
+        offset: None,
+    }
+}
@@ -0,0 +1,236 @@
+//! Rewrite rules over [`LogicalPlan`] run before physical planning. Each
+//! rule rewrites single plan nodes; the [`Optimizer`] walks the plan top-down
+//! applying every rule in turn and repeats until a pass changes nothing.
+
+mod constant_folding;
+mod limit_pushdown;
+mod predicate_pushdown;
+mod projection_pruning;
+mod redundant_operators;
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+pub use constant_folding::ConstantFolding;
+pub use limit_pushdown::LimitPushdown;
+pub use predicate_pushdown::PredicatePushdown;
+pub use projection_pruning::ProjectionPruning;
+pub use redundant_operators::RemoveRedundantOperators;
+use yachtsql_common::error::Result;
+use yachtsql_ir::{Expr, LogicalPlan, PlanSchema, ScalarFunction};
+
+use crate::optimized_logical_plan::OptimizedLogicalPlan;
+use crate::planner::PhysicalPlanner;
+
+/// Passes after which the driver stops even if rules keep firing.
+const DEFAULT_MAX_PASSES: usize = 16;
+
+pub trait OptimizerRule: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Rewrites the root node of `plan`, returning `None` when the rule does
+    /// not apply. Inputs are visited by the driver; a rule only needs to look
+    /// as deep as the pattern it matches.
+    fn rewrite(&self, plan: &LogicalPlan) -> Result<Option<LogicalPlan>>;
+}
+
+#[derive(Clone)]
+pub struct Optimizer {
+    rules: Vec<Arc<dyn OptimizerRule>>,
+    max_passes: usize,
+}
+
+impl Optimizer {
+    pub fn new() -> Self {
+        Self {
+            rules: default_rules(),
+            max_passes: DEFAULT_MAX_PASSES,
+        }
+    }
+
+    /// An optimizer that runs no rewrite rules, only physical planning.
+    pub fn without_rules() -> Self {
+        Self {
+            rules: Vec::new(),
+            max_passes: DEFAULT_MAX_PASSES,
+        }
+    }
+
+    pub fn with_rule(mut self, rule: Arc<dyn OptimizerRule>) -> Self {
+        self.add_rule(rule);
+        self
+    }
+
+    pub fn add_rule(&mut self, rule: Arc<dyn OptimizerRule>) {
+        self.rules.push(rule);
+    }
+
+    pub fn with_max_passes(mut self, max_passes: usize) -> Self {
+        self.max_passes = max_passes;
+        self
+    }
+
+    pub fn rules(&self) -> &[Arc<dyn OptimizerRule>] {
+        &self.rules
+    }
+
+    pub fn rewrite(&self, plan: LogicalPlan) -> Result<LogicalPlan> {
+        let mut plan = plan;
+        for _ in 0..self.max_passes {
+            let mut changed = false;
+            for rule in &self.rules {
+                plan = apply_rule(rule.as_ref(), plan, &mut changed)?;
+            }
+            if !changed {
+                break;
+            }
+        }
+        Ok(plan)
+    }
+
+    pub fn optimize(&self, logical: &LogicalPlan) -> Result<OptimizedLogicalPlan> {
+        let rewritten = self.rewrite(logical.clone())?;
+        PhysicalPlanner::new().plan(&rewritten)
+    }
+}
+
+impl Default for Optimizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for Optimizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Optimizer")
+            .field(
+                "rules",
+                &self.rules.iter().map(|r| r.name()).collect::<Vec<_>>(),
+            )
+            .field("max_passes", &self.max_passes)
+            .finish()
+    }
+}
+
+pub fn default_rules() -> Vec<Arc<dyn OptimizerRule>> {
+    vec![
+        Arc::new(ConstantFolding),
+        Arc::new(PredicatePushdown),
+        Arc::new(RemoveRedundantOperators),
+        Arc::new(LimitPushdown),
+        Arc::new(ProjectionPruning),
+    ]
+}
+
+fn apply_rule(
+    rule: &dyn OptimizerRule,
+    plan: LogicalPlan,
+    changed: &mut bool,
+) -> Result<LogicalPlan> {
+    let mut plan = match rule.rewrite(&plan)? {
+        Some(rewritten) => {
+            *changed = true;
+            rewritten
+        }
+        None => plan,
+    };
+    for child in plan.children_mut() {
+        let taken = std::mem::replace(child, LogicalPlan::empty());
+        *child = apply_rule(rule, taken, changed)?;
+    }
+    Ok(plan)
+}
+
+/// Column indices referenced by `expr`, or `None` when it refers to columns
+/// by name only or contains a subquery that may reference outer columns.
+pub(crate) fn referenced_columns(expr: &Expr) -> Option<BTreeSet<usize>> {
+    let mut columns = BTreeSet::new();
+    collect_columns(expr, &mut columns).then_some(columns)
+}
+
+fn collect_columns(expr: &Expr, columns: &mut BTreeSet<usize>) -> bool {
+    match expr {
+        Expr::Column {
+            index: Some(idx), ..
+        } => {
+            columns.insert(*idx);
+            true
+        }
+        Expr::Column { index: None, .. }
+        | Expr::Wildcard { .. }
+        | Expr::Lambda { .. }
+        | Expr::InSubquery { .. }
+        | Expr::Exists { .. }
+        | Expr::Subquery(_)
+        | Expr::ScalarSubquery(_)
+        | Expr::ArraySubquery(_) => false,
+        _ => expr
+            .children()
+            .into_iter()
+            .all(|child| collect_columns(child, columns)),
+    }
+}
+
+/// Whether evaluating `expr` twice, or on a different set of rows, can give
+/// different results.
+pub(crate) fn is_volatile(expr: &Expr) -> bool {
+    match expr {
+        Expr::ScalarFunction {
+            name:
+                ScalarFunction::Rand | ScalarFunction::RandCanonical | ScalarFunction::GenerateUuid,
+            ..
+        }
+        | Expr::ScalarFunction {
+            name: ScalarFunction::Custom(_),
+            ..
+        }
+        | Expr::Aggregate { .. }
+        | Expr::UserDefinedAggregate { .. }
+        | Expr::Window { .. }
+        | Expr::AggregateWindow { .. } => true,
+        other => other.children().into_iter().any(is_volatile),
+    }
+}
+
+/// Rewrites every column index in `expr` through `map`.
+pub(crate) fn map_columns(expr: &mut Expr, map: &impl Fn(usize) -> usize) {
+    if let Expr::Column {
+        index: Some(idx), ..
+    } = expr
+    {
+        *idx = map(*idx);
+        return;
+    }
+    for child in expr.children_mut() {
+        map_columns(child, map);
+    }
+}
+
+/// Replaces each column reference in `expr` with the expression it is
+/// computed from in `sources`.
+pub(crate) fn substitute_columns(expr: &Expr, sources: &[Expr]) -> Expr {
+    if let Expr::Column {
+        index: Some(idx), ..
+    } = expr
+    {
+        return match &sources[*idx] {
+            Expr::Alias { expr, .. } => expr.as_ref().clone(),
+            source => source.clone(),
+        };
+    }
+    let mut expr = expr.clone();
+    for child in expr.children_mut() {
+        *child = substitute_columns(child, sources);
+    }
+    expr
+}
+
+pub(crate) fn plan_width(plan: &LogicalPlan) -> usize {
+    plan.schema().fields.len()
+}
+
+pub(crate) fn empty_like(schema: &PlanSchema) -> LogicalPlan {
+    LogicalPlan::Empty {
+        schema: schema.clone(),
+    }
+}
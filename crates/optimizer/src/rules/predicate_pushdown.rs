@@ -0,0 +1,300 @@
+use yachtsql_common::error::Result;
+use yachtsql_ir::{Expr, JoinType, LogicalPlan, SetOperationType};
+
+use super::{
+    OptimizerRule, is_volatile, map_columns, plan_width, referenced_columns, substitute_columns,
+};
+use crate::planner::{combine_predicates, split_and_predicates};
+
+/// Moves filter conjuncts below projections, aggregates (on grouping
+/// columns), windows (on columns every window partitions by), sorts,
+/// unions and joins, and single-sided join conditions into the join inputs.
+/// Outer joins only accept conjuncts on their preserved side.
+pub struct PredicatePushdown;
+
+impl OptimizerRule for PredicatePushdown {
+    fn name(&self) -> &str {
+        "predicate_pushdown"
+    }
+
+    fn rewrite(&self, plan: &LogicalPlan) -> Result<Option<LogicalPlan>> {
+        Ok(match plan {
+            LogicalPlan::Filter { input, predicate } => push_filter(input, predicate),
+            LogicalPlan::Join {
+                left,
+                right,
+                join_type,
+                condition: Some(condition),
+                schema,
+            } => push_join_condition(left, right, *join_type, condition, schema),
+            _ => None,
+        })
+    }
+}
+
+fn push_filter(input: &LogicalPlan, predicate: &Expr) -> Option<LogicalPlan> {
+    match input {
+        LogicalPlan::Sort {
+            input: inner,
+            sort_exprs,
+        } => Some(LogicalPlan::Sort {
+            input: Box::new(filtered(inner.as_ref().clone(), vec![predicate.clone()])),
+            sort_exprs: sort_exprs.clone(),
+        }),
+
+        LogicalPlan::Project {
+            input: inner,
+            expressions,
+            schema,
+        } => {
+            let (pushed, kept) = partition(predicate, |conjunct| {
+                let columns = referenced_columns(conjunct)?;
+                columns
+                    .iter()
+                    .all(|&c| expressions.get(c).is_some_and(is_pushable))
+                    .then(|| substitute_columns(conjunct, expressions))
+            })?;
+            let project = LogicalPlan::Project {
+                input: Box::new(filtered(inner.as_ref().clone(), pushed)),
+                expressions: expressions.clone(),
+                schema: schema.clone(),
+            };
+            Some(filtered(project, kept))
+        }
+
+        LogicalPlan::Aggregate {
+            input: inner,
+            group_by,
+            aggregates,
+            schema,
+            grouping_sets: None,
+        } if schema.fields.len() == group_by.len() + aggregates.len() => {
+            let (pushed, kept) = partition(predicate, |conjunct| {
+                let columns = referenced_columns(conjunct)?;
+                (!columns.is_empty()
+                    && columns
+                        .iter()
+                        .all(|&c| group_by.get(c).is_some_and(is_pushable)))
+                .then(|| substitute_columns(conjunct, group_by))
+            })?;
+            let aggregate = LogicalPlan::Aggregate {
+                input: Box::new(filtered(inner.as_ref().clone(), pushed)),
+                group_by: group_by.clone(),
+                aggregates: aggregates.clone(),
+                schema: schema.clone(),
+                grouping_sets: None,
+            };
+            Some(filtered(aggregate, kept))
+        }
+
+        LogicalPlan::Window {
+            input: inner,
+            window_exprs,
+            schema,
+        } => {
+            let partitions: Vec<&[Expr]> = window_exprs
+                .iter()
+                .map(partition_by)
+                .collect::<Option<_>>()?;
+            let input_width = plan_width(inner);
+            let (pushed, kept) = partition(predicate, |conjunct| {
+                let columns = referenced_columns(conjunct)?;
+                columns
+                    .iter()
+                    .all(|&c| {
+                        c < input_width
+                            && partitions
+                                .iter()
+                                .all(|keys| keys.iter().any(|key| is_column(key, c)))
+                    })
+                    .then(|| conjunct.clone())
+            })?;
+            let window = LogicalPlan::Window {
+                input: Box::new(filtered(inner.as_ref().clone(), pushed)),
+                window_exprs: window_exprs.clone(),
+                schema: schema.clone(),
+            };
+            Some(filtered(window, kept))
+        }
+
+        LogicalPlan::SetOperation {
+            left,
+            right,
+            op: SetOperationType::Union,
+            all,
+            schema,
+        } => {
+            let width = schema.fields.len();
+            let (pushed, kept) = partition(predicate, |conjunct| {
+                let columns = referenced_columns(conjunct)?;
+                columns.iter().all(|&c| c < width).then(|| conjunct.clone())
+            })?;
+            let union = LogicalPlan::SetOperation {
+                left: Box::new(filtered(left.as_ref().clone(), pushed.clone())),
+                right: Box::new(filtered(right.as_ref().clone(), pushed)),
+                op: SetOperationType::Union,
+                all: *all,
+                schema: schema.clone(),
+            };
+            Some(filtered(union, kept))
+        }
+
+        LogicalPlan::Join {
+            left,
+            right,
+            join_type,
+            condition,
+            schema,
+        } => {
+            let left_width = plan_width(left);
+            let width = left_width + plan_width(right);
+            let (to_left, to_right) = match join_type {
+                JoinType::Inner | JoinType::Cross => (true, true),
+                JoinType::Left => (true, false),
+                JoinType::Right => (false, true),
+                JoinType::Full => (false, false),
+            };
+            let mut left_preds = Vec::new();
+            let mut right_preds = Vec::new();
+            let mut kept = Vec::new();
+            for conjunct in split_and_predicates(predicate) {
+                match side(&conjunct, left_width, width) {
+                    Some(Side::Left) if to_left => left_preds.push(conjunct),
+                    Some(Side::Right) if to_right => {
+                        right_preds.push(shifted(conjunct, left_width))
+                    }
+                    _ => kept.push(conjunct),
+                }
+            }
+            if left_preds.is_empty() && right_preds.is_empty() {
+                return None;
+            }
+            let join = LogicalPlan::Join {
+                left: Box::new(filtered(left.as_ref().clone(), left_preds)),
+                right: Box::new(filtered(right.as_ref().clone(), right_preds)),
+                join_type: *join_type,
+                condition: condition.clone(),
+                schema: schema.clone(),
+            };
+            Some(filtered(join, kept))
+        }
+
+        _ => None,
+    }
+}
+
+fn push_join_condition(
+    left: &LogicalPlan,
+    right: &LogicalPlan,
+    join_type: JoinType,
+    condition: &Expr,
+    schema: &yachtsql_ir::PlanSchema,
+) -> Option<LogicalPlan> {
+    // A conjunct of the ON clause may only filter the input whose unmatched
+    // rows are dropped, i.e. the non-preserved side of an outer join.
+    let (to_left, to_right) = match join_type {
+        JoinType::Inner | JoinType::Cross => (true, true),
+        JoinType::Left => (false, true),
+        JoinType::Right => (true, false),
+        JoinType::Full => (false, false),
+    };
+    let left_width = plan_width(left);
+    let width = left_width + plan_width(right);
+    let mut left_preds = Vec::new();
+    let mut right_preds = Vec::new();
+    let mut kept = Vec::new();
+    for conjunct in split_and_predicates(condition) {
+        match side(&conjunct, left_width, width) {
+            Some(Side::Left) if to_left => left_preds.push(conjunct),
+            Some(Side::Right) if to_right => right_preds.push(shifted(conjunct, left_width)),
+            _ => kept.push(conjunct),
+        }
+    }
+    if left_preds.is_empty() && right_preds.is_empty() {
+        return None;
+    }
+    Some(LogicalPlan::Join {
+        left: Box::new(filtered(left.clone(), left_preds)),
+        right: Box::new(filtered(right.clone(), right_preds)),
+        join_type,
+        condition: combine_predicates(kept),
+        schema: schema.clone(),
+    })
+}
+
+enum Side {
+    Left,
+    Right,
+}
+
+/// The join input a conjunct reads from, if it reads from exactly one and
+/// can safely be evaluated there.
+fn side(conjunct: &Expr, left_width: usize, width: usize) -> Option<Side> {
+    if is_volatile(conjunct) {
+        return None;
+    }
+    let columns = referenced_columns(conjunct)?;
+    let first = *columns.first()?;
+    let last = *columns.last()?;
+    if last < left_width {
+        Some(Side::Left)
+    } else if first >= left_width && last < width {
+        Some(Side::Right)
+    } else {
+        None
+    }
+}
+
+/// Splits `predicate` into the conjuncts `push` rewrites for the input and
+/// those that stay where they are. Returns `None` if nothing can be pushed.
+fn partition(
+    predicate: &Expr,
+    push: impl Fn(&Expr) -> Option<Expr>,
+) -> Option<(Vec<Expr>, Vec<Expr>)> {
+    let mut pushed = Vec::new();
+    let mut kept = Vec::new();
+    for conjunct in split_and_predicates(predicate) {
+        match (!is_volatile(&conjunct)).then(|| push(&conjunct)).flatten() {
+            Some(rewritten) => pushed.push(rewritten),
+            None => kept.push(conjunct),
+        }
+    }
+    (!pushed.is_empty()).then_some((pushed, kept))
+}
+
+fn is_pushable(source: &Expr) -> bool {
+    !is_volatile(source) && referenced_columns(source).is_some()
+}
+
+fn is_column(expr: &Expr, index: usize) -> bool {
+    match expr {
+        Expr::Column { index: Some(i), .. } => *i == index,
+        Expr::Alias { expr, .. } => is_column(expr, index),
+        _ => false,
+    }
+}
+
+fn partition_by(window_expr: &Expr) -> Option<&[Expr]> {
+    match window_expr {
+        Expr::Window { partition_by, .. } | Expr::AggregateWindow { partition_by, .. } => {
+            Some(partition_by)
+        }
+        Expr::Alias { expr, .. } => partition_by(expr),
+        _ => None,
+    }
+}
+
+fn shifted(mut expr: Expr, offset: usize) -> Expr {
+    map_columns(&mut expr, &|idx| idx - offset);
+    expr
+}
+
+fn filtered(input: LogicalPlan, predicates: Vec<Expr>) -> LogicalPlan {
+    match combine_predicates(predicates) {
+        Some(predicate) => LogicalPlan::Filter {
+            input: Box::new(input),
+            predicate,
+        },
+        None => input,
+    }
+}
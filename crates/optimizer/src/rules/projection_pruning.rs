@@ -0,0 +1,113 @@
+use std::collections::BTreeSet;
+
+use yachtsql_common::error::Result;
+use yachtsql_ir::{Expr, LogicalPlan};
+
+use super::OptimizerRule;
+
+/// Narrows a table scan under a projection or aggregate, optionally through
+/// filters, to the columns those operators reference, filling in
+/// `Scan.projection` and rewriting column indices to the narrowed schema.
+pub struct ProjectionPruning;
+
+impl OptimizerRule for ProjectionPruning {
+    fn name(&self) -> &str {
+        "projection_pruning"
+    }
+
+    fn rewrite(&self, plan: &LogicalPlan) -> Result<Option<LogicalPlan>> {
+        let mut plan = plan.clone();
+        let pruned = match &mut plan {
+            LogicalPlan::Project {
+                input, expressions, ..
+            } => prune_scan_columns(input, expressions.iter_mut()),
+            LogicalPlan::Aggregate {
+                input,
+                group_by,
+                aggregates,
+                ..
+            } => prune_scan_columns(input, group_by.iter_mut().chain(aggregates.iter_mut())),
+            _ => false,
+        };
+        Ok(pruned.then_some(plan))
+    }
+}
+
+/// Leaves the plan untouched, returning `false`, when any expression cannot
+/// be resolved to scan columns by index.
+fn prune_scan_columns<'a>(
+    input: &mut LogicalPlan,
+    exprs: impl IntoIterator<Item = &'a mut Expr>,
+) -> bool {
+    let mut exprs: Vec<&mut Expr> = exprs.into_iter().collect();
+    let mut scan = input;
+    while let LogicalPlan::Filter { input, predicate } = scan {
+        exprs.push(predicate);
+        scan = input.as_mut();
+    }
+    let LogicalPlan::Scan {
+        schema, projection, ..
+    } = scan
+    else {
+        return false;
+    };
+    if projection.is_some() || schema.fields.is_empty() {
+        return false;
+    }
+
+    let width = schema.fields.len();
+    let mut used = BTreeSet::new();
+    if exprs.iter().any(|e| e.contains_subquery())
+        || !exprs
+            .iter()
+            .all(|e| collect_scan_columns(e, width, &mut used))
+        || used.len() == width
+    {
+        return false;
+    }
+
+    let mut mapping = vec![None; width];
+    for (new_idx, &old_idx) in used.iter().enumerate() {
+        mapping[old_idx] = Some(new_idx);
+    }
+    for expr in exprs {
+        remap_scan_columns(expr, &mapping);
+    }
+    schema.fields = used.iter().map(|&i| schema.fields[i].clone()).collect();
+    *projection = Some(used.into_iter().collect());
+    true
+}
+
+fn collect_scan_columns(expr: &Expr, width: usize, used: &mut BTreeSet<usize>) -> bool {
+    match expr {
+        Expr::Column {
+            index: Some(idx), ..
+        } if *idx < width => {
+            used.insert(*idx);
+            true
+        }
+        Expr::Column { .. } | Expr::Wildcard { .. } | Expr::Lambda { .. } => false,
+        _ => {
+            let counts_rows = matches!(expr, Expr::Aggregate { .. });
+            expr.children()
+                .into_iter()
+                .filter(|child| !(counts_rows && matches!(child, Expr::Wildcard { table: None })))
+                .all(|child| collect_scan_columns(child, width, used))
+        }
+    }
+}
+
+fn remap_scan_columns(expr: &mut Expr, mapping: &[Option<usize>]) {
+    if let Expr::Column {
+        index: Some(idx), ..
+    } = expr
+    {
+        if let Some(new_idx) = mapping[*idx] {
+            *idx = new_idx;
+        }
+        return;
+    }
+    for child in expr.children_mut() {
+        remap_scan_columns(child, mapping);
+    }
+}
@@ -0,0 +1,62 @@
+use yachtsql_common::error::Result;
+use yachtsql_ir::{Expr, LogicalPlan, SortExpr};
+
+use super::OptimizerRule;
+
+/// Drops sorts whose order is overridden or implied by an adjacent sort,
+/// sorts on constant keys, and DISTINCT over input that is already unique.
+pub struct RemoveRedundantOperators;
+
+impl OptimizerRule for RemoveRedundantOperators {
+    fn name(&self) -> &str {
+        "remove_redundant_operators"
+    }
+
+    fn rewrite(&self, plan: &LogicalPlan) -> Result<Option<LogicalPlan>> {
+        Ok(match plan {
+            LogicalPlan::Sort { input, sort_exprs }
+                if sort_exprs
+                    .iter()
+                    .all(|s| matches!(s.expr, Expr::Literal(_))) =>
+            {
+                Some(input.as_ref().clone())
+            }
+            LogicalPlan::Sort { input, sort_exprs } => match input.as_ref() {
+                LogicalPlan::Sort {
+                    input: inner,
+                    sort_exprs: inner_exprs,
+                } => {
+                    if is_prefix(inner_exprs, sort_exprs) {
+                        Some(LogicalPlan::Sort {
+                            input: inner.clone(),
+                            sort_exprs: sort_exprs.clone(),
+                        })
+                    } else if is_prefix(sort_exprs, inner_exprs) {
+                        Some(input.as_ref().clone())
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            },
+            LogicalPlan::Distinct { input } => match input.as_ref() {
+                LogicalPlan::Distinct { .. } => Some(input.as_ref().clone()),
+                LogicalPlan::Aggregate {
+                    group_by,
+                    aggregates,
+                    schema,
+                    grouping_sets: None,
+                    ..
+                } if schema.fields.len() == group_by.len() + aggregates.len() => {
+                    Some(input.as_ref().clone())
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+    }
+}
+
+fn is_prefix(prefix: &[SortExpr], exprs: &[SortExpr]) -> bool {
+    prefix.len() <= exprs.len() && prefix.iter().zip(exprs).all(|(a, b)| a == b)
+}
@@ -389,7 +389,7 @@ mod optimizer_tests {
         }
 
         #[test]
-        fn inner_join_with_non_equi_condition_uses_nested_loop() {
+        fn inner_join_with_range_condition_becomes_merge_join() {
             let plan = LogicalPlan::Join {
                 left: Box::new(scan_users()),
                 right: Box::new(scan_orders()),
@@ -401,68 +401,197 @@ mod optimizer_tests {
             let optimized = optimize(&plan);
 
             match optimized {
-                OptimizedLogicalPlan::NestedLoopJoin { .. } => {}
-                other => panic!("Expected NestedLoopJoin (non-equi), got {:?}", other),
+                OptimizedLogicalPlan::MergeJoin {
+                    comparison,
+                    left_keys,
+                    right_keys,
+                    residual,
+                    ..
+                } => {
+                    assert_eq!(comparison, BinaryOp::Gt);
+                    assert_eq!(left_keys, vec![col_idx("id", 0)]);
+                    assert_eq!(right_keys, vec![col_idx("user_id", 1)]);
+                    assert!(residual.is_none());
+                }
+                other => panic!("Expected MergeJoin (range), got {:?}", other),
             }
         }
 
         #[test]
-        fn left_join_uses_nested_loop() {
+        fn join_with_disjunctive_condition_uses_nested_loop() {
             let plan = LogicalPlan::Join {
                 left: Box::new(scan_users()),
                 right: Box::new(scan_orders()),
-                join_type: JoinType::Left,
-                condition: Some(eq(col_idx("id", 0), col_idx("user_id", 3))),
+                join_type: JoinType::Inner,
+                condition: Some(Expr::BinaryOp {
+                    left: Box::new(eq(col_idx("id", 0), col_idx("user_id", 3))),
+                    op: BinaryOp::Or,
+                    right: Box::new(eq(col_idx("id", 0), col_idx("order_id", 2))),
+                }),
                 schema: joined_schema(),
             };
 
             let optimized = optimize(&plan);
 
             match optimized {
-                OptimizedLogicalPlan::NestedLoopJoin { join_type, .. } => {
-                    assert_eq!(join_type, JoinType::Left);
+                OptimizedLogicalPlan::NestedLoopJoin { .. } => {}
+                other => panic!("Expected NestedLoopJoin (disjunction), got {:?}", other),
+            }
+        }
+
+        #[test]
+        fn outer_joins_with_equi_condition_become_hash_joins() {
+            for join_type in [JoinType::Left, JoinType::Right, JoinType::Full] {
+                let plan = LogicalPlan::Join {
+                    left: Box::new(scan_users()),
+                    right: Box::new(scan_orders()),
+                    join_type,
+                    condition: Some(eq(col_idx("id", 0), col_idx("user_id", 3))),
+                    schema: joined_schema(),
+                };
+
+                match optimize(&plan) {
+                    OptimizedLogicalPlan::HashJoin {
+                        join_type: planned,
+                        residual,
+                        ..
+                    } => {
+                        assert_eq!(planned, join_type);
+                        assert!(residual.is_none());
+                    }
+                    other => panic!("Expected HashJoin ({:?}), got {:?}", join_type, other),
                 }
-                other => panic!("Expected NestedLoopJoin (left join), got {:?}", other),
             }
         }
 
         #[test]
-        fn right_join_uses_nested_loop() {
+        fn hash_join_on_expression_keys() {
             let plan = LogicalPlan::Join {
                 left: Box::new(scan_users()),
                 right: Box::new(scan_orders()),
-                join_type: JoinType::Right,
-                condition: Some(eq(col_idx("id", 0), col_idx("user_id", 3))),
+                join_type: JoinType::Left,
+                condition: Some(eq(
+                    Expr::BinaryOp {
+                        left: Box::new(col_idx("id", 0)),
+                        op: BinaryOp::Add,
+                        right: Box::new(lit_i64(1)),
+                    },
+                    col_idx("user_id", 3),
+                )),
                 schema: joined_schema(),
             };
 
-            let optimized = optimize(&plan);
-
-            match optimized {
-                OptimizedLogicalPlan::NestedLoopJoin { join_type, .. } => {
-                    assert_eq!(join_type, JoinType::Right);
+            match optimize(&plan) {
+                OptimizedLogicalPlan::HashJoin {
+                    left_keys,
+                    right_keys,
+                    ..
+                } => {
+                    assert!(matches!(
+                        &left_keys[0],
+                        Expr::BinaryOp {
+                            op: BinaryOp::Add,
+                            ..
+                        }
+                    ));
+                    assert_eq!(right_keys, vec![col_idx("user_id", 1)]);
                 }
-                other => panic!("Expected NestedLoopJoin (right join), got {:?}", other),
+                other => panic!("Expected HashJoin, got {:?}", other),
             }
         }
 
         #[test]
-        fn full_join_uses_nested_loop() {
+        fn hash_join_keeps_non_equi_conjuncts_as_residual() {
+            let residual = gt(col_idx("amount", 4), col_idx("id", 0));
             let plan = LogicalPlan::Join {
                 left: Box::new(scan_users()),
                 right: Box::new(scan_orders()),
                 join_type: JoinType::Full,
+                condition: Some(and(
+                    eq(col_idx("id", 0), col_idx("user_id", 3)),
+                    residual.clone(),
+                )),
+                schema: joined_schema(),
+            };
+
+            match optimize(&plan) {
+                OptimizedLogicalPlan::HashJoin {
+                    join_type,
+                    left_keys,
+                    residual: planned,
+                    ..
+                } => {
+                    assert_eq!(join_type, JoinType::Full);
+                    assert_eq!(left_keys.len(), 1);
+                    assert_eq!(planned, Some(residual));
+                }
+                other => panic!("Expected HashJoin with residual, got {:?}", other),
+            }
+        }
+
+        #[test]
+        fn equi_join_of_sorted_inputs_uses_hash_join() {
+            let sorted = |input: LogicalPlan, name: &str, index: usize| LogicalPlan::Sort {
+                input: Box::new(input),
+                sort_exprs: vec![SortExpr {
+                    expr: col_idx(name, index),
+                    asc: true,
+                    nulls_first: false,
+                }],
+            };
+            let plan = LogicalPlan::Join {
+                left: Box::new(sorted(scan_users(), "id", 0)),
+                right: Box::new(sorted(scan_orders(), "user_id", 1)),
+                join_type: JoinType::Inner,
                 condition: Some(eq(col_idx("id", 0), col_idx("user_id", 3))),
                 schema: joined_schema(),
             };
 
-            let optimized = optimize(&plan);
+            match optimize(&plan) {
+                OptimizedLogicalPlan::HashJoin { .. } => {}
+                other => panic!("Expected HashJoin, got {:?}", other),
+            }
+        }
 
-            match optimized {
-                OptimizedLogicalPlan::NestedLoopJoin { join_type, .. } => {
-                    assert_eq!(join_type, JoinType::Full);
+        #[test]
+        fn range_join_casts_keys_to_common_type() {
+            let plan = LogicalPlan::Join {
+                left: Box::new(scan_users()),
+                right: Box::new(scan_orders()),
+                join_type: JoinType::Inner,
+                condition: Some(gt(col_idx("id", 0), col_idx("amount", 4))),
+                schema: joined_schema(),
+            };
+
+            match optimize(&plan) {
+                OptimizedLogicalPlan::MergeJoin {
+                    left_keys,
+                    right_keys,
+                    ..
+                } => {
+                    assert_eq!(
+                        left_keys,
+                        vec![Expr::Cast {
+                            expr: Box::new(col_idx("id", 0)),
+                            data_type: DataType::Float64,
+                            safe: false,
+                        }]
+                    );
+                    assert_eq!(right_keys, vec![col_idx("amount", 2)]);
                 }
-                other => panic!("Expected NestedLoopJoin (full join), got {:?}", other),
+                other => panic!("Expected MergeJoin (range), got {:?}", other),
+            }
+
+            let plan = LogicalPlan::Join {
+                left: Box::new(scan_users()),
+                right: Box::new(scan_orders()),
+                join_type: JoinType::Inner,
+                condition: Some(gt(col_idx("name", 1), col_idx("amount", 4))),
+                schema: joined_schema(),
+            };
+            match optimize(&plan) {
+                OptimizedLogicalPlan::NestedLoopJoin { .. } => {}
+                other => panic!("Expected NestedLoopJoin, got {:?}", other),
             }
         }
 
@@ -667,4 +796,747 @@ mod optimizer_tests {
             }
         }
     }
+
+    mod projection_pruning {
+        use yachtsql_ir::AggregateFunction;
+
+        use super::*;
+
+        fn optimize(plan: &LogicalPlan) -> OptimizedLogicalPlan {
+            crate::optimize(plan).unwrap()
+        }
+
+        fn project(input: LogicalPlan, expressions: Vec<Expr>) -> LogicalPlan {
+            let schema = PlanSchema::from_fields(
+                expressions
+                    .iter()
+                    .map(|_| PlanField::new("out", DataType::Int64))
+                    .collect(),
+            );
+            LogicalPlan::Project {
+                input: Box::new(input),
+                expressions,
+                schema,
+            }
+        }
+
+        #[test]
+        fn project_narrows_scan_to_referenced_columns() {
+            let plan = project(scan("t"), vec![col_idx("value", 2)]);
+
+            match optimize(&plan) {
+                OptimizedLogicalPlan::Project {
+                    input, expressions, ..
+                } => {
+                    assert_eq!(expressions, vec![col_idx("value", 0)]);
+                    match *input {
+                        OptimizedLogicalPlan::TableScan {
+                            schema, projection, ..
+                        } => {
+                            assert_eq!(projection, Some(vec![2]));
+                            assert_eq!(schema.fields.len(), 1);
+                            assert_eq!(schema.fields[0].name, "value");
+                        }
+                        other => panic!("Expected TableScan, got {:?}", other),
+                    }
+                }
+                other => panic!("Expected Project, got {:?}", other),
+            }
+        }
+
+        #[test]
+        fn filter_columns_are_kept_and_remapped() {
+            let plan = project(
+                LogicalPlan::Filter {
+                    input: Box::new(scan("t")),
+                    predicate: gt(col_idx("value", 2), lit_i64(1)),
+                },
+                vec![col_idx("id", 0)],
+            );
+
+            match optimize(&plan) {
+                OptimizedLogicalPlan::Project {
+                    input, expressions, ..
+                } => {
+                    assert_eq!(expressions, vec![col_idx("id", 0)]);
+                    match *input {
+                        OptimizedLogicalPlan::Filter { input, predicate } => {
+                            assert_eq!(predicate, gt(col_idx("value", 1), lit_i64(1)));
+                            match *input {
+                                OptimizedLogicalPlan::TableScan { projection, .. } => {
+                                    assert_eq!(projection, Some(vec![0, 2]));
+                                }
+                                other => panic!("Expected TableScan, got {:?}", other),
+                            }
+                        }
+                        other => panic!("Expected Filter, got {:?}", other),
+                    }
+                }
+                other => panic!("Expected Project, got {:?}", other),
+            }
+        }
+
+        #[test]
+        fn count_star_scans_no_columns() {
+            let plan = LogicalPlan::Aggregate {
+                input: Box::new(scan("t")),
+                group_by: vec![],
+                aggregates: vec![Expr::Aggregate {
+                    func: AggregateFunction::Count,
+                    args: vec![Expr::Wildcard { table: None }],
+                    distinct: false,
+                    filter: None,
+                    order_by: vec![],
+                    limit: None,
+                    ignore_nulls: false,
+                }],
+                schema: PlanSchema::from_fields(vec![PlanField::new("count", DataType::Int64)]),
+                grouping_sets: None,
+            };
+
+            match optimize(&plan) {
+                OptimizedLogicalPlan::HashAggregate { input, .. } => match *input {
+                    OptimizedLogicalPlan::TableScan {
+                        schema, projection, ..
+                    } => {
+                        assert_eq!(projection, Some(vec![]));
+                        assert!(schema.fields.is_empty());
+                    }
+                    other => panic!("Expected TableScan, got {:?}", other),
+                },
+                other => panic!("Expected HashAggregate, got {:?}", other),
+            }
+        }
+
+        #[test]
+        fn unresolved_columns_disable_pruning() {
+            let plan = project(scan("t"), vec![col("name")]);
+
+            match optimize(&plan) {
+                OptimizedLogicalPlan::Project { input, .. } => match *input {
+                    OptimizedLogicalPlan::TableScan {
+                        schema, projection, ..
+                    } => {
+                        assert_eq!(projection, None);
+                        assert_eq!(schema, test_schema());
+                    }
+                    other => panic!("Expected TableScan, got {:?}", other),
+                },
+                other => panic!("Expected Project, got {:?}", other),
+            }
+        }
+    }
+
+    mod join_ordering {
+        use std::collections::HashMap;
+
+        use yachtsql_storage::ColumnStatistics;
+
+        use super::*;
+        use crate::{CostModel, NoStatistics, StatisticsProvider, reorder_joins};
+
+        #[derive(Default)]
+        struct FakeStatistics {
+            rows: HashMap<String, u64>,
+            distinct: HashMap<(String, String), u64>,
+        }
+
+        impl FakeStatistics {
+            fn table(mut self, name: &str, rows: u64, columns: &[(&str, u64)]) -> Self {
+                self.rows.insert(name.to_string(), rows);
+                for (column, distinct) in columns {
+                    self.distinct
+                        .insert((name.to_string(), column.to_string()), *distinct);
+                }
+                self
+            }
+        }
+
+        impl StatisticsProvider for FakeStatistics {
+            fn row_count(&self, table_name: &str) -> Option<u64> {
+                self.rows.get(table_name).copied()
+            }
+
+            fn column_statistics(
+                &self,
+                table_name: &str,
+                column_name: &str,
+            ) -> Option<ColumnStatistics> {
+                let key = (table_name.to_string(), column_name.to_string());
+                self.distinct
+                    .get(&key)
+                    .map(|&distinct_count| ColumnStatistics {
+                        distinct_count,
+                        null_fraction: 0.0,
+                        min: None,
+                        max: None,
+                    })
+            }
+        }
+
+        fn scan_two(name: &str, first: &str, second: &str) -> LogicalPlan {
+            LogicalPlan::Scan {
+                table_name: name.to_string(),
+                schema: PlanSchema::from_fields(vec![
+                    PlanField::new(first, DataType::Int64),
+                    PlanField::new(second, DataType::Int64),
+                ]),
+                projection: None,
+            }
+        }
+
+        fn inner_join(left: LogicalPlan, right: LogicalPlan, condition: Expr) -> LogicalPlan {
+            let schema = PlanSchema::from_fields(
+                left.schema()
+                    .fields
+                    .iter()
+                    .chain(&right.schema().fields)
+                    .cloned()
+                    .collect(),
+            );
+            LogicalPlan::Join {
+                left: Box::new(left),
+                right: Box::new(right),
+                join_type: JoinType::Inner,
+                condition: Some(condition),
+                schema,
+            }
+        }
+
+        fn scan_names(plan: &OptimizedLogicalPlan, names: &mut Vec<String>) {
+            if let OptimizedLogicalPlan::TableScan { table_name, .. } = plan {
+                names.push(table_name.clone());
+            }
+            for child in plan.children() {
+                scan_names(child, names);
+            }
+        }
+
+        #[test]
+        fn hash_join_builds_on_smaller_input() {
+            let plan = optimize(&inner_join(
+                scan_users(),
+                scan_orders(),
+                eq(col_idx("id", 0), col_idx("user_id", 3)),
+            ));
+            let stats = FakeStatistics::default()
+                .table("users", 10, &[("id", 10)])
+                .table("orders", 100_000, &[("user_id", 10)]);
+
+            let reordered = reorder_joins(plan.clone(), &stats);
+            let mut names = Vec::new();
+            scan_names(&reordered, &mut names);
+            assert_eq!(names, vec!["orders", "users"]);
+
+            match reordered {
+                OptimizedLogicalPlan::Project {
+                    input,
+                    expressions,
+                    schema,
+                } => {
+                    assert_eq!(schema, plan.schema().clone());
+                    assert_eq!(expressions[0], col_idx("id", 3));
+                    assert_eq!(expressions[2], col_idx("order_id", 0));
+                    match *input {
+                        OptimizedLogicalPlan::HashJoin {
+                            left_keys,
+                            right_keys,
+                            ..
+                        } => {
+                            assert_eq!(left_keys, vec![col_idx("user_id", 1)]);
+                            assert_eq!(right_keys, vec![col_idx("id", 0)]);
+                        }
+                        other => panic!("Expected HashJoin, got {:?}", other),
+                    }
+                }
+                other => panic!("Expected Project, got {:?}", other),
+            }
+        }
+
+        #[test]
+        fn small_build_side_keeps_written_plan() {
+            let plan = optimize(&inner_join(
+                scan_orders(),
+                scan_users(),
+                eq(col_idx("user_id", 1), col_idx("id", 3)),
+            ));
+            let stats = FakeStatistics::default()
+                .table("users", 10, &[("id", 10)])
+                .table("orders", 100_000, &[("user_id", 10)]);
+
+            assert_eq!(reorder_joins(plan.clone(), &stats), plan);
+        }
+
+        #[test]
+        fn selective_join_is_evaluated_first() {
+            // a.x = b.x fans out (few distinct values) while b.id = c.id is
+            // a key lookup into a small table, so b and c should meet first.
+            let ab = inner_join(
+                scan_two("a", "x", "a_payload"),
+                scan_two("b", "x", "id"),
+                eq(col_idx("x", 0), col_idx("x", 2)),
+            );
+            let plan = optimize(&inner_join(
+                ab,
+                scan_two("c", "id", "c_payload"),
+                eq(col_idx("id", 3), col_idx("id", 4)),
+            ));
+            let stats = FakeStatistics::default()
+                .table("a", 1000, &[("x", 10)])
+                .table("b", 1000, &[("x", 10), ("id", 1000)])
+                .table("c", 10, &[("id", 10)]);
+
+            let model = CostModel::new(&stats);
+            let reordered = reorder_joins(plan.clone(), &stats);
+            assert_eq!(reordered.schema(), plan.schema());
+            assert!(model.estimate_rows(&reordered) <= model.estimate_rows(&plan));
+
+            let root = match reordered {
+                OptimizedLogicalPlan::Project { input, .. } => *input,
+                other => other,
+            };
+            let OptimizedLogicalPlan::HashJoin { left, right, .. } = root else {
+                panic!("Expected HashJoin, got {:?}", root);
+            };
+            let mut inner = Vec::new();
+            for side in [&left, &right] {
+                if matches!(side.as_ref(), OptimizedLogicalPlan::HashJoin { .. }) {
+                    scan_names(side, &mut inner);
+                }
+            }
+            inner.sort();
+            assert_eq!(inner, vec!["b", "c"]);
+        }
+
+        #[test]
+        fn single_table_predicates_move_to_their_scan() {
+            let join = inner_join(
+                scan_users(),
+                scan_orders(),
+                eq(col_idx("id", 0), col_idx("user_id", 3)),
+            );
+            let schema = join.schema().clone();
+            let plan = OptimizedLogicalPlan::Filter {
+                input: Box::new(optimize(&join)),
+                predicate: gt(col_idx("amount", 4), lit_i64(10)),
+            };
+
+            match reorder_joins(plan, &NoStatistics) {
+                OptimizedLogicalPlan::HashJoin {
+                    right,
+                    schema: join_schema,
+                    ..
+                } => {
+                    assert_eq!(join_schema, schema);
+                    match *right {
+                        OptimizedLogicalPlan::Filter { predicate, .. } => {
+                            assert_eq!(predicate, gt(col_idx("amount", 2), lit_i64(10)));
+                        }
+                        other => panic!("Expected Filter, got {:?}", other),
+                    }
+                }
+                other => panic!("Expected HashJoin, got {:?}", other),
+            }
+        }
+
+        #[test]
+        fn outer_joins_are_left_alone() {
+            let mut join = inner_join(
+                scan_users(),
+                scan_orders(),
+                eq(col_idx("id", 0), col_idx("user_id", 3)),
+            );
+            if let LogicalPlan::Join { join_type, .. } = &mut join {
+                *join_type = JoinType::Left;
+            }
+            let plan = optimize(&join);
+            let stats =
+                FakeStatistics::default()
+                    .table("users", 10, &[])
+                    .table("orders", 100_000, &[]);
+
+            assert_eq!(reorder_joins(plan.clone(), &stats), plan);
+        }
+    }
+
+    mod rewrite_rules {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        use yachtsql_common::error::Result;
+        use yachtsql_ir::AggregateFunction;
+
+        use super::*;
+        use crate::{
+            ConstantFolding, LimitPushdown, Optimizer, OptimizerRule, PredicatePushdown,
+            RemoveRedundantOperators,
+        };
+
+        fn rewrite_with(rule: impl OptimizerRule + 'static, plan: LogicalPlan) -> LogicalPlan {
+            Optimizer::without_rules()
+                .with_rule(Arc::new(rule))
+                .rewrite(plan)
+                .unwrap()
+        }
+
+        fn filter(input: LogicalPlan, predicate: Expr) -> LogicalPlan {
+            LogicalPlan::Filter {
+                input: Box::new(input),
+                predicate,
+            }
+        }
+
+        fn project(input: LogicalPlan, expressions: Vec<Expr>) -> LogicalPlan {
+            let schema = PlanSchema::from_fields(
+                expressions
+                    .iter()
+                    .map(|_| PlanField::new("out", DataType::Int64))
+                    .collect(),
+            );
+            LogicalPlan::Project {
+                input: Box::new(input),
+                expressions,
+                schema,
+            }
+        }
+
+        fn join(join_type: JoinType, condition: Expr) -> LogicalPlan {
+            let left = scan_users();
+            let right = scan_orders();
+            let schema = left.schema().clone().merge(right.schema().clone());
+            LogicalPlan::Join {
+                left: Box::new(left),
+                right: Box::new(right),
+                join_type,
+                condition: Some(condition),
+                schema,
+            }
+        }
+
+        fn sort(input: LogicalPlan, keys: Vec<Expr>) -> LogicalPlan {
+            LogicalPlan::Sort {
+                input: Box::new(input),
+                sort_exprs: keys
+                    .into_iter()
+                    .map(|expr| SortExpr {
+                        expr,
+                        asc: true,
+                        nulls_first: false,
+                    })
+                    .collect(),
+            }
+        }
+
+        #[test]
+        fn constant_conjuncts_are_folded_away() {
+            let predicate = and(
+                eq(
+                    Expr::BinaryOp {
+                        left: Box::new(lit_i64(1)),
+                        op: BinaryOp::Add,
+                        right: Box::new(lit_i64(1)),
+                    },
+                    lit_i64(2),
+                ),
+                gt(col_idx("id", 0), lit_i64(5)),
+            );
+            let plan = rewrite_with(ConstantFolding, filter(scan("t"), predicate));
+
+            assert_eq!(plan, filter(scan("t"), gt(col_idx("id", 0), lit_i64(5))));
+        }
+
+        #[test]
+        fn false_filter_becomes_empty() {
+            let predicate = and(gt(col_idx("id", 0), lit_i64(5)), Expr::literal_bool(false));
+            let plan = rewrite_with(ConstantFolding, filter(scan("t"), predicate));
+
+            match plan {
+                LogicalPlan::Empty { schema } => assert_eq!(schema, test_schema()),
+                other => panic!("Expected Empty, got {:?}", other),
+            }
+        }
+
+        #[test]
+        fn overflowing_arithmetic_is_not_folded() {
+            let expr = Expr::BinaryOp {
+                left: Box::new(lit_i64(i64::MAX)),
+                op: BinaryOp::Add,
+                right: Box::new(lit_i64(1)),
+            };
+            let plan = rewrite_with(ConstantFolding, project(scan("t"), vec![expr.clone()]));
+
+            assert_eq!(plan, project(scan("t"), vec![expr]));
+        }
+
+        #[test]
+        fn filter_is_pushed_through_projection() {
+            let plan = filter(
+                project(scan("t"), vec![col_idx("value", 2).alias("v")]),
+                gt(col_idx("v", 0), lit_i64(1)),
+            );
+            let plan = rewrite_with(PredicatePushdown, plan);
+
+            assert_eq!(
+                plan,
+                project(
+                    filter(scan("t"), gt(col_idx("value", 2), lit_i64(1))),
+                    vec![col_idx("value", 2).alias("v")],
+                )
+            );
+        }
+
+        #[test]
+        fn filter_on_aggregate_result_stays_above_aggregate() {
+            let aggregate = LogicalPlan::Aggregate {
+                input: Box::new(scan("t")),
+                group_by: vec![col_idx("id", 0)],
+                aggregates: vec![Expr::Aggregate {
+                    func: AggregateFunction::Sum,
+                    args: vec![col_idx("value", 2)],
+                    distinct: false,
+                    filter: None,
+                    order_by: vec![],
+                    limit: None,
+                    ignore_nulls: false,
+                }],
+                schema: PlanSchema::from_fields(vec![
+                    PlanField::new("id", DataType::Int64),
+                    PlanField::new("total", DataType::Float64),
+                ]),
+                grouping_sets: None,
+            };
+            let plan = filter(
+                aggregate,
+                and(
+                    gt(col_idx("id", 0), lit_i64(1)),
+                    gt(col_idx("total", 1), lit_i64(10)),
+                ),
+            );
+
+            match rewrite_with(PredicatePushdown, plan) {
+                LogicalPlan::Filter { input, predicate } => {
+                    assert_eq!(predicate, gt(col_idx("total", 1), lit_i64(10)));
+                    match *input {
+                        LogicalPlan::Aggregate { input, .. } => {
+                            assert_eq!(*input, filter(scan("t"), gt(col_idx("id", 0), lit_i64(1))))
+                        }
+                        other => panic!("Expected Aggregate, got {:?}", other),
+                    }
+                }
+                other => panic!("Expected Filter, got {:?}", other),
+            }
+        }
+
+        #[test]
+        fn left_join_keeps_filters_on_null_supplying_side() {
+            let plan = filter(
+                join(JoinType::Left, eq(col_idx("id", 0), col_idx("user_id", 3))),
+                and(
+                    gt(col_idx("id", 0), lit_i64(1)),
+                    gt(col_idx("amount", 4), lit_i64(10)),
+                ),
+            );
+
+            match rewrite_with(PredicatePushdown, plan) {
+                LogicalPlan::Filter { input, predicate } => {
+                    assert_eq!(predicate, gt(col_idx("amount", 4), lit_i64(10)));
+                    match *input {
+                        LogicalPlan::Join { left, right, .. } => {
+                            assert_eq!(
+                                *left,
+                                filter(scan_users(), gt(col_idx("id", 0), lit_i64(1)))
+                            );
+                            assert_eq!(*right, scan_orders());
+                        }
+                        other => panic!("Expected Join, got {:?}", other),
+                    }
+                }
+                other => panic!("Expected Filter, got {:?}", other),
+            }
+        }
+
+        #[test]
+        fn left_join_condition_on_right_side_filters_right_input() {
+            let plan = join(
+                JoinType::Left,
+                and(
+                    eq(col_idx("id", 0), col_idx("user_id", 3)),
+                    and(
+                        gt(col_idx("id", 0), lit_i64(1)),
+                        gt(col_idx("amount", 4), lit_i64(10)),
+                    ),
+                ),
+            );
+
+            match rewrite_with(PredicatePushdown, plan) {
+                LogicalPlan::Join {
+                    left,
+                    right,
+                    condition,
+                    ..
+                } => {
+                    assert_eq!(*left, scan_users());
+                    assert_eq!(
+                        *right,
+                        filter(scan_orders(), gt(col_idx("amount", 2), lit_i64(10)))
+                    );
+                    assert_eq!(
+                        condition,
+                        Some(and(
+                            eq(col_idx("id", 0), col_idx("user_id", 3)),
+                            gt(col_idx("id", 0), lit_i64(1)),
+                        ))
+                    );
+                }
+                other => panic!("Expected Join, got {:?}", other),
+            }
+        }
+
+        #[test]
+        fn volatile_predicates_are_not_pushed() {
+            let rand = Expr::ScalarFunction {
+                name: yachtsql_ir::ScalarFunction::Rand,
+                args: vec![],
+            };
+            let plan = filter(
+                project(scan("t"), vec![col_idx("id", 0)]),
+                gt(rand, Expr::literal_f64(0.5)),
+            );
+
+            assert_eq!(rewrite_with(PredicatePushdown, plan.clone()), plan);
+        }
+
+        #[test]
+        fn inner_sort_is_dropped_when_outer_sort_covers_it() {
+            let plan = sort(
+                sort(scan("t"), vec![col_idx("id", 0)]),
+                vec![col_idx("id", 0), col_idx("name", 1)],
+            );
+
+            assert_eq!(
+                rewrite_with(RemoveRedundantOperators, plan),
+                sort(scan("t"), vec![col_idx("id", 0), col_idx("name", 1)])
+            );
+        }
+
+        #[test]
+        fn sort_under_different_sort_is_kept() {
+            let plan = sort(
+                sort(scan("t"), vec![col_idx("name", 1)]),
+                vec![col_idx("id", 0)],
+            );
+
+            assert_eq!(rewrite_with(RemoveRedundantOperators, plan.clone()), plan);
+        }
+
+        #[test]
+        fn distinct_over_aggregate_needs_all_grouping_columns() {
+            let aggregate = |fields: Vec<PlanField>| LogicalPlan::Aggregate {
+                input: Box::new(scan("t")),
+                group_by: vec![col_idx("id", 0)],
+                aggregates: vec![Expr::Aggregate {
+                    func: AggregateFunction::Sum,
+                    args: vec![col_idx("value", 2)],
+                    distinct: false,
+                    filter: None,
+                    order_by: vec![],
+                    limit: None,
+                    ignore_nulls: false,
+                }],
+                schema: PlanSchema::from_fields(fields),
+                grouping_sets: None,
+            };
+            let grouped = aggregate(vec![
+                PlanField::new("id", DataType::Int64),
+                PlanField::new("total", DataType::Float64),
+            ]);
+            let plan = LogicalPlan::Distinct {
+                input: Box::new(grouped.clone()),
+            };
+            assert_eq!(rewrite_with(RemoveRedundantOperators, plan), grouped);
+
+            let plan = LogicalPlan::Distinct {
+                input: Box::new(aggregate(vec![PlanField::new("total", DataType::Float64)])),
+            };
+            assert_eq!(rewrite_with(RemoveRedundantOperators, plan.clone()), plan);
+        }
+
+        #[test]
+        fn stacked_limits_are_merged() {
+            let plan = LogicalPlan::Limit {
+                input: Box::new(LogicalPlan::Limit {
+                    input: Box::new(scan("t")),
+                    limit: Some(10),
+                    offset: Some(2),
+                }),
+                limit: Some(20),
+                offset: Some(3),
+            };
+
+            assert_eq!(
+                rewrite_with(LimitPushdown, plan),
+                LogicalPlan::Limit {
+                    input: Box::new(scan("t")),
+                    limit: Some(7),
+                    offset: Some(5),
+                }
+            );
+        }
+
+        #[test]
+        fn limit_below_projection_enables_topn() {
+            let plan = LogicalPlan::Limit {
+                input: Box::new(project(
+                    sort(scan("t"), vec![col_idx("value", 2)]),
+                    vec![col_idx("id", 0)],
+                )),
+                limit: Some(5),
+                offset: None,
+            };
+
+            match crate::optimize(&plan).unwrap() {
+                OptimizedLogicalPlan::Project { input, .. } => {
+                    assert!(matches!(
+                        *input,
+                        OptimizedLogicalPlan::TopN { limit: 5, .. }
+                    ));
+                }
+                other => panic!("Expected Project, got {:?}", other),
+            }
+        }
+
+        struct CountingRule(Arc<AtomicUsize>);
+
+        impl OptimizerRule for CountingRule {
+            fn name(&self) -> &str {
+                "counting"
+            }
+
+            fn rewrite(&self, plan: &LogicalPlan) -> Result<Option<LogicalPlan>> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                Ok(Some(plan.clone()))
+            }
+        }
+
+        #[test]
+        fn driver_stops_after_max_passes() {
+            let calls = Arc::new(AtomicUsize::new(0));
+            Optimizer::without_rules()
+                .with_rule(Arc::new(CountingRule(Arc::clone(&calls))))
+                .with_max_passes(3)
+                .rewrite(scan("t"))
+                .unwrap();
+
+            assert_eq!(calls.load(Ordering::SeqCst), 3);
+        }
+
+        #[test]
+        fn registered_rules_run_after_defaults() {
+            let optimizer = Optimizer::new().with_rule(Arc::new(CountingRule(Arc::default())));
+            let names: Vec<&str> = optimizer.rules().iter().map(|r| r.name()).collect();
+
+            assert_eq!(names.last(), Some(&"counting"));
+            assert!(names.contains(&"predicate_pushdown"));
+        }
+    }
 }
@@ -6,7 +6,7 @@ use yachtsql_ir::plan::{FunctionArg, FunctionBody};
 use yachtsql_ir::{
     AggregateFunction, BinaryOp, DateTimeField, Expr, JsonPathElement, Literal, LogicalPlan,
     PlanSchema, ScalarFunction, SortExpr, TrimWhere, UnaryOp, WeekStartDay, WhenClause,
-    WindowFrame, WindowFrameBound, WindowFrameUnit, WindowFunction,
+    WindowFrame, WindowFrameBound, WindowFrameExclusion, WindowFrameUnit, WindowFunction,
 };
 
 use crate::FunctionDefinition;
@@ -80,6 +80,9 @@ impl ExprPlanner {
 
             ast::Expr::Value(val) => {
                 if let ast::Value::Placeholder(name) = &val.value {
+                    if name.starts_with('?') {
+                        return Ok(Expr::Placeholder { id: name.clone() });
+                    }
                     return Ok(Expr::Variable { name: name.clone() });
                 }
                 Ok(Expr::Literal(Self::plan_literal(&val.value)?))
@@ -1286,7 +1289,7 @@ impl ExprPlanner {
         }
     }
 
-    fn plan_literal(val: &ast::Value) -> Result<Literal> {
+    pub(crate) fn plan_literal(val: &ast::Value) -> Result<Literal> {
         match val {
             ast::Value::Number(n, _) => {
                 if let Ok(i) = n.parse::<i64>() {
@@ -1810,43 +1813,57 @@ impl ExprPlanner {
             })
             .collect::<Result<Vec<_>>>()?;
 
-        let frame = spec.window_frame.as_ref().map(|f| WindowFrame {
-            unit: match f.units {
-                ast::WindowFrameUnits::Rows => WindowFrameUnit::Rows,
-                ast::WindowFrameUnits::Range => WindowFrameUnit::Range,
-                ast::WindowFrameUnits::Groups => WindowFrameUnit::Groups,
-            },
-            start: Self::plan_window_bound(&f.start_bound),
-            end: f.end_bound.as_ref().map(Self::plan_window_bound),
-        });
+        let frame = spec
+            .window_frame
+            .as_ref()
+            .map(|f| -> Result<WindowFrame> {
+                Ok(WindowFrame {
+                    unit: match f.units {
+                        ast::WindowFrameUnits::Rows => WindowFrameUnit::Rows,
+                        ast::WindowFrameUnits::Range => WindowFrameUnit::Range,
+                        ast::WindowFrameUnits::Groups => WindowFrameUnit::Groups,
+                    },
+                    start: Self::plan_window_bound(&f.start_bound, schema)?,
+                    end: f
+                        .end_bound
+                        .as_ref()
+                        .map(|b| Self::plan_window_bound(b, schema))
+                        .transpose()?,
+                    exclusion: f.exclusion.map(|e| match e {
+                        ast::WindowFrameExclusion::CurrentRow => WindowFrameExclusion::CurrentRow,
+                        ast::WindowFrameExclusion::Group => WindowFrameExclusion::Group,
+                        ast::WindowFrameExclusion::Ties => WindowFrameExclusion::Ties,
+                        ast::WindowFrameExclusion::NoOthers => WindowFrameExclusion::NoOthers,
+                    }),
+                })
+            })
+            .transpose()?;
 
         Ok((partition_by, order_by, frame))
     }
 
-    fn plan_window_bound(bound: &ast::WindowFrameBound) -> WindowFrameBound {
-        match bound {
-            ast::WindowFrameBound::CurrentRow => WindowFrameBound::CurrentRow,
-            ast::WindowFrameBound::Preceding(None) => WindowFrameBound::Preceding(None),
-            ast::WindowFrameBound::Preceding(Some(e)) => {
-                if let ast::Expr::Value(v) = e.as_ref()
-                    && let ast::Value::Number(n, _) = &v.value
-                    && let Ok(num) = n.parse::<u64>()
-                {
-                    return WindowFrameBound::Preceding(Some(num));
-                }
-                WindowFrameBound::Preceding(None)
-            }
-            ast::WindowFrameBound::Following(None) => WindowFrameBound::Following(None),
-            ast::WindowFrameBound::Following(Some(e)) => {
-                if let ast::Expr::Value(v) = e.as_ref()
-                    && let ast::Value::Number(n, _) = &v.value
-                    && let Ok(num) = n.parse::<u64>()
-                {
-                    return WindowFrameBound::Following(Some(num));
-                }
-                WindowFrameBound::Following(None)
+    fn plan_window_bound(
+        bound: &ast::WindowFrameBound,
+        schema: &PlanSchema,
+    ) -> Result<WindowFrameBound> {
+        let plan_offset = |e: &Option<Box<ast::Expr>>| -> Result<Option<Box<Expr>>> {
+            let Some(e) = e else {
+                return Ok(None);
+            };
+            let offset = Self::plan_expr(e, schema)?;
+            if offset.contains_column_ref() || offset.contains_subquery() {
+                return Err(Error::invalid_query(format!(
+                    "Window frame offset must be a constant expression: {}",
+                    e
+                )));
             }
-        }
+            Ok(Some(Box::new(offset)))
+        };
+        Ok(match bound {
+            ast::WindowFrameBound::CurrentRow => WindowFrameBound::CurrentRow,
+            ast::WindowFrameBound::Preceding(e) => WindowFrameBound::Preceding(plan_offset(e)?),
+            ast::WindowFrameBound::Following(e) => WindowFrameBound::Following(plan_offset(e)?),
+        })
     }
 
     fn try_aggregate_function(name: &str) -> Option<AggregateFunction> {
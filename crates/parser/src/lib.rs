@@ -1,15 +1,18 @@
 mod error;
 mod expr_planner;
+mod parameters;
 mod planner;
 
 pub use error::PlannerError;
+use expr_planner::ExprPlanner;
+pub use parameters::QueryParameter;
 pub use planner::Planner;
 use sqlparser::dialect::BigQueryDialect;
 use sqlparser::parser::Parser;
 use yachtsql_common::error::{Error, Result};
 use yachtsql_common::types::DataType;
 use yachtsql_ir::plan::{FunctionArg, FunctionBody};
-use yachtsql_ir::{ColumnDef, LoadFormat, LoadOptions, LogicalPlan};
+use yachtsql_ir::{ColumnDef, Literal, LoadFormat, LoadOptions, LogicalPlan, PlanSchema};
 use yachtsql_storage::Schema;
 
 pub struct ViewDefinition {
@@ -47,22 +50,29 @@ pub fn plan_statement<C: CatalogProvider>(
 }
 
 pub fn parse_and_plan<C: CatalogProvider>(sql: &str, catalog: &C) -> Result<LogicalPlan> {
+    parse_and_plan_with_parameters(sql, catalog).map(|(plan, _)| plan)
+}
+
+pub fn parse_and_plan_with_parameters<C: CatalogProvider>(
+    sql: &str,
+    catalog: &C,
+) -> Result<(LogicalPlan, Vec<QueryParameter>)> {
     let trimmed = sql.trim();
     let upper = trimmed.to_uppercase();
 
     if let Some(load_plan) = try_parse_load_data(sql)? {
-        return Ok(load_plan);
+        return Ok((load_plan, Vec::new()));
     }
 
     if upper.starts_with("CREATE SNAPSHOT TABLE") {
-        return parse_create_snapshot(trimmed);
+        return Ok((parse_create_snapshot(trimmed)?, Vec::new()));
     }
     if upper.starts_with("DROP SNAPSHOT TABLE") {
-        return parse_drop_snapshot(trimmed);
+        return Ok((parse_drop_snapshot(trimmed)?, Vec::new()));
     }
 
     let preprocessed = preprocess_range_types(sql);
-    let statements = parse_sql(&preprocessed)?;
+    let mut statements = parse_sql(&preprocessed)?;
 
     if statements.is_empty() {
         return Err(yachtsql_common::error::Error::parse_error(
@@ -76,7 +86,57 @@ pub fn parse_and_plan<C: CatalogProvider>(sql: &str, catalog: &C) -> Result<Logi
         ));
     }
 
-    plan_statement(&statements[0], catalog)
+    parameters::number_placeholders(&mut statements[0]);
+    let params = parameters::collect_parameters(&statements[0]);
+    let plan = plan_statement(&statements[0], catalog)?;
+    Ok((plan, params))
+}
+
+/// A query with the literal operands of its WHERE comparisons replaced by
+/// placeholders, so that queries differing only in whitespace, keyword case
+/// or those values share a plan.
+pub struct NormalizedQuery {
+    statement: sqlparser::ast::Statement,
+    /// Canonical text of the parameterized query.
+    pub key: String,
+    /// The hoisted values, by placeholder id.
+    pub literals: Vec<(String, Literal)>,
+}
+
+impl NormalizedQuery {
+    /// Plans the parameterized query. The plan refers to the hoisted values
+    /// through `Expr::Placeholder` with the ids in [`Self::literals`].
+    pub fn plan<C: CatalogProvider>(&self, catalog: &C) -> Result<LogicalPlan> {
+        plan_statement(&self.statement, catalog)
+    }
+}
+
+/// Normalizes `sql` if it is a single query; other statements are planned
+/// as written.
+pub fn normalize_query(sql: &str) -> Option<NormalizedQuery> {
+    let mut statements = parse_sql(&preprocess_range_types(sql)).ok()?;
+    if statements.len() != 1 || !matches!(statements[0], sqlparser::ast::Statement::Query(_)) {
+        return None;
+    }
+    let mut statement = statements.remove(0);
+    parameters::number_placeholders(&mut statement);
+    let literals = parameters::hoist_literals(&mut statement);
+    Some(NormalizedQuery {
+        key: statement.to_string(),
+        statement,
+        literals,
+    })
+}
+
+/// The text of `sql` as printed back from its syntax tree if it is a single
+/// query, so that queries differing only in whitespace or keyword case get
+/// the same text. Unlike [`normalize_query`], literals stay in place.
+pub fn canonical_query(sql: &str) -> Option<String> {
+    let statements = parse_sql(&preprocess_range_types(sql)).ok()?;
+    match statements.as_slice() {
+        [statement @ sqlparser::ast::Statement::Query(_)] => Some(statement.to_string()),
+        _ => None,
+    }
 }
 
 fn try_parse_load_data(sql: &str) -> Result<Option<LogicalPlan>> {
@@ -113,6 +173,11 @@ fn try_parse_load_data(sql: &str) -> Result<Option<LogicalPlan>> {
             .find(|c: char| c.is_whitespace() || c == '(')
             .unwrap_or(trimmed.len());
         let table_name = trimmed[..name_end].to_string();
+        let table_name = if is_temp_table && !table_name.contains('.') {
+            format!("_SESSION.{}", table_name)
+        } else {
+            table_name
+        };
         let after_name = trimmed[name_end..].trim_start();
 
         if after_name.starts_with('(') && !after_name.to_uppercase().starts_with("(FORMAT") {
@@ -340,18 +405,36 @@ fn parse_create_snapshot(sql: &str) -> Result<LogicalPlan> {
     let snapshot_name = rest[..clone_idx].trim().to_string();
     let after_clone = rest[clone_idx + 5..].trim();
 
-    let source_name = if let Some(for_idx) = after_clone.to_uppercase().find("FOR SYSTEM_TIME") {
-        after_clone[..for_idx].trim().to_string()
-    } else if let Some(opt_idx) = after_clone.to_uppercase().find("OPTIONS") {
-        after_clone[..opt_idx].trim().to_string()
-    } else {
-        after_clone.to_string()
+    let after_clone_upper = after_clone.to_uppercase();
+    let options_idx = after_clone_upper
+        .find("OPTIONS")
+        .unwrap_or(after_clone.len());
+    let (source_name, as_of) = match after_clone_upper.find("FOR SYSTEM_TIME AS OF") {
+        Some(for_idx) => {
+            let expr_sql = after_clone[for_idx + 21..options_idx]
+                .trim()
+                .trim_end_matches(';');
+            let expr = Parser::new(&BigQueryDialect {})
+                .try_with_sql(expr_sql)
+                .and_then(|mut parser| parser.parse_expr())
+                .map_err(|e| Error::parse_error(e.to_string()))?;
+            let as_of = ExprPlanner::plan_expr(&expr, &PlanSchema::new())?;
+            (after_clone[..for_idx].trim().to_string(), Some(as_of))
+        }
+        None => (
+            after_clone[..options_idx]
+                .trim()
+                .trim_end_matches(';')
+                .to_string(),
+            None,
+        ),
     };
 
     Ok(LogicalPlan::CreateSnapshot {
         snapshot_name,
         source_name,
         if_not_exists,
+        as_of,
     })
 }
 
@@ -0,0 +1,195 @@
+use std::ops::ControlFlow;
+
+use sqlparser::ast::{
+    BinaryOperator, Expr, Query, SetExpr, Statement, TableFactor, UnaryOperator, Value,
+    visit_expressions, visit_expressions_mut,
+};
+use yachtsql_ir::Literal;
+
+use crate::ExprPlanner;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryParameter {
+    Named(String),
+    Positional(usize),
+}
+
+impl QueryParameter {
+    pub fn variable_name(&self) -> String {
+        match self {
+            QueryParameter::Named(name) => format!("@{}", name.to_uppercase()),
+            QueryParameter::Positional(position) => format!("?{}", position),
+        }
+    }
+}
+
+pub(crate) fn number_placeholders(stmt: &mut Statement) {
+    let mut next_position = 0;
+    let _ = visit_expressions_mut(stmt, |expr| {
+        if let Expr::Value(val) = expr
+            && let Value::Placeholder(name) = &mut val.value
+            && name == "?"
+        {
+            next_position += 1;
+            *name = format!("?{}", next_position);
+        }
+        ControlFlow::<()>::Continue(())
+    });
+}
+
+pub(crate) fn collect_parameters(stmt: &Statement) -> Vec<QueryParameter> {
+    let mut params: Vec<QueryParameter> = Vec::new();
+    let _ = visit_expressions(stmt, |expr| {
+        let param = match expr {
+            Expr::Identifier(ident) => named_parameter(&ident.value),
+            Expr::CompoundIdentifier(parts) => {
+                parts.first().and_then(|p| named_parameter(&p.value))
+            }
+            Expr::Value(val) => match &val.value {
+                Value::Placeholder(name) => match name.strip_prefix('?') {
+                    Some(position) => position.parse().ok().map(QueryParameter::Positional),
+                    None => named_parameter(name),
+                },
+                _ => None,
+            },
+            _ => None,
+        };
+        if let Some(param) = param {
+            let duplicate = params
+                .iter()
+                .any(|p| p.variable_name() == param.variable_name());
+            if !duplicate {
+                params.push(param);
+            }
+        }
+        ControlFlow::<()>::Continue(())
+    });
+
+    params.sort_by_key(|p| match p {
+        QueryParameter::Positional(position) => *position,
+        QueryParameter::Named(_) => 0,
+    });
+    params
+}
+
+fn named_parameter(name: &str) -> Option<QueryParameter> {
+    if name.starts_with("@@") {
+        return None;
+    }
+    name.strip_prefix('@')
+        .filter(|rest| !rest.is_empty())
+        .map(|rest| QueryParameter::Named(rest.to_string()))
+}
+
+/// Replaces the literal operands of comparisons in the WHERE clauses of a
+/// query, and of the derived tables it selects from, with `?_N` placeholders.
+/// Returns the placeholder ids with the values they replaced. Subqueries in
+/// expressions and CTE bodies are left untouched.
+pub(crate) fn hoist_literals(stmt: &mut Statement) -> Vec<(String, Literal)> {
+    let mut hoisted = Vec::new();
+    if let Statement::Query(query) = stmt {
+        hoist_in_query(query, &mut hoisted);
+    }
+    hoisted
+}
+
+fn hoist_in_query(query: &mut Query, hoisted: &mut Vec<(String, Literal)>) {
+    hoist_in_set_expr(&mut query.body, hoisted);
+}
+
+fn hoist_in_set_expr(body: &mut SetExpr, hoisted: &mut Vec<(String, Literal)>) {
+    match body {
+        SetExpr::Select(select) => {
+            for table in &mut select.from {
+                if let TableFactor::Derived {
+                    lateral: false,
+                    subquery,
+                    ..
+                } = &mut table.relation
+                {
+                    hoist_in_query(subquery, hoisted);
+                }
+            }
+            if let Some(selection) = &mut select.selection {
+                hoist_in_predicate(selection, hoisted);
+            }
+        }
+        SetExpr::Query(query) => hoist_in_query(query, hoisted),
+        SetExpr::SetOperation { left, right, .. } => {
+            hoist_in_set_expr(left, hoisted);
+            hoist_in_set_expr(right, hoisted);
+        }
+        _ => {}
+    }
+}
+
+fn hoist_in_predicate(expr: &mut Expr, hoisted: &mut Vec<(String, Literal)>) {
+    match expr {
+        Expr::BinaryOp {
+            left,
+            op: BinaryOperator::And | BinaryOperator::Or,
+            right,
+        } => {
+            hoist_in_predicate(left, hoisted);
+            hoist_in_predicate(right, hoisted);
+        }
+        Expr::BinaryOp {
+            left,
+            op:
+                BinaryOperator::Eq
+                | BinaryOperator::NotEq
+                | BinaryOperator::Lt
+                | BinaryOperator::LtEq
+                | BinaryOperator::Gt
+                | BinaryOperator::GtEq,
+            right,
+        } => match (is_literal(left), is_literal(right)) {
+            (true, false) => hoist_literal(left, hoisted),
+            (false, true) => hoist_literal(right, hoisted),
+            _ => {}
+        },
+        Expr::Nested(inner)
+        | Expr::UnaryOp {
+            op: UnaryOperator::Not,
+            expr: inner,
+        } => hoist_in_predicate(inner, hoisted),
+        Expr::InList { expr, list, .. } if !is_literal(expr) => {
+            for item in list {
+                hoist_literal(item, hoisted);
+            }
+        }
+        Expr::Between {
+            expr, low, high, ..
+        } if !is_literal(expr) => {
+            hoist_literal(low, hoisted);
+            hoist_literal(high, hoisted);
+        }
+        _ => {}
+    }
+}
+
+fn is_literal(expr: &Expr) -> bool {
+    matches!(expr, Expr::Value(val) if !matches!(val.value, Value::Placeholder(_)))
+}
+
+/// Hoists `expr` if it is a number, string or boolean literal.
+fn hoist_literal(expr: &mut Expr, hoisted: &mut Vec<(String, Literal)>) {
+    let Expr::Value(val) = expr else {
+        return;
+    };
+    if !matches!(
+        val.value,
+        Value::Number(..)
+            | Value::SingleQuotedString(_)
+            | Value::DoubleQuotedString(_)
+            | Value::Boolean(_)
+    ) {
+        return;
+    }
+    let Ok(literal) = ExprPlanner::plan_literal(&val.value) else {
+        return;
+    };
+    let id = format!("?_{}", hoisted.len() + 1);
+    val.value = Value::Placeholder(id.clone());
+    hoisted.push((id, literal));
+}
@@ -2,6 +2,7 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 
 use regex::Regex;
+use sqlparser::ast::helpers::attached_token::AttachedToken;
 use sqlparser::ast::{
     self, ObjectName, ObjectNamePart, SetExpr, Statement, TableFactor, TableObject,
 };
@@ -9,10 +10,10 @@ use yachtsql_common::error::{Error, Result};
 use yachtsql_common::types::{DataType, StructField};
 use yachtsql_ir::{
     AlterColumnAction, AlterTableOp, Assignment, BinaryOp, ColumnDef, ConstraintType,
-    CteDefinition, DateTimeField, DclResourceType, ExportFormat, ExportOptions, Expr, FunctionArg,
-    FunctionBody, GapFillColumn, GapFillStrategy, JoinType, Literal, LogicalPlan, MergeClause,
-    PlanField, PlanSchema, ProcedureArg, ProcedureArgMode, RaiseLevel, SampleType,
-    SetOperationType, SortExpr, TableConstraint,
+    CteDefinition, DateTimeField, DclResourceType, ExplainFormat, ExportFormat, ExportOptions,
+    Expr, FunctionArg, FunctionBody, GapFillColumn, GapFillStrategy, IndexKind, JoinType, Literal,
+    LogicalPlan, MergeClause, PlanField, PlanSchema, ProcedureArg, ProcedureArgMode, RaiseLevel,
+    SampleType, ScalarFunction, SetOperationType, SortExpr, TableConstraint,
 };
 use yachtsql_storage::Schema;
 
@@ -81,6 +82,15 @@ impl<'a, C: CatalogProvider> Planner<'a, C> {
                 ..
             } => self.plan_drop(object_type, names, *if_exists, *cascade),
             Statement::Truncate { table_names, .. } => self.plan_truncate(table_names),
+            Statement::Analyze { table_name, .. } => Ok(LogicalPlan::Analyze {
+                table_name: object_name_to_raw_string(table_name),
+            }),
+            Statement::Explain {
+                analyze,
+                statement,
+                format,
+                ..
+            } => self.plan_explain(statement, *analyze, format.as_ref()),
             Statement::AlterTable {
                 name,
                 operations,
@@ -91,8 +101,16 @@ impl<'a, C: CatalogProvider> Planner<'a, C> {
                 schema_name,
                 if_not_exists,
                 or_replace,
+                options,
+                default_collate_spec,
                 ..
-            } => self.plan_create_schema(schema_name, *if_not_exists, *or_replace),
+            } => self.plan_create_schema(
+                schema_name,
+                *if_not_exists,
+                *or_replace,
+                options.as_deref().unwrap_or_default(),
+                default_collate_spec.as_ref(),
+            ),
             Statement::AlterSchema(alter_schema) => self.plan_alter_schema(alter_schema),
             Statement::UndropSchema {
                 if_not_exists,
@@ -116,20 +134,69 @@ impl<'a, C: CatalogProvider> Planner<'a, C> {
             Statement::AlterProcedure { .. } => Ok(LogicalPlan::Empty {
                 schema: PlanSchema::new(),
             }),
-            Statement::CreateSearchIndex { .. } => Ok(LogicalPlan::Empty {
-                schema: PlanSchema::new(),
-            }),
-            Statement::CreateVectorIndex { .. } => Ok(LogicalPlan::Empty {
-                schema: PlanSchema::new(),
-            }),
+            Statement::CreateSearchIndex {
+                or_replace,
+                if_not_exists,
+                name,
+                table_name,
+                columns,
+                all_columns,
+                options,
+            } => {
+                let columns = if *all_columns {
+                    vec!["ALL COLUMNS".to_string()]
+                } else {
+                    columns.iter().map(|c| c.value.clone()).collect()
+                };
+                self.plan_create_index(
+                    name,
+                    table_name,
+                    IndexKind::Search,
+                    columns,
+                    options,
+                    *or_replace,
+                    *if_not_exists,
+                )
+            }
+            Statement::CreateVectorIndex {
+                or_replace,
+                if_not_exists,
+                name,
+                table_name,
+                column,
+                options,
+                ..
+            } => self.plan_create_index(
+                name,
+                table_name,
+                IndexKind::Vector,
+                vec![column.value.clone()],
+                options,
+                *or_replace,
+                *if_not_exists,
+            ),
             Statement::CreateRowAccessPolicy { .. } => Ok(LogicalPlan::Empty {
                 schema: PlanSchema::new(),
             }),
-            Statement::DropSearchIndex { .. } => Ok(LogicalPlan::Empty {
-                schema: PlanSchema::new(),
+            Statement::DropSearchIndex {
+                if_exists,
+                name,
+                table_name,
+            } => Ok(LogicalPlan::DropIndex {
+                name: object_name_to_raw_string(name),
+                table_name: object_name_to_raw_string(table_name),
+                kind: IndexKind::Search,
+                if_exists: *if_exists,
             }),
-            Statement::DropVectorIndex { .. } => Ok(LogicalPlan::Empty {
-                schema: PlanSchema::new(),
+            Statement::DropVectorIndex {
+                if_exists,
+                name,
+                table_name,
+            } => Ok(LogicalPlan::DropIndex {
+                name: object_name_to_raw_string(name),
+                table_name: object_name_to_raw_string(table_name),
+                kind: IndexKind::Vector,
+                if_exists: *if_exists,
             }),
             Statement::DropRowAccessPolicy { .. } => Ok(LogicalPlan::Empty {
                 schema: PlanSchema::new(),
@@ -146,8 +213,16 @@ impl<'a, C: CatalogProvider> Planner<'a, C> {
                 query,
                 or_replace,
                 if_not_exists,
+                materialized,
                 ..
-            } => self.plan_create_view(name, columns, query, *or_replace, *if_not_exists),
+            } => self.plan_create_view(
+                name,
+                columns,
+                query,
+                *or_replace,
+                *if_not_exists,
+                *materialized,
+            ),
             Statement::CreateFunction(create) => self.plan_create_function(create),
             Statement::DropFunction {
                 func_desc,
@@ -613,6 +688,12 @@ impl<'a, C: CatalogProvider> Planner<'a, C> {
             };
         }
 
+        for (i, operator) in query.pipe_operators.iter().enumerate() {
+            plan = self
+                .plan_pipe_operator(plan, operator)
+                .map_err(|e| Self::pipe_operator_error(e, i + 1, operator))?;
+        }
+
         if let Some(ctes) = ctes {
             for cte in &ctes {
                 self.cte_schemas.borrow_mut().remove(&cte.name);
@@ -627,6 +708,438 @@ impl<'a, C: CatalogProvider> Planner<'a, C> {
         Ok(plan)
     }
 
+    fn plan_pipe_operator(
+        &self,
+        plan: LogicalPlan,
+        operator: &ast::PipeOperator,
+    ) -> Result<LogicalPlan> {
+        use ast::PipeOperator;
+
+        match operator {
+            PipeOperator::Where { expr } => {
+                let subquery_planner = |query: &ast::Query| self.plan_query(query);
+                let predicate = ExprPlanner::plan_expr_with_subquery(
+                    expr,
+                    plan.schema(),
+                    Some(&subquery_planner),
+                )?;
+                Ok(plan.filter(predicate))
+            }
+            PipeOperator::Select { exprs } => self.plan_projection(plan, exprs, &[]),
+            PipeOperator::Extend { exprs } => self.plan_pipe_extend(plan, exprs),
+            PipeOperator::Set { assignments } => self.plan_pipe_set(plan, assignments),
+            PipeOperator::Drop { columns } => {
+                let mut dropped = Vec::new();
+                for column in columns {
+                    dropped.push(Self::pipe_column_index(plan.schema(), &column.value, None)?);
+                }
+                let kept = plan
+                    .schema()
+                    .fields
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| !dropped.contains(i))
+                    .map(|(i, field)| (i, field.clone()))
+                    .collect();
+                Ok(Self::project_fields(plan, kept))
+            }
+            PipeOperator::Rename { mappings } => {
+                let mut fields: Vec<(usize, PlanField)> =
+                    plan.schema().fields.iter().cloned().enumerate().collect();
+                for mapping in mappings {
+                    let i = Self::pipe_column_index(plan.schema(), &mapping.ident.value, None)?;
+                    fields[i].1.name = mapping.alias.value.clone();
+                }
+                Ok(Self::project_fields(plan, fields))
+            }
+            PipeOperator::As { alias } => Ok(self.alias_table_plan(plan, Some(&alias.value))),
+            PipeOperator::Aggregate {
+                full_table_exprs,
+                group_by_expr,
+            } => self.plan_pipe_aggregate(plan, full_table_exprs, group_by_expr),
+            PipeOperator::OrderBy { exprs } => {
+                let order_by = ast::OrderBy {
+                    kind: ast::OrderByKind::Expressions(exprs.clone()),
+                    interpolate: None,
+                };
+                self.plan_order_by(plan, &order_by)
+            }
+            PipeOperator::Limit { expr, offset } => {
+                let limit = self.extract_limit_value(expr)?;
+                let offset = offset
+                    .as_ref()
+                    .map(|o| self.extract_limit_value(o))
+                    .transpose()?;
+                Ok(plan.limit(Some(limit), offset))
+            }
+            PipeOperator::Join(join) => {
+                let right = self.plan_table_factor(&join.relation, Some(plan.schema()))?;
+                self.plan_join(plan, right, &join.join_operator)
+            }
+            PipeOperator::Union {
+                set_quantifier,
+                queries,
+            } => {
+                self.plan_pipe_set_operation(plan, ast::SetOperator::Union, set_quantifier, queries)
+            }
+            PipeOperator::Intersect {
+                set_quantifier,
+                queries,
+            } => self.plan_pipe_set_operation(
+                plan,
+                ast::SetOperator::Intersect,
+                set_quantifier,
+                queries,
+            ),
+            PipeOperator::Except {
+                set_quantifier,
+                queries,
+            } => self.plan_pipe_set_operation(
+                plan,
+                ast::SetOperator::Except,
+                set_quantifier,
+                queries,
+            ),
+            PipeOperator::TableSample { sample } => self.sample_plan(plan, sample),
+            PipeOperator::Pivot {
+                aggregate_functions,
+                value_column,
+                value_source,
+                alias,
+            } => {
+                let ast::PivotValueSource::List(values) = value_source else {
+                    return Err(Error::unsupported(
+                        "PIVOT requires a constant list of IN values",
+                    ));
+                };
+                let pivot_column = match value_column.as_slice() {
+                    [column] => ast::Expr::Identifier(column.clone()),
+                    _ => ast::Expr::CompoundIdentifier(value_column.clone()),
+                };
+                let plan = self.plan_pivot(plan, aggregate_functions, &pivot_column, values)?;
+                Ok(self.alias_table_plan(plan, alias.as_ref().map(|a| a.value.as_str())))
+            }
+            PipeOperator::Unpivot {
+                value_column,
+                name_column,
+                unpivot_columns,
+                alias,
+            } => {
+                let columns: Vec<ast::ExprWithAlias> = unpivot_columns
+                    .iter()
+                    .map(|column| ast::ExprWithAlias {
+                        expr: ast::Expr::Identifier(column.clone()),
+                        alias: None,
+                    })
+                    .collect();
+                let value = ast::Expr::Identifier(value_column.clone());
+                let plan = self.plan_unpivot(plan, &value, name_column, &columns, false)?;
+                Ok(self.alias_table_plan(plan, alias.as_ref().map(|a| a.value.as_str())))
+            }
+            PipeOperator::Call { function, alias } => {
+                let plan = self.plan_pipe_call(plan, function)?;
+                Ok(self.alias_table_plan(plan, alias.as_ref().map(|a| a.value.as_str())))
+            }
+        }
+    }
+
+    /// Prefixes planning errors with the pipe stage they came from. Nested
+    /// pipe queries add their own stage, so the message reads outermost last.
+    fn pipe_operator_error(err: Error, position: usize, operator: &ast::PipeOperator) -> Error {
+        let with_stage =
+            |msg: String| format!("{} (in pipe operator {}: |> {})", msg, position, operator);
+        match err {
+            Error::ParseError(msg) => Error::ParseError(with_stage(msg)),
+            Error::InvalidQuery(msg) => Error::InvalidQuery(with_stage(msg)),
+            Error::TableNotFound(name) => Error::TableNotFound(with_stage(name)),
+            Error::FunctionNotFound(name) => Error::FunctionNotFound(with_stage(name)),
+            Error::ColumnNotFound(name) => Error::ColumnNotFound(with_stage(name)),
+            Error::SchemaMismatch(msg) => Error::SchemaMismatch(with_stage(msg)),
+            Error::UnsupportedFeature(msg) => Error::UnsupportedFeature(with_stage(msg)),
+            Error::Internal(msg) => Error::Internal(with_stage(msg)),
+            other => other,
+        }
+    }
+
+    fn pipe_column_index(schema: &PlanSchema, name: &str, table: Option<&str>) -> Result<usize> {
+        schema
+            .field_index_qualified(name, table)
+            .ok_or_else(|| Error::column_not_found(name))
+    }
+
+    /// Projects the given input columns, each under the field it is given.
+    fn project_fields(plan: LogicalPlan, fields: Vec<(usize, PlanField)>) -> LogicalPlan {
+        let input_fields = &plan.schema().fields;
+        let expressions = fields
+            .iter()
+            .map(|(i, _)| Expr::Column {
+                table: input_fields[*i].table.clone(),
+                name: input_fields[*i].name.clone(),
+                index: Some(*i),
+            })
+            .collect();
+        let schema = PlanSchema::from_fields(fields.into_iter().map(|(_, f)| f).collect());
+        plan.project(expressions, schema)
+    }
+
+    fn plan_pipe_extend(
+        &self,
+        plan: LogicalPlan,
+        exprs: &[ast::SelectItem],
+    ) -> Result<LogicalPlan> {
+        let mut items = vec![ast::SelectItem::Wildcard(
+            ast::WildcardAdditionalOptions::default(),
+        )];
+        items.extend(exprs.iter().cloned());
+        self.plan_projection(plan, &items, &[])
+    }
+
+    /// Plans `|> SET` as an `EXTEND` of the new values followed by a
+    /// projection that puts each one in place of the column it replaces.
+    fn plan_pipe_set(
+        &self,
+        plan: LogicalPlan,
+        assignments: &[ast::Assignment],
+    ) -> Result<LogicalPlan> {
+        let width = plan.schema().fields.len();
+        let mut targets = Vec::new();
+        let mut items = Vec::new();
+        for assignment in assignments {
+            let ast::AssignmentTarget::ColumnName(name) = &assignment.target else {
+                return Err(Error::unsupported("SET of a tuple of columns"));
+            };
+            let parts: Vec<&str> = name
+                .0
+                .iter()
+                .filter_map(|part| part.as_ident().map(|ident| ident.value.as_str()))
+                .collect();
+            let (table, column) = match parts.as_slice() {
+                [column] => (None, *column),
+                [table, column] => (Some(*table), *column),
+                _ => return Err(Error::column_not_found(name.to_string())),
+            };
+            let index = Self::pipe_column_index(plan.schema(), column, table)?;
+            if targets.contains(&index) {
+                return Err(Error::invalid_query(format!(
+                    "Column {} is assigned more than once",
+                    column
+                )));
+            }
+            targets.push(index);
+            items.push(ast::SelectItem::ExprWithAlias {
+                expr: assignment.value.clone(),
+                alias: ast::Ident::new(column),
+            });
+        }
+
+        let original = plan.schema().fields.clone();
+        let extended = self.plan_pipe_extend(plan, &items)?;
+        let fields = original
+            .into_iter()
+            .enumerate()
+            .map(|(i, field)| match targets.iter().position(|t| *t == i) {
+                Some(j) => {
+                    let mut field = field;
+                    field.data_type = extended.schema().fields[width + j].data_type.clone();
+                    (width + j, field)
+                }
+                None => (i, field),
+            })
+            .collect();
+        Ok(Self::project_fields(extended, fields))
+    }
+
+    /// Plans `|> AGGREGATE` as a grouped select whose output is the grouping
+    /// columns followed by the aggregates. Items marked `ASC` or `DESC` also
+    /// order the result.
+    fn plan_pipe_aggregate(
+        &self,
+        plan: LogicalPlan,
+        aggregates: &[ast::ExprWithAliasAndOrderBy],
+        group_by: &[ast::ExprWithAliasAndOrderBy],
+    ) -> Result<LogicalPlan> {
+        if aggregates.is_empty() && group_by.is_empty() {
+            return Err(Error::invalid_query(
+                "AGGREGATE requires an aggregate expression or a GROUP BY list",
+            ));
+        }
+        let items: Vec<&ast::ExprWithAliasAndOrderBy> = group_by.iter().chain(aggregates).collect();
+        let projection = items
+            .iter()
+            .map(|item| match &item.expr.alias {
+                Some(alias) => ast::SelectItem::ExprWithAlias {
+                    expr: item.expr.expr.clone(),
+                    alias: alias.clone(),
+                },
+                None => ast::SelectItem::UnnamedExpr(item.expr.expr.clone()),
+            })
+            .collect();
+        let select = ast::Select {
+            select_token: AttachedToken::empty(),
+            distinct: None,
+            top: None,
+            top_before_distinct: false,
+            projection,
+            exclude: None,
+            into: None,
+            from: vec![],
+            lateral_views: vec![],
+            prewhere: None,
+            selection: None,
+            group_by: ast::GroupByExpr::Expressions(
+                group_by.iter().map(|item| item.expr.expr.clone()).collect(),
+                vec![],
+            ),
+            cluster_by: vec![],
+            distribute_by: vec![],
+            sort_by: vec![],
+            having: None,
+            named_window: vec![],
+            window_before_qualify: false,
+            qualify: None,
+            value_table_mode: None,
+            connect_by: None,
+            flavor: ast::SelectFlavor::Standard,
+        };
+        let plan = self.plan_aggregate_with_order(plan, &select, None)?;
+
+        let sort_exprs: Vec<SortExpr> = items
+            .iter()
+            .enumerate()
+            .filter_map(|(i, item)| {
+                let asc = item.order_by.asc?;
+                let field = &plan.schema().fields[i];
+                Some(SortExpr {
+                    expr: Expr::Column {
+                        table: field.table.clone(),
+                        name: field.name.clone(),
+                        index: Some(i),
+                    },
+                    asc,
+                    nulls_first: item.order_by.nulls_first.unwrap_or(!asc),
+                })
+            })
+            .collect();
+        if sort_exprs.is_empty() {
+            Ok(plan)
+        } else {
+            Ok(plan.sort(sort_exprs))
+        }
+    }
+
+    fn plan_pipe_set_operation(
+        &self,
+        plan: LogicalPlan,
+        op: ast::SetOperator,
+        set_quantifier: &ast::SetQuantifier,
+        queries: &[ast::Query],
+    ) -> Result<LogicalPlan> {
+        let mut plan = plan;
+        for query in queries {
+            let right = self.plan_query(query)?;
+            if right.schema().fields.len() != plan.schema().fields.len() {
+                return Err(Error::invalid_query(format!(
+                    "{} input has {} columns but the pipe input has {}",
+                    op,
+                    right.schema().fields.len(),
+                    plan.schema().fields.len()
+                )));
+            }
+            plan = Self::set_operation(plan, right, &op, set_quantifier);
+        }
+        Ok(plan)
+    }
+
+    /// Plans `|> CALL f(args)` by binding the pipe input to the first
+    /// parameter of the SQL table function `f` as a CTE of that name, and the
+    /// remaining arguments to its other parameters.
+    fn plan_pipe_call(&self, input: LogicalPlan, function: &ast::Function) -> Result<LogicalPlan> {
+        let name = object_name_to_raw_string(&function.name);
+        let func_def = self
+            .catalog
+            .get_function(&name)
+            .ok_or_else(|| Error::function_not_found(&name))?;
+        let FunctionBody::SqlQuery(query_str) = &func_def.body else {
+            return Err(Error::invalid_query(format!(
+                "Function {} is not a SQL table function",
+                name
+            )));
+        };
+        let Some((table_param, params)) = func_def.parameters.split_first() else {
+            return Err(Error::invalid_query(format!(
+                "Table function {} has no parameter to receive the pipe input",
+                name
+            )));
+        };
+
+        let args = match &function.args {
+            ast::FunctionArguments::None => &[][..],
+            ast::FunctionArguments::List(list) => list.args.as_slice(),
+            ast::FunctionArguments::Subquery(_) => {
+                return Err(Error::unsupported("Subquery arguments to a table function"));
+            }
+        };
+        if args.len() != params.len() {
+            return Err(Error::invalid_query(format!(
+                "Table function {} takes {} arguments after the pipe input, got {}",
+                name,
+                params.len(),
+                args.len()
+            )));
+        }
+        let mut param_bindings: HashMap<String, String> = HashMap::new();
+        for (param, arg) in params.iter().zip(args) {
+            let ast::FunctionArg::Unnamed(ast::FunctionArgExpr::Expr(e)) = arg else {
+                return Err(Error::unsupported("Unsupported function argument type"));
+            };
+            param_bindings.insert(param.name.to_uppercase(), e.to_string());
+        }
+        let substituted_query = Self::substitute_query_params(query_str, &param_bindings);
+
+        let cte_name = table_param.name.to_uppercase();
+        let shadowed = self.cte_schemas.borrow_mut().insert(
+            cte_name.clone(),
+            self.rename_schema(input.schema(), &cte_name),
+        );
+        let body = parse_sql(&substituted_query).and_then(|parsed| match parsed.first() {
+            Some(Statement::Query(q)) => self.plan_query(q),
+            _ => Err(Error::invalid_query(
+                "Table function body must be a query".to_string(),
+            )),
+        });
+        match shadowed {
+            Some(schema) => self
+                .cte_schemas
+                .borrow_mut()
+                .insert(cte_name.clone(), schema),
+            None => self.cte_schemas.borrow_mut().remove(&cte_name),
+        };
+
+        Ok(LogicalPlan::WithCte {
+            ctes: vec![CteDefinition {
+                name: cte_name,
+                columns: None,
+                query: Box::new(input),
+                recursive: false,
+                materialized: None,
+            }],
+            body: Box::new(body?),
+        })
+    }
+
+    fn substitute_query_params(query: &str, bindings: &HashMap<String, String>) -> String {
+        let mut substituted_query = query.to_string();
+        for (param_name, value) in bindings {
+            let pattern_lower = format!(r"(?i)\b{}\b", regex::escape(&param_name.to_lowercase()));
+            if let Ok(re) = Regex::new(&pattern_lower) {
+                substituted_query = re
+                    .replace_all(&substituted_query, value.as_str())
+                    .to_string();
+            }
+        }
+        substituted_query
+    }
+
     fn plan_ctes(&self, with_clause: &ast::With) -> Result<Vec<CteDefinition>> {
         let mut ctes = Vec::new();
         for cte in &with_clause.cte_tables {
@@ -820,27 +1333,12 @@ impl<'a, C: CatalogProvider> Planner<'a, C> {
             } => {
                 let left_plan = self.plan_set_expr(left)?;
                 let right_plan = self.plan_set_expr(right)?;
-
-                let ir_op = match op {
-                    ast::SetOperator::Union => SetOperationType::Union,
-                    ast::SetOperator::Intersect => SetOperationType::Intersect,
-                    ast::SetOperator::Except | ast::SetOperator::Minus => SetOperationType::Except,
-                };
-
-                let all = matches!(
+                Ok(Self::set_operation(
+                    left_plan,
+                    right_plan,
+                    op,
                     set_quantifier,
-                    ast::SetQuantifier::All | ast::SetQuantifier::AllByName
-                );
-
-                let schema = left_plan.schema().clone();
-
-                Ok(LogicalPlan::SetOperation {
-                    left: Box::new(left_plan),
-                    right: Box::new(right_plan),
-                    op: ir_op,
-                    all,
-                    schema,
-                })
+                ))
             }
             SetExpr::Insert(stmt) => self.plan_statement(stmt),
             SetExpr::Update(stmt) => self.plan_statement(stmt),
@@ -853,12 +1351,43 @@ impl<'a, C: CatalogProvider> Planner<'a, C> {
         }
     }
 
+    fn set_operation(
+        left: LogicalPlan,
+        right: LogicalPlan,
+        op: &ast::SetOperator,
+        set_quantifier: &ast::SetQuantifier,
+    ) -> LogicalPlan {
+        let ir_op = match op {
+            ast::SetOperator::Union => SetOperationType::Union,
+            ast::SetOperator::Intersect => SetOperationType::Intersect,
+            ast::SetOperator::Except | ast::SetOperator::Minus => SetOperationType::Except,
+        };
+
+        let all = matches!(
+            set_quantifier,
+            ast::SetQuantifier::All | ast::SetQuantifier::AllByName
+        );
+
+        let schema = left.schema().clone();
+
+        LogicalPlan::SetOperation {
+            left: Box::new(left),
+            right: Box::new(right),
+            op: ir_op,
+            all,
+            schema,
+        }
+    }
+
     fn plan_select_with_order(
         &self,
         select: &ast::Select,
         order_by: Option<&ast::OrderBy>,
     ) -> Result<LogicalPlan> {
         let mut plan = self.plan_from(&select.from)?;
+        if select.flavor == ast::SelectFlavor::FromFirstNoSelect {
+            return Ok(plan);
+        }
 
         if let Some(ref selection) = select.selection {
             let subquery_planner = |query: &ast::Query| self.plan_query(query);
@@ -979,11 +1508,35 @@ impl<'a, C: CatalogProvider> Planner<'a, C> {
                 alias,
                 sample,
                 args,
+                version,
                 ..
             } => {
                 let table_name = object_name_to_raw_string(name);
                 let table_name_upper = table_name.to_uppercase();
 
+                let as_of = match version {
+                    Some(ast::TableVersion::ForSystemTimeAsOf(expr)) => {
+                        Some(ExprPlanner::plan_expr(expr, &PlanSchema::new())?)
+                    }
+                    Some(ast::TableVersion::Function(_)) => {
+                        return Err(Error::unsupported(format!(
+                            "Unsupported table version clause on {}",
+                            table_name
+                        )));
+                    }
+                    None => None,
+                };
+                if as_of.is_some()
+                    && (args.is_some()
+                        || self.cte_schemas.borrow().contains_key(&table_name_upper)
+                        || self.catalog.get_table_schema(&table_name).is_none())
+                {
+                    return Err(Error::invalid_query(format!(
+                        "FOR SYSTEM_TIME AS OF can only be used with a table, but {} is not a table",
+                        table_name
+                    )));
+                }
+
                 let base_plan = if let Some(tbl_args) = args {
                     if table_name_upper == "GAP_FILL" {
                         return self.plan_gap_fill(tbl_args, alias);
@@ -1066,18 +1619,8 @@ impl<'a, C: CatalogProvider> Planner<'a, C> {
                                     }
                                 }
 
-                                let mut substituted_query = query_str.clone();
-                                for (param_name, value) in &param_bindings {
-                                    let pattern_lower = format!(
-                                        r"(?i)\b{}\b",
-                                        regex::escape(&param_name.to_lowercase())
-                                    );
-                                    if let Ok(re) = Regex::new(&pattern_lower) {
-                                        substituted_query = re
-                                            .replace_all(&substituted_query, value.as_str())
-                                            .to_string();
-                                    }
-                                }
+                                let substituted_query =
+                                    Self::substitute_query_params(query_str, &param_bindings);
 
                                 let parsed = parse_sql(&substituted_query)?;
                                 let query_stmt = parsed.first().ok_or_else(|| {
@@ -1143,10 +1686,17 @@ impl<'a, C: CatalogProvider> Planner<'a, C> {
                         alias_name.or(Some(&table_name)),
                     );
 
-                    LogicalPlan::Scan {
-                        table_name,
-                        schema,
-                        projection: None,
+                    match as_of {
+                        Some(as_of) => LogicalPlan::TimeTravelScan {
+                            table_name,
+                            schema,
+                            as_of,
+                        },
+                        None => LogicalPlan::Scan {
+                            table_name,
+                            schema,
+                            projection: None,
+                        },
                     }
                 } else if let Some(view_def) = self.catalog.get_view(&table_name) {
                     let view_plan = crate::parse_and_plan(&view_def.query, self.catalog)?;
@@ -1334,6 +1884,43 @@ impl<'a, C: CatalogProvider> Planner<'a, C> {
                     schema,
                 })
             }
+            TableFactor::Pivot {
+                table,
+                aggregate_functions,
+                value_column,
+                value_source,
+                default_on_null,
+                alias,
+            } => {
+                if default_on_null.is_some() {
+                    return Err(Error::unsupported("PIVOT with DEFAULT ON NULL"));
+                }
+                let ast::PivotValueSource::List(values) = value_source else {
+                    return Err(Error::unsupported(
+                        "PIVOT requires a constant list of IN values",
+                    ));
+                };
+                let [pivot_column] = value_column.as_slice() else {
+                    return Err(Error::invalid_query("PIVOT takes a single pivot column"));
+                };
+                let input = self.plan_table_factor(table, left_schema)?;
+                let plan = self.plan_pivot(input, aggregate_functions, pivot_column, values)?;
+                Ok(self.alias_table_plan(plan, alias.as_ref().map(|a| a.name.value.as_str())))
+            }
+            TableFactor::Unpivot {
+                table,
+                value,
+                name,
+                columns,
+                null_inclusion,
+                alias,
+            } => {
+                let input = self.plan_table_factor(table, left_schema)?;
+                let include_nulls =
+                    matches!(null_inclusion, Some(ast::NullInclusion::IncludeNulls));
+                let plan = self.plan_unpivot(input, value, name, columns, include_nulls)?;
+                Ok(self.alias_table_plan(plan, alias.as_ref().map(|a| a.name.value.as_str())))
+            }
             _ => Err(Error::unsupported(format!(
                 "Unsupported table factor: {:?}",
                 factor
@@ -1341,34 +1928,366 @@ impl<'a, C: CatalogProvider> Planner<'a, C> {
         }
     }
 
+    fn alias_table_plan(&self, plan: LogicalPlan, alias: Option<&str>) -> LogicalPlan {
+        let Some(alias) = alias else {
+            return plan;
+        };
+        let schema = self.rename_schema(plan.schema(), alias);
+        LogicalPlan::Project {
+            expressions: Self::column_refs(plan.schema()),
+            input: Box::new(plan),
+            schema,
+        }
+    }
+
+    fn column_refs(schema: &PlanSchema) -> Vec<Expr> {
+        schema
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(i, f)| Expr::Column {
+                table: f.table.clone(),
+                name: f.name.clone(),
+                index: Some(i),
+            })
+            .collect()
+    }
+
+    /// Plans `PIVOT` as an aggregate grouped by every input column that is
+    /// neither the pivot column nor an aggregate argument. Each IN value
+    /// gets one column per aggregate, computed over the rows whose pivot
+    /// column equals that value.
+    fn plan_pivot(
+        &self,
+        input: LogicalPlan,
+        aggregates: &[ast::ExprWithAlias],
+        pivot_column: &ast::Expr,
+        values: &[ast::ExprWithAlias],
+    ) -> Result<LogicalPlan> {
+        if aggregates.len() > 1 && aggregates.iter().any(|agg| agg.alias.is_none()) {
+            return Err(Error::invalid_query(
+                "PIVOT with multiple aggregate functions requires an alias for each of them",
+            ));
+        }
+
+        let mut referenced = Vec::new();
+        for expr in aggregates
+            .iter()
+            .map(|agg| &agg.expr)
+            .chain(std::iter::once(pivot_column))
+        {
+            let _ = ast::visit_expressions(expr, |e| {
+                match e {
+                    ast::Expr::Identifier(ident) => referenced.push(ident.value.to_uppercase()),
+                    ast::Expr::CompoundIdentifier(parts) => {
+                        if let Some(last) = parts.last() {
+                            referenced.push(last.value.to_uppercase());
+                        }
+                    }
+                    _ => {}
+                }
+                std::ops::ControlFlow::<()>::Continue(())
+            });
+        }
+
+        let input_schema = input.schema().clone();
+        let mut group_by = Vec::new();
+        let mut fields = Vec::new();
+        for (i, field) in input_schema.fields.iter().enumerate() {
+            if !referenced.contains(&field.name.to_uppercase()) {
+                group_by.push(Expr::Column {
+                    table: field.table.clone(),
+                    name: field.name.clone(),
+                    index: Some(i),
+                });
+                fields.push(field.clone());
+            }
+        }
+
+        let mut aggregate_exprs = Vec::new();
+        for value in values {
+            let value_name = match &value.alias {
+                Some(alias) => alias.value.clone(),
+                None => Self::pivot_value_name(&value.expr)?,
+            };
+            let condition = match &value.expr {
+                ast::Expr::Value(ast::ValueWithSpan {
+                    value: ast::Value::Null,
+                    ..
+                }) => ast::Expr::IsNull(Box::new(pivot_column.clone())),
+                expr => ast::Expr::BinaryOp {
+                    left: Box::new(pivot_column.clone()),
+                    op: ast::BinaryOperator::Eq,
+                    right: Box::new(expr.clone()),
+                },
+            };
+            for agg in aggregates {
+                let data_type = self.infer_expr_type(
+                    &ExprPlanner::plan_expr(&agg.expr, &input_schema)?,
+                    &input_schema,
+                );
+                let filtered = Self::filter_aggregate_args(&agg.expr, &condition)?;
+                aggregate_exprs.push(ExprPlanner::plan_expr(&filtered, &input_schema)?);
+                let name = match &agg.alias {
+                    Some(alias) => format!("{}_{}", alias.value, value_name),
+                    None => value_name.clone(),
+                };
+                fields.push(PlanField::new(name, data_type));
+            }
+        }
+
+        Ok(LogicalPlan::Aggregate {
+            input: Box::new(input),
+            group_by,
+            aggregates: aggregate_exprs,
+            schema: PlanSchema::from_fields(fields),
+            grouping_sets: None,
+        })
+    }
+
+    /// Column name suffix for an IN value without an alias.
+    fn pivot_value_name(value: &ast::Expr) -> Result<String> {
+        let name = match value {
+            ast::Expr::Value(val) => match &val.value {
+                ast::Value::SingleQuotedString(s) | ast::Value::DoubleQuotedString(s) => s.clone(),
+                ast::Value::Number(n, _) => format!("_{}", n),
+                ast::Value::Boolean(b) => b.to_string(),
+                ast::Value::Null => "NULL".to_string(),
+                _ => String::new(),
+            },
+            ast::Expr::UnaryOp {
+                op: ast::UnaryOperator::Minus,
+                expr,
+            } => match expr.as_ref() {
+                ast::Expr::Value(ast::ValueWithSpan {
+                    value: ast::Value::Number(n, _),
+                    ..
+                }) => format!("minus_{}", n),
+                _ => String::new(),
+            },
+            _ => String::new(),
+        };
+        if name.is_empty() {
+            return Err(Error::invalid_query(format!(
+                "PIVOT value {} requires an alias",
+                value
+            )));
+        }
+        Ok(name.replace('.', "_"))
+    }
+
+    /// Rewrites `AGG(arg, ...)` as `AGG(CASE WHEN condition THEN arg END, ...)`,
+    /// and `COUNT(*)` as `COUNT(CASE WHEN condition THEN 1 END)`.
+    fn filter_aggregate_args(agg: &ast::Expr, condition: &ast::Expr) -> Result<ast::Expr> {
+        let mut agg = agg.clone();
+        let ast::Expr::Function(ast::Function {
+            args: ast::FunctionArguments::List(list),
+            ..
+        }) = &mut agg
+        else {
+            return Err(Error::invalid_query(format!(
+                "PIVOT expects an aggregate function call, got {}",
+                agg
+            )));
+        };
+        let Some(first) = list.args.first_mut() else {
+            return Err(Error::invalid_query(
+                "PIVOT aggregate functions require an argument",
+            ));
+        };
+        let arg = match first {
+            ast::FunctionArg::Unnamed(ast::FunctionArgExpr::Expr(expr)) => expr.clone(),
+            ast::FunctionArg::Unnamed(ast::FunctionArgExpr::Wildcard) => {
+                ast::Expr::value(ast::Value::Number("1".to_string(), false))
+            }
+            _ => {
+                return Err(Error::unsupported(format!(
+                    "Unsupported PIVOT aggregate argument: {}",
+                    first
+                )));
+            }
+        };
+        *first = ast::FunctionArg::Unnamed(ast::FunctionArgExpr::Expr(ast::Expr::Case {
+            case_token: AttachedToken::empty(),
+            end_token: AttachedToken::empty(),
+            operand: None,
+            conditions: vec![ast::CaseWhen {
+                condition: condition.clone(),
+                result: arg,
+            }],
+            else_result: None,
+        }));
+        Ok(agg)
+    }
+
+    /// Plans `UNPIVOT` as a UNION ALL with one branch per IN entry. Each
+    /// branch keeps the columns not being unpivoted and adds the entry's
+    /// values and its name; with EXCLUDE NULLS, the default, rows whose
+    /// values are all NULL are dropped.
+    fn plan_unpivot(
+        &self,
+        input: LogicalPlan,
+        value: &ast::Expr,
+        name: &ast::Ident,
+        columns: &[ast::ExprWithAlias],
+        include_nulls: bool,
+    ) -> Result<LogicalPlan> {
+        fn tuple_items(expr: &ast::Expr) -> Vec<&ast::Expr> {
+            match expr {
+                ast::Expr::Tuple(items) => items.iter().collect(),
+                ast::Expr::Nested(inner) => vec![inner.as_ref()],
+                other => vec![other],
+            }
+        }
+
+        let value_names: Vec<String> = tuple_items(value)
+            .into_iter()
+            .map(|item| match item {
+                ast::Expr::Identifier(ident) => Ok(ident.value.clone()),
+                other => Err(Error::invalid_query(format!(
+                    "UNPIVOT value column must be a name, got {}",
+                    other
+                ))),
+            })
+            .collect::<Result<_>>()?;
+
+        let input_schema = input.schema().clone();
+        let mut entries = Vec::new();
+        for column in columns {
+            let items = tuple_items(&column.expr);
+            if items.len() != value_names.len() {
+                return Err(Error::invalid_query(format!(
+                    "UNPIVOT entry {} must have {} columns",
+                    column.expr,
+                    value_names.len()
+                )));
+            }
+            let exprs = items
+                .iter()
+                .map(|item| ExprPlanner::plan_expr(item, &input_schema))
+                .collect::<Result<Vec<_>>>()?;
+            let label = match &column.alias {
+                Some(alias) => alias.value.clone(),
+                None => items
+                    .iter()
+                    .map(|item| self.expr_name(item))
+                    .collect::<Vec<_>>()
+                    .join("_"),
+            };
+            entries.push((exprs, label));
+        }
+        if entries.is_empty() {
+            return Err(Error::invalid_query("UNPIVOT requires at least one column"));
+        }
+
+        let unpivoted: Vec<usize> = entries
+            .iter()
+            .flat_map(|(exprs, _)| exprs)
+            .filter_map(|expr| match expr {
+                Expr::Column { index, .. } => *index,
+                _ => None,
+            })
+            .collect();
+        let kept: Vec<usize> = (0..input_schema.fields.len())
+            .filter(|i| !unpivoted.contains(i))
+            .collect();
+
+        let mut value_types = Vec::new();
+        for position in 0..value_names.len() {
+            let mut data_type = DataType::Unknown;
+            for (exprs, _) in &entries {
+                let entry_type = self.infer_expr_type(&exprs[position], &input_schema);
+                if data_type == DataType::Unknown {
+                    data_type = entry_type;
+                } else if entry_type != DataType::Unknown && entry_type != data_type {
+                    return Err(Error::invalid_query(format!(
+                        "UNPIVOT columns for {} must all have the same type, found {:?} and {:?}",
+                        value_names[position], data_type, entry_type
+                    )));
+                }
+            }
+            value_types.push(data_type);
+        }
+
+        let mut fields: Vec<PlanField> = kept
+            .iter()
+            .map(|&i| input_schema.fields[i].clone())
+            .collect();
+        for (value_name, data_type) in value_names.iter().zip(&value_types) {
+            fields.push(PlanField::new(value_name.clone(), data_type.clone()));
+        }
+        fields.push(PlanField::new(name.value.clone(), DataType::String));
+        let schema = PlanSchema::from_fields(fields);
+
+        let mut branches = Vec::new();
+        for (exprs, label) in entries {
+            let mut branch_input = input.clone();
+            if !include_nulls {
+                let predicate = exprs
+                    .iter()
+                    .map(|expr| Expr::IsNull {
+                        expr: Box::new(expr.clone()),
+                        negated: true,
+                    })
+                    .reduce(|left, right| Expr::BinaryOp {
+                        left: Box::new(left),
+                        op: BinaryOp::Or,
+                        right: Box::new(right),
+                    })
+                    .unwrap();
+                branch_input = LogicalPlan::Filter {
+                    input: Box::new(branch_input),
+                    predicate,
+                };
+            }
+            let mut expressions: Vec<Expr> = kept
+                .iter()
+                .map(|&i| Expr::Column {
+                    table: input_schema.fields[i].table.clone(),
+                    name: input_schema.fields[i].name.clone(),
+                    index: Some(i),
+                })
+                .collect();
+            expressions.extend(exprs);
+            expressions.push(Expr::Literal(Literal::String(label)));
+            branches.push(LogicalPlan::Project {
+                input: Box::new(branch_input),
+                expressions,
+                schema: schema.clone(),
+            });
+        }
+
+        let mut branches = branches.into_iter();
+        let first = branches.next().unwrap();
+        Ok(
+            branches.fold(first, |left, right| LogicalPlan::SetOperation {
+                left: Box::new(left),
+                right: Box::new(right),
+                op: SetOperationType::Union,
+                all: true,
+                schema: schema.clone(),
+            }),
+        )
+    }
+
     fn plan_join(
         &self,
         left: LogicalPlan,
         right: LogicalPlan,
         join_op: &ast::JoinOperator,
     ) -> Result<LogicalPlan> {
-        let (join_type, condition) = match join_op {
-            ast::JoinOperator::Inner(constraint) => (
-                JoinType::Inner,
-                self.extract_join_condition(constraint, &left, &right)?,
-            ),
-            ast::JoinOperator::Left(constraint) | ast::JoinOperator::LeftOuter(constraint) => (
-                JoinType::Left,
-                self.extract_join_condition(constraint, &left, &right)?,
-            ),
-            ast::JoinOperator::Right(constraint) | ast::JoinOperator::RightOuter(constraint) => (
-                JoinType::Right,
-                self.extract_join_condition(constraint, &left, &right)?,
-            ),
-            ast::JoinOperator::FullOuter(constraint) => (
-                JoinType::Full,
-                self.extract_join_condition(constraint, &left, &right)?,
-            ),
+        let (join_type, constraint) = match join_op {
+            ast::JoinOperator::Inner(constraint) | ast::JoinOperator::Join(constraint) => {
+                (JoinType::Inner, Some(constraint))
+            }
+            ast::JoinOperator::Left(constraint) | ast::JoinOperator::LeftOuter(constraint) => {
+                (JoinType::Left, Some(constraint))
+            }
+            ast::JoinOperator::Right(constraint) | ast::JoinOperator::RightOuter(constraint) => {
+                (JoinType::Right, Some(constraint))
+            }
+            ast::JoinOperator::FullOuter(constraint) => (JoinType::Full, Some(constraint)),
             ast::JoinOperator::CrossJoin(_) => (JoinType::Cross, None),
-            ast::JoinOperator::Join(constraint) => (
-                JoinType::Inner,
-                self.extract_join_condition(constraint, &left, &right)?,
-            ),
             _ => {
                 return Err(Error::unsupported(format!(
                     "Unsupported join type: {:?}",
@@ -1377,6 +2296,14 @@ impl<'a, C: CatalogProvider> Planner<'a, C> {
             }
         };
 
+        if let Some(ast::JoinConstraint::Using(columns)) = constraint {
+            return self.plan_using_join(left, right, join_type, columns);
+        }
+        let condition = match constraint {
+            Some(constraint) => self.extract_join_condition(constraint, &left, &right)?,
+            None => None,
+        };
+
         let schema = left.schema().clone().merge(right.schema().clone());
 
         Ok(LogicalPlan::Join {
@@ -1388,6 +2315,104 @@ impl<'a, C: CatalogProvider> Planner<'a, C> {
         })
     }
 
+    /// `JOIN ... USING (columns)`: an equi-join on the named columns whose
+    /// output lists each of them once, ahead of the remaining columns of
+    /// both inputs. The merged column of a FULL join takes whichever side
+    /// matched.
+    fn plan_using_join(
+        &self,
+        left: LogicalPlan,
+        right: LogicalPlan,
+        join_type: JoinType,
+        columns: &[ObjectName],
+    ) -> Result<LogicalPlan> {
+        let left_schema = left.schema().clone();
+        let right_schema = right.schema().clone();
+        let left_len = left_schema.fields.len();
+        let column = |schema: &PlanSchema, idx: usize, offset: usize| Expr::Column {
+            table: schema.fields[idx].table.clone(),
+            name: schema.fields[idx].name.clone(),
+            index: Some(idx + offset),
+        };
+
+        let mut keys = Vec::with_capacity(columns.len());
+        for name in columns {
+            let name = object_name_to_raw_string(name);
+            let find = |schema: &PlanSchema, side: &str| {
+                schema.field_index(&name).ok_or_else(|| {
+                    Error::invalid_query(format!(
+                        "Column {} in USING clause not found on {} side of join",
+                        name, side
+                    ))
+                })
+            };
+            keys.push((find(&left_schema, "left")?, find(&right_schema, "right")?));
+        }
+
+        let condition = keys
+            .iter()
+            .map(|&(l, r)| Expr::BinaryOp {
+                left: Box::new(column(&left_schema, l, 0)),
+                op: BinaryOp::Eq,
+                right: Box::new(column(&right_schema, r, left_len)),
+            })
+            .reduce(|acc, eq| Expr::BinaryOp {
+                left: Box::new(acc),
+                op: BinaryOp::And,
+                right: Box::new(eq),
+            });
+
+        let mut expressions = Vec::new();
+        let mut fields = Vec::new();
+        for &(l, r) in &keys {
+            let (expr, field) = match join_type {
+                JoinType::Right => (
+                    column(&right_schema, r, left_len),
+                    right_schema.fields[r].clone(),
+                ),
+                JoinType::Full => (
+                    Expr::ScalarFunction {
+                        name: ScalarFunction::Coalesce,
+                        args: vec![
+                            column(&left_schema, l, 0),
+                            column(&right_schema, r, left_len),
+                        ],
+                    },
+                    PlanField {
+                        nullable: true,
+                        table: None,
+                        ..left_schema.fields[l].clone()
+                    },
+                ),
+                _ => (column(&left_schema, l, 0), left_schema.fields[l].clone()),
+            };
+            expressions.push(expr);
+            fields.push(field);
+        }
+        for idx in (0..left_len).filter(|idx| keys.iter().all(|&(l, _)| l != *idx)) {
+            expressions.push(column(&left_schema, idx, 0));
+            fields.push(left_schema.fields[idx].clone());
+        }
+        for idx in (0..right_schema.fields.len()).filter(|idx| keys.iter().all(|&(_, r)| r != *idx))
+        {
+            expressions.push(column(&right_schema, idx, left_len));
+            fields.push(right_schema.fields[idx].clone());
+        }
+
+        let schema = left_schema.merge(right_schema);
+        Ok(LogicalPlan::Project {
+            input: Box::new(LogicalPlan::Join {
+                left: Box::new(left),
+                right: Box::new(right),
+                join_type,
+                condition,
+                schema,
+            }),
+            expressions,
+            schema: PlanSchema::from_fields(fields),
+        })
+    }
+
     fn extract_join_condition(
         &self,
         constraint: &ast::JoinConstraint,
@@ -3873,6 +4898,11 @@ impl<'a, C: CatalogProvider> Planner<'a, C> {
 
     fn plan_create_table(&self, create: &ast::CreateTable) -> Result<LogicalPlan> {
         let table_name = object_name_to_raw_string(&create.name);
+        let table_name = if create.temporary && !table_name.contains('.') {
+            format!("_SESSION.{}", table_name)
+        } else {
+            table_name
+        };
         let empty_schema = PlanSchema::new();
 
         let default_collation = match &create.table_options {
@@ -3949,15 +4979,69 @@ impl<'a, C: CatalogProvider> Planner<'a, C> {
             })
             .collect();
 
+        let mut constraints = Vec::new();
+        for col in &create.columns {
+            for option in &col.options {
+                let constraint_type = match &option.option {
+                    ast::ColumnOption::Unique { is_primary, .. } => {
+                        let columns = vec![col.name.value.clone()];
+                        if *is_primary {
+                            ConstraintType::PrimaryKey { columns }
+                        } else {
+                            ConstraintType::Unique { columns }
+                        }
+                    }
+                    ast::ColumnOption::ForeignKey {
+                        foreign_table,
+                        referred_columns,
+                        ..
+                    } => ConstraintType::ForeignKey {
+                        columns: vec![col.name.value.clone()],
+                        references_table: foreign_table.to_string(),
+                        references_columns: referred_columns
+                            .iter()
+                            .map(|c| c.value.clone())
+                            .collect(),
+                    },
+                    ast::ColumnOption::Check(expr) => ConstraintType::Check {
+                        expr: ExprPlanner::plan_expr(expr, &empty_schema)?,
+                    },
+                    _ => continue,
+                };
+                constraints.push(TableConstraint {
+                    name: option.name.as_ref().map(|n| n.value.clone()),
+                    constraint_type,
+                });
+            }
+        }
+        for constraint in &create.constraints {
+            constraints.push(self.plan_table_constraint(constraint)?);
+        }
+
         let query = if let Some(query_box) = &create.query {
             Some(Box::new(self.plan_query(query_box)?))
         } else if let Some(clone_source) = &create.clone {
             let source_name = object_name_to_raw_string(clone_source);
-            Some(Box::new(LogicalPlan::Scan {
-                table_name: source_name,
-                schema: PlanSchema::new(),
-                projection: None,
-            }))
+            match &create.version {
+                Some(ast::TableVersion::ForSystemTimeAsOf(expr)) => {
+                    Some(Box::new(LogicalPlan::TimeTravelScan {
+                        table_name: source_name,
+                        schema: PlanSchema::new(),
+                        as_of: ExprPlanner::plan_expr(expr, &PlanSchema::new())?,
+                    }))
+                }
+                Some(ast::TableVersion::Function(_)) => {
+                    return Err(Error::unsupported(format!(
+                        "Unsupported table version clause on {}",
+                        source_name
+                    )));
+                }
+                None => Some(Box::new(LogicalPlan::Scan {
+                    table_name: source_name,
+                    schema: PlanSchema::new(),
+                    projection: None,
+                })),
+            }
         } else if let Some(copy_source) = &create.copy {
             let source_name = object_name_to_raw_string(copy_source);
             Some(Box::new(LogicalPlan::Scan {
@@ -3969,9 +5053,24 @@ impl<'a, C: CatalogProvider> Planner<'a, C> {
             None
         };
 
+        let options = match &create.table_options {
+            ast::CreateTableOptions::Options(opts) => opts
+                .iter()
+                .filter_map(|opt| match opt {
+                    ast::SqlOption::KeyValue { key, value } => {
+                        Some((key.value.clone(), value.to_string()))
+                    }
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+
         Ok(LogicalPlan::CreateTable {
             table_name,
             columns,
+            constraints,
+            options,
             if_not_exists: create.if_not_exists,
             or_replace: create.or_replace,
             query,
@@ -4044,24 +5143,98 @@ impl<'a, C: CatalogProvider> Planner<'a, C> {
         Ok(LogicalPlan::Truncate { table_name })
     }
 
+    fn plan_explain(
+        &self,
+        statement: &Statement,
+        analyze: bool,
+        format: Option<&ast::AnalyzeFormatKind>,
+    ) -> Result<LogicalPlan> {
+        let format = match format {
+            None => ExplainFormat::Text,
+            Some(
+                ast::AnalyzeFormatKind::Keyword(format)
+                | ast::AnalyzeFormatKind::Assignment(format),
+            ) => match format {
+                ast::AnalyzeFormat::TEXT => ExplainFormat::Text,
+                ast::AnalyzeFormat::JSON => ExplainFormat::Json,
+                other => {
+                    return Err(Error::unsupported(format!("EXPLAIN FORMAT {}", other)));
+                }
+            },
+        };
+        let input = self.plan_statement(statement)?;
+        Ok(LogicalPlan::Explain {
+            input: Box::new(input),
+            analyze,
+            format,
+            schema: PlanSchema::from_fields(vec![PlanField::new("plan", DataType::String)]),
+        })
+    }
+
     fn plan_create_schema(
         &self,
         schema_name: &ast::SchemaName,
         if_not_exists: bool,
         or_replace: bool,
+        options: &[ast::SqlOption],
+        default_collate: Option<&ast::Expr>,
     ) -> Result<LogicalPlan> {
         let name = match schema_name {
             ast::SchemaName::Simple(name) => object_name_to_raw_string(name),
             ast::SchemaName::UnnamedAuthorization(auth) => auth.value.clone(),
             ast::SchemaName::NamedAuthorization(name, _) => object_name_to_raw_string(name),
         };
+        let mut options = self.plan_sql_options(options);
+        if let Some(collate) = default_collate {
+            options.push((
+                "default_collate".to_string(),
+                self.extract_sql_option_value(collate),
+            ));
+        }
         Ok(LogicalPlan::CreateSchema {
             name,
             if_not_exists,
             or_replace,
+            options,
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn plan_create_index(
+        &self,
+        name: &ast::ObjectName,
+        table_name: &ast::ObjectName,
+        kind: IndexKind,
+        columns: Vec<String>,
+        options: &[ast::SqlOption],
+        or_replace: bool,
+        if_not_exists: bool,
+    ) -> Result<LogicalPlan> {
+        Ok(LogicalPlan::CreateIndex {
+            name: object_name_to_raw_string(name),
+            table_name: object_name_to_raw_string(table_name),
+            kind,
+            columns,
+            options: self.plan_sql_options(options),
+            or_replace,
+            if_not_exists,
         })
     }
 
+    /// `key = value` options with string and number values unquoted, as
+    /// `ALTER SCHEMA SET OPTIONS` stores them.
+    fn plan_sql_options(&self, options: &[ast::SqlOption]) -> Vec<(String, String)> {
+        options
+            .iter()
+            .filter_map(|opt| match opt {
+                ast::SqlOption::KeyValue { key, value } => {
+                    Some((key.value.clone(), self.extract_sql_option_value(value)))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
     fn plan_alter_schema(&self, alter_schema: &ast::AlterSchema) -> Result<LogicalPlan> {
         let name = object_name_to_raw_string(&alter_schema.name);
         let mut options = Vec::new();
@@ -4261,6 +5434,7 @@ impl<'a, C: CatalogProvider> Planner<'a, C> {
             if_not_exists: create.if_not_exists,
             is_temp: create.temporary,
             is_aggregate: create.aggregate,
+            options: self.plan_sql_options(create.options.as_deref().unwrap_or_default()),
         })
     }
 
@@ -4511,6 +5685,7 @@ impl<'a, C: CatalogProvider> Planner<'a, C> {
         query: &ast::Query,
         or_replace: bool,
         if_not_exists: bool,
+        materialized: bool,
     ) -> Result<LogicalPlan> {
         let view_name = object_name_to_raw_string(name);
         let query_sql = query.to_string();
@@ -4524,6 +5699,7 @@ impl<'a, C: CatalogProvider> Planner<'a, C> {
             column_aliases,
             or_replace,
             if_not_exists,
+            materialized,
         })
     }
 
@@ -6264,13 +7440,21 @@ impl<'a, C: CatalogProvider> Planner<'a, C> {
         plan: LogicalPlan,
         sample: &Option<sqlparser::ast::TableSampleKind>,
     ) -> Result<LogicalPlan> {
-        use sqlparser::ast::{TableSampleKind, TableSampleUnit};
+        use sqlparser::ast::TableSampleKind;
 
-        let sample_spec = match sample {
-            Some(TableSampleKind::BeforeTableAlias(s)) => s,
-            Some(TableSampleKind::AfterTableAlias(s)) => s,
-            None => return Ok(plan),
-        };
+        match sample {
+            Some(TableSampleKind::BeforeTableAlias(s)) => self.sample_plan(plan, s),
+            Some(TableSampleKind::AfterTableAlias(s)) => self.sample_plan(plan, s),
+            None => Ok(plan),
+        }
+    }
+
+    fn sample_plan(
+        &self,
+        plan: LogicalPlan,
+        sample_spec: &sqlparser::ast::TableSample,
+    ) -> Result<LogicalPlan> {
+        use sqlparser::ast::TableSampleUnit;
 
         let quantity = match &sample_spec.quantity {
             Some(q) => q,
@@ -133,6 +133,22 @@ impl NullBitmap {
     pub fn words(&self) -> &[u64] {
         &self.data
     }
+
+    pub fn extend(&mut self, other: &NullBitmap) {
+        for index in 0..other.len {
+            self.push(other.is_null(index));
+        }
+    }
+
+    pub fn gather(&self, indices: &[usize]) -> Self {
+        let mut bitmap = Self::new_valid(indices.len());
+        for (i, &index) in indices.iter().enumerate() {
+            if self.is_null(index) {
+                bitmap.set_null(i);
+            }
+        }
+        bitmap
+    }
 }
 
 impl Default for NullBitmap {
@@ -141,6 +157,73 @@ impl Default for NullBitmap {
     }
 }
 
+/// Rows deleted from a table segment. Indices past the last marked row read
+/// as live, so the bitmap does not grow as rows are appended.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct DeleteBitmap {
+    data: Vec<u64>,
+    count: usize,
+}
+
+impl DeleteBitmap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn is_deleted(&self, index: usize) -> bool {
+        self.data
+            .get(index / 64)
+            .is_some_and(|word| (word >> (index % 64)) & 1 == 1)
+    }
+
+    /// Marks `index` deleted, returning whether it was live before.
+    pub fn delete(&mut self, index: usize) -> bool {
+        if self.is_deleted(index) {
+            return false;
+        }
+        let word = index / 64;
+        if word >= self.data.len() {
+            self.data.resize(word + 1, 0);
+        }
+        self.data[word] |= 1 << (index % 64);
+        self.count += 1;
+        true
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Position of the `n`-th index below `len` that is not deleted, found
+    /// by counting the live bits of whole words before scanning one.
+    pub fn nth_live(&self, mut n: usize, len: usize) -> Option<usize> {
+        for (word_idx, start) in (0..len).step_by(64).enumerate() {
+            let bits = (len - start).min(64);
+            let mask = if bits == 64 {
+                u64::MAX
+            } else {
+                (1 << bits) - 1
+            };
+            let mut live = !self.data.get(word_idx).copied().unwrap_or(0) & mask;
+            let count = live.count_ones() as usize;
+            if n >= count {
+                n -= count;
+                continue;
+            }
+            for _ in 0..n {
+                live &= live - 1;
+            }
+            return Some(start + live.trailing_zeros() as usize);
+        }
+        None
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -206,6 +289,22 @@ mod tests {
         assert_eq!(bitmap.count_valid(), 66);
     }
 
+    #[tokio::test]
+    async fn test_gather() {
+        let mut bitmap = NullBitmap::new();
+        for i in 0..130 {
+            bitmap.push(i % 5 == 0);
+        }
+        let gathered = bitmap.gather(&[0, 1, 64, 65, 129, 125]);
+        assert_eq!(gathered.len(), 6);
+        assert!(gathered.is_null(0));
+        assert!(gathered.is_valid(1));
+        assert!(gathered.is_valid(2));
+        assert!(gathered.is_null(3));
+        assert!(gathered.is_valid(4));
+        assert!(gathered.is_null(5));
+    }
+
     #[tokio::test]
     async fn test_across_word_boundary() {
         let mut bitmap = NullBitmap::new();
@@ -217,4 +316,37 @@ mod tests {
             assert_eq!(bitmap.is_null(i), i % 2 == 0);
         }
     }
+
+    #[tokio::test]
+    async fn test_extend() {
+        let mut bitmap = NullBitmap::new();
+        for i in 0..70 {
+            bitmap.push(i % 4 == 0);
+        }
+        let mut other = NullBitmap::new();
+        for i in 0..70 {
+            other.push(i % 3 == 0);
+        }
+        bitmap.extend(&other);
+        assert_eq!(bitmap.len(), 140);
+        for i in 0..70 {
+            assert_eq!(bitmap.is_null(i), i % 4 == 0);
+            assert_eq!(bitmap.is_null(70 + i), i % 3 == 0);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delete_bitmap() {
+        let mut deleted = DeleteBitmap::new();
+        assert!(deleted.is_empty());
+        assert!(!deleted.is_deleted(500));
+        assert!(deleted.delete(3));
+        assert!(deleted.delete(130));
+        assert!(!deleted.delete(3));
+        assert_eq!(deleted.count(), 2);
+        assert!(deleted.is_deleted(3));
+        assert!(deleted.is_deleted(130));
+        assert!(!deleted.is_deleted(4));
+        assert!(!deleted.is_deleted(10_000));
+    }
 }
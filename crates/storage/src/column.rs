@@ -319,6 +319,44 @@ impl Column {
         self.len() - self.count_null()
     }
 
+    /// Approximate heap bytes held by the column. Nested values are counted
+    /// at their inline size only.
+    pub fn memory_size(&self) -> usize {
+        fn slots<T>(data: &[T]) -> usize {
+            std::mem::size_of_val(data)
+        }
+        let (data, nulls) = match self {
+            Column::Bool { data, nulls } => (slots(data), nulls),
+            Column::Int64 { data, nulls } => (slots(data.as_slice()), nulls),
+            Column::Float64 { data, nulls } => (slots(data.as_slice()), nulls),
+            Column::Numeric { data, nulls } => (slots(data), nulls),
+            Column::String { data, nulls } | Column::Geography { data, nulls } => (
+                slots(data) + data.iter().map(String::len).sum::<usize>(),
+                nulls,
+            ),
+            Column::Bytes { data, nulls } => (
+                slots(data) + data.iter().map(Vec::len).sum::<usize>(),
+                nulls,
+            ),
+            Column::Date { data, nulls } => (slots(data), nulls),
+            Column::Time { data, nulls } => (slots(data), nulls),
+            Column::DateTime { data, nulls } => (slots(data), nulls),
+            Column::Timestamp { data, nulls } => (slots(data), nulls),
+            Column::Json { data, nulls } => (slots(data), nulls),
+            Column::Array { data, nulls, .. } => (
+                slots(data) + data.iter().map(|v| slots(v.as_slice())).sum::<usize>(),
+                nulls,
+            ),
+            Column::Struct { data, nulls, .. } => (
+                slots(data) + data.iter().map(|v| slots(v.as_slice())).sum::<usize>(),
+                nulls,
+            ),
+            Column::Interval { data, nulls } => (slots(data), nulls),
+            Column::Range { data, nulls, .. } => (slots(data), nulls),
+        };
+        data + slots(nulls.words())
+    }
+
     pub fn sum(&self) -> Option<f64> {
         match self {
             Column::Int64 { data, nulls } => {
@@ -1180,6 +1218,294 @@ impl Column {
         }
     }
 
+    /// Builds a new column from the rows at `indices`, in that order.
+    pub fn gather(&self, indices: &[usize]) -> Column {
+        match self {
+            Column::Bool { data, nulls } => Column::Bool {
+                data: gather_values(data, indices),
+                nulls: nulls.gather(indices),
+            },
+            Column::Int64 { data, nulls } => Column::Int64 {
+                data: AVec::from_iter(64, indices.iter().map(|&i| data[i])),
+                nulls: nulls.gather(indices),
+            },
+            Column::Float64 { data, nulls } => Column::Float64 {
+                data: AVec::from_iter(64, indices.iter().map(|&i| data[i])),
+                nulls: nulls.gather(indices),
+            },
+            Column::Numeric { data, nulls } => Column::Numeric {
+                data: gather_values(data, indices),
+                nulls: nulls.gather(indices),
+            },
+            Column::String { data, nulls } => Column::String {
+                data: gather_values(data, indices),
+                nulls: nulls.gather(indices),
+            },
+            Column::Bytes { data, nulls } => Column::Bytes {
+                data: gather_values(data, indices),
+                nulls: nulls.gather(indices),
+            },
+            Column::Date { data, nulls } => Column::Date {
+                data: gather_values(data, indices),
+                nulls: nulls.gather(indices),
+            },
+            Column::Time { data, nulls } => Column::Time {
+                data: gather_values(data, indices),
+                nulls: nulls.gather(indices),
+            },
+            Column::DateTime { data, nulls } => Column::DateTime {
+                data: gather_values(data, indices),
+                nulls: nulls.gather(indices),
+            },
+            Column::Timestamp { data, nulls } => Column::Timestamp {
+                data: gather_values(data, indices),
+                nulls: nulls.gather(indices),
+            },
+            Column::Json { data, nulls } => Column::Json {
+                data: gather_values(data, indices),
+                nulls: nulls.gather(indices),
+            },
+            Column::Array {
+                data,
+                nulls,
+                element_type,
+            } => Column::Array {
+                data: gather_values(data, indices),
+                nulls: nulls.gather(indices),
+                element_type: element_type.clone(),
+            },
+            Column::Struct {
+                data,
+                nulls,
+                fields,
+            } => Column::Struct {
+                data: gather_values(data, indices),
+                nulls: nulls.gather(indices),
+                fields: fields.clone(),
+            },
+            Column::Geography { data, nulls } => Column::Geography {
+                data: gather_values(data, indices),
+                nulls: nulls.gather(indices),
+            },
+            Column::Interval { data, nulls } => Column::Interval {
+                data: gather_values(data, indices),
+                nulls: nulls.gather(indices),
+            },
+            Column::Range {
+                data,
+                nulls,
+                element_type,
+            } => Column::Range {
+                data: gather_values(data, indices),
+                nulls: nulls.gather(indices),
+                element_type: element_type.clone(),
+            },
+        }
+    }
+
+    /// Appends every row of `other`. Columns of another layout go through
+    /// `push`, so the usual coercions apply.
+    pub fn append(&mut self, other: &Column) -> Result<()> {
+        if !other.same_layout(self) {
+            for index in 0..other.len() {
+                self.push(other.get_value(index))?;
+            }
+            return Ok(());
+        }
+        match (self, other) {
+            (
+                Column::Bool { data, nulls },
+                Column::Bool {
+                    data: other_data,
+                    nulls: other_nulls,
+                },
+            ) => {
+                data.extend_from_slice(other_data);
+                nulls.extend(other_nulls);
+            }
+            (
+                Column::Int64 { data, nulls },
+                Column::Int64 {
+                    data: other_data,
+                    nulls: other_nulls,
+                },
+            ) => {
+                data.extend_from_slice(other_data);
+                nulls.extend(other_nulls);
+            }
+            (
+                Column::Float64 { data, nulls },
+                Column::Float64 {
+                    data: other_data,
+                    nulls: other_nulls,
+                },
+            ) => {
+                data.extend_from_slice(other_data);
+                nulls.extend(other_nulls);
+            }
+            (
+                Column::Numeric { data, nulls },
+                Column::Numeric {
+                    data: other_data,
+                    nulls: other_nulls,
+                },
+            ) => {
+                data.extend_from_slice(other_data);
+                nulls.extend(other_nulls);
+            }
+            (
+                Column::String { data, nulls },
+                Column::String {
+                    data: other_data,
+                    nulls: other_nulls,
+                },
+            ) => {
+                data.extend_from_slice(other_data);
+                nulls.extend(other_nulls);
+            }
+            (
+                Column::Bytes { data, nulls },
+                Column::Bytes {
+                    data: other_data,
+                    nulls: other_nulls,
+                },
+            ) => {
+                data.extend_from_slice(other_data);
+                nulls.extend(other_nulls);
+            }
+            (
+                Column::Date { data, nulls },
+                Column::Date {
+                    data: other_data,
+                    nulls: other_nulls,
+                },
+            ) => {
+                data.extend_from_slice(other_data);
+                nulls.extend(other_nulls);
+            }
+            (
+                Column::Time { data, nulls },
+                Column::Time {
+                    data: other_data,
+                    nulls: other_nulls,
+                },
+            ) => {
+                data.extend_from_slice(other_data);
+                nulls.extend(other_nulls);
+            }
+            (
+                Column::DateTime { data, nulls },
+                Column::DateTime {
+                    data: other_data,
+                    nulls: other_nulls,
+                },
+            ) => {
+                data.extend_from_slice(other_data);
+                nulls.extend(other_nulls);
+            }
+            (
+                Column::Timestamp { data, nulls },
+                Column::Timestamp {
+                    data: other_data,
+                    nulls: other_nulls,
+                },
+            ) => {
+                data.extend_from_slice(other_data);
+                nulls.extend(other_nulls);
+            }
+            (
+                Column::Json { data, nulls },
+                Column::Json {
+                    data: other_data,
+                    nulls: other_nulls,
+                },
+            ) => {
+                data.extend_from_slice(other_data);
+                nulls.extend(other_nulls);
+            }
+            (
+                Column::Array { data, nulls, .. },
+                Column::Array {
+                    data: other_data,
+                    nulls: other_nulls,
+                    ..
+                },
+            ) => {
+                data.extend_from_slice(other_data);
+                nulls.extend(other_nulls);
+            }
+            (
+                Column::Struct { data, nulls, .. },
+                Column::Struct {
+                    data: other_data,
+                    nulls: other_nulls,
+                    ..
+                },
+            ) => {
+                data.extend_from_slice(other_data);
+                nulls.extend(other_nulls);
+            }
+            (
+                Column::Geography { data, nulls },
+                Column::Geography {
+                    data: other_data,
+                    nulls: other_nulls,
+                },
+            ) => {
+                data.extend_from_slice(other_data);
+                nulls.extend(other_nulls);
+            }
+            (
+                Column::Interval { data, nulls },
+                Column::Interval {
+                    data: other_data,
+                    nulls: other_nulls,
+                },
+            ) => {
+                data.extend_from_slice(other_data);
+                nulls.extend(other_nulls);
+            }
+            (
+                Column::Range { data, nulls, .. },
+                Column::Range {
+                    data: other_data,
+                    nulls: other_nulls,
+                    ..
+                },
+            ) => {
+                data.extend_from_slice(other_data);
+                nulls.extend(other_nulls);
+            }
+            _ => unreachable!("same_layout guarantees matching variants"),
+        }
+        Ok(())
+    }
+
+    /// Whether values of `self` can be moved into `other` without the
+    /// coercions `push` would apply.
+    pub fn same_layout(&self, other: &Column) -> bool {
+        match (self, other) {
+            (
+                Column::Array {
+                    element_type: a, ..
+                },
+                Column::Array {
+                    element_type: b, ..
+                },
+            )
+            | (
+                Column::Range {
+                    element_type: a, ..
+                },
+                Column::Range {
+                    element_type: b, ..
+                },
+            ) => a == b,
+            (Column::Struct { fields: a, .. }, Column::Struct { fields: b, .. }) => a == b,
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+
     pub fn clear(&mut self) {
         match self {
             Column::Bool { data, nulls } => {
@@ -1249,3 +1575,7 @@ impl Column {
         }
     }
 }
+
+fn gather_values<T: Clone>(data: &[T], indices: &[usize]) -> Vec<T> {
+    indices.iter().map(|&i| data[i].clone()).collect()
+}
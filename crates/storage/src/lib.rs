@@ -2,10 +2,12 @@ mod bitmap;
 mod column;
 mod record;
 mod schema;
+mod statistics;
 mod table;
 
-pub use bitmap::NullBitmap;
+pub use bitmap::{DeleteBitmap, NullBitmap};
 pub use column::Column;
 pub use record::Record;
 pub use schema::{Field, FieldMode, Schema};
-pub use table::{Table, TableSchemaOps};
+pub use statistics::{ColumnStatistics, TableStatistics};
+pub use table::{SEGMENT_ROWS, Segment, Table, TableDelta, TableSchemaOps};
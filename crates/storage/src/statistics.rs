@@ -0,0 +1,113 @@
+use std::collections::HashSet;
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use yachtsql_common::types::Value;
+
+use crate::Table;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColumnStatistics {
+    pub distinct_count: u64,
+    pub null_fraction: f64,
+    pub min: Option<Value>,
+    pub max: Option<Value>,
+}
+
+/// Row count and per-column distribution of a table at the time it was
+/// analyzed. Columns are keyed by upper-cased name so that statistics
+/// survive columns being added or reordered.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableStatistics {
+    pub row_count: u64,
+    pub columns: IndexMap<String, ColumnStatistics>,
+}
+
+impl TableStatistics {
+    pub fn collect(table: &Table) -> Self {
+        let row_count = table.row_count();
+        let mut columns = IndexMap::with_capacity(table.num_columns());
+        for (idx, field) in table.schema().fields().iter().enumerate() {
+            let Some(parts) = table.column_parts(idx) else {
+                continue;
+            };
+            let mut distinct = HashSet::new();
+            let mut nulls = 0usize;
+            let mut min: Option<Value> = None;
+            let mut max: Option<Value> = None;
+            for column in &parts {
+                for row in 0..column.len() {
+                    if column.is_null(row) {
+                        nulls += 1;
+                        continue;
+                    }
+                    let value = column.get_value(row);
+                    if min.as_ref().is_none_or(|m| value < *m) {
+                        min = Some(value.clone());
+                    }
+                    if max.as_ref().is_none_or(|m| value > *m) {
+                        max = Some(value.clone());
+                    }
+                    distinct.insert(value);
+                }
+            }
+            let null_fraction = if row_count == 0 {
+                0.0
+            } else {
+                nulls as f64 / row_count as f64
+            };
+            columns.insert(
+                field.name.to_uppercase(),
+                ColumnStatistics {
+                    distinct_count: distinct.len() as u64,
+                    null_fraction,
+                    min,
+                    max,
+                },
+            );
+        }
+        Self {
+            row_count: row_count as u64,
+            columns,
+        }
+    }
+
+    pub fn column(&self, name: &str) -> Option<&ColumnStatistics> {
+        self.columns.get(&name.to_uppercase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yachtsql_common::types::DataType;
+
+    use super::*;
+    use crate::{Field, Schema};
+
+    #[tokio::test]
+    async fn test_collect_statistics() {
+        let schema = Schema::from_fields(vec![
+            Field::nullable("id", DataType::Int64),
+            Field::nullable("tag", DataType::String),
+        ]);
+        let mut table = Table::new(schema);
+        for (id, tag) in [(3, Some("a")), (1, Some("b")), (2, Some("a")), (5, None)] {
+            let tag = tag.map_or(Value::Null, |t| Value::String(t.to_string()));
+            table.push_row(vec![Value::Int64(id), tag]).unwrap();
+        }
+
+        let stats = TableStatistics::collect(&table);
+        assert_eq!(stats.row_count, 4);
+
+        let id = stats.column("ID").unwrap();
+        assert_eq!(id.distinct_count, 4);
+        assert_eq!(id.null_fraction, 0.0);
+        assert_eq!(id.min, Some(Value::Int64(1)));
+        assert_eq!(id.max, Some(Value::Int64(5)));
+
+        let tag = stats.column("tag").unwrap();
+        assert_eq!(tag.distinct_count, 2);
+        assert_eq!(tag.null_fraction, 0.25);
+        assert_eq!(tag.max, Some(Value::String("b".to_string())));
+    }
+}
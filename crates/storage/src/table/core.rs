@@ -1,36 +1,125 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use yachtsql_common::error::Result;
 use yachtsql_common::types::Value;
 
-use crate::{Column, Record, Schema};
+use super::segment::{SEGMENT_ROWS, Segment};
+use crate::{Column, DeleteBitmap, Record, Schema};
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Rows are stored in segments of at most `SEGMENT_ROWS` appended rows.
+/// Segments and their column buffers are reference counted, so cloning a
+/// table is cheap and a clone shares storage with its source until one side
+/// writes to a segment; only that segment is copied.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "TableImage", into = "TableImage")]
 pub struct Table {
+    schema: Schema,
+    segments: Vec<Arc<Segment>>,
+    row_count: usize,
+}
+
+/// Serialized form of a table: one contiguous column per field, with deleted
+/// rows dropped.
+#[derive(Serialize, Deserialize)]
+struct TableImage {
     schema: Schema,
     columns: IndexMap<String, Column>,
     row_count: usize,
 }
 
-impl Table {
-    pub fn new(schema: Schema) -> Self {
-        let columns = schema
+impl From<Table> for TableImage {
+    fn from(table: Table) -> Self {
+        let columns = table
+            .schema
             .fields()
             .iter()
-            .map(|f| (f.name.clone(), Column::new(&f.data_type)))
+            .enumerate()
+            .filter_map(|(idx, field)| {
+                let column = table.column(idx)?.into_owned();
+                Some((field.name.clone(), column))
+            })
             .collect();
+        TableImage {
+            schema: table.schema,
+            columns,
+            row_count: table.row_count,
+        }
+    }
+}
+
+impl From<TableImage> for Table {
+    fn from(image: TableImage) -> Self {
+        let columns: Vec<Column> = image.columns.into_values().collect();
+        let rows = image.row_count;
+        let mut segments = Vec::new();
+        if rows <= SEGMENT_ROWS {
+            let columns = columns.into_iter().map(Arc::new).collect();
+            segments.push(Arc::new(Segment::from_columns(columns, rows)));
+        } else {
+            for start in (0..rows).step_by(SEGMENT_ROWS) {
+                let positions: Vec<usize> = (start..rows.min(start + SEGMENT_ROWS)).collect();
+                let chunk = columns
+                    .iter()
+                    .map(|column| Arc::new(column.gather(&positions)))
+                    .collect();
+                segments.push(Arc::new(Segment::from_columns(chunk, positions.len())));
+            }
+        }
+        Table {
+            schema: image.schema,
+            segments,
+            row_count: rows,
+        }
+    }
+}
+
+/// The segments of a table described relative to an earlier version of
+/// it: segments still shared with that version are referenced by position,
+/// and changed segments carry only the columns no longer shared.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableDelta {
+    schema: Schema,
+    segments: Vec<SegmentDelta>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum SegmentDelta {
+    Base(usize),
+    Changed {
+        base: Option<usize>,
+        rows: usize,
+        columns: Vec<Option<Column>>,
+        deleted: DeleteBitmap,
+    },
+}
+
+impl PartialEq for Table {
+    fn eq(&self, other: &Self) -> bool {
+        self.schema == other.schema
+            && self.row_count == other.row_count
+            && (0..self.num_columns()).all(|idx| self.column(idx) == other.column(idx))
+    }
+}
+
+impl Table {
+    pub fn new(schema: Schema) -> Self {
         Self {
             schema,
-            columns,
+            segments: Vec::new(),
             row_count: 0,
         }
     }
 
     pub fn from_columns(schema: Schema, columns: IndexMap<String, Column>) -> Self {
         let row_count = columns.values().next().map(|c| c.len()).unwrap_or(0);
+        let columns = columns.into_values().map(Arc::new).collect();
         Self {
             schema,
-            columns,
+            segments: vec![Arc::new(Segment::from_columns(columns, row_count))],
             row_count,
         }
     }
@@ -55,50 +144,250 @@ impl Table {
         self.row_count == 0
     }
 
-    pub fn column(&self, idx: usize) -> Option<&Column> {
-        self.columns.values().nth(idx)
+    /// The live rows of one column. Borrowed when the table is a single
+    /// segment without deletes, otherwise assembled from the segments on
+    /// every call; use `column_parts` when the column need not be contiguous.
+    pub fn column(&self, idx: usize) -> Option<Cow<'_, Column>> {
+        let field = self.schema.fields().get(idx)?;
+        let mut segments = self.segments.iter().filter(|s| s.num_rows() > 0);
+        let Some(first) = segments.next() else {
+            return Some(Cow::Owned(Column::new(&field.data_type)));
+        };
+        let mut column = first.column(idx)?;
+        for segment in segments {
+            let part = segment.column(idx)?;
+            column.to_mut().append(&part).ok()?;
+        }
+        Some(column)
+    }
+
+    /// The live rows of one column, one part per non-empty segment, without
+    /// concatenating them.
+    pub fn column_parts(&self, idx: usize) -> Option<Vec<Cow<'_, Column>>> {
+        self.schema.fields().get(idx)?;
+        self.segments
+            .iter()
+            .filter(|s| s.num_rows() > 0)
+            .map(|segment| segment.column(idx))
+            .collect()
     }
 
-    pub fn column_by_name(&self, name: &str) -> Option<&Column> {
-        self.columns.get(name)
+    pub fn column_by_name(&self, name: &str) -> Option<Cow<'_, Column>> {
+        let idx = self.schema.fields().iter().position(|f| f.name == name)?;
+        self.column(idx)
     }
 
-    pub fn columns(&self) -> &IndexMap<String, Column> {
-        &self.columns
+    pub fn segments(&self) -> &[Arc<Segment>] {
+        &self.segments
     }
 
-    pub fn num_columns(&self) -> usize {
-        self.columns.len()
+    /// Approximate bytes held by the table's segments, counting segments
+    /// shared with other tables as well.
+    pub fn memory_size(&self) -> usize {
+        self.segments.iter().map(|s| s.memory_size()).sum()
+    }
+
+    /// The changes that turn `base` into this table. Without a base, or for
+    /// a base sharing no storage with the table, every segment is copied.
+    pub fn delta_from(&self, base: Option<&Table>) -> TableDelta {
+        let base_segments = base.map(|b| b.segments.as_slice()).unwrap_or_default();
+        let shared: HashMap<*const Segment, usize> = base_segments
+            .iter()
+            .enumerate()
+            .map(|(idx, segment)| (Arc::as_ptr(segment), idx))
+            .collect();
+        let mut next = 0;
+        let segments = self
+            .segments
+            .iter()
+            .map(|segment| {
+                if let Some(&idx) = shared.get(&Arc::as_ptr(segment)) {
+                    next = idx + 1;
+                    return SegmentDelta::Base(idx);
+                }
+                let source = base_segments.get(next).filter(|source| {
+                    source
+                        .columns()
+                        .iter()
+                        .zip(segment.columns())
+                        .any(|(a, b)| Arc::ptr_eq(a, b))
+                });
+                let columns = segment
+                    .columns()
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, column)| {
+                        let unchanged = source
+                            .and_then(|source| source.columns().get(idx))
+                            .is_some_and(|old| Arc::ptr_eq(old, column));
+                        (!unchanged).then(|| column.as_ref().clone())
+                    })
+                    .collect();
+                let base = source.map(|_| next);
+                if source.is_some() {
+                    next += 1;
+                }
+                SegmentDelta::Changed {
+                    base,
+                    rows: segment.physical_rows(),
+                    columns,
+                    deleted: segment.deleted().clone(),
+                }
+            })
+            .collect();
+        TableDelta {
+            schema: self.schema.clone(),
+            segments,
+        }
+    }
+
+    /// Rebuilds the table `delta` was taken from, given the same `base`.
+    pub fn from_delta(base: Option<&Table>, delta: TableDelta) -> Result<Table> {
+        let base_segments = base.map(|b| b.segments.as_slice()).unwrap_or_default();
+        let missing = |idx: usize| {
+            yachtsql_common::error::Error::internal(format!(
+                "Table delta refers to segment {} missing from its base",
+                idx
+            ))
+        };
+        let mut segments = Vec::with_capacity(delta.segments.len());
+        for segment in delta.segments {
+            match segment {
+                SegmentDelta::Base(idx) => {
+                    segments.push(base_segments.get(idx).ok_or_else(|| missing(idx))?.clone());
+                }
+                SegmentDelta::Changed {
+                    base,
+                    rows,
+                    columns,
+                    deleted,
+                } => {
+                    let source = match base {
+                        Some(idx) => Some(base_segments.get(idx).ok_or_else(|| missing(idx))?),
+                        None => None,
+                    };
+                    let columns = columns
+                        .into_iter()
+                        .enumerate()
+                        .map(|(idx, column)| match column {
+                            Some(column) => Ok(Arc::new(column)),
+                            None => source
+                                .and_then(|source| source.columns().get(idx).cloned())
+                                .ok_or_else(|| missing(base.unwrap_or_default())),
+                        })
+                        .collect::<Result<Vec<_>>>()?;
+                    segments.push(Arc::new(Segment::from_parts(columns, rows, deleted)));
+                }
+            }
+        }
+        let row_count = segments.iter().map(|s| s.num_rows()).sum();
+        Ok(Table {
+            schema: delta.schema,
+            segments,
+            row_count,
+        })
+    }
+
+    /// Splits the table into one table per segment, sharing storage.
+    pub fn split_segments(&self) -> Vec<Table> {
+        (0..self.segments.len())
+            .map(|index| self.segment_table(index))
+            .filter(|table| table.row_count > 0)
+            .collect()
+    }
+
+    /// The segment at `index` as a table of its own, sharing storage.
+    pub fn segment_table(&self, index: usize) -> Table {
+        let segment = &self.segments[index];
+        Table {
+            schema: self.schema.clone(),
+            segments: vec![segment.clone()],
+            row_count: segment.num_rows(),
+        }
+    }
+
+    /// The rows of this table held in segments `other` does not share, such
+    /// as the rows a statement added to or changed in a copy of `other`.
+    pub fn unshared_with(&self, other: &Table) -> Table {
+        let shared: HashSet<*const Segment> = other.segments.iter().map(Arc::as_ptr).collect();
+        let segments: Vec<Arc<Segment>> = self
+            .segments
+            .iter()
+            .filter(|segment| !shared.contains(&Arc::as_ptr(segment)))
+            .cloned()
+            .collect();
+        Table {
+            schema: self.schema.clone(),
+            row_count: segments.iter().map(|segment| segment.num_rows()).sum(),
+            segments,
+        }
+    }
+
+    /// Appends the rows of `other` by sharing its segments.
+    pub fn append(&mut self, other: &Table) {
+        self.segments.extend(other.segments.iter().cloned());
+        self.row_count += other.row_count;
+    }
+
+    pub fn shares_column_buffers(&self, other: &Table) -> bool {
+        self.segments.len() == other.segments.len()
+            && self
+                .segments
+                .iter()
+                .zip(&other.segments)
+                .all(|(a, b)| Arc::ptr_eq(a, b) || a.shares_buffers(b))
     }
 
-    pub fn columns_mut(&mut self) -> &mut IndexMap<String, Column> {
-        &mut self.columns
+    pub fn num_columns(&self) -> usize {
+        self.schema.field_count()
     }
 
     pub fn push_row(&mut self, values: Vec<Value>) -> Result<()> {
-        for (col, value) in self.columns.values_mut().zip(values.into_iter()) {
-            col.push(value)?;
+        let full = self
+            .segments
+            .last()
+            .is_none_or(|segment| segment.physical_rows() >= SEGMENT_ROWS);
+        if full {
+            self.segments.push(Arc::new(Segment::new(&self.schema)));
         }
+        let segment = self.segments.last_mut().expect("segment was just ensured");
+        Arc::make_mut(segment).push(values)?;
         self.row_count += 1;
         Ok(())
     }
 
+    /// Segment index and physical position of the `index`-th live row.
+    fn locate(&self, mut index: usize) -> Option<(usize, usize)> {
+        for (segment_idx, segment) in self.segments.iter().enumerate() {
+            let live = segment.num_rows();
+            if index < live {
+                return Some((segment_idx, segment.position(index)));
+            }
+            index -= live;
+        }
+        None
+    }
+
     pub fn get_row(&self, index: usize) -> Result<Record> {
-        if index >= self.row_count {
-            return Err(yachtsql_common::error::Error::invalid_query(format!(
+        match self.locate(index) {
+            Some((segment, position)) => {
+                Ok(Record::from_values(self.segments[segment].values(position)))
+            }
+            None => Err(yachtsql_common::error::Error::invalid_query(format!(
                 "Row index {} out of bounds (count: {})",
                 index, self.row_count
-            )));
+            ))),
         }
-        let columns: Vec<&Column> = self.columns.values().collect();
-        let values: Vec<Value> = columns.iter().map(|c| c.get_value(index)).collect();
-        Ok(Record::from_values(values))
     }
 
     pub fn to_records(&self) -> Result<Vec<Record>> {
         let mut records = Vec::with_capacity(self.row_count);
-        for i in 0..self.row_count {
-            records.push(self.get_row(i)?);
+        for segment in &self.segments {
+            for position in 0..segment.physical_rows() {
+                if !segment.is_deleted(position) {
+                    records.push(Record::from_values(segment.values(position)));
+                }
+            }
         }
         Ok(records)
     }
@@ -107,6 +396,30 @@ impl Table {
         self.to_records()
     }
 
+    /// Keeps the rows at `indices`. Columns whose layout differs from the
+    /// schema are rebuilt value by value, exactly as `push_row` would.
+    pub fn gather_rows(&self, indices: &[usize]) -> Result<Table> {
+        let mut columns = Vec::with_capacity(self.num_columns());
+        for (idx, field) in self.schema.fields().iter().enumerate() {
+            let mut target = Column::new(&field.data_type);
+            if let Some(source) = self.column(idx) {
+                if source.same_layout(&target) {
+                    target = source.gather(indices);
+                } else {
+                    for &index in indices {
+                        target.push(source.get_value(index))?;
+                    }
+                }
+            }
+            columns.push(Arc::new(target));
+        }
+        Ok(Table {
+            schema: self.schema.clone(),
+            segments: vec![Arc::new(Segment::from_columns(columns, indices.len()))],
+            row_count: indices.len(),
+        })
+    }
+
     pub fn from_records(schema: Schema, records: Vec<Record>) -> Result<Self> {
         let mut table = Self::new(schema);
         for record in records {
@@ -124,24 +437,89 @@ impl Table {
     }
 
     pub fn clear(&mut self) {
-        for col in self.columns.values_mut() {
-            col.clear();
-        }
+        self.segments.clear();
         self.row_count = 0;
     }
 
     pub fn remove_row(&mut self, index: usize) {
-        for col in self.columns.values_mut() {
-            col.remove(index);
-        }
-        if self.row_count > 0 {
-            self.row_count -= 1;
+        self.delete_rows(&[index]);
+    }
+
+    /// Marks the rows at `indices` deleted. Only the segments holding them
+    /// are copied, and no other row moves.
+    pub fn delete_rows(&mut self, indices: &[usize]) {
+        let mut indices = indices.to_vec();
+        indices.sort_unstable();
+        indices.dedup();
+
+        let mut pending = indices.into_iter().peekable();
+        let mut offset = 0;
+        for segment in &mut self.segments {
+            let live = segment.num_rows();
+            let mut positions = Vec::new();
+            let mut live_positions = None;
+            while let Some(&index) = pending.peek() {
+                if index >= offset + live {
+                    break;
+                }
+                positions.push(physical_position(
+                    segment,
+                    &mut live_positions,
+                    index - offset,
+                ));
+                pending.next();
+            }
+            offset += live;
+            if positions.is_empty() {
+                continue;
+            }
+            let segment = Arc::make_mut(segment);
+            for position in positions {
+                segment.delete(position);
+            }
+            segment.compact_if_sparse();
         }
+
+        self.segments.retain(|segment| segment.num_rows() > 0);
+        self.row_count = self.segments.iter().map(|s| s.num_rows()).sum();
     }
 
     pub fn update_row(&mut self, index: usize, values: Vec<Value>) -> Result<()> {
-        for (col, value) in self.columns.values_mut().zip(values.into_iter()) {
-            col.set(index, value)?;
+        let Some((segment, position)) = self.locate(index) else {
+            return Ok(());
+        };
+        Arc::make_mut(&mut self.segments[segment]).set(position, values)
+    }
+
+    /// Overwrites many rows in one pass. Segments without an update stay
+    /// shared with any clone of the table.
+    pub fn update_rows(&mut self, mut updates: Vec<(usize, Vec<Value>)>) -> Result<()> {
+        updates.sort_by_key(|(index, _)| *index);
+
+        let mut pending = updates.into_iter().peekable();
+        let mut offset = 0;
+        for segment in &mut self.segments {
+            let live = segment.num_rows();
+            let mut live_positions = None;
+            let mut rows = Vec::new();
+            while let Some((index, _)) = pending.peek() {
+                if *index >= offset + live {
+                    break;
+                }
+                let (index, values) = pending.next().expect("peeked");
+                rows.push((
+                    physical_position(segment, &mut live_positions, index - offset),
+                    values,
+                ));
+            }
+            offset += live;
+            if rows.is_empty() {
+                continue;
+            }
+            let segment = Arc::make_mut(segment);
+            for (position, values) in rows {
+                segment.set(position, values)?;
+            }
         }
         Ok(())
     }
@@ -149,12 +527,14 @@ impl Table {
     pub fn drop_column(&mut self, name: &str) -> Result<()> {
         let upper = name.to_uppercase();
         let found = self
-            .columns
-            .keys()
-            .find(|k| k.to_uppercase() == upper)
-            .cloned();
-        if let Some(key) = found {
-            self.columns.shift_remove(&key);
+            .schema
+            .fields()
+            .iter()
+            .position(|f| f.name.to_uppercase() == upper);
+        if let Some(idx) = found {
+            for segment in &mut self.segments {
+                Arc::make_mut(segment).remove_column(idx);
+            }
             let fields: Vec<_> = self
                 .schema
                 .fields()
@@ -173,12 +553,12 @@ impl Table {
 
     pub fn rename_column(&mut self, old_name: &str, new_name: &str) -> Result<()> {
         let upper = old_name.to_uppercase();
-        let found_idx = self.columns.keys().position(|k| k.to_uppercase() == upper);
-        if let Some(idx) = found_idx {
-            let key = self.columns.keys().nth(idx).cloned().unwrap();
-            if let Some(col) = self.columns.shift_remove(&key) {
-                self.columns.shift_insert(idx, new_name.to_string(), col);
-            }
+        let found = self
+            .schema
+            .fields()
+            .iter()
+            .any(|f| f.name.to_uppercase() == upper);
+        if found {
             let fields: Vec<_> = self
                 .schema
                 .fields()
@@ -350,16 +730,11 @@ impl Table {
         let needs_conversion = !Self::types_compatible(old_type, &new_data_type);
 
         if needs_conversion {
-            let col_name_key = self
-                .columns
-                .keys()
-                .find(|k| k.to_uppercase() == upper)
-                .cloned();
-            if let Some(key) = col_name_key
-                && let Some(old_col) = self.columns.get(&key)
-            {
-                let new_col = Self::convert_column(old_col, old_type, &new_data_type)?;
-                self.columns.insert(key, new_col);
+            for segment in &mut self.segments {
+                if let Some(old_col) = segment.columns().get(field_idx) {
+                    let new_col = Self::convert_column(old_col, old_type, &new_data_type)?;
+                    Arc::make_mut(segment).replace_column(field_idx, new_col);
+                }
             }
         }
 
@@ -481,28 +856,25 @@ impl Table {
     }
 
     pub fn with_schema(&self, new_schema: Schema) -> Table {
-        let mut new_columns = IndexMap::new();
-        for (old_col, new_field) in self.columns.values().zip(new_schema.fields().iter()) {
-            new_columns.insert(new_field.name.clone(), old_col.clone());
-        }
-        Table {
-            schema: new_schema,
-            columns: new_columns,
-            row_count: self.row_count,
-        }
+        let column_indices: Vec<usize> = (0..new_schema.field_count()).collect();
+        self.with_reordered_schema(new_schema, &column_indices)
     }
 
+    /// Keeps the columns at `column_indices`, in that order, under
+    /// `new_schema`. Column buffers are shared, not copied; fields without a
+    /// source column read as NULL.
     pub fn with_reordered_schema(&self, new_schema: Schema, column_indices: &[usize]) -> Table {
-        let source_columns: Vec<_> = self.columns.values().collect();
-        let mut new_columns = IndexMap::new();
-        for (new_field, &idx) in new_schema.fields().iter().zip(column_indices.iter()) {
-            if idx < source_columns.len() {
-                new_columns.insert(new_field.name.clone(), source_columns[idx].clone());
-            }
-        }
+        let indices: Vec<Option<usize>> = (0..new_schema.field_count())
+            .map(|position| column_indices.get(position).copied())
+            .collect();
+        let segments = self
+            .segments
+            .iter()
+            .map(|segment| Arc::new(segment.select(&indices, &new_schema)))
+            .collect();
         Table {
             schema: new_schema,
-            columns: new_columns,
+            segments,
             row_count: self.row_count,
         }
     }
@@ -534,14 +906,223 @@ pub trait TableSchemaOps {
 impl TableSchemaOps for Table {
     fn add_column(&mut self, field: crate::Field, default: Option<Value>) -> Result<()> {
         let default_val = default.unwrap_or(Value::Null);
-        let mut col = Column::new(&field.data_type);
-        for _ in 0..self.row_count {
-            col.push(default_val.clone())?;
+        for segment in &mut self.segments {
+            let mut col = Column::new(&field.data_type);
+            for _ in 0..segment.physical_rows() {
+                col.push(default_val.clone())?;
+            }
+            Arc::make_mut(segment).push_column(col);
         }
-        self.columns.insert(field.name.clone(), col);
         let mut fields: Vec<_> = self.schema.fields().to_vec();
         fields.push(field);
         self.schema = Schema::from_fields(fields);
         Ok(())
     }
 }
+
+fn physical_position(
+    segment: &Segment,
+    live_positions: &mut Option<Vec<usize>>,
+    index: usize,
+) -> usize {
+    if !segment.has_deletes() {
+        return index;
+    }
+    live_positions.get_or_insert_with(|| segment.live_positions())[index]
+}
+
+#[cfg(test)]
+mod tests {
+    use yachtsql_common::types::DataType;
+
+    use super::*;
+    use crate::Field;
+
+    fn numbered(rows: usize) -> Table {
+        let schema = Schema::from_fields(vec![
+            Field::nullable("id", DataType::Int64),
+            Field::nullable("name", DataType::String),
+        ]);
+        let mut table = Table::new(schema);
+        for i in 0..rows {
+            table
+                .push_row(vec![Value::Int64(i as i64), Value::String(format!("r{i}"))])
+                .unwrap();
+        }
+        table
+    }
+
+    fn ids(table: &Table) -> Vec<i64> {
+        table
+            .rows()
+            .unwrap()
+            .iter()
+            .map(|r| r.values()[0].as_i64().unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_push_row_starts_new_segments() {
+        let table = numbered(SEGMENT_ROWS * 2 + 5);
+        assert_eq!(table.segments().len(), 3);
+        assert_eq!(table.row_count(), SEGMENT_ROWS * 2 + 5);
+        assert_eq!(
+            table.get_row(SEGMENT_ROWS + 1).unwrap().values()[0],
+            Value::Int64(SEGMENT_ROWS as i64 + 1)
+        );
+        assert_eq!(table.column(0).unwrap().len(), table.row_count());
+    }
+
+    #[tokio::test]
+    async fn test_delete_rows_copies_only_touched_segments() {
+        let original = numbered(SEGMENT_ROWS * 2);
+        let mut table = original.clone();
+        table.delete_rows(&[SEGMENT_ROWS + 3, SEGMENT_ROWS + 1]);
+
+        assert_eq!(table.row_count(), SEGMENT_ROWS * 2 - 2);
+        assert_eq!(original.row_count(), SEGMENT_ROWS * 2);
+        assert!(Arc::ptr_eq(&table.segments()[0], &original.segments()[0]));
+        assert!(!Arc::ptr_eq(&table.segments()[1], &original.segments()[1]));
+        assert!(table.segments()[1].has_deletes());
+
+        let ids = ids(&table);
+        assert_eq!(ids[SEGMENT_ROWS], SEGMENT_ROWS as i64);
+        assert_eq!(ids[SEGMENT_ROWS + 1], SEGMENT_ROWS as i64 + 2);
+        assert_eq!(ids[SEGMENT_ROWS + 2], SEGMENT_ROWS as i64 + 4);
+
+        table.delete_rows(&[SEGMENT_ROWS + 1]);
+        assert_eq!(
+            table.get_row(SEGMENT_ROWS + 1).unwrap().values()[0],
+            Value::Int64(SEGMENT_ROWS as i64 + 4)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_memory_size_grows_with_rows() {
+        let empty = numbered(0);
+        let small = numbered(10);
+        let large = numbered(1000);
+        assert_eq!(empty.memory_size(), 0);
+        assert!(small.memory_size() >= 10 * std::mem::size_of::<i64>());
+        assert!(large.memory_size() > small.memory_size() * 50);
+    }
+
+    #[tokio::test]
+    async fn test_sparse_segments_are_compacted_or_dropped() {
+        let mut table = numbered(SEGMENT_ROWS * 2);
+        let first_half: Vec<usize> = (0..SEGMENT_ROWS / 2).collect();
+        table.delete_rows(&first_half);
+        assert!(!table.segments()[0].has_deletes());
+        assert_eq!(table.segments()[0].physical_rows(), SEGMENT_ROWS / 2);
+
+        let rest: Vec<usize> = (0..SEGMENT_ROWS / 2).collect();
+        table.delete_rows(&rest);
+        assert_eq!(table.segments().len(), 1);
+        assert_eq!(ids(&table)[0], SEGMENT_ROWS as i64);
+    }
+
+    #[tokio::test]
+    async fn test_update_rows_copies_only_changed_columns() {
+        let original = numbered(SEGMENT_ROWS * 2);
+        let mut table = original.clone();
+        table
+            .update_rows(vec![(
+                SEGMENT_ROWS + 7,
+                vec![
+                    Value::Int64(SEGMENT_ROWS as i64 + 7),
+                    Value::String("changed".into()),
+                ],
+            )])
+            .unwrap();
+
+        assert!(Arc::ptr_eq(&table.segments()[0], &original.segments()[0]));
+        let (updated, before) = (&table.segments()[1], &original.segments()[1]);
+        assert!(Arc::ptr_eq(&updated.columns()[0], &before.columns()[0]));
+        assert!(!Arc::ptr_eq(&updated.columns()[1], &before.columns()[1]));
+        assert_eq!(
+            table.get_row(SEGMENT_ROWS + 7).unwrap().values()[1],
+            Value::String("changed".into())
+        );
+        assert_eq!(
+            original.get_row(SEGMENT_ROWS + 7).unwrap().values()[1],
+            Value::String(format!("r{}", SEGMENT_ROWS + 7))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_coerces_like_push() {
+        let schema = Schema::from_fields(vec![Field::nullable("x", DataType::Float64)]);
+        let mut table = Table::new(schema);
+        table.push_row(vec![Value::Int64(1)]).unwrap();
+        table.update_row(0, vec![Value::Int64(2)]).unwrap();
+        assert_eq!(table.get_row(0).unwrap().values()[0], Value::float64(2.0));
+    }
+
+    #[tokio::test]
+    async fn test_row_lookup_skips_deleted_rows() {
+        let mut table = numbered(SEGMENT_ROWS + 300);
+        let deleted: Vec<usize> = (0..SEGMENT_ROWS + 300).filter(|i| i % 3 == 0).collect();
+        table.delete_rows(&deleted);
+
+        let live: Vec<usize> = (0..SEGMENT_ROWS + 300).filter(|i| i % 3 != 0).collect();
+        assert_eq!(table.row_count(), live.len());
+        for (index, id) in live.iter().enumerate().step_by(97) {
+            let row = table.get_row(index).unwrap();
+            assert_eq!(row.values()[0], Value::Int64(*id as i64));
+        }
+        assert!(table.get_row(live.len()).is_err());
+
+        let parts = table.column_parts(0).unwrap();
+        assert_eq!(parts.len(), table.segments().len());
+        assert_eq!(
+            parts.iter().map(|p| p.len()).sum::<usize>(),
+            table.row_count()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delta_carries_only_changed_segments_and_columns() {
+        let base = numbered(SEGMENT_ROWS * 3);
+        let mut table = base.clone();
+        table
+            .update_rows(vec![(1, vec![Value::Int64(1), Value::String("x".into())])])
+            .unwrap();
+        table.delete_rows(&[SEGMENT_ROWS + 5]);
+        table
+            .push_row(vec![Value::Int64(-1), Value::String("new".into())])
+            .unwrap();
+
+        let delta = table.delta_from(Some(&base));
+        let copied: Vec<usize> = delta
+            .segments
+            .iter()
+            .map(|segment| match segment {
+                SegmentDelta::Base(_) => 0,
+                SegmentDelta::Changed { columns, .. } => {
+                    columns.iter().filter(|c| c.is_some()).count()
+                }
+            })
+            .collect();
+        assert_eq!(copied, vec![1, 0, 0, 2]);
+
+        let restored = Table::from_delta(Some(&base), delta).unwrap();
+        assert_eq!(restored, table);
+        assert_eq!(restored.segments().len(), table.segments().len());
+        assert!(restored.segments()[1].has_deletes());
+
+        let full = Table::from_delta(None, table.delta_from(None)).unwrap();
+        assert_eq!(full, table);
+    }
+
+    #[tokio::test]
+    async fn test_serde_roundtrip_keeps_live_rows() {
+        let mut table = numbered(SEGMENT_ROWS + 10);
+        table.delete_rows(&[0, SEGMENT_ROWS + 2]);
+
+        let json = serde_json::to_string(&table).unwrap();
+        let restored: Table = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, table);
+        assert_eq!(restored.segments().len(), 2);
+        assert!(restored.segments().iter().all(|s| !s.has_deletes()));
+    }
+}
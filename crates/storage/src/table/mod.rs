@@ -1,3 +1,6 @@
 mod core;
+mod segment;
 
-pub use core::{Table, TableSchemaOps};
+pub use core::{Table, TableDelta, TableSchemaOps};
+
+pub use segment::{SEGMENT_ROWS, Segment};
@@ -0,0 +1,209 @@
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use yachtsql_common::error::Result;
+use yachtsql_common::types::Value;
+
+use crate::{Column, DeleteBitmap, Schema};
+
+/// Rows appended to a segment before the table starts a new one.
+pub const SEGMENT_ROWS: usize = 8192;
+
+/// A run of table rows stored column by column. Tables share segments with
+/// their clones; a write copies only the segment and columns it touches, and
+/// a delete only marks the row in the segment's delete bitmap.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    columns: Vec<Arc<Column>>,
+    rows: usize,
+    deleted: DeleteBitmap,
+}
+
+impl Segment {
+    pub(crate) fn new(schema: &Schema) -> Self {
+        let columns = schema
+            .fields()
+            .iter()
+            .map(|f| Arc::new(Column::new(&f.data_type)))
+            .collect();
+        Self::from_columns(columns, 0)
+    }
+
+    pub(crate) fn from_columns(columns: Vec<Arc<Column>>, rows: usize) -> Self {
+        Self::from_parts(columns, rows, DeleteBitmap::new())
+    }
+
+    pub(crate) fn from_parts(
+        columns: Vec<Arc<Column>>,
+        rows: usize,
+        deleted: DeleteBitmap,
+    ) -> Self {
+        Self {
+            columns,
+            rows,
+            deleted,
+        }
+    }
+
+    /// Live rows, excluding deleted ones.
+    pub fn num_rows(&self) -> usize {
+        self.rows - self.deleted.count()
+    }
+
+    /// Stored rows, including deleted ones.
+    pub fn physical_rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn num_columns(&self) -> usize {
+        self.columns.len()
+    }
+
+    pub fn has_deletes(&self) -> bool {
+        !self.deleted.is_empty()
+    }
+
+    pub fn is_deleted(&self, position: usize) -> bool {
+        self.deleted.is_deleted(position)
+    }
+
+    pub(crate) fn deleted(&self) -> &DeleteBitmap {
+        &self.deleted
+    }
+
+    /// Approximate bytes held by the stored columns.
+    pub fn memory_size(&self) -> usize {
+        self.columns.iter().map(|c| c.memory_size()).sum()
+    }
+
+    /// Stored column buffers; rows marked deleted are still present.
+    pub fn columns(&self) -> &[Arc<Column>] {
+        &self.columns
+    }
+
+    /// The live rows of one column. Borrowed unless rows were deleted.
+    pub fn column(&self, idx: usize) -> Option<Cow<'_, Column>> {
+        let column = self.columns.get(idx)?;
+        if self.deleted.is_empty() {
+            Some(Cow::Borrowed(column.as_ref()))
+        } else {
+            Some(Cow::Owned(column.gather(&self.live_positions())))
+        }
+    }
+
+    pub fn live_positions(&self) -> Vec<usize> {
+        (0..self.rows)
+            .filter(|&position| !self.deleted.is_deleted(position))
+            .collect()
+    }
+
+    /// Physical position of the `index`-th live row.
+    pub(crate) fn position(&self, index: usize) -> usize {
+        if self.deleted.is_empty() {
+            return index;
+        }
+        self.deleted.nth_live(index, self.rows).unwrap_or(self.rows)
+    }
+
+    pub(crate) fn values(&self, position: usize) -> Vec<Value> {
+        self.columns
+            .iter()
+            .map(|column| column.get_value(position))
+            .collect()
+    }
+
+    pub(crate) fn push(&mut self, values: Vec<Value>) -> Result<()> {
+        for (column, value) in self.columns.iter_mut().zip(values) {
+            Arc::make_mut(column).push(value)?;
+        }
+        self.rows += 1;
+        Ok(())
+    }
+
+    /// Overwrites the row at `position`, copying only columns whose value
+    /// actually changes. Values are coerced the way `push` coerces them.
+    pub(crate) fn set(&mut self, position: usize, values: Vec<Value>) -> Result<()> {
+        for (column, value) in self.columns.iter_mut().zip(values) {
+            if column.get_value(position) == value {
+                continue;
+            }
+            let mut coerced = Column::new(&column.data_type());
+            coerced.push(value)?;
+            Arc::make_mut(column).set(position, coerced.get_value(0))?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn delete(&mut self, position: usize) {
+        if position < self.rows {
+            self.deleted.delete(position);
+        }
+    }
+
+    /// Rewrites the segment without its deleted rows once they make up half
+    /// of it, bounding the space deletes can waste.
+    pub(crate) fn compact_if_sparse(&mut self) {
+        if self.deleted.count() * 2 < self.rows {
+            return;
+        }
+        let live = self.live_positions();
+        self.columns = self
+            .columns
+            .iter()
+            .map(|column| Arc::new(column.gather(&live)))
+            .collect();
+        self.rows = live.len();
+        self.deleted = DeleteBitmap::new();
+    }
+
+    pub(crate) fn select(&self, column_indices: &[Option<usize>], schema: &Schema) -> Segment {
+        let columns = column_indices
+            .iter()
+            .zip(schema.fields())
+            .map(
+                |(idx, field)| match idx.and_then(|idx| self.columns.get(idx)) {
+                    Some(column) => column.clone(),
+                    None => {
+                        let mut column = Column::new(&field.data_type);
+                        for _ in 0..self.rows {
+                            let _ = column.push(Value::Null);
+                        }
+                        Arc::new(column)
+                    }
+                },
+            )
+            .collect();
+        Segment {
+            columns,
+            rows: self.rows,
+            deleted: self.deleted.clone(),
+        }
+    }
+
+    pub(crate) fn remove_column(&mut self, idx: usize) {
+        if idx < self.columns.len() {
+            self.columns.remove(idx);
+        }
+    }
+
+    pub(crate) fn push_column(&mut self, column: Column) {
+        self.columns.push(Arc::new(column));
+    }
+
+    pub(crate) fn replace_column(&mut self, idx: usize, column: Column) {
+        if let Some(slot) = self.columns.get_mut(idx) {
+            *slot = Arc::new(column);
+        }
+    }
+
+    pub(crate) fn shares_buffers(&self, other: &Segment) -> bool {
+        self.rows == other.rows
+            && self.deleted == other.deleted
+            && self.columns.len() == other.columns.len()
+            && self
+                .columns
+                .iter()
+                .zip(&other.columns)
+                .all(|(a, b)| Arc::ptr_eq(a, b))
+    }
+}
@@ -25,12 +25,13 @@ pub fn assert_batch_empty(batch: &yachtsql::Table) {
         "expected zero columns but schema has {} fields",
         batch.schema().fields().len()
     );
-    let cols = batch.columns();
-    assert!(
-        cols.is_empty(),
-        "expected zero column buffers but found {}",
-        cols.len()
-    );
+    let cols = batch
+        .segments()
+        .iter()
+        .map(|segment| segment.num_columns())
+        .max()
+        .unwrap_or(0);
+    assert!(cols == 0, "expected zero column buffers but found {}", cols);
 }
 
 pub fn assert_float_eq(actual: f64, expected: f64, epsilon: f64) {
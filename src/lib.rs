@@ -10,7 +10,11 @@
 //! SQL String → Parser → LogicalPlan → Optimizer → PhysicalPlan → Executor → Result
 //! ```
 //!
-//! The `YachtSQLEngine` creates isolated sessions with their own catalog and state.
+//! The `YachtSQLEngine` owns the datasets; every session it creates sees the same
+//! tables, views and routines, while variables, `@@` system variables, temp tables
+//! and the current schema stay private to the session. An engine opened with
+//! `YachtSQLEngine::open` also persists its catalog to a directory (write-ahead log
+//! plus periodic checkpoints).
 //!
 //! # Example
 //!
@@ -46,25 +50,68 @@ pub use yachtsql_common::error::{Error, Result};
 pub use yachtsql_common::result::{ColumnInfo, QueryResult, Row};
 pub use yachtsql_common::types::{DataType, Value};
 pub use yachtsql_executor::{
-    AsyncQueryExecutor, ConcurrentCatalog, ConcurrentSession, Record, Table,
+    AsyncQueryExecutor, CatalogImage, ConcurrentCatalog, ConcurrentSession, HistoryClock,
+    ParameterInfo, PersistenceOptions, PreparedStatement, QueryHandle, QueryParams, QueryStream,
+    Record, Table,
 };
 pub use yachtsql_ir::LogicalPlan;
-pub use yachtsql_optimizer::OptimizedLogicalPlan;
-pub use yachtsql_parser::{CatalogProvider, Planner, PlannerError, parse_and_plan, parse_sql};
+pub use yachtsql_optimizer::{OptimizedLogicalPlan, OptimizerRule};
+pub use yachtsql_parser::{
+    CatalogProvider, Planner, PlannerError, QueryParameter, parse_and_plan, parse_sql,
+};
 pub use yachtsql_storage::{Field, FieldMode, Schema};
 
-pub struct YachtSQLEngine;
+pub struct YachtSQLEngine {
+    executor: AsyncQueryExecutor,
+}
 
 impl YachtSQLEngine {
     pub fn new() -> Self {
-        Self
+        Self {
+            executor: AsyncQueryExecutor::new(),
+        }
+    }
+
+    pub fn from_image(image: &CatalogImage) -> Self {
+        Self {
+            executor: AsyncQueryExecutor::from_image(image),
+        }
+    }
+
+    pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        Self::open_with_options(path, PersistenceOptions::default())
+    }
+
+    pub fn open_with_options(
+        path: impl AsRef<std::path::Path>,
+        options: PersistenceOptions,
+    ) -> Result<Self> {
+        Ok(Self {
+            executor: AsyncQueryExecutor::open_with_options(path, options)?,
+        })
     }
 
     pub fn create_session(&self) -> YachtSQLSession {
         YachtSQLSession {
-            executor: AsyncQueryExecutor::new(),
+            executor: self.executor.new_session(),
         }
     }
+
+    pub fn catalog(&self) -> &ConcurrentCatalog {
+        self.executor.catalog()
+    }
+
+    pub fn snapshot(&self) -> CatalogImage {
+        self.executor.catalog().snapshot()
+    }
+
+    pub fn checkpoint(&self) -> Result<()> {
+        self.executor.checkpoint()
+    }
+
+    pub fn register_optimizer_rule(&self, rule: std::sync::Arc<dyn OptimizerRule>) {
+        self.executor.register_optimizer_rule(rule);
+    }
 }
 
 impl Default for YachtSQLEngine {
@@ -88,9 +135,34 @@ impl YachtSQLSession {
         self.executor.execute_sql(sql).await
     }
 
+    /// Returns a [`QueryHandle`] that cancels the statement along with the
+    /// future that runs it.
+    pub fn execute_cancellable<'a>(
+        &'a self,
+        sql: &'a str,
+    ) -> (QueryHandle, impl Future<Output = Result<Table>> + 'a) {
+        self.executor.execute_cancellable(sql)
+    }
+
     pub async fn query(&self, sql: &str) -> Result<QueryResult> {
-        let table = self.executor.execute_sql(sql).await?;
-        table.to_query_result()
+        self.executor.query(sql).await
+    }
+
+    /// Runs a query as a `futures::Stream` of record batches. Filters,
+    /// projections and limits at the top of the query are applied batch by
+    /// batch as the stream is pulled; the rest of the query runs before the
+    /// stream is returned.
+    pub async fn query_stream(&self, sql: &str) -> Result<QueryStream> {
+        self.executor.query_stream(sql).await
+    }
+
+    /// Returns a [`QueryHandle`] that cancels the stream along with the
+    /// future that opens it.
+    pub fn query_stream_cancellable<'a>(
+        &'a self,
+        sql: &'a str,
+    ) -> (QueryHandle, impl Future<Output = Result<QueryStream>> + 'a) {
+        self.executor.query_stream_cancellable(sql)
     }
 
     pub async fn run(&self, sql: &str) -> Result<u64> {
@@ -98,6 +170,14 @@ impl YachtSQLSession {
         Ok(table.row_count() as u64)
     }
 
+    pub fn prepare(&self, sql: &str) -> Result<PreparedStatement> {
+        self.executor.prepare(sql)
+    }
+
+    pub fn register_optimizer_rule(&self, rule: std::sync::Arc<dyn OptimizerRule>) {
+        self.executor.register_optimizer_rule(rule);
+    }
+
     pub fn session(&self) -> &ConcurrentSession {
         self.executor.session()
     }
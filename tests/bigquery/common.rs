@@ -1,3 +1,6 @@
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, TimeDelta, Utc};
 use yachtsql::{YachtSQLEngine, YachtSQLSession};
 
 #[path = "../test_helpers.rs"]
@@ -9,3 +12,38 @@ pub fn create_session() -> YachtSQLSession {
     let engine = YachtSQLEngine::new();
     engine.create_session()
 }
+
+/// A clock that moves one millisecond every time it is read, so that each
+/// commit and each mark gets a timestamp of its own.
+#[derive(Clone)]
+pub struct TestClock(Arc<Mutex<DateTime<Utc>>>);
+
+impl TestClock {
+    fn tick(&self) -> DateTime<Utc> {
+        let mut now = self.0.lock().unwrap();
+        *now += TimeDelta::milliseconds(1);
+        *now
+    }
+
+    /// Sets `variable` to a time after every commit so far and before any
+    /// later one.
+    pub async fn mark(&self, session: &YachtSQLSession, variable: &str) {
+        let micros = self.tick().timestamp_micros();
+        session
+            .execute_sql(&format!("SET {} = TIMESTAMP_MICROS({})", variable, micros))
+            .await
+            .unwrap();
+    }
+}
+
+/// A session whose table history is stamped by a [`TestClock`], for
+/// time travel tests that must not depend on the wall clock.
+pub fn create_session_with_history_clock() -> (YachtSQLSession, TestClock) {
+    let engine = YachtSQLEngine::new();
+    let clock = TestClock(Arc::new(Mutex::new(Utc::now())));
+    let ticks = clock.clone();
+    engine
+        .catalog()
+        .set_history_clock(Arc::new(move || ticks.tick()));
+    (engine.create_session(), clock)
+}
@@ -1,5 +1,5 @@
 use crate::assert_table_eq;
-use crate::common::create_session;
+use crate::common::{create_session, create_session_with_history_clock};
 
 #[tokio::test]
 async fn test_create_simple_table() {
@@ -1305,7 +1305,7 @@ async fn test_create_table_clone_isolation() {
 
 #[tokio::test]
 async fn test_create_table_clone_for_system_time() {
-    let session = create_session();
+    let (session, clock) = create_session_with_history_clock();
 
     session
         .execute_sql("CREATE TABLE time_src (id INT64, value INT64)")
@@ -1317,10 +1317,16 @@ async fn test_create_table_clone_for_system_time() {
         .await
         .unwrap();
 
+    clock.mark(&session, "@before_update").await;
+    session
+        .execute_sql("UPDATE time_src SET value = 200 WHERE id = 1")
+        .await
+        .unwrap();
+
     session
         .execute_sql(
             "CREATE TABLE time_clone CLONE time_src
-            FOR SYSTEM_TIME AS OF TIMESTAMP_SUB(CURRENT_TIMESTAMP(), INTERVAL 1 HOUR)",
+            FOR SYSTEM_TIME AS OF @before_update",
         )
         .await
         .unwrap();
@@ -1560,7 +1566,7 @@ async fn test_create_snapshot_table_if_not_exists() {
 
 #[tokio::test]
 async fn test_create_snapshot_table_for_system_time() {
-    let session = create_session();
+    let (session, clock) = create_session_with_history_clock();
 
     session
         .execute_sql("CREATE TABLE history_src (id INT64, value INT64)")
@@ -1572,14 +1578,26 @@ async fn test_create_snapshot_table_for_system_time() {
         .await
         .unwrap();
 
+    clock.mark(&session, "@before_delete").await;
+    session
+        .execute_sql("DELETE FROM history_src WHERE TRUE")
+        .await
+        .unwrap();
+
     session
         .execute_sql(
             "CREATE SNAPSHOT TABLE historical_snap
             CLONE history_src
-            FOR SYSTEM_TIME AS OF TIMESTAMP_SUB(CURRENT_TIMESTAMP(), INTERVAL 1 HOUR)",
+            FOR SYSTEM_TIME AS OF @before_delete",
         )
         .await
         .unwrap();
+
+    let result = session
+        .execute_sql("SELECT id, value FROM historical_snap")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1, 100]]);
 }
 
 #[tokio::test]
@@ -1,5 +1,5 @@
 use crate::assert_table_eq;
-use crate::common::create_session;
+use crate::common::{create_session, create_session_with_history_clock};
 
 #[tokio::test]
 async fn test_create_snapshot_table() {
@@ -66,7 +66,7 @@ async fn test_create_snapshot_with_expiration() {
 
 #[tokio::test]
 async fn test_create_snapshot_for_system_time() {
-    let session = create_session();
+    let (session, clock) = create_session_with_history_clock();
     session
         .execute_sql("CREATE TABLE time_travel_source (id INT64, value INT64)")
         .await
@@ -75,15 +75,49 @@ async fn test_create_snapshot_for_system_time() {
         .execute_sql("INSERT INTO time_travel_source VALUES (1, 100)")
         .await
         .unwrap();
+    clock.mark(&session, "@before_update").await;
+    session
+        .execute_sql("UPDATE time_travel_source SET value = 200 WHERE id = 1")
+        .await
+        .unwrap();
 
-    let result = session
+    session
         .execute_sql(
             "CREATE SNAPSHOT TABLE time_snapshot
         CLONE time_travel_source
+        FOR SYSTEM_TIME AS OF @before_update",
+        )
+        .await
+        .unwrap();
+
+    let result = session
+        .execute_sql("SELECT id, value FROM time_snapshot")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1, 100]]);
+}
+
+#[tokio::test]
+async fn test_create_snapshot_before_table_existed() {
+    let session = create_session();
+    session
+        .execute_sql("CREATE TABLE young_source (id INT64)")
+        .await
+        .unwrap();
+
+    let result = session
+        .execute_sql(
+            "CREATE SNAPSHOT TABLE young_snapshot
+        CLONE young_source
         FOR SYSTEM_TIME AS OF TIMESTAMP_SUB(CURRENT_TIMESTAMP(), INTERVAL 1 HOUR)",
         )
         .await;
-    assert!(result.is_ok());
+    assert!(
+        result
+            .unwrap_err()
+            .to_string()
+            .contains("Invalid snapshot time")
+    );
 }
 
 #[tokio::test]
@@ -339,3 +373,135 @@ async fn test_snapshot_clustered_table() {
         .await;
     assert!(result.is_ok());
 }
+
+#[tokio::test]
+async fn test_snapshot_rejects_dml() {
+    let session = create_session();
+    session
+        .execute_sql("CREATE TABLE guarded (id INT64, value INT64)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO guarded VALUES (1, 10)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("CREATE SNAPSHOT TABLE guarded_snap CLONE guarded")
+        .await
+        .unwrap();
+
+    for sql in [
+        "INSERT INTO guarded_snap VALUES (2, 20)",
+        "UPDATE guarded_snap SET value = 0 WHERE TRUE",
+        "DELETE FROM guarded_snap WHERE TRUE",
+        "TRUNCATE TABLE guarded_snap",
+    ] {
+        let err = session.execute_sql(sql).await.unwrap_err();
+        assert!(err.to_string().contains("read-only"), "{sql}: {err}");
+    }
+
+    let result = session
+        .execute_sql("SELECT id, value FROM guarded_snap")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1, 10]]);
+}
+
+#[tokio::test]
+async fn test_clone_of_snapshot_is_writable() {
+    let session = create_session();
+    session
+        .execute_sql("CREATE TABLE backup_src (id INT64)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO backup_src VALUES (1), (2)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("CREATE SNAPSHOT TABLE backup_snap CLONE backup_src")
+        .await
+        .unwrap();
+    session
+        .execute_sql("CREATE TABLE restored CLONE backup_snap")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO restored VALUES (3)")
+        .await
+        .unwrap();
+
+    let result = session
+        .execute_sql("SELECT id FROM restored ORDER BY id")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1], [2], [3]]);
+
+    let result = session
+        .execute_sql("SELECT COUNT(*) FROM backup_snap")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[2]]);
+}
+
+#[tokio::test]
+async fn test_drop_snapshot_rejects_regular_table() {
+    let session = create_session();
+    session
+        .execute_sql("CREATE TABLE plain_table (id INT64)")
+        .await
+        .unwrap();
+
+    let err = session
+        .execute_sql("DROP SNAPSHOT TABLE plain_table")
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("not a table snapshot"));
+}
+
+#[tokio::test]
+async fn test_information_schema_table_snapshots() {
+    let session = create_session();
+    session.execute_sql("CREATE SCHEMA backups").await.unwrap();
+    session
+        .execute_sql("CREATE TABLE backups.orders (id INT64)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("CREATE SNAPSHOT TABLE backups.orders_snap CLONE backups.orders")
+        .await
+        .unwrap();
+    session
+        .execute_sql("CREATE TABLE backups.orders_clone CLONE backups.orders")
+        .await
+        .unwrap();
+
+    let result = session
+        .execute_sql(
+            "SELECT table_schema, table_name, base_table_schema, base_table_name
+            FROM backups.INFORMATION_SCHEMA.TABLE_SNAPSHOTS",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(result, [["backups", "orders_snap", "backups", "orders"]]);
+
+    let result = session
+        .execute_sql(
+            "SELECT COUNT(*)
+            FROM `region-us`.INFORMATION_SCHEMA.TABLE_SNAPSHOTS
+            WHERE snapshot_time <= CURRENT_TIMESTAMP()",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1]]);
+
+    session
+        .execute_sql("DROP SNAPSHOT TABLE backups.orders_snap")
+        .await
+        .unwrap();
+    let result = session
+        .execute_sql("SELECT COUNT(*) FROM backups.INFORMATION_SCHEMA.TABLE_SNAPSHOTS")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[0]]);
+}
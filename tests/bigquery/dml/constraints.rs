@@ -0,0 +1,362 @@
+use yachtsql::YachtSQLSession;
+
+use crate::assert_table_eq;
+use crate::common::create_session;
+
+async fn strict_session() -> YachtSQLSession {
+    let session = create_session();
+    session
+        .execute_sql("SET @@yachtsql.enforce_constraints = TRUE")
+        .await
+        .unwrap();
+    session
+}
+
+async fn exec_err(session: &YachtSQLSession, sql: &str) -> String {
+    session.execute_sql(sql).await.unwrap_err().to_string()
+}
+
+#[tokio::test]
+async fn test_constraints_not_enforced_by_default() {
+    let session = create_session();
+    session
+        .execute_sql(
+            "CREATE TABLE accounts (id INT64, email STRING, balance INT64,
+                PRIMARY KEY (id) NOT ENFORCED,
+                CONSTRAINT positive CHECK (balance >= 0))",
+        )
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO accounts VALUES (1, 'a', 10), (1, 'b', -5)")
+        .await
+        .unwrap();
+
+    let result = session
+        .execute_sql("SELECT COUNT(*) FROM accounts")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[2]]);
+}
+
+#[tokio::test]
+async fn test_primary_key_on_insert() {
+    let session = strict_session().await;
+    session
+        .execute_sql(
+            "CREATE TABLE accounts (id INT64, email STRING, CONSTRAINT pk_accounts PRIMARY KEY (id) NOT ENFORCED)",
+        )
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO accounts VALUES (1, 'a'), (2, 'b')")
+        .await
+        .unwrap();
+
+    let err = exec_err(&session, "INSERT INTO accounts VALUES (3, 'c'), (1, 'd')").await;
+    assert!(err.contains("pk_accounts"), "{}", err);
+    assert!(err.contains("duplicate key (1)"), "{}", err);
+
+    let err = exec_err(&session, "INSERT INTO accounts VALUES (NULL, 'e')").await;
+    assert!(err.contains("pk_accounts"), "{}", err);
+    assert!(err.contains("cannot be NULL"), "{}", err);
+
+    let result = session
+        .execute_sql("SELECT id FROM accounts ORDER BY id")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1], [2]]);
+}
+
+#[tokio::test]
+async fn test_unique_on_update() {
+    let session = strict_session().await;
+    session
+        .execute_sql("CREATE TABLE users (id INT64 PRIMARY KEY NOT ENFORCED, email STRING UNIQUE)")
+        .await
+        .unwrap();
+    session
+        .execute_sql(
+            "INSERT INTO users VALUES (1, 'a@x.com'), (2, 'b@x.com'), (3, NULL), (4, NULL)",
+        )
+        .await
+        .unwrap();
+
+    let err = exec_err(&session, "UPDATE users SET email = 'a@x.com' WHERE id = 2").await;
+    assert!(
+        err.contains("UNIQUE (email) constraint on users"),
+        "{}",
+        err
+    );
+
+    let result = session
+        .execute_sql("SELECT email FROM users WHERE id = 2")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [["b@x.com"]]);
+}
+
+#[tokio::test]
+async fn test_check_on_insert_and_update() {
+    let session = strict_session().await;
+    session
+        .execute_sql(
+            "CREATE TABLE accounts (id INT64, balance INT64, CONSTRAINT non_negative CHECK (balance >= 0))",
+        )
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO accounts VALUES (1, 10), (2, NULL)")
+        .await
+        .unwrap();
+
+    let err = exec_err(&session, "INSERT INTO accounts VALUES (3, -1)").await;
+    assert!(err.contains("non_negative"), "{}", err);
+    assert!(err.contains("violated by row (3, -1)"), "{}", err);
+
+    let err = exec_err(&session, "UPDATE accounts SET balance = balance - 20").await;
+    assert!(err.contains("non_negative"), "{}", err);
+
+    let result = session
+        .execute_sql("SELECT id, balance FROM accounts ORDER BY id")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1, 10], [2, null]]);
+}
+
+#[tokio::test]
+async fn test_foreign_key_on_insert_and_merge() {
+    let session = strict_session().await;
+    session
+        .execute_sql("CREATE TABLE customers (id INT64, PRIMARY KEY (id) NOT ENFORCED)")
+        .await
+        .unwrap();
+    session
+        .execute_sql(
+            "CREATE TABLE orders (id INT64, customer_id INT64,
+                CONSTRAINT fk_customer FOREIGN KEY (customer_id) REFERENCES customers(id) NOT ENFORCED)",
+        )
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO customers VALUES (1), (2)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO orders VALUES (10, 1), (11, NULL)")
+        .await
+        .unwrap();
+
+    let err = exec_err(&session, "INSERT INTO orders VALUES (12, 3)").await;
+    assert!(err.contains("fk_customer"), "{}", err);
+    assert!(
+        err.contains("key (3) not found in customers(id)"),
+        "{}",
+        err
+    );
+
+    let err = exec_err(
+        &session,
+        "MERGE orders t USING (SELECT 10 AS id, 5 AS customer_id) s ON t.id = s.id
+         WHEN MATCHED THEN UPDATE SET customer_id = s.customer_id",
+    )
+    .await;
+    assert!(err.contains("fk_customer"), "{}", err);
+
+    session
+        .execute_sql(
+            "MERGE orders t USING (SELECT 10 AS id, 2 AS customer_id) s ON t.id = s.id
+             WHEN MATCHED THEN UPDATE SET customer_id = s.customer_id",
+        )
+        .await
+        .unwrap();
+    let result = session
+        .execute_sql("SELECT id, customer_id FROM orders ORDER BY id")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[10, 2], [11, null]]);
+}
+
+#[tokio::test]
+async fn test_foreign_key_on_delete_and_parent_update() {
+    let session = strict_session().await;
+    session
+        .execute_sql(
+            "CREATE TABLE customers (id INT64, name STRING, PRIMARY KEY (id) NOT ENFORCED)",
+        )
+        .await
+        .unwrap();
+    session
+        .execute_sql(
+            "CREATE TABLE orders (id INT64, customer_id INT64,
+                CONSTRAINT fk_customer FOREIGN KEY (customer_id) REFERENCES customers(id) NOT ENFORCED)",
+        )
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO customers VALUES (1, 'a'), (2, 'b'), (3, 'c')")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO orders VALUES (10, 1), (11, 2)")
+        .await
+        .unwrap();
+
+    let err = exec_err(&session, "DELETE FROM customers WHERE id = 1").await;
+    assert!(err.contains("fk_customer"), "{}", err);
+    assert!(
+        err.contains("key (1) removed from customers is still referenced"),
+        "{}",
+        err
+    );
+    let err = exec_err(&session, "UPDATE customers SET id = 5 WHERE id = 2").await;
+    assert!(err.contains("fk_customer"), "{}", err);
+
+    session
+        .execute_sql("UPDATE customers SET name = 'z' WHERE id = 2")
+        .await
+        .unwrap();
+    session
+        .execute_sql("UPDATE customers SET id = 6 WHERE id = 3")
+        .await
+        .unwrap();
+    session
+        .execute_sql("DELETE FROM customers WHERE id = 6")
+        .await
+        .unwrap();
+    session
+        .execute_sql("DELETE FROM orders WHERE id = 10")
+        .await
+        .unwrap();
+    session
+        .execute_sql("DELETE FROM customers WHERE id = 1")
+        .await
+        .unwrap();
+
+    let result = session
+        .execute_sql("SELECT id, name FROM customers ORDER BY id")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[2, "z"]]);
+}
+
+#[tokio::test]
+async fn test_writes_checked_against_existing_keys() {
+    let session = strict_session().await;
+    session
+        .execute_sql("CREATE TABLE ids (id INT64, PRIMARY KEY (id) NOT ENFORCED)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO ids SELECT n FROM UNNEST(GENERATE_ARRAY(1, 20000)) AS n")
+        .await
+        .unwrap();
+
+    let err = exec_err(&session, "INSERT INTO ids VALUES (20001), (5)").await;
+    assert!(err.contains("duplicate key (5)"), "{}", err);
+    let err = exec_err(&session, "UPDATE ids SET id = 19999 WHERE id = 3").await;
+    assert!(err.contains("duplicate key (19999)"), "{}", err);
+
+    session
+        .execute_sql("INSERT INTO ids VALUES (20001)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("UPDATE ids SET id = 0 WHERE id = 3")
+        .await
+        .unwrap();
+    let result = session
+        .execute_sql("SELECT COUNT(*), MIN(id), MAX(id) FROM ids")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[20001, 0, 20001]]);
+}
+
+#[tokio::test]
+async fn test_alter_table_constraints() {
+    let session = strict_session().await;
+    session
+        .execute_sql("CREATE TABLE items (id INT64, sku STRING)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO items VALUES (1, 'a'), (2, 'a')")
+        .await
+        .unwrap();
+
+    let err = exec_err(
+        &session,
+        "ALTER TABLE items ADD CONSTRAINT unique_sku UNIQUE (sku)",
+    )
+    .await;
+    assert!(err.contains("unique_sku"), "{}", err);
+
+    session
+        .execute_sql("ALTER TABLE items ADD PRIMARY KEY (id) NOT ENFORCED")
+        .await
+        .unwrap();
+    let err = exec_err(&session, "INSERT INTO items VALUES (1, 'b')").await;
+    assert!(
+        err.contains("PRIMARY KEY (id) constraint on items"),
+        "{}",
+        err
+    );
+
+    session
+        .execute_sql("ALTER TABLE items DROP PRIMARY KEY")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO items VALUES (1, 'b')")
+        .await
+        .unwrap();
+
+    session
+        .execute_sql("ALTER TABLE items ADD CONSTRAINT sku_set CHECK (sku IS NOT NULL)")
+        .await
+        .unwrap();
+    let err = exec_err(&session, "INSERT INTO items VALUES (3, NULL)").await;
+    assert!(err.contains("sku_set"), "{}", err);
+    session
+        .execute_sql("ALTER TABLE items DROP CONSTRAINT sku_set")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO items VALUES (3, NULL)")
+        .await
+        .unwrap();
+
+    let err = exec_err(&session, "ALTER TABLE items DROP CONSTRAINT sku_set").await;
+    assert!(err.contains("not found"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_enforcement_can_be_turned_off() {
+    let session = strict_session().await;
+    session
+        .execute_sql("CREATE TABLE t (id INT64 PRIMARY KEY NOT ENFORCED)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO t VALUES (1)")
+        .await
+        .unwrap();
+    assert!(
+        session
+            .execute_sql("INSERT INTO t VALUES (1)")
+            .await
+            .is_err()
+    );
+
+    session
+        .execute_sql("SET @@yachtsql.enforce_constraints = FALSE")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO t VALUES (1)")
+        .await
+        .unwrap();
+
+    let result = session.execute_sql("SELECT COUNT(*) FROM t").await.unwrap();
+    assert_table_eq!(result, [[2]]);
+}
@@ -1,7 +1,9 @@
+mod constraints;
 mod delete;
 mod export_load;
 mod insert;
 mod merge;
+mod segments;
 mod select_basic;
 mod truncate;
 mod update;
@@ -0,0 +1,173 @@
+use yachtsql::YachtSQLSession;
+
+use crate::assert_table_eq;
+use crate::common::create_session;
+
+async fn setup_large_table(session: &YachtSQLSession) {
+    session
+        .execute_sql("CREATE TABLE events (id INT64, kind STRING, amount INT64)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO events (id) SELECT n FROM UNNEST(GENERATE_ARRAY(1, 20000)) AS n")
+        .await
+        .unwrap();
+    session
+        .execute_sql("UPDATE events SET kind = IF(MOD(id, 2) = 0, 'even', 'odd'), amount = id * 10 WHERE TRUE")
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_delete_across_segments() {
+    let session = create_session();
+    setup_large_table(&session).await;
+
+    session
+        .execute_sql("DELETE FROM events WHERE kind = 'odd' OR id > 19990")
+        .await
+        .unwrap();
+
+    let result = session
+        .execute_sql("SELECT COUNT(*), MIN(id), MAX(id), SUM(amount) FROM events")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[9995, 2, 19990, 999_100_200]]);
+
+    session
+        .execute_sql("DELETE FROM events WHERE id BETWEEN 8000 AND 9000")
+        .await
+        .unwrap();
+    let result = session
+        .execute_sql("SELECT id FROM events WHERE id BETWEEN 7996 AND 9004 ORDER BY id")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[7996], [7998], [9002], [9004]]);
+}
+
+#[tokio::test]
+async fn test_update_across_segments() {
+    let session = create_session();
+    setup_large_table(&session).await;
+
+    session
+        .execute_sql("UPDATE events SET amount = -1 WHERE MOD(id, 5000) = 0")
+        .await
+        .unwrap();
+
+    let result = session
+        .execute_sql("SELECT id, kind, amount FROM events WHERE amount < 0 ORDER BY id")
+        .await
+        .unwrap();
+    assert_table_eq!(
+        result,
+        [
+            [5000, "even", -1],
+            [10000, "even", -1],
+            [15000, "even", -1],
+            [20000, "even", -1],
+        ]
+    );
+
+    let result = session
+        .execute_sql("SELECT COUNT(*), SUM(amount) FROM events WHERE amount > 0")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[19996, 1_999_600_000]]);
+}
+
+#[tokio::test]
+async fn test_update_after_delete_targets_live_rows() {
+    let session = create_session();
+    setup_large_table(&session).await;
+
+    session
+        .execute_sql("DELETE FROM events WHERE id <= 100 OR id BETWEEN 9000 AND 9100")
+        .await
+        .unwrap();
+    session
+        .execute_sql("UPDATE events SET kind = 'edge' WHERE id IN (101, 8999, 9101, 20000)")
+        .await
+        .unwrap();
+
+    let result = session
+        .execute_sql("SELECT id FROM events WHERE kind = 'edge' ORDER BY id")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[101], [8999], [9101], [20000]]);
+
+    let result = session
+        .execute_sql("SELECT COUNT(*) FROM events")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[19799]]);
+}
+
+#[tokio::test]
+async fn test_merge_across_segments() {
+    let session = create_session();
+    setup_large_table(&session).await;
+    session
+        .execute_sql("CREATE TABLE changes (id INT64, amount INT64)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO changes VALUES (1, 0), (12000, 7), (30000, 3)")
+        .await
+        .unwrap();
+
+    session
+        .execute_sql(
+            "MERGE INTO events t USING changes s ON t.id = s.id
+            WHEN MATCHED AND s.amount = 0 THEN DELETE
+            WHEN MATCHED THEN UPDATE SET amount = s.amount
+            WHEN NOT MATCHED THEN INSERT (id, kind, amount) VALUES (s.id, 'new', s.amount)",
+        )
+        .await
+        .unwrap();
+
+    let result = session
+        .execute_sql(
+            "SELECT id, kind, amount FROM events WHERE id IN (1, 12000, 30000) ORDER BY id",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[12000, "even", 7], [30000, "new", 3]]);
+
+    let result = session
+        .execute_sql("SELECT COUNT(*) FROM events")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[20000]]);
+}
+
+#[tokio::test]
+async fn test_snapshot_unaffected_by_large_update_and_delete() {
+    let session = create_session();
+    setup_large_table(&session).await;
+    session
+        .execute_sql("CREATE SNAPSHOT TABLE events_snap CLONE events")
+        .await
+        .unwrap();
+
+    session
+        .execute_sql("UPDATE events SET amount = 0 WHERE id > 10000")
+        .await
+        .unwrap();
+    session
+        .execute_sql("DELETE FROM events WHERE id <= 5000")
+        .await
+        .unwrap();
+
+    let result = session
+        .execute_sql("SELECT COUNT(*), SUM(amount) FROM events_snap")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[20000, 2_000_100_000]]);
+
+    let result = session
+        .execute_sql("SELECT COUNT(*), SUM(amount) FROM events")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[15000, 375_025_000]]);
+}
@@ -0,0 +1,179 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use yachtsql::{Error, YachtSQLSession};
+
+use crate::assert_table_eq;
+use crate::common::create_session;
+
+const HUGE_CROSS_JOIN: &str = "SELECT COUNT(*) FROM numbers a CROSS JOIN numbers b";
+
+async fn setup_table(session: &YachtSQLSession) {
+    session
+        .execute_sql("CREATE TABLE numbers (id INT64)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO numbers (id) SELECT n FROM UNNEST(GENERATE_ARRAY(1, 50000)) AS n")
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_cancel_running_query() {
+    let session = create_session();
+    setup_table(&session).await;
+
+    let (handle, query) = session.execute_cancellable(HUGE_CROSS_JOIN);
+    let canceller = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(100));
+        handle.cancel();
+    });
+    let start = Instant::now();
+    let err = query.await.unwrap_err();
+    canceller.join().unwrap();
+
+    assert!(matches!(err, Error::Cancelled), "{}", err);
+    assert!(start.elapsed() < Duration::from_secs(30));
+
+    let result = session
+        .execute_sql("SELECT COUNT(*) FROM numbers")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[50000]]);
+}
+
+#[tokio::test]
+async fn test_cancel_before_start() {
+    let session = create_session();
+    setup_table(&session).await;
+
+    let (handle, query) = session.execute_cancellable("SELECT COUNT(*) FROM numbers");
+    handle.cancel();
+    assert!(handle.is_cancelled());
+    assert!(matches!(query.await, Err(Error::Cancelled)));
+
+    let (handle, query) = session.execute_cancellable("SELECT MAX(id) FROM numbers");
+    let result = query.await.unwrap();
+    assert!(!handle.is_cancelled());
+    assert_table_eq!(result, [[50000]]);
+}
+
+#[tokio::test]
+async fn test_query_timeout() {
+    let session = create_session();
+    setup_table(&session).await;
+
+    session
+        .execute_sql("SET @@query_timeout_ms = 200")
+        .await
+        .unwrap();
+    let err = session.execute_sql(HUGE_CROSS_JOIN).await.unwrap_err();
+    assert!(matches!(err, Error::Timeout(_)), "{}", err);
+    assert!(err.to_string().contains("200 ms"), "{}", err);
+
+    let result = session
+        .execute_sql("SELECT COUNT(*) FROM numbers WHERE id <= 10")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[10]]);
+
+    session
+        .execute_sql("SET @@query_timeout_ms = 0")
+        .await
+        .unwrap();
+    let result = session
+        .execute_sql("SELECT COUNT(*) FROM numbers a CROSS JOIN (SELECT 1 UNION ALL SELECT 2) b")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[100000]]);
+}
+
+#[tokio::test]
+async fn test_query_timeout_stops_joins_and_windows() {
+    let session = create_session();
+    setup_table(&session).await;
+
+    session
+        .execute_sql("SET @@query_timeout_ms = 200")
+        .await
+        .unwrap();
+    for sql in [
+        "SELECT COUNT(*) FROM numbers a JOIN numbers b ON MOD(a.id, 10) = MOD(b.id, 10)",
+        "SELECT MAX(s) FROM (SELECT SUM(id) OVER (ORDER BY id ROWS BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW) AS s FROM numbers)",
+        "SELECT COUNT(*) FROM (SELECT MOD(a.id, 1000) AS k FROM numbers a CROSS JOIN (SELECT 1 FROM numbers LIMIT 2000) b GROUP BY k)",
+    ] {
+        let err = session.execute_sql(sql).await.unwrap_err();
+        assert!(matches!(err, Error::Timeout(_)), "{}: {}", sql, err);
+    }
+}
+
+#[tokio::test]
+async fn test_timeout_stops_script_loops() {
+    let session = create_session();
+    session
+        .execute_sql("DECLARE i INT64 DEFAULT 0")
+        .await
+        .unwrap();
+    session
+        .execute_sql("DECLARE caught BOOL DEFAULT FALSE")
+        .await
+        .unwrap();
+    session
+        .execute_sql("SET @@query_timeout_ms = 50")
+        .await
+        .unwrap();
+
+    let err = session
+        .execute_sql(
+            "BEGIN
+                WHILE TRUE DO
+                    SET i = (SELECT COUNT(*) FROM UNNEST(GENERATE_ARRAY(1, 1000)));
+                END WHILE;
+            EXCEPTION WHEN ERROR THEN
+                SET caught = TRUE;
+            END",
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Timeout(_)), "{}", err);
+
+    let err = session
+        .execute_sql(
+            "LOOP
+                SET i = (SELECT COUNT(*) FROM UNNEST(GENERATE_ARRAY(1, 1000)));
+            END LOOP",
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Timeout(_)), "{}", err);
+
+    session
+        .execute_sql("SET @@query_timeout_ms = 0")
+        .await
+        .unwrap();
+    let result = session.execute_sql("SELECT caught").await.unwrap();
+    assert_table_eq!(result, [[false]]);
+}
+
+#[tokio::test]
+async fn test_cancel_recursive_cte() {
+    let session = create_session();
+    setup_table(&session).await;
+
+    let (handle, query) = session.execute_cancellable(
+        "WITH RECURSIVE t AS (
+            SELECT id AS n FROM numbers
+            UNION ALL
+            SELECT n + 1 FROM t WHERE n < 1000000
+        )
+        SELECT COUNT(*) FROM t",
+    );
+    let canceller = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        handle.cancel();
+    });
+    let err = query.await.unwrap_err();
+    canceller.join().unwrap();
+    assert!(matches!(err, Error::Cancelled), "{}", err);
+}
@@ -0,0 +1,145 @@
+use crate::assert_table_eq;
+use crate::common::create_session;
+
+async fn setup_orders(session: &yachtsql::YachtSQLSession) {
+    session
+        .execute_sql(
+            "CREATE TABLE orders (id INT64, customer STRING, amount INT64, status STRING, note STRING)",
+        )
+        .await
+        .unwrap();
+    session
+        .execute_sql(
+            "INSERT INTO orders VALUES
+            (1, 'alice', 30, 'paid', 'first'),
+            (2, 'bob', 10, 'open', NULL),
+            (3, 'alice', 25, 'open', 'rush'),
+            (4, 'carol', 40, 'paid', NULL),
+            (5, 'bob', 5, 'paid', 'gift')",
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_select_subset_filtered_on_unselected_column() {
+    let session = create_session();
+    setup_orders(&session).await;
+
+    let result = session
+        .execute_sql("SELECT customer FROM orders WHERE status = 'paid' AND amount > 6 ORDER BY id")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [["alice"], ["carol"]]);
+}
+
+#[tokio::test]
+async fn test_select_columns_out_of_table_order() {
+    let session = create_session();
+    setup_orders(&session).await;
+
+    let result = session
+        .execute_sql("SELECT note, id, amount * 2 FROM orders WHERE note IS NOT NULL")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [["first", 1, 60], ["rush", 3, 50], ["gift", 5, 10]]);
+}
+
+#[tokio::test]
+async fn test_count_star_without_columns() {
+    let session = create_session();
+    setup_orders(&session).await;
+
+    let result = session
+        .execute_sql("SELECT COUNT(*) FROM orders")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[5]]);
+
+    let result = session
+        .execute_sql("SELECT COUNT(*), COUNT(note) FROM orders WHERE status = 'open'")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[2, 1]]);
+}
+
+#[tokio::test]
+async fn test_group_by_reads_grouped_and_aggregated_columns() {
+    let session = create_session();
+    setup_orders(&session).await;
+
+    let result = session
+        .execute_sql(
+            "SELECT customer, SUM(amount) FROM orders WHERE status = 'paid' GROUP BY customer ORDER BY customer",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(result, [["alice", 30], ["bob", 5], ["carol", 40]]);
+}
+
+#[tokio::test]
+async fn test_correlated_subquery_sees_outer_columns() {
+    let session = create_session();
+    setup_orders(&session).await;
+
+    let result = session
+        .execute_sql(
+            "SELECT id FROM orders o
+            WHERE amount = (SELECT MAX(amount) FROM orders i WHERE i.customer = o.customer)
+            ORDER BY id",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1], [2], [4]]);
+}
+
+#[tokio::test]
+async fn test_cte_and_join_with_narrow_selects() {
+    let session = create_session();
+    setup_orders(&session).await;
+    session
+        .execute_sql("CREATE TABLE customers (name STRING, region STRING, tier INT64)")
+        .await
+        .unwrap();
+    session
+        .execute_sql(
+            "INSERT INTO customers VALUES ('alice', 'north', 1), ('bob', 'south', 2), ('carol', 'north', 3)",
+        )
+        .await
+        .unwrap();
+
+    let result = session
+        .execute_sql(
+            "WITH paid AS (SELECT customer, amount FROM orders WHERE status = 'paid')
+            SELECT c.region, SUM(p.amount)
+            FROM paid p JOIN customers c ON p.customer = c.name
+            GROUP BY c.region
+            ORDER BY c.region",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(result, [["north", 70], ["south", 5]]);
+}
+
+#[tokio::test]
+async fn test_narrow_select_after_schema_change() {
+    let session = create_session();
+    setup_orders(&session).await;
+
+    let result = session
+        .execute_sql("SELECT amount FROM orders WHERE id = 2")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[10]]);
+
+    session
+        .execute_sql("ALTER TABLE orders DROP COLUMN customer")
+        .await
+        .unwrap();
+
+    let result = session
+        .execute_sql("SELECT amount FROM orders WHERE id = 2")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[10]]);
+}
@@ -0,0 +1,165 @@
+use yachtsql::{Table, YachtSQLSession};
+
+use crate::assert_table_eq;
+use crate::common::create_session;
+
+async fn setup_tables(session: &YachtSQLSession) {
+    session
+        .execute_sql("CREATE TABLE customers (id INT64, name STRING)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO customers VALUES (1, 'alice'), (2, 'bob'), (3, 'carol')")
+        .await
+        .unwrap();
+    session
+        .execute_sql("CREATE TABLE orders (id INT64, customer_id INT64, amount INT64)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO orders VALUES (10, 1, 30), (11, 1, 5), (12, 2, 50), (13, 9, 1)")
+        .await
+        .unwrap();
+}
+
+fn plan_lines(result: &Table) -> Vec<String> {
+    (0..result.row_count())
+        .map(|i| {
+            result.get_row(i).unwrap().values()[0]
+                .as_str()
+                .unwrap()
+                .to_string()
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_explain_shows_operator_tree() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let result = session
+        .execute_sql(
+            "EXPLAIN SELECT c.name, o.amount FROM customers c JOIN orders o ON c.id = o.customer_id WHERE o.amount > 10",
+        )
+        .await
+        .unwrap();
+    let lines = plan_lines(&result);
+
+    assert!(lines[0].starts_with("Project"));
+    let join = lines.iter().position(|l| l.contains("HashJoin")).unwrap();
+    assert!(lines[join].contains("Inner"));
+    assert!(lines[join + 1..].iter().all(|l| l.starts_with("    ")));
+    assert!(
+        lines
+            .iter()
+            .any(|l| l.trim_start().starts_with("TableScan: customers"))
+    );
+    assert!(lines.iter().any(|l| l.contains("(o.amount > 10)")));
+    assert!(lines.iter().all(|l| l.contains("est_rows=")));
+    assert!(lines.iter().all(|l| !l.contains("actual")));
+}
+
+#[tokio::test]
+async fn test_explain_format_json() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let result = session
+        .execute_sql("EXPLAIN FORMAT JSON SELECT name FROM customers ORDER BY name LIMIT 2")
+        .await
+        .unwrap();
+    let lines = plan_lines(&result);
+    assert_eq!(lines.len(), 1);
+
+    let plan: serde_json::Value = serde_json::from_str(&lines[0]).unwrap();
+    assert!(plan["estimated_rows"].is_u64());
+    assert!(plan.get("actual").is_none());
+    let mut node = &plan;
+    let mut operators = vec![node["operator"].as_str().unwrap()];
+    while let Some(children) = node["children"].as_array() {
+        node = &children[0];
+        operators.push(node["operator"].as_str().unwrap());
+    }
+    assert_eq!(operators.last(), Some(&"TableScan"));
+    assert!(operators.contains(&"TopN"));
+}
+
+#[tokio::test]
+async fn test_explain_analyze_reports_actual_rows() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let result = session
+        .execute_sql(
+            "EXPLAIN ANALYZE SELECT c.name FROM customers c JOIN orders o ON c.id = o.customer_id",
+        )
+        .await
+        .unwrap();
+    let lines = plan_lines(&result);
+
+    let join = lines.iter().find(|l| l.contains("HashJoin")).unwrap();
+    assert!(join.contains("rows_in=7"), "{}", join);
+    assert!(join.contains("rows_out=3"), "{}", join);
+    assert!(join.contains("loops=1"));
+    assert!(join.contains("peak_memory="));
+    assert!(join.contains("parallel=false"));
+    let scan = lines
+        .iter()
+        .find(|l| l.contains("TableScan: orders"))
+        .unwrap();
+    assert!(scan.contains("rows_in=0 rows_out=4"), "{}", scan);
+}
+
+#[tokio::test]
+async fn test_explain_analyze_format_json() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let result = session
+        .execute_sql(
+            "EXPLAIN ANALYZE FORMAT JSON SELECT customer_id, SUM(amount) FROM orders GROUP BY customer_id",
+        )
+        .await
+        .unwrap();
+    let plan: serde_json::Value = serde_json::from_str(&plan_lines(&result)[0]).unwrap();
+
+    let mut node = &plan;
+    while node["operator"] != "HashAggregate" {
+        node = &node["children"][0];
+    }
+    assert_eq!(node["actual"]["rows_in"], 4);
+    assert_eq!(node["actual"]["rows_out"], 3);
+    assert_eq!(node["actual"]["loops"], 1);
+    assert!(node["actual"]["time_ms"].is_f64());
+    assert!(node["actual"]["peak_memory_bytes"].as_u64().unwrap() > 0);
+}
+
+#[tokio::test]
+async fn test_explain_does_not_execute_statement() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let result = session
+        .execute_sql("EXPLAIN DELETE FROM orders WHERE amount > 10")
+        .await
+        .unwrap();
+    assert!(plan_lines(&result)[0].starts_with("Delete: orders"));
+
+    let result = session
+        .execute_sql("SELECT COUNT(*) FROM orders")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[4]]);
+}
+
+#[tokio::test]
+async fn test_explain_unsupported_format() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let result = session
+        .execute_sql("EXPLAIN FORMAT GRAPHVIZ SELECT * FROM customers")
+        .await;
+    assert!(result.is_err());
+}
@@ -1,4 +1,5 @@
-use crate::common::{batch, create_session};
+use crate::assert_table_eq;
+use crate::common::create_session;
 
 async fn setup_test_schema(session: &yachtsql::YachtSQLSession) {
     session.execute_sql("CREATE SCHEMA test_schema").await.unwrap();
@@ -1537,3 +1538,36 @@ async fn test_information_schema_jobs_timeline_by_organization() {
         .unwrap();
     assert_table_eq!(result, [[true]]);
 }
+
+#[tokio::test]
+async fn test_information_schema_table_options_follow_alter() {
+    let session = create_session();
+    setup_test_schema(&session).await;
+    session
+        .execute_sql(
+            "CREATE TABLE test_schema.events (id INT64)
+            OPTIONS (description = 'Raw events', friendly_name = 'Events')",
+        )
+        .await
+        .unwrap();
+    session
+        .execute_sql(
+            "ALTER TABLE test_schema.events
+            SET OPTIONS (description = 'Cleaned events', friendly_name = NULL)",
+        )
+        .await
+        .unwrap();
+
+    let result = session
+        .execute_sql(
+            "SELECT table_name, option_name, option_type, option_value
+            FROM test_schema.INFORMATION_SCHEMA.TABLE_OPTIONS
+            ORDER BY table_name, option_name",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(
+        result,
+        [["events", "description", "STRING", "'Cleaned events'"]]
+    );
+}
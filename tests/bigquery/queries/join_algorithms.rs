@@ -0,0 +1,336 @@
+use yachtsql::{Table, YachtSQLSession};
+
+use crate::assert_table_eq;
+use crate::common::create_session;
+
+async fn setup_tables(session: &YachtSQLSession) {
+    session
+        .execute_sql("CREATE TABLE l (id INT64, k INT64, name STRING)")
+        .await
+        .unwrap();
+    session
+        .execute_sql(
+            "INSERT INTO l VALUES (1, 10, 'a'), (2, 20, 'b'), (3, NULL, 'c'), (4, 40, 'd')",
+        )
+        .await
+        .unwrap();
+    session
+        .execute_sql("CREATE TABLE r (k INT64, v INT64)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO r VALUES (10, 100), (10, 101), (30, 300), (NULL, 900)")
+        .await
+        .unwrap();
+}
+
+async fn explain(session: &YachtSQLSession, sql: &str) -> String {
+    let result: Table = session
+        .execute_sql(&format!("EXPLAIN {}", sql))
+        .await
+        .unwrap();
+    (0..result.row_count())
+        .map(|i| {
+            result.get_row(i).unwrap().values()[0]
+                .as_str()
+                .unwrap()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[tokio::test]
+async fn test_left_hash_join_keeps_unmatched_and_null_keys() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let sql = "SELECT l.id, r.v FROM l LEFT JOIN r ON l.k = r.k ORDER BY l.id, r.v";
+    assert!(explain(&session, sql).await.contains("HashJoin: Left"));
+
+    let result = session.execute_sql(sql).await.unwrap();
+    assert_table_eq!(
+        result,
+        [[1, 100], [1, 101], [2, null], [3, null], [4, null],]
+    );
+}
+
+#[tokio::test]
+async fn test_right_hash_join() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let sql = "SELECT l.id, r.v FROM l RIGHT JOIN r ON l.k = r.k ORDER BY r.v";
+    assert!(explain(&session, sql).await.contains("HashJoin: Right"));
+
+    let result = session.execute_sql(sql).await.unwrap();
+    assert_table_eq!(result, [[1, 100], [1, 101], [null, 300], [null, 900],]);
+}
+
+#[tokio::test]
+async fn test_full_hash_join() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let result = session
+        .execute_sql(
+            "SELECT l.id, r.v FROM l FULL JOIN r ON l.k = r.k ORDER BY l.id NULLS LAST, r.v",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(
+        result,
+        [
+            [1, 100],
+            [1, 101],
+            [2, null],
+            [3, null],
+            [4, null],
+            [null, 300],
+            [null, 900],
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_hash_join_on_expression_keys() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let sql = "SELECT l.id, r.v FROM l JOIN r ON l.k + 20 = r.k ORDER BY l.id";
+    assert!(explain(&session, sql).await.contains("HashJoin"));
+
+    let result = session.execute_sql(sql).await.unwrap();
+    assert_table_eq!(result, [[1, 300]]);
+
+    let result = session
+        .execute_sql(
+            "SELECT l.id, r.v FROM l LEFT JOIN r ON CAST(l.k AS FLOAT64) = CAST(r.k AS NUMERIC) ORDER BY l.id, r.v",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(
+        result,
+        [[1, 100], [1, 101], [2, null], [3, null], [4, null],]
+    );
+}
+
+#[tokio::test]
+async fn test_outer_hash_join_with_residual_filter() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let sql = "SELECT l.id, r.v FROM l LEFT JOIN r ON l.k = r.k AND r.v > l.id * 100 ORDER BY l.id";
+    let plan = explain(&session, sql).await;
+    assert!(plan.contains("HashJoin: Left"), "{}", plan);
+    assert!(plan.contains("FILTER"), "{}", plan);
+
+    let result = session.execute_sql(sql).await.unwrap();
+    assert_table_eq!(result, [[1, 101], [2, null], [3, null], [4, null],]);
+}
+
+#[tokio::test]
+async fn test_join_using() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let result = session
+        .execute_sql("SELECT k, name, v FROM l JOIN r USING (k) ORDER BY v")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[10, "a", 100], [10, "a", 101],]);
+
+    let result = session
+        .execute_sql("SELECT * FROM l LEFT JOIN r USING (k) ORDER BY id, v")
+        .await
+        .unwrap();
+    assert_table_eq!(
+        result,
+        [
+            [10, 1, "a", 100],
+            [10, 1, "a", 101],
+            [20, 2, "b", null],
+            [null, 3, "c", null],
+            [40, 4, "d", null],
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_full_join_using_coalesces_key() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let result = session
+        .execute_sql(
+            "SELECT k, id, v FROM l FULL JOIN r USING (k) ORDER BY k NULLS LAST, id NULLS LAST",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(
+        result,
+        [
+            [10, 1, 100],
+            [10, 1, 101],
+            [20, 2, null],
+            [30, null, 300],
+            [40, 4, null],
+            [null, 3, null],
+            [null, null, 900],
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_join_using_missing_column() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let result = session
+        .execute_sql("SELECT * FROM l JOIN r USING (name)")
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_range_join_uses_merge_join() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let sql = "SELECT l.id, r.v FROM l JOIN r ON l.k > r.k ORDER BY l.id, r.v";
+    assert!(explain(&session, sql).await.contains("MergeJoin: Inner"));
+
+    let result = session.execute_sql(sql).await.unwrap();
+    assert_table_eq!(result, [[2, 100], [2, 101], [4, 100], [4, 101], [4, 300],]);
+}
+
+#[tokio::test]
+async fn test_band_join() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let sql = "SELECT l.id, r.v FROM l LEFT JOIN r ON r.k >= l.k - 10 AND r.k <= l.k + 10 ORDER BY l.id, r.v";
+    assert!(explain(&session, sql).await.contains("MergeJoin: Left"));
+
+    let result = session.execute_sql(sql).await.unwrap();
+    assert_table_eq!(
+        result,
+        [
+            [1, 100],
+            [1, 101],
+            [2, 100],
+            [2, 101],
+            [2, 300],
+            [3, null],
+            [4, 300],
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_equi_join_on_sorted_inputs() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let sql = "SELECT a.id, b.v FROM (SELECT * FROM l ORDER BY k) a FULL JOIN (SELECT * FROM r ORDER BY k) b ON a.k = b.k ORDER BY a.id NULLS LAST, b.v";
+    assert!(explain(&session, sql).await.contains("HashJoin: Full"));
+
+    let result = session.execute_sql(sql).await.unwrap();
+    assert_table_eq!(
+        result,
+        [
+            [1, 100],
+            [1, 101],
+            [2, null],
+            [3, null],
+            [4, null],
+            [null, 300],
+            [null, 900],
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_range_join_on_mixed_numeric_keys() {
+    let session = create_session();
+    session
+        .execute_sql("CREATE TABLE prices (id INT64, price NUMERIC)")
+        .await
+        .unwrap();
+    session
+        .execute_sql(
+            "INSERT INTO prices VALUES (1, NUMERIC '1.5'), (2, NUMERIC '2'), (3, NUMERIC '2.5'), (4, NUMERIC '10')",
+        )
+        .await
+        .unwrap();
+    session
+        .execute_sql("CREATE TABLE limits (name STRING, cap INT64)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO limits VALUES ('low', 2), ('high', 3)")
+        .await
+        .unwrap();
+
+    let sql =
+        "SELECT l.name, p.id FROM limits l JOIN prices p ON p.price <= l.cap ORDER BY l.name, p.id";
+    assert!(explain(&session, sql).await.contains("MergeJoin: Inner"));
+    let result = session.execute_sql(sql).await.unwrap();
+    assert_table_eq!(
+        result,
+        [
+            ["high", 1],
+            ["high", 2],
+            ["high", 3],
+            ["low", 1],
+            ["low", 2]
+        ]
+    );
+
+    let sql =
+        "SELECT a.id, b.id FROM prices a JOIN prices b ON a.price < b.price ORDER BY a.id, b.id";
+    let result = session.execute_sql(sql).await.unwrap();
+    assert_table_eq!(result, [[1, 2], [1, 3], [1, 4], [2, 3], [2, 4], [3, 4]]);
+}
+
+#[tokio::test]
+async fn test_hash_join_matches_nested_loop_results() {
+    let session = create_session();
+    for (table, rows, modulus) in [("big_l", 400, 37), ("big_r", 300, 53)] {
+        session
+            .execute_sql(&format!("CREATE TABLE {} (id INT64, k INT64)", table))
+            .await
+            .unwrap();
+        session
+            .execute_sql(&format!(
+                "INSERT INTO {} (id) SELECT n FROM UNNEST(GENERATE_ARRAY(1, {})) AS n",
+                table, rows
+            ))
+            .await
+            .unwrap();
+        session
+            .execute_sql(&format!(
+                "UPDATE {} SET k = MOD(id, {}) WHERE TRUE",
+                table, modulus
+            ))
+            .await
+            .unwrap();
+    }
+
+    let hashed = session
+        .execute_sql(
+            "SELECT COUNT(*), COUNT(a.id), COUNT(b.id) FROM big_l a FULL JOIN big_r b ON a.k = b.k AND a.id < b.id",
+        )
+        .await
+        .unwrap();
+    let nested = session
+        .execute_sql(
+            "SELECT COUNT(*), COUNT(a.id), COUNT(b.id) FROM big_l a FULL JOIN big_r b ON a.k = b.k AND a.id < b.id OR a.id IS NULL AND b.id IS NULL",
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        hashed.get_row(0).unwrap().values(),
+        nested.get_row(0).unwrap().values()
+    );
+}
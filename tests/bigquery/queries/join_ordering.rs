@@ -0,0 +1,170 @@
+use yachtsql::YachtSQLSession;
+
+use crate::assert_table_eq;
+use crate::common::create_session;
+
+async fn setup_star_schema(session: &YachtSQLSession) {
+    session
+        .execute_sql(
+            "CREATE TABLE sales (id INT64, product_id INT64, store_id INT64, amount INT64)",
+        )
+        .await
+        .unwrap();
+    session
+        .execute_sql("CREATE TABLE products (id INT64, category STRING)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("CREATE TABLE stores (id INT64, region STRING)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO sales (id) SELECT n FROM UNNEST(GENERATE_ARRAY(1, 5000)) AS n")
+        .await
+        .unwrap();
+    session
+        .execute_sql(
+            "UPDATE sales SET product_id = MOD(id, 10) + 1, store_id = MOD(id, 5) + 1, amount = id WHERE TRUE",
+        )
+        .await
+        .unwrap();
+    session
+        .execute_sql(
+            "INSERT INTO products VALUES (1, 'odd'), (2, 'even'), (3, 'odd'), (4, 'even'), (5, 'odd'), (6, 'even'), (7, 'odd'), (8, 'even'), (9, 'odd'), (10, 'even')",
+        )
+        .await
+        .unwrap();
+    session
+        .execute_sql(
+            "INSERT INTO stores VALUES (1, 'north'), (2, 'north'), (3, 'south'), (4, 'south'), (5, 'south')",
+        )
+        .await
+        .unwrap();
+}
+
+async fn analyze_all(session: &YachtSQLSession) {
+    for table in ["sales", "products", "stores"] {
+        session
+            .execute_sql(&format!("ANALYZE {}", table))
+            .await
+            .unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_star_join_written_dimensions_first() {
+    let session = create_session();
+    setup_star_schema(&session).await;
+    analyze_all(&session).await;
+
+    let result = session
+        .execute_sql(
+            "SELECT p.category, s.region, COUNT(*), SUM(f.amount)
+             FROM products p, stores s, sales f
+             WHERE f.product_id = p.id AND f.store_id = s.id
+             GROUP BY p.category, s.region
+             ORDER BY p.category, s.region",
+        )
+        .await
+        .unwrap();
+
+    assert_table_eq!(
+        result,
+        [
+            ["even", "north", 1000, 2498000],
+            ["even", "south", 1500, 3752000],
+            ["odd", "north", 1000, 2503000],
+            ["odd", "south", 1500, 3749500],
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_star_join_with_dimension_filter() {
+    let session = create_session();
+    setup_star_schema(&session).await;
+    analyze_all(&session).await;
+
+    let result = session
+        .execute_sql(
+            "SELECT s.region, COUNT(*), SUM(f.amount)
+             FROM sales f
+             JOIN products p ON f.product_id = p.id
+             JOIN stores s ON f.store_id = s.id
+             WHERE p.id <= 2
+             GROUP BY s.region",
+        )
+        .await
+        .unwrap();
+
+    assert_table_eq!(result, [["north", 1000, 2500500]]);
+}
+
+#[tokio::test]
+async fn test_reordered_join_keeps_select_star_columns() {
+    let session = create_session();
+    setup_star_schema(&session).await;
+    analyze_all(&session).await;
+
+    let result = session
+        .execute_sql(
+            "SELECT * FROM products p JOIN sales f ON f.product_id = p.id ORDER BY f.amount LIMIT 3",
+        )
+        .await
+        .unwrap();
+
+    assert_table_eq!(
+        result,
+        [
+            [2, "even", 1, 2, 2, 1],
+            [3, "odd", 2, 3, 3, 2],
+            [4, "even", 3, 4, 4, 3],
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_join_without_statistics() {
+    let session = create_session();
+    setup_star_schema(&session).await;
+
+    let result = session
+        .execute_sql(
+            "SELECT COUNT(*) FROM stores s JOIN sales f ON f.store_id = s.id WHERE s.region = 'north'",
+        )
+        .await
+        .unwrap();
+
+    assert_table_eq!(result, [[2000]]);
+}
+
+#[tokio::test]
+async fn test_analyze_after_table_changes() {
+    let session = create_session();
+    setup_star_schema(&session).await;
+    analyze_all(&session).await;
+
+    session
+        .execute_sql("DELETE FROM sales WHERE id > 10")
+        .await
+        .unwrap();
+    session.execute_sql("ANALYZE TABLE sales").await.unwrap();
+
+    let result = session
+        .execute_sql(
+            "SELECT COUNT(*), SUM(f.amount) FROM stores s JOIN sales f ON f.store_id = s.id WHERE s.region = 'south'",
+        )
+        .await
+        .unwrap();
+
+    assert_table_eq!(result, [[6, 33]]);
+}
+
+#[tokio::test]
+async fn test_analyze_missing_table() {
+    let session = create_session();
+
+    let result = session.execute_sql("ANALYZE missing_table").await;
+
+    assert!(result.is_err());
+}
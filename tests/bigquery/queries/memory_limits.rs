@@ -0,0 +1,188 @@
+use std::path::PathBuf;
+
+use yachtsql::{Table, YachtSQLSession};
+
+use crate::assert_table_eq;
+use crate::common::create_session;
+
+async fn setup_tables(session: &YachtSQLSession) {
+    session
+        .execute_sql("CREATE TABLE events (id INT64, user_id INT64, payload STRING)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO events (id) SELECT n FROM UNNEST(GENERATE_ARRAY(1, 20000)) AS n")
+        .await
+        .unwrap();
+    session
+        .execute_sql(
+            "UPDATE events SET user_id = MOD(id * 13, 5000), payload = CONCAT('event-', CAST(id AS STRING)) WHERE TRUE",
+        )
+        .await
+        .unwrap();
+    session
+        .execute_sql("CREATE TABLE users (id INT64, name STRING)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO users (id) SELECT n FROM UNNEST(GENERATE_ARRAY(0, 4999)) AS n")
+        .await
+        .unwrap();
+    session
+        .execute_sql("UPDATE users SET name = CONCAT('user-', CAST(id AS STRING)) WHERE TRUE")
+        .await
+        .unwrap();
+}
+
+fn spill_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "yachtsql-spill-test-{}-{}",
+        std::process::id(),
+        name
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+async fn set_memory_limit(session: &YachtSQLSession, bytes: i64) {
+    session
+        .execute_sql(&format!("SET MEMORY_LIMIT_BYTES = {}", bytes))
+        .await
+        .unwrap();
+}
+
+async fn set_spill_directory(session: &YachtSQLSession, dir: &std::path::Path) {
+    session
+        .execute_sql(&format!("SET SPILL_DIRECTORY = '{}'", dir.display()))
+        .await
+        .unwrap();
+}
+
+fn assert_same_rows(expected: &Table, actual: &Table) {
+    assert_eq!(expected.row_count(), actual.row_count());
+    for i in 0..expected.row_count() {
+        assert_eq!(
+            expected.get_row(i).unwrap().values(),
+            actual.get_row(i).unwrap().values(),
+            "row {}",
+            i
+        );
+    }
+}
+
+fn assert_resources_exceeded(result: yachtsql::Result<Table>) {
+    let err = result.unwrap_err().to_string();
+    assert!(
+        err.contains("Resources exceeded during query execution"),
+        "{}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn test_cross_join_exceeds_memory_limit() {
+    let session = create_session();
+    setup_tables(&session).await;
+    set_memory_limit(&session, 5_000_000).await;
+
+    assert_resources_exceeded(
+        session
+            .execute_sql("SELECT e.id, u.id FROM events e CROSS JOIN users u")
+            .await,
+    );
+
+    let result = session
+        .execute_sql(
+            "SELECT COUNT(*) FROM users u1 CROSS JOIN (SELECT 1 AS x UNION ALL SELECT 2) t",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[10000]]);
+}
+
+#[tokio::test]
+async fn test_operators_exceed_memory_limit_without_spill_directory() {
+    let session = create_session();
+    setup_tables(&session).await;
+    set_memory_limit(&session, 50_000).await;
+
+    assert_resources_exceeded(
+        session
+            .execute_sql("SELECT id FROM events ORDER BY payload")
+            .await,
+    );
+    assert_resources_exceeded(
+        session
+            .execute_sql("SELECT user_id, COUNT(*) FROM events GROUP BY user_id")
+            .await,
+    );
+    assert_resources_exceeded(
+        session
+            .execute_sql("SELECT COUNT(*) FROM events e JOIN users u ON e.user_id = u.id")
+            .await,
+    );
+
+    let result = session
+        .execute_sql("SELECT COUNT(*), MAX(id) FROM events WHERE user_id < 10")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[40, 20000]]);
+}
+
+#[tokio::test]
+async fn test_sort_spills_to_disk() {
+    let session = create_session();
+    setup_tables(&session).await;
+    let sql = "SELECT id, user_id FROM events ORDER BY user_id DESC, payload";
+    let expected = session.execute_sql(sql).await.unwrap();
+
+    let dir = spill_dir("sort");
+    set_memory_limit(&session, 4_000_000).await;
+    assert_resources_exceeded(session.execute_sql(sql).await);
+    set_spill_directory(&session, &dir).await;
+    let spilled = session.execute_sql(sql).await.unwrap();
+
+    assert!(expected.row_count() > 0);
+    assert_same_rows(&expected, &spilled);
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_hash_aggregate_spills_to_disk() {
+    let session = create_session();
+    setup_tables(&session).await;
+    let sql = "SELECT user_id, COUNT(*), SUM(id), MIN(payload) FROM events GROUP BY user_id ORDER BY user_id";
+    let expected = session.execute_sql(sql).await.unwrap();
+
+    let dir = spill_dir("aggregate");
+    set_memory_limit(&session, 2_000_000).await;
+    assert_resources_exceeded(session.execute_sql(sql).await);
+    set_spill_directory(&session, &dir).await;
+    let spilled = session.execute_sql(sql).await.unwrap();
+
+    assert_eq!(spilled.row_count(), 5000);
+    assert_same_rows(&expected, &spilled);
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_hash_join_spills_to_disk() {
+    let session = create_session();
+    setup_tables(&session).await;
+    let sql =
+        "SELECT e.id, u.name FROM events e JOIN users u ON e.user_id + 4990 = u.id ORDER BY e.id";
+    let expected = session.execute_sql(sql).await.unwrap();
+
+    let dir = spill_dir("join");
+    set_memory_limit(&session, 100_000).await;
+    assert_resources_exceeded(session.execute_sql(sql).await);
+    set_spill_directory(&session, &dir).await;
+    let spilled = session.execute_sql(sql).await.unwrap();
+
+    assert!(expected.row_count() > 0);
+    assert_same_rows(&expected, &spilled);
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+    let _ = std::fs::remove_dir_all(&dir);
+}
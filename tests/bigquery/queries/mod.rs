@@ -1,17 +1,35 @@
 mod aliases;
 mod analytical;
+mod cancellation;
+mod column_pruning;
 mod cte;
 mod distinct;
+mod explain;
 mod group_by;
 mod grouping;
+mod information_schema;
+mod join_algorithms;
+mod join_ordering;
 mod joins;
+mod memory_limits;
+mod optimizer_rules;
 mod order_limit;
+mod parallel_execution;
+mod pipe_syntax;
 mod pivot;
+mod plan_cache;
+mod prepared;
 mod qualify;
+mod query_cache;
 mod scripting;
+mod sessions;
 mod set_operations;
+mod streaming;
 mod subqueries;
 mod tablesample;
 mod time_travel;
+mod transactions;
+mod vectorized;
+mod window_frames;
 mod window_functions;
 mod workloads;
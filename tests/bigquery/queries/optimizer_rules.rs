@@ -0,0 +1,240 @@
+use std::sync::Arc;
+
+use yachtsql::{LogicalPlan, OptimizerRule, YachtSQLSession};
+
+use crate::assert_table_eq;
+use crate::common::create_session;
+
+async fn setup_tables(session: &YachtSQLSession) {
+    session
+        .execute_sql("CREATE TABLE customers (id INT64, name STRING, region STRING)")
+        .await
+        .unwrap();
+    session
+        .execute_sql(
+            "INSERT INTO customers VALUES
+            (1, 'alice', 'north'),
+            (2, 'bob', 'south'),
+            (3, 'carol', 'north'),
+            (4, 'dave', 'west')",
+        )
+        .await
+        .unwrap();
+    session
+        .execute_sql("CREATE TABLE orders (id INT64, customer_id INT64, amount INT64)")
+        .await
+        .unwrap();
+    session
+        .execute_sql(
+            "INSERT INTO orders VALUES
+            (10, 1, 30),
+            (11, 1, 5),
+            (12, 2, 50),
+            (13, 3, 20)",
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_where_on_left_join_null_side_is_not_pushed_into_join() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let result = session
+        .execute_sql(
+            "SELECT c.name, o.amount FROM customers c LEFT JOIN orders o ON c.id = o.customer_id WHERE o.amount IS NULL OR o.amount > 25 ORDER BY c.name",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(result, [["alice", 30], ["bob", 50], ["dave", null]]);
+}
+
+#[tokio::test]
+async fn test_left_join_on_condition_filters_only_matches() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let result = session
+        .execute_sql(
+            "SELECT c.name, o.amount FROM customers c LEFT JOIN orders o ON c.id = o.customer_id AND o.amount > 25 ORDER BY c.name",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(
+        result,
+        [["alice", 30], ["bob", 50], ["carol", null], ["dave", null]]
+    );
+}
+
+#[tokio::test]
+async fn test_filter_on_grouped_subquery() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let result = session
+        .execute_sql(
+            "SELECT customer_id, total FROM (SELECT customer_id, SUM(amount) AS total FROM orders GROUP BY customer_id) WHERE customer_id < 3 AND total > 20 ORDER BY customer_id",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1, 35], [2, 50]]);
+}
+
+#[tokio::test]
+async fn test_filter_on_window_partition_column() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let result = session
+        .execute_sql(
+            "SELECT id, running FROM (SELECT id, customer_id, SUM(amount) OVER (PARTITION BY customer_id ORDER BY id) AS running FROM orders) WHERE customer_id = 1 ORDER BY id",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[10, 30], [11, 35]]);
+}
+
+#[tokio::test]
+async fn test_filter_on_window_ordering_column_is_applied_after_window() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let result = session
+        .execute_sql(
+            "SELECT id, rn FROM (SELECT id, ROW_NUMBER() OVER (ORDER BY id) AS rn FROM orders) WHERE id > 11 ORDER BY id",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[12, 3], [13, 4]]);
+}
+
+#[tokio::test]
+async fn test_filter_over_union_all() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let result = session
+        .execute_sql(
+            "SELECT v FROM (SELECT id AS v FROM customers UNION ALL SELECT amount AS v FROM orders) WHERE v > 4 ORDER BY v",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[5], [20], [30], [50]]);
+}
+
+#[tokio::test]
+async fn test_limit_over_union_all() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let result = session
+        .execute_sql(
+            "SELECT COUNT(*) FROM (SELECT id FROM customers UNION ALL SELECT id FROM orders LIMIT 6)",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[6]]);
+}
+
+#[tokio::test]
+async fn test_constant_predicates() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let result = session
+        .execute_sql("SELECT name FROM customers WHERE 1 + 1 = 2 AND region = 'north' ORDER BY id")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [["alice"], ["carol"]]);
+
+    let result = session
+        .execute_sql("SELECT name FROM customers WHERE 1 = 2 OR NULL")
+        .await
+        .unwrap();
+    assert_eq!(result.row_count(), 0);
+}
+
+#[tokio::test]
+async fn test_redundant_nested_order_by() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let result = session
+        .execute_sql(
+            "SELECT name FROM (SELECT * FROM customers ORDER BY region) ORDER BY region, name DESC",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(result, [["carol"], ["alice"], ["bob"], ["dave"]]);
+}
+
+#[tokio::test]
+async fn test_distinct_over_aggregate_without_grouping_columns() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let result = session
+        .execute_sql("SELECT DISTINCT COUNT(*) AS n FROM customers GROUP BY region ORDER BY n")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1], [2]]);
+
+    session
+        .execute_sql("INSERT INTO orders VALUES (14, 4, 35)")
+        .await
+        .unwrap();
+    let result = session
+        .execute_sql(
+            "SELECT DISTINCT SUM(amount) AS total FROM orders GROUP BY customer_id ORDER BY total",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[20], [35], [50]]);
+
+    let result = session
+        .execute_sql(
+            "SELECT DISTINCT region, COUNT(*) FROM customers GROUP BY region ORDER BY region",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(result, [["north", 2], ["south", 1], ["west", 1]]);
+}
+
+struct CapLimits;
+
+impl OptimizerRule for CapLimits {
+    fn name(&self) -> &str {
+        "cap_limits"
+    }
+
+    fn rewrite(&self, plan: &LogicalPlan) -> yachtsql::Result<Option<LogicalPlan>> {
+        Ok(match plan {
+            LogicalPlan::Limit {
+                input,
+                limit: Some(limit),
+                offset,
+            } if *limit > 1 => Some(LogicalPlan::Limit {
+                input: input.clone(),
+                limit: Some(1),
+                offset: *offset,
+            }),
+            _ => None,
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_registered_rule_applies_to_cached_queries() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let sql = "SELECT name FROM customers ORDER BY id LIMIT 3";
+    let result = session.execute_sql(sql).await.unwrap();
+    assert_table_eq!(result, [["alice"], ["bob"], ["carol"]]);
+
+    session.register_optimizer_rule(Arc::new(CapLimits));
+
+    let result = session.execute_sql(sql).await.unwrap();
+    assert_table_eq!(result, [["alice"]]);
+}
@@ -0,0 +1,198 @@
+use yachtsql::{Table, YachtSQLSession};
+
+use crate::assert_table_eq;
+use crate::common::create_session;
+
+const ROWS: i64 = 40_000;
+
+async fn setup_tables(session: &YachtSQLSession) {
+    session
+        .execute_sql("CREATE TABLE facts (id INT64, k INT64, v INT64, x FLOAT64)")
+        .await
+        .unwrap();
+    session
+        .execute_sql(&format!(
+            "INSERT INTO facts (id) SELECT n FROM UNNEST(GENERATE_ARRAY(1, {})) AS n",
+            ROWS
+        ))
+        .await
+        .unwrap();
+    session
+        .execute_sql(
+            "UPDATE facts SET k = MOD(id, 97), v = MOD(id * 7, 1000), x = id / 8.0 WHERE TRUE",
+        )
+        .await
+        .unwrap();
+    session
+        .execute_sql("CREATE TABLE dims (k INT64, label STRING)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO dims (k) SELECT n FROM UNNEST(GENERATE_ARRAY(0, 200)) AS n")
+        .await
+        .unwrap();
+    session
+        .execute_sql("UPDATE dims SET label = CONCAT('k', CAST(k AS STRING)) WHERE TRUE")
+        .await
+        .unwrap();
+}
+
+async fn run_with_threads(session: &YachtSQLSession, threads: i64, sql: &str) -> Table {
+    session
+        .execute_sql(&format!("SET PARALLEL_THREADS = {}", threads))
+        .await
+        .unwrap();
+    session.execute_sql(sql).await.unwrap()
+}
+
+async fn assert_same_results(session: &YachtSQLSession, sql: &str) {
+    let serial = run_with_threads(session, 1, sql).await;
+    let parallel = run_with_threads(session, 4, sql).await;
+    assert_eq!(serial.row_count(), parallel.row_count(), "{}", sql);
+    for i in 0..serial.row_count() {
+        assert_eq!(
+            serial.get_row(i).unwrap().values(),
+            parallel.get_row(i).unwrap().values(),
+            "row {} of {}",
+            i,
+            sql
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_parallel_filter_and_project() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    assert_same_results(
+        &session,
+        "SELECT id, v * 2 + k, CONCAT('r', CAST(id AS STRING)) FROM facts WHERE MOD(v, 3) = 0",
+    )
+    .await;
+
+    let result = run_with_threads(
+        &session,
+        4,
+        "SELECT COUNT(*) FROM (SELECT id FROM facts WHERE k = 5)",
+    )
+    .await;
+    assert_table_eq!(result, [[413]]);
+}
+
+#[tokio::test]
+async fn test_parallel_group_by_aggregate() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    assert_same_results(
+        &session,
+        "SELECT k, COUNT(*), SUM(v), MIN(v), MAX(v), AVG(id), COUNTIF(v > 500) FROM facts GROUP BY k ORDER BY k",
+    )
+    .await;
+
+    let result = run_with_threads(
+        &session,
+        4,
+        "SELECT k, COUNT(*), MIN(id), MAX(id) FROM facts WHERE k < 2 GROUP BY k ORDER BY k",
+    )
+    .await;
+    assert_table_eq!(result, [[0, 412, 97, 39964], [1, 413, 1, 39965],]);
+}
+
+#[tokio::test]
+async fn test_parallel_aggregate_without_group_by() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let result = run_with_threads(
+        &session,
+        4,
+        "SELECT COUNT(*), SUM(id), MIN(id), MAX(id), LOGICAL_AND(id > 0) FROM facts",
+    )
+    .await;
+    assert_table_eq!(result, [[40000, 800020000, 1, 40000, true]]);
+
+    let serial = run_with_threads(&session, 1, "SELECT VARIANCE(x), STDDEV(v) FROM facts").await;
+    let parallel = run_with_threads(&session, 4, "SELECT VARIANCE(x), STDDEV(v) FROM facts").await;
+    let serial = serial.get_row(0).unwrap();
+    let parallel = parallel.get_row(0).unwrap();
+    for (a, b) in serial.values().iter().zip(parallel.values()) {
+        let (a, b) = (a.as_f64().unwrap(), b.as_f64().unwrap());
+        assert!((a - b).abs() <= a.abs() * 1e-9, "{} vs {}", a, b);
+    }
+}
+
+#[tokio::test]
+async fn test_distinct_aggregate_falls_back_to_serial() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let result = run_with_threads(
+        &session,
+        4,
+        "SELECT COUNT(DISTINCT k), COUNT(DISTINCT v) FROM facts",
+    )
+    .await;
+    assert_table_eq!(result, [[97, 1000]]);
+}
+
+#[tokio::test]
+async fn test_parallel_hash_join() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    assert_same_results(
+        &session,
+        "SELECT f.id, d.label FROM facts f JOIN dims d ON f.k = d.k WHERE f.v < 100",
+    )
+    .await;
+    assert_same_results(
+        &session,
+        "SELECT d.k, f.id FROM dims d LEFT JOIN facts f ON d.k = f.k AND f.v = 7",
+    )
+    .await;
+
+    let result = run_with_threads(
+        &session,
+        4,
+        "SELECT COUNT(*), COUNT(d.label) FROM facts f LEFT JOIN dims d ON f.k + 150 = d.k",
+    )
+    .await;
+    assert_table_eq!(result, [[40000, 21048]]);
+}
+
+#[tokio::test]
+async fn test_parallel_sort_is_stable() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    assert_same_results(&session, "SELECT id, k, v FROM facts ORDER BY v DESC, k").await;
+    assert_same_results(&session, "SELECT id FROM facts ORDER BY k LIMIT 50").await;
+
+    let result = run_with_threads(
+        &session,
+        4,
+        "SELECT id FROM facts ORDER BY k, v DESC, id LIMIT 3",
+    )
+    .await;
+    assert_table_eq!(result, [[7857], [15714], [23571],]);
+}
+
+#[tokio::test]
+async fn test_parallel_execution_disabled() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    session
+        .execute_sql("SET PARALLEL_EXECUTION = false")
+        .await
+        .unwrap();
+    let result = run_with_threads(
+        &session,
+        8,
+        "SELECT k, COUNT(*) FROM facts WHERE k IN (3, 4) GROUP BY k ORDER BY k",
+    )
+    .await;
+    assert_table_eq!(result, [[3, 413], [4, 413],]);
+}
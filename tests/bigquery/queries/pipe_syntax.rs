@@ -0,0 +1,286 @@
+use yachtsql::{Table, YachtSQLSession};
+
+use crate::assert_table_eq;
+use crate::common::create_session;
+
+async fn setup(session: &YachtSQLSession) {
+    session
+        .execute_sql(
+            "CREATE TABLE orders (id INT64, customer STRING, item STRING, qty INT64, price INT64)",
+        )
+        .await
+        .unwrap();
+    session
+        .execute_sql(
+            "INSERT INTO orders VALUES (1, 'ann', 'pen', 3, 2), (2, 'bob', 'cup', 1, 8), (3, 'ann', 'cup', 2, 8), (4, 'cid', 'pen', 10, 2), (5, 'bob', 'lamp', 1, 40)",
+        )
+        .await
+        .unwrap();
+    session
+        .execute_sql("CREATE TABLE customers (name STRING, city STRING)")
+        .await
+        .unwrap();
+    session
+        .execute_sql(
+            "INSERT INTO customers VALUES ('ann', 'Oslo'), ('bob', 'Rome'), ('cid', 'Oslo')",
+        )
+        .await
+        .unwrap();
+}
+
+fn column_names(table: &Table) -> Vec<String> {
+    table
+        .schema()
+        .fields()
+        .iter()
+        .map(|f| f.name.clone())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_from_first_query() {
+    let session = create_session();
+    setup(&session).await;
+
+    let result = session
+        .execute_sql("FROM customers |> ORDER BY name")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [["ann", "Oslo"], ["bob", "Rome"], ["cid", "Oslo"]]);
+
+    let result = session
+        .execute_sql("FROM orders |> WHERE qty > 2 |> SELECT id, item |> ORDER BY id")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1, "pen"], [4, "pen"]]);
+}
+
+#[tokio::test]
+async fn test_pipe_after_standard_query() {
+    let session = create_session();
+    setup(&session).await;
+
+    let result = session
+        .execute_sql(
+            "SELECT id, qty * price AS total FROM orders WHERE item = 'cup' |> WHERE total > 10 |> ORDER BY id",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[3, 16]]);
+
+    let result = session
+        .execute_sql("WITH big AS (FROM orders |> WHERE price >= 8) SELECT COUNT(*) FROM big")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[3]]);
+}
+
+#[tokio::test]
+async fn test_extend_set_drop_rename() {
+    let session = create_session();
+    setup(&session).await;
+
+    let result = session
+        .execute_sql(
+            "FROM orders
+             |> EXTEND qty * price AS total, ROW_NUMBER() OVER (ORDER BY id DESC) AS rn
+             |> SET price = price * 10
+             |> DROP customer, item
+             |> RENAME qty AS quantity
+             |> ORDER BY id",
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        column_names(&result),
+        ["id", "quantity", "price", "total", "rn"]
+    );
+    assert_table_eq!(
+        result,
+        [
+            [1, 3, 20, 6, 5],
+            [2, 1, 80, 8, 4],
+            [3, 2, 80, 16, 3],
+            [4, 10, 20, 20, 2],
+            [5, 1, 400, 40, 1],
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_aggregate() {
+    let session = create_session();
+    setup(&session).await;
+
+    let result = session
+        .execute_sql(
+            "FROM orders |> AGGREGATE SUM(qty) AS units, COUNT(*) AS n GROUP BY customer |> ORDER BY customer",
+        )
+        .await
+        .unwrap();
+    assert_eq!(column_names(&result), ["customer", "units", "n"]);
+    assert_table_eq!(result, [["ann", 5, 2], ["bob", 2, 2], ["cid", 10, 1]]);
+
+    let result = session
+        .execute_sql("FROM orders |> AGGREGATE SUM(qty * price) AS revenue DESC GROUP BY item")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [["lamp", 40], ["pen", 26], ["cup", 24]]);
+
+    let result = session
+        .execute_sql("FROM orders |> AGGREGATE MAX(price) AS top, COUNT(*)")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[40, 5]]);
+
+    let result = session
+        .execute_sql("FROM orders |> AGGREGATE GROUP BY customer DESC")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [["cid"], ["bob"], ["ann"]]);
+}
+
+#[tokio::test]
+async fn test_join_and_as() {
+    let session = create_session();
+    setup(&session).await;
+
+    let result = session
+        .execute_sql(
+            "FROM orders
+             |> AS o
+             |> JOIN customers AS c ON o.customer = c.name
+             |> WHERE c.city = 'Oslo'
+             |> AGGREGATE SUM(o.qty) AS units GROUP BY o.item
+             |> ORDER BY item",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(result, [["cup", 2], ["pen", 13]]);
+
+    let result = session
+        .execute_sql(
+            "FROM customers |> LEFT JOIN orders ON customers.name = orders.customer AND orders.item = 'lamp' |> SELECT name, id |> ORDER BY name",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(result, [["ann", null], ["bob", 5], ["cid", null]]);
+}
+
+#[tokio::test]
+async fn test_set_operations() {
+    let session = create_session();
+    setup(&session).await;
+
+    let result = session
+        .execute_sql(
+            "FROM orders |> SELECT customer |> UNION ALL (SELECT name FROM customers), (SELECT 'dee') |> AGGREGATE COUNT(*) AS n GROUP BY customer |> ORDER BY customer",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(result, [["ann", 3], ["bob", 3], ["cid", 2], ["dee", 1]]);
+
+    let result = session
+        .execute_sql(
+            "FROM orders |> SELECT item |> INTERSECT DISTINCT (SELECT 'cup' UNION ALL SELECT 'fork')",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(result, [["cup"]]);
+
+    let result = session
+        .execute_sql(
+            "FROM orders |> SELECT item |> EXCEPT DISTINCT (SELECT name FROM customers), (FROM orders |> WHERE price = 8 |> SELECT item) |> ORDER BY item",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(result, [["lamp"], ["pen"]]);
+}
+
+#[tokio::test]
+async fn test_order_by_and_limit() {
+    let session = create_session();
+    setup(&session).await;
+
+    let result = session
+        .execute_sql("FROM orders |> ORDER BY price DESC, id |> LIMIT 2 OFFSET 1 |> SELECT id")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[2], [3]]);
+}
+
+#[tokio::test]
+async fn test_tablesample_and_pivot() {
+    let session = create_session();
+    setup(&session).await;
+
+    let result = session
+        .execute_sql("FROM orders |> TABLESAMPLE SYSTEM (100 PERCENT) |> AGGREGATE COUNT(*)")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[5]]);
+
+    let result = session
+        .execute_sql(
+            "FROM orders |> SELECT customer, item, qty |> PIVOT(SUM(qty) FOR item IN ('pen', 'cup')) |> ORDER BY customer",
+        )
+        .await
+        .unwrap();
+    assert_eq!(column_names(&result), ["customer", "pen", "cup"]);
+    assert_table_eq!(result, [["ann", 3, 2], ["bob", null, 1], ["cid", 10, null]]);
+
+    let result = session
+        .execute_sql(
+            "FROM orders |> WHERE id = 1 |> SELECT id, qty, price |> UNPIVOT(amount FOR kind IN (qty, price)) |> ORDER BY kind",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1, 2, "price"], [1, 3, "qty"]]);
+}
+
+#[tokio::test]
+async fn test_call_table_function() {
+    let session = create_session();
+    setup(&session).await;
+    session
+        .execute_sql(
+            "CREATE TABLE FUNCTION top_rows(input TABLE<id INT64, qty INT64>, min_qty INT64)
+             AS (SELECT id, qty FROM input WHERE qty >= min_qty)",
+        )
+        .await
+        .unwrap();
+
+    let result = session
+        .execute_sql("FROM orders |> SELECT id, qty |> CALL top_rows(3) AS t |> ORDER BY t.id")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1, 3], [4, 10]]);
+}
+
+#[tokio::test]
+async fn test_errors_name_the_failing_stage() {
+    let session = create_session();
+    setup(&session).await;
+
+    let err = session
+        .execute_sql("FROM orders |> WHERE qty > 1 |> DROP nope")
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("pipe operator 2"), "{}", err);
+    assert!(err.contains("|> DROP nope"), "{}", err);
+
+    let err = session
+        .execute_sql("FROM orders |> SELECT id |> SET qty = 1")
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("pipe operator 2: |> SET qty = 1"), "{}", err);
+
+    let err = session
+        .execute_sql("FROM orders |> SELECT id |> UNION ALL (SELECT 1, 2)")
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("pipe operator 2"), "{}", err);
+}
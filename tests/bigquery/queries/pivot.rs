@@ -1,3 +1,6 @@
+use yachtsql::Table;
+
+use crate::assert_table_eq;
 use crate::common::create_session;
 
 async fn setup_sales_table(session: &yachtsql::YachtSQLSession) {
@@ -10,14 +13,31 @@ async fn setup_sales_table(session: &yachtsql::YachtSQLSession) {
         .unwrap();
 }
 
+fn column_names(table: &Table) -> Vec<String> {
+    table
+        .schema()
+        .fields()
+        .iter()
+        .map(|f| f.name.clone())
+        .collect()
+}
+
 #[tokio::test]
 async fn test_pivot_basic() {
     let session = create_session();
     setup_sales_table(&session).await;
 
     let result = session
-        .execute_sql("SELECT * FROM sales PIVOT(SUM(amount) FOR quarter IN ('Q1', 'Q2', 'Q3', 'Q4')) ORDER BY product").await;
-    assert!(result.is_ok() || result.is_err());
+        .execute_sql("SELECT * FROM sales PIVOT(SUM(amount) FOR quarter IN ('Q1', 'Q2', 'Q3', 'Q4')) ORDER BY product").await
+        .unwrap();
+    assert_eq!(column_names(&result), ["product", "Q1", "Q2", "Q3", "Q4"]);
+    assert_table_eq!(
+        result,
+        [
+            ["Gadget", 80, 120, 160, 200],
+            ["Widget", 100, 150, 200, 250],
+        ]
+    );
 }
 
 #[tokio::test]
@@ -26,8 +46,10 @@ async fn test_pivot_with_alias() {
     setup_sales_table(&session).await;
 
     let result = session
-        .execute_sql("SELECT * FROM sales PIVOT(SUM(amount) AS total FOR quarter IN ('Q1' AS q1, 'Q2' AS q2)) ORDER BY product").await;
-    assert!(result.is_ok() || result.is_err());
+        .execute_sql("SELECT * FROM sales PIVOT(SUM(amount) AS total FOR quarter IN ('Q1' AS q1, 'Q2' AS q2)) ORDER BY product").await
+        .unwrap();
+    assert_eq!(column_names(&result), ["product", "total_q1", "total_q2"]);
+    assert_table_eq!(result, [["Gadget", 80, 120], ["Widget", 100, 150]]);
 }
 
 #[tokio::test]
@@ -42,8 +64,19 @@ async fn test_pivot_multiple_aggregates() {
         .unwrap();
 
     let result = session
-        .execute_sql("SELECT * FROM data PIVOT(SUM(value), COUNT(*) FOR period IN ('P1', 'P2')) ORDER BY category").await;
-    assert!(result.is_ok() || result.is_err());
+        .execute_sql("SELECT * FROM data PIVOT(SUM(value) AS total, COUNT(*) AS n FOR period IN ('P1', 'P2')) ORDER BY category").await
+        .unwrap();
+    assert_eq!(
+        column_names(&result),
+        ["category", "total_P1", "n_P1", "total_P2", "n_P2"]
+    );
+    assert_table_eq!(result, [["A", 30, 2, 30, 1], ["B", 40, 1, 50, 1]]);
+
+    let err = session
+        .execute_sql("SELECT * FROM data PIVOT(SUM(value), COUNT(*) FOR period IN ('P1', 'P2'))")
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("alias"), "{}", err);
 }
 
 #[tokio::test]
@@ -52,8 +85,9 @@ async fn test_pivot_with_where() {
     setup_sales_table(&session).await;
 
     let result = session
-        .execute_sql("SELECT * FROM (SELECT * FROM sales WHERE product = 'Widget') PIVOT(SUM(amount) FOR quarter IN ('Q1', 'Q2', 'Q3', 'Q4'))").await;
-    assert!(result.is_ok() || result.is_err());
+        .execute_sql("SELECT * FROM (SELECT * FROM sales WHERE product = 'Widget') PIVOT(SUM(amount) FOR quarter IN ('Q1', 'Q2', 'Q3', 'Q4'))").await
+        .unwrap();
+    assert_table_eq!(result, [["Widget", 100, 150, 200, 250]]);
 }
 
 #[tokio::test]
@@ -65,8 +99,9 @@ async fn test_pivot_avg() {
         .execute_sql(
             "SELECT * FROM sales PIVOT(AVG(amount) FOR quarter IN ('Q1', 'Q2')) ORDER BY product",
         )
-        .await;
-    assert!(result.is_ok() || result.is_err());
+        .await
+        .unwrap();
+    assert_table_eq!(result, [["Gadget", 80.0, 120.0], ["Widget", 100.0, 150.0]]);
 }
 
 #[tokio::test]
@@ -84,8 +119,41 @@ async fn test_pivot_count() {
         .execute_sql(
             "SELECT * FROM logs PIVOT(COUNT(*) FOR action IN ('view', 'click')) ORDER BY user_id",
         )
-        .await;
-    assert!(result.is_ok() || result.is_err());
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1, 2, 1], [2, 0, 2]]);
+}
+
+#[tokio::test]
+async fn test_pivot_implicit_grouping_excludes_aggregated_columns() {
+    let session = create_session();
+    session
+        .execute_sql("CREATE TABLE orders (region STRING, product STRING, quarter INT64, units INT64, price INT64)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO orders VALUES ('east', 'pen', 1, 10, 2), ('east', 'cup', 1, 5, 4), ('west', 'pen', 2, 7, 2), ('west', 'pen', 1, 3, 3)")
+        .await
+        .unwrap();
+
+    let result = session
+        .execute_sql(
+            "SELECT * FROM orders PIVOT(SUM(units * price) AS revenue FOR quarter IN (1, 2 AS second)) AS p ORDER BY p.region, p.product",
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        column_names(&result),
+        ["region", "product", "revenue__1", "revenue_second"]
+    );
+    assert_table_eq!(
+        result,
+        [
+            ["east", "cup", 20, null],
+            ["east", "pen", 20, null],
+            ["west", "pen", 9, 14],
+        ]
+    );
 }
 
 #[tokio::test]
@@ -102,8 +170,22 @@ async fn test_unpivot_basic() {
         .unwrap();
 
     let result = session
-        .execute_sql("SELECT * FROM quarterly_sales UNPIVOT(amount FOR quarter IN (Q1, Q2, Q3, Q4)) ORDER BY product, quarter").await;
-    assert!(result.is_ok() || result.is_err());
+        .execute_sql("SELECT * FROM quarterly_sales UNPIVOT(amount FOR quarter IN (Q1, Q2, Q3, Q4)) ORDER BY product, quarter").await
+        .unwrap();
+    assert_eq!(column_names(&result), ["product", "amount", "quarter"]);
+    assert_table_eq!(
+        result,
+        [
+            ["Gadget", 80, "Q1"],
+            ["Gadget", 120, "Q2"],
+            ["Gadget", 160, "Q3"],
+            ["Gadget", 200, "Q4"],
+            ["Widget", 100, "Q1"],
+            ["Widget", 150, "Q2"],
+            ["Widget", 200, "Q3"],
+            ["Widget", 250, "Q4"],
+        ]
+    );
 }
 
 #[tokio::test]
@@ -119,8 +201,12 @@ async fn test_unpivot_with_alias() {
         .unwrap();
 
     let result = session
-        .execute_sql("SELECT * FROM data UNPIVOT(value FOR type IN (val_a AS 'A', val_b AS 'B')) ORDER BY id, type").await;
-    assert!(result.is_ok() || result.is_err());
+        .execute_sql("SELECT * FROM data UNPIVOT(value FOR type IN (val_a AS 'A', val_b AS 'B')) ORDER BY id, type").await
+        .unwrap();
+    assert_table_eq!(
+        result,
+        [[1, 10, "A"], [1, 20, "B"], [2, 30, "A"], [2, 40, "B"]]
+    );
 }
 
 #[tokio::test]
@@ -136,8 +222,17 @@ async fn test_unpivot_include_nulls() {
         .unwrap();
 
     let result = session
-        .execute_sql("SELECT * FROM data UNPIVOT INCLUDE NULLS (value FOR column_name IN (col1, col2)) ORDER BY id").await;
-    assert!(result.is_ok() || result.is_err());
+        .execute_sql("SELECT * FROM data UNPIVOT INCLUDE NULLS (value FOR column_name IN (col1, col2)) ORDER BY id, column_name").await
+        .unwrap();
+    assert_table_eq!(
+        result,
+        [
+            [1, 10, "col1"],
+            [1, null, "col2"],
+            [2, null, "col1"],
+            [2, 20, "col2"],
+        ]
+    );
 }
 
 #[tokio::test]
@@ -153,8 +248,17 @@ async fn test_unpivot_exclude_nulls() {
         .unwrap();
 
     let result = session
-        .execute_sql("SELECT * FROM data UNPIVOT EXCLUDE NULLS (value FOR column_name IN (col1, col2)) ORDER BY id").await;
-    assert!(result.is_ok() || result.is_err());
+        .execute_sql("SELECT * FROM data UNPIVOT EXCLUDE NULLS (value FOR column_name IN (col1, col2)) ORDER BY id").await
+        .unwrap();
+    assert_table_eq!(result, [[1, 10, "col1"], [2, 20, "col2"]]);
+
+    let result = session
+        .execute_sql(
+            "SELECT * FROM data UNPIVOT (value FOR column_name IN (col1, col2)) ORDER BY id",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1, 10, "col1"], [2, 20, "col2"]]);
 }
 
 #[tokio::test]
@@ -163,8 +267,9 @@ async fn test_pivot_in_subquery() {
     setup_sales_table(&session).await;
 
     let result = session
-        .execute_sql("SELECT product, Q1 + Q2 AS first_half FROM (SELECT * FROM sales PIVOT(SUM(amount) FOR quarter IN ('Q1', 'Q2', 'Q3', 'Q4'))) ORDER BY product").await;
-    assert!(result.is_ok() || result.is_err());
+        .execute_sql("SELECT product, Q1 + Q2 AS first_half FROM (SELECT * FROM sales PIVOT(SUM(amount) FOR quarter IN ('Q1', 'Q2', 'Q3', 'Q4'))) ORDER BY product").await
+        .unwrap();
+    assert_table_eq!(result, [["Gadget", 200], ["Widget", 250]]);
 }
 
 #[tokio::test]
@@ -183,8 +288,9 @@ async fn test_pivot_with_null_values() {
         .execute_sql(
             "SELECT * FROM data PIVOT(SUM(value) FOR type IN ('X', 'Y', 'Z')) ORDER BY category",
         )
-        .await;
-    assert!(result.is_ok() || result.is_err());
+        .await
+        .unwrap();
+    assert_table_eq!(result, [["A", 10, 20, null], ["B", 30, null, null]]);
 }
 
 #[tokio::test]
@@ -199,6 +305,11 @@ async fn test_unpivot_multiple_columns() {
         .unwrap();
 
     let result = session
-        .execute_sql("SELECT * FROM metrics UNPIVOT((value, unit) FOR metric IN ((metric1_val, metric1_unit) AS 'metric1', (metric2_val, metric2_unit) AS 'metric2'))").await;
-    assert!(result.is_ok() || result.is_err());
+        .execute_sql("SELECT * FROM metrics UNPIVOT((value, unit) FOR metric IN ((metric1_val, metric1_unit) AS 'metric1', (metric2_val, metric2_unit) AS 'metric2')) ORDER BY metric").await
+        .unwrap();
+    assert_eq!(column_names(&result), ["id", "value", "unit", "metric"]);
+    assert_table_eq!(
+        result,
+        [[1, 100, "kg", "metric1"], [1, 200, "m", "metric2"]]
+    );
 }
@@ -0,0 +1,245 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use yachtsql::{LogicalPlan, OptimizerRule, YachtSQLSession};
+
+use crate::assert_table_eq;
+use crate::common::create_session;
+
+#[derive(Default)]
+struct CountPlans {
+    calls: AtomicUsize,
+}
+
+impl OptimizerRule for CountPlans {
+    fn name(&self) -> &str {
+        "count_plans"
+    }
+
+    fn rewrite(&self, _plan: &LogicalPlan) -> yachtsql::Result<Option<LogicalPlan>> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        Ok(None)
+    }
+}
+
+async fn setup(session: &YachtSQLSession) -> Arc<CountPlans> {
+    session
+        .execute_sql("CREATE TABLE items (id INT64, name STRING, price INT64)")
+        .await
+        .unwrap();
+    session
+        .execute_sql(
+            "INSERT INTO items VALUES (1, 'pen', 3), (2, 'book', 12), (3, 'lamp', 40), (4, 'cup', 7)",
+        )
+        .await
+        .unwrap();
+    let counter = Arc::new(CountPlans::default());
+    session.register_optimizer_rule(counter.clone());
+    counter
+}
+
+async fn planned(session: &YachtSQLSession, counter: &CountPlans, sql: &str) -> bool {
+    let before = counter.calls.load(Ordering::SeqCst);
+    session.execute_sql(sql).await.unwrap();
+    counter.calls.load(Ordering::SeqCst) != before
+}
+
+#[tokio::test]
+async fn test_literals_whitespace_and_keyword_case_share_a_plan() {
+    let session = create_session();
+    let counter = setup(&session).await;
+
+    assert!(planned(&session, &counter, "SELECT name FROM items WHERE id = 1").await);
+    assert!(
+        !planned(
+            &session,
+            &counter,
+            "select name\n  from items\n  where id = 3"
+        )
+        .await
+    );
+
+    let result = session
+        .execute_sql("SELECT name FROM items WHERE id = 2")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [["book"]]);
+
+    let sql = "SELECT id FROM items WHERE price BETWEEN 5 AND 20 AND name <> 'cup' ORDER BY id";
+    assert!(planned(&session, &counter, sql).await);
+    let result = session
+        .execute_sql(
+            "SELECT id FROM items WHERE price BETWEEN 1 AND 50 AND name <> 'pen' ORDER BY id",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[2], [3], [4]]);
+    assert!(
+        !planned(
+            &session,
+            &counter,
+            "SELECT id FROM items WHERE price BETWEEN 0 AND 10 AND name <> 'x' ORDER BY id"
+        )
+        .await
+    );
+
+    let result = session
+        .execute_sql("SELECT id FROM items WHERE name IN ('lamp', 'pen') ORDER BY id")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1], [3]]);
+    let result = session
+        .execute_sql("SELECT id FROM items WHERE name IN ('cup', 'book') ORDER BY id")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[2], [4]]);
+}
+
+#[tokio::test]
+async fn test_literals_outside_where_are_part_of_the_key() {
+    let session = create_session();
+    let counter = setup(&session).await;
+
+    let result = session
+        .execute_sql("SELECT price * 2 AS doubled FROM items WHERE id = 1")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[6]]);
+    let result = session
+        .execute_sql("SELECT price * 3 AS doubled FROM items WHERE id = 1")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[9]]);
+
+    let result = session
+        .execute_sql("SELECT name FROM items ORDER BY name LIMIT 2")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [["book"], ["cup"]]);
+    assert!(
+        planned(
+            &session,
+            &counter,
+            "SELECT name FROM items ORDER BY name LIMIT 3"
+        )
+        .await
+    );
+}
+
+#[tokio::test]
+async fn test_unrelated_ddl_keeps_cached_plans() {
+    let session = create_session();
+    let counter = setup(&session).await;
+
+    let sql = "SELECT COUNT(*) FROM items WHERE price > 5";
+    assert!(planned(&session, &counter, sql).await);
+
+    for _ in 0..3 {
+        session
+            .execute_sql("CREATE TEMP TABLE scratch (x INT64)")
+            .await
+            .unwrap();
+        session
+            .execute_sql("INSERT INTO scratch VALUES (1)")
+            .await
+            .unwrap();
+        session.execute_sql("DROP TABLE scratch").await.unwrap();
+        session
+            .execute_sql("CREATE TEMP FUNCTION twice(x INT64) AS (x * 2)")
+            .await
+            .unwrap();
+        session.execute_sql("DROP FUNCTION twice").await.unwrap();
+        assert!(!planned(&session, &counter, sql).await);
+    }
+}
+
+#[tokio::test]
+async fn test_ddl_evicts_dependent_plans() {
+    let session = create_session();
+    let counter = setup(&session).await;
+
+    let sql = "SELECT * FROM items WHERE id = 1";
+    assert!(planned(&session, &counter, sql).await);
+    session
+        .execute_sql("ALTER TABLE items ADD COLUMN stock INT64")
+        .await
+        .unwrap();
+    assert!(planned(&session, &counter, sql).await);
+    let result = session.execute_sql(sql).await.unwrap();
+    assert_table_eq!(result, [[1, "pen", 3, null]]);
+
+    session
+        .execute_sql("CREATE VIEW cheap AS SELECT id FROM items WHERE price < 10")
+        .await
+        .unwrap();
+    let view_sql = "SELECT COUNT(*) FROM cheap WHERE id > 0";
+    assert!(planned(&session, &counter, view_sql).await);
+    assert!(!planned(&session, &counter, view_sql).await);
+    session
+        .execute_sql("CREATE OR REPLACE VIEW cheap AS SELECT id FROM items WHERE price < 5")
+        .await
+        .unwrap();
+    assert!(planned(&session, &counter, view_sql).await);
+    let result = session.execute_sql(view_sql).await.unwrap();
+    assert_table_eq!(result, [[1]]);
+
+    session
+        .execute_sql("CREATE TEMP FUNCTION markup(x INT64) AS (x + 1)")
+        .await
+        .unwrap();
+    let fn_sql = "SELECT markup(price) FROM items WHERE id = 2";
+    assert!(planned(&session, &counter, fn_sql).await);
+    session
+        .execute_sql("CREATE OR REPLACE TEMP FUNCTION markup(x INT64) AS (x + 100)")
+        .await
+        .unwrap();
+    assert!(planned(&session, &counter, fn_sql).await);
+    let result = session.execute_sql(fn_sql).await.unwrap();
+    assert_table_eq!(result, [[112]]);
+}
+
+#[tokio::test]
+async fn test_ddl_in_scripts_and_procedures_evicts_dependent_plans() {
+    let session = create_session();
+    let counter = setup(&session).await;
+
+    session
+        .execute_sql("CREATE TABLE scratch (x INT64)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO scratch VALUES (1)")
+        .await
+        .unwrap();
+    let sql = "SELECT * FROM scratch WHERE x = 1";
+    assert!(planned(&session, &counter, sql).await);
+
+    session
+        .execute_sql(
+            "BEGIN
+                CREATE OR REPLACE TABLE scratch (x INT64, y STRING);
+                INSERT INTO scratch VALUES (1, 'a');
+            END",
+        )
+        .await
+        .unwrap();
+    assert!(planned(&session, &counter, sql).await);
+    let result = session.execute_sql(sql).await.unwrap();
+    assert_table_eq!(result, [[1, "a"]]);
+
+    session
+        .execute_sql(
+            "CREATE PROCEDURE rebuild_scratch()
+            BEGIN
+                CREATE OR REPLACE TABLE scratch (x INT64, y STRING, z BOOL);
+                INSERT INTO scratch VALUES (1, 'b', TRUE);
+            END",
+        )
+        .await
+        .unwrap();
+    assert!(!planned(&session, &counter, sql).await);
+    session.execute_sql("CALL rebuild_scratch()").await.unwrap();
+    assert!(planned(&session, &counter, sql).await);
+    let result = session.execute_sql(sql).await.unwrap();
+    assert_table_eq!(result, [[1, "b", true]]);
+}
@@ -0,0 +1,297 @@
+use std::collections::HashMap;
+
+use yachtsql::{DataType, Value};
+
+use crate::assert_table_eq;
+use crate::common::create_session;
+
+async fn setup_users(session: &yachtsql::YachtSQLSession) {
+    session
+        .execute_sql("CREATE TABLE users (id INT64, name STRING, score FLOAT64)")
+        .await
+        .unwrap();
+    session
+        .execute_sql(
+            "INSERT INTO users VALUES (1, 'Alice', 9.5), (2, 'Bob', 7.0), (3, 'Carol', 8.25)",
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_prepared_named_parameters() {
+    let session = create_session();
+    setup_users(&session).await;
+
+    let stmt = session
+        .prepare("SELECT name FROM users WHERE id >= @min_id AND name != @skip ORDER BY id")
+        .unwrap();
+    let result = stmt
+        .execute([
+            ("min_id", Value::int64(2)),
+            ("skip", Value::string("Carol")),
+        ])
+        .await
+        .unwrap();
+    assert_table_eq!(result, [["Bob"]]);
+
+    let result = stmt
+        .execute([("min_id", Value::int64(1)), ("skip", Value::string("Bob"))])
+        .await
+        .unwrap();
+    assert_table_eq!(result, [["Alice"], ["Carol"]]);
+}
+
+#[tokio::test]
+async fn test_prepared_positional_parameters() {
+    let session = create_session();
+    setup_users(&session).await;
+
+    let stmt = session
+        .prepare("SELECT id, name FROM users WHERE score > ? AND id < ? ORDER BY id")
+        .unwrap();
+    let result = stmt
+        .execute([Value::float64(7.5), Value::int64(3)])
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1, "Alice"]]);
+
+    let result = stmt
+        .execute(vec![Value::float64(0.0), Value::int64(10)])
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1, "Alice"], [2, "Bob"], [3, "Carol"]]);
+}
+
+#[tokio::test]
+async fn test_prepared_named_parameters_from_map() {
+    let session = create_session();
+    setup_users(&session).await;
+
+    let stmt = session
+        .prepare("SELECT COUNT(*) FROM users WHERE name = @Name")
+        .unwrap();
+    let mut params = HashMap::new();
+    params.insert("@name".to_string(), Value::string("Bob"));
+    let result = stmt.query(params).await.unwrap();
+    assert_eq!(result.rows.len(), 1);
+    assert_eq!(result.rows[0].values()[0], Value::int64(1));
+}
+
+#[tokio::test]
+async fn test_prepared_parameter_type_inference() {
+    let session = create_session();
+    setup_users(&session).await;
+
+    let stmt = session
+        .prepare("SELECT name FROM users WHERE id = @id OR score BETWEEN @low AND @high")
+        .unwrap();
+    let params: Vec<(Option<String>, DataType)> = stmt
+        .parameters()
+        .into_iter()
+        .map(|p| (p.name, p.data_type))
+        .collect();
+    assert_eq!(
+        params,
+        vec![
+            (Some("id".to_string()), DataType::Int64),
+            (Some("low".to_string()), DataType::Float64),
+            (Some("high".to_string()), DataType::Float64),
+        ]
+    );
+
+    let stmt = session
+        .prepare("INSERT INTO users (name, id) VALUES (?, ?)")
+        .unwrap();
+    let types: Vec<DataType> = stmt.parameters().into_iter().map(|p| p.data_type).collect();
+    assert_eq!(types, vec![DataType::String, DataType::Int64]);
+}
+
+#[tokio::test]
+async fn test_prepared_parameters_are_coerced() {
+    let session = create_session();
+    setup_users(&session).await;
+
+    let stmt = session
+        .prepare("SELECT name FROM users WHERE score = @score")
+        .unwrap();
+    let result = stmt.execute([("score", Value::int64(7))]).await.unwrap();
+    assert_table_eq!(result, [["Bob"]]);
+
+    let err = stmt
+        .execute([("score", Value::string("7"))])
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Query parameter @score has type STRING but FLOAT64 was expected")
+    );
+}
+
+#[tokio::test]
+async fn test_prepared_dml() {
+    let session = create_session();
+    setup_users(&session).await;
+
+    let insert = session
+        .prepare("INSERT INTO users VALUES (@id, @name, @score)")
+        .unwrap();
+    insert
+        .execute([
+            ("id", Value::int64(4)),
+            ("name", Value::string("Dave")),
+            ("score", Value::float64(6.5)),
+        ])
+        .await
+        .unwrap();
+    insert
+        .execute([
+            ("id", Value::int64(5)),
+            ("name", Value::string("Eve")),
+            ("score", Value::Null),
+        ])
+        .await
+        .unwrap();
+
+    let update = session
+        .prepare("UPDATE users SET score = ? WHERE id = ?")
+        .unwrap();
+    update
+        .execute([Value::float64(1.0), Value::int64(5)])
+        .await
+        .unwrap();
+
+    let delete = session.prepare("DELETE FROM users WHERE id < ?").unwrap();
+    delete.execute([Value::int64(3)]).await.unwrap();
+
+    let result = session
+        .execute_sql("SELECT id, name, score FROM users ORDER BY id")
+        .await
+        .unwrap();
+    assert_table_eq!(
+        result,
+        [[3, "Carol", 8.25], [4, "Dave", 6.5], [5, "Eve", 1.0]]
+    );
+}
+
+#[tokio::test]
+async fn test_prepared_parameter_in_subquery() {
+    let session = create_session();
+    setup_users(&session).await;
+
+    let stmt = session
+        .prepare("SELECT name FROM users WHERE id IN (SELECT id FROM users WHERE score > @min)")
+        .unwrap();
+    let result = stmt.execute([("min", Value::float64(8.0))]).await.unwrap();
+    let mut names: Vec<String> = result
+        .to_records()
+        .unwrap()
+        .into_iter()
+        .map(|r| r.values()[0].as_str().unwrap().to_string())
+        .collect();
+    names.sort();
+    assert_eq!(names, vec!["Alice".to_string(), "Carol".to_string()]);
+}
+
+#[tokio::test]
+async fn test_prepared_parameter_binding_errors() {
+    let session = create_session();
+    setup_users(&session).await;
+
+    let stmt = session
+        .prepare("SELECT name FROM users WHERE id = @id")
+        .unwrap();
+    let err = stmt.execute(Vec::<Value>::new()).await.unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Missing value for query parameter @id")
+    );
+    let err = stmt
+        .execute([("id", Value::int64(1)), ("other", Value::int64(2))])
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Query has no parameter named @OTHER")
+    );
+
+    let stmt = session.prepare("SELECT ? + ?").unwrap();
+    let err = stmt.execute([Value::int64(1)]).await.unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Query expects 2 positional parameters but 1 were provided")
+    );
+
+    let err = session
+        .prepare("SELECT * FROM users WHERE id = @id AND score > ?")
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("cannot mix named"));
+}
+
+#[tokio::test]
+async fn test_prepared_statement_replans_after_schema_change() {
+    let session = create_session();
+    setup_users(&session).await;
+
+    let stmt = session
+        .prepare("SELECT * FROM users WHERE id = @id")
+        .unwrap();
+    let result = stmt.execute([("id", Value::int64(1))]).await.unwrap();
+    assert_table_eq!(result, [[1, "Alice", 9.5]]);
+
+    session
+        .execute_sql("ALTER TABLE users ADD COLUMN active BOOL")
+        .await
+        .unwrap();
+    let result = stmt.execute([("id", Value::int64(1))]).await.unwrap();
+    assert_table_eq!(result, [[1, "Alice", 9.5, null]]);
+}
+
+#[tokio::test]
+async fn test_prepared_statement_replans_after_view_or_function_redefinition() {
+    let session = create_session();
+    setup_users(&session).await;
+    session
+        .execute_sql("CREATE VIEW user_names AS SELECT id, name FROM users")
+        .await
+        .unwrap();
+    session
+        .execute_sql("CREATE FUNCTION bump(x INT64) AS (x + 1)")
+        .await
+        .unwrap();
+
+    let stmt = session
+        .prepare("SELECT name, bump(id) FROM user_names WHERE id = @id")
+        .unwrap();
+    let result = stmt.execute([("id", Value::int64(1))]).await.unwrap();
+    assert_table_eq!(result, [["Alice", 2]]);
+
+    session
+        .execute_sql(
+            "CREATE OR REPLACE VIEW user_names AS SELECT id, UPPER(name) AS name FROM users",
+        )
+        .await
+        .unwrap();
+    let result = stmt.execute([("id", Value::int64(1))]).await.unwrap();
+    assert_table_eq!(result, [["ALICE", 2]]);
+
+    session
+        .execute_sql("CREATE OR REPLACE FUNCTION bump(x INT64) AS (x + 100)")
+        .await
+        .unwrap();
+    let result = stmt.execute([("id", Value::int64(1))]).await.unwrap();
+    assert_table_eq!(result, [["ALICE", 101]]);
+}
+
+#[tokio::test]
+async fn test_session_variables_still_resolve() {
+    let session = create_session();
+    session.execute_sql("SET @threshold = 5").await.unwrap();
+    let result = session.execute_sql("SELECT @threshold + 1").await.unwrap();
+    assert_table_eq!(result, [[6]]);
+
+    let stmt = session.prepare("SELECT @threshold * @factor").unwrap();
+    let result = stmt.execute([("factor", Value::int64(3))]).await.unwrap();
+    assert_table_eq!(result, [[15]]);
+}
@@ -0,0 +1,207 @@
+use yachtsql::{Value, YachtSQLSession};
+
+use crate::common::create_session;
+
+async fn setup_tables(session: &YachtSQLSession) {
+    session
+        .execute_sql("CREATE TABLE sales (id INT64, region STRING, amount INT64)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO sales VALUES (1, 'east', 100), (2, 'west', 250), (3, 'east', 75)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("CREATE TABLE regions (name STRING)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO regions VALUES ('east'), ('west')")
+        .await
+        .unwrap();
+}
+
+async fn total(session: &YachtSQLSession, sql: &str) -> (i64, bool) {
+    let result = session.query(sql).await.unwrap();
+    (
+        result.first_value().unwrap().as_i64().unwrap(),
+        result.cache_hit,
+    )
+}
+
+#[tokio::test]
+async fn test_repeated_query_hits_cache() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let sql = "SELECT CAST(SUM(amount) AS INT64) FROM sales WHERE region = 'east'";
+    assert_eq!(total(&session, sql).await, (175, false));
+    assert_eq!(total(&session, sql).await, (175, true));
+    assert_eq!(
+        total(
+            &session,
+            "  SELECT CAST(SUM(amount) AS INT64)\n  FROM sales\n  WHERE region = 'east';"
+        )
+        .await,
+        (175, true)
+    );
+
+    let result = session.query("SELECT 'a  b' AS s").await.unwrap();
+    assert!(!result.cache_hit);
+    let result = session.query("SELECT 'a b' AS s").await.unwrap();
+    assert!(!result.cache_hit);
+    assert_eq!(result.first_value(), Some(&Value::string("a b")));
+}
+
+#[tokio::test]
+async fn test_cache_invalidated_by_changes_to_tables_read() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let sql =
+        "SELECT CAST(SUM(s.amount) AS INT64) FROM sales s JOIN regions r ON s.region = r.name";
+    assert_eq!(total(&session, sql).await, (425, false));
+
+    session
+        .execute_sql("CREATE TABLE audit (note STRING)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO audit VALUES ('unrelated')")
+        .await
+        .unwrap();
+    assert_eq!(total(&session, sql).await, (425, true));
+
+    session
+        .execute_sql("INSERT INTO sales VALUES (4, 'west', 50)")
+        .await
+        .unwrap();
+    assert_eq!(total(&session, sql).await, (475, false));
+    assert_eq!(total(&session, sql).await, (475, true));
+
+    session
+        .execute_sql("DELETE FROM regions WHERE name = 'west'")
+        .await
+        .unwrap();
+    assert_eq!(total(&session, sql).await, (175, false));
+}
+
+#[tokio::test]
+async fn test_use_query_cache_toggle() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let sql = "SELECT COUNT(*) FROM sales";
+    session
+        .execute_sql("SET @@use_query_cache = false")
+        .await
+        .unwrap();
+    assert_eq!(total(&session, sql).await, (3, false));
+    assert_eq!(total(&session, sql).await, (3, false));
+
+    session
+        .execute_sql("SET @@use_query_cache = true")
+        .await
+        .unwrap();
+    assert_eq!(total(&session, sql).await, (3, false));
+    assert_eq!(total(&session, sql).await, (3, true));
+}
+
+#[tokio::test]
+async fn test_nondeterministic_queries_are_not_cached() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    for sql in [
+        "SELECT COUNT(*) FROM sales WHERE RAND() < 2",
+        "SELECT COUNT(*) FROM sales WHERE CURRENT_TIMESTAMP() IS NOT NULL",
+        "SELECT COUNT(*) FROM sales WHERE region IN (SELECT name FROM regions)",
+    ] {
+        assert_eq!(total(&session, sql).await, (3, false));
+        assert_eq!(total(&session, sql).await, (3, false));
+    }
+
+    let result = session
+        .query("INSERT INTO regions VALUES ('north')")
+        .await
+        .unwrap();
+    assert!(!result.cache_hit);
+}
+
+#[tokio::test]
+async fn test_cache_keyed_on_parameters_and_variables() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    let stmt = session
+        .prepare("SELECT CAST(SUM(amount) AS INT64) FROM sales WHERE id >= @min_id")
+        .unwrap();
+    let first = stmt.query([("min_id", Value::int64(2))]).await.unwrap();
+    let again = stmt.query([("min_id", Value::int64(2))]).await.unwrap();
+    let other = stmt.query([("min_id", Value::int64(3))]).await.unwrap();
+    assert_eq!(first.first_value().and_then(Value::as_i64), Some(325));
+    assert!(!first.cache_hit);
+    assert_eq!(again.first_value().and_then(Value::as_i64), Some(325));
+    assert!(again.cache_hit);
+    assert_eq!(other.first_value().and_then(Value::as_i64), Some(75));
+    assert!(!other.cache_hit);
+
+    session
+        .execute_sql("DECLARE min_amount INT64 DEFAULT 80")
+        .await
+        .unwrap();
+    let sql = "SELECT COUNT(*) FROM sales WHERE amount > min_amount";
+    assert_eq!(total(&session, sql).await, (2, false));
+    assert_eq!(total(&session, sql).await, (2, true));
+    session.execute_sql("SET min_amount = 10").await.unwrap();
+    assert_eq!(total(&session, sql).await, (3, false));
+}
+
+#[tokio::test]
+async fn test_cache_respects_view_redefinition() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    session
+        .execute_sql("CREATE VIEW east_sales AS SELECT * FROM sales WHERE region = 'east'")
+        .await
+        .unwrap();
+    let sql = "SELECT COUNT(*) FROM east_sales";
+    assert_eq!(total(&session, sql).await, (2, false));
+    assert_eq!(total(&session, sql).await, (2, true));
+
+    session
+        .execute_sql(
+            "CREATE OR REPLACE VIEW east_sales AS SELECT * FROM sales WHERE region = 'west'",
+        )
+        .await
+        .unwrap();
+    assert_eq!(total(&session, sql).await, (1, false));
+}
+
+#[tokio::test]
+async fn test_cache_respects_view_redefinition_inside_script() {
+    let session = create_session();
+    setup_tables(&session).await;
+
+    session
+        .execute_sql("CREATE VIEW east_sales AS SELECT * FROM sales WHERE region = 'east'")
+        .await
+        .unwrap();
+    let sql = "SELECT COUNT(*) FROM east_sales";
+    assert_eq!(total(&session, sql).await, (2, false));
+    assert_eq!(
+        total(&session, "select count(*) from east_sales").await,
+        (2, true)
+    );
+
+    session
+        .execute_sql(
+            "BEGIN
+                CREATE OR REPLACE VIEW east_sales AS SELECT * FROM sales WHERE region = 'west';
+            END",
+        )
+        .await
+        .unwrap();
+    assert_eq!(total(&session, sql).await, (1, false));
+}
@@ -0,0 +1,310 @@
+use yachtsql::YachtSQLEngine;
+
+use crate::assert_table_eq;
+
+#[tokio::test]
+async fn test_sessions_share_tables() {
+    let engine = YachtSQLEngine::new();
+    let writer = engine.create_session();
+    let reader = engine.create_session();
+
+    writer
+        .execute_sql("CREATE TABLE events (id INT64, kind STRING)")
+        .await
+        .unwrap();
+    writer
+        .execute_sql("INSERT INTO events VALUES (1, 'open'), (2, 'close')")
+        .await
+        .unwrap();
+
+    let result = reader
+        .execute_sql("SELECT id, kind FROM events ORDER BY id")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1, "open"], [2, "close"]]);
+
+    reader
+        .execute_sql("INSERT INTO events VALUES (3, 'open')")
+        .await
+        .unwrap();
+    let result = writer
+        .execute_sql("SELECT COUNT(*) FROM events")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[3]]);
+}
+
+#[tokio::test]
+async fn test_sessions_share_views_and_functions() {
+    let engine = YachtSQLEngine::new();
+    let first = engine.create_session();
+    let second = engine.create_session();
+
+    first
+        .execute_sql("CREATE TABLE nums (n INT64)")
+        .await
+        .unwrap();
+    first
+        .execute_sql("INSERT INTO nums VALUES (1), (2), (3)")
+        .await
+        .unwrap();
+    first
+        .execute_sql("CREATE VIEW big_nums AS SELECT n FROM nums WHERE n > 1")
+        .await
+        .unwrap();
+    first
+        .execute_sql("CREATE FUNCTION triple(x INT64) AS (x * 3)")
+        .await
+        .unwrap();
+
+    let result = second
+        .execute_sql("SELECT triple(n) FROM big_nums ORDER BY n")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[6], [9]]);
+}
+
+#[tokio::test]
+async fn test_temp_tables_are_private_to_session() {
+    let engine = YachtSQLEngine::new();
+    let first = engine.create_session();
+    let second = engine.create_session();
+
+    first
+        .execute_sql("CREATE TEMP TABLE scratch (x INT64)")
+        .await
+        .unwrap();
+    first
+        .execute_sql("INSERT INTO scratch VALUES (1)")
+        .await
+        .unwrap();
+
+    assert!(second.execute_sql("SELECT * FROM scratch").await.is_err());
+
+    second
+        .execute_sql("CREATE TEMP TABLE scratch (x INT64)")
+        .await
+        .unwrap();
+    second
+        .execute_sql("INSERT INTO scratch VALUES (2), (3)")
+        .await
+        .unwrap();
+
+    let result = first.execute_sql("SELECT x FROM scratch").await.unwrap();
+    assert_table_eq!(result, [[1]]);
+    let result = second
+        .execute_sql("SELECT x FROM _SESSION.scratch ORDER BY x")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[2], [3]]);
+}
+
+#[tokio::test]
+async fn test_temp_table_shadows_shared_table() {
+    let engine = YachtSQLEngine::new();
+    let first = engine.create_session();
+    let second = engine.create_session();
+
+    first
+        .execute_sql("CREATE TABLE items (id INT64)")
+        .await
+        .unwrap();
+    first
+        .execute_sql("INSERT INTO items VALUES (1)")
+        .await
+        .unwrap();
+    second
+        .execute_sql("CREATE TEMP TABLE items (id INT64)")
+        .await
+        .unwrap();
+    second
+        .execute_sql("INSERT INTO items VALUES (100)")
+        .await
+        .unwrap();
+
+    let result = second.execute_sql("SELECT id FROM items").await.unwrap();
+    assert_table_eq!(result, [[100]]);
+    let result = first.execute_sql("SELECT id FROM items").await.unwrap();
+    assert_table_eq!(result, [[1]]);
+
+    second.execute_sql("DROP TABLE items").await.unwrap();
+    let result = second.execute_sql("SELECT id FROM items").await.unwrap();
+    assert_table_eq!(result, [[1]]);
+}
+
+#[tokio::test]
+async fn test_temp_tables_dropped_with_session() {
+    let engine = YachtSQLEngine::new();
+    let session = engine.create_session();
+    session
+        .execute_sql("CREATE TEMP TABLE scratch (x INT64)")
+        .await
+        .unwrap();
+    let temp_name = format!("_SESSION_{}.scratch", session.session().id());
+    assert!(engine.catalog().table_exists(&temp_name));
+
+    drop(session);
+    assert!(!engine.catalog().table_exists(&temp_name));
+}
+
+#[tokio::test]
+async fn test_temp_functions_are_private_to_session() {
+    let engine = YachtSQLEngine::new();
+    let first = engine.create_session();
+    let second = engine.create_session();
+
+    first
+        .execute_sql("CREATE TEMP FUNCTION scale(x INT64) AS (x * 2)")
+        .await
+        .unwrap();
+    let result = first.execute_sql("SELECT scale(21)").await.unwrap();
+    assert_table_eq!(result, [[42]]);
+    assert!(second.execute_sql("SELECT scale(21)").await.is_err());
+
+    second
+        .execute_sql("CREATE TEMP FUNCTION scale(x INT64) AS (x * 10)")
+        .await
+        .unwrap();
+    let result = second.execute_sql("SELECT scale(21)").await.unwrap();
+    assert_table_eq!(result, [[210]]);
+    let result = first.execute_sql("SELECT scale(21)").await.unwrap();
+    assert_table_eq!(result, [[42]]);
+
+    let temp_name = format!("_SESSION_{}.scale", first.session().id());
+    assert!(engine.catalog().function_exists(&temp_name));
+    drop(first);
+    assert!(!engine.catalog().function_exists(&temp_name));
+    let result = second.execute_sql("SELECT scale(21)").await.unwrap();
+    assert_table_eq!(result, [[210]]);
+}
+
+#[tokio::test]
+async fn test_variables_are_private_to_session() {
+    let engine = YachtSQLEngine::new();
+    let first = engine.create_session();
+    let second = engine.create_session();
+
+    first
+        .execute_sql("DECLARE limit_value INT64 DEFAULT 10")
+        .await
+        .unwrap();
+    second
+        .execute_sql("DECLARE limit_value INT64 DEFAULT 20")
+        .await
+        .unwrap();
+
+    let result = first.execute_sql("SELECT limit_value").await.unwrap();
+    assert_table_eq!(result, [[10]]);
+    let result = second.execute_sql("SELECT limit_value").await.unwrap();
+    assert_table_eq!(result, [[20]]);
+}
+
+#[tokio::test]
+async fn test_system_variables_are_private_to_session() {
+    let engine = YachtSQLEngine::new();
+    let first = engine.create_session();
+    let second = engine.create_session();
+
+    first
+        .execute_sql("SET @@time_zone = 'America/Los_Angeles'")
+        .await
+        .unwrap();
+
+    let result = first.execute_sql("SELECT @@time_zone").await.unwrap();
+    assert_table_eq!(result, [["America/Los_Angeles"]]);
+    let result = second.execute_sql("SELECT @@time_zone").await.unwrap();
+    assert_table_eq!(result, [["UTC"]]);
+}
+
+#[tokio::test]
+async fn test_current_schema_is_private_to_session() {
+    let engine = YachtSQLEngine::new();
+    let first = engine.create_session();
+    let second = engine.create_session();
+
+    first.execute_sql("CREATE SCHEMA sales").await.unwrap();
+    first.execute_sql("CREATE SCHEMA hr").await.unwrap();
+    first
+        .execute_sql("CREATE TABLE sales.people (name STRING)")
+        .await
+        .unwrap();
+    first
+        .execute_sql("CREATE TABLE hr.people (name STRING)")
+        .await
+        .unwrap();
+    first
+        .execute_sql("INSERT INTO sales.people VALUES ('seller')")
+        .await
+        .unwrap();
+    first
+        .execute_sql("INSERT INTO hr.people VALUES ('recruiter')")
+        .await
+        .unwrap();
+
+    first.execute_sql("SET search_path TO sales").await.unwrap();
+    second.execute_sql("SET search_path TO hr").await.unwrap();
+
+    let result = first.execute_sql("SELECT name FROM people").await.unwrap();
+    assert_table_eq!(result, [["seller"]]);
+    let result = second.execute_sql("SELECT name FROM people").await.unwrap();
+    assert_table_eq!(result, [["recruiter"]]);
+}
+
+#[tokio::test]
+async fn test_cached_plans_follow_session_name_resolution() {
+    let engine = YachtSQLEngine::new();
+    let first = engine.create_session();
+    let second = engine.create_session();
+
+    first
+        .execute_sql("CREATE TABLE items (id INT64, name STRING)")
+        .await
+        .unwrap();
+    first
+        .execute_sql("INSERT INTO items VALUES (1, 'shared')")
+        .await
+        .unwrap();
+    let result = first.execute_sql("SELECT * FROM items").await.unwrap();
+    assert_table_eq!(result, [[1, "shared"]]);
+
+    second
+        .execute_sql("CREATE TEMP TABLE items (code STRING, qty INT64, price FLOAT64)")
+        .await
+        .unwrap();
+    second
+        .execute_sql("INSERT INTO items VALUES ('temp', 5, 2.5)")
+        .await
+        .unwrap();
+    let result = second.execute_sql("SELECT * FROM items").await.unwrap();
+    assert_table_eq!(result, [["temp", 5, 2.5]]);
+    let result = first.execute_sql("SELECT * FROM items").await.unwrap();
+    assert_table_eq!(result, [[1, "shared"]]);
+    let result = second.execute_sql("SELECT * FROM items").await.unwrap();
+    assert_table_eq!(result, [["temp", 5, 2.5]]);
+
+    first.execute_sql("CREATE SCHEMA sales").await.unwrap();
+    first.execute_sql("CREATE SCHEMA hr").await.unwrap();
+    first
+        .execute_sql("CREATE TABLE sales.people (name STRING)")
+        .await
+        .unwrap();
+    first
+        .execute_sql("CREATE TABLE hr.people (id INT64, active BOOL)")
+        .await
+        .unwrap();
+    first
+        .execute_sql("INSERT INTO sales.people VALUES ('seller')")
+        .await
+        .unwrap();
+    first
+        .execute_sql("INSERT INTO hr.people VALUES (7, TRUE)")
+        .await
+        .unwrap();
+    first.execute_sql("SET search_path TO sales").await.unwrap();
+    second.execute_sql("SET search_path TO hr").await.unwrap();
+
+    let result = first.execute_sql("SELECT * FROM people").await.unwrap();
+    assert_table_eq!(result, [["seller"]]);
+    let result = second.execute_sql("SELECT * FROM people").await.unwrap();
+    assert_table_eq!(result, [[7, true]]);
+}
@@ -0,0 +1,243 @@
+use futures::{StreamExt, TryStreamExt};
+use yachtsql::{Table, Value, YachtSQLSession};
+
+use crate::assert_table_eq;
+use crate::common::create_session;
+
+async fn setup_table(session: &YachtSQLSession) {
+    session
+        .execute_sql("CREATE TABLE numbers (id INT64, grp INT64)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO numbers (id) SELECT n FROM UNNEST(GENERATE_ARRAY(1, 20000)) AS n")
+        .await
+        .unwrap();
+    session
+        .execute_sql("UPDATE numbers SET grp = MOD(id, 7) WHERE TRUE")
+        .await
+        .unwrap();
+}
+
+fn rows(batches: &[Table]) -> Vec<Vec<Value>> {
+    batches
+        .iter()
+        .flat_map(|batch| {
+            (0..batch.row_count()).map(|i| batch.get_row(i).unwrap().values().to_vec())
+        })
+        .collect()
+}
+
+fn table_rows(table: &Table) -> Vec<Vec<Value>> {
+    rows(std::slice::from_ref(table))
+}
+
+#[tokio::test]
+async fn test_stream_yields_batches_in_order() {
+    let session = create_session();
+    setup_table(&session).await;
+
+    let sql = "SELECT id, grp * 10 FROM numbers WHERE grp < 3";
+    let stream = session.query_stream(sql).await.unwrap();
+    assert_eq!(stream.schema().field_count(), 2);
+    let batches: Vec<Table> = stream.try_collect().await.unwrap();
+
+    assert!(batches.len() > 1);
+    assert!(batches.iter().all(|batch| batch.row_count() > 0));
+    let expected = session.execute_sql(sql).await.unwrap();
+    assert_eq!(rows(&batches), table_rows(&expected));
+}
+
+#[tokio::test]
+async fn test_stream_limit_stops_upstream_work() {
+    let session = create_session();
+    setup_table(&session).await;
+
+    let sql = "SELECT id, grp FROM numbers WHERE DIV(100, id - 15000) = 0 LIMIT 3 OFFSET 2";
+    assert!(session.execute_sql(sql).await.is_err());
+
+    let batches: Vec<Table> = session
+        .query_stream(sql)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(batches.len(), 1);
+    assert_table_eq!(batches[0].clone(), [[3, 3], [4, 4], [5, 5]]);
+}
+
+#[tokio::test]
+async fn test_dropping_stream_stops_upstream_work() {
+    let session = create_session();
+    setup_table(&session).await;
+
+    let mut stream = session
+        .query_stream("SELECT id, DIV(100, id - 15000) FROM numbers")
+        .await
+        .unwrap();
+    let first = stream.next().await.unwrap().unwrap();
+    assert_eq!(first.get_row(0).unwrap().values()[0], Value::Int64(1));
+    drop(stream);
+
+    let mut stream = session
+        .query_stream("SELECT id, DIV(100, id - 15000) FROM numbers")
+        .await
+        .unwrap();
+    let mut failed = false;
+    while let Some(batch) = stream.next().await {
+        if batch.is_err() {
+            failed = true;
+        }
+    }
+    assert!(failed);
+}
+
+#[tokio::test]
+async fn test_stream_scans_table_as_of_open() {
+    let session = create_session();
+    setup_table(&session).await;
+
+    let stream = session
+        .query_stream("SELECT id * 2 AS doubled FROM numbers WHERE grp = 0 LIMIT 4 OFFSET 1")
+        .await
+        .unwrap();
+    assert_eq!(stream.schema().fields()[0].name, "doubled");
+    session
+        .execute_sql("DELETE FROM numbers WHERE TRUE")
+        .await
+        .unwrap();
+
+    let batches: Vec<Table> = stream.try_collect().await.unwrap();
+    assert_eq!(batches.len(), 1);
+    assert_table_eq!(batches[0].clone(), [[28], [42], [56], [70]]);
+}
+
+#[tokio::test]
+async fn test_stream_over_pipeline_breakers() {
+    let session = create_session();
+    setup_table(&session).await;
+
+    let batches: Vec<Table> = session
+        .query_stream(
+            "SELECT grp, COUNT(*) AS n FROM numbers GROUP BY grp HAVING grp > 3 ORDER BY grp",
+        )
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(
+        rows(&batches),
+        vec![
+            vec![Value::Int64(4), Value::Int64(2857)],
+            vec![Value::Int64(5), Value::Int64(2857)],
+            vec![Value::Int64(6), Value::Int64(2857)],
+        ]
+    );
+
+    let sql = "SELECT a.id FROM numbers a JOIN numbers b ON a.id = b.id * 2 WHERE b.grp = 1";
+    let batches: Vec<Table> = session
+        .query_stream(sql)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    let expected = session.execute_sql(sql).await.unwrap();
+    assert_eq!(rows(&batches), table_rows(&expected));
+}
+
+#[tokio::test]
+async fn test_stream_empty_result_and_statements() {
+    let session = create_session();
+    setup_table(&session).await;
+
+    let stream = session
+        .query_stream("SELECT id AS only_id FROM numbers WHERE id < 0")
+        .await
+        .unwrap();
+    assert_eq!(stream.schema().fields()[0].name, "only_id");
+    let batches: Vec<Table> = stream.try_collect().await.unwrap();
+    assert!(batches.is_empty());
+
+    let batches: Vec<Table> = session
+        .query_stream("DELETE FROM numbers WHERE id > 10")
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert!(rows(&batches).len() <= 1);
+
+    let count: usize = session
+        .query_stream("SELECT * FROM numbers")
+        .await
+        .unwrap()
+        .map(|batch| batch.unwrap().row_count())
+        .fold(0, |total, n| async move { total + n })
+        .await;
+    assert_eq!(count, 10);
+}
+
+#[tokio::test]
+async fn test_stream_uses_session_variables() {
+    let session = create_session();
+    setup_table(&session).await;
+
+    session
+        .execute_sql("DECLARE threshold INT64 DEFAULT 19998")
+        .await
+        .unwrap();
+    let batches: Vec<Table> = session
+        .query_stream("SELECT id FROM numbers WHERE id > threshold")
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(
+        rows(&batches),
+        vec![vec![Value::Int64(19999)], vec![Value::Int64(20000)]]
+    );
+}
+
+#[tokio::test]
+async fn test_stream_checks_query_timeout_between_batches() {
+    let session = create_session();
+    setup_table(&session).await;
+    session
+        .execute_sql("SET @@query_timeout_ms = 500")
+        .await
+        .unwrap();
+
+    let mut stream = session
+        .query_stream("SELECT id FROM numbers")
+        .await
+        .unwrap();
+    assert!(stream.next().await.unwrap().is_ok());
+    std::thread::sleep(std::time::Duration::from_millis(600));
+    let err = stream.next().await.unwrap().unwrap_err().to_string();
+    assert!(err.contains("500 ms"), "{}", err);
+    assert!(stream.next().await.is_none());
+}
+
+#[tokio::test]
+async fn test_cancel_stream_between_batches() {
+    let session = create_session();
+    setup_table(&session).await;
+
+    let (handle, stream) = session.query_stream_cancellable("SELECT id FROM numbers");
+    let mut stream = stream.await.unwrap();
+    assert!(stream.next().await.unwrap().is_ok());
+    handle.cancel();
+    assert!(matches!(
+        stream.next().await.unwrap(),
+        Err(yachtsql::Error::Cancelled)
+    ));
+    assert!(stream.next().await.is_none());
+
+    let (handle, stream) = session.query_stream_cancellable("SELECT id FROM numbers");
+    handle.cancel();
+    assert!(matches!(stream.await, Err(yachtsql::Error::Cancelled)));
+}
@@ -1,4 +1,5 @@
-use crate::common::create_session;
+use crate::assert_table_eq;
+use crate::common::{create_session, create_session_with_history_clock};
 
 #[tokio::test]
 async fn test_for_system_time_as_of_timestamp() {
@@ -292,3 +293,254 @@ async fn test_assert_comparison() {
         .await;
     assert!(result.is_ok());
 }
+
+#[tokio::test]
+async fn test_for_system_time_reads_earlier_version() {
+    let (session, clock) = create_session_with_history_clock();
+    session
+        .execute_sql("CREATE TABLE balances (id INT64, amount INT64)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO balances VALUES (1, 100), (2, 200)")
+        .await
+        .unwrap();
+    clock.mark(&session, "@before_fix").await;
+    session
+        .execute_sql("UPDATE balances SET amount = 0 WHERE id = 1")
+        .await
+        .unwrap();
+    session
+        .execute_sql("DELETE FROM balances WHERE id = 2")
+        .await
+        .unwrap();
+
+    let result = session
+        .execute_sql(
+            "SELECT id, amount FROM balances FOR SYSTEM_TIME AS OF @before_fix ORDER BY id",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1, 100], [2, 200]]);
+
+    let result = session
+        .execute_sql("SELECT id, amount FROM balances ORDER BY id")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1, 0]]);
+}
+
+#[tokio::test]
+async fn test_for_system_time_restores_deleted_rows() {
+    let (session, clock) = create_session_with_history_clock();
+    session
+        .execute_sql("CREATE TABLE accounts (id INT64, name STRING)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO accounts VALUES (1, 'a'), (2, 'b'), (3, 'c')")
+        .await
+        .unwrap();
+    clock.mark(&session, "@checkpoint").await;
+    session
+        .execute_sql("DELETE FROM accounts WHERE id > 1")
+        .await
+        .unwrap();
+
+    session
+        .execute_sql(
+            "INSERT INTO accounts
+            SELECT old.id, old.name
+            FROM accounts FOR SYSTEM_TIME AS OF @checkpoint AS old
+            LEFT JOIN accounts AS cur ON old.id = cur.id
+            WHERE cur.id IS NULL",
+        )
+        .await
+        .unwrap();
+
+    let result = session
+        .execute_sql("SELECT id, name FROM accounts ORDER BY id")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1, "a"], [2, "b"], [3, "c"]]);
+}
+
+#[tokio::test]
+async fn test_for_system_time_follows_schema_changes() {
+    let (session, clock) = create_session_with_history_clock();
+    session
+        .execute_sql("CREATE TABLE people (id INT64, name STRING, age INT64)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO people VALUES (1, 'ann', 30), (2, 'bob', 40)")
+        .await
+        .unwrap();
+    clock.mark(&session, "@before_alter").await;
+    session
+        .execute_sql("ALTER TABLE people DROP COLUMN name")
+        .await
+        .unwrap();
+    session
+        .execute_sql("ALTER TABLE people ADD COLUMN city STRING")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO people VALUES (3, 50, 'Oslo')")
+        .await
+        .unwrap();
+
+    let result = session
+        .execute_sql("SELECT * FROM people FOR SYSTEM_TIME AS OF @before_alter ORDER BY id")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1, 30, null], [2, 40, null]]);
+
+    let result = session
+        .execute_sql(
+            "SELECT city, age FROM people FOR SYSTEM_TIME AS OF @before_alter WHERE id = 2",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[null, 40]]);
+
+    session
+        .execute_sql("ALTER TABLE people ALTER COLUMN age SET DATA TYPE NUMERIC")
+        .await
+        .unwrap();
+    let err = session
+        .execute_sql("SELECT age FROM people FOR SYSTEM_TIME AS OF @before_alter")
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("Column age of table people was INT64"),
+        "{}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn test_for_system_time_keeps_every_version_in_window() {
+    let (session, clock) = create_session_with_history_clock();
+    session
+        .execute_sql("CREATE TABLE counter (n INT64)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO counter VALUES (0)")
+        .await
+        .unwrap();
+    clock.mark(&session, "@start").await;
+    for _ in 0..300 {
+        session
+            .execute_sql("UPDATE counter SET n = n + 1 WHERE TRUE")
+            .await
+            .unwrap();
+    }
+
+    let result = session
+        .execute_sql("SELECT n FROM counter FOR SYSTEM_TIME AS OF @start")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[0]]);
+    let result = session.execute_sql("SELECT n FROM counter").await.unwrap();
+    assert_table_eq!(result, [[300]]);
+}
+
+#[tokio::test]
+async fn test_for_system_time_history_is_capped_per_table() {
+    let (session, clock) = create_session_with_history_clock();
+    session
+        .execute_sql("CREATE TABLE busy (n INT64)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO busy VALUES (0)")
+        .await
+        .unwrap();
+    clock.mark(&session, "@start").await;
+    for _ in 0..1000 {
+        session
+            .execute_sql("UPDATE busy SET n = n + 1 WHERE TRUE")
+            .await
+            .unwrap();
+    }
+
+    let err = session
+        .execute_sql("SELECT n FROM busy FOR SYSTEM_TIME AS OF @start")
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("Cannot read before"));
+    clock.mark(&session, "@end").await;
+    let result = session
+        .execute_sql("SELECT n FROM busy FOR SYSTEM_TIME AS OF @end")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1000]]);
+}
+
+#[tokio::test]
+async fn test_for_system_time_before_creation_errors() {
+    let session = create_session();
+    session
+        .execute_sql("CREATE TABLE fresh (id INT64)")
+        .await
+        .unwrap();
+
+    let err = session
+        .execute_sql(
+            "SELECT * FROM fresh FOR SYSTEM_TIME AS OF TIMESTAMP_SUB(CURRENT_TIMESTAMP(), INTERVAL 1 HOUR)",
+        )
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("Invalid snapshot time"));
+    assert!(err.to_string().contains("Cannot read before"));
+}
+
+#[tokio::test]
+async fn test_for_system_time_max_time_travel_hours_option() {
+    let session = create_session();
+    session.execute_sql("CREATE SCHEMA audit").await.unwrap();
+    session
+        .execute_sql("ALTER SCHEMA audit SET OPTIONS (max_time_travel_hours = 48)")
+        .await
+        .unwrap();
+
+    let err = session
+        .execute_sql("ALTER SCHEMA audit SET OPTIONS (max_time_travel_hours = 12)")
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("max_time_travel_hours"));
+
+    session
+        .execute_sql("CREATE TABLE audit.entries (id INT64)")
+        .await
+        .unwrap();
+    let err = session
+        .execute_sql(
+            "SELECT * FROM audit.entries FOR SYSTEM_TIME AS OF TIMESTAMP_SUB(CURRENT_TIMESTAMP(), INTERVAL 3 DAY)",
+        )
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("Cannot read before"));
+}
+
+#[tokio::test]
+async fn test_for_system_time_rejects_non_tables() {
+    let session = create_session();
+    session
+        .execute_sql("CREATE TABLE base (id INT64)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("CREATE VIEW base_view AS SELECT id FROM base")
+        .await
+        .unwrap();
+
+    let err = session
+        .execute_sql("SELECT * FROM base_view FOR SYSTEM_TIME AS OF CURRENT_TIMESTAMP()")
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("can only be used with a table"));
+}
@@ -0,0 +1,409 @@
+use yachtsql::YachtSQLEngine;
+
+use crate::assert_table_eq;
+
+async fn setup_accounts(session: &yachtsql::YachtSQLSession) {
+    session
+        .execute_sql("CREATE TABLE accounts (id INT64, balance INT64)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO accounts VALUES (1, 100), (2, 50)")
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_transaction_writes_are_private_until_commit() {
+    let engine = YachtSQLEngine::new();
+    let writer = engine.create_session();
+    let reader = engine.create_session();
+    setup_accounts(&writer).await;
+
+    writer.execute_sql("BEGIN TRANSACTION").await.unwrap();
+    writer
+        .execute_sql("UPDATE accounts SET balance = balance - 30 WHERE id = 1")
+        .await
+        .unwrap();
+    writer
+        .execute_sql("INSERT INTO accounts VALUES (3, 10)")
+        .await
+        .unwrap();
+
+    let result = writer
+        .execute_sql("SELECT id, balance FROM accounts ORDER BY id")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1, 70], [2, 50], [3, 10]]);
+
+    let result = reader
+        .execute_sql("SELECT id, balance FROM accounts ORDER BY id")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1, 100], [2, 50]]);
+
+    writer.execute_sql("COMMIT TRANSACTION").await.unwrap();
+
+    let result = reader
+        .execute_sql("SELECT id, balance FROM accounts ORDER BY id")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1, 70], [2, 50], [3, 10]]);
+}
+
+#[tokio::test]
+async fn test_transaction_reads_from_snapshot() {
+    let engine = YachtSQLEngine::new();
+    let first = engine.create_session();
+    let second = engine.create_session();
+    setup_accounts(&first).await;
+
+    first.execute_sql("BEGIN TRANSACTION").await.unwrap();
+    let result = first
+        .execute_sql("SELECT SUM(balance) FROM accounts")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[150]]);
+
+    second
+        .execute_sql("INSERT INTO accounts VALUES (3, 25)")
+        .await
+        .unwrap();
+
+    let result = first
+        .execute_sql("SELECT SUM(balance) FROM accounts")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[150]]);
+    first.execute_sql("COMMIT TRANSACTION").await.unwrap();
+
+    let result = first
+        .execute_sql("SELECT SUM(balance) FROM accounts")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[175]]);
+}
+
+#[tokio::test]
+async fn test_concurrent_update_aborts_transaction() {
+    let engine = YachtSQLEngine::new();
+    let first = engine.create_session();
+    let second = engine.create_session();
+    setup_accounts(&first).await;
+
+    first.execute_sql("BEGIN TRANSACTION").await.unwrap();
+    second.execute_sql("BEGIN TRANSACTION").await.unwrap();
+    first
+        .execute_sql("UPDATE accounts SET balance = balance + 1 WHERE id = 1")
+        .await
+        .unwrap();
+    second
+        .execute_sql("UPDATE accounts SET balance = balance + 10 WHERE id = 2")
+        .await
+        .unwrap();
+
+    first.execute_sql("COMMIT TRANSACTION").await.unwrap();
+    let err = second.execute_sql("COMMIT TRANSACTION").await.unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Transaction aborted due to concurrent update")
+    );
+
+    let result = second
+        .execute_sql("SELECT id, balance FROM accounts ORDER BY id")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1, 101], [2, 50]]);
+
+    second.execute_sql("BEGIN TRANSACTION").await.unwrap();
+    second
+        .execute_sql("UPDATE accounts SET balance = balance + 10 WHERE id = 2")
+        .await
+        .unwrap();
+    second.execute_sql("COMMIT TRANSACTION").await.unwrap();
+
+    let result = first
+        .execute_sql("SELECT id, balance FROM accounts ORDER BY id")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1, 101], [2, 60]]);
+}
+
+#[tokio::test]
+async fn test_transactions_on_different_tables_both_commit() {
+    let engine = YachtSQLEngine::new();
+    let first = engine.create_session();
+    let second = engine.create_session();
+    setup_accounts(&first).await;
+    first
+        .execute_sql("CREATE TABLE audit (note STRING)")
+        .await
+        .unwrap();
+
+    first.execute_sql("BEGIN TRANSACTION").await.unwrap();
+    second.execute_sql("BEGIN TRANSACTION").await.unwrap();
+    first
+        .execute_sql("DELETE FROM accounts WHERE id = 2")
+        .await
+        .unwrap();
+    second
+        .execute_sql("INSERT INTO audit VALUES ('checked')")
+        .await
+        .unwrap();
+    second.execute_sql("COMMIT TRANSACTION").await.unwrap();
+    first.execute_sql("COMMIT TRANSACTION").await.unwrap();
+
+    let result = second
+        .execute_sql("SELECT (SELECT COUNT(*) FROM accounts), (SELECT COUNT(*) FROM audit)")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1, 1]]);
+}
+
+#[tokio::test]
+async fn test_rollback_discards_staged_writes() {
+    let engine = YachtSQLEngine::new();
+    let first = engine.create_session();
+    let second = engine.create_session();
+    setup_accounts(&first).await;
+
+    first.execute_sql("BEGIN TRANSACTION").await.unwrap();
+    first
+        .execute_sql("DELETE FROM accounts WHERE TRUE")
+        .await
+        .unwrap();
+    let result = first
+        .execute_sql("SELECT COUNT(*) FROM accounts")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[0]]);
+    first.execute_sql("ROLLBACK TRANSACTION").await.unwrap();
+
+    let result = first
+        .execute_sql("SELECT COUNT(*) FROM accounts")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[2]]);
+    let result = second
+        .execute_sql("SELECT COUNT(*) FROM accounts")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[2]]);
+}
+
+#[tokio::test]
+async fn test_nested_begin_is_rejected() {
+    let engine = YachtSQLEngine::new();
+    let session = engine.create_session();
+    setup_accounts(&session).await;
+
+    session.execute_sql("BEGIN TRANSACTION").await.unwrap();
+    let err = session.execute_sql("BEGIN TRANSACTION").await.unwrap_err();
+    assert!(err.to_string().contains("Nested transactions"));
+    session.execute_sql("COMMIT TRANSACTION").await.unwrap();
+}
+
+#[tokio::test]
+async fn test_snapshot_is_taken_at_begin() {
+    let engine = YachtSQLEngine::new();
+    let first = engine.create_session();
+    let second = engine.create_session();
+    setup_accounts(&first).await;
+
+    first.execute_sql("BEGIN TRANSACTION").await.unwrap();
+    second
+        .execute_sql("UPDATE accounts SET balance = 0 WHERE id = 2")
+        .await
+        .unwrap();
+    second
+        .execute_sql("INSERT INTO accounts VALUES (3, 25)")
+        .await
+        .unwrap();
+
+    let result = first
+        .execute_sql("SELECT id, balance FROM accounts ORDER BY id")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1, 100], [2, 50]]);
+    first
+        .execute_sql("UPDATE accounts SET balance = balance + 1 WHERE id = 1")
+        .await
+        .unwrap();
+    let err = first.execute_sql("COMMIT TRANSACTION").await.unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Transaction aborted due to concurrent update")
+    );
+
+    let result = first
+        .execute_sql("SELECT id, balance FROM accounts ORDER BY id")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1, 100], [2, 0], [3, 25]]);
+}
+
+#[tokio::test]
+async fn test_ddl_is_rejected_inside_transaction() {
+    let engine = YachtSQLEngine::new();
+    let session = engine.create_session();
+    setup_accounts(&session).await;
+
+    session.execute_sql("BEGIN TRANSACTION").await.unwrap();
+    for sql in [
+        "CREATE TABLE other (id INT64)",
+        "ALTER TABLE accounts ADD COLUMN note STRING",
+        "DROP TABLE accounts",
+        "CREATE VIEW rich AS SELECT id FROM accounts WHERE balance > 60",
+        "CREATE SCHEMA archive",
+    ] {
+        let err = session.execute_sql(sql).await.unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("DDL statements are not supported inside a transaction"),
+            "{}: {}",
+            sql,
+            err
+        );
+    }
+
+    session
+        .execute_sql("CREATE TEMP TABLE staged AS SELECT id FROM accounts WHERE balance > 60")
+        .await
+        .unwrap();
+    session
+        .execute_sql("DELETE FROM accounts WHERE id IN (SELECT id FROM staged)")
+        .await
+        .unwrap();
+    session.execute_sql("DROP TABLE staged").await.unwrap();
+    session.execute_sql("COMMIT TRANSACTION").await.unwrap();
+
+    let result = session
+        .execute_sql("SELECT id, balance FROM accounts ORDER BY id")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[2, 50]]);
+    session
+        .execute_sql("CREATE TABLE other (id INT64)")
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_table_created_after_begin_is_pinned_on_first_access() {
+    let engine = YachtSQLEngine::new();
+    let first = engine.create_session();
+    let second = engine.create_session();
+    setup_accounts(&first).await;
+
+    first.execute_sql("BEGIN TRANSACTION").await.unwrap();
+    second
+        .execute_sql("CREATE TABLE ledger (id INT64, amount INT64)")
+        .await
+        .unwrap();
+    second
+        .execute_sql("INSERT INTO ledger VALUES (1, 10)")
+        .await
+        .unwrap();
+
+    let result = first
+        .execute_sql("SELECT id, amount FROM ledger ORDER BY id")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1, 10]]);
+    second
+        .execute_sql("INSERT INTO ledger VALUES (2, 20)")
+        .await
+        .unwrap();
+    let result = first
+        .execute_sql("SELECT id, amount FROM ledger ORDER BY id")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1, 10]]);
+
+    first
+        .execute_sql("UPDATE ledger SET amount = 0 WHERE id = 1")
+        .await
+        .unwrap();
+    let err = first.execute_sql("COMMIT TRANSACTION").await.unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Transaction aborted due to concurrent update")
+    );
+
+    let result = first
+        .execute_sql("SELECT id, amount FROM ledger ORDER BY id")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1, 10], [2, 20]]);
+}
+
+#[test]
+fn test_concurrent_autocommit_updates_are_not_lost() {
+    let engine = YachtSQLEngine::new();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    runtime.block_on(setup_accounts(&engine.create_session()));
+
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                let runtime = tokio::runtime::Builder::new_current_thread()
+                    .build()
+                    .unwrap();
+                let session = engine.create_session();
+                runtime.block_on(async {
+                    let mut applied = 0;
+                    while applied < 25 {
+                        match session
+                            .execute_sql("UPDATE accounts SET balance = balance + 1 WHERE id = 1")
+                            .await
+                        {
+                            Ok(_) => applied += 1,
+                            Err(err) => assert!(
+                                err.to_string()
+                                    .contains("Transaction aborted due to concurrent update"),
+                                "{}",
+                                err
+                            ),
+                        }
+                    }
+                });
+            });
+        }
+    });
+
+    let result = runtime
+        .block_on(
+            engine
+                .create_session()
+                .execute_sql("SELECT balance FROM accounts WHERE id = 1"),
+        )
+        .unwrap();
+    assert_table_eq!(result, [[200]]);
+}
+
+#[tokio::test]
+async fn test_transaction_aborts_when_snapshot_version_is_pruned() {
+    let engine = YachtSQLEngine::new();
+    let first = engine.create_session();
+    let second = engine.create_session();
+    setup_accounts(&first).await;
+
+    first.execute_sql("BEGIN TRANSACTION").await.unwrap();
+    for _ in 0..1001 {
+        second
+            .execute_sql("UPDATE accounts SET balance = balance + 1 WHERE id = 2")
+            .await
+            .unwrap();
+    }
+
+    let err = first
+        .execute_sql("SELECT SUM(balance) FROM accounts")
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Transaction aborted due to concurrent update")
+    );
+}
@@ -0,0 +1,183 @@
+use crate::assert_table_eq;
+use crate::common::create_session;
+
+async fn setup_measurements(session: &yachtsql::YachtSQLSession) {
+    session
+        .execute_sql(
+            "CREATE TABLE measurements (id INT64, reading FLOAT64, label STRING, flagged BOOL)",
+        )
+        .await
+        .unwrap();
+    session
+        .execute_sql("CREATE TABLE ids (n INT64)")
+        .await
+        .unwrap();
+    session
+        .execute_sql("INSERT INTO ids SELECT * FROM UNNEST(GENERATE_ARRAY(1, 1000))")
+        .await
+        .unwrap();
+    session
+        .execute_sql(
+            "INSERT INTO measurements
+            SELECT
+                n AS id,
+                CASE WHEN MOD(n, 7) != 0 THEN n * 0.5 END AS reading,
+                CASE WHEN MOD(n, 5) != 0 THEN CONCAT('sensor_', CAST(MOD(n, 3) AS STRING)) END AS label,
+                CASE WHEN MOD(n, 11) != 0 THEN MOD(n, 2) = 0 END AS flagged
+            FROM ids",
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_filter_three_valued_logic() {
+    let session = create_session();
+    setup_measurements(&session).await;
+
+    let result = session
+        .execute_sql("SELECT COUNT(*) FROM measurements WHERE flagged AND reading > 100")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[312]]);
+
+    let result = session
+        .execute_sql("SELECT COUNT(*) FROM measurements WHERE flagged OR reading IS NULL")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[532]]);
+
+    let result = session
+        .execute_sql("SELECT COUNT(*) FROM measurements WHERE NOT flagged")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[455]]);
+}
+
+#[tokio::test]
+async fn test_filter_mixed_numeric_comparisons() {
+    let session = create_session();
+    setup_measurements(&session).await;
+
+    let result = session
+        .execute_sql("SELECT COUNT(*) FROM measurements WHERE reading = id / 2 AND id <= 20")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[18]]);
+
+    let result = session
+        .execute_sql("SELECT COUNT(*) FROM measurements WHERE reading * 2 >= -id + 1990")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[6]]);
+}
+
+#[tokio::test]
+async fn test_filter_in_list_with_null() {
+    let session = create_session();
+    setup_measurements(&session).await;
+
+    let result = session
+        .execute_sql("SELECT id FROM measurements WHERE id IN (3, 5, NULL, 2000) ORDER BY id")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[3], [5]]);
+
+    let result = session
+        .execute_sql("SELECT COUNT(*) FROM measurements WHERE id NOT IN (3, NULL)")
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[0]]);
+
+    let result = session
+        .execute_sql(
+            "SELECT COUNT(*) FROM measurements WHERE label NOT IN ('sensor_0', 'sensor_1')",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[266]]);
+}
+
+#[tokio::test]
+async fn test_project_string_functions() {
+    let session = create_session();
+    setup_measurements(&session).await;
+
+    let result = session
+        .execute_sql(
+            "SELECT id, UPPER(label), LENGTH(label), STARTS_WITH(label, 'sensor'), label || '!'
+            FROM measurements
+            WHERE id IN (4, 5)
+            ORDER BY id",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(
+        result,
+        [
+            [4, "SENSOR_1", 8, true, "sensor_1!"],
+            [5, null, null, null, null],
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_project_arithmetic_and_null_checks() {
+    let session = create_session();
+    setup_measurements(&session).await;
+
+    let result = session
+        .execute_sql(
+            "SELECT id * 2 + 1, reading - id, id / 4, reading IS NULL, -id
+            FROM measurements
+            WHERE id BETWEEN 6 AND 7
+            ORDER BY id",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(
+        result,
+        [[13, -3.0, 1.5, false, -6], [15, null, 1.75, true, -7],]
+    );
+}
+
+#[tokio::test]
+async fn test_division_by_zero_still_errors() {
+    let session = create_session();
+    setup_measurements(&session).await;
+
+    let err = session
+        .execute_sql("SELECT id FROM measurements WHERE id / (id - 500) > 1")
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("Division by zero"));
+}
+
+#[tokio::test]
+async fn test_unsupported_expressions_fall_back() {
+    let session = create_session();
+    setup_measurements(&session).await;
+
+    let result = session
+        .execute_sql(
+            "SELECT id, CASE WHEN flagged THEN 'even' ELSE 'odd' END
+            FROM measurements
+            WHERE MOD(id, 125) = 0 AND COALESCE(label, 'none') = 'none'
+            ORDER BY id",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(
+        result,
+        [
+            [125, "odd"],
+            [250, "even"],
+            [375, "odd"],
+            [500, "even"],
+            [625, "odd"],
+            [750, "even"],
+            [875, "odd"],
+            [1000, "even"]
+        ]
+    );
+}
@@ -0,0 +1,276 @@
+use yachtsql::{Value, YachtSQLSession};
+
+use crate::assert_table_eq;
+use crate::common::create_session;
+
+async fn setup_visits(session: &YachtSQLSession) {
+    session
+        .execute_sql("CREATE TABLE visits (day DATE, n INT64)")
+        .await
+        .unwrap();
+    session
+        .execute_sql(
+            "INSERT INTO visits VALUES (DATE '2024-01-01', 1), (DATE '2024-01-03', 2), (DATE '2024-01-08', 4), (DATE '2024-01-09', 8), (DATE '2024-01-20', 16)",
+        )
+        .await
+        .unwrap();
+}
+
+async fn setup_scores(session: &YachtSQLSession) {
+    session
+        .execute_sql("CREATE TABLE scores (id INT64, g INT64, n INT64)")
+        .await
+        .unwrap();
+    session
+        .execute_sql(
+            "INSERT INTO scores VALUES (1, 1, 1), (2, 1, 2), (3, 2, 4), (4, 3, 8), (5, 3, 16)",
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_range_interval_over_date() {
+    let session = create_session();
+    setup_visits(&session).await;
+
+    let result = session
+        .execute_sql(
+            "SELECT n, SUM(n) OVER (ORDER BY day RANGE BETWEEN INTERVAL 7 DAY PRECEDING AND CURRENT ROW) AS rolling FROM visits ORDER BY day",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1, 1], [2, 3], [4, 7], [8, 14], [16, 16]]);
+
+    let result = session
+        .execute_sql(
+            "SELECT n, SUM(n) OVER (ORDER BY day DESC RANGE BETWEEN CURRENT ROW AND INTERVAL 7 DAY FOLLOWING) AS rolling FROM visits ORDER BY day",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1, 1], [2, 3], [4, 7], [8, 14], [16, 16]]);
+}
+
+#[tokio::test]
+async fn test_range_interval_over_timestamp() {
+    let session = create_session();
+    session
+        .execute_sql("CREATE TABLE events (user STRING, at TIMESTAMP, amount INT64)")
+        .await
+        .unwrap();
+    session
+        .execute_sql(
+            "INSERT INTO events VALUES ('a', TIMESTAMP '2024-01-01 10:00:00', 1), ('a', TIMESTAMP '2024-01-01 10:30:00', 2), ('a', TIMESTAMP '2024-01-01 11:00:00', 4), ('a', TIMESTAMP '2024-01-01 12:30:00', 8), ('b', TIMESTAMP '2024-01-01 10:00:00', 16)",
+        )
+        .await
+        .unwrap();
+
+    let result = session
+        .execute_sql(
+            "SELECT user, amount,
+                COUNT(*) OVER (PARTITION BY user ORDER BY at RANGE BETWEEN INTERVAL 1 HOUR PRECEDING AND CURRENT ROW) AS last_hour,
+                SUM(amount) OVER (PARTITION BY user ORDER BY at RANGE BETWEEN CURRENT ROW AND INTERVAL 30 MINUTE FOLLOWING) AS next_half_hour
+             FROM events ORDER BY user, at",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(
+        result,
+        [
+            ["a", 1, 1, 3],
+            ["a", 2, 2, 6],
+            ["a", 4, 3, 4],
+            ["a", 8, 1, 8],
+            ["b", 16, 1, 16],
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_range_numeric_and_float_offsets() {
+    let session = create_session();
+    session
+        .execute_sql("CREATE TABLE prices (id INT64, price NUMERIC, weight FLOAT64)")
+        .await
+        .unwrap();
+    session
+        .execute_sql(
+            "INSERT INTO prices VALUES (1, NUMERIC '1.5', 1.0), (2, NUMERIC '2.0', 1.25), (3, NUMERIC '2.5', 2.0), (4, NUMERIC '4.0', 2.4)",
+        )
+        .await
+        .unwrap();
+
+    let result = session
+        .execute_sql(
+            "SELECT id,
+                COUNT(*) OVER (ORDER BY price RANGE BETWEEN 1 PRECEDING AND 1 FOLLOWING) AS near_price,
+                SUM(id) OVER (ORDER BY weight RANGE BETWEEN 0.5 PRECEDING AND CURRENT ROW) AS near_weight
+             FROM prices ORDER BY id",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1, 3, 1], [2, 3, 3], [3, 3, 3], [4, 1, 7]]);
+}
+
+#[tokio::test]
+async fn test_rows_and_groups_frames() {
+    let session = create_session();
+    setup_scores(&session).await;
+
+    let result = session
+        .execute_sql(
+            "SELECT id,
+                SUM(n) OVER (ORDER BY g, id ROWS BETWEEN 1 PRECEDING AND 1 FOLLOWING) AS rows_sum,
+                SUM(n) OVER (ORDER BY g GROUPS BETWEEN 1 PRECEDING AND CURRENT ROW) AS groups_sum,
+                SUM(n) OVER (ORDER BY g RANGE BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW) AS peers_sum
+             FROM scores ORDER BY id",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(
+        result,
+        [
+            [1, 3, 3, 3],
+            [2, 7, 3, 3],
+            [3, 14, 7, 7],
+            [4, 28, 28, 31],
+            [5, 24, 28, 31],
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_offsets_from_parameters_and_variables() {
+    let session = create_session();
+    setup_visits(&session).await;
+
+    let stmt = session
+        .prepare(
+            "SELECT n, SUM(n) OVER (ORDER BY day ROWS BETWEEN @k PRECEDING AND CURRENT ROW) FROM visits ORDER BY day",
+        )
+        .unwrap();
+    let result = stmt.execute([("k", Value::int64(1))]).await.unwrap();
+    assert_table_eq!(result, [[1, 1], [2, 3], [4, 6], [8, 12], [16, 24]]);
+    let result = stmt.execute([("k", Value::int64(0))]).await.unwrap();
+    assert_table_eq!(result, [[1, 1], [2, 2], [4, 4], [8, 8], [16, 16]]);
+
+    session
+        .execute_sql("DECLARE days INT64 DEFAULT 2")
+        .await
+        .unwrap();
+    let result = session
+        .execute_sql(
+            "SELECT n, SUM(n) OVER (ORDER BY day ROWS BETWEEN days PRECEDING AND CURRENT ROW) FROM visits ORDER BY day",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(result, [[1, 1], [2, 3], [4, 7], [8, 14], [16, 28]]);
+
+    session.execute_sql("SET days = NULL").await.unwrap();
+    let err = session
+        .execute_sql(
+            "SELECT SUM(n) OVER (ORDER BY day ROWS BETWEEN days PRECEDING AND CURRENT ROW) FROM visits",
+        )
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("NULL"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_exclude_clause() {
+    let session = create_session();
+    setup_scores(&session).await;
+
+    let result = session
+        .execute_sql(
+            "SELECT id,
+                SUM(n) OVER (ORDER BY g ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING EXCLUDE CURRENT ROW) AS no_current,
+                SUM(n) OVER (ORDER BY g ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING EXCLUDE GROUP) AS no_group,
+                SUM(n) OVER (ORDER BY g ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING EXCLUDE TIES) AS no_ties,
+                SUM(n) OVER (ORDER BY g ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING EXCLUDE NO OTHERS) AS everything
+             FROM scores ORDER BY id",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(
+        result,
+        [
+            [1, 30, 28, 29, 31],
+            [2, 29, 28, 30, 31],
+            [3, 27, 27, 31, 31],
+            [4, 23, 7, 15, 31],
+            [5, 15, 7, 23, 31],
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_navigation_functions_honor_frame() {
+    let session = create_session();
+    setup_scores(&session).await;
+
+    let result = session
+        .execute_sql(
+            "SELECT id,
+                FIRST_VALUE(n) OVER (ORDER BY id ROWS BETWEEN 1 PRECEDING AND 1 FOLLOWING EXCLUDE CURRENT ROW) AS first_n,
+                LAST_VALUE(n) OVER (ORDER BY id ROWS BETWEEN 1 PRECEDING AND 1 FOLLOWING EXCLUDE CURRENT ROW) AS last_n,
+                NTH_VALUE(n, 2) OVER (ORDER BY id ROWS BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW) AS second_n,
+                LAST_VALUE(n) OVER (ORDER BY g RANGE BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW) AS last_peer
+             FROM scores ORDER BY id",
+        )
+        .await
+        .unwrap();
+    assert_table_eq!(
+        result,
+        [
+            [1, 2, 2, null, 2],
+            [2, 1, 4, 2, 2],
+            [3, 2, 8, 2, 4],
+            [4, 4, 16, 2, 16],
+            [5, 8, 8, 2, 16],
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_frame_offsets() {
+    let session = create_session();
+    setup_scores(&session).await;
+
+    let err = session
+        .execute_sql(
+            "SELECT SUM(n) OVER (ORDER BY id ROWS BETWEEN g PRECEDING AND CURRENT ROW) FROM scores",
+        )
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("constant"), "{}", err);
+
+    let err = session
+        .execute_sql(
+            "SELECT SUM(n) OVER (ORDER BY id ROWS BETWEEN -1 PRECEDING AND CURRENT ROW) FROM scores",
+        )
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("negative"), "{}", err);
+
+    let err = session
+        .execute_sql(
+            "SELECT SUM(n) OVER (ORDER BY g, id RANGE BETWEEN 1 PRECEDING AND CURRENT ROW) FROM scores",
+        )
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("exactly one ORDER BY"), "{}", err);
+
+    let err = session
+        .execute_sql(
+            "SELECT SUM(n) OVER (ORDER BY id ROWS BETWEEN CAST(NULL AS INT64) PRECEDING AND CURRENT ROW) FROM scores",
+        )
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("NULL"), "{}", err);
+}